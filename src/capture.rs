@@ -0,0 +1,260 @@
+//! Recording IQ from an SDR through SoapySDR into a SigMF meta/data pair.
+//!
+//! The samples are written as `cf32_le` while they arrive and hashed on the
+//! way, so the meta file written at the end carries `core:sha512` and passes
+//! validation. [`RecordingWriter`] does the writing for network streams too
+//! (see [`crate::ingest`]). Talking to hardware needs the SoapySDR library, so this is
+//! behind the `soapysdr` feature; without it, [`capture`] says so.
+
+use crate::parser::sigmf::SigMFDataType;
+use anyhow::Result;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha512};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+/// What to record and how
+#[derive(Debug, Clone)]
+pub struct CaptureSettings {
+    /// A SoapySDR driver (`rtlsdr`, `hackrf`, ...) or full device arguments
+    /// (`driver=rtlsdr,serial=00000001`)
+    pub driver: String,
+    pub frequency: f64,
+    pub sample_rate: f64,
+    pub seconds: f64,
+    /// Gain in dB; automatic gain control if not given
+    pub gain: Option<f64>,
+    pub channel: usize,
+}
+
+impl CaptureSettings {
+    /// Device arguments for SoapySDR
+    pub fn device_args(&self) -> String {
+        if self.driver.contains('=') {
+            self.driver.clone()
+        } else {
+            format!("driver={}", self.driver)
+        }
+    }
+
+    pub fn num_samples(&self) -> u64 {
+        (self.seconds * self.sample_rate).round() as u64
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CaptureReport {
+    pub meta_path: PathBuf,
+    pub data_path: PathBuf,
+    pub num_samples: u64,
+    /// Times the device dropped samples because they weren't read in time
+    pub overflows: u64,
+}
+
+/// `capture` → (`capture.sigmf-meta`, `capture.sigmf-data`); a `.sigmf-meta`
+/// or `.sigmf-data` extension given is replaced
+pub fn output_paths(out: &Path) -> (PathBuf, PathBuf) {
+    let base = match out.extension().and_then(|ext| ext.to_str()) {
+        Some("sigmf-meta" | "sigmf-data") => out.with_extension(""),
+        _ => out.to_path_buf(),
+    };
+    (with_suffix(&base, ".sigmf-meta"), with_suffix(&base, ".sigmf-data"))
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut path = path.as_os_str().to_os_string();
+    path.push(suffix);
+    PathBuf::from(path)
+}
+
+/// What the meta file of a new recording says
+#[derive(Debug, Clone)]
+pub struct RecordingInfo {
+    pub data_type: SigMFDataType,
+    pub sample_rate: f64,
+    pub frequency: Option<f64>,
+    /// When the first sample arrived (ISO 8601)
+    pub datetime: String,
+    pub hardware: Option<String>,
+    pub description: Option<String>,
+    pub gain: Option<f64>,
+    pub agc: Option<bool>,
+}
+
+impl RecordingInfo {
+    /// The meta file: one capture segment, no annotations
+    pub fn metadata(&self, sha512: &str) -> Value {
+        let mut global = json!({
+            "core:datatype": self.data_type.name(),
+            "core:sample_rate": self.sample_rate,
+            "core:version": "1.2.0",
+            "core:recorder": concat!("sig_viewer ", env!("CARGO_PKG_VERSION")),
+            "core:sha512": sha512,
+        });
+        let mut capture = json!({ "core:sample_start": 0, "core:datetime": self.datetime });
+        if let Some(hardware) = &self.hardware {
+            global["core:hw"] = json!(hardware);
+        }
+        if let Some(description) = &self.description {
+            global["core:description"] = json!(description);
+        }
+        if let Some(frequency) = self.frequency {
+            capture["core:frequency"] = json!(frequency);
+        }
+        if let Some(agc) = self.agc {
+            capture["ds:agc"] = json!(agc);
+        }
+        if let Some(gain) = self.gain {
+            capture["ds:gain"] = json!(gain);
+        }
+        json!({ "global": global, "captures": [capture], "annotations": [] })
+    }
+}
+
+/// Now, as a `core:datetime`
+pub fn now_datetime() -> String {
+    chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string()
+}
+
+/// Writes a recording's samples as they come, hashing them on the way. The
+/// data goes to a `.part` file until [`finish`](Self::finish) renames it and
+/// writes the meta file, so a directory watcher (or a scan) never sees a
+/// recording half-written, and an interrupted one is left as `.part`.
+pub struct RecordingWriter {
+    meta_path: PathBuf,
+    data_path: PathBuf,
+    part_path: PathBuf,
+    writer: BufWriter<File>,
+    hasher: Sha512,
+    bytes: u64,
+}
+
+impl RecordingWriter {
+    /// Start `out` (see [`output_paths`]); existing recordings are not
+    /// overwritten
+    pub fn create(out: &Path) -> Result<Self> {
+        let (meta_path, data_path) = output_paths(out);
+        for path in [&meta_path, &data_path] {
+            if path.exists() {
+                anyhow::bail!("{:?} already exists", path);
+            }
+        }
+        if let Some(dir) = data_path.parent().filter(|dir| !dir.as_os_str().is_empty()) {
+            std::fs::create_dir_all(dir)?;
+        }
+        let part_path = with_suffix(&data_path, ".part");
+        let writer = BufWriter::new(File::create(&part_path)?);
+        Ok(RecordingWriter { meta_path, data_path, part_path, writer, hasher: Sha512::new(), bytes: 0 })
+    }
+
+    pub fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.writer.write_all(bytes)?;
+        self.hasher.update(bytes);
+        self.bytes += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Bytes written so far
+    pub fn len(&self) -> u64 {
+        self.bytes
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.bytes == 0
+    }
+
+    /// Move the data into place and write the meta file; returns the meta
+    /// and data file paths
+    pub fn finish(mut self, info: &RecordingInfo) -> Result<(PathBuf, PathBuf)> {
+        self.writer.flush()?;
+        drop(self.writer);
+        std::fs::rename(&self.part_path, &self.data_path)?;
+        let digest: String = self.hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+        let meta_part = with_suffix(&self.meta_path, ".part");
+        std::fs::write(&meta_part, serde_json::to_string_pretty(&info.metadata(&digest))? + "\n")?;
+        std::fs::rename(&meta_part, &self.meta_path)?;
+        Ok((self.meta_path, self.data_path))
+    }
+}
+
+#[cfg(not(feature = "soapysdr"))]
+pub fn capture(_settings: &CaptureSettings, _out: &Path, _on_progress: impl FnMut(u64)) -> Result<CaptureReport> {
+    anyhow::bail!("This build can't talk to SDRs; rebuild with `--features soapysdr`")
+}
+
+/// Record `settings.seconds` of IQ to `out` (see [`output_paths`]), calling
+/// `on_progress` with the samples written so far about once a second.
+/// Existing files are not overwritten.
+#[cfg(feature = "soapysdr")]
+pub fn capture(settings: &CaptureSettings, out: &Path, mut on_progress: impl FnMut(u64)) -> Result<CaptureReport> {
+    use num_complex::Complex;
+    use soapysdr::{Direction, ErrorCode};
+    use std::time::{Duration, Instant};
+
+    if settings.sample_rate <= 0.0 || settings.seconds <= 0.0 {
+        anyhow::bail!("The sample rate and duration must be positive");
+    }
+
+    let device = soapysdr::Device::new(settings.device_args().as_str())
+        .map_err(|e| anyhow::anyhow!("Could not open SDR {:?}: {}", settings.device_args(), e))?;
+    let channel = settings.channel;
+    device.set_sample_rate(Direction::Rx, channel, settings.sample_rate)?;
+    device.set_frequency(Direction::Rx, channel, settings.frequency, ())?;
+    match settings.gain {
+        Some(gain) => {
+            device.set_gain_mode(Direction::Rx, channel, false)?;
+            device.set_gain(Direction::Rx, channel, gain)?;
+        }
+        None => device.set_gain_mode(Direction::Rx, channel, true)?,
+    }
+    let hardware = match device.hardware_key() {
+        Ok(key) if !key.is_empty() => format!("{} ({})", settings.driver, key),
+        _ => settings.driver.clone(),
+    };
+
+    let mut writer = RecordingWriter::create(out)?;
+    let mut stream = device.rx_stream::<Complex<f32>>(&[channel])?;
+    let mut buffer = vec![Complex::new(0.0f32, 0.0); stream.mtu()?];
+    let wanted = settings.num_samples();
+    let mut written = 0u64;
+    let mut overflows = 0;
+    let mut last_report = Instant::now();
+
+    let datetime = now_datetime();
+    stream.activate(None)?;
+    while written < wanted {
+        let len = buffer.len().min((wanted - written) as usize);
+        match stream.read(&mut [&mut buffer[..len]], 1_000_000) {
+            Ok(count) => {
+                writer.write(&SigMFDataType::Cf32Le.encode_samples(&buffer[..count]).0)?;
+                written += count as u64;
+            }
+            // Samples were lost; the recording goes on, with a gap its metadata doesn't show
+            Err(e) if matches!(e.code, ErrorCode::Overflow) => overflows += 1,
+            Err(e) if matches!(e.code, ErrorCode::Timeout) => {}
+            Err(e) => {
+                let _ = stream.deactivate(None);
+                return Err(anyhow::anyhow!("Reading from the SDR failed: {}", e));
+            }
+        }
+        if last_report.elapsed() >= Duration::from_secs(1) {
+            on_progress(written);
+            last_report = Instant::now();
+        }
+    }
+    stream.deactivate(None)?;
+
+    let info = RecordingInfo {
+        data_type: SigMFDataType::Cf32Le,
+        sample_rate: settings.sample_rate,
+        frequency: Some(settings.frequency),
+        datetime,
+        hardware: Some(hardware),
+        description: None,
+        gain: settings.gain,
+        agc: Some(settings.gain.is_none()),
+    };
+    let (meta_path, data_path) = writer.finish(&info)?;
+    Ok(CaptureReport { meta_path, data_path, num_samples: written, overflows })
+}
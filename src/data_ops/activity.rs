@@ -0,0 +1,121 @@
+use anyhow::Result;
+use chrono::{Datelike, NaiveDate, NaiveDateTime, Timelike};
+use polars::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+pub(crate) const UNKNOWN_SENSOR: &str = "(unknown)";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeBucket {
+    HourOfDay,
+    Day,
+}
+
+impl TimeBucket {
+    /// Parse `hour` or `day`
+    pub fn from_string(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "hour" => Ok(TimeBucket::HourOfDay),
+            "day" => Ok(TimeBucket::Day),
+            other => anyhow::bail!("Unknown time bucket {:?}; expected hour or day", other),
+        }
+    }
+
+    /// Sortable key of the bucket `datetime` falls in
+    pub(crate) fn key(&self, datetime: &NaiveDateTime) -> i64 {
+        match self {
+            TimeBucket::HourOfDay => datetime.hour() as i64,
+            TimeBucket::Day => datetime.date().num_days_from_ce() as i64,
+        }
+    }
+
+    /// Every bucket to show, given the keys in use: all 24 hours, or every
+    /// day in the range so gaps show up as empty columns
+    pub(crate) fn keys(&self, used: impl Iterator<Item = i64> + Clone) -> Vec<i64> {
+        match self {
+            TimeBucket::HourOfDay => (0..24).collect(),
+            TimeBucket::Day => match (used.clone().min(), used.max()) {
+                (Some(first), Some(last)) => (first..=last).collect(),
+                _ => Vec::new(),
+            },
+        }
+    }
+
+    pub(crate) fn label(&self, key: i64) -> String {
+        match self {
+            TimeBucket::HourOfDay => format!("{:02}:00", key),
+            TimeBucket::Day => NaiveDate::from_num_days_from_ce_opt(key as i32)
+                .map(|date| date.to_string())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Recording counts per sensor (rows) and time bucket (columns)
+#[derive(Debug, Clone, Default)]
+pub struct ActivityHeatmap {
+    pub sensors: Vec<String>,
+    pub buckets: Vec<String>,
+    pub counts: Vec<Vec<u32>>,
+    /// Rows whose capture_datetime couldn't be parsed
+    pub skipped_rows: usize,
+}
+
+impl ActivityHeatmap {
+    pub fn max_count(&self) -> u32 {
+        self.counts.iter().flatten().copied().max().unwrap_or(0)
+    }
+}
+
+/// Parse a SigMF `core:datetime` (ISO 8601, normally UTC with a trailing Z)
+pub fn parse_capture_datetime(value: &str) -> Option<NaiveDateTime> {
+    let value = value.trim().trim_end_matches('Z');
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S%.f")
+        .or_else(|_| NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S"))
+        .ok()
+}
+
+/// Count recordings per `sdr_handle` and hour-of-day or calendar day, from
+/// `capture_datetime`. Rows of the same recording (one per annotation) are
+/// only counted once. Day buckets cover every day in the range so gaps show
+/// up as empty columns.
+pub fn activity_heatmap(df: &DataFrame, bucket: TimeBucket) -> Result<ActivityHeatmap> {
+    let sensors = df.column("sdr_handle")?.str()?;
+    let datetimes = df.column("capture_datetime")?.str()?;
+    let filenames = df.column("meta_filename")?.str()?;
+
+    let mut seen = HashSet::new();
+    let mut cells: BTreeMap<(String, i64), u32> = BTreeMap::new();
+    let mut sensor_names = BTreeSet::new();
+    let mut skipped_rows = 0;
+    for idx in 0..df.height() {
+        let sensor = sensors.get(idx).filter(|s| !s.is_empty()).unwrap_or(UNKNOWN_SENSOR);
+        let Some(datetime) = datetimes.get(idx).and_then(parse_capture_datetime) else {
+            skipped_rows += 1;
+            continue;
+        };
+        if !seen.insert((sensor, filenames.get(idx))) {
+            continue;
+        }
+        let key = bucket.key(&datetime);
+        sensor_names.insert(sensor.to_string());
+        *cells.entry((sensor.to_string(), key)).or_default() += 1;
+    }
+
+    let bucket_keys = bucket.keys(cells.keys().map(|(_, key)| *key));
+    let buckets = bucket_keys.iter().map(|key| bucket.label(*key)).collect();
+
+    let sensors: Vec<String> = sensor_names.into_iter().collect();
+    let counts = sensors.iter()
+        .map(|sensor| bucket_keys.iter()
+            .map(|key| cells.get(&(sensor.clone(), *key)).copied().unwrap_or(0))
+            .collect())
+        .collect();
+
+    Ok(ActivityHeatmap {
+        sensors,
+        buckets,
+        counts,
+        skipped_rows,
+    })
+}
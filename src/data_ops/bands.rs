@@ -0,0 +1,230 @@
+//! Naming the band (and channel) a row's signal falls in, from a band map:
+//! built-in ISM 915, WiFi and GPS L1 bands, plus any from a TOML file.
+
+use anyhow::Result;
+use polars::prelude::*;
+use serde::Deserialize;
+use std::path::Path;
+use std::sync::RwLock;
+
+/// Band a row's signal falls in, empty where none does
+pub const BAND_COLUMN: &str = "band";
+/// Channel within the band, empty where the band has no channels or none fits
+pub const CHANNEL_COLUMN: &str = "channel";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Channel {
+    pub name: String,
+    pub center_hz: f64,
+    pub width_hz: f64,
+}
+
+impl Channel {
+    fn overlaps(&self, low: f64, high: f64) -> bool {
+        let half = self.width_hz / 2.0;
+        self.center_hz - half <= high && low <= self.center_hz + half
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Band {
+    pub name: String,
+    pub freq_min_hz: f64,
+    pub freq_max_hz: f64,
+    pub channels: Vec<Channel>,
+}
+
+/// Bands checked in order; the first containing a signal's center wins
+#[derive(Debug, Clone, PartialEq)]
+pub struct BandMap {
+    pub bands: Vec<Band>,
+}
+
+impl Default for BandMap {
+    fn default() -> Self {
+        BandMap::builtin()
+    }
+}
+
+/// 20 MHz WiFi channels at 5000 + 5·n MHz
+fn wifi_5ghz_channel(number: u32) -> Channel {
+    Channel {
+        name: number.to_string(),
+        center_hz: (5000.0 + 5.0 * number as f64) * 1e6,
+        width_hz: 20e6,
+    }
+}
+
+impl BandMap {
+    /// ISM 902–928 MHz, WiFi 2.4 GHz (channels 1–14) and 5 GHz (20 MHz
+    /// channels 36–177) and GPS L1
+    pub fn builtin() -> Self {
+        let wifi_24 = (1..=14)
+            .map(|number| Channel {
+                name: number.to_string(),
+                center_hz: if number == 14 { 2484e6 } else { (2407.0 + 5.0 * number as f64) * 1e6 },
+                width_hz: 22e6,
+            })
+            .collect();
+        let wifi_5 = (36..=64).step_by(4).chain((100..=144).step_by(4)).chain((149..=177).step_by(4)).map(wifi_5ghz_channel).collect();
+        BandMap {
+            bands: vec![
+                Band { name: "ISM 915".to_string(), freq_min_hz: 902e6, freq_max_hz: 928e6, channels: Vec::new() },
+                Band { name: "GPS L1".to_string(), freq_min_hz: 1563.42e6, freq_max_hz: 1587.42e6, channels: Vec::new() },
+                Band { name: "WiFi 2.4 GHz".to_string(), freq_min_hz: 2400e6, freq_max_hz: 2495e6, channels: wifi_24 },
+                Band { name: "WiFi 5 GHz".to_string(), freq_min_hz: 5150e6, freq_max_hz: 5895e6, channels: wifi_5 },
+            ],
+        }
+    }
+
+    /// A band map file's bands, checked before the built-in ones unless it
+    /// sets `builtin = false`:
+    ///
+    /// ```toml
+    /// [[band]]
+    /// name = "LoRa EU"
+    /// freq_min_mhz = 863
+    /// freq_max_mhz = 870
+    /// channels = [{ name = "1", center_mhz = 868.1, width_mhz = 0.125 }]
+    /// ```
+    ///
+    /// Frequencies can be given in `_hz` or `_mhz`.
+    pub fn from_toml<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| anyhow::anyhow!("Failed to read {:?}: {}", path, e))?;
+        let file: BandMapFile = toml::from_str(&contents).map_err(|e| anyhow::anyhow!("Invalid band map {:?}: {}", path, e))?;
+        let mut bands = Vec::new();
+        for band in file.band {
+            let hz = |value_hz: Option<f64>, value_mhz: Option<f64>, field: &str| {
+                value_hz
+                    .or(value_mhz.map(|mhz| mhz * 1e6))
+                    .ok_or_else(|| anyhow::anyhow!("Band {:?} in {:?} has no {}_hz or {}_mhz", band.name, path, field, field))
+            };
+            let freq_min_hz = hz(band.freq_min_hz, band.freq_min_mhz, "freq_min")?;
+            let freq_max_hz = hz(band.freq_max_hz, band.freq_max_mhz, "freq_max")?;
+            if freq_min_hz > freq_max_hz {
+                anyhow::bail!("Band {:?} in {:?} ends below its start", band.name, path);
+            }
+            let channels = band
+                .channels
+                .iter()
+                .map(|channel| {
+                    Ok(Channel {
+                        name: channel.name.clone(),
+                        center_hz: hz(channel.center_hz, channel.center_mhz, "center")?,
+                        width_hz: hz(channel.width_hz, channel.width_mhz, "width").unwrap_or(0.0),
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?;
+            bands.push(Band { name: band.name, freq_min_hz, freq_max_hz, channels });
+        }
+        if file.builtin {
+            bands.extend(BandMap::builtin().bands);
+        }
+        Ok(BandMap { bands })
+    }
+
+    /// Band and channel of a signal centered on `center_hz`. The channel is
+    /// the one nearest the center among those the signal's bandwidth
+    /// overlaps (or that contain the center, without a bandwidth).
+    pub fn classify(&self, center_hz: f64, bandwidth_hz: f64) -> Option<(&Band, Option<&Channel>)> {
+        let band = self.bands.iter().find(|band| (band.freq_min_hz..=band.freq_max_hz).contains(&center_hz))?;
+        let half = bandwidth_hz.max(0.0) / 2.0;
+        let channel = band
+            .channels
+            .iter()
+            .filter(|channel| channel.overlaps(center_hz - half, center_hz + half))
+            .min_by(|a, b| (a.center_hz - center_hz).abs().total_cmp(&(b.center_hz - center_hz).abs()));
+        Some((band, channel))
+    }
+}
+
+#[derive(Deserialize)]
+struct BandMapFile {
+    #[serde(default)]
+    band: Vec<BandEntry>,
+    #[serde(default = "default_builtin")]
+    builtin: bool,
+}
+
+fn default_builtin() -> bool {
+    true
+}
+
+#[derive(Deserialize)]
+struct BandEntry {
+    name: String,
+    freq_min_hz: Option<f64>,
+    freq_max_hz: Option<f64>,
+    freq_min_mhz: Option<f64>,
+    freq_max_mhz: Option<f64>,
+    #[serde(default)]
+    channels: Vec<ChannelEntry>,
+}
+
+#[derive(Deserialize)]
+struct ChannelEntry {
+    name: String,
+    center_hz: Option<f64>,
+    center_mhz: Option<f64>,
+    width_hz: Option<f64>,
+    width_mhz: Option<f64>,
+}
+
+static BAND_MAP: RwLock<Option<BandMap>> = RwLock::new(None);
+
+/// Classify rows with `map` instead of the built-in bands for the rest of
+/// the process
+pub fn set_band_map(map: BandMap) {
+    *BAND_MAP.write().unwrap_or_else(|e| e.into_inner()) = Some(map);
+}
+
+pub fn band_map() -> BandMap {
+    BAND_MAP.read().unwrap_or_else(|e| e.into_inner()).clone().unwrap_or_default()
+}
+
+/// Replace (or add) the `band` and `channel` columns, right after
+/// `center_freq_hz`. A row's signal is `sig_center_freq_hz` wide
+/// `sig_bandwidth_hz`, or `center_freq_hz` for rows without one. Datasets
+/// without `center_freq_hz` are left alone.
+pub fn add_band_columns(df: &mut DataFrame) -> Result<()> {
+    let Ok(center) = df.column("center_freq_hz").and_then(|c| c.cast(&DataType::Float64)) else {
+        return Ok(());
+    };
+    let floats = |name: &str| -> Result<Vec<Option<f64>>> {
+        match df.column(name) {
+            Ok(column) => Ok(column.cast(&DataType::Float64)?.f64()?.into_iter().collect()),
+            Err(_) => Ok(vec![None; df.height()]),
+        }
+    };
+    let sig_center = floats("sig_center_freq_hz")?;
+    let sig_bandwidth = floats("sig_bandwidth_hz")?;
+    let map = band_map();
+    let (bands, channels): (Vec<&str>, Vec<&str>) = center
+        .f64()?
+        .into_iter()
+        .zip(sig_center.iter().zip(&sig_bandwidth))
+        .map(|(center, (sig_center, sig_bandwidth))| {
+            // The parser writes 0 for a missing value
+            let (center, bandwidth) = match sig_center.filter(|hz| *hz > 0.0) {
+                Some(sig_center) => (Some(sig_center), sig_bandwidth.unwrap_or(0.0)),
+                None => (center.filter(|hz| *hz > 0.0), 0.0),
+            };
+            match center.and_then(|hz| map.classify(hz, bandwidth)) {
+                Some((band, channel)) => (band.name.as_str(), channel.map_or("", |c| c.name.as_str())),
+                None => ("", ""),
+            }
+        })
+        .unzip();
+    let columns = [Series::new(BAND_COLUMN.into(), bands), Series::new(CHANNEL_COLUMN.into(), channels)];
+    for name in [BAND_COLUMN, CHANNEL_COLUMN] {
+        if df.column(name).is_ok() {
+            let _ = df.drop_in_place(name)?;
+        }
+    }
+    let first = df.get_column_index("center_freq_hz").map_or(df.width(), |idx| idx + 1);
+    for (position, column) in (first..).zip(columns) {
+        df.insert_column(position, column)?;
+    }
+    Ok(())
+}
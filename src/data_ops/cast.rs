@@ -0,0 +1,98 @@
+use anyhow::Result;
+use polars::prelude::*;
+
+/// Dtypes a column can be cast to for the current session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CastTarget {
+    Float64,
+    Int64,
+    UInt64,
+    Boolean,
+    String,
+}
+
+impl CastTarget {
+    pub const ALL: [CastTarget; 5] = [
+        CastTarget::Float64,
+        CastTarget::Int64,
+        CastTarget::UInt64,
+        CastTarget::Boolean,
+        CastTarget::String,
+    ];
+
+    pub fn from_string(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "f64" | "float" | "float64" => Ok(CastTarget::Float64),
+            "i64" | "int" | "int64" => Ok(CastTarget::Int64),
+            "u64" | "uint" | "uint64" => Ok(CastTarget::UInt64),
+            "bool" | "boolean" => Ok(CastTarget::Boolean),
+            "str" | "string" => Ok(CastTarget::String),
+            _ => Err(anyhow::anyhow!("Unsupported cast type: {}", s)),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            CastTarget::Float64 => "f64",
+            CastTarget::Int64 => "i64",
+            CastTarget::UInt64 => "u64",
+            CastTarget::Boolean => "bool",
+            CastTarget::String => "str",
+        }
+    }
+
+    pub fn dtype(&self) -> DataType {
+        match self {
+            CastTarget::Float64 => DataType::Float64,
+            CastTarget::Int64 => DataType::Int64,
+            CastTarget::UInt64 => DataType::UInt64,
+            CastTarget::Boolean => DataType::Boolean,
+            CastTarget::String => DataType::String,
+        }
+    }
+
+    /// Expression casting `column` to this type. Values that can't be
+    /// converted become null instead of failing.
+    pub fn cast_expr(&self, column: &str) -> Expr {
+        match self {
+            // polars won't cast strings to booleans directly, so go through
+            // the string representation, which covers every source type
+            CastTarget::Boolean => {
+                let value = col(column).cast(DataType::String);
+                let is_true = value.clone().eq(lit("true"))
+                    .or(value.clone().eq(lit("True")))
+                    .or(value.clone().eq(lit("1")));
+                let is_false = value.clone().eq(lit("false"))
+                    .or(value.clone().eq(lit("False")))
+                    .or(value.eq(lit("0")));
+                when(is_true)
+                    .then(lit(true))
+                    .when(is_false)
+                    .then(lit(false))
+                    .otherwise(lit(NULL).cast(DataType::Boolean))
+                    .alias(column)
+            }
+            _ => col(column).cast(self.dtype()),
+        }
+    }
+}
+
+/// Parse a `column=type` cast specification, as used by the CLI `--cast` flag
+pub fn parse_cast_spec(spec: &str) -> Result<(String, CastTarget)> {
+    let (column, target) = spec
+        .split_once('=')
+        .ok_or_else(|| anyhow::anyhow!("Invalid cast '{}', expected column=type", spec))?;
+    Ok((column.trim().to_string(), CastTarget::from_string(target.trim())?))
+}
+
+/// Apply a set of column casts to a lazy frame
+pub fn cast_columns(lf: LazyFrame, casts: &[(String, CastTarget)]) -> Result<LazyFrame> {
+    if casts.is_empty() {
+        return Ok(lf);
+    }
+    let exprs: Vec<Expr> = casts
+        .iter()
+        .map(|(column, target)| target.cast_expr(column))
+        .collect();
+    Ok(lf.with_columns(exprs))
+}
@@ -0,0 +1,311 @@
+//! Clustering rows by numeric feature columns, to find structure in a corpus
+//! nobody has labeled yet.
+//!
+//! Features are z-scored by default so that a frequency in Hz doesn't drown
+//! out a probability. Rows missing any feature are left unclustered. Cluster
+//! ids are numbered from the largest cluster down; DBSCAN noise is
+//! [`DBSCAN_NOISE`].
+
+use super::columns::empty_columns;
+use anyhow::Result;
+use polars::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::BTreeMap;
+
+/// Column added by [`with_cluster_column`]
+pub const CLUSTER_COLUMN: &str = "cluster_id";
+
+/// Cluster id of rows DBSCAN finds in no dense region
+pub const DBSCAN_NOISE: i64 = -1;
+
+/// Features used when none are given, if the dataset has them
+const DEFAULT_FEATURES: [&str; 2] = ["snr_db", "sig_bandwidth_hz"];
+
+const MAX_KMEANS_ITERATIONS: usize = 100;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClusterMethod {
+    KMeans { k: usize },
+    /// `eps` is in feature units (standard deviations when standardized)
+    Dbscan { eps: f64, min_points: usize },
+}
+
+impl ClusterMethod {
+    pub fn describe(&self) -> String {
+        match self {
+            ClusterMethod::KMeans { k } => format!("k-means, k = {}", k),
+            ClusterMethod::Dbscan { eps, min_points } => format!("DBSCAN, eps = {}, min points = {}", eps, min_points),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClusterOptions {
+    pub columns: Vec<String>,
+    pub method: ClusterMethod,
+    /// Scale each feature to zero mean and unit variance first
+    pub standardize: bool,
+    /// Seed for the k-means starting centers; random otherwise
+    pub seed: Option<u64>,
+}
+
+/// A cluster id for each row
+#[derive(Debug, Clone, Default)]
+pub struct Clustering {
+    /// `None` for rows missing a feature value
+    pub labels: Vec<Option<i64>>,
+    /// Rows per cluster id
+    pub sizes: BTreeMap<i64, usize>,
+    pub skipped_rows: usize,
+    /// k-means only: sum of squared distances to the centers, in feature
+    /// units
+    pub inertia: Option<f64>,
+}
+
+impl Clustering {
+    /// Clusters found, not counting DBSCAN noise
+    pub fn num_clusters(&self) -> usize {
+        self.sizes.keys().filter(|id| **id != DBSCAN_NOISE).count()
+    }
+}
+
+/// `snr_db`, `sig_bandwidth_hz` and the `ml_*` probability columns that
+/// aren't empty (see [`empty_columns`])
+pub fn default_cluster_columns(df: &DataFrame) -> Vec<String> {
+    let empty = empty_columns(df);
+    df.get_columns()
+        .iter()
+        .filter(|column| column.dtype().is_float())
+        .map(|column| column.name().to_string())
+        .filter(|name| DEFAULT_FEATURES.contains(&name.as_str()) || name.starts_with("ml_"))
+        .filter(|name| !empty.contains(name))
+        .collect()
+}
+
+/// Cluster the rows of `df` by `options.columns`. `progress` is called with
+/// the fraction done now and then; an error from it stops the run.
+pub fn cluster(df: &DataFrame, options: &ClusterOptions, mut progress: impl FnMut(f32) -> Result<()>) -> Result<Clustering> {
+    if options.columns.is_empty() {
+        anyhow::bail!("At least one feature column is required");
+    }
+    let dims = options.columns.len();
+    let (rows, mut points) = feature_matrix(df, &options.columns)?;
+    if options.standardize {
+        standardize(&mut points, dims);
+    }
+    let (assigned, inertia) = match options.method {
+        ClusterMethod::KMeans { k } => {
+            if k == 0 {
+                anyhow::bail!("k must be at least 1");
+            }
+            let (labels, inertia) = kmeans(&points, dims, k, options.seed, &mut progress)?;
+            (labels, Some(inertia))
+        }
+        ClusterMethod::Dbscan { eps, min_points } => {
+            if eps <= 0.0 {
+                anyhow::bail!("eps must be positive");
+            }
+            (dbscan(&points, dims, eps, min_points.max(1), &mut progress)?, None)
+        }
+    };
+
+    // Renumber so 0 is the largest cluster
+    let mut counts: BTreeMap<i64, usize> = BTreeMap::new();
+    for id in &assigned {
+        *counts.entry(*id).or_default() += 1;
+    }
+    let mut by_size: Vec<(i64, usize)> = counts.iter().filter(|(id, _)| **id != DBSCAN_NOISE).map(|(id, n)| (*id, *n)).collect();
+    by_size.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+    let renumber: BTreeMap<i64, i64> = by_size.iter().enumerate().map(|(new, (old, _))| (*old, new as i64)).collect();
+
+    let mut labels = vec![None; df.height()];
+    let mut sizes = BTreeMap::new();
+    for (row, id) in rows.iter().zip(assigned) {
+        let id = renumber.get(&id).copied().unwrap_or(DBSCAN_NOISE);
+        labels[*row] = Some(id);
+        *sizes.entry(id).or_default() += 1;
+    }
+    Ok(Clustering { labels, sizes, skipped_rows: df.height() - rows.len(), inertia })
+}
+
+/// `df` with [`CLUSTER_COLUMN`], replacing an earlier one
+pub fn with_cluster_column(df: &DataFrame, clustering: &Clustering) -> Result<DataFrame> {
+    if clustering.labels.len() != df.height() {
+        anyhow::bail!("The clustering has {} rows, the dataset {}", clustering.labels.len(), df.height());
+    }
+    let column: Int64Chunked = clustering.labels.iter().copied().collect();
+    let mut df = df.clone();
+    df.with_column(column.into_series().with_name(CLUSTER_COLUMN.into()))?;
+    Ok(df)
+}
+
+/// Rows with every feature present and finite, and their features flattened
+/// row by row
+pub(crate) fn feature_matrix(df: &DataFrame, columns: &[String]) -> Result<(Vec<usize>, Vec<f64>)> {
+    let values = columns
+        .iter()
+        .map(|name| {
+            let column = df.column(name)?;
+            if !column.dtype().is_numeric() && !matches!(column.dtype(), DataType::Boolean) {
+                anyhow::bail!("Column {} is not numeric ({})", name, column.dtype());
+            }
+            Ok(column.cast(&DataType::Float64)?.f64()?.clone())
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let mut rows = Vec::new();
+    let mut points = Vec::new();
+    for row in 0..df.height() {
+        let features: Option<Vec<f64>> = values.iter().map(|column| column.get(row).filter(|v| v.is_finite())).collect();
+        if let Some(features) = features {
+            rows.push(row);
+            points.extend(features);
+        }
+    }
+    Ok((rows, points))
+}
+
+pub(crate) fn standardize(points: &mut [f64], dims: usize) {
+    let n = points.len() / dims;
+    if n == 0 {
+        return;
+    }
+    for dim in 0..dims {
+        let mean = points.iter().skip(dim).step_by(dims).sum::<f64>() / n as f64;
+        let var = points.iter().skip(dim).step_by(dims).map(|v| (v - mean).powi(2)).sum::<f64>() / n as f64;
+        let std = if var > 0.0 { var.sqrt() } else { 1.0 };
+        points.iter_mut().skip(dim).step_by(dims).for_each(|v| *v = (*v - mean) / std);
+    }
+}
+
+pub(crate) fn distance_sq(a: &[f64], b: &[f64]) -> f64 {
+    a.iter().zip(b).map(|(x, y)| (x - y).powi(2)).sum()
+}
+
+/// Lloyd's algorithm from k-means++ starting centers. Returns each point's
+/// cluster and the inertia.
+fn kmeans(points: &[f64], dims: usize, k: usize, seed: Option<u64>, progress: &mut impl FnMut(f32) -> Result<()>) -> Result<(Vec<i64>, f64)> {
+    let n = points.len() / dims;
+    if n == 0 {
+        return Ok((Vec::new(), 0.0));
+    }
+    let point = |i: usize| &points[i * dims..(i + 1) * dims];
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    // k-means++: each next center drawn with probability proportional to
+    // its squared distance from the nearest center so far
+    let k = k.min(n);
+    let mut centers: Vec<f64> = point(rng.gen_range(0..n)).to_vec();
+    let mut nearest: Vec<f64> = (0..n).map(|i| distance_sq(point(i), &centers[..dims])).collect();
+    while centers.len() / dims < k {
+        let total: f64 = nearest.iter().sum();
+        let next = if total > 0.0 {
+            let mut target = rng.gen_range(0.0..total);
+            nearest.iter().position(|d| {
+                target -= d;
+                target < 0.0
+            }).unwrap_or(n - 1)
+        } else {
+            rng.gen_range(0..n)
+        };
+        let start = centers.len();
+        centers.extend_from_slice(point(next));
+        for (i, d) in nearest.iter_mut().enumerate() {
+            *d = d.min(distance_sq(point(i), &centers[start..]));
+        }
+    }
+
+    let mut labels = vec![0usize; n];
+    for iteration in 0..MAX_KMEANS_ITERATIONS {
+        progress(iteration as f32 / MAX_KMEANS_ITERATIONS as f32)?;
+        let mut changed = iteration == 0;
+        for (i, label) in labels.iter_mut().enumerate() {
+            let best = (0..k)
+                .min_by(|a, b| {
+                    let da = distance_sq(point(i), &centers[a * dims..(a + 1) * dims]);
+                    let db = distance_sq(point(i), &centers[b * dims..(b + 1) * dims]);
+                    da.total_cmp(&db)
+                })
+                .unwrap_or(0);
+            changed |= best != *label;
+            *label = best;
+        }
+        if !changed {
+            break;
+        }
+        let mut sums = vec![0.0; k * dims];
+        let mut counts = vec![0usize; k];
+        for (i, label) in labels.iter().enumerate() {
+            counts[*label] += 1;
+            sums[label * dims..(label + 1) * dims].iter_mut().zip(point(i)).for_each(|(s, v)| *s += v);
+        }
+        for c in 0..k {
+            if counts[c] > 0 {
+                for d in 0..dims {
+                    centers[c * dims + d] = sums[c * dims + d] / counts[c] as f64;
+                }
+            } else {
+                // An empty cluster restarts at the point farthest from its center
+                let far = (0..n)
+                    .max_by(|a, b| {
+                        let da = distance_sq(point(*a), &centers[labels[*a] * dims..(labels[*a] + 1) * dims]);
+                        let db = distance_sq(point(*b), &centers[labels[*b] * dims..(labels[*b] + 1) * dims]);
+                        da.total_cmp(&db)
+                    })
+                    .unwrap_or(0);
+                centers[c * dims..(c + 1) * dims].copy_from_slice(point(far));
+            }
+        }
+    }
+    let inertia = labels.iter().enumerate().map(|(i, c)| distance_sq(point(i), &centers[c * dims..(c + 1) * dims])).sum();
+    Ok((labels.into_iter().map(|c| c as i64).collect(), inertia))
+}
+
+/// Density-based clustering. Neighbors are found by comparing every pair of
+/// points, which is fine up to some tens of thousands of rows.
+fn dbscan(points: &[f64], dims: usize, eps: f64, min_points: usize, progress: &mut impl FnMut(f32) -> Result<()>) -> Result<Vec<i64>> {
+    let n = points.len() / dims;
+    let point = |i: usize| &points[i * dims..(i + 1) * dims];
+    let eps_sq = eps * eps;
+    // Includes the point itself, as the usual definition does
+    let neighbors = |i: usize| -> Vec<usize> { (0..n).filter(|j| distance_sq(point(i), point(*j)) <= eps_sq).collect() };
+
+    const UNVISITED: i64 = i64::MIN;
+    let mut labels = vec![UNVISITED; n];
+    let mut next_cluster = 0;
+    for i in 0..n {
+        if i % 1000 == 0 {
+            progress(i as f32 / n as f32)?;
+        }
+        if labels[i] != UNVISITED {
+            continue;
+        }
+        let seeds = neighbors(i);
+        if seeds.len() < min_points {
+            labels[i] = DBSCAN_NOISE;
+            continue;
+        }
+        let cluster = next_cluster;
+        next_cluster += 1;
+        labels[i] = cluster;
+        let mut queue = seeds;
+        while let Some(j) = queue.pop() {
+            if labels[j] == DBSCAN_NOISE {
+                // A border point
+                labels[j] = cluster;
+            }
+            if labels[j] != UNVISITED {
+                continue;
+            }
+            labels[j] = cluster;
+            let reachable = neighbors(j);
+            if reachable.len() >= min_points {
+                queue.extend(reachable.into_iter().filter(|r| labels[*r] == UNVISITED || labels[*r] == DBSCAN_NOISE));
+            }
+        }
+    }
+    Ok(labels)
+}
@@ -0,0 +1,74 @@
+use super::histogram::{histogram, Histogram};
+use anyhow::Result;
+use polars::prelude::*;
+use std::collections::HashMap;
+
+/// Bins in a column's inline histogram
+pub const COLUMN_STATS_BINS: usize = 20;
+
+/// A quick profile of one column, for the GUI's Column Stats popup
+#[derive(Debug, Clone)]
+pub struct ColumnStats {
+    pub column: String,
+    pub dtype: String,
+    pub rows: usize,
+    pub nulls: usize,
+    /// Distinct non-null values
+    pub n_unique: usize,
+    pub numeric: Option<NumericSummary>,
+    /// Most frequent values with their counts, most common first; for
+    /// columns that aren't numeric
+    pub top_values: Vec<(String, usize)>,
+    pub histogram: Option<Histogram>,
+}
+
+/// Null when the column has no non-null values
+#[derive(Debug, Clone, Copy)]
+pub struct NumericSummary {
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub mean: Option<f64>,
+    pub std: Option<f64>,
+}
+
+impl ColumnStats {
+    /// Non-null values
+    pub fn count(&self) -> usize {
+        self.rows - self.nulls
+    }
+}
+
+/// Profile `column` of `df`, keeping its `top_n` most frequent values
+pub fn column_stats(df: &DataFrame, column: &str, top_n: usize) -> Result<ColumnStats> {
+    let series = df.column(column).map_err(|_| anyhow::anyhow!("No column named '{}'", column))?;
+    let numeric = if series.dtype().is_numeric() {
+        let values = series.cast(&DataType::Float64)?;
+        let values = values.f64()?;
+        Some(NumericSummary { min: values.min(), max: values.max(), mean: values.mean(), std: values.std(1) })
+    } else {
+        None
+    };
+    let top_values = if numeric.is_none() { top_values(series, top_n)? } else { Vec::new() };
+    Ok(ColumnStats {
+        column: column.to_string(),
+        dtype: series.dtype().to_string(),
+        rows: series.len(),
+        nulls: series.null_count(),
+        n_unique: series.drop_nulls().n_unique()?,
+        numeric,
+        top_values,
+        histogram: histogram(series, COLUMN_STATS_BINS)?,
+    })
+}
+
+/// Ties go to the value that sorts first, so the list is stable
+fn top_values(series: &Series, top_n: usize) -> Result<Vec<(String, usize)>> {
+    let text = series.cast(&DataType::String)?;
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for value in text.str()?.into_iter().flatten() {
+        *counts.entry(value).or_default() += 1;
+    }
+    let mut counts: Vec<(&str, usize)> = counts.into_iter().collect();
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    Ok(counts.into_iter().take(top_n).map(|(value, count)| (value.to_string(), count)).collect())
+}
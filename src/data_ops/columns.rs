@@ -0,0 +1,33 @@
+use polars::prelude::*;
+
+/// Columns that carry no information for this dataset: every value is null,
+/// zero, an empty string or false. Typical for the ml_* columns of datasets
+/// without ML annotations, which are filled with defaults.
+pub fn empty_columns(df: &DataFrame) -> Vec<String> {
+    if df.height() == 0 {
+        return Vec::new();
+    }
+    df.get_column_names()
+        .iter()
+        .map(|name| name.to_string())
+        .filter(|name| is_empty_column(df, name).unwrap_or(false))
+        .collect()
+}
+
+fn is_empty_column(df: &DataFrame, name: &str) -> PolarsResult<bool> {
+    let column = df.column(name)?;
+    if column.null_count() == column.len() {
+        return Ok(true);
+    }
+    let dtype = column.dtype();
+    Ok(match dtype {
+        DataType::String => column.str()?.into_iter().all(|v| v.is_none_or(str::is_empty)),
+        DataType::Boolean => column.bool()?.into_iter().all(|v| v != Some(true)),
+        _ if dtype.is_numeric() => column
+            .cast(&DataType::Float64)?
+            .f64()?
+            .into_iter()
+            .all(|v| v.is_none_or(|x| x == 0.0)),
+        _ => false,
+    })
+}
@@ -0,0 +1,295 @@
+use super::activity::{parse_capture_datetime, UNKNOWN_SENSOR};
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use polars::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+
+/// Seconds between one recording's end and the next one's start that still
+/// counts as continuous, either way (clock jitter, rounded durations)
+pub const DEFAULT_CONTINUITY_TOLERANCE_S: f64 = 0.5;
+
+/// One recording of a sensor's collection
+#[derive(Debug, Clone)]
+pub struct CollectedRecording {
+    pub meta_filename: String,
+    /// `ds:sequence_num`; `None` when no recording of the sensor has one
+    pub sequence_num: Option<u64>,
+    pub start: Option<NaiveDateTime>,
+    pub duration_s: Option<f64>,
+}
+
+impl CollectedRecording {
+    pub fn end(&self) -> Option<NaiveDateTime> {
+        let duration = chrono::Duration::microseconds((self.duration_s? * 1e6).round() as i64);
+        Some(self.start? + duration)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContinuityIssueKind {
+    /// Sequence numbers `first..=last` are missing
+    MissingSequence { first: u64, last: u64 },
+    /// Two recordings carry the same sequence number
+    DuplicateSequence(u64),
+    /// Nothing was recorded for this many seconds
+    Gap(f64),
+    /// The next recording starts this many seconds before this one ends
+    Overlap(f64),
+}
+
+impl ContinuityIssueKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ContinuityIssueKind::MissingSequence { .. } => "missing",
+            ContinuityIssueKind::DuplicateSequence(_) => "duplicate",
+            ContinuityIssueKind::Gap(_) => "gap",
+            ContinuityIssueKind::Overlap(_) => "overlap",
+        }
+    }
+}
+
+/// A break in continuity between two recordings of a sensor, consecutive by
+/// sequence number (missing, duplicate) or by time (gap, overlap)
+#[derive(Debug, Clone)]
+pub struct ContinuityIssue {
+    pub kind: ContinuityIssueKind,
+    pub before: String,
+    pub after: String,
+    /// End of `before`, for gaps and overlaps
+    pub at: Option<NaiveDateTime>,
+}
+
+impl std::fmt::Display for ContinuityIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            ContinuityIssueKind::MissingSequence { first, last } if first == last => write!(f, "sequence {} missing", first)?,
+            ContinuityIssueKind::MissingSequence { first, last } => write!(f, "sequence {}..{} missing ({})", first, last, last - first + 1)?,
+            ContinuityIssueKind::DuplicateSequence(seq) => write!(f, "sequence {} repeated", seq)?,
+            ContinuityIssueKind::Gap(seconds) => write!(f, "{:.3} s gap", seconds)?,
+            ContinuityIssueKind::Overlap(seconds) => write!(f, "{:.3} s overlap", seconds)?,
+        }
+        write!(f, " between {} and {}", self.before, self.after)?;
+        if let Some(at) = self.at {
+            write!(f, " at {}", at)?;
+        }
+        Ok(())
+    }
+}
+
+/// Continuity of one sensor's (`sdr_handle`) recordings
+#[derive(Debug, Clone)]
+pub struct SensorContinuity {
+    pub sensor: String,
+    /// By sequence number, then capture time
+    pub recordings: Vec<CollectedRecording>,
+    /// Gaps and overlaps in time order, after the sequence issues
+    pub issues: Vec<ContinuityIssue>,
+}
+
+impl SensorContinuity {
+    /// Lowest and highest sequence number
+    pub fn sequence_range(&self) -> Option<(u64, u64)> {
+        let numbers = self.recordings.iter().filter_map(|r| r.sequence_num);
+        Some((numbers.clone().min()?, numbers.max()?))
+    }
+
+    /// Sequence numbers missing between the lowest and highest
+    pub fn missing_count(&self) -> u64 {
+        self.issues
+            .iter()
+            .map(|issue| match issue.kind {
+                ContinuityIssueKind::MissingSequence { first, last } => last - first + 1,
+                _ => 0,
+            })
+            .sum()
+    }
+
+    pub fn count(&self, kind: &str) -> usize {
+        self.issues.iter().filter(|issue| issue.kind.name() == kind).count()
+    }
+
+    /// First start and last end
+    pub fn time_range(&self) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let start = self.recordings.iter().filter_map(|r| r.start).min()?;
+        let end = self.recordings.iter().filter_map(|r| r.end().or(r.start)).max()?;
+        Some((start, end))
+    }
+
+    pub fn total_gap_s(&self) -> f64 {
+        self.issues
+            .iter()
+            .map(|issue| match issue.kind {
+                ContinuityIssueKind::Gap(seconds) => seconds,
+                _ => 0.0,
+            })
+            .sum()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ContinuityReport {
+    pub sensors: Vec<SensorContinuity>,
+    /// Recordings left out of the time checks for lack of a parseable
+    /// capture_datetime
+    pub untimed_recordings: usize,
+}
+
+impl ContinuityReport {
+    pub fn issue_count(&self) -> usize {
+        self.sensors.iter().map(|s| s.issues.len()).sum()
+    }
+
+    /// One row per issue: sensor, kind, the recordings either side, the
+    /// missing sequence numbers or the gap/overlap length, and when
+    pub fn issues_dataframe(&self) -> Result<DataFrame> {
+        let issues: Vec<(&str, &ContinuityIssue)> = self
+            .sensors
+            .iter()
+            .flat_map(|sensor| sensor.issues.iter().map(move |issue| (sensor.sensor.as_str(), issue)))
+            .collect();
+        let sequence = |pick: fn(u64, u64) -> u64| -> Vec<Option<u64>> {
+            issues
+                .iter()
+                .map(|(_, issue)| match issue.kind {
+                    ContinuityIssueKind::MissingSequence { first, last } => Some(pick(first, last)),
+                    ContinuityIssueKind::DuplicateSequence(seq) => Some(seq),
+                    _ => None,
+                })
+                .collect()
+        };
+        let seconds: Vec<Option<f64>> = issues
+            .iter()
+            .map(|(_, issue)| match issue.kind {
+                ContinuityIssueKind::Gap(seconds) | ContinuityIssueKind::Overlap(seconds) => Some(seconds),
+                _ => None,
+            })
+            .collect();
+        let df = DataFrame::new(vec![
+            Series::new("sdr_handle".into(), issues.iter().map(|(sensor, _)| *sensor).collect::<Vec<_>>()),
+            Series::new("issue".into(), issues.iter().map(|(_, issue)| issue.kind.name()).collect::<Vec<_>>()),
+            Series::new("before".into(), issues.iter().map(|(_, issue)| issue.before.as_str()).collect::<Vec<_>>()),
+            Series::new("after".into(), issues.iter().map(|(_, issue)| issue.after.as_str()).collect::<Vec<_>>()),
+            Series::new("first_sequence_num".into(), sequence(|first, _| first)),
+            Series::new("last_sequence_num".into(), sequence(|_, last| last)),
+            Series::new("seconds".into(), seconds),
+            Series::new(
+                "at".into(),
+                issues.iter().map(|(_, issue)| issue.at.map(|at| at.format("%Y-%m-%dT%H:%M:%S%.fZ").to_string())).collect::<Vec<_>>(),
+            ),
+        ])?;
+        Ok(df)
+    }
+}
+
+/// Check each `sdr_handle`'s recordings for missing or repeated
+/// `sequence_num`s, and for gaps and overlaps longer than `tolerance_s`
+/// between one recording's end (`capture_datetime` plus `duration_s`) and
+/// the next one's start. Each recording is counted once however many
+/// annotation rows it has. A sensor whose recordings all have sequence
+/// number 0 is taken not to number them.
+pub fn continuity_report(df: &DataFrame, tolerance_s: f64) -> Result<ContinuityReport> {
+    let sensors = df.column("sdr_handle")?.str()?;
+    let datetimes = df.column("capture_datetime")?.str()?;
+    let filenames = df.column("meta_filename")?.str()?;
+    let sequence_nums = df.column("sequence_num").ok().map(|c| c.cast(&DataType::UInt64)).transpose()?;
+    let sequence_nums = sequence_nums.as_ref().map(|c| c.u64()).transpose()?;
+    let durations = df.column("duration_s").ok().map(|c| c.cast(&DataType::Float64)).transpose()?;
+    let durations = durations.as_ref().map(|c| c.f64()).transpose()?;
+
+    let mut seen = HashSet::new();
+    let mut by_sensor: BTreeMap<String, Vec<CollectedRecording>> = BTreeMap::new();
+    for idx in 0..df.height() {
+        let sensor = sensors.get(idx).filter(|s| !s.is_empty()).unwrap_or(UNKNOWN_SENSOR);
+        let filename = filenames.get(idx).unwrap_or_default();
+        if !seen.insert((sensor, filename)) {
+            continue;
+        }
+        by_sensor.entry(sensor.to_string()).or_default().push(CollectedRecording {
+            meta_filename: filename.to_string(),
+            sequence_num: sequence_nums.and_then(|s| s.get(idx)),
+            start: datetimes.get(idx).and_then(parse_capture_datetime),
+            duration_s: durations.and_then(|d| d.get(idx)).filter(|d| d.is_finite() && *d >= 0.0),
+        });
+    }
+
+    let mut untimed_recordings = 0;
+    let sensors = by_sensor
+        .into_iter()
+        .map(|(sensor, mut recordings)| {
+            if recordings.iter().all(|r| r.sequence_num.unwrap_or(0) == 0) {
+                for recording in &mut recordings {
+                    recording.sequence_num = None;
+                }
+            }
+            untimed_recordings += recordings.iter().filter(|r| r.start.is_none()).count();
+            let mut issues = sequence_issues(&mut recordings);
+            issues.extend(time_issues(&recordings, tolerance_s));
+            SensorContinuity { sensor, recordings, issues }
+        })
+        .collect();
+    Ok(ContinuityReport { sensors, untimed_recordings })
+}
+
+/// Sort by sequence number, then time, and report the numbers skipped or
+/// repeated
+fn sequence_issues(recordings: &mut [CollectedRecording]) -> Vec<ContinuityIssue> {
+    recordings.sort_by(|a, b| {
+        a.sequence_num
+            .cmp(&b.sequence_num)
+            .then_with(|| a.start.cmp(&b.start))
+            .then_with(|| a.meta_filename.cmp(&b.meta_filename))
+    });
+    let mut issues = Vec::new();
+    for pair in recordings.windows(2) {
+        let (Some(before), Some(after)) = (pair[0].sequence_num, pair[1].sequence_num) else {
+            continue;
+        };
+        let kind = if after == before {
+            ContinuityIssueKind::DuplicateSequence(after)
+        } else if after > before + 1 {
+            ContinuityIssueKind::MissingSequence { first: before + 1, last: after - 1 }
+        } else {
+            continue;
+        };
+        issues.push(ContinuityIssue { kind, before: pair[0].meta_filename.clone(), after: pair[1].meta_filename.clone(), at: None });
+    }
+    issues
+}
+
+/// Gaps and overlaps beyond `tolerance_s` between recordings consecutive in
+/// time. Each start is compared with the latest end so far, so a long
+/// recording spanning several short ones isn't read as gaps between them.
+fn time_issues(recordings: &[CollectedRecording], tolerance_s: f64) -> Vec<ContinuityIssue> {
+    let mut timed: Vec<&CollectedRecording> = recordings.iter().filter(|r| r.start.is_some()).collect();
+    timed.sort_by(|a, b| a.start.cmp(&b.start).then_with(|| a.meta_filename.cmp(&b.meta_filename)));
+    let mut issues = Vec::new();
+    let mut latest: Option<(&CollectedRecording, NaiveDateTime)> = None;
+    for recording in timed {
+        let Some(start) = recording.start else {
+            continue;
+        };
+        let end = recording.end().unwrap_or(start);
+        if let Some((previous, previous_end)) = latest {
+            let seconds = (start - previous_end).num_microseconds().unwrap_or(i64::MAX) as f64 / 1e6;
+            let kind = if seconds > tolerance_s {
+                Some(ContinuityIssueKind::Gap(seconds))
+            } else if -seconds > tolerance_s {
+                Some(ContinuityIssueKind::Overlap(-seconds))
+            } else {
+                None
+            };
+            if let Some(kind) = kind {
+                issues.push(ContinuityIssue {
+                    kind,
+                    before: previous.meta_filename.clone(),
+                    after: recording.meta_filename.clone(),
+                    at: Some(previous_end),
+                });
+            }
+        }
+        if latest.is_none_or(|(_, previous_end)| end > previous_end) {
+            latest = Some((recording, end));
+        }
+    }
+    issues
+}
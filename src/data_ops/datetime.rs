@@ -0,0 +1,53 @@
+use super::activity::parse_capture_datetime;
+use anyhow::Result;
+use chrono::{NaiveDate, NaiveDateTime, Timelike};
+use polars::prelude::*;
+
+/// Columns derived from `capture_datetime`, in table order after it
+pub const TIME_COLUMNS: [&str; 4] = ["capture_time", "capture_date", "capture_hour", "capture_unix"];
+
+/// Replace (or add) the columns derived from the `capture_datetime` text:
+/// `capture_time` (a Datetime, UTC), `capture_date` (a Date), `capture_hour`
+/// (0–23) and `capture_unix` (seconds since 1970). They're null where the
+/// text doesn't parse. Datasets without `capture_datetime` are left alone.
+pub fn add_time_columns(df: &mut DataFrame) -> Result<()> {
+    let Ok(text) = df.column("capture_datetime").and_then(|c| c.cast(&DataType::String)) else {
+        return Ok(());
+    };
+    let parsed: Vec<Option<NaiveDateTime>> = text.str()?.into_iter().map(|v| v.and_then(parse_capture_datetime)).collect();
+    let epoch = NaiveDate::from_ymd_opt(1970, 1, 1).expect("valid date");
+    let micros: Vec<Option<i64>> = parsed.iter().map(|dt| dt.map(|dt| dt.and_utc().timestamp_micros())).collect();
+    let days: Vec<Option<i32>> = parsed.iter().map(|dt| dt.map(|dt| (dt.date() - epoch).num_days() as i32)).collect();
+    let hours: Vec<Option<u32>> = parsed.iter().map(|dt| dt.map(|dt| dt.hour())).collect();
+    let unix: Vec<Option<f64>> = micros.iter().map(|us| us.map(|us| us as f64 / 1e6)).collect();
+    let columns = [
+        Series::new("capture_time".into(), micros).cast(&DataType::Datetime(TimeUnit::Microseconds, None))?,
+        Series::new("capture_date".into(), days).cast(&DataType::Date)?,
+        Series::new("capture_hour".into(), hours),
+        Series::new("capture_unix".into(), unix),
+    ];
+    for name in TIME_COLUMNS {
+        if df.column(name).is_ok() {
+            let _ = df.drop_in_place(name)?;
+        }
+    }
+    let first = df.get_column_index("capture_datetime").map_or(df.width(), |idx| idx + 1);
+    for (position, column) in (first..).zip(columns) {
+        df.insert_column(position, column)?;
+    }
+    Ok(())
+}
+
+/// A point in time typed into a filter: `2024-01-01`, `2024-01-01T12:30`,
+/// `2024-01-01 12:30:00` or a full `core:datetime`. The flag is true for a
+/// bare date, which as the end of a range stands for the whole day.
+pub fn parse_time_literal(text: &str) -> Option<(NaiveDateTime, bool)> {
+    let text = text.trim();
+    if let Ok(date) = NaiveDate::parse_from_str(text, "%Y-%m-%d") {
+        return date.and_hms_opt(0, 0, 0).map(|dt| (dt, true));
+    }
+    let text = text.replacen(' ', "T", 1);
+    parse_capture_datetime(&text)
+        .or_else(|| NaiveDateTime::parse_from_str(text.trim_end_matches('Z'), "%Y-%m-%dT%H:%M").ok())
+        .map(|dt| (dt, false))
+}
@@ -0,0 +1,129 @@
+//! Extra columns computed from the others after a dataset is built, from a
+//! TOML file of SQL-style expressions, e.g. `center_freq_hz / 1e6`.
+
+use anyhow::{Context, Result};
+use polars::prelude::*;
+use polars::sql::sql_expr;
+use serde::Deserialize;
+use std::path::Path;
+
+#[derive(Debug, Clone)]
+pub struct DerivedColumn {
+    pub name: String,
+    /// The expression as written in the file
+    pub text: String,
+    expr: Expr,
+}
+
+impl DerivedColumn {
+    /// Parse `text` (SQL expression syntax) now, so a typo shows up before
+    /// the scan rather than after it
+    pub fn new(name: &str, text: &str) -> Result<Self> {
+        if name.trim().is_empty() {
+            anyhow::bail!("Derived column {:?} has no name", text);
+        }
+        let expr = sql_expr(expand_exponents(text)).map_err(|e| anyhow::anyhow!("Derived column {}: invalid expression {:?}: {}", name, text, e))?;
+        Ok(DerivedColumn { name: name.to_string(), text: text.to_string(), expr })
+    }
+}
+
+/// Columns added in order, so later ones can use earlier ones
+#[derive(Debug, Clone, Default)]
+pub struct DerivedColumns {
+    pub columns: Vec<DerivedColumn>,
+}
+
+impl DerivedColumns {
+    /// A derived columns file:
+    ///
+    /// ```toml
+    /// [[column]]
+    /// name = "freq_mhz"
+    /// expr = "center_freq_hz / 1e6"
+    ///
+    /// [[column]]
+    /// name = "is_strong"
+    /// expr = "snr_db > 20"
+    /// ```
+    ///
+    /// Column names that aren't plain identifiers go in double quotes.
+    pub fn from_toml<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path).map_err(|e| anyhow::anyhow!("Failed to read {:?}: {}", path, e))?;
+        let file: DerivedColumnsFile = toml::from_str(&contents).map_err(|e| anyhow::anyhow!("Invalid derived columns file {:?}: {}", path, e))?;
+        let columns = file
+            .column
+            .iter()
+            .map(|entry| DerivedColumn::new(&entry.name, &entry.expr))
+            .collect::<Result<Vec<_>>>()
+            .with_context(|| format!("Invalid derived columns file {:?}", path))?;
+        Ok(DerivedColumns { columns })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.columns.is_empty()
+    }
+}
+
+/// Write numbers like `1e6` out in full (`1000000`), since the SQL parser
+/// only takes plain decimals. Quoted text and names are left alone.
+fn expand_exponents(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut quote = None;
+    let mut rest = text;
+    while let Some(c) = rest.chars().next() {
+        let starts_number = quote.is_none() && c.is_ascii_digit() && !out.ends_with(|p: char| p.is_alphanumeric() || p == '_' || p == '.');
+        if starts_number {
+            let mut end = rest.find(|c: char| !(c.is_ascii_digit() || c == '.')).unwrap_or(rest.len());
+            let mut has_exponent = false;
+            if let Some(exponent) = rest[end..].strip_prefix(['e', 'E']) {
+                let sign = usize::from(exponent.starts_with(['+', '-']));
+                let digits = exponent[sign..].find(|c: char| !c.is_ascii_digit()).unwrap_or(exponent.len() - sign);
+                if digits > 0 {
+                    end += 1 + sign + digits;
+                    has_exponent = true;
+                }
+            }
+            match rest[..end].parse::<f64>() {
+                Ok(value) if has_exponent => out.push_str(&value.to_string()),
+                _ => out.push_str(&rest[..end]),
+            }
+            rest = &rest[end..];
+            continue;
+        }
+        match quote {
+            Some(q) if c == q => quote = None,
+            None if c == '\'' || c == '"' => quote = Some(c),
+            _ => {}
+        }
+        out.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    out
+}
+
+#[derive(Deserialize)]
+struct DerivedColumnsFile {
+    #[serde(default)]
+    column: Vec<DerivedColumnEntry>,
+}
+
+#[derive(Deserialize)]
+struct DerivedColumnEntry {
+    name: String,
+    expr: String,
+}
+
+/// Add (or replace) the derived columns at the end of `df`. A column the
+/// expression uses that the dataset lacks is an error naming the derived
+/// column.
+pub fn apply_derived_columns(mut df: DataFrame, derived: &DerivedColumns) -> Result<DataFrame> {
+    for column in &derived.columns {
+        df = df
+            .lazy()
+            .with_column(column.expr.clone().alias(column.name.as_str()))
+            .collect()
+            .map_err(|e| anyhow::anyhow!("Derived column {} ({}): {}", column.name, column.text, e))?;
+    }
+    Ok(df)
+}
@@ -0,0 +1,133 @@
+use anyhow::Result;
+use polars::prelude::*;
+
+/// A statistic computed for each described column
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Metric {
+    /// Non-null values
+    Count,
+    Mean,
+    Std,
+    Min,
+    Max,
+    /// Linearly interpolated, `0.0..=1.0`
+    Percentile(f64),
+    NUnique,
+}
+
+/// Metrics used when none are given
+pub const DEFAULT_METRICS: [Metric; 8] = [
+    Metric::Count,
+    Metric::Mean,
+    Metric::Std,
+    Metric::Min,
+    Metric::Percentile(0.25),
+    Metric::Percentile(0.5),
+    Metric::Percentile(0.75),
+    Metric::Max,
+];
+
+impl Metric {
+    /// Parse `count`, `mean`, `std`, `min`, `max`, `n_unique`, `pNN` (e.g.
+    /// `p95`) or `percentiles` (p25, p50 and p75)
+    pub fn parse(spec: &str) -> Result<Vec<Self>> {
+        let spec = spec.trim();
+        let metric = match spec {
+            "count" => Metric::Count,
+            "mean" => Metric::Mean,
+            "std" => Metric::Std,
+            "min" => Metric::Min,
+            "max" => Metric::Max,
+            "n_unique" => Metric::NUnique,
+            "percentiles" => return Ok(vec![Metric::Percentile(0.25), Metric::Percentile(0.5), Metric::Percentile(0.75)]),
+            _ => {
+                let percent = spec
+                    .strip_prefix('p')
+                    .and_then(|p| p.parse::<f64>().ok())
+                    .filter(|p| (0.0..=100.0).contains(p))
+                    .ok_or_else(|| anyhow::anyhow!("Unknown metric '{}', expected count, mean, std, min, max, n_unique, percentiles or pNN", spec))?;
+                Metric::Percentile(percent / 100.0)
+            }
+        };
+        Ok(vec![metric])
+    }
+
+    /// Name of the metric's column in the output
+    pub fn output_name(&self) -> String {
+        match self {
+            Metric::Count => "count".to_string(),
+            Metric::Mean => "mean".to_string(),
+            Metric::Std => "std".to_string(),
+            Metric::Min => "min".to_string(),
+            Metric::Max => "max".to_string(),
+            // Rounded, as q / 100 * 100 isn't always q again (p7 would be
+            // p7.000000000000001)
+            Metric::Percentile(q) => format!("p{}", (q * 100.0 * 1e6).round() / 1e6),
+            Metric::NUnique => "n_unique".to_string(),
+        }
+    }
+
+    /// Null where the metric doesn't apply, e.g. the mean of a text column
+    fn compute(&self, series: &Series) -> Result<Option<f64>> {
+        let numeric = || -> Result<Option<Float64Chunked>> {
+            if !series.dtype().is_numeric() && series.dtype() != &DataType::Boolean {
+                return Ok(None);
+            }
+            Ok(Some(series.cast(&DataType::Float64)?.f64()?.clone()))
+        };
+        Ok(match self {
+            Metric::Count => Some((series.len() - series.null_count()) as f64),
+            Metric::NUnique => Some(series.drop_nulls().n_unique()? as f64),
+            Metric::Mean => numeric()?.and_then(|values| values.mean()),
+            Metric::Std => numeric()?.and_then(|values| values.std(1)),
+            Metric::Min => numeric()?.and_then(|values| values.min()),
+            Metric::Max => numeric()?.and_then(|values| values.max()),
+            Metric::Percentile(q) => match numeric()? {
+                Some(values) => values.quantile(*q, QuantileInterpolOptions::Linear)?,
+                None => None,
+            },
+        })
+    }
+}
+
+/// One row per column of `lf` with `metrics` as columns. `columns` defaults
+/// to every numeric column; naming a column that doesn't exist is an error.
+pub fn describe(mut lf: LazyFrame, columns: &[String], metrics: &[Metric]) -> Result<DataFrame> {
+    let schema = lf.schema()?;
+    let columns: Vec<String> = if columns.is_empty() {
+        schema.iter().filter(|(_, dtype)| dtype.is_numeric()).map(|(name, _)| name.to_string()).collect()
+    } else {
+        for column in columns {
+            if schema.get(column).is_none() {
+                anyhow::bail!("No column named '{}'", column);
+            }
+        }
+        columns.to_vec()
+    };
+    let mut unique: Vec<Metric> = Vec::new();
+    for metric in if metrics.is_empty() { &DEFAULT_METRICS[..] } else { metrics } {
+        if !unique.contains(metric) {
+            unique.push(*metric);
+        }
+    }
+    let metrics = unique;
+
+    let data = lf.select(columns.iter().map(|c| col(c)).collect::<Vec<_>>()).collect()?;
+    let mut values: Vec<Vec<Option<f64>>> = vec![Vec::with_capacity(columns.len()); metrics.len()];
+    for column in &columns {
+        let series = data.column(column)?;
+        for (metric, values) in metrics.iter().zip(values.iter_mut()) {
+            values.push(metric.compute(series)?);
+        }
+    }
+    let mut stats = vec![Series::new("column".into(), &columns)];
+    for (metric, values) in metrics.iter().zip(values) {
+        let series = Series::new(metric.output_name().as_str().into(), values);
+        // Counts read better as integers
+        stats.push(match metric {
+            Metric::Count | Metric::NUnique => series.cast(&DataType::UInt64)?,
+            _ => series,
+        });
+    }
+    Ok(DataFrame::new(stats)?)
+}
@@ -0,0 +1,188 @@
+use anyhow::Result;
+use polars::prelude::*;
+use std::collections::{BTreeMap, HashMap};
+
+/// Columns rows are matched on unless others are given: one row per
+/// annotation of a recording
+pub const DEFAULT_DIFF_KEY: [&str; 2] = ["meta_filename", "sig_uuid"];
+
+/// A value that differs between the two datasets
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueChange {
+    pub key: String,
+    pub column: String,
+    pub old: String,
+    pub new: String,
+}
+
+/// How one dataset became another
+#[derive(Debug, Clone, Default)]
+pub struct DatasetDiff {
+    /// Keys of rows only in the new dataset
+    pub added: Vec<String>,
+    /// Keys of rows only in the old dataset
+    pub removed: Vec<String>,
+    /// Changed values of rows in both, in new-dataset row order
+    pub changes: Vec<ValueChange>,
+    /// Rows in both with at least one changed value
+    pub changed_rows: usize,
+    pub unchanged_rows: usize,
+    pub added_columns: Vec<String>,
+    pub removed_columns: Vec<String>,
+}
+
+impl DatasetDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changes.is_empty()
+            && self.added_columns.is_empty()
+            && self.removed_columns.is_empty()
+    }
+
+    /// Number of changed values per column, most changed first
+    pub fn changes_by_column(&self) -> Vec<(String, usize)> {
+        let mut counts: BTreeMap<&str, usize> = BTreeMap::new();
+        for change in &self.changes {
+            *counts.entry(&change.column).or_default() += 1;
+        }
+        let mut counts: Vec<(String, usize)> = counts.into_iter().map(|(column, n)| (column.to_string(), n)).collect();
+        counts.sort_by_key(|(_, n)| std::cmp::Reverse(*n));
+        counts
+    }
+
+    /// One row per added row, removed row and changed value: `change`
+    /// (added, removed or changed), `key`, `column`, `old_value`, `new_value`
+    pub fn to_dataframe(&self) -> Result<DataFrame> {
+        let (mut kinds, mut keys, mut columns, mut olds, mut news) = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for key in &self.added {
+            kinds.push("added");
+            keys.push(key.as_str());
+            columns.push(None);
+            olds.push(None);
+            news.push(None);
+        }
+        for key in &self.removed {
+            kinds.push("removed");
+            keys.push(key.as_str());
+            columns.push(None);
+            olds.push(None);
+            news.push(None);
+        }
+        for change in &self.changes {
+            kinds.push("changed");
+            keys.push(change.key.as_str());
+            columns.push(Some(change.column.as_str()));
+            olds.push(Some(change.old.as_str()));
+            news.push(Some(change.new.as_str()));
+        }
+        Ok(df! {
+            "change" => kinds,
+            "key" => keys,
+            "column" => columns,
+            "old_value" => olds,
+            "new_value" => news,
+        }?)
+    }
+}
+
+/// Compare two datasets row by row, matching rows on `key` columns. Rows
+/// whose key repeats (e.g. files without annotations have an empty
+/// `sig_uuid`) are matched in order of appearance. Values of columns in
+/// both are compared as text, except that numbers compare as numbers, so
+/// an integer column read back as floats isn't a change. Null and empty
+/// text count as the same, as CSV round trips don't keep them apart.
+pub fn diff_datasets(old: &DataFrame, new: &DataFrame, key: &[String]) -> Result<DatasetDiff> {
+    if key.is_empty() {
+        anyhow::bail!("At least one key column is required");
+    }
+    let names = |df: &DataFrame| -> Vec<String> { df.get_column_names().iter().map(|name| name.to_string()).collect() };
+    let (old_columns, new_columns) = (names(old), names(new));
+    let mut diff = DatasetDiff {
+        added_columns: new_columns.iter().filter(|c| !old_columns.contains(c)).cloned().collect(),
+        removed_columns: old_columns.iter().filter(|c| !new_columns.contains(c)).cloned().collect(),
+        ..Default::default()
+    };
+    let compared: Vec<&String> = new_columns.iter().filter(|c| old_columns.contains(c) && !key.contains(c)).collect();
+
+    let old_keys = row_keys(old, key)?;
+    let new_keys = row_keys(new, key)?;
+    let mut old_rows: HashMap<&str, usize> = old_keys.iter().enumerate().map(|(idx, key)| (key.as_str(), idx)).collect();
+
+    let mut old_values = Vec::with_capacity(compared.len());
+    let mut new_values = Vec::with_capacity(compared.len());
+    for column in &compared {
+        let (old_series, new_series) = comparable(old.column(column)?, new.column(column)?)?;
+        old_values.push(old_series);
+        new_values.push(new_series);
+    }
+
+    for (new_idx, row_key) in new_keys.iter().enumerate() {
+        let Some(old_idx) = old_rows.remove(row_key.as_str()) else {
+            diff.added.push(row_key.clone());
+            continue;
+        };
+        let mut changed = false;
+        for (i, column) in compared.iter().enumerate() {
+            let old_value = old_values[i].str()?.get(old_idx).unwrap_or("");
+            let new_value = new_values[i].str()?.get(new_idx).unwrap_or("");
+            if old_value != new_value {
+                changed = true;
+                diff.changes.push(ValueChange {
+                    key: row_key.clone(),
+                    column: column.to_string(),
+                    old: old_value.to_string(),
+                    new: new_value.to_string(),
+                });
+            }
+        }
+        if changed {
+            diff.changed_rows += 1;
+        } else {
+            diff.unchanged_rows += 1;
+        }
+    }
+    let mut removed: Vec<usize> = old_rows.into_values().collect();
+    removed.sort_unstable();
+    diff.removed = removed.into_iter().map(|idx| old_keys[idx].clone()).collect();
+    Ok(diff)
+}
+
+/// A printable key per row, e.g. `rec1.sigmf-meta / 5f2c...`; repeats get
+/// ` #2`, ` #3`, ... so every key is unique
+fn row_keys(df: &DataFrame, key: &[String]) -> Result<Vec<String>> {
+    let columns = key
+        .iter()
+        .map(|column| Ok(df.column(column).map_err(|_| anyhow::anyhow!("No key column '{}'", column))?.cast(&DataType::String)?))
+        .collect::<Result<Vec<Series>>>()?;
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let mut keys = Vec::with_capacity(df.height());
+    for idx in 0..df.height() {
+        let parts = columns
+            .iter()
+            .map(|series| Ok(series.str()?.get(idx).unwrap_or("").to_string()))
+            .collect::<Result<Vec<_>>>()?;
+        let row_key = parts.join(" / ");
+        let count = seen.entry(row_key.clone()).or_default();
+        *count += 1;
+        keys.push(if *count == 1 { row_key } else { format!("{} #{}", row_key, count) });
+    }
+    Ok(keys)
+}
+
+/// Both sides as text, going through floats where both are numeric
+fn comparable(old: &Series, new: &Series) -> Result<(Series, Series)> {
+    let via = if old.dtype() != new.dtype() && old.dtype().is_numeric() && new.dtype().is_numeric() {
+        Some(DataType::Float64)
+    } else {
+        None
+    };
+    let as_text = |series: &Series| -> Result<Series> {
+        let series = match via {
+            Some(ref dtype) => series.cast(dtype)?,
+            None => series.clone(),
+        };
+        Ok(series.cast(&DataType::String)?)
+    };
+    Ok((as_text(old)?, as_text(new)?))
+}
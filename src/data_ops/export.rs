@@ -0,0 +1,250 @@
+use super::{format_frequency, geo_points};
+use anyhow::Result;
+use polars::prelude::*;
+use serde_json::{Map, Value};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+/// File formats a dataset can be written to and re-opened from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportFormat {
+    Csv,
+    Parquet,
+    /// One JSON object per row, for jq or an Elasticsearch bulk ingest
+    Ndjson,
+    /// A point feature per located row, for QGIS and other GIS tools
+    GeoJson,
+    /// A placemark per located row, for Google Earth
+    Kml,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 5] =
+        [ExportFormat::Csv, ExportFormat::Parquet, ExportFormat::Ndjson, ExportFormat::GeoJson, ExportFormat::Kml];
+
+    pub fn from_string(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "csv" => Ok(ExportFormat::Csv),
+            "parquet" | "pq" => Ok(ExportFormat::Parquet),
+            "ndjson" | "jsonl" | "json" => Ok(ExportFormat::Ndjson),
+            "geojson" => Ok(ExportFormat::GeoJson),
+            "kml" => Ok(ExportFormat::Kml),
+            _ => Err(anyhow::anyhow!("Unsupported export format: {}", s)),
+        }
+    }
+
+    /// Guess the format from a file extension
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        let extension = path.as_ref().extension()?.to_str()?;
+        Self::from_string(extension).ok()
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Parquet => "Parquet",
+            ExportFormat::Ndjson => "NDJSON",
+            ExportFormat::GeoJson => "GeoJSON",
+            ExportFormat::Kml => "KML",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Parquet => "parquet",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::GeoJson => "geojson",
+            ExportFormat::Kml => "kml",
+        }
+    }
+}
+
+/// Write a dataset to `path`. Parquet keeps column types, so casts and
+/// unsigned/boolean columns survive a round trip; CSV doesn't. GeoJSON and
+/// KML only have the rows with a location.
+pub fn write_dataset<P: AsRef<Path>>(df: &mut DataFrame, path: P, format: ExportFormat) -> Result<()> {
+    let mut file = File::create(path)?;
+    match format {
+        ExportFormat::Csv => CsvWriter::new(&mut file).finish(df)?,
+        ExportFormat::Parquet => {
+            ParquetWriter::new(&mut file).finish(df)?;
+        }
+        ExportFormat::Ndjson => write_ndjson(df, &mut file)?,
+        ExportFormat::GeoJson => write_geojson(df, &mut file)?,
+        ExportFormat::Kml => write_kml(df, &mut file)?,
+    }
+    Ok(())
+}
+
+// polars' JSON writer needs its `json` feature; the summary columns are all
+// scalars, so building the objects ourselves is simple enough
+fn write_ndjson<W: Write>(df: &DataFrame, writer: W) -> Result<()> {
+    let mut writer = BufWriter::new(writer);
+    let names: Vec<String> = df.get_column_names().iter().map(|name| name.to_string()).collect();
+    let columns = names.iter().map(|name| df.column(name)).collect::<PolarsResult<Vec<_>>>()?;
+    for row_idx in 0..df.height() {
+        let mut object = Map::new();
+        for (name, column) in names.iter().zip(&columns) {
+            object.insert(name.clone(), json_value(column.get(row_idx)?));
+        }
+        serde_json::to_writer(&mut writer, &object)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// A FeatureCollection of points, every column in each feature's properties
+fn write_geojson<W: Write>(df: &DataFrame, writer: W) -> Result<()> {
+    let names: Vec<String> = df.get_column_names().iter().map(|name| name.to_string()).collect();
+    let columns = names.iter().map(|name| df.column(name)).collect::<PolarsResult<Vec<_>>>()?;
+    let mut features = Vec::new();
+    for point in geo_points(df, None)?.points {
+        let mut properties = Map::new();
+        for (name, column) in names.iter().zip(&columns) {
+            properties.insert(name.clone(), json_value(column.get(point.row)?));
+        }
+        features.push(serde_json::json!({
+            "type": "Feature",
+            // GeoJSON order: longitude first
+            "geometry": {"type": "Point", "coordinates": [point.longitude, point.latitude]},
+            "properties": properties,
+        }));
+    }
+    let mut writer = BufWriter::new(writer);
+    serde_json::to_writer_pretty(&mut writer, &serde_json::json!({"type": "FeatureCollection", "features": features}))?;
+    writer.write_all(b"\n")?;
+    writer.flush()?;
+    Ok(())
+}
+
+/// Columns summarized in a KML placemark's description balloon, with how
+/// to show them
+const KML_DESCRIPTION_COLUMNS: [(&str, &str); 8] = [
+    ("capture_datetime", "Time"),
+    ("center_freq_hz", "Center frequency"),
+    ("sample_rate_hz", "Sample rate"),
+    ("duration_s", "Duration"),
+    ("band", "Band"),
+    ("sdr_handle", "Sensor"),
+    ("label", "Label"),
+    ("snr_db", "SNR"),
+];
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// A cell as text, empty for nulls
+fn cell_text(value: AnyValue) -> String {
+    match value {
+        AnyValue::Null => String::new(),
+        AnyValue::String(v) => v.to_string(),
+        AnyValue::StringOwned(v) => v.to_string(),
+        // Plain decimals rather than polars' 9.15e8
+        AnyValue::Float32(v) => v.to_string(),
+        AnyValue::Float64(v) => v.to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// One placemark per row, named after its meta file, with the key metadata
+/// in its description and every column as ExtendedData
+fn write_kml<W: Write>(df: &DataFrame, writer: W) -> Result<()> {
+    let names: Vec<String> = df.get_column_names().iter().map(|name| name.to_string()).collect();
+    let columns = names.iter().map(|name| df.column(name)).collect::<PolarsResult<Vec<_>>>()?;
+    let mut writer = BufWriter::new(writer);
+    writeln!(writer, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(writer, r#"<kml xmlns="http://www.opengis.net/kml/2.2">"#)?;
+    writeln!(writer, "<Document>")?;
+    for point in geo_points(df, None)?.points {
+        let name = match df.column("meta_filename") {
+            Ok(column) => cell_text(column.get(point.row)?),
+            Err(_) => format!("row {}", point.row + 1),
+        };
+        let mut description = Vec::new();
+        for (column_name, title) in KML_DESCRIPTION_COLUMNS {
+            let Ok(column) = df.column(column_name) else {
+                continue;
+            };
+            let value = match column.get(point.row)? {
+                // The parser writes 0 for a missing value
+                AnyValue::Float64(0.0) => String::new(),
+                AnyValue::Float64(hz) if column_name.ends_with("_hz") => format_frequency(hz),
+                AnyValue::Float64(s) if column_name == "duration_s" => format!("{} s", s),
+                AnyValue::Float64(db) if column_name == "snr_db" => format!("{:.1} dB", db),
+                other => cell_text(other),
+            };
+            if !value.is_empty() {
+                description.push(format!("{}: {}", title, value));
+            }
+        }
+        writeln!(writer, "  <Placemark>")?;
+        writeln!(writer, "    <name>{}</name>", xml_escape(&name))?;
+        writeln!(writer, "    <description>{}</description>", xml_escape(&description.join("\n")))?;
+        writeln!(writer, "    <ExtendedData>")?;
+        for (column_name, column) in names.iter().zip(&columns) {
+            writeln!(
+                writer,
+                r#"      <Data name="{}"><value>{}</value></Data>"#,
+                xml_escape(column_name),
+                xml_escape(&cell_text(column.get(point.row)?))
+            )?;
+        }
+        writeln!(writer, "    </ExtendedData>")?;
+        // KML order: longitude first
+        writeln!(writer, "    <Point><coordinates>{},{}</coordinates></Point>", point.longitude, point.latitude)?;
+        writeln!(writer, "  </Placemark>")?;
+    }
+    writeln!(writer, "</Document>")?;
+    writeln!(writer, "</kml>")?;
+    writer.flush()?;
+    Ok(())
+}
+
+fn json_value(value: AnyValue) -> Value {
+    match value {
+        AnyValue::Null => Value::Null,
+        AnyValue::Boolean(v) => Value::from(v),
+        AnyValue::String(v) => Value::from(v),
+        AnyValue::StringOwned(v) => Value::from(v.as_str()),
+        AnyValue::UInt8(v) => Value::from(v),
+        AnyValue::UInt16(v) => Value::from(v),
+        AnyValue::UInt32(v) => Value::from(v),
+        AnyValue::UInt64(v) => Value::from(v),
+        AnyValue::Int8(v) => Value::from(v),
+        AnyValue::Int16(v) => Value::from(v),
+        AnyValue::Int32(v) => Value::from(v),
+        AnyValue::Int64(v) => Value::from(v),
+        // NaN/inf aren't JSON; from_f64 gives None for them
+        AnyValue::Float32(v) => serde_json::Number::from_f64(v as f64).map_or(Value::Null, Value::Number),
+        AnyValue::Float64(v) => serde_json::Number::from_f64(v).map_or(Value::Null, Value::Number),
+        other => Value::from(other.to_string()),
+    }
+}
+
+/// Open a dataset file written by `write_dataset` without reading it yet.
+/// The format comes from the extension; anything unrecognized is read as CSV.
+/// NDJSON, GeoJSON and KML exports are for other tools and can't be re-opened.
+pub fn scan_dataset<P: AsRef<Path>>(path: P) -> Result<LazyFrame> {
+    let path = path.as_ref();
+    let lf = match ExportFormat::from_path(path) {
+        Some(ExportFormat::Parquet) => LazyFrame::scan_parquet(path, ScanArgsParquet::default())?,
+        Some(format @ (ExportFormat::Ndjson | ExportFormat::GeoJson | ExportFormat::Kml)) => {
+            anyhow::bail!("{} datasets can't be re-opened; export as CSV or Parquet instead", format.name())
+        }
+        // CSV keeps times as text; read the derived columns back typed
+        _ => {
+            let schema = Schema::from_iter([
+                Field::new("capture_time".into(), DataType::Datetime(TimeUnit::Microseconds, None)),
+                Field::new("capture_date".into(), DataType::Date),
+                // Channel names are often numbers, but not always
+                Field::new("channel".into(), DataType::String),
+            ]);
+            LazyCsvReader::new(path).with_dtype_overwrite(Some(Arc::new(schema))).finish()?
+        }
+    };
+    Ok(lf)
+}
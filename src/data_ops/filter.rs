@@ -0,0 +1,261 @@
+use super::datetime::parse_time_literal;
+use anyhow::Result;
+use chrono::{NaiveDateTime, TimeDelta};
+use polars::prelude::*;
+use std::fmt;
+
+/// One column filter parsed from text like `>= 10`, `100e6..200e6`,
+/// `!= QPSK`, `contains:wifi` or `regex:^cap_\d+`. A bare value means
+/// equality. Date and datetime columns take times instead of numbers, e.g.
+/// `2024-01-01..2024-02-01` or `>= 2024-01-01T12:00`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterExpr {
+    pub column: String,
+    pub op: FilterOp,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterOp {
+    Compare(CompareOp, FilterLiteral),
+    /// Inclusive numeric range
+    Range(f64, f64),
+    /// Inclusive time range; with `whole_end_day` the end is a bare date
+    /// and the range runs through the end of that day
+    TimeRange { start: NaiveDateTime, end: NaiveDateTime, whole_end_day: bool },
+    Contains(String),
+    Regex(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Eq,
+    Ne,
+    Gt,
+    Ge,
+    Lt,
+    Le,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterLiteral {
+    Number(f64),
+    Bool(bool),
+    Text(String),
+    Time(NaiveDateTime),
+}
+
+fn is_temporal(dtype: &DataType) -> bool {
+    matches!(dtype, DataType::Date | DataType::Datetime(..))
+}
+
+/// Microseconds since 1970, the unit temporal columns are compared in
+fn micros(dt: &NaiveDateTime) -> i64 {
+    dt.and_utc().timestamp_micros()
+}
+
+/// Midnight shown as just the date
+fn format_time(dt: &NaiveDateTime) -> String {
+    if dt.time() == chrono::NaiveTime::MIN {
+        dt.format("%Y-%m-%d").to_string()
+    } else {
+        dt.format("%Y-%m-%dT%H:%M:%S%.f").to_string()
+    }
+}
+
+fn parse_time(column: &str, text: &str) -> Result<(NaiveDateTime, bool)> {
+    parse_time_literal(unquote(text.trim()))
+        .ok_or_else(|| anyhow::anyhow!("{}: '{}' is not a date or time (e.g. 2024-01-31 or 2024-01-31T12:00)", column, text.trim()))
+}
+
+impl CompareOp {
+    fn symbol(self) -> &'static str {
+        match self {
+            CompareOp::Eq => "==",
+            CompareOp::Ne => "!=",
+            CompareOp::Gt => ">",
+            CompareOp::Ge => ">=",
+            CompareOp::Lt => "<",
+            CompareOp::Le => "<=",
+        }
+    }
+
+    fn apply(self, left: Expr, right: Expr) -> Expr {
+        match self {
+            CompareOp::Eq => left.eq(right),
+            CompareOp::Ne => left.neq(right),
+            CompareOp::Gt => left.gt(right),
+            CompareOp::Ge => left.gt_eq(right),
+            CompareOp::Lt => left.lt(right),
+            CompareOp::Le => left.lt_eq(right),
+        }
+    }
+}
+
+impl FilterLiteral {
+    /// Interpret `text` according to the column type: numbers for numeric
+    /// columns, true/false for booleans, dates or times for temporal columns,
+    /// (optionally quoted) text otherwise
+    fn parse(column: &str, dtype: &DataType, text: &str) -> Result<Self> {
+        let text = text.trim();
+        if is_temporal(dtype) {
+            parse_time(column, text).map(|(dt, _)| FilterLiteral::Time(dt))
+        } else if dtype.is_numeric() {
+            parse_number(column, text).map(FilterLiteral::Number)
+        } else if matches!(dtype, DataType::Boolean) {
+            match text.to_lowercase().as_str() {
+                "true" | "yes" | "1" => Ok(FilterLiteral::Bool(true)),
+                "false" | "no" | "0" => Ok(FilterLiteral::Bool(false)),
+                _ => anyhow::bail!("{}: expected true or false, got '{}'", column, text),
+            }
+        } else {
+            Ok(FilterLiteral::Text(unquote(text).to_string()))
+        }
+    }
+}
+
+fn parse_number(column: &str, text: &str) -> Result<f64> {
+    text.parse::<f64>()
+        .map_err(|_| anyhow::anyhow!("{}: '{}' is not a number", column, text))
+}
+
+fn unquote(text: &str) -> &str {
+    for quote in ['\'', '"'] {
+        if let Some(inner) = text.strip_prefix(quote).and_then(|t| t.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    text
+}
+
+impl FilterExpr {
+    /// Parse a filter for `column`. Returns `None` for blank text (no filter).
+    pub fn parse(column: &str, dtype: &DataType, text: &str) -> Result<Option<Self>> {
+        let text = text.trim();
+        if text.is_empty() {
+            return Ok(None);
+        }
+        let op = if let Some(needle) = text.strip_prefix("contains:") {
+            FilterOp::Contains(unquote(needle.trim()).to_string())
+        } else if let Some(pattern) = text.strip_prefix("regex:") {
+            let pattern = unquote(pattern.trim());
+            // Check the pattern now rather than failing when the query runs
+            StringChunked::from_slice("".into(), &[""])
+                .contains(pattern, true)
+                .map_err(|e| anyhow::anyhow!("{}: invalid regex: {}", column, e))?;
+            FilterOp::Regex(pattern.to_string())
+        } else if let Some((op, rest)) = split_operator(text) {
+            FilterOp::Compare(op, FilterLiteral::parse(column, dtype, rest)?)
+        } else if is_temporal(dtype) {
+            let (start, end) = text.split_once("..").unwrap_or((text, text));
+            let (start, _) = parse_time(column, start)?;
+            let (end, whole_end_day) = parse_time(column, end)?;
+            if start > end {
+                anyhow::bail!("{}: empty range {}..{}", column, format_time(&start), format_time(&end));
+            }
+            FilterOp::TimeRange { start, end, whole_end_day }
+        } else if let Some((low, high)) = text
+            .split_once("..")
+            // `a..b` in a text column is just text
+            .filter(|_| !matches!(dtype, DataType::String))
+        {
+            let low = parse_number(column, low.trim())?;
+            let high = parse_number(column, high.trim())?;
+            if low > high {
+                anyhow::bail!("{}: empty range {}..{}", column, low, high);
+            }
+            FilterOp::Range(low, high)
+        } else {
+            FilterOp::Compare(CompareOp::Eq, FilterLiteral::parse(column, dtype, text)?)
+        };
+        Ok(Some(FilterExpr {
+            column: column.to_string(),
+            op,
+        }))
+    }
+
+    pub fn to_expr(&self) -> Expr {
+        let column = col(&self.column);
+        match &self.op {
+            FilterOp::Compare(op, FilterLiteral::Number(n)) => op.apply(column.cast(DataType::Float64), lit(*n)),
+            FilterOp::Compare(op, FilterLiteral::Bool(b)) => op.apply(column, lit(*b)),
+            FilterOp::Compare(op, FilterLiteral::Text(s)) => op.apply(column.cast(DataType::String), lit(s.clone())),
+            FilterOp::Compare(op, FilterLiteral::Time(dt)) => op.apply(time_micros(column), lit(micros(dt))),
+            FilterOp::TimeRange { start, end, whole_end_day } => {
+                let column = time_micros(column);
+                let after_start = column.clone().gt_eq(lit(micros(start)));
+                if *whole_end_day {
+                    after_start.and(column.lt(lit(micros(&(*end + TimeDelta::days(1))))))
+                } else {
+                    after_start.and(column.lt_eq(lit(micros(end))))
+                }
+            }
+            FilterOp::Range(low, high) => {
+                let column = column.cast(DataType::Float64);
+                column.clone().gt_eq(lit(*low)).and(column.lt_eq(lit(*high)))
+            }
+            FilterOp::Contains(needle) => column
+                .cast(DataType::String)
+                .str()
+                .contains_literal(lit(needle.clone())),
+            FilterOp::Regex(pattern) => column
+                .cast(DataType::String)
+                .str()
+                .contains(lit(pattern.clone()), true),
+        }
+    }
+}
+
+/// A Date or Datetime column as microseconds since 1970
+fn time_micros(column: Expr) -> Expr {
+    column.cast(DataType::Datetime(TimeUnit::Microseconds, None)).cast(DataType::Int64)
+}
+
+/// Split a leading comparison operator off `text`
+fn split_operator(text: &str) -> Option<(CompareOp, &str)> {
+    // Two-character operators first so `>=` isn't read as `>`
+    const OPERATORS: [(&str, CompareOp); 7] = [
+        (">=", CompareOp::Ge),
+        ("<=", CompareOp::Le),
+        ("!=", CompareOp::Ne),
+        ("==", CompareOp::Eq),
+        (">", CompareOp::Gt),
+        ("<", CompareOp::Lt),
+        ("=", CompareOp::Eq),
+    ];
+    OPERATORS
+        .iter()
+        .find_map(|(symbol, op)| text.strip_prefix(symbol).map(|rest| (*op, rest)))
+}
+
+impl fmt::Display for FilterExpr {
+    /// Same form as the provenance filter description, e.g. `snr_db >= 10`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.op {
+            FilterOp::Compare(op, FilterLiteral::Number(n)) => write!(f, "{} {} {}", self.column, op.symbol(), n),
+            FilterOp::Compare(op, FilterLiteral::Bool(b)) => write!(f, "{} {} {}", self.column, op.symbol(), b),
+            FilterOp::Compare(op, FilterLiteral::Text(s)) => write!(f, "{} {} '{}'", self.column, op.symbol(), s),
+            FilterOp::Compare(op, FilterLiteral::Time(dt)) => write!(f, "{} {} {}", self.column, op.symbol(), format_time(dt)),
+            FilterOp::TimeRange { start, end, .. } if start == end => write!(f, "{} on {}", self.column, format_time(start)),
+            FilterOp::TimeRange { start, end, .. } => write!(f, "{} in {}..{}", self.column, format_time(start), format_time(end)),
+            FilterOp::Range(low, high) => write!(f, "{} in {}..{}", self.column, low, high),
+            FilterOp::Contains(needle) => write!(f, "{} contains '{}'", self.column, needle),
+            FilterOp::Regex(pattern) => write!(f, "{} matches /{}/", self.column, pattern),
+        }
+    }
+}
+
+/// Parse `(column, filter text)` pairs against the column types of `df`.
+/// Blank filters are skipped.
+pub fn parse_filters<'a, I>(df: &DataFrame, filters: I) -> Result<Vec<FilterExpr>>
+where
+    I: IntoIterator<Item = (&'a str, &'a str)>,
+{
+    let mut parsed = Vec::new();
+    for (column, text) in filters {
+        let dtype = df.column(column)?.dtype().clone();
+        if let Some(filter) = FilterExpr::parse(column, &dtype, text)? {
+            parsed.push(filter);
+        }
+    }
+    Ok(parsed)
+}
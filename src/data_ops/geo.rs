@@ -0,0 +1,208 @@
+use anyhow::Result;
+use polars::prelude::*;
+use std::fmt;
+
+/// Mean Earth radius, for great-circle distances
+const EARTH_RADIUS_KM: f64 = 6371.0;
+
+/// One located row of the dataset
+#[derive(Debug, Clone, PartialEq)]
+pub struct GeoPoint {
+    /// Row index in the DataFrame the points were taken from
+    pub row: usize,
+    pub latitude: f64,
+    pub longitude: f64,
+    /// Value of the color-by column, if one was given and the row has it
+    pub value: Option<f64>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct GeoPoints {
+    pub points: Vec<GeoPoint>,
+    /// Rows without a usable location
+    pub skipped_rows: usize,
+}
+
+impl GeoPoints {
+    /// (min latitude, max latitude, min longitude, max longitude)
+    pub fn bounds(&self) -> Option<(f64, f64, f64, f64)> {
+        let first = self.points.first()?;
+        Some(self.points.iter().fold(
+            (first.latitude, first.latitude, first.longitude, first.longitude),
+            |(lat_min, lat_max, lon_min, lon_max), p| {
+                (lat_min.min(p.latitude), lat_max.max(p.latitude), lon_min.min(p.longitude), lon_max.max(p.longitude))
+            },
+        ))
+    }
+
+    /// Smallest and largest color-by value
+    pub fn value_range(&self) -> Option<(f64, f64)> {
+        let mut values = self.points.iter().filter_map(|p| p.value);
+        let first = values.next()?;
+        Some(values.fold((first, first), |(min, max), v| (min.min(v), max.max(v))))
+    }
+}
+
+/// A latitude/longitude pair that can be plotted. (0, 0) is what the parser
+/// writes when `core:geolocation` is absent, so it counts as no location.
+fn is_located(latitude: f64, longitude: f64) -> bool {
+    latitude.is_finite()
+        && longitude.is_finite()
+        && (-90.0..=90.0).contains(&latitude)
+        && (-180.0..=180.0).contains(&longitude)
+        && !(latitude == 0.0 && longitude == 0.0)
+}
+
+/// Rows with a location from the `latitude`/`longitude` columns, with the
+/// numeric value of `color_by` for each (booleans count as 0/1)
+pub fn geo_points(df: &DataFrame, color_by: Option<&str>) -> Result<GeoPoints> {
+    let latitudes = df.column("latitude")?.cast(&DataType::Float64)?;
+    let longitudes = df.column("longitude")?.cast(&DataType::Float64)?;
+    let (latitudes, longitudes) = (latitudes.f64()?, longitudes.f64()?);
+    let values = match color_by {
+        Some(column) => Some(df.column(column)?.cast(&DataType::Float64)?),
+        None => None,
+    };
+    let values = values.as_ref().map(|v| v.f64()).transpose()?;
+
+    let mut geo = GeoPoints::default();
+    for row in 0..df.height() {
+        match (latitudes.get(row), longitudes.get(row)) {
+            (Some(latitude), Some(longitude)) if is_located(latitude, longitude) => geo.points.push(GeoPoint {
+                row,
+                latitude,
+                longitude,
+                value: values.and_then(|v| v.get(row)).filter(|v| v.is_finite()),
+            }),
+            _ => geo.skipped_rows += 1,
+        }
+    }
+    Ok(geo)
+}
+
+/// Great-circle distance between two points, in km
+pub fn distance_km(lat1: f64, lon1: f64, lat2: f64, lon2: f64) -> f64 {
+    let (lat1, lat2) = (lat1.to_radians(), lat2.to_radians());
+    let dlat = lat2 - lat1;
+    let dlon = (lon2 - lon1).to_radians();
+    let a = (dlat / 2.0).sin().powi(2) + lat1.cos() * lat2.cos() * (dlon / 2.0).sin().powi(2);
+    2.0 * EARTH_RADIUS_KM * a.sqrt().min(1.0).asin()
+}
+
+/// An area rows must be recorded in
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GeoFilter {
+    /// Within `radius_km` of a point
+    Within { latitude: f64, longitude: f64, radius_km: f64 },
+    /// Inside a latitude/longitude box. A box with `lon_min` east of
+    /// `lon_max` crosses the antimeridian.
+    BoundingBox { lat_min: f64, lon_min: f64, lat_max: f64, lon_max: f64 },
+}
+
+/// Comma-separated numbers, exactly `count` of them
+fn parse_numbers(text: &str, count: usize, usage: &str) -> Result<Vec<f64>> {
+    let numbers = text
+        .split(',')
+        .map(|part| part.trim().parse::<f64>())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .map_err(|_| anyhow::anyhow!("Expected {}, got {:?}", usage, text))?;
+    if numbers.len() != count || numbers.iter().any(|n| !n.is_finite()) {
+        anyhow::bail!("Expected {}, got {:?}", usage, text);
+    }
+    Ok(numbers)
+}
+
+fn check_latitude(latitude: f64) -> Result<()> {
+    if !(-90.0..=90.0).contains(&latitude) {
+        anyhow::bail!("Latitude {} is outside -90..90", latitude);
+    }
+    Ok(())
+}
+
+fn check_longitude(longitude: f64) -> Result<()> {
+    if !(-180.0..=180.0).contains(&longitude) {
+        anyhow::bail!("Longitude {} is outside -180..180", longitude);
+    }
+    Ok(())
+}
+
+impl GeoFilter {
+    /// `lat,lon,radius_km`, as given to `--within`
+    pub fn parse_within(text: &str) -> Result<Self> {
+        let numbers = parse_numbers(text, 3, "lat,lon,radius_km")?;
+        let (latitude, longitude, radius_km) = (numbers[0], numbers[1], numbers[2]);
+        check_latitude(latitude)?;
+        check_longitude(longitude)?;
+        if radius_km <= 0.0 {
+            anyhow::bail!("Radius must be positive, got {} km", radius_km);
+        }
+        Ok(GeoFilter::Within { latitude, longitude, radius_km })
+    }
+
+    /// `lat_min,lon_min,lat_max,lon_max`, as given to `--bbox`
+    pub fn parse_bbox(text: &str) -> Result<Self> {
+        let numbers = parse_numbers(text, 4, "lat_min,lon_min,lat_max,lon_max")?;
+        let (lat_min, lon_min, lat_max, lon_max) = (numbers[0], numbers[1], numbers[2], numbers[3]);
+        for latitude in [lat_min, lat_max] {
+            check_latitude(latitude)?;
+        }
+        for longitude in [lon_min, lon_max] {
+            check_longitude(longitude)?;
+        }
+        if lat_min > lat_max {
+            anyhow::bail!("Box runs from latitude {} down to {}; give the southern edge first", lat_min, lat_max);
+        }
+        Ok(GeoFilter::BoundingBox { lat_min, lon_min, lat_max, lon_max })
+    }
+
+    pub fn contains(&self, latitude: f64, longitude: f64) -> bool {
+        match *self {
+            GeoFilter::Within { latitude: lat, longitude: lon, radius_km } => {
+                distance_km(lat, lon, latitude, longitude) <= radius_km
+            }
+            GeoFilter::BoundingBox { lat_min, lon_min, lat_max, lon_max } => {
+                let in_lon = if lon_min <= lon_max {
+                    (lon_min..=lon_max).contains(&longitude)
+                } else {
+                    longitude >= lon_min || longitude <= lon_max
+                };
+                (lat_min..=lat_max).contains(&latitude) && in_lon
+            }
+        }
+    }
+}
+
+impl fmt::Display for GeoFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GeoFilter::Within { latitude, longitude, radius_km } => {
+                write!(f, "within {} km of {}, {}", radius_km, latitude, longitude)
+            }
+            GeoFilter::BoundingBox { lat_min, lon_min, lat_max, lon_max } => {
+                write!(f, "inside box {}, {} .. {}, {}", lat_min, lon_min, lat_max, lon_max)
+            }
+        }
+    }
+}
+
+/// Rows whose location passes every filter. Rows without a location never
+/// do.
+pub fn filter_geo(df: &DataFrame, filters: &[GeoFilter]) -> Result<DataFrame> {
+    if filters.is_empty() {
+        return Ok(df.clone());
+    }
+    let latitudes = df.column("latitude")?.cast(&DataType::Float64)?;
+    let longitudes = df.column("longitude")?.cast(&DataType::Float64)?;
+    let mask: BooleanChunked = latitudes
+        .f64()?
+        .into_iter()
+        .zip(longitudes.f64()?)
+        .map(|location| match location {
+            (Some(latitude), Some(longitude)) if is_located(latitude, longitude) => {
+                filters.iter().all(|filter| filter.contains(latitude, longitude))
+            }
+            _ => false,
+        })
+        .collect();
+    Ok(df.filter(&mask)?)
+}
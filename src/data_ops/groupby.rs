@@ -0,0 +1,171 @@
+use super::activity::parse_capture_datetime;
+use anyhow::Result;
+use polars::prelude::*;
+
+/// What to group rows by
+#[derive(Debug, Clone, PartialEq)]
+pub enum GroupKey {
+    Column(String),
+    /// Numeric column floored to multiples of `width`, e.g. 100 MHz bands
+    Binned { column: String, width: f64 },
+    /// Calendar day (YYYY-MM-DD) of a SigMF datetime column
+    Day(String),
+    /// Hour of day (0-23) of a SigMF datetime column
+    Hour(String),
+}
+
+impl GroupKey {
+    /// Parse `column`, `day:column`, `hour:column` or `bin:column:width`
+    pub fn parse(spec: &str) -> Result<Self> {
+        let parts: Vec<&str> = spec.trim().split(':').collect();
+        match parts.as_slice() {
+            [column] => Ok(GroupKey::Column(column.to_string())),
+            ["day", column] => Ok(GroupKey::Day(column.to_string())),
+            ["hour", column] => Ok(GroupKey::Hour(column.to_string())),
+            ["bin", column, width] => {
+                let width: f64 = width.parse()
+                    .map_err(|_| anyhow::anyhow!("Invalid bin width: {}", width))?;
+                if width <= 0.0 {
+                    anyhow::bail!("Bin width must be positive");
+                }
+                Ok(GroupKey::Binned { column: column.to_string(), width })
+            }
+            _ => anyhow::bail!("Invalid group key '{}', expected column, day:column, hour:column or bin:column:width", spec),
+        }
+    }
+
+    /// Name of the key column in the grouped output
+    pub fn output_name(&self) -> String {
+        match self {
+            GroupKey::Column(column) => column.clone(),
+            GroupKey::Binned { column, .. } => format!("{}_bin", column),
+            GroupKey::Day(column) => format!("{}_day", column),
+            GroupKey::Hour(column) => format!("{}_hour", column),
+        }
+    }
+
+    /// Add the key column to `df` (derived keys are computed here)
+    pub(crate) fn attach(&self, df: &DataFrame) -> Result<DataFrame> {
+        let name = self.output_name();
+        let key = match self {
+            GroupKey::Column(column) => {
+                df.column(column)?;
+                return Ok(df.clone());
+            }
+            GroupKey::Binned { column, width } => {
+                let values = df.column(column)?.cast(&DataType::Float64)?;
+                let binned: Float64Chunked = values.f64()?
+                    .into_iter()
+                    .map(|v| v.map(|v| (v / width).floor() * width))
+                    .collect();
+                binned.into_series()
+            }
+            GroupKey::Day(column) => {
+                let days: StringChunked = df.column(column)?.str()?
+                    .into_iter()
+                    .map(|v| v.and_then(parse_capture_datetime).map(|dt| dt.date().to_string()))
+                    .collect();
+                days.into_series()
+            }
+            GroupKey::Hour(column) => {
+                use chrono::Timelike;
+                let hours: UInt32Chunked = df.column(column)?.str()?
+                    .into_iter()
+                    .map(|v| v.and_then(parse_capture_datetime).map(|dt| dt.hour()))
+                    .collect();
+                hours.into_series()
+            }
+        };
+        let mut df = df.clone();
+        df.with_column(key.with_name(name.as_str().into()))?;
+        Ok(df)
+    }
+}
+
+/// An aggregation applied to each group
+#[derive(Debug, Clone, PartialEq)]
+pub enum Aggregation {
+    Count,
+    Mean(String),
+    Median(String),
+    Sum(String),
+    Min(String),
+    Max(String),
+    Std(String),
+    NUnique(String),
+}
+
+impl Aggregation {
+    /// Parse `count` or `<func>:<column>` (mean, median, sum, min, max, std, n_unique)
+    pub fn parse(spec: &str) -> Result<Self> {
+        let spec = spec.trim();
+        if spec == "count" {
+            return Ok(Aggregation::Count);
+        }
+        let (func, column) = spec
+            .split_once(':')
+            .ok_or_else(|| anyhow::anyhow!("Invalid aggregation '{}', expected count or func:column", spec))?;
+        let column = column.to_string();
+        match func {
+            "mean" => Ok(Aggregation::Mean(column)),
+            "median" => Ok(Aggregation::Median(column)),
+            "sum" => Ok(Aggregation::Sum(column)),
+            "min" => Ok(Aggregation::Min(column)),
+            "max" => Ok(Aggregation::Max(column)),
+            "std" => Ok(Aggregation::Std(column)),
+            "n_unique" => Ok(Aggregation::NUnique(column)),
+            _ => anyhow::bail!("Unknown aggregation function: {}", func),
+        }
+    }
+
+    /// Parse a comma separated list, e.g. `mean:snr_db,count`
+    pub fn parse_list(spec: &str) -> Result<Vec<Self>> {
+        spec.split(',')
+            .filter(|part| !part.trim().is_empty())
+            .map(Self::parse)
+            .collect()
+    }
+
+    pub fn output_name(&self) -> String {
+        match self {
+            Aggregation::Count => "count".to_string(),
+            Aggregation::Mean(c) => format!("mean_{}", c),
+            Aggregation::Median(c) => format!("median_{}", c),
+            Aggregation::Sum(c) => format!("sum_{}", c),
+            Aggregation::Min(c) => format!("min_{}", c),
+            Aggregation::Max(c) => format!("max_{}", c),
+            Aggregation::Std(c) => format!("std_{}", c),
+            Aggregation::NUnique(c) => format!("n_unique_{}", c),
+        }
+    }
+
+    fn expr(&self) -> Expr {
+        let expr = match self {
+            Aggregation::Count => len(),
+            Aggregation::Mean(c) => col(c).mean(),
+            Aggregation::Median(c) => col(c).median(),
+            Aggregation::Sum(c) => col(c).sum(),
+            Aggregation::Min(c) => col(c).min(),
+            Aggregation::Max(c) => col(c).max(),
+            Aggregation::Std(c) => col(c).std(1),
+            Aggregation::NUnique(c) => col(c).n_unique(),
+        };
+        expr.alias(&self.output_name())
+    }
+}
+
+/// Group `df` by `key` and compute `aggregations`, sorted by the key
+pub fn group_by(df: &DataFrame, key: &GroupKey, aggregations: &[Aggregation]) -> Result<DataFrame> {
+    if aggregations.is_empty() {
+        anyhow::bail!("At least one aggregation is required");
+    }
+    let key_name = key.output_name();
+    let exprs: Vec<Expr> = aggregations.iter().map(Aggregation::expr).collect();
+    let grouped = key.attach(df)?
+        .lazy()
+        .group_by([col(&key_name)])
+        .agg(exprs)
+        .sort([key_name.as_str()], SortMultipleOptions::default())
+        .collect()?;
+    Ok(grouped)
+}
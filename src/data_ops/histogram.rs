@@ -0,0 +1,66 @@
+use anyhow::Result;
+use polars::prelude::*;
+
+/// Counts of a numeric column's values in equal-width bins
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// Left edge of the first bin
+    pub min: f64,
+    /// Right edge of the last bin, which includes it
+    pub max: f64,
+    pub counts: Vec<u64>,
+}
+
+impl Histogram {
+    pub fn bin_width(&self) -> f64 {
+        (self.max - self.min) / self.counts.len() as f64
+    }
+
+    /// Left and right edges of bin `idx`
+    pub fn bin_edges(&self, idx: usize) -> (f64, f64) {
+        let width = self.bin_width();
+        (self.min + idx as f64 * width, self.min + (idx + 1) as f64 * width)
+    }
+
+    /// Values that landed in a bin
+    pub fn total(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+
+    /// Height of a bar of `count` relative to the tallest, `0.0..=1.0`. On a
+    /// log scale a bin of 1 still shows while a few bins dwarf the rest.
+    pub fn bar_fraction(&self, count: u64, log_scale: bool) -> f64 {
+        let max = self.counts.iter().copied().max().unwrap_or(0);
+        if max == 0 {
+            0.0
+        } else if log_scale {
+            (count as f64).ln_1p() / (max as f64).ln_1p()
+        } else {
+            count as f64 / max as f64
+        }
+    }
+}
+
+/// `bins` equal-width bins over the finite values of `series`. None if it
+/// isn't numeric or has no finite values; a column of one value gets one bin.
+pub fn histogram(series: &Series, bins: usize) -> Result<Option<Histogram>> {
+    if !series.dtype().is_numeric() {
+        return Ok(None);
+    }
+    if bins == 0 {
+        anyhow::bail!("A histogram needs at least one bin");
+    }
+    let values: Vec<f64> = series.cast(&DataType::Float64)?.f64()?.into_iter().flatten().filter(|v| v.is_finite()).collect();
+    let Some(min) = values.iter().copied().reduce(f64::min) else {
+        return Ok(None);
+    };
+    let max = values.iter().copied().fold(min, f64::max);
+    let bins = if max > min { bins } else { 1 };
+    let mut counts = vec![0u64; bins];
+    let width = (max - min) / bins as f64;
+    for value in values {
+        let bin = if width > 0.0 { ((value - min) / width) as usize } else { 0 };
+        counts[bin.min(bins - 1)] += 1;
+    }
+    Ok(Some(Histogram { min, max, counts }))
+}
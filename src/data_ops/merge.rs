@@ -0,0 +1,64 @@
+//! Stacking row sets whose schemas differ, e.g. datasets from different
+//! parser versions, or recordings with different `ann_*` columns.
+
+use anyhow::Result;
+use polars::prelude::*;
+
+/// Stack `parts` into one DataFrame with the union of their columns, in
+/// first-seen order. A column a part doesn't have is null there. Where the
+/// parts disagree on a column's type, integers and floats meet as floats,
+/// a null-only column takes the other type, and anything else becomes text.
+/// `None` if there are no parts.
+pub fn merge_rows(parts: Vec<DataFrame>) -> Result<Option<DataFrame>> {
+    let mut columns: Vec<(String, DataType)> = Vec::new();
+    for part in &parts {
+        for series in part.get_columns() {
+            match columns.iter_mut().find(|(name, _)| name.as_str() == series.name()) {
+                Some((_, dtype)) => *dtype = unify_types(dtype, series.dtype()),
+                None => columns.push((series.name().to_string(), series.dtype().clone())),
+            }
+        }
+    }
+    let mut merged: Option<DataFrame> = None;
+    for part in parts {
+        let aligned = columns
+            .iter()
+            .map(|(name, dtype)| match part.column(name) {
+                Ok(series) => series.cast(dtype),
+                Err(_) => Ok(Series::full_null(name.as_str().into(), part.height(), dtype)),
+            })
+            .collect::<PolarsResult<Vec<_>>>()?;
+        let aligned = DataFrame::new(aligned)?;
+        match merged {
+            Some(ref mut merged) => {
+                merged.vstack_mut(&aligned)?;
+            }
+            None => merged = Some(aligned),
+        }
+    }
+    // One chunk per part otherwise, which slows every later query
+    Ok(merged.map(|mut merged| {
+        merged.align_chunks();
+        merged
+    }))
+}
+
+/// Stack `rows` under `dataset`, as [`merge_rows`] does
+pub fn append_rows(dataset: DataFrame, rows: DataFrame) -> Result<DataFrame> {
+    Ok(merge_rows(vec![dataset, rows])?.unwrap_or_default())
+}
+
+/// The type a column gets when parts have it as `a` and `b`. Unsigned
+/// integers stay unsigned; mixed with signed ones they go to Int64 unless
+/// one is a UInt64, which Int64 can't hold, so Float64.
+fn unify_types(a: &DataType, b: &DataType) -> DataType {
+    match (a, b) {
+        _ if a == b => a.clone(),
+        (DataType::Null, other) | (other, DataType::Null) => other.clone(),
+        _ if a.is_unsigned_integer() && b.is_unsigned_integer() => DataType::UInt64,
+        (DataType::UInt64, _) | (_, DataType::UInt64) if a.is_integer() && b.is_integer() => DataType::Float64,
+        _ if a.is_integer() && b.is_integer() => DataType::Int64,
+        _ if a.is_numeric() && b.is_numeric() => DataType::Float64,
+        _ => DataType::String,
+    }
+}
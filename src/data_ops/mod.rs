@@ -0,0 +1,59 @@
+pub mod activity;
+pub mod bands;
+pub mod cast;
+pub mod cluster;
+pub mod column_stats;
+pub mod columns;
+pub mod continuity;
+pub mod datetime;
+pub mod derived;
+pub mod describe;
+pub mod diff;
+pub mod export;
+pub mod filter;
+pub mod geo;
+pub mod groupby;
+pub mod histogram;
+pub mod merge;
+pub mod multi_sensor;
+pub mod noise_trend;
+pub mod occupancy;
+pub mod presets;
+pub mod profiles;
+pub mod provenance;
+pub mod schema;
+pub mod similar;
+pub mod split;
+pub mod units;
+pub mod watch;
+pub mod watchlist;
+
+pub use activity::{activity_heatmap, ActivityHeatmap, TimeBucket};
+pub use bands::{add_band_columns, band_map, set_band_map, Band, BandMap, Channel, BAND_COLUMN, CHANNEL_COLUMN};
+pub use cast::{cast_columns, parse_cast_spec, CastTarget};
+pub use cluster::{cluster, default_cluster_columns, with_cluster_column, ClusterMethod, ClusterOptions, Clustering, CLUSTER_COLUMN, DBSCAN_NOISE};
+pub use column_stats::{column_stats, ColumnStats, NumericSummary, COLUMN_STATS_BINS};
+pub use columns::empty_columns;
+pub use continuity::{continuity_report, CollectedRecording, ContinuityIssue, ContinuityIssueKind, ContinuityReport, SensorContinuity, DEFAULT_CONTINUITY_TOLERANCE_S};
+pub use datetime::{add_time_columns, parse_time_literal, TIME_COLUMNS};
+pub use derived::{apply_derived_columns, DerivedColumn, DerivedColumns};
+pub use describe::{describe, Metric, DEFAULT_METRICS};
+pub use diff::{diff_datasets, DatasetDiff, ValueChange, DEFAULT_DIFF_KEY};
+pub use export::{scan_dataset, write_dataset, ExportFormat};
+pub use filter::{parse_filters, FilterExpr};
+pub use geo::{distance_km, filter_geo, geo_points, GeoFilter, GeoPoint, GeoPoints};
+pub use groupby::{group_by, Aggregation, GroupKey};
+pub use histogram::{histogram, Histogram};
+pub use merge::{append_rows, merge_rows};
+pub use multi_sensor::{find_simultaneous, SyncedRecording};
+pub use noise_trend::{noise_floor_trend, NoiseTrend, SensorNoiseTrend, DEFAULT_NOISE_STEP_DB};
+pub use occupancy::{format_frequency, occupancy, Occupancy};
+pub use presets::{find_preset, gui_config_path, load_saved_presets, FilterPreset};
+pub use profiles::{apply_sensor_profiles, find_profile, load_sensor_profiles, SensorProfile, SENSOR_PROFILE_COLUMN};
+pub use provenance::{Provenance, ROW_PER_ML_ANNOTATION};
+pub use schema::{migrate_dataset, SchemaMigration, SUMMARY_SCHEMA_VERSION};
+pub use similar::{default_similarity_columns, find_similar, SimilarOptions, SimilarRow};
+pub use split::{dominant_class, split_dataset, with_split_column, DatasetSplit, SplitRatios, DOMINANT_CLASS, SPLIT_COLUMN, SPLIT_NAMES};
+pub use units::{format_bytes, format_duration, humanize_value};
+pub use watch::{update_dataset, DirectoryWatcher, WatchUpdate, WATCH_SETTLE};
+pub use watchlist::{filter_watchlists, watch_matches, WatchEntry, Watchlist, WATCH_MATCH_COLUMN};
@@ -0,0 +1,90 @@
+use super::activity::parse_capture_datetime;
+use anyhow::Result;
+use chrono::{Duration, NaiveDateTime};
+use polars::prelude::*;
+use std::collections::HashSet;
+
+/// One recording of an event, placed in absolute time and frequency
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyncedRecording {
+    pub meta_filename: String,
+    pub sdr_handle: String,
+    pub start: NaiveDateTime,
+    pub duration_s: f64,
+    pub center_freq_hz: f64,
+    pub sample_rate_hz: f64,
+}
+
+impl SyncedRecording {
+    pub fn end(&self) -> NaiveDateTime {
+        self.start + Duration::nanoseconds((self.duration_s * 1e9) as i64)
+    }
+
+    /// Captured band, `center ± rate/2`
+    pub fn band(&self) -> (f64, f64) {
+        (self.center_freq_hz - self.sample_rate_hz / 2.0, self.center_freq_hz + self.sample_rate_hz / 2.0)
+    }
+
+    /// Seconds from `reference` to this recording's start
+    pub fn offset_s(&self, reference: NaiveDateTime) -> f64 {
+        (self.start - reference).num_nanoseconds().unwrap_or(0) as f64 / 1e9
+    }
+
+    fn overlaps(&self, other: &SyncedRecording) -> bool {
+        let ((lo, hi), (other_lo, other_hi)) = (self.band(), other.band());
+        self.start < other.end() && other.start < self.end() && lo < other_hi && other_lo < hi
+    }
+}
+
+/// Recordings of the same event as `meta_filename`: it, then every recording
+/// from another sensor that overlaps it in both time (`capture_datetime` plus
+/// `duration_s`) and frequency. Recordings without an `sdr_handle` count as
+/// other sensors; ones without a parseable datetime are left out. Sorted by
+/// sensor, then start.
+pub fn find_simultaneous(df: &DataFrame, meta_filename: &str) -> Result<Vec<SyncedRecording>> {
+    let filenames = df.column("meta_filename")?.str()?;
+    let handles = df.column("sdr_handle")?.str()?;
+    let datetimes = df.column("capture_datetime")?.str()?;
+    let durations = df.column("duration_s")?.cast(&DataType::Float64)?;
+    let durations = durations.f64()?;
+    let center_freqs = df.column("center_freq_hz")?.cast(&DataType::Float64)?;
+    let center_freqs = center_freqs.f64()?;
+    let sample_rates = df.column("sample_rate_hz")?.cast(&DataType::Float64)?;
+    let sample_rates = sample_rates.f64()?;
+
+    // Rows of the same recording (one per annotation) describe it the same way
+    let mut seen = HashSet::new();
+    let mut recordings = Vec::new();
+    for row in 0..df.height() {
+        let Some(filename) = filenames.get(row) else {
+            continue;
+        };
+        if !seen.insert(filename) {
+            continue;
+        }
+        let Some(start) = datetimes.get(row).and_then(parse_capture_datetime) else {
+            continue;
+        };
+        recordings.push(SyncedRecording {
+            meta_filename: filename.to_string(),
+            sdr_handle: handles.get(row).unwrap_or_default().to_string(),
+            start,
+            duration_s: durations.get(row).unwrap_or(0.0),
+            center_freq_hz: center_freqs.get(row).unwrap_or(0.0),
+            sample_rate_hz: sample_rates.get(row).unwrap_or(0.0),
+        });
+    }
+
+    let reference = recordings
+        .iter()
+        .position(|r| r.meta_filename == meta_filename)
+        .map(|idx| recordings.swap_remove(idx))
+        .ok_or_else(|| anyhow::anyhow!("{} isn't in the dataset or has no capture datetime", meta_filename))?;
+    let same_sensor = |r: &SyncedRecording| !r.sdr_handle.is_empty() && r.sdr_handle == reference.sdr_handle;
+    let mut others: Vec<SyncedRecording> = recordings.into_iter().filter(|r| !same_sensor(r) && r.overlaps(&reference)).collect();
+    others.sort_by(|a, b| a.sdr_handle.cmp(&b.sdr_handle).then(a.start.cmp(&b.start)));
+
+    let mut synced = vec![reference];
+    synced.extend(others);
+    Ok(synced)
+}
@@ -0,0 +1,216 @@
+use super::activity::{parse_capture_datetime, UNKNOWN_SENSOR};
+use anyhow::Result;
+use chrono::NaiveDateTime;
+use polars::prelude::*;
+use std::collections::{BTreeMap, HashSet};
+
+/// Change in dB that counts as a step between recordings, or as a drift over
+/// the whole period
+pub const DEFAULT_NOISE_STEP_DB: f64 = 3.0;
+
+/// Recordings on each side of a candidate step whose median is compared, so
+/// a single odd recording isn't reported as a step
+const STEP_WINDOW: usize = 5;
+
+#[derive(Debug, Clone)]
+pub struct NoiseFloorPoint {
+    pub datetime: NaiveDateTime,
+    pub noise_floor_db: f64,
+    pub meta_filename: String,
+}
+
+/// A sudden change in noise floor starting at `meta_filename`
+#[derive(Debug, Clone)]
+pub struct NoiseFloorStep {
+    pub datetime: NaiveDateTime,
+    pub meta_filename: String,
+    pub before_db: f64,
+    pub after_db: f64,
+}
+
+impl NoiseFloorStep {
+    pub fn change_db(&self) -> f64 {
+        self.after_db - self.before_db
+    }
+}
+
+/// Noise floor over time for one sensor (`sdr_handle`)
+#[derive(Debug, Clone)]
+pub struct SensorNoiseTrend {
+    pub sensor: String,
+    /// In capture time order
+    pub points: Vec<NoiseFloorPoint>,
+    /// Least-squares slope; `None` with fewer than 3 recordings or no time span
+    pub drift_db_per_day: Option<f64>,
+    pub steps: Vec<NoiseFloorStep>,
+}
+
+impl SensorNoiseTrend {
+    /// Change of the fitted line from the first to the last recording
+    pub fn drift_db(&self) -> Option<f64> {
+        let (first, last) = (self.points.first()?, self.points.last()?);
+        Some(self.drift_db_per_day? * days_between(first.datetime, last.datetime))
+    }
+
+    /// Value of the fitted line at `datetime` (it passes through the mean point)
+    pub fn fitted_db(&self, datetime: NaiveDateTime) -> Option<f64> {
+        let per_day = self.drift_db_per_day?;
+        let origin = self.points.first()?.datetime;
+        let n = self.points.len() as f64;
+        let mean_days = self.points.iter().map(|p| days_between(origin, p.datetime)).sum::<f64>() / n;
+        let mean_db = self.points.iter().map(|p| p.noise_floor_db).sum::<f64>() / n;
+        Some(mean_db + per_day * (days_between(origin, datetime) - mean_days))
+    }
+
+    pub fn is_drifting(&self, threshold_db: f64) -> bool {
+        self.drift_db().is_some_and(|drift| drift.abs() >= threshold_db)
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct NoiseTrend {
+    pub sensors: Vec<SensorNoiseTrend>,
+    /// Rows without a noise floor or a parseable capture_datetime
+    pub skipped_rows: usize,
+}
+
+impl NoiseTrend {
+    /// Overall dB range of the points, for a shared plot axis
+    pub fn db_range(&self) -> Option<(f64, f64)> {
+        let values = self.sensors.iter().flat_map(|s| s.points.iter().map(|p| p.noise_floor_db));
+        let min = values.clone().reduce(f64::min)?;
+        let max = values.reduce(f64::max)?;
+        Some((min, max))
+    }
+
+    pub fn time_range(&self) -> Option<(NaiveDateTime, NaiveDateTime)> {
+        let times = self.sensors.iter().flat_map(|s| s.points.iter().map(|p| p.datetime));
+        Some((times.clone().min()?, times.max()?))
+    }
+}
+
+fn days_between(from: NaiveDateTime, to: NaiveDateTime) -> f64 {
+    (to - from).num_milliseconds() as f64 / 86_400_000.0
+}
+
+/// Noise floor per `sdr_handle` over `capture_datetime`, from the
+/// `noise_floor_db` column (see `DatasetOptions::estimate_noise_floor`).
+/// Each recording is counted once however many annotation rows it has.
+/// Steps and drifts of at least `threshold_db` are flagged.
+pub fn noise_floor_trend(df: &DataFrame, threshold_db: f64) -> Result<NoiseTrend> {
+    let Ok(noise_floor) = df.column("noise_floor_db") else {
+        anyhow::bail!("No noise_floor_db column; rebuild the dataset with noise floor estimation enabled");
+    };
+    let noise_floor = noise_floor.cast(&DataType::Float64)?;
+    let noise_floor = noise_floor.f64()?;
+    let sensors = df.column("sdr_handle")?.str()?;
+    let datetimes = df.column("capture_datetime")?.str()?;
+    let filenames = df.column("meta_filename")?.str()?;
+
+    let mut seen = HashSet::new();
+    let mut by_sensor: BTreeMap<String, Vec<NoiseFloorPoint>> = BTreeMap::new();
+    let mut skipped_rows = 0;
+    for idx in 0..df.height() {
+        let sensor = sensors.get(idx).filter(|s| !s.is_empty()).unwrap_or(UNKNOWN_SENSOR);
+        let filename = filenames.get(idx).unwrap_or_default();
+        let (Some(datetime), Some(db)) = (
+            datetimes.get(idx).and_then(parse_capture_datetime),
+            noise_floor.get(idx).filter(|db| db.is_finite()),
+        ) else {
+            skipped_rows += 1;
+            continue;
+        };
+        if !seen.insert((sensor, filename)) {
+            continue;
+        }
+        by_sensor.entry(sensor.to_string()).or_default().push(NoiseFloorPoint {
+            datetime,
+            noise_floor_db: db,
+            meta_filename: filename.to_string(),
+        });
+    }
+
+    let sensors = by_sensor
+        .into_iter()
+        .map(|(sensor, mut points)| {
+            points.sort_by_key(|p| p.datetime);
+            SensorNoiseTrend {
+                sensor,
+                drift_db_per_day: fit_slope(&points),
+                steps: find_steps(&points, threshold_db),
+                points,
+            }
+        })
+        .collect();
+    Ok(NoiseTrend { sensors, skipped_rows })
+}
+
+/// Least-squares slope of dB against days since the first point
+fn fit_slope(points: &[NoiseFloorPoint]) -> Option<f64> {
+    if points.len() < 3 {
+        return None;
+    }
+    let origin = points[0].datetime;
+    let xs: Vec<f64> = points.iter().map(|p| days_between(origin, p.datetime)).collect();
+    let n = points.len() as f64;
+    let mean_x = xs.iter().sum::<f64>() / n;
+    let mean_y = points.iter().map(|p| p.noise_floor_db).sum::<f64>() / n;
+    let mut covariance = 0.0;
+    let mut variance = 0.0;
+    for (x, p) in xs.iter().zip(points) {
+        covariance += (x - mean_x) * (p.noise_floor_db - mean_y);
+        variance += (x - mean_x) * (x - mean_x);
+    }
+    (variance > 0.0).then(|| covariance / variance)
+}
+
+fn median(values: impl Iterator<Item = f64>) -> f64 {
+    let mut values: Vec<f64> = values.collect();
+    values.sort_by(|a, b| a.total_cmp(b));
+    let mid = values.len() / 2;
+    if values.len().is_multiple_of(2) {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+/// Points where the median of the following recordings differs from the
+/// median of the preceding ones by at least `threshold_db`. Both sides need
+/// two or more recordings, and of several adjacent candidates only the
+/// largest change is kept.
+fn find_steps(points: &[NoiseFloorPoint], threshold_db: f64) -> Vec<NoiseFloorStep> {
+    let change_at = |idx: usize| {
+        let before = median(points[idx.saturating_sub(STEP_WINDOW)..idx].iter().map(|p| p.noise_floor_db));
+        let after = median(points[idx..(idx + STEP_WINDOW).min(points.len())].iter().map(|p| p.noise_floor_db));
+        (before, after)
+    };
+    let is_step = |(before, after): (f64, f64)| (after - before).abs() >= threshold_db;
+
+    let mut steps = Vec::new();
+    // Last index with at least two recordings from it onwards
+    let last = points.len().saturating_sub(2);
+    let mut idx = 2;
+    while idx <= last {
+        if !is_step(change_at(idx)) {
+            idx += 1;
+            continue;
+        }
+        let best = (idx..(idx + STEP_WINDOW).min(last + 1))
+            .max_by(|&a, &b| {
+                let (a_before, a_after) = change_at(a);
+                let (b_before, b_after) = change_at(b);
+                (a_after - a_before).abs().total_cmp(&(b_after - b_before).abs())
+            })
+            .unwrap_or(idx);
+        let (before_db, after_db) = change_at(best);
+        steps.push(NoiseFloorStep {
+            datetime: points[best].datetime,
+            meta_filename: points[best].meta_filename.clone(),
+            before_db,
+            after_db,
+        });
+        idx = best + STEP_WINDOW;
+    }
+    steps
+}
@@ -0,0 +1,243 @@
+use super::activity::parse_capture_datetime;
+use super::TimeBucket;
+use crate::viz::Colormap;
+use anyhow::Result;
+use polars::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+
+/// Frequency bins a single capture may span, so a bin width far too small
+/// for the dataset fails instead of running out of memory
+const MAX_BINS_PER_CAPTURE: i64 = 100_000;
+
+/// How often each frequency bin held a signal, per time bucket
+#[derive(Debug, Clone, Default)]
+pub struct Occupancy {
+    pub bin_width_hz: f64,
+    /// Lower edge of each bin some capture covered, ascending
+    pub bins_hz: Vec<f64>,
+    pub buckets: Vec<String>,
+    /// Captures covering each bin, `[bin][bucket]`
+    pub captures: Vec<Vec<u32>>,
+    /// Of those, the captures with an annotated signal in the bin
+    pub occupied: Vec<Vec<u32>>,
+    /// Rows without a parseable capture_datetime or a usable frequency span
+    pub skipped_rows: usize,
+}
+
+impl Occupancy {
+    /// Percentage of captures with a signal in a cell, `None` if no capture covered it
+    pub fn percent(&self, bin: usize, bucket: usize) -> Option<f64> {
+        let captures = self.captures[bin][bucket];
+        (captures > 0).then(|| 100.0 * self.occupied[bin][bucket] as f64 / captures as f64)
+    }
+
+    /// Percentage of captures with a signal in a bin over all time buckets
+    pub fn bin_percent(&self, bin: usize) -> Option<f64> {
+        let captures: u32 = self.captures[bin].iter().sum();
+        let occupied: u32 = self.occupied[bin].iter().sum();
+        (captures > 0).then(|| 100.0 * occupied as f64 / captures as f64)
+    }
+
+    /// One row per covered cell: `freq_bin_hz` (lower edge), `time_bucket`,
+    /// `captures`, `occupied` and `occupancy_pct`
+    pub fn to_dataframe(&self) -> Result<DataFrame> {
+        let (mut bins, mut buckets, mut captures, mut occupied, mut percents) = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+        for (bin, bin_hz) in self.bins_hz.iter().enumerate() {
+            for (bucket, label) in self.buckets.iter().enumerate() {
+                let Some(percent) = self.percent(bin, bucket) else {
+                    continue;
+                };
+                bins.push(*bin_hz);
+                buckets.push(label.as_str());
+                captures.push(self.captures[bin][bucket]);
+                occupied.push(self.occupied[bin][bucket]);
+                percents.push(percent);
+            }
+        }
+        Ok(df! {
+            "freq_bin_hz" => bins,
+            "time_bucket" => buckets,
+            "captures" => captures,
+            "occupied" => occupied,
+            "occupancy_pct" => percents,
+        }?)
+    }
+
+    /// Heatmap of frequency bins (rows, lowest first) over time buckets
+    /// (columns), colored by occupancy; cells no capture covered are gray
+    pub fn to_svg(&self) -> String {
+        const LABEL_WIDTH: usize = 110;
+        const HEADER_HEIGHT: usize = 70;
+        const CELL: usize = 14;
+        // Room on the right for the slanted bucket labels
+        let width = LABEL_WIDTH + CELL * self.buckets.len() + 60;
+        let height = HEADER_HEIGHT + CELL * self.bins_hz.len() + 10;
+        let mut svg = String::new();
+        let _ = writeln!(svg, r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" font-family="monospace" font-size="10">"#, width, height);
+        let _ = writeln!(svg, r#"<rect width="100%" height="100%" fill="white"/>"#);
+        for (bucket, label) in self.buckets.iter().enumerate() {
+            let x = LABEL_WIDTH + bucket * CELL + CELL / 2;
+            let _ = writeln!(
+                svg,
+                r#"<text x="{x}" y="{y}" transform="rotate(-60 {x} {y})">{label}</text>"#,
+                x = x,
+                y = HEADER_HEIGHT - 4,
+                label = label
+            );
+        }
+        for (bin, bin_hz) in self.bins_hz.iter().enumerate() {
+            let y = HEADER_HEIGHT + bin * CELL;
+            let _ = writeln!(
+                svg,
+                r#"<text x="{}" y="{}" text-anchor="end">{}</text>"#,
+                LABEL_WIDTH - 6,
+                y + CELL - 3,
+                format_frequency(*bin_hz)
+            );
+            for bucket in 0..self.buckets.len() {
+                let (fill, title) = match self.percent(bin, bucket) {
+                    Some(percent) => {
+                        let [r, g, b] = Colormap::Viridis.map((percent / 100.0) as f32);
+                        let title = format!(
+                            "{} @ {}: {:.0}% of {} captures",
+                            format_frequency(*bin_hz),
+                            self.buckets[bucket],
+                            percent,
+                            self.captures[bin][bucket]
+                        );
+                        (format!("rgb({},{},{})", r, g, b), title)
+                    }
+                    None => ("rgb(200,200,200)".to_string(), "no captures".to_string()),
+                };
+                let _ = writeln!(
+                    svg,
+                    r#"<rect x="{}" y="{}" width="{}" height="{}" fill="{}"><title>{}</title></rect>"#,
+                    LABEL_WIDTH + bucket * CELL,
+                    y,
+                    CELL - 1,
+                    CELL - 1,
+                    fill,
+                    title
+                );
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// e.g. `915.000 MHz`
+pub fn format_frequency(hz: f64) -> String {
+    if hz.abs() >= 1e9 {
+        format!("{:.3} GHz", hz / 1e9)
+    } else if hz.abs() >= 1e6 {
+        format!("{:.3} MHz", hz / 1e6)
+    } else if hz.abs() >= 1e3 {
+        format!("{:.3} kHz", hz / 1e3)
+    } else {
+        format!("{:.0} Hz", hz)
+    }
+}
+
+/// One capture's span in bins, and the bins its annotations touch
+struct CaptureBins {
+    bucket: i64,
+    first_bin: i64,
+    last_bin: i64,
+    occupied: BTreeSet<i64>,
+}
+
+/// Bin captures by frequency (`bin_width_hz`) and time (`bucket`, from
+/// `capture_datetime`) and count how many had an annotated signal in each
+/// bin. A capture covers `center_freq_hz ± sample_rate_hz / 2`; a row's
+/// signal is `freq_lower_edge_hz..freq_upper_edge_hz`, unless `ml_no_sig`.
+/// Rows are grouped into captures by `meta_filename` (and `capture_index`
+/// for capture-granularity datasets).
+pub fn occupancy(df: &DataFrame, bin_width_hz: f64, bucket: TimeBucket) -> Result<Occupancy> {
+    if bin_width_hz.is_nan() || bin_width_hz <= 0.0 {
+        anyhow::bail!("Bin width must be positive");
+    }
+    let filenames = df.column("meta_filename")?.str()?;
+    let datetimes = df.column("capture_datetime")?.str()?;
+    let center_freqs = df.column("center_freq_hz")?.cast(&DataType::Float64)?;
+    let center_freqs = center_freqs.f64()?;
+    let sample_rates = df.column("sample_rate_hz")?.cast(&DataType::Float64)?;
+    let sample_rates = sample_rates.f64()?;
+    let lower_edges = df.column("freq_lower_edge_hz")?.cast(&DataType::Float64)?;
+    let lower_edges = lower_edges.f64()?;
+    let upper_edges = df.column("freq_upper_edge_hz")?.cast(&DataType::Float64)?;
+    let upper_edges = upper_edges.f64()?;
+    let no_signal = df.column("ml_no_sig").ok().and_then(|c| c.bool().ok().cloned());
+    let capture_indexes = df.column("capture_index").ok().and_then(|c| c.cast(&DataType::UInt32).ok());
+    let capture_indexes = capture_indexes.as_ref().and_then(|c| c.u32().ok());
+
+    // Bins `lower..upper` touches; an upper edge on a bin boundary doesn't reach into the next bin
+    let bins_of = |lower: f64, upper: f64| {
+        let first = (lower / bin_width_hz).floor() as i64;
+        (first, ((upper / bin_width_hz).ceil() as i64 - 1).max(first))
+    };
+    let mut captures: HashMap<(&str, u32), CaptureBins> = HashMap::new();
+    let mut skipped_rows = 0;
+    for idx in 0..df.height() {
+        let (Some(datetime), Some(center), Some(rate)) = (
+            datetimes.get(idx).and_then(parse_capture_datetime),
+            center_freqs.get(idx),
+            sample_rates.get(idx).filter(|rate| *rate > 0.0),
+        ) else {
+            skipped_rows += 1;
+            continue;
+        };
+        let key = (filenames.get(idx).unwrap_or_default(), capture_indexes.and_then(|c| c.get(idx)).unwrap_or(0));
+        let capture = match captures.entry(key) {
+            std::collections::hash_map::Entry::Occupied(entry) => entry.into_mut(),
+            std::collections::hash_map::Entry::Vacant(entry) => {
+                let (first_bin, last_bin) = bins_of(center - rate / 2.0, center + rate / 2.0);
+                if last_bin - first_bin >= MAX_BINS_PER_CAPTURE {
+                    anyhow::bail!(
+                        "A {} bin width splits {} into more than {} bins; use a wider bin",
+                        format_frequency(bin_width_hz),
+                        key.0,
+                        MAX_BINS_PER_CAPTURE
+                    );
+                }
+                entry.insert(CaptureBins { bucket: bucket.key(&datetime), first_bin, last_bin, occupied: BTreeSet::new() })
+            }
+        };
+        let has_signal = !no_signal.as_ref().and_then(|c| c.get(idx)).unwrap_or(false);
+        if let (true, Some(lower), Some(upper)) = (has_signal, lower_edges.get(idx), upper_edges.get(idx)) {
+            if upper > lower {
+                // Only the part of the signal inside the captured band counts
+                let (first, last) = bins_of(lower, upper);
+                capture.occupied.extend(first.max(capture.first_bin)..=last.min(capture.last_bin));
+            }
+        }
+    }
+
+    let mut cells: BTreeMap<(i64, i64), (u32, u32)> = BTreeMap::new();
+    for capture in captures.values() {
+        for bin in capture.first_bin..=capture.last_bin {
+            let cell = cells.entry((bin, capture.bucket)).or_default();
+            cell.0 += 1;
+            if capture.occupied.contains(&bin) {
+                cell.1 += 1;
+            }
+        }
+    }
+    let bin_keys: Vec<i64> = cells.keys().map(|(bin, _)| *bin).collect::<BTreeSet<_>>().into_iter().collect();
+    let bucket_keys = bucket.keys(cells.keys().map(|(_, key)| *key));
+    let count = |pick: fn(&(u32, u32)) -> u32| -> Vec<Vec<u32>> {
+        bin_keys
+            .iter()
+            .map(|bin| bucket_keys.iter().map(|key| cells.get(&(*bin, *key)).map(pick).unwrap_or(0)).collect())
+            .collect()
+    };
+    Ok(Occupancy {
+        bin_width_hz,
+        bins_hz: bin_keys.iter().map(|bin| *bin as f64 * bin_width_hz).collect(),
+        buckets: bucket_keys.iter().map(|key| bucket.label(*key)).collect(),
+        captures: count(|cell| cell.0),
+        occupied: count(|cell| cell.1),
+        skipped_rows,
+    })
+}
@@ -0,0 +1,81 @@
+use super::filter::parse_filters;
+use anyhow::Result;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+/// A named set of column filters, saved from the GUI and re-applied from it
+/// or from `dataset --preset`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct FilterPreset {
+    /// e.g. "strong wifi"
+    pub name: String,
+    /// Filter text per column, in the syntax of the GUI's filter boxes
+    pub filters: BTreeMap<String, String>,
+}
+
+impl FilterPreset {
+    /// The non-blank filters of `filters`
+    pub fn new<'a>(name: &str, filters: impl IntoIterator<Item = (&'a String, &'a String)>) -> Self {
+        FilterPreset {
+            name: name.trim().to_string(),
+            filters: filters
+                .into_iter()
+                .filter(|(_, text)| !text.trim().is_empty())
+                .map(|(column, text)| (column.clone(), text.trim().to_string()))
+                .collect(),
+        }
+    }
+
+    /// e.g. `snr_db: >= 10 AND ml_wifi_prob: > 0.8`
+    pub fn description(&self) -> String {
+        self.filters.iter().map(|(column, text)| format!("{}: {}", column, text)).collect::<Vec<_>>().join(" AND ")
+    }
+
+    /// Keep the rows of `df` passing every filter. A column `df` lacks or a
+    /// filter that doesn't parse is an error, not skipped.
+    pub fn apply(&self, df: &DataFrame) -> Result<DataFrame> {
+        let filters = parse_filters(df, self.filters.iter().map(|(column, text)| (column.as_str(), text.as_str())))
+            .map_err(|e| anyhow::anyhow!("Preset {:?}: {}", self.name, e))?;
+        let mut lazy = df.clone().lazy();
+        for filter in filters {
+            lazy = lazy.filter(filter.to_expr());
+        }
+        Ok(lazy.collect()?)
+    }
+}
+
+/// The GUI's settings file, `config.json` under the platform config
+/// directory (`~/.config/sig_viewer` on Linux)
+pub fn gui_config_path() -> PathBuf {
+    dirs::config_dir().unwrap_or_else(|| PathBuf::from(".")).join("sig_viewer").join("config.json")
+}
+
+/// The presets saved under `filter_presets` in the GUI config; none if it
+/// doesn't exist yet
+pub fn load_saved_presets() -> Result<Vec<FilterPreset>> {
+    let path = gui_config_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let contents = std::fs::read_to_string(&path).map_err(|e| anyhow::anyhow!("Failed to read {:?}: {}", path, e))?;
+    let config: serde_json::Value = serde_json::from_str(&contents)?;
+    match config.get("filter_presets") {
+        Some(presets) => Ok(serde_json::from_value(presets.clone())?),
+        None => Ok(Vec::new()),
+    }
+}
+
+/// The preset called `name`, ignoring case
+pub fn find_preset<'a>(presets: &'a [FilterPreset], name: &str) -> Result<&'a FilterPreset> {
+    presets.iter().find(|preset| preset.name.eq_ignore_ascii_case(name.trim())).ok_or_else(|| {
+        let names: Vec<&str> = presets.iter().map(|preset| preset.name.as_str()).collect();
+        if names.is_empty() {
+            anyhow::anyhow!("No filter preset {:?}; none are saved yet (save one from the GUI's Presets menu)", name)
+        } else {
+            anyhow::anyhow!("No filter preset {:?}; saved presets: {}", name, names.join(", "))
+        }
+    })
+}
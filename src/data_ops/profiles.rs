@@ -0,0 +1,117 @@
+use super::{add_band_columns, BAND_COLUMN};
+use anyhow::Result;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::Path;
+
+/// Column naming the profile applied to each row (empty where none matched).
+/// Its presence also marks a dataset as already corrected, so re-opening an
+/// export doesn't apply the offsets twice.
+pub const SENSOR_PROFILE_COLUMN: &str = "sensor_profile";
+
+/// Per-sensor settings, matched to rows by `sdr_handle`
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct SensorProfile {
+    /// Display name, e.g. "Rooftop B210"
+    pub name: String,
+    pub sdr_handle: String,
+    /// Added to `power_dbm`, from calibrating against a reference source
+    pub power_offset_db: f64,
+    /// Added to `center_freq_hz`, `sig_center_freq_hz` and the annotation
+    /// edges, to correct the tuner's frequency error
+    pub freq_offset_hz: f64,
+    /// Program to open this sensor's recordings with instead of inspectrum,
+    /// with `{meta}` and `{data}` replaced by the file paths
+    pub viewer_command: String,
+    /// RGB color for the sensor in tables and plots
+    pub color: Option<[u8; 3]>,
+}
+
+impl SensorProfile {
+    /// Program and arguments of `viewer_command` for a recording, or None to
+    /// use the default viewer. Arguments are split on whitespace.
+    pub fn viewer_args(&self, meta_path: &Path, data_path: &Path) -> Option<Vec<String>> {
+        let args: Vec<String> = self
+            .viewer_command
+            .split_whitespace()
+            .map(|arg| {
+                arg.replace("{meta}", &meta_path.to_string_lossy())
+                    .replace("{data}", &data_path.to_string_lossy())
+            })
+            .collect();
+        (!args.is_empty()).then_some(args)
+    }
+
+    fn corrects_values(&self) -> bool {
+        self.power_offset_db != 0.0 || self.freq_offset_hz != 0.0
+    }
+}
+
+/// The profile for `sdr_handle`; the first one wins if several match
+pub fn find_profile<'a>(profiles: &'a [SensorProfile], sdr_handle: &str) -> Option<&'a SensorProfile> {
+    profiles.iter().find(|profile| !profile.sdr_handle.is_empty() && profile.sdr_handle == sdr_handle)
+}
+
+/// A JSON array of profiles, as stored under `sensor_profiles` in the GUI config
+pub fn load_sensor_profiles<P: AsRef<Path>>(path: P) -> Result<Vec<SensorProfile>> {
+    let path = path.as_ref();
+    let contents = std::fs::read_to_string(path).map_err(|e| anyhow::anyhow!("Failed to read {:?}: {}", path, e))?;
+    Ok(serde_json::from_str(&contents)?)
+}
+
+/// Apply each row's profile: calibration and frequency offsets, plus the
+/// `sensor_profile` column. Datasets without `sdr_handle`, or that already
+/// have the `sensor_profile` column, are returned unchanged.
+pub fn apply_sensor_profiles(df: DataFrame, profiles: &[SensorProfile]) -> Result<DataFrame> {
+    let has = |column: &str| df.column(column).is_ok();
+    if profiles.is_empty() || !has("sdr_handle") || has(SENSOR_PROFILE_COLUMN) {
+        return Ok(df);
+    }
+
+    let has_band = has(BAND_COLUMN);
+    // Only the first profile per handle applies, as in find_profile
+    let mut seen = HashSet::new();
+    let profiles: Vec<&SensorProfile> = profiles
+        .iter()
+        .filter(|p| !p.sdr_handle.is_empty() && seen.insert(p.sdr_handle.as_str()))
+        .collect();
+
+    let offset_columns = |columns: &[&str], offset: fn(&SensorProfile) -> f64| -> Vec<Expr> {
+        columns
+            .iter()
+            .filter(|column| has(column))
+            .map(|column| {
+                // The parser writes 0 for a missing value, which stays 0
+                profiles.iter().filter(|p| offset(p) != 0.0).fold(col(column), |expr, profile| {
+                    when(col("sdr_handle").eq(lit(profile.sdr_handle.as_str())).and(col(column).neq(lit(0.0))))
+                        .then(col(column) + lit(offset(profile)))
+                        .otherwise(expr)
+                })
+                .alias(column)
+            })
+            .collect()
+    };
+    let mut exprs = Vec::new();
+    if profiles.iter().any(|p| p.corrects_values()) {
+        exprs.extend(offset_columns(&["power_dbm"], |p| p.power_offset_db));
+        exprs.extend(offset_columns(
+            &["center_freq_hz", "sig_center_freq_hz", "freq_lower_edge_hz", "freq_upper_edge_hz"],
+            |p| p.freq_offset_hz,
+        ));
+    }
+    let profile_name = profiles.iter().fold(lit(""), |expr, profile| {
+        let name = if profile.name.is_empty() { &profile.sdr_handle } else { &profile.name };
+        when(col("sdr_handle").eq(lit(profile.sdr_handle.as_str())))
+            .then(lit(name.as_str()))
+            .otherwise(expr)
+    });
+    exprs.push(profile_name.alias(SENSOR_PROFILE_COLUMN));
+    let mut df = df.lazy().with_columns(exprs).collect()?;
+    // Corrected frequencies can move a signal into another band or channel
+    if has_band && profiles.iter().any(|p| p.freq_offset_hz != 0.0) {
+        add_band_columns(&mut df)?;
+    }
+    Ok(df)
+}
@@ -0,0 +1,115 @@
+use super::schema::SUMMARY_SCHEMA_VERSION;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+/// What one row of a dataset stands for. Rows are one per ML annotation, or
+/// one per file for recordings without any.
+pub const ROW_PER_ML_ANNOTATION: &str = "ml_annotation";
+
+/// Where an exported dataset came from. Written next to the export as
+/// `<file>.provenance.json` so the file explains itself when it turns up again
+/// months later (polars can't write Parquet key-value metadata yet).
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Provenance {
+    /// Directory or file the dataset was built from
+    pub source: String,
+    /// Further root directories scanned into the same dataset, whose rows
+    /// name their root in `source_root`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub other_roots: Vec<String>,
+    /// When the recordings were scanned (RFC 3339)
+    pub scanned_at: String,
+    pub exported_at: String,
+    pub tool_version: String,
+    /// Filters applied before export, if any
+    pub filter: Option<String>,
+    /// Column casts applied before export, as `column=type`
+    #[serde(default)]
+    pub casts: Vec<String>,
+    pub row_per: String,
+    pub num_rows: usize,
+    /// Summary schema the rows follow; absent in exports from before
+    /// versioning (see `data_ops::schema`)
+    #[serde(default)]
+    pub schema_version: Option<u32>,
+}
+
+impl Provenance {
+    /// Provenance for a dataset scanned from `source` at `scanned_at`
+    pub fn new(source: &str, scanned_at: chrono::DateTime<chrono::Utc>, num_rows: usize) -> Self {
+        Provenance {
+            // Absolute, since a relative path means nothing once the file moves
+            source: absolute(source),
+            other_roots: Vec::new(),
+            scanned_at: timestamp(scanned_at),
+            exported_at: timestamp(chrono::Utc::now()),
+            tool_version: env!("CARGO_PKG_VERSION").to_string(),
+            filter: None,
+            casts: Vec::new(),
+            row_per: ROW_PER_ML_ANNOTATION.to_string(),
+            num_rows,
+            schema_version: Some(SUMMARY_SCHEMA_VERSION),
+        }
+    }
+
+    /// Mark as exported now by this build, with `num_rows` rows. Re-opened
+    /// datasets are migrated on load, so the rows are in this build's schema.
+    pub fn touch(&mut self, num_rows: usize) {
+        self.exported_at = timestamp(chrono::Utc::now());
+        self.tool_version = env!("CARGO_PKG_VERSION").to_string();
+        self.num_rows = num_rows;
+        self.schema_version = Some(SUMMARY_SCHEMA_VERSION);
+    }
+
+    /// Record the roots scanned after the first, made absolute like `source`
+    pub fn set_other_roots(&mut self, roots: &[String]) {
+        self.other_roots = roots.iter().map(|root| absolute(root)).collect();
+    }
+
+    pub fn sidecar_path<P: AsRef<Path>>(export_path: P) -> PathBuf {
+        let mut name = export_path.as_ref().as_os_str().to_owned();
+        name.push(".provenance.json");
+        PathBuf::from(name)
+    }
+
+    pub fn write<P: AsRef<Path>>(&self, export_path: P) -> Result<()> {
+        let contents = serde_json::to_string_pretty(self)?;
+        std::fs::write(Self::sidecar_path(export_path), contents + "\n")?;
+        Ok(())
+    }
+
+    /// The sidecar for `export_path`, or None if there isn't one
+    pub fn read<P: AsRef<Path>>(export_path: P) -> Result<Option<Self>> {
+        let path = Self::sidecar_path(export_path);
+        if !path.exists() {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&std::fs::read_to_string(path)?)?))
+    }
+
+    /// One-line description for status bars and CLI output
+    pub fn summary(&self) -> String {
+        let sources = std::iter::once(&self.source).chain(&self.other_roots).cloned().collect::<Vec<_>>().join(", ");
+        let mut summary = format!(
+            "{} rows from {} (scanned {}, sig_viewer {}, schema v{})",
+            self.num_rows,
+            sources,
+            self.scanned_at,
+            self.tool_version,
+            super::schema::stamped_version(Some(self))
+        );
+        if let Some(ref filter) = self.filter {
+            summary.push_str(&format!(", filtered by {}", filter));
+        }
+        summary
+    }
+}
+
+fn absolute(path: &str) -> String {
+    std::fs::canonicalize(path).map(|path| path.display().to_string()).unwrap_or_else(|_| path.to_string())
+}
+
+fn timestamp(time: chrono::DateTime<chrono::Utc>) -> String {
+    time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+}
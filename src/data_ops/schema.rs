@@ -0,0 +1,250 @@
+//! Versioning of the summary rows stored in exported datasets.
+//!
+//! An exported CSV/Parquet dataset is a cache of summary rows: it's re-opened
+//! by `stats`, `noise-trend` and the GUI instead of rescanning the recordings.
+//! Its provenance sidecar records which version of the summary schema the rows
+//! follow, and opening it upgrades older files step by step so code expecting
+//! newer columns doesn't trip over them. Files from a newer build can't be
+//! downgraded and have to be rebuilt from their source directory.
+
+use super::{add_band_columns, add_time_columns, Provenance};
+use crate::parser::sigmf::ChecksumStatus;
+use anyhow::Result;
+use polars::prelude::*;
+
+/// Version of the summary row schema written by this build. Bump it whenever
+/// a summary column is added, renamed or changes meaning, and add a step to
+/// `MIGRATIONS` that upgrades rows from the previous version.
+///
+/// 1. Summary rows from before versioning (no `schema_version` in the sidecar)
+/// 2. Adds `annotation_density`, `checksum_status`, `noise_floor_db` and the
+///    `segment_*`/`stitched_duration_s` columns
+/// 3. Adds `label` (the ML annotation's `core:label`)
+/// 4. Adds `parse_warnings` (repairs made by lenient parsing)
+/// 5. Adds `annotation_index` to rows per ML annotation
+/// 6. Adds `capture_time`, `capture_date`, `capture_hour` and `capture_unix`
+///    (parsed from `capture_datetime`)
+/// 7. Adds `band` and `channel` (from the band map)
+/// 8. Fixes `latitude` and `longitude`, which were read from the GeoJSON
+///    coordinates the wrong way round
+/// 9. Adds `meta_path` (the meta file relative to the directory scanned)
+/// 10. `label` of rows without an ML annotation comes from an annotation
+///     covering the whole recording
+pub const SUMMARY_SCHEMA_VERSION: u32 = 10;
+
+/// Version assumed for datasets whose sidecar has no stamp (or that have no sidecar)
+const UNVERSIONED_SCHEMA: u32 = 1;
+
+/// Upgrades rows from schema `from` to `from + 1`
+struct MigrationStep {
+    from: u32,
+    description: &'static str,
+    apply: fn(LazyFrame, &Schema) -> LazyFrame,
+}
+
+const MIGRATIONS: &[MigrationStep] = &[
+    MigrationStep {
+        from: 1,
+        description: "filled annotation_density, checksum_status, noise_floor_db and segment columns with defaults",
+        apply: add_v2_columns,
+    },
+    MigrationStep {
+        from: 2,
+        description: "added an empty label column",
+        apply: add_v3_columns,
+    },
+    MigrationStep {
+        from: 3,
+        description: "added an empty parse_warnings column",
+        apply: add_v4_columns,
+    },
+    MigrationStep {
+        from: 4,
+        description: "added an empty annotation_index column",
+        apply: add_v5_columns,
+    },
+    MigrationStep {
+        from: 5,
+        description: "parsed capture_datetime into capture_time, capture_date, capture_hour and capture_unix",
+        apply: add_v6_columns,
+    },
+    MigrationStep {
+        from: 6,
+        description: "classified center frequencies into band and channel columns",
+        apply: add_v7_columns,
+    },
+    MigrationStep {
+        from: 7,
+        description: "swapped latitude and longitude, which were read from core:geolocation the wrong way round",
+        apply: swap_v7_coordinates,
+    },
+    MigrationStep {
+        from: 8,
+        description: "filled meta_path from meta_filename",
+        apply: add_v9_columns,
+    },
+    MigrationStep {
+        from: 9,
+        description: "left label empty on rows without an ML annotation; rescan to fill it from whole-recording annotations",
+        apply: keep_v9_labels,
+    },
+];
+
+/// What was done to bring a dataset up to the current schema
+#[derive(Debug, Clone, PartialEq)]
+pub struct SchemaMigration {
+    /// Schema the file was written with
+    pub from: u32,
+    pub to: u32,
+    /// Description of each step applied, oldest first
+    pub steps: Vec<&'static str>,
+}
+
+impl SchemaMigration {
+    /// Whether the rows were changed on the way in
+    pub fn was_migrated(&self) -> bool {
+        !self.steps.is_empty()
+    }
+
+    /// One-line description for status bars and CLI output
+    pub fn summary(&self) -> String {
+        format!("Migrated from summary schema v{} to v{}: {}", self.from, self.to, self.steps.join("; "))
+    }
+}
+
+/// Schema version of a dataset with this provenance
+pub fn stamped_version(provenance: Option<&Provenance>) -> u32 {
+    provenance.and_then(|p| p.schema_version).unwrap_or(UNVERSIONED_SCHEMA)
+}
+
+/// Bring a re-opened dataset up to `SUMMARY_SCHEMA_VERSION`. Columns a step
+/// adds are only added if missing, so exports of a subset of columns keep
+/// working. Fails for files written by a newer build.
+pub fn migrate_dataset(mut lf: LazyFrame, provenance: Option<&Provenance>) -> Result<(LazyFrame, SchemaMigration)> {
+    let from = stamped_version(provenance);
+    if from > SUMMARY_SCHEMA_VERSION {
+        anyhow::bail!(
+            "Dataset was written by a newer sig_viewer (summary schema v{}, this build reads up to v{}); rebuild it from {}",
+            from,
+            SUMMARY_SCHEMA_VERSION,
+            provenance.map(|p| p.source.as_str()).unwrap_or("its recordings")
+        );
+    }
+    let mut steps = Vec::new();
+    for step in MIGRATIONS.iter().filter(|step| step.from >= from) {
+        let schema = lf.schema()?;
+        lf = (step.apply)(lf, &schema);
+        steps.push(step.description);
+    }
+    Ok((lf, SchemaMigration { from, to: SUMMARY_SCHEMA_VERSION, steps }))
+}
+
+/// v1 → v2: the per-file columns added by `SigMFDataset`, with the values a
+/// scan without checksums or noise floor estimation would have given
+fn add_v2_columns(lf: LazyFrame, schema: &Schema) -> LazyFrame {
+    let stitched_duration = if schema.contains("duration_s") {
+        col("duration_s")
+    } else {
+        lit(NULL).cast(DataType::Float64)
+    };
+    let defaults = [
+        ("annotation_density", lit("")),
+        ("checksum_status", lit(ChecksumStatus::NotChecked.as_str())),
+        ("noise_floor_db", lit(NULL).cast(DataType::Float64)),
+        ("segment_group", lit("")),
+        ("segment_index", lit(0u32)),
+        ("segment_count", lit(1u32)),
+        ("stitched_duration_s", stitched_duration),
+    ];
+    let missing: Vec<Expr> = defaults
+        .into_iter()
+        .filter(|(name, _)| !schema.contains(name))
+        .map(|(name, value)| value.alias(name))
+        .collect();
+    if missing.is_empty() {
+        lf
+    } else {
+        lf.with_columns(missing)
+    }
+}
+
+/// v2 → v3: `label`, empty since the annotation isn't at hand
+fn add_v3_columns(lf: LazyFrame, schema: &Schema) -> LazyFrame {
+    if schema.contains("label") {
+        lf
+    } else {
+        lf.with_column(lit("").alias("label"))
+    }
+}
+
+/// v3 → v4: `parse_warnings`, empty as strict parsing (the only mode then)
+/// makes no repairs
+fn add_v4_columns(lf: LazyFrame, schema: &Schema) -> LazyFrame {
+    if schema.contains("parse_warnings") {
+        lf
+    } else {
+        lf.with_column(lit("").alias("parse_warnings"))
+    }
+}
+
+/// v4 → v5: `annotation_index`, null since the meta files aren't at hand.
+/// Rows per capture segment (with `capture_index`) don't get one; rows per
+/// file didn't exist before v5.
+fn add_v5_columns(lf: LazyFrame, schema: &Schema) -> LazyFrame {
+    if schema.contains("annotation_index") || schema.contains("capture_index") {
+        lf
+    } else {
+        lf.with_column(lit(NULL).cast(DataType::UInt32).alias("annotation_index"))
+    }
+}
+
+/// v5 → v6: the time columns, derived from `capture_datetime` as a scan
+/// would. Done eagerly, so a dataset that can't be read is left as it is
+/// for the caller to report.
+fn add_v6_columns(lf: LazyFrame, _schema: &Schema) -> LazyFrame {
+    let Ok(mut df) = lf.clone().collect() else {
+        return lf;
+    };
+    match add_time_columns(&mut df) {
+        Ok(()) => df.lazy(),
+        Err(_) => lf,
+    }
+}
+
+/// v6 → v7: `band` and `channel` from the current band map, eagerly as for v6
+fn add_v7_columns(lf: LazyFrame, _schema: &Schema) -> LazyFrame {
+    let Ok(mut df) = lf.clone().collect() else {
+        return lf;
+    };
+    match add_band_columns(&mut df) {
+        Ok(()) => df.lazy(),
+        Err(_) => lf,
+    }
+}
+
+/// v7 → v8: earlier versions put the GeoJSON longitude in `latitude` and
+/// the latitude in `longitude`
+fn swap_v7_coordinates(lf: LazyFrame, schema: &Schema) -> LazyFrame {
+    if schema.contains("latitude") && schema.contains("longitude") {
+        lf.with_columns([col("longitude").alias("latitude"), col("latitude").alias("longitude")])
+    } else {
+        lf
+    }
+}
+
+/// v8 → v9: `meta_path`, taken to be `meta_filename` since the directory
+/// each file was found in wasn't recorded
+fn add_v9_columns(lf: LazyFrame, schema: &Schema) -> LazyFrame {
+    if schema.contains("meta_path") || !schema.contains("meta_filename") {
+        lf
+    } else {
+        lf.with_column(col("meta_filename").alias("meta_path"))
+    }
+}
+
+/// v9 → v10: rows without an ML annotation now take `label` from an
+/// annotation covering the whole recording. That annotation isn't at hand,
+/// so their labels stay empty until the directory is rescanned.
+fn keep_v9_labels(lf: LazyFrame, _schema: &Schema) -> LazyFrame {
+    lf
+}
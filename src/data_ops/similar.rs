@@ -0,0 +1,77 @@
+//! Ranking rows by how close their feature columns are to one row's, to
+//! hunt for an emitter that keeps coming back.
+//!
+//! Distances are Euclidean over the features, z-scored by default as in
+//! [`cluster`](super::cluster). Rows missing a feature aren't ranked.
+
+use super::cluster::{distance_sq, feature_matrix, standardize};
+use super::columns::empty_columns;
+use anyhow::Result;
+use polars::prelude::*;
+
+/// Features used when none are given, if the dataset has them
+const DEFAULT_FEATURES: [&str; 3] = ["center_freq_hz", "sig_center_freq_hz", "sig_bandwidth_hz"];
+
+#[derive(Debug, Clone)]
+pub struct SimilarOptions {
+    pub columns: Vec<String>,
+    /// Scale each feature to zero mean and unit variance first
+    pub standardize: bool,
+    /// Leave out rows from the reference row's recording
+    pub other_recordings_only: bool,
+    /// Most rows returned
+    pub limit: usize,
+}
+
+/// A ranked row and its distance to the reference row, in feature units
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimilarRow {
+    pub row: usize,
+    pub distance: f64,
+}
+
+/// Frequencies, `sig_bandwidth_hz` and the `ml_*` probability columns that
+/// aren't empty (see [`empty_columns`])
+pub fn default_similarity_columns(df: &DataFrame) -> Vec<String> {
+    let empty = empty_columns(df);
+    df.get_columns()
+        .iter()
+        .filter(|column| column.dtype().is_float())
+        .map(|column| column.name().to_string())
+        .filter(|name| DEFAULT_FEATURES.contains(&name.as_str()) || name.starts_with("ml_"))
+        .filter(|name| !empty.contains(name))
+        .collect()
+}
+
+/// The rows of `df` nearest to `reference`, nearest first, not counting
+/// `reference` itself
+pub fn find_similar(df: &DataFrame, reference: usize, options: &SimilarOptions) -> Result<Vec<SimilarRow>> {
+    if options.columns.is_empty() {
+        anyhow::bail!("At least one feature column is required");
+    }
+    if reference >= df.height() {
+        anyhow::bail!("Row {} is out of range ({} rows)", reference + 1, df.height());
+    }
+    let dims = options.columns.len();
+    let (rows, mut points) = feature_matrix(df, &options.columns)?;
+    let Some(position) = rows.iter().position(|row| *row == reference) else {
+        anyhow::bail!("Row {} is missing a value of {}", reference + 1, options.columns.join(", "));
+    };
+    if options.standardize {
+        standardize(&mut points, dims);
+    }
+    let target = &points[position * dims..(position + 1) * dims];
+
+    let names = df.column("meta_filename").ok().and_then(|column| column.str().ok().cloned());
+    let recording = names.as_ref().and_then(|names| names.get(reference));
+    let mut ranked: Vec<SimilarRow> = rows
+        .iter()
+        .enumerate()
+        .filter(|(_, row)| **row != reference)
+        .filter(|(_, row)| !options.other_recordings_only || recording.is_none() || names.as_ref().and_then(|names| names.get(**row)) != recording)
+        .map(|(idx, row)| SimilarRow { row: *row, distance: distance_sq(&points[idx * dims..(idx + 1) * dims], target).sqrt() })
+        .collect();
+    ranked.sort_by(|a, b| a.distance.total_cmp(&b.distance).then(a.row.cmp(&b.row)));
+    ranked.truncate(options.limit);
+    Ok(ranked)
+}
@@ -0,0 +1,183 @@
+//! Train/validation/test splits of a dataset for model training.
+//!
+//! Recordings, not rows, are dealt out, so the annotations of one recording
+//! never end up on both sides of a split. With a stratify key, recordings
+//! are grouped by the key's value on their first row and each group is
+//! divided by the ratios on its own, so every set gets its share of each
+//! class or band.
+
+use super::groupby::GroupKey;
+use crate::parser::SigMFDataset;
+use anyhow::Result;
+use polars::prelude::*;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::Path;
+
+/// Names of the sets, in [`DatasetSplit::sets`] order
+pub const SPLIT_NAMES: [&str; 3] = ["train", "val", "test"];
+
+/// Column added by [`with_split_column`]
+pub const SPLIT_COLUMN: &str = "split";
+
+/// Stratify key computed rather than read: the class of the highest `ml_*`
+/// probability of a row (see [`dominant_class`])
+pub const DOMINANT_CLASS: &str = "dominant_class";
+
+/// Fractions of the recordings going to each set
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SplitRatios {
+    pub train: f64,
+    pub val: f64,
+    pub test: f64,
+}
+
+impl Default for SplitRatios {
+    fn default() -> Self {
+        SplitRatios { train: 0.8, val: 0.1, test: 0.1 }
+    }
+}
+
+impl SplitRatios {
+    /// Parse `train,val,test`, e.g. `0.8,0.1,0.1` or `70,15,15`; scaled to
+    /// sum to 1
+    pub fn parse(spec: &str) -> Result<Self> {
+        let parts = spec
+            .split(',')
+            .map(|part| part.trim().parse::<f64>().map_err(|_| anyhow::anyhow!("Invalid ratio '{}'", part.trim())))
+            .collect::<Result<Vec<_>>>()?;
+        let [train, val, test] = parts[..] else {
+            anyhow::bail!("Expected three ratios (train,val,test), got '{}'", spec);
+        };
+        let total = train + val + test;
+        if [train, val, test].iter().any(|r| *r < 0.0 || !r.is_finite()) || total <= 0.0 {
+            anyhow::bail!("Ratios must be non-negative and not all zero: '{}'", spec);
+        }
+        Ok(SplitRatios { train: train / total, val: val / total, test: test / total })
+    }
+
+    /// How many of `n` items go to each set, rounding so they add up to `n`
+    fn counts(&self, n: usize) -> [usize; 3] {
+        let train = (n as f64 * self.train).round() as usize;
+        let train_val = ((n as f64 * (self.train + self.val)).round() as usize).max(train);
+        [train, train_val - train, n - train_val]
+    }
+}
+
+/// Recordings of each set, by meta file path relative to the directory
+/// scanned (see [`recording_keys`])
+#[derive(Debug, Clone, Default)]
+pub struct DatasetSplit {
+    pub sets: [Vec<String>; 3],
+    /// Recordings per stratify value; a single `""` entry without a key
+    pub strata: BTreeMap<String, [usize; 3]>,
+}
+
+/// Deal the recordings of `df` into train/val/test sets. The same `seed`
+/// gives the same split of the same dataset.
+pub fn split_dataset(df: &DataFrame, ratios: &SplitRatios, stratify: Option<&GroupKey>, seed: Option<u64>) -> Result<DatasetSplit> {
+    let strata_values = match stratify {
+        Some(key) => {
+            let df = match key {
+                GroupKey::Column(column) if column == DOMINANT_CLASS && df.column(DOMINANT_CLASS).is_err() => {
+                    let mut df = df.clone();
+                    df.with_column(dominant_class(&df)?)?;
+                    df
+                }
+                _ => df.clone(),
+            };
+            let keyed = key.attach(&df)?;
+            Some(keyed.column(&key.output_name())?.clone())
+        }
+        None => None,
+    };
+
+    // Each recording once, in dataset order, under the key of its first row
+    let mut strata: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut seen = HashSet::new();
+    for (row, name) in recording_keys(df)?.into_iter().enumerate() {
+        let Some(name) = name.filter(|name| seen.insert(name.clone())) else {
+            continue;
+        };
+        let value = match &strata_values {
+            Some(values) => match values.get(row)? {
+                AnyValue::Null => String::new(),
+                AnyValue::String(s) => s.to_string(),
+                value => value.to_string(),
+            },
+            None => String::new(),
+        };
+        strata.entry(value).or_default().push(name);
+    }
+
+    let mut rng = match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+    let mut split = DatasetSplit::default();
+    for (value, mut recordings) in strata {
+        recordings.shuffle(&mut rng);
+        let counts = ratios.counts(recordings.len());
+        let mut rest = recordings.into_iter();
+        for (set, count) in split.sets.iter_mut().zip(counts) {
+            set.extend(rest.by_ref().take(count));
+        }
+        split.strata.insert(value, counts);
+    }
+    Ok(split)
+}
+
+/// `df` with a [`SPLIT_COLUMN`] naming each row's set; rows of recordings
+/// not in the split are null
+pub fn with_split_column(df: &DataFrame, split: &DatasetSplit) -> Result<DataFrame> {
+    let sets: HashMap<&str, &str> = split
+        .sets
+        .iter()
+        .zip(SPLIT_NAMES)
+        .flat_map(|(set, set_name)| set.iter().map(move |name| (name.as_str(), set_name)))
+        .collect();
+    let column: StringChunked = recording_keys(df)?
+        .into_iter()
+        .map(|name| name.and_then(|name| sets.get(name.as_str()).copied()))
+        .collect();
+    let mut df = df.clone();
+    df.with_column(column.into_series().with_name(SPLIT_COLUMN.into()))?;
+    Ok(df)
+}
+
+/// Each row's recording: its `meta_path`, absolute under its `source_root`
+/// in multi-root datasets, or its `meta_filename` in datasets without one
+fn recording_keys(df: &DataFrame) -> Result<Vec<Option<String>>> {
+    Ok(SigMFDataset::row_meta_paths(df, Path::new(""))?
+        .into_iter()
+        .map(|path| path.map(|path| path.to_string_lossy().to_string()))
+        .collect())
+}
+
+/// The class of each row's highest probability among its `ml_*` float
+/// columns (`ml_wifi_prob` → `wifi`, `ml_model_drone` → `model_drone`), or
+/// null when they're all zero or null
+pub fn dominant_class(df: &DataFrame) -> Result<Series> {
+    let columns: Vec<(String, &Float64Chunked)> = df
+        .get_columns()
+        .iter()
+        .filter(|column| column.name().starts_with("ml_"))
+        .filter_map(|column| {
+            let class = column.name().trim_start_matches("ml_").trim_end_matches("_prob").to_string();
+            Some((class, column.f64().ok()?))
+        })
+        .collect();
+    let classes: StringChunked = (0..df.height())
+        .map(|row| {
+            columns
+                .iter()
+                .filter_map(|(class, values)| Some((class, values.get(row)?)))
+                .filter(|(_, prob)| *prob > 0.0)
+                .max_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(class, _)| class.as_str())
+        })
+        .collect();
+    Ok(classes.into_series().with_name(DOMINANT_CLASS.into()))
+}
@@ -0,0 +1,58 @@
+//! Readable forms of numbers whose unit the column name gives away, for
+//! display only: tables sort and filter on the values themselves.
+
+use super::format_frequency;
+
+/// Readable byte count, e.g. `1.5 GiB`
+pub fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
+}
+
+/// Seconds as µs, ms or s, whichever reads best
+pub fn format_duration(seconds: f64) -> String {
+    let magnitude = seconds.abs();
+    if magnitude == 0.0 || magnitude >= 1.0 {
+        format!("{:.3} s", seconds)
+    } else if magnitude >= 1e-3 {
+        format!("{:.3} ms", seconds * 1e3)
+    } else {
+        format!("{:.3} µs", seconds * 1e6)
+    }
+}
+
+/// `value` in the unit `column`'s name ends in: `_hz` as kHz/MHz/GHz (S/s
+/// for sample rates), `_s` as µs/ms/s, `_bytes` as KiB/MiB/..., and `_dbm`,
+/// `_dbfs` and `_db` with one decimal. None for other columns.
+pub fn humanize_value(column: &str, value: f64) -> Option<String> {
+    if !value.is_finite() {
+        return None;
+    }
+    if column.ends_with("sample_rate_hz") {
+        Some(format_frequency(value).replace("Hz", "S/s"))
+    } else if column.ends_with("_hz") {
+        Some(format_frequency(value))
+    } else if column.ends_with("_bytes") && value >= 0.0 {
+        Some(format_bytes(value as u64))
+    } else if column.ends_with("_s") {
+        Some(format_duration(value))
+    } else if column.ends_with("_dbm") {
+        Some(format!("{:.1} dBm", value))
+    } else if column.ends_with("_dbfs") {
+        Some(format!("{:.1} dBFS", value))
+    } else if column.ends_with("_db") {
+        Some(format!("{:.1} dB", value))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,277 @@
+//! Keeping a dataset current while a pipeline writes recordings into its
+//! directory: a filesystem watcher that reports changed meta files, and an
+//! update that re-reads only those files instead of rescanning everything.
+
+use super::{append_rows, apply_sensor_profiles, SensorProfile};
+use crate::parser::sigmf::{dataset_segments, group_segments, normalize, relative_meta_path, DatasetOptions, Segment, SigMFParser, META_PATH_COLUMN};
+use crate::parser::SigMFDataset;
+use anyhow::Result;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use polars::prelude::*;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+/// How long a directory has to be quiet before changes are reported, so a
+/// recording still being written isn't parsed half-way
+pub const WATCH_SETTLE: Duration = Duration::from_millis(500);
+
+/// Watches a directory tree for `.sigmf-meta` files being created, rewritten
+/// or removed. A change to a `.sigmf-data` file counts as a change to the
+/// meta file next to it, since its sample count and duration change.
+pub struct DirectoryWatcher {
+    // Stops watching when dropped
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    pending: BTreeSet<PathBuf>,
+    last_event: Instant,
+}
+
+impl DirectoryWatcher {
+    pub fn new<P: AsRef<Path>>(dir: P) -> Result<Self> {
+        let dir = dir.as_ref();
+        let (sender, events) = channel();
+        let mut watcher = notify::recommended_watcher(sender)?;
+        watcher
+            .watch(dir, RecursiveMode::Recursive)
+            .map_err(|e| anyhow::anyhow!("Failed to watch {:?}: {}", dir, e))?;
+        Ok(DirectoryWatcher {
+            _watcher: watcher,
+            events,
+            pending: BTreeSet::new(),
+            last_event: Instant::now(),
+        })
+    }
+
+    fn record(&mut self, event: notify::Result<Event>) {
+        let Ok(event) = event else {
+            return;
+        };
+        if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)) {
+            return;
+        }
+        for path in event.paths {
+            if let Some(meta_path) = meta_path_for(&path) {
+                self.pending.insert(meta_path);
+                self.last_event = Instant::now();
+            }
+        }
+    }
+
+    fn take_settled(&mut self) -> Vec<PathBuf> {
+        if self.pending.is_empty() || self.last_event.elapsed() < WATCH_SETTLE {
+            return Vec::new();
+        }
+        std::mem::take(&mut self.pending).into_iter().collect()
+    }
+
+    /// Meta files changed since the last call, once the directory has been
+    /// quiet for [`WATCH_SETTLE`]; empty otherwise. Doesn't block.
+    pub fn take_changed(&mut self) -> Vec<PathBuf> {
+        while let Ok(event) = self.events.try_recv() {
+            self.record(event);
+        }
+        self.take_settled()
+    }
+
+    /// Block until meta files have changed and the directory has settled
+    pub fn wait_changed(&mut self) -> Result<Vec<PathBuf>> {
+        loop {
+            match self.events.recv_timeout(WATCH_SETTLE) {
+                Ok(event) => self.record(event),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => anyhow::bail!("Directory watcher stopped"),
+            }
+            let changed = self.take_settled();
+            if !changed.is_empty() {
+                return Ok(changed);
+            }
+        }
+    }
+}
+
+/// The meta file a changed path stands for
+fn meta_path_for(path: &Path) -> Option<PathBuf> {
+    match path.extension()?.to_str()? {
+        "sigmf-meta" => Some(path.to_path_buf()),
+        "sigmf-data" => Some(path.with_extension("sigmf-meta")),
+        _ => None,
+    }
+}
+
+/// What an incremental update changed
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WatchUpdate {
+    /// Recordings not in the dataset before
+    pub added_files: usize,
+    /// Recordings whose rows were re-read
+    pub updated_files: usize,
+    /// Recordings whose files are gone, with their rows
+    pub removed_files: usize,
+    /// Files that couldn't be read, e.g. still being written; they're
+    /// picked up again on their next change
+    pub errors: Vec<(PathBuf, String)>,
+}
+
+impl WatchUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.added_files == 0 && self.updated_files == 0 && self.removed_files == 0
+    }
+
+    /// One-line description for status bars and CLI output
+    pub fn summary(&self) -> String {
+        let mut summary = format!("{} added, {} updated, {} removed", self.added_files, self.updated_files, self.removed_files);
+        if !self.errors.is_empty() {
+            summary.push_str(&format!(", {} unreadable", self.errors.len()));
+        }
+        summary
+    }
+}
+
+/// Bring `dataset`, scanned from `root`, up to date with the `changed` meta
+/// files: their rows are replaced by freshly parsed ones (with `profiles`
+/// applied), rows of files that no longer exist are dropped, and new files
+/// are appended. Rows are matched on their meta file's path, and the
+/// segment columns are then regrouped as a full scan would.
+pub fn update_dataset(
+    dataset: DataFrame,
+    root: &Path,
+    changed: &[PathBuf],
+    options: &DatasetOptions,
+    profiles: &[SensorProfile],
+) -> Result<(DataFrame, WatchUpdate)> {
+    let row_paths: Vec<Option<PathBuf>> = SigMFDataset::row_meta_paths(&dataset, root)?
+        .into_iter()
+        .map(|path| path.map(|path| normalize(&path)))
+        .collect();
+    let known: HashSet<&PathBuf> = row_paths.iter().flatten().collect();
+    let mut update = WatchUpdate::default();
+    let mut stale = HashSet::new();
+    let mut touched = HashSet::new();
+    let mut new_rows: Option<DataFrame> = None;
+    for path in changed {
+        let normalized = normalize(path);
+        let is_known = known.contains(&normalized);
+        if !path.exists() {
+            if is_known {
+                update.removed_files += 1;
+                stale.insert(normalized.clone());
+                touched.insert(normalized);
+            }
+            continue;
+        }
+        let parsed = SigMFDataset::from_files_with_options(&[path], options).and_then(|mut rows| {
+            let meta_path = relative_meta_path(path, root);
+            rows.with_column(Series::new(META_PATH_COLUMN.into(), vec![meta_path; rows.height()]))?;
+            apply_sensor_profiles(rows, profiles)
+        });
+        match parsed {
+            Ok(rows) => {
+                new_rows = Some(match new_rows {
+                    Some(existing) => append_rows(existing, rows)?,
+                    None => rows,
+                });
+                if is_known {
+                    update.updated_files += 1;
+                    stale.insert(normalized.clone());
+                } else {
+                    update.added_files += 1;
+                }
+                touched.insert(normalized);
+            }
+            Err(e) => update.errors.push((path.clone(), e.to_string())),
+        }
+    }
+
+    let mut dataset = dataset;
+    if !stale.is_empty() {
+        let keep: BooleanChunked = row_paths.iter().map(|path| !path.as_ref().is_some_and(|path| stale.contains(path))).collect();
+        dataset = dataset.filter(&keep)?;
+    }
+    if let Some(rows) = new_rows {
+        dataset = append_rows(dataset, rows)?;
+    }
+    let dataset = regroup_segments(dataset, root, &touched)?;
+    Ok((dataset, update))
+}
+
+/// Regroup the segment columns of the recordings in the directories of the
+/// `changed` meta files (normalized paths), now that files there have been
+/// added, rewritten or removed. Segments never span directories, so this
+/// gives the whole dataset the columns a full scan would.
+fn regroup_segments(mut dataset: DataFrame, root: &Path, changed: &HashSet<PathBuf>) -> Result<DataFrame> {
+    if changed.is_empty() || dataset.column("segment_group").is_err() {
+        return Ok(dataset);
+    }
+    let dirs: HashSet<&Path> = changed.iter().filter_map(|path| path.parent()).collect();
+    let row_paths: Vec<Option<PathBuf>> = SigMFDataset::row_meta_paths(&dataset, root)?
+        .into_iter()
+        .map(|path| path.map(|path| normalize(&path)).filter(|path| path.parent().is_some_and(|dir| dirs.contains(dir))))
+        .collect();
+
+    let mut recordings: BTreeMap<PathBuf, Option<Segment>> = BTreeMap::new();
+    for (path, segment) in row_paths.iter().zip(dataset_segments(&dataset, &row_paths)?) {
+        if let Some(path) = path {
+            recordings.entry(path.clone()).or_insert(segment);
+        }
+    }
+    // A row numbered 0 outside a segment may be the first of one, or have no
+    // ds:sequence_num at all; only its meta file tells. Changed files are
+    // read again, and so are older files beside numbered ones.
+    let read_segment = |path: &Path| SigMFParser::from_meta_file(path).ok().and_then(|parser| Segment::from_parser(path, &parser));
+    for (path, segment) in recordings.iter_mut() {
+        if segment.is_none() && changed.contains(path) {
+            *segment = read_segment(path);
+        }
+    }
+    let numbered_dirs: HashSet<PathBuf> = recordings
+        .iter()
+        .filter(|(_, segment)| segment.is_some())
+        .filter_map(|(path, _)| path.parent().map(Path::to_path_buf))
+        .collect();
+    for (path, segment) in recordings.iter_mut() {
+        if segment.is_none() && !changed.contains(path) && path.parent().is_some_and(|dir| numbered_dirs.contains(dir)) {
+            *segment = read_segment(path);
+        }
+    }
+
+    let grouped = group_segments(recordings.into_values().flatten().collect());
+    let mut segment_of = HashMap::new();
+    for recording in &grouped {
+        for (idx, segment) in recording.segments.iter().enumerate() {
+            segment_of.insert(segment.meta_path.clone(), (recording.name(), idx as u32, recording.segments.len() as u32, recording.duration_s()));
+        }
+    }
+    let column = |name: &str, dtype: DataType| -> Result<Series> { Ok(dataset.column(name)?.cast(&dtype)?) };
+    let (durations, groups, indexes, counts, stitched) = (
+        column("duration_s", DataType::Float64)?,
+        column("segment_group", DataType::String)?,
+        column("segment_index", DataType::UInt32)?,
+        column("segment_count", DataType::UInt32)?,
+        column("stitched_duration_s", DataType::Float64)?,
+    );
+    let (durations, groups, indexes, counts, stitched) = (durations.f64()?, groups.str()?, indexes.u32()?, counts.u32()?, stitched.f64()?);
+    let height = dataset.height();
+    let (mut new_groups, mut new_indexes, mut new_counts, mut new_stitched) =
+        (Vec::with_capacity(height), Vec::with_capacity(height), Vec::with_capacity(height), Vec::with_capacity(height));
+    for (row, path) in row_paths.iter().enumerate() {
+        // Rows outside the changed directories keep their columns
+        let (group, index, count, duration) = match path {
+            Some(path) => match segment_of.get(path) {
+                Some((group, index, count, duration)) => (Some(group.clone()), Some(*index), Some(*count), Some(*duration)),
+                None => (Some(String::new()), Some(0), Some(1), durations.get(row)),
+            },
+            None => (groups.get(row).map(str::to_string), indexes.get(row), counts.get(row), stitched.get(row)),
+        };
+        new_groups.push(group);
+        new_indexes.push(index);
+        new_counts.push(count);
+        new_stitched.push(duration);
+    }
+    dataset.with_column(Series::new("segment_group".into(), new_groups))?;
+    dataset.with_column(Series::new("segment_index".into(), new_indexes))?;
+    dataset.with_column(Series::new("segment_count".into(), new_counts))?;
+    dataset.with_column(Series::new("stitched_duration_s".into(), new_stitched))?;
+    Ok(dataset)
+}
@@ -0,0 +1,170 @@
+use anyhow::Result;
+use polars::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// Column listing the watchlist entries each row matched, comma-separated
+pub const WATCH_MATCH_COLUMN: &str = "watch_match";
+
+/// One signal of interest. Every criterion given must match; an entry with
+/// none matches nothing.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WatchEntry {
+    pub name: String,
+    /// Inclusive range for the signal's frequency: `sig_center_freq_hz`, or
+    /// `center_freq_hz` for rows without one
+    pub freq_range_hz: Option<(f64, f64)>,
+    /// `label` (the annotation's `core:label`), case-insensitive
+    pub label: Option<String>,
+    /// `sig_uuid`, case-insensitive
+    pub uuid: Option<String>,
+}
+
+impl WatchEntry {
+    fn is_empty(&self) -> bool {
+        self.freq_range_hz.is_none() && self.label.is_none() && self.uuid.is_none()
+    }
+
+    fn matches(&self, freq_hz: Option<f64>, label: Option<&str>, uuid: Option<&str>) -> bool {
+        let eq = |want: &Option<String>, have: Option<&str>| match want {
+            Some(want) => have.is_some_and(|have| have.eq_ignore_ascii_case(want)),
+            None => true,
+        };
+        !self.is_empty()
+            && self.freq_range_hz.is_none_or(|(min, max)| freq_hz.is_some_and(|f| (min..=max).contains(&f)))
+            && eq(&self.label, label)
+            && eq(&self.uuid, uuid)
+    }
+}
+
+/// A list of signals of interest, kept as a CSV file so it can be maintained
+/// in a spreadsheet. Recognized headers (all optional, any order):
+/// `name`, `freq_min_hz`/`freq_max_hz` (or `freq_min_mhz`/`freq_max_mhz`),
+/// `label` and `uuid`. Other columns are ignored.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Watchlist {
+    /// File stem, e.g. `priority` for `priority.csv`
+    pub name: String,
+    pub path: PathBuf,
+    pub entries: Vec<WatchEntry>,
+}
+
+impl Watchlist {
+    pub fn from_csv<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let df = LazyCsvReader::new(path)
+            .with_has_header(true)
+            .finish()
+            .and_then(|lf| lf.collect())
+            .map_err(|e| anyhow::anyhow!("Failed to read watchlist {:?}: {}", path, e))?;
+
+        let text = |name: &str| -> Result<Option<Vec<Option<String>>>> {
+            match df.column(name) {
+                Ok(column) => Ok(Some(column.cast(&DataType::String)?.str()?.iter().map(|v| {
+                    v.map(str::trim).filter(|v| !v.is_empty()).map(str::to_string)
+                }).collect())),
+                Err(_) => Ok(None),
+            }
+        };
+        // Hz, or MHz columns scaled to Hz
+        let freq = |hz: &str, mhz: &str| -> Result<Vec<Option<f64>>> {
+            let (column, scale) = match (df.column(hz), df.column(mhz)) {
+                (Ok(column), _) => (column, 1.0),
+                (_, Ok(column)) => (column, 1e6),
+                _ => return Ok(vec![None; df.height()]),
+            };
+            Ok(column.cast(&DataType::Float64)?.f64()?.iter().map(|v| v.map(|v| v * scale)).collect())
+        };
+
+        let names = text("name")?;
+        let labels = text("label")?;
+        let uuids = text("uuid")?;
+        let mins = freq("freq_min_hz", "freq_min_mhz")?;
+        let maxs = freq("freq_max_hz", "freq_max_mhz")?;
+        let at = |column: &Option<Vec<Option<String>>>, row: usize| column.as_ref().and_then(|c| c[row].clone());
+
+        let mut entries = Vec::with_capacity(df.height());
+        for row in 0..df.height() {
+            // A single bound is open-ended on the other side
+            let freq_range_hz = match (mins[row], maxs[row]) {
+                (None, None) => None,
+                (min, max) => Some((min.unwrap_or(f64::NEG_INFINITY), max.unwrap_or(f64::INFINITY))),
+            };
+            let entry = WatchEntry {
+                name: at(&names, row).unwrap_or_else(|| format!("row {}", row + 2)),
+                freq_range_hz,
+                label: at(&labels, row),
+                uuid: at(&uuids, row),
+            };
+            if !entry.is_empty() {
+                entries.push(entry);
+            }
+        }
+        Ok(Watchlist {
+            name: path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+            path: path.to_path_buf(),
+            entries,
+        })
+    }
+}
+
+/// For each row of `df`, the (watchlist, entry) index pairs it matches.
+/// Columns the dataset lacks never match.
+pub fn watch_matches(df: &DataFrame, watchlists: &[Watchlist]) -> Result<Vec<Vec<(usize, usize)>>> {
+    let f64_column = |name: &str| -> Result<Option<Float64Chunked>> {
+        match df.column(name) {
+            Ok(column) => Ok(Some(column.cast(&DataType::Float64)?.f64()?.clone())),
+            Err(_) => Ok(None),
+        }
+    };
+    let str_column = |name: &str| -> Result<Option<StringChunked>> {
+        match df.column(name) {
+            Ok(column) => Ok(Some(column.cast(&DataType::String)?.str()?.clone())),
+            Err(_) => Ok(None),
+        }
+    };
+    let sig_freqs = f64_column("sig_center_freq_hz")?;
+    let center_freqs = f64_column("center_freq_hz")?;
+    let labels = str_column("label")?;
+    let uuids = str_column("sig_uuid")?;
+
+    let mut matches = Vec::with_capacity(df.height());
+    for row in 0..df.height() {
+        // The parser writes 0 for a missing signal frequency
+        let freq = sig_freqs
+            .as_ref()
+            .and_then(|c| c.get(row))
+            .filter(|f| *f != 0.0)
+            .or_else(|| center_freqs.as_ref().and_then(|c| c.get(row)));
+        let label = labels.as_ref().and_then(|c| c.get(row));
+        let uuid = uuids.as_ref().and_then(|c| c.get(row));
+        let mut row_matches = Vec::new();
+        for (list_idx, list) in watchlists.iter().enumerate() {
+            for (entry_idx, entry) in list.entries.iter().enumerate() {
+                if entry.matches(freq, label, uuid) {
+                    row_matches.push((list_idx, entry_idx));
+                }
+            }
+        }
+        matches.push(row_matches);
+    }
+    Ok(matches)
+}
+
+/// Rows matching any watchlist entry, with the `watch_match` column naming
+/// the entries as `list/entry`
+pub fn filter_watchlists(df: &DataFrame, watchlists: &[Watchlist]) -> Result<DataFrame> {
+    let matches = watch_matches(df, watchlists)?;
+    let names: Vec<String> = matches
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|&(list, entry)| format!("{}/{}", watchlists[list].name, watchlists[list].entries[entry].name))
+                .collect::<Vec<_>>()
+                .join(",")
+        })
+        .collect();
+    let mask: BooleanChunked = matches.iter().map(|row| !row.is_empty()).collect();
+    let mut df = df.clone();
+    df.with_column(Series::new(WATCH_MATCH_COLUMN.into(), names))?;
+    Ok(df.filter(&mask)?)
+}
@@ -0,0 +1,310 @@
+//! Demodulation of analog signals (narrowband FM, broadcast FM and AM) to
+//! audio.
+//!
+//! The slice of the band around `offset_hz` is channelized by
+//! [`ChannelizedReader`] (mixed down to baseband, low-pass filtered to
+//! `bandwidth_hz` and decimated to an intermediate rate), demodulated there, filtered to the audio band and
+//! resampled to `audio_rate`. FM is demodulated from the phase difference of
+//! consecutive samples; broadcast FM then gets its de-emphasis. AM is the
+//! envelope with its carrier (the mean) removed. The result is scaled so
+//! its peak sits just under full scale.
+
+use crate::dsp::{ChannelParams, ChannelizedReader, Fir, Resampler};
+use crate::parser::{SampleReader, SigMFParser};
+use anyhow::Result;
+use byteorder::{LittleEndian, WriteBytesExt};
+use num_complex::Complex;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+// Samples read per I/O call
+const READ_CHUNK: usize = 1 << 18;
+// Peak level of the normalized audio
+const AUDIO_PEAK: f32 = 0.9;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DemodMode {
+    /// Narrowband FM: two-way radio, 12.5 or 25 kHz channels
+    #[default]
+    Nbfm,
+    /// Broadcast FM, mono, with de-emphasis
+    Wbfm,
+    Am,
+}
+
+impl DemodMode {
+    pub const ALL: [DemodMode; 3] = [DemodMode::Nbfm, DemodMode::Wbfm, DemodMode::Am];
+
+    pub fn from_string(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "nbfm" | "fm" => Ok(DemodMode::Nbfm),
+            "wbfm" => Ok(DemodMode::Wbfm),
+            "am" => Ok(DemodMode::Am),
+            other => anyhow::bail!("Unknown demodulation mode {:?}; expected nbfm, wbfm or am", other),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DemodMode::Nbfm => "nbfm",
+            DemodMode::Wbfm => "wbfm",
+            DemodMode::Am => "am",
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            DemodMode::Nbfm => "NBFM",
+            DemodMode::Wbfm => "WBFM",
+            DemodMode::Am => "AM",
+        }
+    }
+
+    /// Channel bandwidth used when none is given
+    pub fn default_bandwidth_hz(&self) -> f64 {
+        match self {
+            DemodMode::Nbfm => 12_500.0,
+            DemodMode::Wbfm => 200_000.0,
+            DemodMode::Am => 10_000.0,
+        }
+    }
+
+    /// Upper edge of the audio
+    fn audio_cutoff_hz(&self, bandwidth_hz: f64) -> f64 {
+        match self {
+            // Mono audio stops at 15 kHz, below the stereo pilot
+            DemodMode::Wbfm => 15_000.0,
+            DemodMode::Nbfm | DemodMode::Am => bandwidth_hz / 2.0,
+        }
+    }
+
+    /// The mode suggested by a row's `ml_fm_prob`/`ml_am_prob` and
+    /// bandwidth: AM if it's the likelier, else FM, broadcast when the
+    /// signal is wider than 100 kHz
+    pub fn guess(fm_prob: f64, am_prob: f64, bandwidth_hz: Option<f64>) -> Self {
+        if am_prob > fm_prob {
+            DemodMode::Am
+        } else if bandwidth_hz.is_some_and(|bw| bw > 100_000.0) {
+            DemodMode::Wbfm
+        } else {
+            DemodMode::Nbfm
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DemodParams {
+    pub mode: DemodMode,
+    /// Frequency of the slice relative to the recording's center
+    pub offset_hz: f64,
+    pub bandwidth_hz: f64,
+    pub audio_rate: u32,
+    /// WBFM de-emphasis time constant: 75 µs in the Americas and Korea, 50
+    /// µs elsewhere
+    pub deemphasis_us: f64,
+}
+
+impl Default for DemodParams {
+    fn default() -> Self {
+        DemodParams {
+            mode: DemodMode::Nbfm,
+            offset_hz: 0.0,
+            bandwidth_hz: DemodMode::Nbfm.default_bandwidth_hz(),
+            audio_rate: 48_000,
+            deemphasis_us: 75.0,
+        }
+    }
+}
+
+/// Mono audio samples in [-1, 1]
+#[derive(Debug, Clone, Default)]
+pub struct Audio {
+    pub samples: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+impl Audio {
+    pub fn duration_s(&self) -> f64 {
+        self.samples.len() as f64 / self.sample_rate.max(1) as f64
+    }
+}
+
+/// Demodulate `count` samples of `reader` starting at `start`. `progress`
+/// is called with the fraction done now and then; an error from it stops
+/// the run.
+pub fn demodulate(
+    reader: &mut dyn SampleReader,
+    sample_rate: f64,
+    start: u64,
+    count: u64,
+    params: &DemodParams,
+    mut progress: impl FnMut(f32) -> Result<()>,
+) -> Result<Audio> {
+    if sample_rate <= 0.0 {
+        anyhow::bail!("Demodulation needs a sample rate");
+    }
+    if params.bandwidth_hz <= 0.0 || params.audio_rate == 0 {
+        anyhow::bail!("The bandwidth and audio rate must be positive");
+    }
+    if params.offset_hz.abs() >= sample_rate / 2.0 {
+        anyhow::bail!("The slice at {:+.0} Hz is outside the recorded band (±{:.0} Hz)", params.offset_hz, sample_rate / 2.0);
+    }
+    let end = reader.num_samples().min(start.saturating_add(count));
+    if start >= end {
+        anyhow::bail!("No samples from {} on", start);
+    }
+
+    // Decimate as far as the channel and the audio rate both allow
+    let channel_params = ChannelParams {
+        offset_hz: params.offset_hz,
+        bandwidth_hz: params.bandwidth_hz,
+        decimation: Some(((sample_rate / params.bandwidth_hz.max(params.audio_rate as f64)).floor() as usize).max(1)),
+    };
+    let mut channel = ChannelizedReader::new(reader, sample_rate, &channel_params)?;
+    let channel_rate = channel.output_rate();
+    let mut demodulator = Demodulator::new(params, channel_rate);
+    let audio_cutoff = params.mode.audio_cutoff_hz(params.bandwidth_hz).min(0.45 * params.audio_rate as f64);
+    let mut audio_filter = Fir::<f32>::low_pass(audio_cutoff, channel_rate, 1);
+
+    let decimation = channel.decimation() as u64;
+    let (first, last) = (start.div_ceil(decimation), end.div_ceil(decimation));
+    let mut demodulated = Vec::new();
+    let mut offset = first;
+    while offset < last {
+        progress((offset - first) as f32 / (last - first) as f32)?;
+        let narrow = channel.read_samples(offset, ((last - offset) as usize).min(READ_CHUNK))?;
+        if narrow.is_empty() {
+            break;
+        }
+        let before = demodulated.len();
+        demodulator.process(&narrow, &mut demodulated);
+        let mut filtered = Vec::with_capacity(demodulated.len() - before);
+        audio_filter.process(&demodulated[before..], &mut filtered);
+        demodulated.truncate(before);
+        demodulated.extend(filtered);
+        offset += narrow.len() as u64;
+    }
+    if params.mode == DemodMode::Am {
+        let mean = demodulated.iter().sum::<f32>() / demodulated.len().max(1) as f32;
+        demodulated.iter_mut().for_each(|sample| *sample -= mean);
+    }
+    let mut samples = Vec::new();
+    Resampler::new(channel_rate, params.audio_rate as f64).process(&demodulated, &mut samples);
+    let peak = samples.iter().fold(0.0f32, |peak, sample| peak.max(sample.abs()));
+    if peak > 0.0 {
+        samples.iter_mut().for_each(|sample| *sample *= AUDIO_PEAK / peak);
+    }
+    progress(1.0)?;
+    Ok(Audio { samples, sample_rate: params.audio_rate })
+}
+
+/// An annotation worth listening to, from its analog classifier outputs
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalogSignal {
+    pub sample_start: u64,
+    pub sample_count: u64,
+    /// `ds:sigCenterFreq`, else the middle of the annotation's edges
+    pub freq_hz: Option<f64>,
+    pub bandwidth_hz: Option<f64>,
+    pub mode: DemodMode,
+    /// The larger of `ds:analogFmProb` and `ds:analogAmProb`
+    pub probability: f64,
+}
+
+/// The annotation of `parser` most likely to be an analog signal, if any
+/// has an FM or AM probability above zero
+pub fn likeliest_analog(parser: &SigMFParser) -> Option<AnalogSignal> {
+    let annotations = parser.metadata.annotations.as_ref()?;
+    annotations
+        .iter()
+        .filter_map(|annotation| {
+            let fm = annotation.analog_fm_prob.unwrap_or(0.0);
+            let am = annotation.analog_am_prob.unwrap_or(0.0);
+            let edges = annotation.freq_lower_edge.zip(annotation.freq_upper_edge);
+            let bandwidth_hz = annotation.sig_bandwidth.or(edges.map(|(low, high)| high - low)).filter(|bw| *bw > 0.0);
+            (fm.max(am) > 0.0).then(|| AnalogSignal {
+                sample_start: annotation.sample_start,
+                sample_count: annotation.sample_count,
+                freq_hz: annotation.sig_center_freq.or(edges.map(|(low, high)| (low + high) / 2.0)),
+                bandwidth_hz,
+                mode: DemodMode::guess(fm, am, bandwidth_hz),
+                probability: fm.max(am),
+            })
+        })
+        .max_by(|a, b| a.probability.total_cmp(&b.probability))
+}
+
+/// [`demodulate`] a recording, all of it unless `count` is given
+pub fn demodulate_recording(
+    meta_path: &Path,
+    start: u64,
+    count: Option<u64>,
+    params: &DemodParams,
+    progress: impl FnMut(f32) -> Result<()>,
+) -> Result<Audio> {
+    let parser = SigMFParser::from_meta_file(meta_path)?;
+    let mut reader = parser.open_reader()?;
+    demodulate(&mut reader, parser.sample_rate(), start, count.unwrap_or(u64::MAX), params, progress)
+}
+
+/// Write `audio` as a mono 16-bit PCM WAV file
+pub fn write_wav(path: &Path, audio: &Audio) -> Result<()> {
+    let data_size = (audio.samples.len() * 2) as u32;
+    let mut writer = BufWriter::new(File::create(path)?);
+    writer.write_all(b"RIFF")?;
+    writer.write_u32::<LittleEndian>(36 + data_size)?;
+    writer.write_all(b"WAVEfmt ")?;
+    writer.write_u32::<LittleEndian>(16)?;
+    writer.write_u16::<LittleEndian>(1)?; // PCM
+    writer.write_u16::<LittleEndian>(1)?; // mono
+    writer.write_u32::<LittleEndian>(audio.sample_rate)?;
+    writer.write_u32::<LittleEndian>(audio.sample_rate * 2)?;
+    writer.write_u16::<LittleEndian>(2)?;
+    writer.write_u16::<LittleEndian>(16)?;
+    writer.write_all(b"data")?;
+    writer.write_u32::<LittleEndian>(data_size)?;
+    for sample in &audio.samples {
+        writer.write_i16::<LittleEndian>((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Baseband to audio at the channel rate, carrying state across chunks
+struct Demodulator {
+    mode: DemodMode,
+    previous: Complex<f32>,
+    /// De-emphasis filter coefficient and state, WBFM only
+    deemphasis: f32,
+    deemphasized: f32,
+}
+
+impl Demodulator {
+    fn new(params: &DemodParams, channel_rate: f64) -> Self {
+        let tau = params.deemphasis_us * 1e-6;
+        Demodulator {
+            mode: params.mode,
+            previous: Complex::new(0.0, 0.0),
+            deemphasis: if tau > 0.0 { (1.0 - (-1.0 / (channel_rate * tau)).exp()) as f32 } else { 1.0 },
+            deemphasized: 0.0,
+        }
+    }
+
+    fn process(&mut self, input: &[Complex<f32>], output: &mut Vec<f32>) {
+        for sample in input {
+            let value = match self.mode {
+                DemodMode::Am => sample.norm(),
+                DemodMode::Nbfm | DemodMode::Wbfm => (sample * self.previous.conj()).arg(),
+            };
+            self.previous = *sample;
+            output.push(match self.mode {
+                DemodMode::Wbfm => {
+                    self.deemphasized += self.deemphasis * (value - self.deemphasized);
+                    self.deemphasized
+                }
+                DemodMode::Nbfm | DemodMode::Am => value,
+            });
+        }
+    }
+}
@@ -0,0 +1,301 @@
+//! Energy detection of bursts in a recording's IQ data.
+//!
+//! The recording is cut into windows of `window` samples and each window's
+//! mean power taken in dB relative to full scale. Unless given, the noise
+//! floor is the [`NOISE_FLOOR_PERCENTILE`]th percentile of those powers.
+//! Windows more than `threshold_db` above it are burst windows; runs of them
+//! closer than `merge_gap_s` are joined, and bursts shorter than
+//! `min_duration_s` dropped. Each burst's bandwidth is measured on its
+//! averaged spectrum, from the lowest to the highest bin within
+//! `bandwidth_db` of the peak that is also `threshold_db` above the noise,
+//! so both tones of an FSK burst count.
+//!
+//! Detections can be written back as annotations carrying the same
+//! `ds:sigCenterFreq`/`ds:sigBandwidth`/`ds:snr` fields as the sensors' own
+//! detector, so each burst becomes a row of the dataset.
+
+use crate::parser::sigmf::{sort_annotations, update_meta, Journal};
+use crate::parser::{SampleReader, SigMFParser};
+use crate::viz::spectrogram::hann_window;
+use crate::viz::NOISE_FLOOR_PERCENTILE;
+use anyhow::Result;
+use num_complex::Complex;
+use polars::prelude::*;
+use rustfft::FftPlanner;
+use serde_json::{json, Value};
+use std::path::{Path, PathBuf};
+
+/// `core:generator` of annotations written by [`write_bursts`], so a rerun
+/// can replace them
+pub const DETECT_GENERATOR: &str = "sig_viewer detect";
+
+// Samples read per I/O call while measuring window powers
+const READ_CHUNK: usize = 1 << 20;
+// FFTs averaged at most for a burst's spectrum
+const MAX_SPECTRUM_AVERAGES: u64 = 64;
+
+#[derive(Debug, Clone)]
+pub struct DetectParams {
+    /// Samples per power window, the time resolution of the detection
+    pub window: usize,
+    /// dB above the noise floor a window must reach
+    pub threshold_db: f32,
+    /// Noise floor in dBFS per window; estimated when `None`
+    pub noise_floor_db: Option<f32>,
+    /// Bursts shorter than this are dropped
+    pub min_duration_s: f64,
+    /// Bursts separated by less than this are joined
+    pub merge_gap_s: f64,
+    /// FFT size of the bandwidth measurement
+    pub fft_size: usize,
+    /// dB below the spectral peak that bounds the measured bandwidth
+    pub bandwidth_db: f32,
+}
+
+impl Default for DetectParams {
+    fn default() -> Self {
+        DetectParams {
+            window: 1024,
+            threshold_db: 10.0,
+            noise_floor_db: None,
+            min_duration_s: 0.0,
+            merge_gap_s: 0.0,
+            fft_size: 1024,
+            bandwidth_db: 10.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Burst {
+    pub start_sample: u64,
+    pub sample_count: u64,
+    pub duration_s: f64,
+    /// Mean power over the burst, dBFS
+    pub power_db: f32,
+    pub snr_db: f32,
+    /// Absolute when the recording's center frequency is known, else
+    /// relative to baseband
+    pub center_freq_hz: f64,
+    pub bandwidth_hz: f64,
+}
+
+impl Burst {
+    pub fn stop_sample(&self) -> u64 {
+        self.start_sample + self.sample_count
+    }
+
+    pub fn freq_lower_edge(&self) -> f64 {
+        self.center_freq_hz - self.bandwidth_hz / 2.0
+    }
+
+    pub fn freq_upper_edge(&self) -> f64 {
+        self.center_freq_hz + self.bandwidth_hz / 2.0
+    }
+}
+
+/// The bursts found in one recording
+#[derive(Debug, Clone)]
+pub struct Detection {
+    pub meta_path: PathBuf,
+    pub noise_floor_db: f32,
+    pub bursts: Vec<Burst>,
+}
+
+/// Find the bursts of `reader`. `progress` is called with the fraction done
+/// now and then; an error from it stops the scan.
+pub fn detect_bursts(
+    reader: &mut dyn SampleReader,
+    sample_rate: f64,
+    center_freq: Option<f64>,
+    params: &DetectParams,
+    mut progress: impl FnMut(f32) -> Result<()>,
+) -> Result<(f32, Vec<Burst>)> {
+    if sample_rate <= 0.0 {
+        anyhow::bail!("Burst detection needs a sample rate");
+    }
+    let window = params.window.max(1);
+    let powers = window_powers(reader, window, &mut progress)?;
+    if powers.is_empty() {
+        return Ok((params.noise_floor_db.unwrap_or(f32::NEG_INFINITY), Vec::new()));
+    }
+    let noise_floor_db = params.noise_floor_db.unwrap_or_else(|| percentile(&powers, NOISE_FLOOR_PERCENTILE));
+    let threshold = noise_floor_db + params.threshold_db;
+
+    // Runs of windows above the threshold, as [first, last + 1)
+    let merge_gap = (params.merge_gap_s * sample_rate / window as f64).ceil() as usize;
+    let mut runs: Vec<(usize, usize)> = Vec::new();
+    for (idx, _) in powers.iter().enumerate().filter(|(_, power)| **power >= threshold) {
+        match runs.last_mut() {
+            Some((_, end)) if idx <= *end + merge_gap => *end = idx + 1,
+            _ => runs.push((idx, idx + 1)),
+        }
+    }
+
+    let num_samples = reader.num_samples();
+    let mut bursts = Vec::new();
+    for (first, end) in runs {
+        let start_sample = (first * window) as u64;
+        let sample_count = ((end * window) as u64).min(num_samples) - start_sample;
+        let duration_s = sample_count as f64 / sample_rate;
+        if duration_s < params.min_duration_s {
+            continue;
+        }
+        let linear: f64 = powers[first..end].iter().map(|db| 10f64.powf(*db as f64 / 10.0)).sum::<f64>() / (end - first) as f64;
+        let power_db = (10.0 * linear.log10()) as f32;
+        let (offset_hz, bandwidth_hz) = measure_bandwidth(reader, start_sample, sample_count, sample_rate, noise_floor_db, params)?;
+        bursts.push(Burst {
+            start_sample,
+            sample_count,
+            duration_s,
+            power_db,
+            snr_db: power_db - noise_floor_db,
+            center_freq_hz: center_freq.unwrap_or(0.0) + offset_hz,
+            bandwidth_hz,
+        });
+    }
+    progress(1.0)?;
+    Ok((noise_floor_db, bursts))
+}
+
+pub fn detect_recording(meta_path: &Path, params: &DetectParams, progress: impl FnMut(f32) -> Result<()>) -> Result<Detection> {
+    let parser = SigMFParser::from_meta_file(meta_path)?;
+    let mut reader = parser.open_reader()?;
+    let (noise_floor_db, bursts) = detect_bursts(&mut reader, parser.sample_rate(), parser.center_frequency(), params, progress)?;
+    Ok(Detection { meta_path: meta_path.to_path_buf(), noise_floor_db, bursts })
+}
+
+/// Add a recording's bursts to its annotations, journaled. With `replace`,
+/// annotations from an earlier run (see [`DETECT_GENERATOR`]) are removed
+/// first. Returns the number of annotations written.
+pub fn write_bursts(detection: &Detection, label: Option<&str>, replace: bool, journal: &Journal) -> Result<usize> {
+    if detection.bursts.is_empty() && !replace {
+        return Ok(0);
+    }
+    let meta_path = &detection.meta_path;
+    update_meta(meta_path, journal, |metadata| {
+        let object = metadata
+            .as_object_mut()
+            .ok_or_else(|| anyhow::anyhow!("Metadata of {:?} is not a JSON object", meta_path))?;
+        let annotations = object
+            .entry("annotations")
+            .or_insert_with(|| json!([]))
+            .as_array_mut()
+            .ok_or_else(|| anyhow::anyhow!("annotations of {:?} is not a list", meta_path))?;
+        if replace {
+            annotations.retain(|annotation| annotation.get("core:generator").and_then(Value::as_str) != Some(DETECT_GENERATOR));
+        }
+        for burst in &detection.bursts {
+            let mut annotation = json!({
+                "core:sample_start": burst.start_sample,
+                "core:sample_count": burst.sample_count,
+                "core:freq_lower_edge": burst.freq_lower_edge(),
+                "core:freq_upper_edge": burst.freq_upper_edge(),
+                "core:generator": DETECT_GENERATOR,
+                "ds:sigCenterFreq": burst.center_freq_hz,
+                "ds:sigBandwidth": burst.bandwidth_hz,
+                "ds:sig_power_dbfs": burst.power_db,
+                "ds:snr": burst.snr_db,
+            });
+            if let Some(label) = label {
+                annotation["core:label"] = json!(label);
+            }
+            annotations.push(annotation);
+        }
+        sort_annotations(metadata);
+        Ok(())
+    })?;
+    Ok(detection.bursts.len())
+}
+
+/// One row per burst of `detections`
+pub fn bursts_dataframe(detections: &[Detection]) -> Result<DataFrame> {
+    let bursts = || detections.iter().flat_map(|d| d.bursts.iter().map(move |burst| (d, burst)));
+    let names: Vec<String> = bursts()
+        .map(|(d, _)| d.meta_path.file_name().unwrap_or_default().to_string_lossy().to_string())
+        .collect();
+    Ok(DataFrame::new(vec![
+        Series::new("meta_filename".into(), names),
+        Series::new("start_sample".into(), bursts().map(|(_, b)| b.start_sample).collect::<Vec<_>>()),
+        Series::new("stop_sample".into(), bursts().map(|(_, b)| b.stop_sample()).collect::<Vec<_>>()),
+        Series::new("duration_s".into(), bursts().map(|(_, b)| b.duration_s).collect::<Vec<_>>()),
+        Series::new("center_freq_hz".into(), bursts().map(|(_, b)| b.center_freq_hz).collect::<Vec<_>>()),
+        Series::new("bandwidth_hz".into(), bursts().map(|(_, b)| b.bandwidth_hz).collect::<Vec<_>>()),
+        Series::new("power_db".into(), bursts().map(|(_, b)| b.power_db as f64).collect::<Vec<_>>()),
+        Series::new("snr_db".into(), bursts().map(|(_, b)| b.snr_db as f64).collect::<Vec<_>>()),
+        Series::new("noise_floor_db".into(), bursts().map(|(d, _)| d.noise_floor_db as f64).collect::<Vec<_>>()),
+    ])?)
+}
+
+/// Mean power of each whole window, in dBFS
+fn window_powers(reader: &mut dyn SampleReader, window: usize, progress: &mut impl FnMut(f32) -> Result<()>) -> Result<Vec<f32>> {
+    let num_samples = reader.num_samples();
+    let chunk = (READ_CHUNK / window).max(1) * window;
+    let mut powers = Vec::with_capacity((num_samples / window as u64) as usize);
+    let mut offset = 0u64;
+    while offset < num_samples {
+        progress(offset as f32 / num_samples as f32)?;
+        let samples = reader.read_samples(offset, chunk)?;
+        if samples.is_empty() {
+            break;
+        }
+        // A partial last window still counts, as its own mean
+        powers.extend(samples.chunks(window).map(|w| {
+            let mean = w.iter().map(|s| s.norm_sqr() as f64).sum::<f64>() / w.len() as f64;
+            (10.0 * (mean + 1e-20).log10()) as f32
+        }));
+        offset += samples.len() as u64;
+    }
+    Ok(powers)
+}
+
+/// Frequency offset from the middle of the band and bandwidth of a burst, on
+/// its averaged spectrum
+fn measure_bandwidth(
+    reader: &mut dyn SampleReader,
+    start: u64,
+    count: u64,
+    sample_rate: f64,
+    noise_floor_db: f32,
+    params: &DetectParams,
+) -> Result<(f64, f64)> {
+    // Short bursts get a smaller FFT rather than none
+    let fft_size = params.fft_size.min(count as usize).max(16).next_power_of_two();
+    let ffts = (count / fft_size as u64).clamp(1, MAX_SPECTRUM_AVERAGES);
+    let step = count.saturating_sub(fft_size as u64) / ffts.saturating_sub(1).max(1);
+    let window = hann_window(fft_size);
+    let fft = FftPlanner::new().plan_fft_forward(fft_size);
+    let mut spectrum = vec![0.0f64; fft_size];
+    let mut buffer = vec![Complex::new(0.0f32, 0.0); fft_size];
+    for n in 0..ffts {
+        let samples = reader.read_samples(start + n * step, fft_size)?;
+        for (i, slot) in buffer.iter_mut().enumerate() {
+            *slot = samples.get(i).copied().unwrap_or_default() * window[i];
+        }
+        fft.process(&mut buffer);
+        for (bin, sum) in spectrum.iter_mut().enumerate() {
+            *sum += buffer[(bin + fft_size / 2) % fft_size].norm_sqr() as f64;
+        }
+    }
+
+    // White noise of the floor's power gives each bin its power times the
+    // window's energy, per FFT
+    let window_energy: f64 = window.iter().map(|w| (w * w) as f64).sum();
+    let noise_bin = 10f64.powf(noise_floor_db as f64 / 10.0) * window_energy * ffts as f64;
+    let peak = (0..fft_size).max_by(|a, b| spectrum[*a].total_cmp(&spectrum[*b])).unwrap_or(fft_size / 2);
+    let level = (spectrum[peak] * 10f64.powf(-params.bandwidth_db as f64 / 10.0))
+        .max(noise_bin * 10f64.powf(params.threshold_db as f64 / 10.0))
+        .min(spectrum[peak]);
+    let low = (0..fft_size).find(|bin| spectrum[*bin] >= level).unwrap_or(peak);
+    let high = (0..fft_size).rev().find(|bin| spectrum[*bin] >= level).unwrap_or(peak);
+    let bin_hz = sample_rate / fft_size as f64;
+    let center_bin = (low + high) as f64 / 2.0;
+    Ok(((center_bin - (fft_size / 2) as f64) * bin_hz, (high - low + 1) as f64 * bin_hz))
+}
+
+fn percentile(values: &[f32], percentile: f32) -> f32 {
+    let mut sorted = values.to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let idx = ((percentile.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f32).round() as usize;
+    sorted[idx]
+}
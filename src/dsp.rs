@@ -0,0 +1,276 @@
+//! Working on a sub-band of a wideband recording: mixing it down to
+//! baseband, low-pass filtering and decimating it.
+//!
+//! [`ChannelizedReader`] does all three on the fly as a [`SampleReader`],
+//! so anything that reads samples (the plots, [`crate::demod`],
+//! [`crate::parser::sigmf::channelize_recording`]) can work on the channel
+//! as if it were the recording. Large decimations run as a cascade of stages
+//! of at most [`MAX_STAGE_DECIMATION`], so narrow channels stay sharp
+//! without thousands of taps. Each output sample is centered on the input
+//! sample it decimates, so sample indices scale by the decimation without
+//! an offset.
+
+use crate::parser::SampleReader;
+use anyhow::Result;
+use num_complex::Complex;
+use std::f64::consts::PI;
+use std::ops::{Add, Mul};
+
+/// Output rate over bandwidth when the decimation is picked automatically,
+/// leaving room for the filter's transition band
+pub const CHANNEL_OVERSAMPLE: f64 = 1.25;
+
+/// Largest decimation of one filter stage
+pub const MAX_STAGE_DECIMATION: usize = 8;
+
+// Longest filter, in taps
+const MAX_TAPS: usize = 1023;
+
+/// The sub-band to extract
+#[derive(Debug, Clone, PartialEq)]
+pub struct ChannelParams {
+    /// Center of the channel relative to the recording's center
+    pub offset_hz: f64,
+    /// Passband width, centered on the channel
+    pub bandwidth_hz: f64,
+    /// Input samples per output sample; by default the largest that keeps
+    /// the output rate [`CHANNEL_OVERSAMPLE`] times the bandwidth
+    pub decimation: Option<usize>,
+}
+
+impl ChannelParams {
+    /// The decimation used at `sample_rate`. An automatic one is rounded
+    /// down to a product of factors up to [`MAX_STAGE_DECIMATION`].
+    pub fn resolve_decimation(&self, sample_rate: f64) -> Result<usize> {
+        if self.bandwidth_hz <= 0.0 || self.bandwidth_hz > sample_rate {
+            anyhow::bail!("The channel bandwidth must be positive and at most the sample rate ({} Hz)", sample_rate);
+        }
+        if self.offset_hz.abs() >= sample_rate / 2.0 {
+            anyhow::bail!("The channel at {:+.0} Hz is outside the recorded band (±{:.0} Hz)", self.offset_hz, sample_rate / 2.0);
+        }
+        match self.decimation {
+            Some(0) => anyhow::bail!("The decimation must be at least 1"),
+            Some(decimation) if sample_rate / (decimation as f64) < self.bandwidth_hz => anyhow::bail!(
+                "Decimating by {} leaves {:.0} Hz, less than the {:.0} Hz bandwidth",
+                decimation,
+                sample_rate / decimation as f64,
+                self.bandwidth_hz
+            ),
+            Some(decimation) => Ok(decimation),
+            None => {
+                let most = ((sample_rate / (self.bandwidth_hz * CHANNEL_OVERSAMPLE)).floor() as usize).max(1);
+                Ok((1..=most).rev().find(|d| stage_factors(*d).iter().all(|f| *f <= MAX_STAGE_DECIMATION)).unwrap_or(1))
+            }
+        }
+    }
+}
+
+/// Taps of a Blackman-windowed sinc low-pass filter cutting off at
+/// `cutoff_hz`, long enough for a transition band about `transition_hz` wide
+pub fn low_pass_taps(cutoff_hz: f64, transition_hz: f64, sample_rate: f64) -> Vec<f32> {
+    let cutoff = (cutoff_hz / sample_rate).min(0.5);
+    // A Blackman window needs about 5.5 / width taps for a transition band
+    // of that width
+    let len = ((5.5 * sample_rate / transition_hz.max(1e-9)) as usize).clamp(15, MAX_TAPS) | 1;
+    let middle = (len / 2) as f64;
+    let mut taps: Vec<f32> = (0..len)
+        .map(|n| {
+            let x = n as f64 - middle;
+            let sinc = if x == 0.0 { 2.0 * cutoff } else { (2.0 * PI * cutoff * x).sin() / (PI * x) };
+            let phase = 2.0 * PI * n as f64 / (len - 1) as f64;
+            let blackman = 0.42 - 0.5 * phase.cos() + 0.08 * (2.0 * phase).cos();
+            (sinc * blackman) as f32
+        })
+        .collect();
+    let gain: f32 = taps.iter().sum();
+    taps.iter_mut().for_each(|tap| *tap /= gain);
+    taps
+}
+
+/// Streaming FIR filter keeping every `decimation`th output. Outputs
+/// computed before the filter has seen a full window of input are dropped;
+/// their ramp up from silence would otherwise stand out.
+pub struct Fir<T> {
+    taps: Vec<f32>,
+    decimation: usize,
+    /// The last `taps.len() - 1` inputs, then the current chunk
+    history: Vec<T>,
+    /// Inputs to skip before the next output
+    phase: usize,
+    /// Outputs still to drop
+    warmup: usize,
+}
+
+impl<T: Copy + Default + Add<Output = T> + Mul<f32, Output = T>> Fir<T> {
+    pub fn new(taps: Vec<f32>, decimation: usize) -> Self {
+        let decimation = decimation.max(1);
+        let len = taps.len().max(1);
+        Fir { history: vec![T::default(); len - 1], taps, decimation, phase: 0, warmup: (len - 1).div_ceil(decimation) }
+    }
+
+    /// Low-pass at `cutoff_hz` with a transition band a fifth of it wide
+    pub fn low_pass(cutoff_hz: f64, sample_rate: f64, decimation: usize) -> Self {
+        Self::new(low_pass_taps(cutoff_hz, 0.2 * cutoff_hz, sample_rate), decimation)
+    }
+
+    pub fn process(&mut self, input: &[T], output: &mut Vec<T>) {
+        let len = self.taps.len();
+        self.history.extend_from_slice(input);
+        let mut idx = self.phase;
+        while idx + len <= self.history.len() {
+            let sum = convolve(&self.history[idx..idx + len], &self.taps);
+            if self.warmup > 0 {
+                self.warmup -= 1;
+            } else {
+                output.push(sum);
+            }
+            idx += self.decimation;
+        }
+        let consumed = self.history.len() - (len - 1);
+        self.phase = idx - consumed;
+        self.history.drain(..consumed);
+    }
+}
+
+/// Streaming linear-interpolation resampler. The input should already be
+/// band-limited below the lower of the two Nyquist frequencies.
+pub struct Resampler<T> {
+    /// Input samples per output sample
+    step: f64,
+    /// Position of the next output, counted from `last`
+    position: f64,
+    last: Option<T>,
+}
+
+impl<T: Copy + Add<Output = T> + Mul<f32, Output = T>> Resampler<T> {
+    pub fn new(from_rate: f64, to_rate: f64) -> Self {
+        Resampler { step: from_rate / to_rate, position: 0.0, last: None }
+    }
+
+    pub fn process(&mut self, input: &[T], output: &mut Vec<T>) {
+        let buffer: Vec<T> = self.last.into_iter().chain(input.iter().copied()).collect();
+        if buffer.is_empty() {
+            return;
+        }
+        while self.position + 1.0 < buffer.len() as f64 {
+            let below = self.position.floor() as usize;
+            let frac = (self.position - below as f64) as f32;
+            output.push(buffer[below] * (1.0 - frac) + buffer[below + 1] * frac);
+            self.position += self.step;
+        }
+        self.position -= (buffer.len() - 1) as f64;
+        self.last = buffer.last().copied();
+    }
+}
+
+/// A channel of another reader, mixed down, filtered and decimated as it's
+/// read. Output sample `n` is centered on input sample `n * decimation`.
+pub struct ChannelizedReader<R> {
+    inner: R,
+    sample_rate: f64,
+    decimation: usize,
+    /// Mixer step in radians per input sample
+    step: f64,
+    stages: Vec<Stage>,
+}
+
+struct Stage {
+    taps: Vec<f32>,
+    decimation: usize,
+}
+
+impl<R: SampleReader> ChannelizedReader<R> {
+    pub fn new(inner: R, sample_rate: f64, params: &ChannelParams) -> Result<Self> {
+        let decimation = params.resolve_decimation(sample_rate)?;
+        // Each stage only has to keep what would alias into the channel out of
+        // its own output band; the last one cuts the channel itself, rolling
+        // off over at most the room the output rate leaves
+        let factors = stage_factors(decimation);
+        let mut rate = sample_rate;
+        let mut stages = Vec::new();
+        let pass = params.bandwidth_hz / 2.0;
+        for (idx, factor) in factors.iter().enumerate() {
+            let out_rate = rate / *factor as f64;
+            let stop = if idx + 1 == factors.len() {
+                (pass * CHANNEL_OVERSAMPLE).min(out_rate / 2.0).max(pass * 1.1)
+            } else {
+                out_rate - pass
+            };
+            stages.push(Stage { taps: low_pass_taps((pass + stop) / 2.0, stop - pass, rate), decimation: *factor });
+            rate = out_rate;
+        }
+        Ok(ChannelizedReader { inner, sample_rate, decimation, step: -2.0 * PI * params.offset_hz / sample_rate, stages })
+    }
+
+    pub fn decimation(&self) -> usize {
+        self.decimation
+    }
+
+    pub fn output_rate(&self) -> f64 {
+        self.sample_rate / self.decimation as f64
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Outputs `start..start + count` of stage `stage` (0 is the mixed
+    /// input), with silence before the first input sample and after the last
+    fn read_stage(&mut self, stage: usize, start: i64, count: usize) -> Result<Vec<Complex<f32>>> {
+        if stage == 0 {
+            let mut samples = vec![Complex::new(0.0, 0.0); count];
+            let first = start.max(0);
+            let end = start + count as i64;
+            if first < end && (first as u64) < self.inner.num_samples() {
+                let read = self.inner.read_samples(first as u64, (end - first) as usize)?;
+                for (i, sample) in read.into_iter().enumerate() {
+                    let n = first + i as i64;
+                    let phase = (self.step * n as f64) % (2.0 * PI);
+                    samples[(n - start) as usize] = sample * Complex::new(phase.cos() as f32, phase.sin() as f32);
+                }
+            }
+            return Ok(samples);
+        }
+        let (len, decimation) = {
+            let Stage { taps, decimation } = &self.stages[stage - 1];
+            (taps.len(), *decimation)
+        };
+        let half = (len / 2) as i64;
+        let input = self.read_stage(stage - 1, start * decimation as i64 - half, (count.max(1) - 1) * decimation + len)?;
+        let taps = &self.stages[stage - 1].taps;
+        Ok((0..count).map(|m| convolve(&input[m * decimation..m * decimation + len], taps)).collect())
+    }
+}
+
+impl<R: SampleReader> SampleReader for ChannelizedReader<R> {
+    fn num_samples(&self) -> u64 {
+        self.inner.num_samples().div_ceil(self.decimation as u64)
+    }
+
+    fn read_samples(&mut self, start: u64, count: usize) -> Result<Vec<Complex<f32>>> {
+        let count = (count as u64).min(self.num_samples().saturating_sub(start)) as usize;
+        if count == 0 {
+            return Ok(Vec::new());
+        }
+        self.read_stage(self.stages.len(), start as i64, count)
+    }
+}
+
+/// `decimation` split into stages, largest first, each at most
+/// [`MAX_STAGE_DECIMATION`] unless a prime factor is larger
+fn stage_factors(mut decimation: usize) -> Vec<usize> {
+    let mut factors = Vec::new();
+    while decimation > 1 {
+        let factor = (2..=MAX_STAGE_DECIMATION.min(decimation))
+            .rev()
+            .find(|f| decimation.is_multiple_of(*f))
+            .unwrap_or(decimation);
+        factors.push(factor);
+        decimation /= factor;
+    }
+    factors
+}
+
+fn convolve<T: Copy + Default + Add<Output = T> + Mul<f32, Output = T>>(window: &[T], taps: &[f32]) -> T {
+    window.iter().zip(taps.iter().rev()).fold(T::default(), |sum, (x, tap)| sum + *x * *tap)
+}
@@ -0,0 +1,119 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use sig_viewer::data_ops::{activity_heatmap, ActivityHeatmap, TimeBucket};
+use sig_viewer::viz::Colormap;
+
+const LABEL_WIDTH: f32 = 120.0;
+const HEADER_HEIGHT: f32 = 60.0;
+const CELL_HEIGHT: f32 = 18.0;
+
+// handle sensor activity heatmap
+impl SigViewerApp {
+    pub(crate) fn render_activity_window(&mut self, ctx: &egui::Context) {
+        if !self.show_activity_window {
+            return;
+        }
+        let mut open = true;
+        egui::Window::new("Sensor Activity")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([800.0, 400.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Columns:");
+                    let mut changed = ui.selectable_value(&mut self.activity_bucket, TimeBucket::HourOfDay, "Hour of day").changed();
+                    changed |= ui.selectable_value(&mut self.activity_bucket, TimeBucket::Day, "Day").changed();
+                    if changed {
+                        self.activity_heatmap = None;
+                    }
+                });
+
+                if self.activity_heatmap.is_none() {
+                    if let Some(ref dataset) = self.filtered_dataset {
+                        match activity_heatmap(dataset, self.activity_bucket) {
+                            Ok(heatmap) => self.activity_heatmap = Some(heatmap),
+                            Err(e) => {
+                                ui.colored_label(egui::Color32::RED, format!("Cannot build heatmap: {}", e));
+                                return;
+                            }
+                        }
+                    }
+                }
+                let Some(ref heatmap) = self.activity_heatmap else {
+                    ui.label("Load a dataset first");
+                    return;
+                };
+                if heatmap.skipped_rows > 0 {
+                    ui.label(format!("{} rows without a parseable capture_datetime were skipped", heatmap.skipped_rows));
+                }
+                if heatmap.sensors.is_empty() {
+                    ui.label("No recordings with timestamps in the current view");
+                    return;
+                }
+
+                egui::ScrollArea::both().show(ui, |ui| {
+                    draw_heatmap(ui, heatmap);
+                });
+            });
+        if !open {
+            self.show_activity_window = false;
+        }
+    }
+}
+
+fn draw_heatmap(ui: &mut egui::Ui, heatmap: &ActivityHeatmap) {
+    let cell_width: f32 = if heatmap.buckets.len() > 60 { 10.0 } else { 24.0 };
+    let size = egui::vec2(
+        LABEL_WIDTH + cell_width * heatmap.buckets.len() as f32,
+        HEADER_HEIGHT + CELL_HEIGHT * heatmap.sensors.len() as f32,
+    );
+    let (response, painter) = ui.allocate_painter(size, egui::Sense::hover());
+    let origin = response.rect.min + egui::vec2(LABEL_WIDTH, HEADER_HEIGHT);
+    let text_color = ui.visuals().text_color();
+    let max_count = heatmap.max_count().max(1) as f32;
+
+    // Column labels, thinned out so they don't overlap
+    let label_every = (40.0 / cell_width).ceil() as usize;
+    for (col, bucket) in heatmap.buckets.iter().enumerate().step_by(label_every.max(1)) {
+        painter.text(
+            origin + egui::vec2(col as f32 * cell_width, -4.0),
+            egui::Align2::LEFT_BOTTOM,
+            bucket,
+            egui::FontId::monospace(9.0),
+            text_color,
+        );
+    }
+
+    let mut hovered = None;
+    for (row, sensor) in heatmap.sensors.iter().enumerate() {
+        let y = origin.y + row as f32 * CELL_HEIGHT;
+        painter.text(
+            egui::pos2(response.rect.left() + LABEL_WIDTH - 6.0, y + CELL_HEIGHT / 2.0),
+            egui::Align2::RIGHT_CENTER,
+            sensor,
+            egui::FontId::proportional(12.0),
+            text_color,
+        );
+        for (col, count) in heatmap.counts[row].iter().enumerate() {
+            let rect = egui::Rect::from_min_size(
+                egui::pos2(origin.x + col as f32 * cell_width, y),
+                egui::vec2(cell_width - 1.0, CELL_HEIGHT - 1.0),
+            );
+            // Empty cells stay gray so dead periods stand out from low activity
+            let color = if *count == 0 {
+                egui::Color32::from_gray(60)
+            } else {
+                let [r, g, b] = Colormap::Viridis.map(*count as f32 / max_count);
+                egui::Color32::from_rgb(r, g, b)
+            };
+            painter.rect_filled(rect, 0.0, color);
+            if response.hover_pos().is_some_and(|pos| rect.contains(pos)) {
+                hovered = Some((sensor, &heatmap.buckets[col], *count));
+            }
+        }
+    }
+
+    if let Some((sensor, bucket, count)) = hovered {
+        response.on_hover_text(format!("{} @ {}: {} recordings", sensor, bucket, count));
+    }
+}
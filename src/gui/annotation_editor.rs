@@ -0,0 +1,182 @@
+use super::meta_fields::{build_object, drafts_of, edit_fields, read_editable, save_bar, FieldDraft, NewField};
+use crate::SigViewerApp;
+use eframe::egui;
+use serde_json::Value;
+use sig_viewer::parser::sigmf::{check_annotation, sort_annotations, MetaSnapshot};
+use std::path::Path;
+
+/// Core annotation fields offered when adding a field
+const CORE_ANNOTATION_FIELDS: [&str; 7] = [
+    "core:sample_start",
+    "core:sample_count",
+    "core:freq_lower_edge",
+    "core:freq_upper_edge",
+    "core:label",
+    "core:comment",
+    "core:generator",
+];
+
+/// State of the Edit Annotations window: every annotation of one recording
+/// as editable text, written back in one go on Save
+pub struct AnnotationEditor {
+    snapshot: MetaSnapshot,
+    annotations: Vec<Vec<FieldDraft>>,
+    /// Annotation shown in the field grid
+    current: usize,
+    new_field: NewField,
+    error: Option<String>,
+    dirty: bool,
+}
+
+impl AnnotationEditor {
+    fn new(snapshot: MetaSnapshot) -> Self {
+        let annotations = snapshot
+            .value
+            .get("annotations")
+            .and_then(Value::as_array)
+            .map(|annotations| {
+                annotations
+                    .iter()
+                    .filter_map(Value::as_object)
+                    .map(drafts_of)
+                    .collect()
+            })
+            .unwrap_or_default();
+        AnnotationEditor {
+            snapshot,
+            annotations,
+            current: 0,
+            new_field: NewField::default(),
+            error: None,
+            dirty: false,
+        }
+    }
+
+    /// The metadata with the edited annotations in place of the originals.
+    /// Everything outside `annotations` is left exactly as read.
+    fn build(&self) -> anyhow::Result<Value> {
+        let mut annotations = Vec::with_capacity(self.annotations.len());
+        for (idx, fields) in self.annotations.iter().enumerate() {
+            let annotation = build_object(fields).map_err(|e| anyhow::anyhow!("Annotation {}: {}", idx + 1, e))?;
+            check_annotation(&annotation).map_err(|e| anyhow::anyhow!("Annotation {}: {}", idx + 1, e))?;
+            annotations.push(Value::Object(annotation));
+        }
+        let mut meta = self.snapshot.value.clone();
+        let Some(object) = meta.as_object_mut() else {
+            anyhow::bail!("Metadata is not a JSON object");
+        };
+        object.insert("annotations".to_string(), Value::Array(annotations));
+        sort_annotations(&mut meta);
+        Ok(meta)
+    }
+
+    /// One-line summary for the annotation list
+    fn summary(fields: &[FieldDraft]) -> String {
+        let field = |key: &str| fields.iter().find(|f| f.key == key).map(|f| f.text.as_str());
+        let mut summary = format!("@{}", field("core:sample_start").unwrap_or("?"));
+        if let Some(count) = field("core:sample_count") {
+            summary.push_str(&format!(" +{}", count));
+        }
+        if let Some(label) = field("core:label").filter(|label| !label.is_empty()) {
+            summary.push_str(&format!("  {}", label));
+        }
+        summary
+    }
+}
+
+// handle editing a recording's annotations
+impl SigViewerApp {
+    pub(crate) fn open_annotation_editor(&mut self, meta_path: &Path) {
+        match read_editable(meta_path) {
+            Ok(snapshot) => self.annotation_editor = Some(AnnotationEditor::new(snapshot)),
+            Err(e) => self.error_message = Some(e),
+        }
+    }
+
+    pub(crate) fn render_annotation_editor(&mut self, ctx: &egui::Context) {
+        let read_only = self.read_only;
+        let Some(ref mut editor) = self.annotation_editor else {
+            return;
+        };
+        let mut open = true;
+        let (mut save, mut revert) = (false, false);
+        let title = format!(
+            "Edit Annotations: {}",
+            editor.snapshot.path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default()
+        );
+        egui::Window::new(title)
+            .id(egui::Id::new("annotation_editor"))
+            .open(&mut open)
+            .resizable(true)
+            .default_size([720.0, 460.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} annotations", editor.annotations.len()));
+                    ui.weak("Save rewrites only the annotations; everything else in the file is kept as it is.");
+                });
+                ui.separator();
+
+                ui.horizontal_top(|ui| {
+                    // Annotation list
+                    ui.vertical(|ui| {
+                        ui.set_width(200.0);
+                        egui::ScrollArea::vertical().id_salt("annotation_list").max_height(300.0).show(ui, |ui| {
+                            for (idx, fields) in editor.annotations.iter().enumerate() {
+                                let label = format!("{}. {}", idx + 1, AnnotationEditor::summary(fields));
+                                if ui.selectable_label(editor.current == idx, label).clicked() {
+                                    editor.current = idx;
+                                }
+                            }
+                        });
+                        ui.horizontal(|ui| {
+                            if ui.button("Add").clicked() {
+                                editor.annotations.push(vec![FieldDraft::new("core:sample_start", "0")]);
+                                editor.current = editor.annotations.len() - 1;
+                                editor.dirty = true;
+                            }
+                            let can_delete = editor.current < editor.annotations.len();
+                            if ui.add_enabled(can_delete, egui::Button::new("Delete")).clicked() {
+                                editor.annotations.remove(editor.current);
+                                editor.current = editor.current.min(editor.annotations.len().saturating_sub(1));
+                                editor.dirty = true;
+                            }
+                        });
+                    });
+                    ui.separator();
+
+                    // Fields of the current annotation
+                    ui.vertical(|ui| {
+                        let Some(fields) = editor.annotations.get_mut(editor.current) else {
+                            ui.label("No annotations. Add one to start.");
+                            return;
+                        };
+                        let id = ("annotation", editor.current);
+                        editor.dirty |= edit_fields(ui, id, fields, &mut editor.new_field, &CORE_ANNOTATION_FIELDS, &[]);
+                    });
+                });
+
+                (save, revert) = save_bar(ui, editor.dirty, read_only, editor.error.as_deref());
+            });
+
+        if save {
+            match editor.build() {
+                Ok(value) => {
+                    let snapshot = editor.snapshot.clone();
+                    if let Some(written) = self.save_meta(&snapshot, &value) {
+                        let current = self.annotation_editor.as_ref().map_or(0, |editor| editor.current);
+                        let mut reloaded = AnnotationEditor::new(written);
+                        reloaded.current = current.min(reloaded.annotations.len().saturating_sub(1));
+                        self.annotation_editor = Some(reloaded);
+                        self.status_message.push_str(" (reload the dataset to refresh the table)");
+                    }
+                }
+                Err(e) => editor.error = Some(e.to_string()),
+            }
+        } else if revert {
+            let path = editor.snapshot.path.clone();
+            self.open_annotation_editor(&path);
+        } else if !open {
+            self.annotation_editor = None;
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use sig_viewer::data_ops::{set_band_map, BandMap, BAND_COLUMN};
+
+// handle the band map setting and the band dropdown next to the filters
+impl SigViewerApp {
+    /// Classify rows with the configured band map file, or the built-in
+    /// bands without one. Returns false (with an error shown) if it can't
+    /// be read.
+    pub(crate) fn apply_band_map(&mut self) -> bool {
+        let path = self.config.band_map.trim();
+        let map = if path.is_empty() { Ok(BandMap::builtin()) } else { BandMap::from_toml(path) };
+        match map {
+            Ok(map) => {
+                set_band_map(map);
+                self.save_config();
+                true
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Invalid band map: {:#}", e));
+                false
+            }
+        }
+    }
+
+    /// Dropdown of the bands in the dataset, setting the `band` column's
+    /// filter. Hidden for datasets without a `band` column.
+    pub(crate) fn render_band_filter(&mut self, ui: &mut egui::Ui) {
+        let Some(ref dataset) = self.dataset else {
+            return;
+        };
+        let Ok(column) = dataset.column(BAND_COLUMN) else {
+            return;
+        };
+        let current = self.column_filters.get(BAND_COLUMN).map(|text| text.trim().trim_matches('\'').to_string()).unwrap_or_default();
+        let mut selected = None;
+        ui.label("Band:");
+        egui::ComboBox::from_id_salt("band_filter")
+            .selected_text(if current.is_empty() { "All" } else { current.as_str() })
+            .show_ui(ui, |ui| {
+                if ui.selectable_label(current.is_empty(), "All").clicked() {
+                    selected = Some(String::new());
+                }
+                // Only worked out while the list is open
+                let mut bands: Vec<String> = column
+                    .str()
+                    .map(|values| values.into_iter().flatten().filter(|band| !band.is_empty()).map(str::to_string).collect())
+                    .unwrap_or_default();
+                bands.sort();
+                bands.dedup();
+                for band in bands {
+                    if ui.selectable_label(current == band, &band).clicked() {
+                        selected = Some(format!("'{}'", band));
+                    }
+                }
+            });
+        if let Some(text) = selected {
+            self.column_filters.insert(BAND_COLUMN.to_string(), text);
+            self.apply_filters();
+            self.invalidate_cache();
+            self.clear_selection();
+        }
+    }
+}
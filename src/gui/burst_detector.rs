@@ -0,0 +1,168 @@
+use crate::gui::plot::format_frequency;
+use crate::SigViewerApp;
+use eframe::egui;
+use sig_viewer::detect::{detect_recording, write_bursts, DetectParams, Detection};
+use sig_viewer::parser::sigmf::Journal;
+use sig_viewer::workers::{Priority, TaskHandle};
+
+/// State of the Burst Detection window: parameters, scans running on the
+/// worker pool and the bursts found so far
+pub struct BurstDetector {
+    params: DetectParams,
+    label: String,
+    replace: bool,
+    tasks: Vec<TaskHandle<Detection>>,
+    detections: Vec<Detection>,
+    errors: Vec<String>,
+}
+
+// handle detecting bursts in the selected recordings
+impl SigViewerApp {
+    pub(crate) fn open_burst_detector(&mut self) {
+        if self.burst_detector.is_none() {
+            self.burst_detector = Some(BurstDetector {
+                params: DetectParams::default(),
+                label: String::new(),
+                replace: true,
+                tasks: Vec::new(),
+                detections: Vec::new(),
+                errors: Vec::new(),
+            });
+        }
+    }
+
+    pub(crate) fn render_burst_detector(&mut self, ctx: &egui::Context) {
+        let Some(mut detector) = self.burst_detector.take() else {
+            return;
+        };
+        detector.tasks.retain(|task| match task.try_take() {
+            None => true,
+            Some(Ok(detection)) => {
+                detector.detections.push(detection);
+                false
+            }
+            Some(Err(e)) => {
+                detector.errors.push(e.to_string());
+                false
+            }
+        });
+        let selected = self.selected_meta_filenames();
+
+        let mut open = true;
+        let mut detect = false;
+        let mut write = false;
+        egui::Window::new("Burst Detection")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([760.0, 480.0])
+            .show(ctx, |ui| {
+                let params = &mut detector.params;
+                egui::Grid::new("burst_params").num_columns(2).show(ui, |ui| {
+                    ui.label("Window");
+                    ui.add(egui::DragValue::new(&mut params.window).range(16..=1 << 20).suffix(" samples"));
+                    ui.end_row();
+                    ui.label("Threshold");
+                    ui.add(egui::DragValue::new(&mut params.threshold_db).range(0.5..=60.0).speed(0.1).suffix(" dB above the noise"));
+                    ui.end_row();
+                    ui.label("Minimum duration");
+                    ui.add(egui::DragValue::new(&mut params.min_duration_s).range(0.0..=60.0).speed(0.0001).suffix(" s"));
+                    ui.end_row();
+                    ui.label("Merge gaps under");
+                    ui.add(egui::DragValue::new(&mut params.merge_gap_s).range(0.0..=60.0).speed(0.0001).suffix(" s"));
+                    ui.end_row();
+                    ui.label("Bandwidth within");
+                    ui.add(egui::DragValue::new(&mut params.bandwidth_db).range(1.0..=60.0).speed(0.1).suffix(" dB of the peak"));
+                    ui.end_row();
+                });
+                ui.horizontal(|ui| {
+                    let text = format!("Detect in {} selected recording{}", selected.len(), if selected.len() == 1 { "" } else { "s" });
+                    if ui.add_enabled(!selected.is_empty() && detector.tasks.is_empty(), egui::Button::new(text)).clicked() {
+                        detect = true;
+                    }
+                    if !detector.tasks.is_empty() {
+                        ui.spinner();
+                        ui.label(format!("{} scanning", detector.tasks.len()));
+                    }
+                });
+                for error in &detector.errors {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+                if detector.detections.is_empty() {
+                    return;
+                }
+                ui.separator();
+                let total: usize = detector.detections.iter().map(|d| d.bursts.len()).sum();
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} bursts in {} recordings", total, detector.detections.len()));
+                    ui.label("Label:");
+                    ui.add(egui::TextEdit::singleline(&mut detector.label).hint_text("none").desired_width(100.0));
+                    ui.checkbox(&mut detector.replace, "Replace earlier detections");
+                    let enabled = !self.read_only && detector.tasks.is_empty();
+                    if ui.add_enabled(enabled, egui::Button::new("Write Annotations")).on_hover_text("Add the bursts to each recording's annotations, as one journal batch").clicked() {
+                        write = true;
+                    }
+                });
+                egui::ScrollArea::both().show(ui, |ui| {
+                    egui::Grid::new("bursts").striped(true).show(ui, |ui| {
+                        for heading in ["Recording", "Start", "Stop", "Duration", "Center", "Bandwidth", "SNR"] {
+                            ui.strong(heading);
+                        }
+                        ui.end_row();
+                        for detection in &detector.detections {
+                            let name = detection.meta_path.file_name().unwrap_or_default().to_string_lossy();
+                            if detection.bursts.is_empty() {
+                                ui.label(name.as_ref());
+                                ui.weak(format!("no bursts (noise floor {:.1} dB)", detection.noise_floor_db));
+                                ui.end_row();
+                            }
+                            for burst in &detection.bursts {
+                                ui.label(name.as_ref());
+                                ui.label(burst.start_sample.to_string());
+                                ui.label(burst.stop_sample().to_string());
+                                ui.label(format!("{:.6} s", burst.duration_s));
+                                ui.label(format_frequency(burst.center_freq_hz));
+                                ui.label(format_frequency(burst.bandwidth_hz));
+                                ui.label(format!("{:.1} dB", burst.snr_db));
+                                ui.end_row();
+                            }
+                        }
+                    });
+                });
+            });
+
+        if detect {
+            detector.detections.clear();
+            detector.errors.clear();
+            for meta_filename in selected {
+                let Some(meta_path) = self.locate_meta_file(&meta_filename) else {
+                    detector.errors.push(format!("Could not locate {}", meta_filename));
+                    continue;
+                };
+                let params = detector.params.clone();
+                let name = format!("Detect bursts: {}", meta_filename);
+                detector
+                    .tasks
+                    .push(self.workers.submit(&name, Priority::Normal, move |ctx| detect_recording(&meta_path, &params, |f| ctx.report(f))));
+            }
+        }
+        if write {
+            let journal = Journal::begin("GUI burst detection");
+            let label = Some(detector.label.trim()).filter(|label| !label.is_empty());
+            let mut written = 0;
+            for detection in &detector.detections {
+                match write_bursts(detection, label, detector.replace, &journal) {
+                    Ok(count) => written += count,
+                    Err(e) => detector.errors.push(format!("Failed to write {}: {}", detection.meta_path.display(), e)),
+                }
+            }
+            self.status_message = format!("Wrote {} burst annotations (batch {}); reload to see them as rows", written, journal.batch());
+        }
+        if open {
+            self.burst_detector = Some(detector);
+        } else {
+            for task in detector.tasks {
+                self.workers.cancel(task.id());
+            }
+        }
+    }
+}
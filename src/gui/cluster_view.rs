@@ -0,0 +1,325 @@
+use crate::gui::plot;
+use crate::SigViewerApp;
+use eframe::egui;
+use polars::prelude::*;
+use sig_viewer::data_ops::{cluster, default_cluster_columns, with_cluster_column, ClusterMethod, ClusterOptions, Clustering, CLUSTER_COLUMN, DBSCAN_NOISE};
+use sig_viewer::workers::{Priority, TaskHandle};
+
+const AXIS_MARGIN_LEFT: f32 = 60.0;
+const AXIS_MARGIN_BOTTOM: f32 = 20.0;
+const POINT_RADIUS: f32 = 3.0;
+/// How close (in pixels) a click or hover has to be to pick a point
+const PICK_DISTANCE: f32 = 6.0;
+const NOISE_COLOR: egui::Color32 = egui::Color32::from_rgb(150, 150, 150);
+/// Categorical palette (tab10), repeated past ten clusters
+const CLUSTER_COLORS: [egui::Color32; 10] = [
+    egui::Color32::from_rgb(31, 119, 180),
+    egui::Color32::from_rgb(255, 127, 14),
+    egui::Color32::from_rgb(44, 160, 44),
+    egui::Color32::from_rgb(214, 39, 40),
+    egui::Color32::from_rgb(148, 103, 189),
+    egui::Color32::from_rgb(140, 86, 75),
+    egui::Color32::from_rgb(227, 119, 194),
+    egui::Color32::from_rgb(127, 127, 127),
+    egui::Color32::from_rgb(188, 189, 34),
+    egui::Color32::from_rgb(23, 190, 207),
+];
+
+/// Display color of a cluster id; DBSCAN noise is gray
+pub(crate) fn cluster_color(id: i64) -> egui::Color32 {
+    if id == DBSCAN_NOISE {
+        NOISE_COLOR
+    } else {
+        CLUSTER_COLORS[id.rem_euclid(CLUSTER_COLORS.len() as i64) as usize]
+    }
+}
+
+/// State of the Clusters window: feature choice, method parameters, the
+/// clustering running on the worker pool and the scatter plot axes
+pub struct ClusterView {
+    columns: Vec<String>,
+    use_dbscan: bool,
+    k: usize,
+    eps: f64,
+    min_points: usize,
+    standardize: bool,
+    task: Option<TaskHandle<Clustering>>,
+    /// Summary of the last run applied to the dataset
+    result: Option<Result<Clustering, String>>,
+    x_column: Option<String>,
+    y_column: Option<String>,
+}
+
+// handle clustering rows by feature columns
+impl SigViewerApp {
+    pub(crate) fn open_cluster_view(&mut self) {
+        if self.cluster_view.is_none() {
+            let columns = self.dataset.as_ref().map(default_cluster_columns).unwrap_or_default();
+            self.cluster_view = Some(ClusterView {
+                x_column: columns.first().cloned(),
+                y_column: columns.get(1).or(columns.first()).cloned(),
+                columns,
+                use_dbscan: false,
+                k: 5,
+                eps: 0.5,
+                min_points: 5,
+                standardize: true,
+                task: None,
+                result: None,
+            });
+        }
+    }
+
+    pub(crate) fn render_cluster_view(&mut self, ctx: &egui::Context) {
+        let Some(mut view) = self.cluster_view.take() else {
+            return;
+        };
+        if let Some(result) = view.task.as_ref().and_then(|task| task.try_take()) {
+            view.task = None;
+            view.result = Some(self.apply_clustering(result).map_err(|e| e.to_string()));
+        }
+        if view.task.is_some() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+        let numeric_columns: Vec<String> = self
+            .dataset
+            .as_ref()
+            .map(|df| {
+                df.get_columns()
+                    .iter()
+                    .filter(|column| column.dtype().is_numeric() && column.name() != CLUSTER_COLUMN)
+                    .map(|column| column.name().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        let mut open = true;
+        let mut run = false;
+        let mut show_cluster = None;
+        let mut clicked_row = None;
+        egui::Window::new("Clusters")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([800.0, 650.0])
+            .show(ctx, |ui| {
+                ui.collapsing(format!("Features ({} selected)", view.columns.len()), |ui| {
+                    egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                        for name in &numeric_columns {
+                            let mut selected = view.columns.contains(name);
+                            if ui.checkbox(&mut selected, name).changed() {
+                                if selected {
+                                    view.columns.push(name.clone());
+                                } else {
+                                    view.columns.retain(|c| c != name);
+                                }
+                            }
+                        }
+                    });
+                });
+                ui.horizontal(|ui| {
+                    ui.radio_value(&mut view.use_dbscan, false, "k-means");
+                    ui.radio_value(&mut view.use_dbscan, true, "DBSCAN");
+                    if view.use_dbscan {
+                        ui.label("eps:");
+                        ui.add(egui::DragValue::new(&mut view.eps).range(0.001..=1e12).speed(0.01));
+                        ui.label("min points:");
+                        ui.add(egui::DragValue::new(&mut view.min_points).range(1..=1000));
+                    } else {
+                        ui.label("k:");
+                        ui.add(egui::DragValue::new(&mut view.k).range(1..=100));
+                    }
+                    ui.checkbox(&mut view.standardize, "Standardize")
+                        .on_hover_text("Scale each feature to zero mean and unit variance, so eps is in standard deviations");
+                });
+                ui.horizontal(|ui| {
+                    let enabled = view.task.is_none() && !view.columns.is_empty() && self.dataset.is_some();
+                    if ui.add_enabled(enabled, egui::Button::new("Run")).on_hover_text("Cluster every loaded row").clicked() {
+                        run = true;
+                    }
+                    if view.task.is_some() {
+                        ui.spinner();
+                        ui.label("Clustering...");
+                    }
+                });
+
+                match view.result {
+                    Some(Ok(ref clustering)) => {
+                        let mut summary = format!("{} clusters", clustering.num_clusters());
+                        if clustering.skipped_rows > 0 {
+                            summary.push_str(&format!(", {} rows missing a feature", clustering.skipped_rows));
+                        }
+                        if let Some(inertia) = clustering.inertia {
+                            summary.push_str(&format!(", inertia {:.3}", inertia));
+                        }
+                        ui.label(summary);
+                        ui.horizontal_wrapped(|ui| {
+                            for (id, size) in &clustering.sizes {
+                                let name = if *id == DBSCAN_NOISE { "noise".to_string() } else { format!("cluster {}", id) };
+                                let text = egui::RichText::new(format!("■ {}: {}", name, size)).color(cluster_color(*id));
+                                if ui.add(egui::Label::new(text).sense(egui::Sense::click())).on_hover_text("Click to show only these rows").clicked() {
+                                    show_cluster = Some(*id);
+                                }
+                            }
+                        });
+                    }
+                    Some(Err(ref e)) => {
+                        ui.colored_label(egui::Color32::RED, format!("Clustering failed: {}", e));
+                    }
+                    None => {}
+                }
+
+                let Some(dataset) = self.filtered_dataset.clone().filter(|df| df.column(CLUSTER_COLUMN).is_ok()) else {
+                    return;
+                };
+                ui.separator();
+                ui.horizontal(|ui| {
+                    for (label, axis) in [("x:", &mut view.x_column), ("y:", &mut view.y_column)] {
+                        ui.label(label);
+                        egui::ComboBox::from_id_salt(format!("cluster_axis_{}", label))
+                            .selected_text(axis.clone().unwrap_or_else(|| "(none)".to_string()))
+                            .height(400.0)
+                            .show_ui(ui, |ui| {
+                                for name in &numeric_columns {
+                                    ui.selectable_value(axis, Some(name.clone()), name);
+                                }
+                            });
+                    }
+                });
+                if let (Some(x), Some(y)) = (view.x_column.clone(), view.y_column.clone()) {
+                    match scatter_points(&dataset, &x, &y) {
+                        Ok(points) if points.is_empty() => {
+                            ui.label("No rows with both values in the current view");
+                        }
+                        Ok(points) => clicked_row = self.draw_scatter(ui, &points, &dataset, &x, &y),
+                        Err(e) => {
+                            ui.colored_label(egui::Color32::RED, format!("Cannot plot: {}", e));
+                        }
+                    }
+                }
+            });
+
+        if run {
+            self.run_clustering(&mut view);
+        }
+        if open {
+            self.cluster_view = Some(view);
+        } else if let Some(task) = view.task {
+            self.workers.cancel(task.id());
+        }
+        if let Some(id) = show_cluster {
+            self.column_filters.insert(CLUSTER_COLUMN.to_string(), id.to_string());
+            self.apply_filters();
+            self.clear_selection();
+        }
+        if let Some(row) = clicked_row {
+            let modifiers = ctx.input(|i| i.modifiers);
+            self.click_row(row, modifiers);
+        }
+    }
+
+    fn run_clustering(&mut self, view: &mut ClusterView) {
+        let Some(dataset) = self.dataset.clone() else {
+            return;
+        };
+        let method = if view.use_dbscan {
+            ClusterMethod::Dbscan { eps: view.eps, min_points: view.min_points }
+        } else {
+            ClusterMethod::KMeans { k: view.k }
+        };
+        let options = ClusterOptions { columns: view.columns.clone(), method, standardize: view.standardize, seed: None };
+        let name = format!("Cluster {} rows ({})", dataset.height(), options.method.describe());
+        view.task = Some(self.workers.submit(&name, Priority::High, move |ctx| cluster(&dataset, &options, |fraction| ctx.report(fraction))));
+    }
+
+    /// Add the cluster ids to the loaded dataset and refilter
+    fn apply_clustering(&mut self, result: anyhow::Result<Clustering>) -> anyhow::Result<Clustering> {
+        let clustering = result?;
+        let dataset = self.dataset.as_ref().ok_or_else(|| anyhow::anyhow!("The dataset was closed while clustering"))?;
+        // A reload or watch update while clustering leaves the ids for other rows
+        let dataset = with_cluster_column(dataset, &clustering).map_err(|_| anyhow::anyhow!("The dataset changed while clustering; run again"))?;
+        self.dataset = Some(dataset);
+        self.force_refilter();
+        self.status_message = format!("Clustered into {} clusters", clustering.num_clusters());
+        Ok(clustering)
+    }
+
+    /// Draw the points colored by cluster; returns the row of a clicked point
+    fn draw_scatter(&self, ui: &mut egui::Ui, points: &[ScatterPoint], dataset: &DataFrame, x_name: &str, y_name: &str) -> Option<usize> {
+        let size = ui.available_size().max(egui::vec2(300.0, 200.0));
+        let (response, painter) = ui.allocate_painter(size, egui::Sense::click());
+        let rect = egui::Rect::from_min_max(
+            response.rect.min + egui::vec2(AXIS_MARGIN_LEFT, 0.0),
+            response.rect.max - egui::vec2(0.0, AXIS_MARGIN_BOTTOM),
+        );
+        let range = |values: &mut dyn Iterator<Item = f64>| {
+            let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| (min.min(v), max.max(v)));
+            if max > min { (min, max) } else { (min - 0.5, max + 0.5) }
+        };
+        let (x_min, x_max) = range(&mut points.iter().map(|p| p.x));
+        let (y_min, y_max) = range(&mut points.iter().map(|p| p.y));
+        let to_screen = |p: &ScatterPoint| {
+            egui::pos2(
+                rect.left() + ((p.x - x_min) / (x_max - x_min)) as f32 * rect.width(),
+                rect.bottom() - ((p.y - y_min) / (y_max - y_min)) as f32 * rect.height(),
+            )
+        };
+
+        let visuals = ui.visuals();
+        painter.rect_stroke(rect, 0.0, egui::Stroke::new(1.0, visuals.weak_text_color()), egui::StrokeKind::Inside);
+        plot::draw_x_axis(&painter, rect, x_min, x_max, |v| format!("{:.3}", v));
+        plot::draw_y_axis(&painter, rect, y_min, y_max, false, |v| format!("{:.3}", v));
+        let positions: Vec<egui::Pos2> = points.iter().map(to_screen).collect();
+        let highlight = egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 140, 0));
+        for (point, pos) in points.iter().zip(&positions) {
+            let color = point.cluster.map_or(NOISE_COLOR, cluster_color);
+            if self.selected_rows.contains(&point.row) {
+                painter.circle(*pos, POINT_RADIUS + 2.0, color, highlight);
+            } else {
+                painter.circle_filled(*pos, POINT_RADIUS, color);
+            }
+        }
+
+        let pointer = response.hover_pos()?;
+        let idx = positions
+            .iter()
+            .enumerate()
+            .filter(|(_, pos)| pos.distance(pointer) <= PICK_DISTANCE)
+            .min_by(|(_, a), (_, b)| a.distance_sq(pointer).total_cmp(&b.distance_sq(pointer)))
+            .map(|(idx, _)| idx)?;
+        let point = &points[idx];
+        let clicked = response.clicked();
+        let name = dataset
+            .column("meta_filename")
+            .ok()
+            .and_then(|c| c.str().ok().and_then(|s| s.get(point.row).map(str::to_string)))
+            .unwrap_or_default();
+        let cluster = match point.cluster {
+            Some(DBSCAN_NOISE) => "noise".to_string(),
+            Some(id) => id.to_string(),
+            None => "none".to_string(),
+        };
+        response.on_hover_text_at_pointer(format!("{}\nrow {}\n{}: {}\n{}: {}\ncluster: {}", name, point.row + 1, x_name, point.x, y_name, point.y, cluster));
+        clicked.then_some(point.row)
+    }
+}
+
+struct ScatterPoint {
+    row: usize,
+    x: f64,
+    y: f64,
+    cluster: Option<i64>,
+}
+
+/// Rows of `df` with finite `x` and `y` values
+fn scatter_points(df: &DataFrame, x: &str, y: &str) -> anyhow::Result<Vec<ScatterPoint>> {
+    let xs = df.column(x)?.cast(&DataType::Float64)?;
+    let ys = df.column(y)?.cast(&DataType::Float64)?;
+    let clusters = df.column(CLUSTER_COLUMN)?.cast(&DataType::Int64)?;
+    let (xs, ys, clusters) = (xs.f64()?, ys.f64()?, clusters.i64()?);
+    Ok((0..df.height())
+        .filter_map(|row| {
+            let (x, y) = (xs.get(row)?, ys.get(row)?);
+            (x.is_finite() && y.is_finite()).then(|| ScatterPoint { row, x, y, cluster: clusters.get(row) })
+        })
+        .collect())
+}
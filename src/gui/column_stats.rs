@@ -0,0 +1,101 @@
+use crate::gui::plot;
+use crate::SigViewerApp;
+use eframe::egui;
+use sig_viewer::data_ops::{column_stats, ColumnStats};
+
+/// Most frequent values listed for a text column
+const TOP_VALUES: usize = 10;
+
+// handle the Column Stats popup opened from a column header
+impl SigViewerApp {
+    pub(crate) fn open_column_stats(&mut self, column: &str) {
+        self.column_stats_column = Some(column.to_string());
+        self.column_stats = None;
+    }
+
+    pub(crate) fn render_column_stats(&mut self, ctx: &egui::Context) {
+        let Some(column) = self.column_stats_column.clone() else {
+            return;
+        };
+        if self.column_stats.is_none() {
+            if let Some(ref dataset) = self.filtered_dataset {
+                self.column_stats = Some(column_stats(dataset, &column, TOP_VALUES).map_err(|e| e.to_string()));
+            }
+        }
+        let mut open = true;
+        egui::Window::new(format!("Column Stats: {}", column))
+            .id(egui::Id::new("column_stats"))
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| match self.column_stats {
+                Some(Ok(ref stats)) => show_stats(ui, stats),
+                Some(Err(ref e)) => {
+                    ui.colored_label(egui::Color32::RED, e);
+                }
+                None => {
+                    ui.label("Load a dataset first");
+                }
+            });
+        if !open {
+            self.column_stats_column = None;
+            self.column_stats = None;
+        }
+    }
+}
+
+fn show_stats(ui: &mut egui::Ui, stats: &ColumnStats) {
+    ui.weak("Over the rows passing the current filters");
+    egui::Grid::new("column_stats_grid").num_columns(2).striped(true).show(ui, |ui| {
+        let mut row = |name: &str, value: String| {
+            ui.label(name);
+            ui.monospace(value);
+            ui.end_row();
+        };
+        row("Type", stats.dtype.clone());
+        row("Count", stats.count().to_string());
+        row("Nulls", format!("{} of {}", stats.nulls, stats.rows));
+        row("Unique", stats.n_unique.to_string());
+        if let Some(numeric) = stats.numeric {
+            for (name, value) in [("Min", numeric.min), ("Max", numeric.max), ("Mean", numeric.mean), ("Std", numeric.std)] {
+                row(name, value.map_or_else(|| "–".to_string(), format_stat));
+            }
+        }
+    });
+
+    if let Some(ref histogram) = stats.histogram {
+        ui.separator();
+        ui.label(format!("Distribution ({} bins)", histogram.counts.len()));
+        let response = plot::sparkline(ui, &histogram.counts);
+        let hovered = response.hover_pos().map(|pos| ((pos.x - response.rect.left()) / 4.0) as usize);
+        if let Some(bin) = hovered.filter(|bin| *bin < histogram.counts.len()) {
+            let (left, right) = histogram.bin_edges(bin);
+            response.on_hover_text(format!("{} – {}: {}", format_stat(left), format_stat(right), histogram.counts[bin]));
+        }
+        ui.horizontal(|ui| {
+            ui.weak(format_stat(histogram.min));
+            ui.weak("to");
+            ui.weak(format_stat(histogram.max));
+        });
+    }
+
+    if !stats.top_values.is_empty() {
+        ui.separator();
+        ui.label(format!("Top {} values", stats.top_values.len()));
+        egui::Grid::new("column_stats_top").num_columns(2).striped(true).show(ui, |ui| {
+            for (value, count) in &stats.top_values {
+                ui.label(if value.is_empty() { "(empty)" } else { value });
+                ui.monospace(format!("{} ({:.1}%)", count, *count as f64 / stats.count().max(1) as f64 * 100.0));
+                ui.end_row();
+            }
+        });
+    }
+}
+
+/// Whole numbers in full (frequencies, sample counts), others to 4 decimals
+fn format_stat(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{:.0}", value)
+    } else {
+        format!("{:.4}", value)
+    }
+}
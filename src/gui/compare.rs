@@ -0,0 +1,227 @@
+use super::psd_compare::PsdComparison;
+use super::spectrogram_view::{SpectrogramView, ViewSpan};
+use super::RecordingView;
+use crate::{format_cell_value, SigViewerApp};
+use eframe::egui;
+use polars::prelude::*;
+use sig_viewer::parser::sigmf::SegmentedRecording;
+use sig_viewer::workers::WorkerPool;
+use std::path::PathBuf;
+
+const DIFFERENT_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 140, 0);
+
+#[derive(Clone, Copy, PartialEq)]
+enum CompareTab {
+    Spectrum,
+    Spectrograms,
+    Metadata,
+}
+
+/// A column of the two rows: its name, each row's value as shown in the
+/// table, and whether the stored values differ
+struct MetadataField {
+    name: String,
+    values: [String; 2],
+    differs: bool,
+}
+
+/// Both recordings' spectrograms side by side. While linked, zooming or
+/// panning either one shows the same time and frequency span in the other.
+struct SpectrogramPair {
+    views: Vec<SpectrogramView>,
+    /// Each view's span as of the last frame, to tell which one moved
+    spans: Vec<ViewSpan>,
+}
+
+impl SpectrogramPair {
+    fn open(recordings: &[(String, PathBuf); 2], stitched: &[Option<SegmentedRecording>; 2]) -> anyhow::Result<Self> {
+        let views = recordings
+            .iter()
+            .zip(stitched)
+            .map(|((_, path), stitched)| SpectrogramView::open(path, stitched.clone()))
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let spans = views.iter().map(SpectrogramView::view_span).collect();
+        Ok(SpectrogramPair { views, spans })
+    }
+
+    fn show(&mut self, ui: &mut egui::Ui, names: [&str; 2], linked: bool) {
+        ui.columns(2, |columns| {
+            for (idx, ((column, view), (name, letter))) in columns.iter_mut().zip(&mut self.views).zip(names.iter().zip(["A", "B"])).enumerate() {
+                column.push_id(idx, |ui| {
+                    ui.horizontal(|ui| {
+                        ui.strong(letter);
+                        ui.label(*name);
+                    });
+                    view.show(ui);
+                });
+            }
+        });
+        if linked {
+            if let Some(moved) = (0..2).find(|&idx| self.views[idx].view_span() != self.spans[idx]) {
+                let span = self.views[moved].view_span();
+                self.views[1 - moved].set_view_span(&span);
+            }
+        }
+        self.spans = self.views.iter().map(SpectrogramView::view_span).collect();
+    }
+}
+
+/// State of the comparison window for two selected rows: their spectra
+/// overlaid, their spectrograms side by side and a table of their columns
+pub struct RecordingComparison {
+    recordings: [(String, PathBuf); 2],
+    /// Segmented captures the recordings are part of, for the spectrograms
+    stitched: [Option<SegmentedRecording>; 2],
+    tab: CompareTab,
+    psd: PsdComparison,
+    /// Opened the first time the Spectrograms tab is shown
+    spectrograms: Option<Result<SpectrogramPair, String>>,
+    linked: bool,
+    metadata: Vec<MetadataField>,
+    only_differences: bool,
+}
+
+impl RecordingComparison {
+    fn new(recordings: [(String, PathBuf); 2], stitched: [Option<SegmentedRecording>; 2], metadata: Vec<MetadataField>, workers: &WorkerPool) -> Self {
+        let [a, b] = recordings.clone();
+        RecordingComparison {
+            recordings,
+            stitched,
+            tab: CompareTab::Spectrum,
+            psd: PsdComparison::new(a, b, workers),
+            spectrograms: None,
+            linked: true,
+            metadata,
+            only_differences: false,
+        }
+    }
+
+    fn cancel(&mut self, workers: &WorkerPool) {
+        self.psd.cancel(workers);
+    }
+
+    fn show(&mut self, ui: &mut egui::Ui, workers: &WorkerPool) {
+        ui.horizontal(|ui| {
+            ui.selectable_value(&mut self.tab, CompareTab::Spectrum, "Spectrum");
+            ui.selectable_value(&mut self.tab, CompareTab::Spectrograms, "Spectrograms");
+            ui.selectable_value(&mut self.tab, CompareTab::Metadata, "Metadata");
+        });
+        ui.separator();
+        match self.tab {
+            CompareTab::Spectrum => self.psd.show(ui, workers),
+            CompareTab::Spectrograms => self.show_spectrograms(ui),
+            CompareTab::Metadata => self.show_metadata(ui),
+        }
+    }
+
+    fn show_spectrograms(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.linked, "Link zoom")
+            .on_hover_text("Zooming or panning one spectrogram shows the same time (from the start of each recording) and frequency span in the other");
+        let names = [self.recordings[0].0.as_str(), self.recordings[1].0.as_str()];
+        let pair = self
+            .spectrograms
+            .get_or_insert_with(|| SpectrogramPair::open(&self.recordings, &self.stitched).map_err(|e| format!("Cannot open spectrograms: {}", e)));
+        match pair {
+            Ok(pair) => pair.show(ui, names, self.linked),
+            Err(e) => {
+                ui.colored_label(egui::Color32::RED, e.as_str());
+            }
+        }
+    }
+
+    fn show_metadata(&mut self, ui: &mut egui::Ui) {
+        let differing = self.metadata.iter().filter(|field| field.differs).count();
+        ui.horizontal(|ui| {
+            ui.label(format!("{} of {} columns differ", differing, self.metadata.len()));
+            ui.checkbox(&mut self.only_differences, "Only differences");
+        });
+        egui::ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+            egui::Grid::new("compare_metadata").striped(true).num_columns(3).show(ui, |ui| {
+                ui.strong("Column");
+                ui.strong(format!("A: {}", self.recordings[0].0));
+                ui.strong(format!("B: {}", self.recordings[1].0));
+                ui.end_row();
+                for field in self.metadata.iter().filter(|field| field.differs || !self.only_differences) {
+                    if field.differs {
+                        ui.colored_label(DIFFERENT_COLOR, &field.name);
+                        for value in &field.values {
+                            ui.colored_label(DIFFERENT_COLOR, value);
+                        }
+                    } else {
+                        ui.label(&field.name);
+                        for value in &field.values {
+                            ui.label(value);
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+        });
+    }
+}
+
+/// Every column of rows `a` and `b`, compared as stored and shown humanized
+/// unless `raw`
+fn metadata_fields(df: &DataFrame, a: usize, b: usize, raw: bool) -> Vec<MetadataField> {
+    df.get_columns()
+        .iter()
+        .map(|column| MetadataField {
+            name: column.name().to_string(),
+            values: [format_cell_value(column, a, !raw), format_cell_value(column, b, !raw)],
+            differs: format_cell_value(column, a, false) != format_cell_value(column, b, false),
+        })
+        .collect()
+}
+
+// handle comparison of two selected rows
+impl SigViewerApp {
+    /// Open the comparison for the first two selected rows (by table order)
+    pub(crate) fn open_comparison(&mut self) {
+        let Some(df) = self.filtered_dataset.clone() else {
+            return;
+        };
+        let Some(filenames) = df.column("meta_filename").ok().and_then(|column| column.str().ok()) else {
+            return;
+        };
+        let rows: Vec<usize> = self.selected_rows.iter().copied().take(2).collect();
+        let mut recordings = Vec::new();
+        for &row in &rows {
+            let Some(name) = filenames.get(row) else {
+                continue;
+            };
+            match self.locate_meta_file(name) {
+                Some(path) => recordings.push((name.to_string(), path)),
+                None => {
+                    self.error_message = Some(format!("Could not locate {}", name));
+                    return;
+                }
+            }
+        }
+        let Ok(recordings) = <[(String, PathBuf); 2]>::try_from(recordings) else {
+            return;
+        };
+        if let Some(mut old) = self.comparison.take() {
+            old.cancel(&self.workers);
+        }
+        let metadata = metadata_fields(&df, rows[0], rows[1], self.config.raw_values);
+        let stitched = [self.stitched_recording(&recordings[0].1), self.stitched_recording(&recordings[1].1)];
+        self.comparison = Some(RecordingComparison::new(recordings, stitched, metadata, &self.workers));
+    }
+
+    pub(crate) fn render_comparison(&mut self, ctx: &egui::Context) {
+        let Some(ref mut comparison) = self.comparison else {
+            return;
+        };
+        let workers = &self.workers;
+        let mut open = true;
+        egui::Window::new("Compare Recordings")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([1000.0, 650.0])
+            .show(ctx, |ui| comparison.show(ui, workers));
+        if !open {
+            comparison.cancel(workers);
+            self.comparison = None;
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use super::plot::{draw_x_axis, draw_y_axis, format_frequency};
+use super::RecordingView;
+use eframe::egui;
+use sig_viewer::parser::sigmf::SegmentedRecording;
+use sig_viewer::parser::{SampleReader, SigMFParser};
+use sig_viewer::viz::{Constellation, FreqCorrection};
+use std::path::{Path, PathBuf};
+
+const AXIS_MARGIN_LEFT: f32 = 50.0;
+const AXIS_MARGIN_BOTTOM: f32 = 20.0;
+const CORRECTION_POWERS: [u32; 4] = [1, 2, 4, 8];
+
+/// I vs Q scatter for a sample range of one recording
+pub struct ConstellationView {
+    meta_path: PathBuf,
+    sample_rate: f64,
+    total_samples: u64,
+    start_sample: u64,
+    num_samples: usize,
+    correct_frequency: bool,
+    correction_power: u32,
+    constellation: Option<Constellation>,
+    needs_compute: bool,
+    error: Option<String>,
+}
+
+impl RecordingView for ConstellationView {
+    fn open(meta_path: &Path, _stitched: Option<SegmentedRecording>) -> anyhow::Result<Self> {
+        let parser = SigMFParser::from_meta_file(meta_path)?;
+        let total_samples = parser.open_reader()?.num_samples();
+        Ok(ConstellationView {
+            meta_path: meta_path.to_path_buf(),
+            sample_rate: parser.sample_rate(),
+            total_samples,
+            start_sample: 0,
+            num_samples: 4096.min(total_samples as usize),
+            correct_frequency: false,
+            correction_power: 4,
+            constellation: None,
+            needs_compute: true,
+            error: None,
+        })
+    }
+
+    fn meta_path(&self) -> &Path {
+        &self.meta_path
+    }
+
+    fn show(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Start sample:");
+            let max_start = self.total_samples.saturating_sub(1);
+            if ui.add(egui::DragValue::new(&mut self.start_sample).range(0..=max_start).speed(100.0)).changed() {
+                self.needs_compute = true;
+            }
+            ui.label("Samples:");
+            if ui.add(egui::DragValue::new(&mut self.num_samples).range(16..=200_000).speed(16.0)).changed() {
+                self.needs_compute = true;
+            }
+        });
+        ui.horizontal(|ui| {
+            if ui.checkbox(&mut self.correct_frequency, "Coarse frequency correction").changed() {
+                self.needs_compute = true;
+            }
+            ui.add_enabled_ui(self.correct_frequency, |ui| {
+                egui::ComboBox::from_label("Power")
+                    .selected_text(self.correction_power.to_string())
+                    .show_ui(ui, |ui| {
+                        for power in CORRECTION_POWERS {
+                            if ui.selectable_value(&mut self.correction_power, power, power.to_string()).changed() {
+                                self.needs_compute = true;
+                            }
+                        }
+                    });
+            });
+            if let Some(offset) = self.constellation.as_ref().and_then(|c| c.freq_offset_hz) {
+                ui.label(format!("Removed offset: {}", format_frequency(offset)));
+            }
+        });
+
+        if let Some(ref error) = self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
+        if self.needs_compute {
+            self.compute();
+        }
+
+        // Keep the plot square so the constellation isn't distorted
+        let side = ui.available_width().min(ui.available_height().max(250.0));
+        let (response, painter) = ui.allocate_painter(egui::vec2(side, side), egui::Sense::hover());
+        let plot_rect = egui::Rect::from_min_max(
+            response.rect.min + egui::vec2(AXIS_MARGIN_LEFT, 0.0),
+            response.rect.max - egui::vec2(0.0, AXIS_MARGIN_BOTTOM),
+        );
+        let visuals = ui.visuals();
+        painter.rect_filled(plot_rect, 0.0, visuals.extreme_bg_color);
+        painter.hline(plot_rect.x_range(), plot_rect.center().y, egui::Stroke::new(1.0, visuals.weak_text_color()));
+        painter.vline(plot_rect.center().x, plot_rect.y_range(), egui::Stroke::new(1.0, visuals.weak_text_color()));
+
+        let peak = self.constellation.as_ref().map_or(1.0, |c| c.peak_amplitude()).max(1e-6) * 1.1;
+        if let Some(ref constellation) = self.constellation {
+            let color = egui::Color32::from_rgba_unmultiplied(31, 119, 180, 120);
+            for point in &constellation.points {
+                let pos = egui::pos2(
+                    plot_rect.center().x + point.re / peak * plot_rect.width() / 2.0,
+                    plot_rect.center().y - point.im / peak * plot_rect.height() / 2.0,
+                );
+                painter.circle_filled(pos, 1.5, color);
+            }
+        }
+
+        let format = |v: f64| format!("{:.2}", v);
+        draw_x_axis(&painter, plot_rect, -peak as f64, peak as f64, format);
+        draw_y_axis(&painter, plot_rect, -peak as f64, peak as f64, false, format);
+    }
+}
+
+impl ConstellationView {
+    fn compute(&mut self) {
+        self.needs_compute = false;
+        let correction = if self.correct_frequency {
+            FreqCorrection::PowerPeak(self.correction_power)
+        } else {
+            FreqCorrection::None
+        };
+        let result = SigMFParser::from_meta_file(&self.meta_path)
+            .and_then(|parser| parser.open_reader())
+            .and_then(|mut reader| {
+                Constellation::compute(&mut reader, self.start_sample, self.num_samples, self.sample_rate, correction)
+            });
+        match result {
+            Ok(constellation) => {
+                self.constellation = Some(constellation);
+                self.error = None;
+            }
+            Err(e) => self.error = Some(format!("Failed to read samples: {}", e)),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use sig_viewer::data_ops::{continuity_report, format_duration, ContinuityIssueKind};
+
+// handle the sequence and time continuity report per sensor
+impl SigViewerApp {
+    pub(crate) fn render_continuity_window(&mut self, ctx: &egui::Context) {
+        if !self.show_continuity {
+            return;
+        }
+        let mut open = true;
+        let mut clicked_recording = None;
+        egui::Window::new("Continuity")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([760.0, 480.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Allow gaps and overlaps of up to");
+                    let response = ui.add(egui::DragValue::new(&mut self.continuity_tolerance_s).range(0.0..=3600.0).speed(0.05).suffix(" s"));
+                    if response.changed() {
+                        self.continuity = None;
+                    }
+                });
+
+                if self.continuity.is_none() {
+                    if let Some(ref dataset) = self.filtered_dataset {
+                        self.continuity = Some(continuity_report(dataset, self.continuity_tolerance_s).map_err(|e| e.to_string()));
+                    }
+                }
+                let report = match self.continuity {
+                    Some(Ok(ref report)) => report,
+                    Some(Err(ref e)) => {
+                        ui.colored_label(egui::Color32::RED, format!("Cannot check continuity: {}", e));
+                        return;
+                    }
+                    None => {
+                        ui.label("Load a dataset first");
+                        return;
+                    }
+                };
+                if report.untimed_recordings > 0 {
+                    ui.label(format!("{} recordings without a parseable capture_datetime were left out of the time checks", report.untimed_recordings));
+                }
+                let broken = report.sensors.iter().filter(|s| !s.issues.is_empty()).count();
+                ui.label(format!("{} of {} sensors with breaks, {} issues", broken, report.sensors.len(), report.issue_count()));
+                ui.separator();
+
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show(ui, |ui| {
+                    egui::Grid::new("continuity_summary").striped(true).num_columns(6).show(ui, |ui| {
+                        for heading in ["Sensor", "Recordings", "Sequence", "Missing", "Gaps", "Overlaps"] {
+                            ui.strong(heading);
+                        }
+                        ui.end_row();
+                        for sensor in &report.sensors {
+                            ui.label(&sensor.sensor);
+                            ui.label(sensor.recordings.len().to_string());
+                            match sensor.sequence_range() {
+                                Some((first, last)) => ui.label(format!("{}..{}", first, last)),
+                                None => ui.weak("—"),
+                            };
+                            ui.label(format!("{} (+{} repeated)", sensor.missing_count(), sensor.count("duplicate")));
+                            ui.label(format!("{} ({})", sensor.count("gap"), format_duration(sensor.total_gap_s())));
+                            ui.label(sensor.count("overlap").to_string());
+                            ui.end_row();
+                        }
+                    });
+                    ui.separator();
+
+                    for sensor in report.sensors.iter().filter(|s| !s.issues.is_empty()) {
+                        egui::CollapsingHeader::new(format!("{} — {} issues", sensor.sensor, sensor.issues.len()))
+                            .id_salt(("continuity", &sensor.sensor))
+                            .show(ui, |ui| {
+                                for issue in &sensor.issues {
+                                    let color = match issue.kind {
+                                        ContinuityIssueKind::Gap(_) | ContinuityIssueKind::MissingSequence { .. } => egui::Color32::from_rgb(255, 140, 0),
+                                        ContinuityIssueKind::Overlap(_) | ContinuityIssueKind::DuplicateSequence(_) => egui::Color32::RED,
+                                    };
+                                    let response = ui.add(egui::Label::new(egui::RichText::new(issue.to_string()).color(color)).sense(egui::Sense::click()));
+                                    if response.on_hover_text(format!("Click to select {}", issue.after)).clicked() {
+                                        clicked_recording = Some(issue.after.clone());
+                                    }
+                                }
+                            });
+                    }
+                });
+            });
+
+        if let Some(meta_filename) = clicked_recording {
+            let row = self.filtered_dataset.as_ref().and_then(|df| {
+                let names = df.column("meta_filename").ok()?.str().ok()?;
+                names.into_iter().position(|name| name == Some(meta_filename.as_str()))
+            });
+            if let Some(row) = row {
+                self.select_row(row);
+            }
+        }
+        if !open {
+            self.show_continuity = false;
+        }
+    }
+}
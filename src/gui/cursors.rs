@@ -0,0 +1,135 @@
+use eframe::egui;
+
+/// How close (in points) the pointer has to be to grab a cursor
+const GRAB_RADIUS: f32 = 8.0;
+const CURSOR_COLORS: [egui::Color32; 2] = [egui::Color32::from_rgb(255, 220, 0), egui::Color32::from_rgb(0, 220, 255)];
+
+/// Two measurement cursors on a plot, placed by clicking and moved by
+/// dragging. They're kept in the plot's data coordinates, so they stay on the
+/// same frequency (and time) while the view zooms or pans. On a 1-D plot
+/// they are vertical lines and only x matters.
+pub struct MeasurementCursors {
+    pub enabled: bool,
+    two_d: bool,
+    points: [Option<(f64, f64)>; 2],
+    dragging: Option<usize>,
+}
+
+impl MeasurementCursors {
+    pub fn new(two_d: bool) -> Self {
+        MeasurementCursors {
+            enabled: false,
+            two_d,
+            points: [None, None],
+            dragging: None,
+        }
+    }
+
+    pub fn points(&self) -> [Option<(f64, f64)>; 2] {
+        self.points
+    }
+
+    pub fn clear(&mut self) {
+        self.points = [None, None];
+        self.dragging = None;
+    }
+
+    /// Checkbox to turn the cursors on, and a Clear button while they are
+    pub fn controls(&mut self, ui: &mut egui::Ui) {
+        ui.checkbox(&mut self.enabled, "Cursors")
+            .on_hover_text("Click to place two cursors and drag to move them; the readout shows the difference between them");
+        if self.enabled && self.points.iter().any(Option::is_some) && ui.button("Clear Cursors").clicked() {
+            self.clear();
+        }
+    }
+
+    /// Place and drag cursors with the pointer. Returns whether the cursors
+    /// took the pointer this frame, in which case the plot shouldn't pan.
+    pub fn interact(
+        &mut self,
+        response: &egui::Response,
+        rect: egui::Rect,
+        to_screen: impl Fn(f64, f64) -> egui::Pos2,
+        from_screen: impl Fn(egui::Pos2) -> (f64, f64),
+    ) -> bool {
+        if !self.enabled {
+            self.dragging = None;
+            return false;
+        }
+        if response.drag_stopped() && self.dragging.take().is_some() {
+            return true;
+        }
+        let Some(pointer) = response.interact_pointer_pos().map(|pos| rect.clamp(pos)) else {
+            return self.dragging.is_some();
+        };
+        if response.drag_started() {
+            self.dragging = self.nearest(pointer, &to_screen).filter(|(_, distance)| *distance <= GRAB_RADIUS).map(|(idx, _)| idx);
+        }
+        if let Some(idx) = self.dragging {
+            self.points[idx] = Some(from_screen(pointer));
+            return true;
+        }
+        if response.clicked() {
+            // Fill the empty cursor first, then move whichever is closer
+            let idx = self.points.iter().position(Option::is_none).or_else(|| self.nearest(pointer, &to_screen).map(|(idx, _)| idx));
+            if let Some(idx) = idx {
+                self.points[idx] = Some(from_screen(pointer));
+            }
+            return true;
+        }
+        false
+    }
+
+    /// The placed cursor closest to `pos` and its distance
+    fn nearest(&self, pos: egui::Pos2, to_screen: &impl Fn(f64, f64) -> egui::Pos2) -> Option<(usize, f32)> {
+        self.points
+            .iter()
+            .enumerate()
+            .filter_map(|(idx, point)| {
+                let (x, y) = (*point)?;
+                let screen = to_screen(x, y);
+                let distance = if self.two_d { screen.distance(pos) } else { (screen.x - pos.x).abs() };
+                Some((idx, distance))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+    }
+
+    pub fn draw(&self, painter: &egui::Painter, rect: egui::Rect, to_screen: impl Fn(f64, f64) -> egui::Pos2) {
+        if !self.enabled {
+            return;
+        }
+        let painter = painter.with_clip_rect(rect);
+        for (idx, point) in self.points.iter().enumerate() {
+            let Some((x, y)) = *point else {
+                continue;
+            };
+            let pos = to_screen(x, y);
+            let stroke = egui::Stroke::new(1.0, CURSOR_COLORS[idx]);
+            painter.line_segment([egui::pos2(pos.x, rect.top()), egui::pos2(pos.x, rect.bottom())], stroke);
+            if self.two_d {
+                painter.line_segment([egui::pos2(rect.left(), pos.y), egui::pos2(rect.right(), pos.y)], stroke);
+                painter.circle_stroke(pos, 4.0, stroke);
+            }
+            let label_y = if self.two_d { pos.y } else { rect.top() };
+            painter.text(
+                egui::pos2(pos.x + 3.0, label_y + 2.0),
+                egui::Align2::LEFT_TOP,
+                (idx + 1).to_string(),
+                egui::FontId::monospace(11.0),
+                CURSOR_COLORS[idx],
+            );
+        }
+    }
+
+    /// Paint `lines` in a box in the top right corner of `rect`
+    pub fn draw_readout(&self, painter: &egui::Painter, rect: egui::Rect, lines: &[String]) {
+        if !self.enabled || lines.is_empty() {
+            return;
+        }
+        let galley = painter.layout_no_wrap(lines.join("\n"), egui::FontId::monospace(11.0), egui::Color32::WHITE);
+        let min = egui::pos2(rect.right() - galley.size().x - 12.0, rect.top() + 6.0);
+        let background = egui::Rect::from_min_size(min, galley.size() + egui::vec2(8.0, 6.0));
+        painter.rect_filled(background, 3.0, egui::Color32::from_black_alpha(180));
+        painter.galley(min + egui::vec2(4.0, 3.0), galley, egui::Color32::WHITE);
+    }
+}
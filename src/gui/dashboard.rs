@@ -0,0 +1,293 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+use sig_viewer::data_ops::{group_by, occupancy, Aggregation, GroupKey, TimeBucket};
+
+const MAX_BARS: usize = 40;
+const BAR_HEIGHT: f32 = 14.0;
+const LABEL_WIDTH: f32 = 140.0;
+
+/// What a dashboard panel charts
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+pub enum PanelKind {
+    /// `aggregation` of each group of `group_by`
+    #[default]
+    GroupBy,
+    /// Percentage of captures with a signal in each `bin_width_hz` band, as
+    /// the CLI's `occupancy`
+    Occupancy,
+}
+
+/// One overview chart. `group_by` and `aggregation` use the same spec
+/// syntax as the CLI (`bin:center_freq_hz:100e6`, `day:capture_datetime`,
+/// `mean:snr_db`, ...).
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct DashboardPanel {
+    pub title: String,
+    #[serde(default)]
+    pub kind: PanelKind,
+    pub group_by: String,
+    pub aggregation: String,
+    #[serde(default = "default_bin_width")]
+    pub bin_width_hz: f64,
+}
+
+fn default_bin_width() -> f64 {
+    1e6
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct DashboardConfig {
+    pub show_on_load: bool,
+    pub panels: Vec<DashboardPanel>,
+}
+
+impl Default for DashboardConfig {
+    fn default() -> Self {
+        let panel = |title: &str, group_by: &str, aggregation: &str| DashboardPanel {
+            title: title.to_string(),
+            group_by: group_by.to_string(),
+            aggregation: aggregation.to_string(),
+            ..DashboardPanel::new()
+        };
+        Self {
+            show_on_load: true,
+            panels: vec![
+                panel("Detections by band (100 MHz)", "bin:center_freq_hz:100e6", "count"),
+                panel("Mean SNR by sensor", "sdr_handle", "mean:snr_db"),
+                panel("Detections per day", "day:capture_datetime", "count"),
+            ],
+        }
+    }
+}
+
+impl DashboardPanel {
+    fn new() -> Self {
+        DashboardPanel { bin_width_hz: default_bin_width(), ..Default::default() }
+    }
+}
+
+/// Computed bars for one panel
+pub struct PanelData {
+    pub labels: Vec<String>,
+    pub values: Vec<f64>,
+    pub truncated: usize,
+}
+
+fn compute_panel(dataset: &DataFrame, panel: &DashboardPanel) -> anyhow::Result<PanelData> {
+    if panel.kind == PanelKind::Occupancy {
+        return compute_occupancy_panel(dataset, panel);
+    }
+    let key = GroupKey::parse(&panel.group_by)?;
+    let aggregation = Aggregation::parse(&panel.aggregation)?;
+    let grouped = group_by(dataset, &key, std::slice::from_ref(&aggregation))?;
+
+    let keys = grouped.column(&key.output_name())?;
+    let values = grouped.column(&aggregation.output_name())?.cast(&DataType::Float64)?;
+    let values = values.f64()?;
+
+    let mut data = PanelData { labels: Vec::new(), values: Vec::new(), truncated: 0 };
+    for i in 0..grouped.height() {
+        if data.labels.len() == MAX_BARS {
+            data.truncated = grouped.height() - MAX_BARS;
+            break;
+        }
+        let label = match keys.get(i)? {
+            AnyValue::Null => "(none)".to_string(),
+            AnyValue::String("") => "(empty)".to_string(),
+            AnyValue::String(s) => s.to_string(),
+            AnyValue::Float64(v) if matches!(key, GroupKey::Binned { .. }) => format_band(v),
+            other => other.to_string(),
+        };
+        data.labels.push(label);
+        data.values.push(values.get(i).unwrap_or(0.0));
+    }
+    Ok(data)
+}
+
+/// One bar per frequency bin, over the whole view
+fn compute_occupancy_panel(dataset: &DataFrame, panel: &DashboardPanel) -> anyhow::Result<PanelData> {
+    let occupancy = occupancy(dataset, panel.bin_width_hz, TimeBucket::Day)?;
+    let mut data = PanelData { labels: Vec::new(), values: Vec::new(), truncated: 0 };
+    for (bin, bin_hz) in occupancy.bins_hz.iter().enumerate() {
+        if data.labels.len() == MAX_BARS {
+            data.truncated = occupancy.bins_hz.len() - MAX_BARS;
+            break;
+        }
+        data.labels.push(format_band(*bin_hz));
+        data.values.push(occupancy.bin_percent(bin).unwrap_or(0.0));
+    }
+    Ok(data)
+}
+
+fn format_band(hz: f64) -> String {
+    if hz.abs() >= 1e9 {
+        format!("{:.2} GHz", hz / 1e9)
+    } else if hz.abs() >= 1e6 {
+        format!("{:.0} MHz", hz / 1e6)
+    } else {
+        format!("{:.0} Hz", hz)
+    }
+}
+
+// handle load-time overview dashboard
+impl SigViewerApp {
+    pub(crate) fn render_dashboard(&mut self, ctx: &egui::Context) {
+        if !self.show_dashboard {
+            return;
+        }
+        let mut open = true;
+        let mut config_changed = false;
+        egui::Window::new("Dashboard")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([500.0, 600.0])
+            .show(ctx, |ui| {
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    if self.dashboard_data.is_none() {
+                        if let Some(ref dataset) = self.filtered_dataset {
+                            let panels = &self.config.dashboard.panels;
+                            self.dashboard_data = Some(
+                                panels
+                                    .iter()
+                                    .map(|panel| compute_panel(dataset, panel).map_err(|e| e.to_string()))
+                                    .collect(),
+                            );
+                        }
+                    }
+                    match self.dashboard_data {
+                        Some(ref data) => {
+                            for (panel, result) in self.config.dashboard.panels.iter().zip(data) {
+                                ui.heading(&panel.title);
+                                match result {
+                                    Ok(data) => draw_bars(ui, data),
+                                    Err(e) => {
+                                        ui.colored_label(egui::Color32::RED, e);
+                                    }
+                                }
+                                ui.add_space(8.0);
+                            }
+                        }
+                        None => {
+                            ui.label("Load a dataset first");
+                        }
+                    }
+
+                    ui.separator();
+                    egui::CollapsingHeader::new("Configure").show(ui, |ui| {
+                        config_changed |= render_panel_editor(ui, &mut self.config.dashboard);
+                    });
+                });
+            });
+        if config_changed {
+            self.dashboard_data = None;
+            self.save_config();
+        }
+        if !open {
+            self.show_dashboard = false;
+        }
+    }
+}
+
+/// Returns true if the configuration changed
+fn render_panel_editor(ui: &mut egui::Ui, config: &mut DashboardConfig) -> bool {
+    let mut changed = ui.checkbox(&mut config.show_on_load, "Show when a dataset loads").changed();
+    ui.label("Group by: column, day:column, hour:column or bin:column:width");
+    ui.label("Aggregation: count or mean/median/sum/min/max/std/n_unique:column");
+    ui.label("Occupancy panels chart the % of captures with a signal per frequency bin");
+
+    let mut remove = None;
+    for (i, panel) in config.panels.iter_mut().enumerate() {
+        ui.horizontal(|ui| {
+            changed |= ui.add(egui::TextEdit::singleline(&mut panel.title).desired_width(160.0).hint_text("Title")).lost_focus();
+            match panel.kind {
+                PanelKind::GroupBy => {
+                    changed |= ui.add(egui::TextEdit::singleline(&mut panel.group_by).desired_width(140.0).hint_text("Group by")).lost_focus();
+                    changed |= ui.add(egui::TextEdit::singleline(&mut panel.aggregation).desired_width(100.0).hint_text("Aggregation")).lost_focus();
+                }
+                PanelKind::Occupancy => {
+                    ui.label("Bin width (Hz)");
+                    // Recomputed once the value is set, not on every step of a drag
+                    let response = ui.add(egui::DragValue::new(&mut panel.bin_width_hz).range(1.0..=1e10).speed(1e4));
+                    changed |= response.drag_stopped() || response.lost_focus();
+                }
+            }
+            if ui.button("🗑").clicked() {
+                remove = Some(i);
+            }
+        });
+    }
+    if let Some(i) = remove {
+        config.panels.remove(i);
+        changed = true;
+    }
+    ui.horizontal(|ui| {
+        if ui.button("Add Panel").clicked() {
+            config.panels.push(DashboardPanel {
+                title: "New panel".to_string(),
+                group_by: "sdr_handle".to_string(),
+                aggregation: "count".to_string(),
+                ..DashboardPanel::new()
+            });
+            changed = true;
+        }
+        if ui.button("Add Occupancy Panel").clicked() {
+            config.panels.push(DashboardPanel {
+                title: "Occupancy (1 MHz bins, %)".to_string(),
+                kind: PanelKind::Occupancy,
+                ..DashboardPanel::new()
+            });
+            changed = true;
+        }
+        if ui.button("Restore Defaults").clicked() {
+            *config = DashboardConfig::default();
+            changed = true;
+        }
+    });
+    changed
+}
+
+fn draw_bars(ui: &mut egui::Ui, data: &PanelData) {
+    if data.values.is_empty() {
+        ui.label("No data");
+        return;
+    }
+    let max_value = data.values.iter().cloned().fold(0.0f64, f64::max);
+    let width = ui.available_width().max(LABEL_WIDTH + 120.0);
+    let size = egui::vec2(width, BAR_HEIGHT * data.values.len() as f32);
+    let (response, painter) = ui.allocate_painter(size, egui::Sense::hover());
+    let text_color = ui.visuals().text_color();
+    let bar_color = ui.visuals().selection.bg_fill;
+    // Leave room for the value text after the longest bar
+    let bar_span = width - LABEL_WIDTH - 70.0;
+
+    for (i, (label, value)) in data.labels.iter().zip(&data.values).enumerate() {
+        let y = response.rect.top() + i as f32 * BAR_HEIGHT;
+        painter.text(
+            egui::pos2(response.rect.left() + LABEL_WIDTH - 6.0, y + BAR_HEIGHT / 2.0),
+            egui::Align2::RIGHT_CENTER,
+            label,
+            egui::FontId::proportional(11.0),
+            text_color,
+        );
+        let frac = if max_value > 0.0 { (*value / max_value).max(0.0) as f32 } else { 0.0 };
+        let bar = egui::Rect::from_min_size(
+            egui::pos2(response.rect.left() + LABEL_WIDTH, y + 1.0),
+            egui::vec2(bar_span * frac, BAR_HEIGHT - 2.0),
+        );
+        painter.rect_filled(bar, 0.0, bar_color);
+        let text = if value.fract() == 0.0 { format!("{}", value) } else { format!("{:.2}", value) };
+        painter.text(
+            egui::pos2(bar.right() + 4.0, y + BAR_HEIGHT / 2.0),
+            egui::Align2::LEFT_CENTER,
+            text,
+            egui::FontId::monospace(11.0),
+            text_color,
+        );
+    }
+    if data.truncated > 0 {
+        ui.label(format!("… {} more groups not shown", data.truncated));
+    }
+}
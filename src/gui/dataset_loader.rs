@@ -0,0 +1,187 @@
+use crate::gui::session::Session;
+use crate::gui::watch_mode::DirectoryWatch;
+use crate::SigViewerApp;
+use anyhow::Result;
+use chrono::{DateTime, Utc};
+use eframe::egui;
+use polars::prelude::*;
+use sig_viewer::data_ops::{apply_derived_columns, apply_sensor_profiles, migrate_dataset, scan_dataset, DerivedColumns, Provenance, SchemaMigration, SensorProfile};
+use sig_viewer::parser::sigmf::{DatasetOptions, Granularity, ScanProgress, ScanReport};
+use sig_viewer::parser::SigMFDataset;
+use sig_viewer::viz::DeepLink;
+use sig_viewer::workers::{Cancelled, Priority, TaskContext, TaskHandle};
+use tracing::warn;
+
+/// What the worker hands back: the rows, how a re-opened export was
+/// migrated, and the files a directory scan skipped
+pub type LoadedDataset = (DataFrame, Option<SchemaMigration>, ScanReport);
+
+/// A dataset being read on a worker thread
+pub struct DatasetLoad {
+    pub path: String,
+    /// Further root directories scanned along with `path`
+    pub other_roots: Vec<String>,
+    /// A previously exported CSV/Parquet file rather than a directory
+    pub is_dataset_file: bool,
+    pub scanned_at: DateTime<Utc>,
+    /// What a row stands for in a directory scan, for the provenance
+    pub granularity: Granularity,
+    /// Number of files in a preview sample, None for a full scan
+    pub sample: Option<usize>,
+    /// Sidecar of a re-opened export, read up front so a failed load can
+    /// still offer a rebuild from its source
+    pub provenance: Option<Provenance>,
+    /// Deep link to show once the dataset is in
+    pub then_open: Option<DeepLink>,
+    /// Session whose filters, selection and windows go over the dataset
+    pub then_restore: Option<Session>,
+    /// Watch to keep the directory current once loaded; replaces any earlier one
+    pub watch: Option<DirectoryWatch>,
+    task: TaskHandle<LoadedDataset>,
+}
+
+// handle loading datasets on a worker so the window stays responsive
+impl SigViewerApp {
+    /// Start reading `path` in the background, replacing any load in progress
+    pub(crate) fn start_dataset_load(&mut self, path: &str, is_dataset_file: bool, provenance: Option<Provenance>, options: DatasetOptions) {
+        self.cancel_dataset_load();
+        let granularity = options.granularity;
+        let sample = options.sample.filter(|_| !is_dataset_file);
+        let other_roots = if is_dataset_file { Vec::new() } else { self.other_roots_to_load() };
+        let mut roots = vec![path.to_string()];
+        roots.extend(other_roots.iter().cloned());
+        let sidecar = provenance.clone();
+        let profiles = self.config.sensor_profiles.clone();
+        let derived = self.derived_columns.clone();
+        let task = self.workers.submit(&format!("Load {}", roots.join(", ")), Priority::High, move |ctx| {
+            read_dataset(&roots, is_dataset_file, sidecar.as_ref(), &options, &profiles, &derived, ctx)
+        });
+        self.dataset_load = Some(DatasetLoad {
+            path: path.to_string(),
+            other_roots,
+            is_dataset_file,
+            scanned_at: Utc::now(),
+            granularity,
+            sample,
+            provenance,
+            then_open: None,
+            then_restore: None,
+            watch: None,
+            task,
+        });
+        self.status_message = format!("Loading {}...", path);
+    }
+
+    /// The Load Dataset window's further root directories, one per line
+    pub(crate) fn other_roots_to_load(&self) -> Vec<String> {
+        self.other_roots_text.lines().map(str::trim).filter(|line| !line.is_empty()).map(str::to_string).collect()
+    }
+
+    pub(crate) fn cancel_dataset_load(&mut self) {
+        if let Some(load) = self.dataset_load.take() {
+            self.workers.cancel(load.task.id());
+            self.status_message = "Load cancelled".to_string();
+        }
+    }
+
+    /// Take over the dataset once its load has finished. Called once per frame.
+    pub(crate) fn poll_dataset_load(&mut self) {
+        let Some(result) = self.dataset_load.as_ref().and_then(|load| load.task.try_take()) else {
+            return;
+        };
+        let Some(load) = self.dataset_load.take() else {
+            return;
+        };
+        match result {
+            Err(e) if e.is::<Cancelled>() => self.status_message = "Load cancelled".to_string(),
+            result => {
+                let loaded = result.is_ok();
+                self.finish_dataset_load(&load, result);
+                if loaded {
+                    self.directory_watch = load.watch;
+                }
+                if let Some(link) = load.then_open.filter(|_| loaded) {
+                    self.show_deep_link(&link);
+                }
+                if let Some(session) = load.then_restore.filter(|_| loaded) {
+                    self.restore_session_view(session);
+                }
+            }
+        }
+    }
+
+    /// Marks a preview in the menu bar, with a button to load every file
+    pub(crate) fn render_preview_indicator(&mut self, ui: &mut egui::Ui) {
+        let Some(n) = self.loaded_sample.filter(|_| self.dataset_load.is_none()) else {
+            return;
+        };
+        let response = ui
+            .button(format!("Preview ({} files) — Load All", n))
+            .on_hover_text("The table holds a random sample of the directory. Click to scan every file.");
+        if response.clicked() {
+            self.preview_sample = false;
+            let path = self.directory_path.clone();
+            self.load_dataset(&path);
+        }
+    }
+
+    /// Progress bar with the files-processed count and a Cancel button, while a load runs
+    pub(crate) fn render_load_progress(&mut self, ui: &mut egui::Ui) {
+        let Some(load) = self.dataset_load.as_ref() else {
+            return;
+        };
+        let info = self.workers.tasks().into_iter().find(|task| task.id == load.task.id());
+        let progress = info.as_ref().and_then(|task| task.progress);
+        let message = info.map(|task| task.message).filter(|m| !m.is_empty()).unwrap_or_else(|| "Starting".to_string());
+        let mut cancel = false;
+        ui.horizontal(|ui| {
+            if ui.button("Cancel").clicked() {
+                cancel = true;
+            }
+            ui.add(egui::ProgressBar::new(progress.unwrap_or(0.0)).desired_width(220.0).text(message).animate(progress.is_none()))
+                .on_hover_text(format!("Loading {}", load.path));
+        });
+        if cancel {
+            self.cancel_dataset_load();
+        }
+    }
+}
+
+/// The background half of a load: scan the directory or directories (or
+/// read and migrate the export), apply the sensor profiles and add the
+/// derived columns
+fn read_dataset(
+    roots: &[String],
+    is_dataset_file: bool,
+    provenance: Option<&Provenance>,
+    options: &DatasetOptions,
+    profiles: &[SensorProfile],
+    derived: &DerivedColumns,
+    ctx: &TaskContext,
+) -> Result<LoadedDataset> {
+    let mut report = ScanReport::default();
+    let (dataset, migration) = if is_dataset_file {
+        ctx.set_message("Reading dataset file");
+        let (lf, migration) = migrate_dataset(scan_dataset(&roots[0])?, provenance)?;
+        (lf.collect()?, Some(migration))
+    } else {
+        let on_progress = |progress: ScanProgress| {
+            for failure in progress.failures {
+                warn!("{}", failure);
+            }
+            report.record(&progress);
+            ctx.set_message(&format!("{} of {} files", progress.done, progress.total));
+            ctx.report(progress.done as f32 / progress.total.max(1) as f32)
+        };
+        let dataset = if roots.len() > 1 {
+            SigMFDataset::from_directories_with_progress(roots, options, on_progress)?
+        } else {
+            SigMFDataset::from_directory_with_progress(&roots[0], options, on_progress)?
+        };
+        (dataset, None)
+    };
+    ctx.check_cancelled()?;
+    // Exports already carrying sensor_profile were corrected when built
+    let dataset = apply_sensor_profiles(dataset, profiles)?;
+    Ok((apply_derived_columns(dataset, derived)?, migration, report))
+}
@@ -0,0 +1,61 @@
+use crate::{SigViewerApp, VizTab};
+use super::spectrogram_view::SpectrogramView;
+use super::RecordingView;
+use sig_viewer::viz::DeepLink;
+
+// handle sigviewer:// links
+impl SigViewerApp {
+    /// Show the linked location in the spectrogram, loading the recording's
+    /// directory first if it isn't in the table
+    pub(crate) fn open_deep_link(&mut self, link: &DeepLink) {
+        if !link.file.exists() {
+            self.error_message = Some(format!("Linked recording not found: {}", link.file.display()));
+            return;
+        }
+        if self.deep_link_row(link).is_none() {
+            if let Some(dir) = link.file.parent() {
+                // Shown once the directory has loaded
+                self.load_dataset(&dir.to_string_lossy());
+                if let Some(load) = self.dataset_load.as_mut() {
+                    load.then_open = Some(link.clone());
+                    return;
+                }
+            }
+        }
+        self.show_deep_link(link);
+    }
+
+    /// Row of the linked recording in the filtered table
+    fn deep_link_row(&self, link: &DeepLink) -> Option<usize> {
+        let meta_filename = link.file.file_name()?.to_string_lossy().to_string();
+        self.filtered_dataset.as_ref()?
+            .column("meta_filename").ok()?
+            .str().ok()?
+            .into_iter()
+            .position(|name| name == Some(meta_filename.as_str()))
+    }
+
+    /// Select the linked recording in the loaded table and open the spectrogram there
+    pub(crate) fn show_deep_link(&mut self, link: &DeepLink) {
+        let Some(row) = self.deep_link_row(link) else {
+            let meta_filename = link.file.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+            if self.error_message.is_none() {
+                self.error_message = Some(format!("{} isn't in the loaded dataset", meta_filename));
+            }
+            return;
+        };
+        self.select_row(row);
+
+        match SpectrogramView::open(&link.file, self.stitched_recording(&link.file)) {
+            Ok(mut view) => {
+                view.goto(link);
+                self.spectrogram_view = Some(view);
+                self.viz_tab = VizTab::Spectrogram;
+                self.show_visualization_dialog = true;
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to open {}: {}", link.file.display(), e));
+            }
+        }
+    }
+}
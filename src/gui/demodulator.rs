@@ -0,0 +1,298 @@
+use crate::gui::plot::format_frequency;
+use crate::SigViewerApp;
+use eframe::egui;
+use polars::prelude::*;
+use sig_viewer::demod::{demodulate_recording, write_wav, Audio, DemodMode, DemodParams};
+use sig_viewer::parser::SigMFParser;
+use sig_viewer::workers::{Priority, TaskHandle, WorkerPool};
+use std::path::PathBuf;
+
+/// State of the Demodulate window: the slice of one recording to listen to,
+/// its audio once demodulated, and the playback of it
+pub struct Demodulator {
+    meta_path: PathBuf,
+    sample_rate: f64,
+    center_freq: f64,
+    num_samples: u64,
+    params: DemodParams,
+    /// Absolute frequency tuned to; `params.offset_hz` follows it
+    freq_hz: f64,
+    start: u64,
+    count: u64,
+    task: Option<TaskHandle<Audio>>,
+    audio: Option<Audio>,
+    player: Option<Player>,
+    wav_dialog: Option<egui_file::FileDialog>,
+    error: Option<String>,
+}
+
+// handle demodulating analog signals and playing them back
+impl SigViewerApp {
+    /// Open on the selected row's recording, tuned to its signal with the
+    /// mode its `ml_fm_prob`/`ml_am_prob` suggest
+    pub(crate) fn open_demodulator(&mut self) {
+        let (Some(row), Some(df)) = (self.selected_row, self.filtered_dataset.as_ref()) else {
+            return;
+        };
+        let Some(meta_path) = self.selected_meta_path() else {
+            self.error_message = Some("Could not locate the selected recording's meta file".to_string());
+            return;
+        };
+        let parser = match SigMFParser::from_meta_file(&meta_path) {
+            Ok(parser) => parser,
+            Err(e) => {
+                self.error_message = Some(format!("Demodulate: {}", e));
+                return;
+            }
+        };
+        let value = |name: &str| row_value(df, row, name).filter(|v| *v != 0.0);
+        let center_freq = parser.center_frequency().unwrap_or(0.0);
+        let bandwidth = value("sig_bandwidth_hz").filter(|bw| *bw > 0.0);
+        let mode = DemodMode::guess(value("ml_fm_prob").unwrap_or(0.0), value("ml_am_prob").unwrap_or(0.0), bandwidth);
+        let freq_hz = value("sig_center_freq_hz").unwrap_or(center_freq);
+        if let Some(old) = self.demodulator.take() {
+            old.cancel(&self.workers);
+        }
+        self.demodulator = Some(Demodulator {
+            meta_path,
+            sample_rate: parser.sample_rate(),
+            center_freq,
+            num_samples: parser.num_samples(),
+            params: DemodParams {
+                mode,
+                offset_hz: freq_hz - center_freq,
+                bandwidth_hz: bandwidth.unwrap_or(mode.default_bandwidth_hz()),
+                ..DemodParams::default()
+            },
+            freq_hz,
+            start: 0,
+            count: parser.num_samples(),
+            task: None,
+            audio: None,
+            player: None,
+            wav_dialog: None,
+            error: None,
+        });
+    }
+
+    pub(crate) fn render_demodulator(&mut self, ctx: &egui::Context) {
+        let Some(mut demod) = self.demodulator.take() else {
+            return;
+        };
+        match demod.task.as_ref().and_then(|task| task.try_take()) {
+            Some(Ok(audio)) => {
+                demod.task = None;
+                demod.audio = Some(audio);
+                demod.play();
+            }
+            Some(Err(e)) => {
+                demod.task = None;
+                demod.error = Some(e.to_string());
+            }
+            None => {}
+        }
+        // The spectrogram's view when it starts in this recording (it can be
+        // scrolled into a neighbouring segment), to take the slice from
+        let visible = self
+            .spectrogram_view
+            .as_ref()
+            .map(|view| view.deep_link())
+            .filter(|link| std::fs::canonicalize(&demod.meta_path).is_ok_and(|path| path == link.file));
+
+        let mut open = true;
+        let mut run = false;
+        egui::Window::new("Demodulate")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(demod.meta_path.file_name().unwrap_or_default().to_string_lossy().as_ref());
+                let half_band = demod.sample_rate / 2.0;
+                egui::Grid::new("demod_params").num_columns(2).show(ui, |ui| {
+                    ui.label("Mode");
+                    ui.horizontal(|ui| {
+                        for mode in DemodMode::ALL {
+                            if ui.radio(demod.params.mode == mode, mode.name()).clicked() && demod.params.mode != mode {
+                                demod.params.mode = mode;
+                                demod.params.bandwidth_hz = mode.default_bandwidth_hz();
+                            }
+                        }
+                    });
+                    ui.end_row();
+                    ui.label("Frequency");
+                    let range = (demod.center_freq - half_band)..=(demod.center_freq + half_band);
+                    ui.add(egui::DragValue::new(&mut demod.freq_hz).range(range).speed(100.0).custom_formatter(|v, _| format_frequency(v)));
+                    ui.end_row();
+                    ui.label("Bandwidth");
+                    ui.add(egui::DragValue::new(&mut demod.params.bandwidth_hz).range(100.0..=demod.sample_rate).speed(100.0).custom_formatter(|v, _| format_frequency(v)));
+                    ui.end_row();
+                    ui.label("Samples");
+                    ui.horizontal(|ui| {
+                        ui.add(egui::DragValue::new(&mut demod.start).range(0..=demod.num_samples).prefix("from "));
+                        ui.add(egui::DragValue::new(&mut demod.count).range(1..=demod.num_samples).prefix("count "));
+                    });
+                    ui.end_row();
+                    if demod.params.mode == DemodMode::Wbfm {
+                        ui.label("De-emphasis");
+                        ui.horizontal(|ui| {
+                            ui.radio_value(&mut demod.params.deemphasis_us, 75.0, "75 µs");
+                            ui.radio_value(&mut demod.params.deemphasis_us, 50.0, "50 µs");
+                        });
+                        ui.end_row();
+                    }
+                });
+                let slice = ui
+                    .add_enabled(visible.is_some(), egui::Button::new("Use the spectrogram's view"))
+                    .on_hover_text("Tune to the visible frequency span and time range of the spectrogram tab")
+                    .on_disabled_hover_text("Open this recording in the spectrogram tab to pick a slice there");
+                if let (true, Some(link)) = (slice.clicked(), visible.as_ref()) {
+                    demod.start = link.sample_start.unwrap_or(0);
+                    demod.count = link.sample_count.unwrap_or(demod.num_samples);
+                    if let (Some(lower), Some(upper)) = (link.freq_lower, link.freq_upper) {
+                        demod.freq_hz = (lower + upper) / 2.0;
+                        demod.params.bandwidth_hz = upper - lower;
+                    }
+                }
+                ui.separator();
+
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(demod.task.is_none(), egui::Button::new("Demodulate")).clicked() {
+                        run = true;
+                    }
+                    if demod.task.is_some() {
+                        ui.spinner();
+                    }
+                    let Some(ref audio) = demod.audio else {
+                        return;
+                    };
+                    ui.label(format!("{:.2} s of audio", audio.duration_s()));
+                    if demod.is_playing() {
+                        if ui.button("⏹ Stop").clicked() {
+                            demod.player = None;
+                        }
+                    } else if ui.add_enabled(Player::AVAILABLE, egui::Button::new("▶ Play")).on_disabled_hover_text(Player::UNAVAILABLE).clicked() {
+                        demod.play();
+                    }
+                    if ui.button("Save WAV...").clicked() {
+                        let name = demod.meta_path.file_stem().unwrap_or_default().to_string_lossy().replace(".sigmf", "");
+                        let mut dialog = egui_file::FileDialog::save_file(demod.meta_path.parent().map(|p| p.to_path_buf()))
+                            .title("Save Audio")
+                            .default_filename(format!("{}_{}.wav", name, demod.params.mode.as_str()));
+                        dialog.open();
+                        demod.wav_dialog = Some(dialog);
+                    }
+                });
+                if let Some(ref error) = demod.error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+            });
+
+        if let Some(ref mut dialog) = demod.wav_dialog {
+            if dialog.show(ctx).selected() {
+                if let (Some(path), Some(audio)) = (dialog.path(), demod.audio.as_ref()) {
+                    match write_wav(path, audio) {
+                        Ok(()) => self.status_message = format!("Saved audio to {}", path.display()),
+                        Err(e) => demod.error = Some(format!("Failed to save audio: {}", e)),
+                    }
+                }
+                demod.wav_dialog = None;
+            } else if !dialog.visible() {
+                demod.wav_dialog = None;
+            }
+        }
+        if run {
+            demod.player = None;
+            demod.error = None;
+            demod.params.offset_hz = demod.freq_hz - demod.center_freq;
+            let (meta_path, start, count, params) = (demod.meta_path.clone(), demod.start, demod.count, demod.params.clone());
+            let name = format!("Demodulate {}: {}", params.mode.name(), meta_path.file_name().unwrap_or_default().to_string_lossy());
+            demod.task = Some(self.workers.submit(&name, Priority::High, move |ctx| {
+                demodulate_recording(&meta_path, start, Some(count), &params, |f| ctx.report(f))
+            }));
+        }
+        if demod.is_playing() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(250));
+        }
+        if open {
+            self.demodulator = Some(demod);
+        } else {
+            demod.cancel(&self.workers);
+        }
+    }
+}
+
+impl Demodulator {
+    fn play(&mut self) {
+        let Some(ref audio) = self.audio else {
+            return;
+        };
+        if !Player::AVAILABLE {
+            return;
+        }
+        match Player::play(audio) {
+            Ok(player) => self.player = Some(player),
+            Err(e) => self.error = Some(format!("Cannot play audio: {}", e)),
+        }
+    }
+
+    fn is_playing(&self) -> bool {
+        self.player.as_ref().is_some_and(Player::is_playing)
+    }
+
+    fn cancel(self, workers: &WorkerPool) {
+        if let Some(task) = self.task {
+            workers.cancel(task.id());
+        }
+    }
+}
+
+/// Audio output through the default device, behind the `audio` feature.
+/// Dropping it stops playback.
+#[cfg(feature = "audio")]
+struct Player {
+    // Playback stops when the stream goes
+    _stream: rodio::OutputStream,
+    sink: rodio::Sink,
+}
+
+#[cfg(feature = "audio")]
+impl Player {
+    const AVAILABLE: bool = true;
+    const UNAVAILABLE: &'static str = "";
+
+    fn play(audio: &Audio) -> anyhow::Result<Self> {
+        let (stream, handle) = rodio::OutputStream::try_default()?;
+        let sink = rodio::Sink::try_new(&handle)?;
+        sink.append(rodio::buffer::SamplesBuffer::new(1, audio.sample_rate, audio.samples.clone()));
+        Ok(Player { _stream: stream, sink })
+    }
+
+    fn is_playing(&self) -> bool {
+        !self.sink.empty()
+    }
+}
+
+#[cfg(not(feature = "audio"))]
+struct Player;
+
+#[cfg(not(feature = "audio"))]
+impl Player {
+    const AVAILABLE: bool = false;
+    const UNAVAILABLE: &'static str = "This build has no audio output; rebuild with `--features audio`, or save a WAV file";
+
+    fn play(_audio: &Audio) -> anyhow::Result<Self> {
+        anyhow::bail!("{}", Self::UNAVAILABLE)
+    }
+
+    fn is_playing(&self) -> bool {
+        false
+    }
+}
+
+/// A numeric cell as f64, `None` if null or not numeric
+fn row_value(df: &DataFrame, row: usize, name: &str) -> Option<f64> {
+    let column = df.column(name).ok()?;
+    if !column.dtype().is_numeric() {
+        return None;
+    }
+    column.cast(&DataType::Float64).ok()?.f64().ok()?.get(row)
+}
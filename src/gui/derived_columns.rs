@@ -0,0 +1,24 @@
+use crate::SigViewerApp;
+use sig_viewer::data_ops::DerivedColumns;
+
+// handle the derived columns setting, read before each load
+impl SigViewerApp {
+    /// Read the configured derived columns file (none without one). Returns
+    /// false (with an error shown) if it can't be read or an expression
+    /// doesn't parse.
+    pub(crate) fn load_derived_columns(&mut self) -> bool {
+        let path = self.config.derived_columns.trim();
+        let derived = if path.is_empty() { Ok(DerivedColumns::default()) } else { DerivedColumns::from_toml(path) };
+        match derived {
+            Ok(derived) => {
+                self.derived_columns = derived;
+                self.save_config();
+                true
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Invalid derived columns: {:#}", e));
+                false
+            }
+        }
+    }
+}
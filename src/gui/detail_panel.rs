@@ -0,0 +1,149 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use serde_json::Value;
+use sig_viewer::parser::sigmf::{field_text, read_meta_text};
+use std::path::PathBuf;
+
+/// The selected recording's metadata as read for the detail panel, kept
+/// until the selection moves to another recording
+pub struct DetailPanel {
+    meta_filename: String,
+    meta_path: Option<PathBuf>,
+    metadata: Result<Value, String>,
+}
+
+// handle the side panel showing the selected recording's full metadata
+impl SigViewerApp {
+    pub(crate) fn render_detail_panel(&mut self, ctx: &egui::Context) {
+        if !self.config.show_detail_panel {
+            return;
+        }
+        let selected = self.selected_row_data.as_ref().and_then(|row| row.get("meta_filename")).cloned();
+        if let Some(ref meta_filename) = selected {
+            if self.detail_panel.as_ref().is_none_or(|panel| panel.meta_filename != *meta_filename) {
+                self.detail_panel = Some(self.read_detail(meta_filename));
+            }
+        }
+        let mut close = false;
+        let mut reload = false;
+        egui::SidePanel::right("detail_panel").resizable(true).default_width(380.0).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.heading("Metadata");
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    close = ui.button("✖").on_hover_text("Hide the panel (View → Metadata Panel)").clicked();
+                    reload = ui.button("⟳").on_hover_text("Read the meta file again").clicked();
+                });
+            });
+            let Some(panel) = self.detail_panel.as_ref().filter(|_| selected.is_some()) else {
+                ui.label("Select a row to see its recording's metadata");
+                return;
+            };
+            match panel.meta_path {
+                Some(ref path) => ui.label(path.display().to_string()),
+                None => ui.label(panel.meta_filename.as_str()),
+            };
+            ui.separator();
+            match panel.metadata {
+                Ok(ref metadata) => {
+                    egui::ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+                        if let Some(object) = metadata.as_object() {
+                            for (key, value) in object {
+                                json_tree(ui, &section_label(key, value), value, key, true);
+                            }
+                        }
+                    });
+                }
+                Err(ref e) => {
+                    ui.colored_label(egui::Color32::RED, e);
+                }
+            }
+        });
+        if reload {
+            if let Some(meta_filename) = selected {
+                self.detail_panel = Some(self.read_detail(&meta_filename));
+            }
+        }
+        if close {
+            self.config.show_detail_panel = false;
+            self.save_config();
+        }
+    }
+
+    fn read_detail(&self, meta_filename: &str) -> DetailPanel {
+        let meta_path = self.locate_meta_file(meta_filename);
+        let metadata = match meta_path {
+            Some(ref path) => read_meta_text(path)
+                .and_then(|text| Ok(serde_json::from_str(&text)?))
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e)),
+            None => Err(format!("Could not locate {}", meta_filename)),
+        };
+        DetailPanel { meta_filename: meta_filename.to_string(), meta_path, metadata }
+    }
+}
+
+/// Top-level sections with their entry counts, e.g. "annotations (12)"
+fn section_label(key: &str, value: &Value) -> String {
+    match value {
+        Value::Array(items) => format!("{} ({})", key, items.len()),
+        _ => key.to_string(),
+    }
+}
+
+/// One value of the tree: objects and arrays collapse, with "Copy JSON" on
+/// right-click; leaves show their value with a copy button
+fn json_tree(ui: &mut egui::Ui, label: &str, value: &Value, id: &str, open: bool) {
+    match value {
+        Value::Object(object) => {
+            let response = egui::CollapsingHeader::new(label).id_salt(id).default_open(open).show(ui, |ui| {
+                for (key, child) in object {
+                    json_tree(ui, key, child, &format!("{}.{}", id, key), false);
+                }
+            });
+            copy_menu(&response.header_response, value);
+        }
+        Value::Array(items) => {
+            let response = egui::CollapsingHeader::new(label).id_salt(id).default_open(open).show(ui, |ui| {
+                for (idx, child) in items.iter().enumerate() {
+                    json_tree(ui, &item_label(idx, child), child, &format!("{}[{}]", id, idx), false);
+                }
+            });
+            copy_menu(&response.header_response, value);
+        }
+        leaf => {
+            let text = field_text(leaf);
+            ui.horizontal(|ui| {
+                if ui.small_button("📋").on_hover_text("Copy the value").clicked() {
+                    ui.ctx().copy_text(text.clone());
+                }
+                ui.label(egui::RichText::new(format!("{}:", label)).strong());
+                let shown = match leaf {
+                    Value::String(_) => egui::RichText::new(&text),
+                    _ => egui::RichText::new(&text).monospace(),
+                };
+                ui.add(egui::Label::new(shown).wrap());
+            });
+        }
+    }
+}
+
+/// Array entries by index, with what tells captures and annotations apart
+fn item_label(idx: usize, value: &Value) -> String {
+    let field = |key: &str| value.get(key).map(field_text);
+    let mut label = format!("[{}]", idx);
+    if let Some(start) = field("core:sample_start") {
+        label.push_str(&format!(" sample {}", start));
+    }
+    if let Some(text) = field("core:label").or_else(|| field("core:frequency")) {
+        label.push_str(&format!(" · {}", text));
+    }
+    label
+}
+
+fn copy_menu(response: &egui::Response, value: &Value) {
+    response.context_menu(|ui| {
+        if ui.button("Copy JSON").clicked() {
+            ui.ctx().copy_text(serde_json::to_string_pretty(value).unwrap_or_default());
+            ui.close();
+        }
+    });
+}
@@ -0,0 +1,230 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use polars::prelude::*;
+use sig_viewer::data_ops::{write_dataset, ExportFormat, Provenance};
+use std::path::{Path, PathBuf};
+
+/// State of the File → Export... window
+pub struct ExportDialog {
+    format: ExportFormat,
+    /// Every dataset column and whether it's included
+    columns: Vec<(String, bool)>,
+    path: String,
+    file_dialog: Option<egui_file::FileDialog>,
+    /// Rows of the filtered dataset to export, in table order; all if `None`
+    rows: Option<Vec<usize>>,
+}
+
+impl ExportDialog {
+    fn set_format(&mut self, format: ExportFormat) {
+        self.format = format;
+        if !self.path.is_empty() {
+            self.path = PathBuf::from(&self.path)
+                .with_extension(format.extension())
+                .to_string_lossy()
+                .to_string();
+        }
+    }
+
+    fn selected_columns(&self) -> Vec<String> {
+        self.columns
+            .iter()
+            .filter(|(_, included)| *included)
+            .map(|(name, _)| name.clone())
+            .collect()
+    }
+}
+
+// handle exporting the filtered dataset
+impl SigViewerApp {
+    /// Open the export window with the currently visible columns selected.
+    /// `rows` limits the export to those rows of the filtered dataset.
+    pub(crate) fn open_export_dialog(&mut self, rows: Option<Vec<usize>>) {
+        let Some(ref dataset) = self.filtered_dataset else {
+            return;
+        };
+        let visible = self.get_visible_columns(dataset);
+        let columns = dataset
+            .get_column_names()
+            .iter()
+            .map(|name| name.to_string())
+            .map(|name| {
+                let included = visible.contains(&name);
+                (name, included)
+            })
+            .collect();
+
+        let directory = PathBuf::from(&self.directory_path);
+        let directory = if directory.is_file() {
+            directory.parent().map(|p| p.to_path_buf()).unwrap_or_default()
+        } else {
+            directory
+        };
+        let format = ExportFormat::Csv;
+        let stem = if rows.is_some() { "selection" } else { "dataset" };
+        self.export_dialog = Some(ExportDialog {
+            format,
+            columns,
+            path: directory.join(format!("{}.{}", stem, format.extension())).to_string_lossy().to_string(),
+            file_dialog: None,
+            rows,
+        });
+    }
+
+    pub(crate) fn render_export_dialog(&mut self, ctx: &egui::Context) {
+        let Some(ref mut dialog) = self.export_dialog else {
+            return;
+        };
+        let num_rows = self.filtered_dataset.as_ref().map_or(0, |df| df.height());
+        let mut open = true;
+        let mut export = false;
+        let mut cancel = false;
+        egui::Window::new("Export Dataset")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(true)
+            .default_size([360.0, 480.0])
+            .show(ctx, |ui| {
+                match dialog.rows {
+                    Some(ref rows) => ui.label(format!("Exports the {} selected rows.", rows.len())),
+                    None => ui.label(format!("Exports the {} rows currently shown (after filters).", num_rows)),
+                };
+                ui.horizontal(|ui| {
+                    ui.label("Format:");
+                    for format in ExportFormat::ALL {
+                        if ui.radio(dialog.format == format, format.name()).clicked() {
+                            dialog.set_format(format);
+                        }
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.label("File:");
+                    ui.text_edit_singleline(&mut dialog.path);
+                    if ui.button("Browse...").clicked() {
+                        let path = PathBuf::from(&dialog.path);
+                        let mut file_dialog = egui_file::FileDialog::save_file(path.parent().map(|p| p.to_path_buf()))
+                            .title(&format!("Export {}", dialog.format.name()))
+                            .default_filename(
+                                path.file_name()
+                                    .map(|name| name.to_string_lossy().to_string())
+                                    .unwrap_or_else(|| format!("dataset.{}", dialog.format.extension())),
+                            );
+                        file_dialog.open();
+                        dialog.file_dialog = Some(file_dialog);
+                    }
+                });
+
+                ui.separator();
+                let selected = dialog.columns.iter().filter(|(_, included)| *included).count();
+                ui.horizontal(|ui| {
+                    ui.strong(format!("Columns ({} of {})", selected, dialog.columns.len()));
+                    if ui.small_button("All").clicked() {
+                        dialog.columns.iter_mut().for_each(|(_, included)| *included = true);
+                    }
+                    if ui.small_button("None").clicked() {
+                        dialog.columns.iter_mut().for_each(|(_, included)| *included = false);
+                    }
+                });
+                egui::ScrollArea::vertical().max_height(280.0).show(ui, |ui| {
+                    for (name, included) in dialog.columns.iter_mut() {
+                        ui.checkbox(included, name.as_str());
+                    }
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    let can_export = selected > 0 && !dialog.path.trim().is_empty();
+                    if ui.add_enabled(can_export, egui::Button::new("Export")).clicked() {
+                        export = true;
+                    }
+                    if ui.button("Cancel").clicked() {
+                        cancel = true;
+                    }
+                });
+            });
+
+        if let Some(ref mut file_dialog) = dialog.file_dialog {
+            if file_dialog.show(ctx).selected() {
+                if let Some(path) = file_dialog.path() {
+                    dialog.path = path.to_string_lossy().to_string();
+                    if let Some(format) = ExportFormat::from_path(path) {
+                        dialog.format = format;
+                    }
+                }
+                dialog.file_dialog = None;
+            } else if !file_dialog.visible() {
+                dialog.file_dialog = None;
+            }
+        }
+
+        if export {
+            let path = PathBuf::from(dialog.path.trim());
+            let format = dialog.format;
+            let columns = dialog.selected_columns();
+            let rows = dialog.rows.clone();
+            if self.export_dataset(&path, format, &columns, rows.as_deref()) {
+                self.export_dialog = None;
+            }
+        } else if cancel || !open {
+            self.export_dialog = None;
+        }
+    }
+
+    /// Write the filtered dataset (only `columns`, and only `rows` if given)
+    /// plus its provenance sidecar. Returns whether it succeeded; failures are
+    /// shown in the error popup.
+    fn export_dataset(&mut self, path: &Path, format: ExportFormat, columns: &[String], rows: Option<&[usize]>) -> bool {
+        let Some(ref dataset) = self.filtered_dataset else {
+            return false;
+        };
+        let dataset = match rows {
+            Some(rows) => {
+                let indices = IdxCa::from_vec("".into(), rows.iter().map(|&row| row as IdxSize).collect());
+                match dataset.take(&indices) {
+                    Ok(subset) => subset,
+                    Err(e) => {
+                        self.error_message = Some(format!("Failed to export {}: {}", path.display(), e));
+                        return false;
+                    }
+                }
+            }
+            None => dataset.clone(),
+        };
+        let mut provenance = self
+            .provenance
+            .clone()
+            .unwrap_or_else(|| Provenance::new(&self.directory_path, chrono::Utc::now(), 0));
+        provenance.touch(dataset.height());
+        let current = match (self.filter_description(), rows) {
+            (filter, None) => filter,
+            (Some(filter), Some(rows)) => Some(format!("{} AND {} hand-picked rows", filter, rows.len())),
+            (None, Some(rows)) => Some(format!("{} hand-picked rows", rows.len())),
+        };
+        provenance.filter = match (provenance.filter.take(), current) {
+            (Some(earlier), Some(current)) => Some(format!("({}) AND {}", earlier, current)),
+            (earlier, current) => current.or(earlier),
+        };
+        provenance.casts.extend(self.column_casts.iter().map(|(column, target)| format!("{}={}", column, target.name())));
+
+        let written = dataset
+            .select(columns.iter().map(|c| c.as_str()))
+            .map_err(anyhow::Error::from)
+            .and_then(|mut selected| write_dataset(&mut selected, path, format))
+            .and_then(|()| provenance.write(path));
+        match written {
+            Ok(()) => {
+                self.status_message = format!(
+                    "Exported {} rows × {} columns to {}",
+                    dataset.height(),
+                    columns.len(),
+                    path.display()
+                );
+                true
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to export {}: {}", path.display(), e));
+                false
+            }
+        }
+    }
+}
@@ -0,0 +1,93 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use sig_viewer::data_ops::FilterPreset;
+
+// handle saving the column filters under a name and applying them again
+impl SigViewerApp {
+    /// The Presets menu next to Apply Filters
+    pub(crate) fn render_filter_presets(&mut self, ui: &mut egui::Ui) {
+        let mut apply = None;
+        let mut delete = None;
+        let mut save = false;
+        ui.menu_button("Presets", |ui| {
+            if self.config.filter_presets.is_empty() {
+                ui.weak("No presets saved yet");
+            }
+            for (idx, preset) in self.config.filter_presets.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    if ui.button(&preset.name).on_hover_text(preset.description()).clicked() {
+                        apply = Some(idx);
+                        ui.close();
+                    }
+                    if ui.small_button("🗑").on_hover_text("Delete this preset").clicked() {
+                        delete = Some(idx);
+                    }
+                });
+            }
+            ui.separator();
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.preset_name).hint_text("preset name").desired_width(140.0));
+                let has_filters = self.column_filters.values().any(|text| !text.trim().is_empty());
+                let enabled = has_filters && !self.preset_name.trim().is_empty();
+                if ui
+                    .add_enabled(enabled, egui::Button::new("Save Current Filters"))
+                    .on_hover_text("Replaces a preset of the same name")
+                    .on_disabled_hover_text("Type a name and set at least one filter")
+                    .clicked()
+                {
+                    save = true;
+                    ui.close();
+                }
+            });
+        });
+
+        if let Some(idx) = apply {
+            let preset = self.config.filter_presets[idx].clone();
+            self.apply_filter_preset(&preset);
+        }
+        if let Some(idx) = delete {
+            let preset = self.config.filter_presets.remove(idx);
+            self.status_message = format!("Deleted filter preset \"{}\"", preset.name);
+            self.save_config();
+        }
+        if save {
+            let preset = FilterPreset::new(&self.preset_name, &self.column_filters);
+            self.status_message = format!("Saved filter preset \"{}\": {}", preset.name, preset.description());
+            match self.config.filter_presets.iter_mut().find(|p| p.name.eq_ignore_ascii_case(&preset.name)) {
+                Some(existing) => *existing = preset,
+                None => self.config.filter_presets.push(preset),
+            }
+            self.preset_name.clear();
+            self.save_config();
+        }
+    }
+
+    /// Replace the column filters with the preset's. Filters on columns this
+    /// dataset lacks are left out and named in the status line.
+    fn apply_filter_preset(&mut self, preset: &FilterPreset) {
+        let Some(ref dataset) = self.dataset else {
+            return;
+        };
+        let missing: Vec<&str> = preset
+            .filters
+            .keys()
+            .filter(|column| dataset.column(column).is_err())
+            .map(String::as_str)
+            .collect();
+        let missing = (!missing.is_empty()).then(|| format!(" (no column {})", missing.join(", ")));
+        for text in self.column_filters.values_mut() {
+            text.clear();
+        }
+        for (column, text) in &preset.filters {
+            if dataset.column(column).is_ok() {
+                self.column_filters.insert(column.clone(), text.clone());
+            }
+        }
+        self.apply_filters();
+        self.invalidate_cache();
+        self.clear_selection();
+        let shown = self.filtered_dataset.as_ref().map_or(0, |df| df.height());
+        let total = self.dataset.as_ref().map_or(0, |df| df.height());
+        self.status_message = format!("Showing {} of {} files with preset \"{}\"{}", shown, total, preset.name, missing.unwrap_or_default());
+    }
+}
@@ -0,0 +1,114 @@
+use super::meta_fields::{build_object, drafts_of, edit_fields, read_editable, save_bar, FieldDraft, NewField};
+use crate::SigViewerApp;
+use eframe::egui;
+use serde_json::{Map, Value};
+use sig_viewer::parser::sigmf::{MetaSnapshot, PROTECTED_GLOBAL_FIELDS};
+use std::path::Path;
+
+/// Core global fields offered when adding a field
+const CORE_GLOBAL_FIELDS: [&str; 7] = [
+    "core:author",
+    "core:description",
+    "core:license",
+    "core:hw",
+    "core:recorder",
+    "core:dataset",
+    "core:meta_doi",
+];
+
+/// Fields a SigMF file can't do without
+const REQUIRED_GLOBAL_FIELDS: [&str; 3] = ["core:datatype", "core:sample_rate", "core:version"];
+
+/// State of the Edit Global Metadata window for one recording
+pub struct GlobalEditor {
+    snapshot: MetaSnapshot,
+    fields: Vec<FieldDraft>,
+    new_field: NewField,
+    error: Option<String>,
+    dirty: bool,
+}
+
+impl GlobalEditor {
+    fn new(snapshot: MetaSnapshot) -> Self {
+        let fields = snapshot
+            .value
+            .get("global")
+            .and_then(Value::as_object)
+            .map(drafts_of)
+            .unwrap_or_default();
+        GlobalEditor {
+            snapshot,
+            fields,
+            new_field: NewField::default(),
+            error: None,
+            dirty: false,
+        }
+    }
+
+    /// The metadata with the edited global object. Captures and annotations
+    /// are left exactly as read.
+    fn build(&self) -> anyhow::Result<Value> {
+        let global: Map<String, Value> = build_object(&self.fields)?;
+        if let Some(missing) = REQUIRED_GLOBAL_FIELDS.iter().find(|key| !global.contains_key(**key)) {
+            anyhow::bail!("{} is required", missing);
+        }
+        let mut meta = self.snapshot.value.clone();
+        let Some(object) = meta.as_object_mut() else {
+            anyhow::bail!("Metadata is not a JSON object");
+        };
+        object.insert("global".to_string(), Value::Object(global));
+        Ok(meta)
+    }
+}
+
+// handle editing a recording's global metadata
+impl SigViewerApp {
+    pub(crate) fn open_global_editor(&mut self, meta_path: &Path) {
+        match read_editable(meta_path) {
+            Ok(snapshot) => self.global_editor = Some(GlobalEditor::new(snapshot)),
+            Err(e) => self.error_message = Some(e),
+        }
+    }
+
+    pub(crate) fn render_global_editor(&mut self, ctx: &egui::Context) {
+        let read_only = self.read_only;
+        let Some(ref mut editor) = self.global_editor else {
+            return;
+        };
+        let mut open = true;
+        let (mut save, mut revert) = (false, false);
+        let title = format!(
+            "Edit Global Metadata: {}",
+            editor.snapshot.path.file_name().map(|n| n.to_string_lossy()).unwrap_or_default()
+        );
+        egui::Window::new(title)
+            .id(egui::Id::new("global_editor"))
+            .open(&mut open)
+            .resizable(true)
+            .default_size([560.0, 400.0])
+            .show(ctx, |ui| {
+                ui.weak("Datatype and checksum follow the data file; change them with the convert and checksum commands.");
+                ui.separator();
+                editor.dirty |= edit_fields(ui, "global", &mut editor.fields, &mut editor.new_field, &CORE_GLOBAL_FIELDS, &PROTECTED_GLOBAL_FIELDS);
+                (save, revert) = save_bar(ui, editor.dirty, read_only, editor.error.as_deref());
+            });
+
+        if save {
+            match editor.build() {
+                Ok(value) => {
+                    let snapshot = editor.snapshot.clone();
+                    if let Some(written) = self.save_meta(&snapshot, &value) {
+                        self.global_editor = Some(GlobalEditor::new(written));
+                        self.status_message.push_str(" (reload the dataset to refresh the table)");
+                    }
+                }
+                Err(e) => editor.error = Some(e.to_string()),
+            }
+        } else if revert {
+            let path = editor.snapshot.path.clone();
+            self.open_global_editor(&path);
+        } else if !open {
+            self.global_editor = None;
+        }
+    }
+}
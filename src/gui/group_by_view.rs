@@ -0,0 +1,215 @@
+use crate::{format_cell_value, SigViewerApp};
+use eframe::egui;
+use polars::prelude::*;
+use sig_viewer::data_ops::{group_by, Aggregation, GroupKey};
+
+/// Aggregation functions offered, as in `groupby --agg`
+const FUNCTIONS: [&str; 7] = ["mean", "median", "sum", "min", "max", "std", "n_unique"];
+
+/// How the rows are grouped; the GUI side of [`GroupKey`]
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum KeyKind {
+    Column,
+    Binned,
+    Day,
+    Hour,
+}
+
+impl KeyKind {
+    fn label(&self) -> &'static str {
+        match self {
+            KeyKind::Column => "Value of",
+            KeyKind::Binned => "Bins of",
+            KeyKind::Day => "Day of",
+            KeyKind::Hour => "Hour of day of",
+        }
+    }
+}
+
+/// State of the Group By window: the key, the aggregations and the grouped
+/// table of the filtered rows
+pub struct GroupByView {
+    kind: KeyKind,
+    column: String,
+    bin_width: f64,
+    aggregations: Vec<Aggregation>,
+    /// Function and column of the aggregation being added
+    new_function: &'static str,
+    new_column: String,
+    /// Rebuilt when the key, the aggregations or the filtered rows change
+    pub result: Option<Result<DataFrame, String>>,
+}
+
+impl GroupByView {
+    fn key(&self) -> GroupKey {
+        match self.kind {
+            KeyKind::Column => GroupKey::Column(self.column.clone()),
+            KeyKind::Binned => GroupKey::Binned { column: self.column.clone(), width: self.bin_width },
+            KeyKind::Day => GroupKey::Day(self.column.clone()),
+            KeyKind::Hour => GroupKey::Hour(self.column.clone()),
+        }
+    }
+}
+
+// handle the group-by summary of the filtered rows
+impl SigViewerApp {
+    /// Open grouped by sdr_handle when the dataset has it, counting rows
+    pub(crate) fn open_group_by_view(&mut self) {
+        if self.group_by_view.is_some() {
+            return;
+        }
+        let columns: Vec<String> = self.filtered_dataset.as_ref().map_or_else(Vec::new, |df| {
+            df.get_column_names().iter().map(|name| name.to_string()).collect()
+        });
+        let column = ["sdr_handle", "datatype"]
+            .iter()
+            .map(|name| name.to_string())
+            .find(|name| columns.contains(name))
+            .or_else(|| columns.first().cloned())
+            .unwrap_or_default();
+        self.group_by_view = Some(GroupByView {
+            kind: KeyKind::Column,
+            column,
+            bin_width: 1e8,
+            aggregations: vec![Aggregation::Count],
+            new_function: "mean",
+            new_column: self.numeric_columns().into_iter().find(|name| name == "snr_db").unwrap_or_default(),
+            result: None,
+        });
+    }
+
+    pub(crate) fn render_group_by_view(&mut self, ctx: &egui::Context) {
+        let Some(mut view) = self.group_by_view.take() else {
+            return;
+        };
+        let mut open = true;
+        egui::Window::new("Group By")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([700.0, 500.0])
+            .show(ctx, |ui| {
+                let Some(dataset) = self.filtered_dataset.clone() else {
+                    ui.label("Load a dataset first");
+                    return;
+                };
+                let columns: Vec<String> = dataset.get_column_names().iter().map(|name| name.to_string()).collect();
+                let numeric = self.numeric_columns();
+                if render_key(ui, &mut view, &columns, &numeric) | render_aggregations(ui, &mut view, &columns) {
+                    view.result = None;
+                }
+                ui.separator();
+
+                if view.result.is_none() {
+                    view.result = Some(group_by(&dataset, &view.key(), &view.aggregations).map_err(|e| e.to_string()));
+                }
+                match view.result {
+                    Some(Ok(ref grouped)) => {
+                        ui.label(format!("{} groups over {} filtered rows", grouped.height(), dataset.height()));
+                        show_grouped(ui, grouped, !self.config.raw_values);
+                    }
+                    Some(Err(ref e)) => {
+                        ui.colored_label(egui::Color32::RED, format!("Cannot group: {}", e));
+                    }
+                    None => {}
+                }
+            });
+        if open {
+            self.group_by_view = Some(view);
+        }
+    }
+}
+
+/// The key row; returns whether it changed
+fn render_key(ui: &mut egui::Ui, view: &mut GroupByView, columns: &[String], numeric: &[String]) -> bool {
+    let before = (view.kind, view.column.clone(), view.bin_width);
+    ui.horizontal(|ui| {
+        ui.label("Group by");
+        egui::ComboBox::from_id_salt("group_by_kind").selected_text(view.kind.label()).show_ui(ui, |ui| {
+            for kind in [KeyKind::Column, KeyKind::Binned, KeyKind::Day, KeyKind::Hour] {
+                ui.selectable_value(&mut view.kind, kind, kind.label());
+            }
+        });
+        // Bins need numbers; days and hours a SigMF datetime column
+        let choices: Vec<&String> = match view.kind {
+            KeyKind::Column => columns.iter().collect(),
+            KeyKind::Binned => numeric.iter().collect(),
+            KeyKind::Day | KeyKind::Hour => columns.iter().filter(|name| name.contains("datetime")).collect(),
+        };
+        egui::ComboBox::from_id_salt("group_by_column").selected_text(view.column.as_str()).height(400.0).show_ui(ui, |ui| {
+            for name in choices {
+                ui.selectable_value(&mut view.column, name.clone(), name);
+            }
+        });
+        if view.kind == KeyKind::Binned {
+            ui.label("width");
+            let speed = view.bin_width * 0.01;
+            ui.add(egui::DragValue::new(&mut view.bin_width).range(f64::MIN_POSITIVE..=f64::MAX).speed(speed));
+        }
+    });
+    before != (view.kind, view.column.clone(), view.bin_width)
+}
+
+/// The aggregation list with remove buttons and a row to add one; returns
+/// whether it changed
+fn render_aggregations(ui: &mut egui::Ui, view: &mut GroupByView, columns: &[String]) -> bool {
+    let mut changed = false;
+    ui.horizontal_wrapped(|ui| {
+        ui.label("Aggregations:");
+        let mut remove = None;
+        for (idx, aggregation) in view.aggregations.iter().enumerate() {
+            if ui.button(format!("{} ✖", aggregation.output_name())).on_hover_text("Remove").clicked() {
+                remove = Some(idx);
+            }
+        }
+        if let Some(idx) = remove {
+            view.aggregations.remove(idx);
+            changed = true;
+        }
+    });
+    ui.horizontal(|ui| {
+        egui::ComboBox::from_id_salt("group_by_function").selected_text(view.new_function).show_ui(ui, |ui| {
+            for function in FUNCTIONS {
+                ui.selectable_value(&mut view.new_function, function, function);
+            }
+        });
+        egui::ComboBox::from_id_salt("group_by_agg_column").selected_text(view.new_column.as_str()).height(400.0).show_ui(ui, |ui| {
+            for name in columns {
+                ui.selectable_value(&mut view.new_column, name.clone(), name);
+            }
+        });
+        let new = Aggregation::parse(&format!("{}:{}", view.new_function, view.new_column)).ok();
+        let addable = new.as_ref().filter(|agg| !view.new_column.is_empty() && !view.aggregations.contains(agg));
+        if ui.add_enabled(addable.is_some(), egui::Button::new("Add")).clicked() {
+            view.aggregations.extend(addable.cloned());
+            changed = true;
+        }
+        let has_count = view.aggregations.contains(&Aggregation::Count);
+        if ui.add_enabled(!has_count, egui::Button::new("Add Count")).clicked() {
+            view.aggregations.insert(0, Aggregation::Count);
+            changed = true;
+        }
+    });
+    changed
+}
+
+fn show_grouped(ui: &mut egui::Ui, grouped: &DataFrame, humanize: bool) {
+    egui::ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+        egui::Grid::new("group_by_result").striped(true).show(ui, |ui| {
+            for name in grouped.get_column_names() {
+                ui.strong(name.to_string());
+            }
+            ui.end_row();
+            for row in 0..grouped.height() {
+                for column in grouped.get_columns() {
+                    let text = format_cell_value(column, row, humanize);
+                    if column.dtype().is_numeric() {
+                        ui.monospace(text);
+                    } else {
+                        ui.label(text);
+                    }
+                }
+                ui.end_row();
+            }
+        });
+    });
+}
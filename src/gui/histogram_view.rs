@@ -0,0 +1,155 @@
+use crate::gui::plot;
+use crate::SigViewerApp;
+use eframe::egui;
+use sig_viewer::data_ops::{histogram, Histogram};
+use sig_viewer::viz::edge_precision;
+
+const DEFAULT_BINS: usize = 30;
+const CHART_HEIGHT: f32 = 280.0;
+const AXIS_WIDTH: f32 = 60.0;
+const AXIS_HEIGHT: f32 = 24.0;
+
+/// The Histogram window's column and options
+pub struct HistogramView {
+    column: String,
+    bins: usize,
+    log_scale: bool,
+    /// Rebuilt when the column, bins or filtered rows change
+    pub histogram: Option<Result<Option<Histogram>, String>>,
+}
+
+// handle the histogram chart of a numeric column
+impl SigViewerApp {
+    /// Open the window on `column`, or on the last or first numeric column
+    pub(crate) fn open_histogram(&mut self, column: Option<&str>) {
+        let numeric = self.numeric_columns();
+        let previous = self.histogram_view.take();
+        let column = column
+            .map(str::to_string)
+            .or_else(|| previous.as_ref().map(|view| view.column.clone()).filter(|column| numeric.contains(column)))
+            .or_else(|| numeric.first().cloned())
+            .unwrap_or_default();
+        let (bins, log_scale) = previous.map_or((DEFAULT_BINS, false), |view| (view.bins, view.log_scale));
+        self.histogram_view = Some(HistogramView { column, bins, log_scale, histogram: None });
+    }
+
+    /// Columns of the filtered dataset that can be binned or plotted
+    pub(crate) fn numeric_columns(&self) -> Vec<String> {
+        self.filtered_dataset.as_ref().map_or_else(Vec::new, |dataset| {
+            dataset.get_columns().iter().filter(|column| column.dtype().is_numeric()).map(|column| column.name().to_string()).collect()
+        })
+    }
+
+    pub(crate) fn render_histogram_window(&mut self, ctx: &egui::Context) {
+        let Some(mut view) = self.histogram_view.take() else {
+            return;
+        };
+        let numeric = self.numeric_columns();
+        let mut open = true;
+        egui::Window::new("Histogram")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([700.0, 380.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    let before = (view.column.clone(), view.bins);
+                    egui::ComboBox::from_id_salt("histogram_column")
+                        .selected_text(view.column.as_str())
+                        .show_ui(ui, |ui| {
+                            for column in &numeric {
+                                ui.selectable_value(&mut view.column, column.clone(), column);
+                            }
+                        });
+                    ui.label("Bins:");
+                    ui.add(egui::DragValue::new(&mut view.bins).range(1..=500));
+                    ui.checkbox(&mut view.log_scale, "Log scale")
+                        .on_hover_text("Scale bars by log(1 + count), so small bins stay visible next to large ones");
+                    if before != (view.column.clone(), view.bins) {
+                        view.histogram = None;
+                    }
+                });
+
+                if view.histogram.is_none() {
+                    if let Some(ref dataset) = self.filtered_dataset {
+                        view.histogram = Some(match dataset.column(&view.column) {
+                            Ok(series) => histogram(series, view.bins).map_err(|e| e.to_string()),
+                            Err(_) => Err("Pick a numeric column".to_string()),
+                        });
+                    }
+                }
+                match view.histogram {
+                    Some(Ok(Some(ref histogram))) => {
+                        let rows = self.filtered_dataset.as_ref().map_or(0, |df| df.height());
+                        ui.label(format!("{} of {} filtered rows have a value", histogram.total(), rows));
+                        draw_histogram(ui, histogram, view.log_scale);
+                    }
+                    Some(Ok(None)) => {
+                        ui.label("No finite values in the filtered rows");
+                    }
+                    Some(Err(ref e)) => {
+                        ui.colored_label(egui::Color32::RED, e);
+                    }
+                    None => {
+                        ui.label("Load a dataset first");
+                    }
+                }
+            });
+        if open {
+            self.histogram_view = Some(view);
+        }
+    }
+}
+
+/// Bars over the column's range with the count axis on the left; hover a
+/// bar for its edges and count
+fn draw_histogram(ui: &mut egui::Ui, histogram: &Histogram, log_scale: bool) {
+    let width = ui.available_width().max(AXIS_WIDTH + 200.0);
+    let (response, painter) = ui.allocate_painter(egui::vec2(width, CHART_HEIGHT + AXIS_HEIGHT), egui::Sense::hover());
+    let plot_rect = egui::Rect::from_min_max(
+        response.rect.min + egui::vec2(AXIS_WIDTH, 8.0),
+        egui::pos2(response.rect.right() - 10.0, response.rect.top() + CHART_HEIGHT),
+    );
+    let visuals = ui.visuals();
+    painter.rect_stroke(plot_rect, 0.0, egui::Stroke::new(1.0, visuals.weak_text_color()), egui::StrokeKind::Inside);
+
+    let bin_width = plot_rect.width() / histogram.counts.len() as f32;
+    let bar = |idx: usize| {
+        let height = histogram.bar_fraction(histogram.counts[idx], log_scale) as f32 * plot_rect.height();
+        let left = plot_rect.left() + idx as f32 * bin_width;
+        egui::Rect::from_min_max(egui::pos2(left, plot_rect.bottom() - height), egui::pos2(left + bin_width, plot_rect.bottom()))
+    };
+    let hovered = response
+        .hover_pos()
+        .filter(|pos| plot_rect.contains(*pos))
+        .map(|pos| (((pos.x - plot_rect.left()) / bin_width) as usize).min(histogram.counts.len() - 1));
+    for idx in 0..histogram.counts.len() {
+        let color = if hovered == Some(idx) { visuals.selection.stroke.color } else { visuals.selection.bg_fill };
+        painter.rect_filled(bar(idx).shrink2(egui::vec2((bin_width * 0.05).min(1.0), 0.0)), 0.0, color);
+    }
+
+    let precision = edge_precision(histogram.bin_width());
+    plot::draw_x_axis(&painter, plot_rect, histogram.min, histogram.max, |value| format!("{:.*}", precision, value));
+    let max_count = histogram.counts.iter().copied().max().unwrap_or(0);
+    if log_scale {
+        draw_log_count_axis(&painter, plot_rect, histogram, max_count);
+    } else {
+        plot::draw_y_axis(&painter, plot_rect, 0.0, max_count as f64, false, |count| format!("{:.0}", count));
+    }
+
+    if let Some(idx) = hovered {
+        let (left, right) = histogram.bin_edges(idx);
+        response.on_hover_text(format!("{:.*} – {:.*}: {}", precision, left, precision, right, histogram.counts[idx]));
+    }
+}
+
+/// Ticks at 1, 10, 100, ... up to the largest count
+fn draw_log_count_axis(painter: &egui::Painter, rect: egui::Rect, histogram: &Histogram, max_count: u64) {
+    let color = painter.ctx().style().visuals.text_color();
+    let mut tick = 1u64;
+    while tick <= max_count {
+        let y = rect.bottom() - histogram.bar_fraction(tick, true) as f32 * rect.height();
+        painter.line_segment([egui::pos2(rect.left() - 4.0, y), egui::pos2(rect.left(), y)], egui::Stroke::new(1.0, color));
+        painter.text(egui::pos2(rect.left() - 6.0, y), egui::Align2::RIGHT_CENTER, tick.to_string(), egui::FontId::monospace(10.0), color);
+        tick = tick.saturating_mul(10);
+    }
+}
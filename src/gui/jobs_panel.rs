@@ -0,0 +1,241 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use polars::prelude::*;
+use sig_viewer::parser::sigmf::ChecksumStatus;
+use sig_viewer::parser::SigMFParser;
+use sig_viewer::viz::estimate_noise_floor;
+use sig_viewer::workers::{Priority, TaskHandle, TaskInfo, TaskStatus};
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// How often the UI refreshes while jobs are running
+const JOB_REPAINT_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Per-recording work that fills a dataset column when it finishes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeasurementKind {
+    Checksum,
+    NoiseFloor,
+}
+
+impl MeasurementKind {
+    fn label(&self) -> &'static str {
+        match self {
+            MeasurementKind::Checksum => "Verify checksum",
+            MeasurementKind::NoiseFloor => "Estimate noise floor",
+        }
+    }
+}
+
+enum Measurement {
+    Checksum(ChecksumStatus),
+    NoiseFloor(f32),
+}
+
+/// A submitted measurement whose result hasn't been applied to the dataset yet
+pub struct PendingMeasurement {
+    meta_filename: String,
+    task: TaskHandle<Measurement>,
+}
+
+// handle background jobs: submitting per-recording measurements, applying
+// their results and the jobs panel
+impl SigViewerApp {
+    /// Queue `kind` for every selected recording
+    pub(crate) fn measure_selected(&mut self, kind: MeasurementKind) {
+        let mut missing = Vec::new();
+        for meta_filename in self.selected_meta_filenames() {
+            let Some(meta_path) = self.locate_meta_file(&meta_filename) else {
+                missing.push(meta_filename);
+                continue;
+            };
+            let name = format!("{}: {}", kind.label(), meta_filename);
+            let task = self.workers.submit(&name, Priority::Normal, move |ctx| {
+                let parser = SigMFParser::from_meta_file(&meta_path)?;
+                match kind {
+                    MeasurementKind::Checksum => {
+                        let status = parser.verify_checksum_with_progress(|f| ctx.report(f))?;
+                        ctx.set_message(status.as_str());
+                        Ok(Measurement::Checksum(status))
+                    }
+                    MeasurementKind::NoiseFloor => {
+                        let db = estimate_noise_floor(&mut parser.open_reader()?)?;
+                        ctx.set_message(&format!("{:.1} dB", db));
+                        Ok(Measurement::NoiseFloor(db))
+                    }
+                }
+            });
+            self.pending_measurements.push(PendingMeasurement { meta_filename, task });
+        }
+        if !missing.is_empty() {
+            self.error_message = Some(format!("Could not locate {}", missing.join(", ")));
+        }
+    }
+
+    /// Menu of measurements for the selected rows
+    pub(crate) fn render_measure_menu(&mut self, ui: &mut egui::Ui) {
+        ui.menu_button("Measure", |ui| {
+            for kind in [MeasurementKind::Checksum, MeasurementKind::NoiseFloor] {
+                if ui.button(kind.label()).clicked() {
+                    self.measure_selected(kind);
+                    self.show_jobs_panel = true;
+                    ui.close();
+                }
+            }
+            ui.separator();
+            if ui.button("Detect bursts...").clicked() {
+                self.open_burst_detector();
+                ui.close();
+            }
+        });
+    }
+
+    /// Apply finished measurements to the dataset and keep the UI ticking
+    /// while anything is running. Called once per frame.
+    pub(crate) fn poll_jobs(&mut self, ctx: &egui::Context) {
+        let mut checksums = HashMap::new();
+        let mut noise_floors = HashMap::new();
+        self.pending_measurements.retain(|pending| match pending.task.try_take() {
+            None => true,
+            Some(Ok(Measurement::Checksum(status))) => {
+                checksums.insert(pending.meta_filename.clone(), status.as_str());
+                false
+            }
+            Some(Ok(Measurement::NoiseFloor(db))) => {
+                noise_floors.insert(pending.meta_filename.clone(), db as f64);
+                false
+            }
+            // Failures stay listed in the jobs panel
+            Some(Err(_)) => false,
+        });
+        if !checksums.is_empty() || !noise_floors.is_empty() {
+            if let Some(ref mut dataset) = self.dataset {
+                match apply_measurements(dataset, &checksums, &noise_floors) {
+                    Ok(()) => self.force_refilter(),
+                    Err(e) => self.error_message = Some(format!("Could not update the dataset: {}", e)),
+                }
+            }
+        }
+        if self.workers.active_count() > 0 {
+            ctx.request_repaint_after(JOB_REPAINT_INTERVAL);
+        }
+    }
+
+    /// Top bar indicator of running jobs, opening the panel
+    pub(crate) fn render_jobs_indicator(&mut self, ui: &mut egui::Ui) {
+        let active = self.workers.active_count();
+        if active > 0 && ui.button(format!("⏳ {} job{}", active, if active == 1 { "" } else { "s" })).clicked() {
+            self.show_jobs_panel = true;
+        }
+    }
+
+    pub(crate) fn render_jobs_panel(&mut self, ctx: &egui::Context) {
+        if !self.show_jobs_panel {
+            return;
+        }
+        let tasks = self.workers.tasks();
+        let mut open = true;
+        egui::Window::new("Jobs")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([560.0, 320.0])
+            .show(ctx, |ui| {
+                let count = |f: fn(&TaskStatus) -> bool| tasks.iter().filter(|t| f(&t.status)).count();
+                ui.horizontal(|ui| {
+                    ui.label(format!(
+                        "{} running, {} queued, {} failed · {} workers",
+                        count(|s| *s == TaskStatus::Running),
+                        count(|s| *s == TaskStatus::Queued),
+                        count(|s| matches!(s, TaskStatus::Failed(_))),
+                        self.workers.threads()
+                    ));
+                    if ui.add_enabled(self.workers.active_count() > 0, egui::Button::new("Cancel All")).clicked() {
+                        self.workers.cancel_all();
+                    }
+                    if ui.button("Clear Finished").clicked() {
+                        self.workers.clear_finished();
+                    }
+                });
+                ui.separator();
+                if tasks.is_empty() {
+                    ui.label("No jobs");
+                    return;
+                }
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    egui::Grid::new("jobs_grid").num_columns(4).striped(true).show(ui, |ui| {
+                        // Newest first
+                        for task in tasks.iter().rev() {
+                            self.render_task_row(ui, task);
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        if !open {
+            self.show_jobs_panel = false;
+        }
+    }
+
+    /// Name, status, detail and cancel button of one task
+    fn render_task_row(&self, ui: &mut egui::Ui, task: &TaskInfo) {
+        let name = ui.label(&task.name);
+        if task.priority != Priority::Normal {
+            name.on_hover_text(format!("{} priority", task.priority.as_str()));
+        }
+        match (&task.status, task.progress) {
+            (TaskStatus::Running, Some(fraction)) => {
+                ui.add(egui::ProgressBar::new(fraction).desired_width(160.0).show_percentage());
+            }
+            (TaskStatus::Running, None) => {
+                ui.spinner();
+            }
+            (TaskStatus::Queued, _) => {
+                ui.weak("queued");
+            }
+            (TaskStatus::Done, _) => {
+                ui.label(if task.message.is_empty() { "done" } else { &task.message });
+            }
+            (TaskStatus::Failed(_), _) => {
+                ui.colored_label(egui::Color32::RED, "failed");
+            }
+            (TaskStatus::Cancelled, _) => {
+                ui.weak("cancelled");
+            }
+        }
+        match task.status {
+            TaskStatus::Failed(ref e) => ui.label(e),
+            _ => ui.weak(task.elapsed().map(|d| format!("{:.1}s", d.as_secs_f32())).unwrap_or_default()),
+        };
+        if task.status.is_finished() {
+            ui.label("");
+        } else if ui.small_button("Cancel").clicked() {
+            self.workers.cancel(task.id);
+        }
+    }
+}
+
+/// Write finished measurements into the dataset's `checksum_status` and
+/// `noise_floor_db` columns, by `meta_filename`. Other rows keep their value.
+fn apply_measurements(dataset: &mut DataFrame, checksums: &HashMap<String, &str>, noise_floors: &HashMap<String, f64>) -> anyhow::Result<()> {
+    if !checksums.is_empty() {
+        let new = lookup_by_filename(dataset, checksums)?;
+        let merged: StringChunked = dataset.column("checksum_status")?.str()?.iter().zip(new).map(|(old, new)| new.or(old)).collect();
+        dataset.with_column(merged.into_series().with_name("checksum_status".into()))?;
+    }
+    if !noise_floors.is_empty() {
+        let new = lookup_by_filename(dataset, noise_floors)?;
+        let merged: Float64Chunked = dataset.column("noise_floor_db")?.f64()?.iter().zip(new).map(|(old, new)| new.or(old)).collect();
+        dataset.with_column(merged.into_series().with_name("noise_floor_db".into()))?;
+    }
+    Ok(())
+}
+
+/// The entry of `values` for each row's `meta_filename`
+fn lookup_by_filename<T: Copy>(dataset: &DataFrame, values: &HashMap<String, T>) -> anyhow::Result<Vec<Option<T>>> {
+    Ok(dataset
+        .column("meta_filename")?
+        .str()?
+        .iter()
+        .map(|name| name.and_then(|name| values.get(name).copied()))
+        .collect())
+}
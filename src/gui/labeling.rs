@@ -0,0 +1,280 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use polars::prelude::*;
+use sig_viewer::parser::sigmf::{write_row_label, Journal, RowAnnotation};
+
+/// Keys assigning the first nine classes
+const CLASS_KEYS: [egui::Key; 9] = [
+    egui::Key::Num1,
+    egui::Key::Num2,
+    egui::Key::Num3,
+    egui::Key::Num4,
+    egui::Key::Num5,
+    egui::Key::Num6,
+    egui::Key::Num7,
+    egui::Key::Num8,
+    egui::Key::Num9,
+];
+
+const LABELING_HELP: &str = "1-9: assign a class\n\
+    → or J: next row, ← or K: previous row\n\
+    N: next unlabeled row\n\
+    Delete: clear the label";
+
+/// State of the Labeling window. Every label written while it's open is one
+/// journal batch, so a session can be undone with `rollback`.
+pub struct LabelingSession {
+    journal: Journal,
+    auto_advance: bool,
+    new_class: String,
+    labeled: usize,
+}
+
+enum LabelAction {
+    Assign(String),
+    Clear,
+    Step(isize),
+    NextUnlabeled,
+}
+
+// handle labeling rows for training sets
+impl SigViewerApp {
+    pub(crate) fn open_labeling(&mut self) {
+        if self.labeling.is_none() {
+            self.labeling = Some(LabelingSession {
+                journal: Journal::begin("GUI labeling session"),
+                auto_advance: true,
+                new_class: String::new(),
+                labeled: 0,
+            });
+        }
+        if self.selected_row.is_none() && self.filtered_dataset.as_ref().is_some_and(|df| df.height() > 0) {
+            self.select_row(0);
+        }
+    }
+
+    pub(crate) fn render_labeling(&mut self, ctx: &egui::Context) {
+        let Some(mut session) = self.labeling.take() else {
+            return;
+        };
+        let (row_count, current_label) = match self.filtered_dataset.as_ref() {
+            Some(df) => (df.height(), self.selected_row.and_then(|row| row_label(df, row))),
+            None => (0, None),
+        };
+        // Capture segments and whole files don't map to a single annotation
+        let per_annotation = self.filtered_dataset.as_ref().is_some_and(|df| df.column("annotation_index").is_ok());
+        let mut action = None;
+        let mut open = true;
+
+        if per_annotation && !self.read_only && !ctx.wants_keyboard_input() {
+            ctx.input(|input| {
+                for (key, class) in CLASS_KEYS.iter().zip(&self.config.label_classes) {
+                    if input.key_pressed(*key) {
+                        action = Some(LabelAction::Assign(class.clone()));
+                    }
+                }
+                if input.key_pressed(egui::Key::ArrowRight) || input.key_pressed(egui::Key::J) {
+                    action = Some(LabelAction::Step(1));
+                } else if input.key_pressed(egui::Key::ArrowLeft) || input.key_pressed(egui::Key::K) {
+                    action = Some(LabelAction::Step(-1));
+                } else if input.key_pressed(egui::Key::N) {
+                    action = Some(LabelAction::NextUnlabeled);
+                } else if input.key_pressed(egui::Key::Delete) {
+                    action = Some(LabelAction::Clear);
+                }
+            });
+        }
+
+        let mut classes_changed = false;
+        egui::Window::new("Labeling")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([900.0, 650.0])
+            .show(ctx, |ui| {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("Classes:");
+                    let mut remove = None;
+                    for (idx, class) in self.config.label_classes.iter().enumerate() {
+                        let text = if idx < CLASS_KEYS.len() { format!("{} {}", idx + 1, class) } else { class.clone() };
+                        let selected = current_label.as_deref() == Some(class.as_str());
+                        let response = ui.add_enabled(per_annotation && !self.read_only, egui::Button::new(text).selected(selected));
+                        if response.clicked() {
+                            action = Some(LabelAction::Assign(class.clone()));
+                        }
+                        response.context_menu(|ui| {
+                            if ui.button("Remove class").clicked() {
+                                remove = Some(idx);
+                                ui.close();
+                            }
+                        });
+                    }
+                    if let Some(idx) = remove {
+                        self.config.label_classes.remove(idx);
+                        classes_changed = true;
+                    }
+                    let response = ui.add(egui::TextEdit::singleline(&mut session.new_class).hint_text("new class").desired_width(100.0));
+                    let entered = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    let class = session.new_class.trim().to_string();
+                    if (ui.button("Add").clicked() || entered) && !class.is_empty() && !self.config.label_classes.contains(&class) {
+                        self.config.label_classes.push(class);
+                        session.new_class.clear();
+                        classes_changed = true;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("◀ Previous").clicked() {
+                        action = Some(LabelAction::Step(-1));
+                    }
+                    if ui.button("Next ▶").clicked() {
+                        action = Some(LabelAction::Step(1));
+                    }
+                    if ui.button("Next unlabeled").clicked() {
+                        action = Some(LabelAction::NextUnlabeled);
+                    }
+                    if ui.add_enabled(per_annotation && !self.read_only && current_label.is_some(), egui::Button::new("Clear label")).clicked() {
+                        action = Some(LabelAction::Clear);
+                    }
+                    ui.checkbox(&mut session.auto_advance, "Advance after labeling");
+                    ui.weak("Keys").on_hover_text(LABELING_HELP);
+                });
+                match self.selected_row {
+                    Some(row) => ui.label(format!(
+                        "Row {} of {}: {}",
+                        row + 1,
+                        row_count,
+                        current_label.as_deref().map_or("unlabeled".to_string(), |label| format!("labeled {}", label))
+                    )),
+                    None => ui.label(format!("{} rows; step to one to start", row_count)),
+                };
+                ui.weak(format!("{} labels written this session", session.labeled));
+                if !per_annotation {
+                    ui.colored_label(egui::Color32::RED, "Labeling needs one row per ML annotation; reload without \"one row per capture segment\".");
+                } else if self.read_only {
+                    ui.colored_label(egui::Color32::from_rgb(200, 120, 0), "Read-only mode: labels can't be written");
+                }
+                ui.separator();
+                if self.selected_row.is_some() {
+                    self.render_recording_tab(ui, |app| &mut app.spectrogram_view);
+                }
+            });
+
+        if classes_changed {
+            self.save_config();
+        }
+        if open {
+            self.labeling = Some(session);
+        }
+        match action {
+            Some(LabelAction::Assign(class)) => self.label_selected_row(Some(&class)),
+            Some(LabelAction::Clear) => self.label_selected_row(None),
+            Some(LabelAction::Step(step)) => self.step_labeling_row(step),
+            Some(LabelAction::NextUnlabeled) => self.next_unlabeled_row(),
+            None => {}
+        }
+    }
+
+    fn step_labeling_row(&mut self, step: isize) {
+        let Some(height) = self.filtered_dataset.as_ref().map(DataFrame::height).filter(|h| *h > 0) else {
+            return;
+        };
+        let row = match self.selected_row {
+            Some(row) => row.saturating_add_signed(step).min(height - 1),
+            None => 0,
+        };
+        self.select_row(row);
+    }
+
+    fn next_unlabeled_row(&mut self) {
+        let Some(df) = self.filtered_dataset.as_ref() else {
+            return;
+        };
+        let start = self.selected_row.map_or(0, |row| row + 1);
+        match (start..df.height()).find(|&row| row_label(df, row).is_none()) {
+            Some(row) => self.select_row(row),
+            None => self.status_message = "No unlabeled rows after this one".to_string(),
+        }
+    }
+
+    /// Write `label` to the selected row's annotation and `label` column
+    fn label_selected_row(&mut self, label: Option<&str>) {
+        if self.read_only {
+            self.error_message = Some("Read-only mode: changes can't be saved".to_string());
+            return;
+        }
+        let (Some(row), Some(df)) = (self.selected_row, self.filtered_dataset.as_ref()) else {
+            return;
+        };
+        let Some((meta_filename, key)) = row_annotation(df, row) else {
+            return;
+        };
+        let Some(meta_path) = self.locate_meta_file(&meta_filename) else {
+            self.error_message = Some(format!("Could not locate {}", meta_filename));
+            return;
+        };
+        let Some(session) = self.labeling.as_mut() else {
+            return;
+        };
+        if let Err(e) = write_row_label(&meta_path, &key, label, &session.journal) {
+            self.error_message = Some(format!("Failed to label {}: {}", meta_filename, e));
+            return;
+        }
+        session.labeled += 1;
+        let auto_advance = session.auto_advance && label.is_some();
+
+        let label = label.unwrap_or("");
+        let relabel = |df: &DataFrame| set_row_label(df, &meta_filename, &key, label);
+        match (self.dataset.as_ref().map(relabel).transpose(), self.filtered_dataset.as_ref().map(relabel).transpose()) {
+            (Ok(dataset), Ok(filtered)) => {
+                self.dataset = dataset;
+                self.filtered_dataset = filtered;
+                self.invalidate_cache();
+            }
+            (Err(e), _) | (_, Err(e)) => self.error_message = Some(format!("Labeled {} but couldn't update the table: {}", meta_filename, e)),
+        }
+        if auto_advance {
+            self.step_labeling_row(1);
+        } else {
+            self.focus_row(row);
+        }
+    }
+}
+
+/// A row's label, if it has one
+fn row_label(df: &DataFrame, row: usize) -> Option<String> {
+    let label = df.column("label").ok()?.str().ok()?.get(row)?;
+    (!label.is_empty()).then(|| label.to_string())
+}
+
+/// `meta_filename` of a row, and what identifies its annotation
+fn row_annotation(df: &DataFrame, row: usize) -> Option<(String, RowAnnotation)> {
+    let text = |name: &str| df.column(name).ok()?.str().ok()?.get(row).map(str::to_string);
+    let number = |name: &str| df.column(name).ok()?.f64().ok()?.get(row);
+    Some((
+        text("meta_filename")?,
+        RowAnnotation {
+            sig_uuid: text("sig_uuid").unwrap_or_default(),
+            sig_center_freq_hz: number("sig_center_freq_hz").unwrap_or(0.0),
+            sig_bandwidth_hz: number("sig_bandwidth_hz").unwrap_or(0.0),
+        },
+    ))
+}
+
+/// `df` with `label` in the `label` column of the rows of `key`'s annotation
+fn set_row_label(df: &DataFrame, meta_filename: &str, key: &RowAnnotation, label: &str) -> PolarsResult<DataFrame> {
+    let mut matches = col("meta_filename").eq(lit(meta_filename.to_string()));
+    if df.column("sig_uuid").is_ok() {
+        matches = matches.and(col("sig_uuid").eq(lit(key.sig_uuid.clone())));
+    }
+    if key.sig_uuid.is_empty() {
+        for (name, value) in [("sig_center_freq_hz", key.sig_center_freq_hz), ("sig_bandwidth_hz", key.sig_bandwidth_hz)] {
+            if df.column(name).is_ok_and(|column| column.dtype() == &DataType::Float64) {
+                matches = matches.and(col(name).eq(lit(value)));
+            }
+        }
+    }
+    let label_expr = if df.column("label").is_ok() { col("label") } else { lit("") };
+    df.clone()
+        .lazy()
+        .with_column(when(matches).then(lit(label.to_string())).otherwise(label_expr).alias("label"))
+        .collect()
+}
@@ -0,0 +1,118 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// A deployment-specific "Open in…" link, e.g. a case tracker URL with
+/// `{column}` placeholders filled from the row
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct LinkTemplate {
+    pub name: String,
+    pub url_template: String,
+}
+
+impl LinkTemplate {
+    /// Fill in `{column}` placeholders. Returns None if a referenced column is
+    /// missing or empty, since a half-filled URL isn't useful.
+    pub fn expand(&self, lookup: impl Fn(&str) -> Option<String>) -> Option<String> {
+        let mut url = String::with_capacity(self.url_template.len());
+        let mut rest = self.url_template.as_str();
+        while let Some(open) = rest.find('{') {
+            let close = open + rest[open..].find('}')?;
+            url.push_str(&rest[..open]);
+            let value = lookup(&rest[open + 1..close]).filter(|v| !v.is_empty())?;
+            url.push_str(&percent_encode(&value));
+            rest = &rest[close + 1..];
+        }
+        url.push_str(rest);
+        Some(url)
+    }
+}
+
+fn percent_encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-_.~".contains(&byte) {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+/// Unformatted cell value, so numbers keep full precision in URLs
+fn raw_cell_value(column: &Series, row_idx: usize) -> Option<String> {
+    match column.get(row_idx).ok()? {
+        AnyValue::Null => None,
+        AnyValue::String(s) => Some(s.to_string()),
+        AnyValue::StringOwned(s) => Some(s.to_string()),
+        other => Some(other.to_string()),
+    }
+}
+
+// handle record links
+impl SigViewerApp {
+    /// (name, url) for every configured template that can be filled for a row
+    /// of the filtered dataset
+    pub(crate) fn row_links(&self, dataset: &DataFrame, row_idx: usize) -> Vec<(String, String)> {
+        self.config.link_templates.iter()
+            .filter_map(|template| {
+                let url = template.expand(|name| {
+                    dataset.column(name).ok().and_then(|column| raw_cell_value(column, row_idx))
+                })?;
+                Some((template.name.clone(), url))
+            })
+            .collect()
+    }
+
+    pub(crate) fn render_link_settings(&mut self, ctx: &egui::Context) {
+        if !self.show_link_settings {
+            return;
+        }
+        let mut open = true;
+        let mut changed = false;
+        egui::Window::new("Record Links")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([500.0, 250.0])
+            .show(ctx, |ui| {
+                ui.label("URL templates shown as \"Open in…\" links for each row. Use {column_name} placeholders, e.g. https://tracker/cases?uuid={sig_uuid}");
+                ui.separator();
+
+                let mut remove = None;
+                egui::Grid::new("link_templates").num_columns(3).striped(true).show(ui, |ui| {
+                    ui.strong("Name");
+                    ui.strong("URL template");
+                    ui.end_row();
+                    for (idx, template) in self.config.link_templates.iter_mut().enumerate() {
+                        changed |= ui.text_edit_singleline(&mut template.name).changed();
+                        changed |= ui.add(egui::TextEdit::singleline(&mut template.url_template).desired_width(320.0)).changed();
+                        if ui.button("Remove").clicked() {
+                            remove = Some(idx);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(idx) = remove {
+                    self.config.link_templates.remove(idx);
+                    changed = true;
+                }
+                if ui.button("Add Link").clicked() {
+                    self.config.link_templates.push(LinkTemplate {
+                        name: "Tracker".to_string(),
+                        url_template: "https://tracker/cases?uuid={sig_uuid}".to_string(),
+                    });
+                    changed = true;
+                }
+            });
+
+        if changed {
+            self.config.save();
+            self.invalidate_cache();
+        }
+        if !open {
+            self.show_link_settings = false;
+        }
+    }
+}
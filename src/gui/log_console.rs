@@ -0,0 +1,173 @@
+use crate::SigViewerApp;
+use chrono::{DateTime, Local};
+use eframe::egui;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+
+/// Lines the console keeps; older ones are dropped
+const MAX_LOG_LINES: usize = 5000;
+
+/// Levels offered by the console's filter, most severe first
+const LEVELS: [Level; 4] = [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG];
+
+pub struct LogLine {
+    time: DateTime<Local>,
+    level: Level,
+    message: String,
+}
+
+/// Events captured for the log console, filled by its tracing layer from
+/// any thread
+#[derive(Clone, Default)]
+pub struct LogBuffer {
+    lines: Arc<Mutex<VecDeque<LogLine>>>,
+    /// Warnings and errors logged since startup, dropped lines included
+    warnings: Arc<AtomicUsize>,
+}
+
+impl LogBuffer {
+    /// Log sig_viewer's own events to stderr (info and up) and into a new
+    /// buffer (debug and up); other crates only from warnings
+    pub fn install() -> Self {
+        let buffer = LogBuffer::default();
+        let targets = |level: LevelFilter| {
+            Targets::new().with_target("sig_viewer", level).with_target("sig_viewer_gui", level).with_default(LevelFilter::WARN)
+        };
+        let stderr = tracing_subscriber::fmt::layer().with_writer(std::io::stderr).without_time().with_target(false);
+        let installed = tracing_subscriber::registry()
+            .with(stderr.with_filter(targets(LevelFilter::INFO)))
+            .with(buffer.clone().with_filter(targets(LevelFilter::DEBUG)))
+            .try_init();
+        if let Err(e) = installed {
+            eprintln!("Log console unavailable: {}", e);
+        }
+        buffer
+    }
+
+    fn push(&self, line: LogLine) {
+        if line.level <= Level::WARN {
+            self.warnings.fetch_add(1, Ordering::Relaxed);
+        }
+        let Ok(mut lines) = self.lines.lock() else {
+            return;
+        };
+        if lines.len() == MAX_LOG_LINES {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.warnings.load(Ordering::Relaxed)
+    }
+
+    fn clear(&self) {
+        if let Ok(mut lines) = self.lines.lock() {
+            lines.clear();
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for LogBuffer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut message = MessageText::default();
+        event.record(&mut message);
+        self.push(LogLine { time: Local::now(), level: *event.metadata().level(), message: message.0 });
+    }
+}
+
+/// An event's message followed by any other fields as `name=value`
+#[derive(Default)]
+struct MessageText(String);
+
+impl Visit for MessageText {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.0.insert_str(0, &format!("{:?}", value));
+        } else {
+            let _ = write!(self.0, " {}={:?}", field.name(), value);
+        }
+    }
+}
+
+fn level_color(level: Level, ui: &egui::Ui) -> egui::Color32 {
+    match level {
+        Level::ERROR => egui::Color32::RED,
+        Level::WARN => egui::Color32::from_rgb(200, 120, 0),
+        Level::INFO => ui.visuals().text_color(),
+        _ => ui.visuals().weak_text_color(),
+    }
+}
+
+// handle the log console panel at the bottom of the window
+impl SigViewerApp {
+    /// Warnings logged since the console was last open, as a button that
+    /// opens it
+    pub(crate) fn render_log_indicator(&mut self, ui: &mut egui::Ui) {
+        let unseen = self.log.warning_count().saturating_sub(self.log_warnings_seen);
+        if unseen == 0 || self.config.show_log_console {
+            return;
+        }
+        let label = format!("⚠ {} warning{}", unseen, if unseen == 1 { "" } else { "s" });
+        if ui.button(egui::RichText::new(label).color(egui::Color32::from_rgb(200, 120, 0))).on_hover_text("Open the log console").clicked() {
+            self.config.show_log_console = true;
+            self.save_config();
+        }
+    }
+
+    pub(crate) fn render_log_console(&mut self, ctx: &egui::Context) {
+        if !self.config.show_log_console {
+            return;
+        }
+        self.log_warnings_seen = self.log.warning_count();
+        let mut close = false;
+        egui::TopBottomPanel::bottom("log_console").resizable(true).default_height(180.0).show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                ui.strong("Log");
+                egui::ComboBox::from_id_salt("log_level").selected_text(self.log_level.as_str()).show_ui(ui, |ui| {
+                    for level in LEVELS {
+                        ui.selectable_value(&mut self.log_level, level, level.as_str());
+                    }
+                });
+                ui.add(egui::TextEdit::singleline(&mut self.log_search).hint_text("Search").desired_width(200.0));
+                if ui.button("Clear").clicked() {
+                    self.log.clear();
+                }
+                ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                    close = ui.button("✖").on_hover_text("Hide the console (View → Log Console)").clicked();
+                });
+            });
+            ui.separator();
+            let Ok(lines) = self.log.lines.lock() else {
+                return;
+            };
+            let search = self.log_search.to_lowercase();
+            let shown: Vec<&LogLine> = lines
+                .iter()
+                .filter(|line| line.level <= self.log_level)
+                .filter(|line| search.is_empty() || line.message.to_lowercase().contains(&search))
+                .collect();
+            let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+            egui::ScrollArea::both().auto_shrink([false, false]).stick_to_bottom(true).show_rows(ui, row_height, shown.len(), |ui, rows| {
+                for line in &shown[rows] {
+                    ui.horizontal(|ui| {
+                        ui.weak(egui::RichText::new(line.time.format("%H:%M:%S").to_string()).monospace());
+                        ui.label(egui::RichText::new(format!("{:5}", line.level.as_str())).monospace().color(level_color(line.level, ui)));
+                        ui.label(egui::RichText::new(&line.message).monospace());
+                    });
+                }
+            });
+        });
+        if close {
+            self.config.show_log_console = false;
+            self.save_config();
+        }
+    }
+}
@@ -0,0 +1,365 @@
+use crate::gui::cluster_view::cluster_color;
+use crate::gui::plot;
+use crate::SigViewerApp;
+use eframe::egui;
+use polars::prelude::*;
+use sig_viewer::data_ops::{geo_points, GeoFilter, GeoPoints, CLUSTER_COLUMN};
+use sig_viewer::viz::Colormap;
+
+const AXIS_MARGIN_LEFT: f32 = 70.0;
+const AXIS_MARGIN_BOTTOM: f32 = 20.0;
+const MARKER_RADIUS: f32 = 4.0;
+/// How close (in pixels) a click or hover has to be to pick a marker
+const PICK_DISTANCE: f32 = 8.0;
+/// Smallest area shown when fitting, in degrees, so a single site isn't
+/// zoomed in infinitely
+const MIN_FIT_SPAN_DEG: f64 = 0.01;
+const NO_VALUE_COLOR: egui::Color32 = egui::Color32::from_rgb(150, 150, 150);
+const UNIFORM_COLOR: egui::Color32 = egui::Color32::from_rgb(31, 119, 180);
+const BOX_COLOR: egui::Color32 = egui::Color32::from_rgb(214, 39, 40);
+
+/// Pan and zoom of the map. Longitude is scaled by the cosine of the center
+/// latitude (equirectangular projection), so distances look right locally.
+#[derive(Debug, Clone, Default)]
+pub struct MapViewport {
+    /// (longitude, latitude) at the middle of the plot; `None` fits all points
+    center: Option<(f64, f64)>,
+    /// Degrees of latitude per pixel
+    scale: f64,
+    /// Bounds of the points last fitted, to refit when filtering moves them
+    fitted_to: Option<(f64, f64, f64, f64)>,
+    /// (latitude, longitude) where a box being drawn started
+    box_start: Option<(f64, f64)>,
+}
+
+impl MapViewport {
+    pub fn reset(&mut self) {
+        self.center = None;
+    }
+
+    fn fit(&mut self, geo: &GeoPoints, size: egui::Vec2) {
+        self.fitted_to = geo.bounds();
+        let Some((lat_min, lat_max, lon_min, lon_max)) = self.fitted_to else {
+            return;
+        };
+        let center_lat = (lat_min + lat_max) / 2.0;
+        let lon_scale = center_lat.to_radians().cos().max(0.01);
+        let lat_span = (lat_max - lat_min).max(MIN_FIT_SPAN_DEG);
+        let lon_span = ((lon_max - lon_min) * lon_scale).max(MIN_FIT_SPAN_DEG);
+        self.center = Some(((lon_min + lon_max) / 2.0, center_lat));
+        // A margin so markers on the edge aren't cut in half
+        self.scale = (lat_span / size.y as f64).max(lon_span / size.x as f64) * 1.15;
+    }
+
+    fn lon_scale(&self) -> f64 {
+        let (_, lat) = self.center.unwrap_or_default();
+        lat.to_radians().cos().max(0.01)
+    }
+
+    fn to_screen(&self, rect: egui::Rect, latitude: f64, longitude: f64) -> egui::Pos2 {
+        let (center_lon, center_lat) = self.center.unwrap_or_default();
+        rect.center()
+            + egui::vec2(
+                ((longitude - center_lon) * self.lon_scale() / self.scale) as f32,
+                (-(latitude - center_lat) / self.scale) as f32,
+            )
+    }
+
+    /// (latitude, longitude) under a screen position
+    fn geo_at(&self, rect: egui::Rect, pos: egui::Pos2) -> (f64, f64) {
+        let (center_lon, center_lat) = self.center.unwrap_or_default();
+        let offset = pos - rect.center();
+        (
+            center_lat - offset.y as f64 * self.scale,
+            center_lon + offset.x as f64 * self.scale / self.lon_scale(),
+        )
+    }
+
+    fn pan(&mut self, delta: egui::Vec2) {
+        let lon_scale = self.lon_scale();
+        if let Some((ref mut lon, ref mut lat)) = self.center {
+            *lon -= delta.x as f64 * self.scale / lon_scale;
+            *lat += delta.y as f64 * self.scale;
+        }
+    }
+
+    /// Zoom by `factor` (> 1 zooms in) keeping the point under `anchor` fixed
+    fn zoom(&mut self, rect: egui::Rect, factor: f64, anchor: egui::Pos2) {
+        let (lat, lon) = self.geo_at(rect, anchor);
+        self.scale /= factor;
+        let (new_lat, new_lon) = self.geo_at(rect, anchor);
+        if let Some((ref mut center_lon, ref mut center_lat)) = self.center {
+            *center_lon += lon - new_lon;
+            *center_lat += lat - new_lat;
+        }
+    }
+}
+
+fn format_degrees(degrees: f64) -> String {
+    format!("{:.3}°", degrees)
+}
+
+/// The box with corners at two (latitude, longitude) points, kept on the map
+fn bounding_box((lat_a, lon_a): (f64, f64), (lat_b, lon_b): (f64, f64)) -> GeoFilter {
+    let lat = |lat: f64| lat.clamp(-90.0, 90.0);
+    let lon = |lon: f64| lon.clamp(-180.0, 180.0);
+    GeoFilter::BoundingBox {
+        lat_min: lat(lat_a.min(lat_b)),
+        lon_min: lon(lon_a.min(lon_b)),
+        lat_max: lat(lat_a.max(lat_b)),
+        lon_max: lon(lon_a.max(lon_b)),
+    }
+}
+
+// handle the geolocation map of the filtered recordings
+impl SigViewerApp {
+    pub(crate) fn render_map_window(&mut self, ctx: &egui::Context) {
+        if !self.show_map_window {
+            return;
+        }
+        let mut open = true;
+        egui::Window::new("Recording Map")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([800.0, 600.0])
+            .show(ctx, |ui| {
+                let Some(dataset) = self.filtered_dataset.clone() else {
+                    ui.label("Load a dataset first");
+                    return;
+                };
+                self.render_map_controls(ui, &dataset);
+
+                if self.map_points.is_none() {
+                    self.map_points = Some(geo_points(&dataset, self.map_color_column.as_deref()).map_err(|e| e.to_string()));
+                }
+                let geo = match self.map_points {
+                    Some(Ok(ref geo)) => geo.clone(),
+                    Some(Err(ref e)) => {
+                        ui.colored_label(egui::Color32::RED, format!("Cannot plot locations: {}", e));
+                        return;
+                    }
+                    None => return,
+                };
+                if geo.skipped_rows > 0 {
+                    ui.label(format!("{} rows without a location were skipped", geo.skipped_rows));
+                }
+                if geo.points.is_empty() {
+                    ui.label("No recordings with a location in the current view");
+                    return;
+                }
+                if let Some(row) = self.draw_map(ui, &geo, &dataset) {
+                    let modifiers = ui.input(|i| i.modifiers);
+                    self.click_row(row, modifiers);
+                }
+            });
+        if !open {
+            self.show_map_window = false;
+        }
+    }
+
+    fn render_map_controls(&mut self, ui: &mut egui::Ui, dataset: &DataFrame) {
+        ui.horizontal(|ui| {
+            ui.label("Color by:");
+            let selected = self.map_color_column.clone().unwrap_or_else(|| "(none)".to_string());
+            let mut choice = self.map_color_column.clone();
+            egui::ComboBox::from_id_salt("map_color_column")
+                .selected_text(selected)
+                .height(400.0)
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut choice, None, "(none)");
+                    for column in dataset.get_columns() {
+                        let dtype = column.dtype();
+                        if dtype.is_numeric() || matches!(dtype, DataType::Boolean) {
+                            let name = column.name().to_string();
+                            ui.selectable_value(&mut choice, Some(name.clone()), name);
+                        }
+                    }
+                });
+            if choice != self.map_color_column {
+                self.map_color_column = choice;
+                self.map_points = None;
+            }
+            if ui.button("Reset View").clicked() {
+                self.map_viewport.reset();
+            }
+            ui.toggle_value(&mut self.map_drawing_box, "Draw Box")
+                .on_hover_text("Drag on the map to keep only the rows recorded inside a box");
+            if let Some(bbox) = self.map_bbox {
+                ui.label(bbox.to_string());
+                if ui.button("Clear Box").clicked() {
+                    self.set_map_bbox(None);
+                }
+            }
+            if self.map_drawing_box {
+                ui.weak("Drag to draw a box, scroll to zoom");
+            } else {
+                ui.weak("Drag to pan, scroll to zoom, click a marker to select its row");
+            }
+        });
+    }
+
+    /// Filter the rows to a box drawn on the map, or stop with `None`
+    fn set_map_bbox(&mut self, bbox: Option<GeoFilter>) {
+        self.map_bbox = bbox;
+        self.apply_filters();
+        self.clear_selection();
+    }
+
+    /// Draw the markers; returns the row of a clicked marker
+    fn draw_map(&mut self, ui: &mut egui::Ui, geo: &GeoPoints, dataset: &DataFrame) -> Option<usize> {
+        let size = ui.available_size().max(egui::vec2(300.0, 200.0));
+        let (response, painter) = ui.allocate_painter(size, egui::Sense::click_and_drag());
+        let rect = egui::Rect::from_min_max(
+            response.rect.min + egui::vec2(AXIS_MARGIN_LEFT, 0.0),
+            response.rect.max - egui::vec2(0.0, AXIS_MARGIN_BOTTOM),
+        );
+        let viewport = &mut self.map_viewport;
+        if viewport.center.is_none() || viewport.fitted_to != geo.bounds() {
+            viewport.fit(geo, rect.size());
+        }
+        let mut drawn_box = None;
+        if self.map_drawing_box {
+            if response.drag_started() {
+                viewport.box_start = response.interact_pointer_pos().map(|pos| viewport.geo_at(rect, pos));
+            }
+            if response.drag_stopped() {
+                let end = response.interact_pointer_pos().map(|pos| viewport.geo_at(rect, pos));
+                if let (Some(start), Some(end)) = (viewport.box_start.take(), end) {
+                    drawn_box = Some(bounding_box(start, end));
+                }
+            }
+        } else if response.dragged() {
+            viewport.pan(response.drag_delta());
+        }
+        if let Some(pos) = response.hover_pos() {
+            let scroll = ui.input(|i| i.smooth_scroll_delta.y);
+            if scroll != 0.0 {
+                viewport.zoom(rect, (scroll as f64 * 0.003).exp(), pos);
+            }
+        }
+        let viewport = viewport.clone();
+
+        // Graticule, labelled along the axes
+        let visuals = ui.visuals();
+        let grid = egui::Stroke::new(1.0, visuals.weak_text_color().gamma_multiply(0.3));
+        let (lat_min, lon_min) = viewport.geo_at(rect, rect.left_bottom());
+        let (lat_max, lon_max) = viewport.geo_at(rect, rect.right_top());
+        for lon in plot::nice_ticks(lon_min, lon_max, 5) {
+            let x = viewport.to_screen(rect, lat_min, lon).x;
+            painter.line_segment([egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())], grid);
+        }
+        for lat in plot::nice_ticks(lat_min, lat_max, 5) {
+            let y = viewport.to_screen(rect, lat, lon_min).y;
+            painter.line_segment([egui::pos2(rect.left(), y), egui::pos2(rect.right(), y)], grid);
+        }
+        painter.rect_stroke(rect, 0.0, egui::Stroke::new(1.0, visuals.weak_text_color()), egui::StrokeKind::Inside);
+        plot::draw_x_axis(&painter, rect, lon_min, lon_max, format_degrees);
+        plot::draw_y_axis(&painter, rect, lat_min, lat_max, false, format_degrees);
+
+        let value_range = geo.value_range();
+        // Cluster ids are categories, not a scale
+        let by_cluster = self.map_color_column.as_deref() == Some(CLUSTER_COLUMN);
+        let color_of = |value: Option<f64>| match (self.map_color_column.is_some(), value, value_range) {
+            (false, _, _) => UNIFORM_COLOR,
+            (true, Some(value), _) if by_cluster => cluster_color(value as i64),
+            (true, Some(value), Some((min, max))) => {
+                let t = if max > min { ((value - min) / (max - min)) as f32 } else { 0.5 };
+                let [r, g, b] = Colormap::Viridis.map(t);
+                egui::Color32::from_rgb(r, g, b)
+            }
+            (true, _, _) => NO_VALUE_COLOR,
+        };
+
+        let clipped = painter.with_clip_rect(rect);
+        let positions: Vec<egui::Pos2> = geo.points.iter().map(|p| viewport.to_screen(rect, p.latitude, p.longitude)).collect();
+        let outline = egui::Stroke::new(1.0, visuals.text_color().gamma_multiply(0.6));
+        for (point, pos) in geo.points.iter().zip(&positions) {
+            clipped.circle(*pos, MARKER_RADIUS, color_of(point.value), outline);
+        }
+        // Selected rows on top, ringed
+        let highlight = egui::Stroke::new(2.5, egui::Color32::from_rgb(255, 140, 0));
+        for (point, pos) in geo.points.iter().zip(&positions) {
+            if self.selected_rows.contains(&point.row) {
+                clipped.circle(*pos, MARKER_RADIUS + 2.0, color_of(point.value), highlight);
+            }
+        }
+
+        if let Some((min, max)) = value_range.filter(|_| self.map_color_column.is_some() && !by_cluster) {
+            draw_legend(&painter, rect, min, max);
+        }
+
+        // The applied box, and the one being drawn
+        let box_being_drawn = viewport
+            .box_start
+            .zip(response.interact_pointer_pos().filter(|_| response.dragged()))
+            .map(|(start, pos)| bounding_box(start, viewport.geo_at(rect, pos)));
+        for (bbox, width) in [(self.map_bbox, 2.0), (box_being_drawn, 1.0)] {
+            if let Some(GeoFilter::BoundingBox { lat_min, lon_min, lat_max, lon_max }) = bbox {
+                let corners = egui::Rect::from_two_pos(
+                    viewport.to_screen(rect, lat_min, lon_min),
+                    viewport.to_screen(rect, lat_max, lon_max),
+                );
+                clipped.rect_stroke(corners, 0.0, egui::Stroke::new(width, BOX_COLOR), egui::StrokeKind::Middle);
+            }
+        }
+        if let Some(bbox) = drawn_box {
+            self.set_map_bbox(Some(bbox));
+            return None;
+        }
+
+        // Nearest marker under the pointer; markers often overlap, as every
+        // annotation row of a recording shares its location
+        let pointer = response.hover_pos().or(response.interact_pointer_pos())?;
+        let nearest = positions
+            .iter()
+            .enumerate()
+            .filter(|(_, pos)| pos.distance(pointer) <= PICK_DISTANCE)
+            .min_by(|(_, a), (_, b)| a.distance_sq(pointer).total_cmp(&b.distance_sq(pointer)))
+            .map(|(idx, _)| idx);
+        let Some(idx) = nearest else {
+            if let Some(pos) = response.hover_pos().filter(|pos| rect.contains(*pos)) {
+                let (lat, lon) = viewport.geo_at(rect, pos);
+                response.on_hover_text_at_pointer(format!("{:.5}, {:.5}", lat, lon));
+            }
+            return None;
+        };
+        let point = &geo.points[idx];
+        let clicked = response.clicked();
+        let stacked = positions.iter().filter(|pos| pos.distance(positions[idx]) < 1.0).count();
+        let name = dataset
+            .column("meta_filename")
+            .ok()
+            .and_then(|c| c.str().ok().and_then(|s| s.get(point.row).map(str::to_string)))
+            .unwrap_or_default();
+        let mut text = format!("{}\nrow {}\n{:.5}, {:.5}", name, point.row + 1, point.latitude, point.longitude);
+        if let Some(ref column) = self.map_color_column {
+            let value = point.value.map(|v| format!("{}", v)).unwrap_or_else(|| "null".to_string());
+            text.push_str(&format!("\n{}: {}", column, value));
+        }
+        if stacked > 1 {
+            text.push_str(&format!("\n({} rows at this spot)", stacked));
+        }
+        response.on_hover_text_at_pointer(text);
+        clicked.then_some(point.row)
+    }
+}
+
+/// Color bar in the top right corner of the plot
+pub(crate) fn draw_legend(painter: &egui::Painter, rect: egui::Rect, min: f64, max: f64) {
+    let bar = egui::Rect::from_min_size(rect.right_top() + egui::vec2(-130.0, 8.0), egui::vec2(120.0, 10.0));
+    let steps = 32;
+    for i in 0..steps {
+        let [r, g, b] = Colormap::Viridis.map(i as f32 / (steps - 1) as f32);
+        let x0 = bar.left() + bar.width() * i as f32 / steps as f32;
+        let x1 = bar.left() + bar.width() * (i + 1) as f32 / steps as f32;
+        painter.rect_filled(
+            egui::Rect::from_min_max(egui::pos2(x0, bar.top()), egui::pos2(x1, bar.bottom())),
+            0.0,
+            egui::Color32::from_rgb(r, g, b),
+        );
+    }
+    let color = painter.ctx().style().visuals.text_color();
+    let font = egui::FontId::monospace(10.0);
+    painter.text(bar.left_bottom() + egui::vec2(0.0, 2.0), egui::Align2::LEFT_TOP, format!("{:.3}", min), font.clone(), color);
+    painter.text(bar.right_bottom() + egui::vec2(0.0, 2.0), egui::Align2::RIGHT_TOP, format!("{:.3}", max), font, color);
+}
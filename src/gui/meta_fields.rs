@@ -0,0 +1,150 @@
+use eframe::egui;
+use serde_json::{Map, Value};
+use sig_viewer::parser::sigmf::{field_text, parse_field_value, MetaSnapshot};
+use std::path::Path;
+
+/// One field of a metadata object being edited, as typed
+pub struct FieldDraft {
+    pub key: String,
+    pub text: String,
+    /// Value before editing, so the typed text keeps its type
+    original: Option<Value>,
+}
+
+impl FieldDraft {
+    pub fn new(key: &str, text: &str) -> Self {
+        FieldDraft {
+            key: key.to_string(),
+            text: text.to_string(),
+            original: None,
+        }
+    }
+
+    fn parse(&self) -> anyhow::Result<Value> {
+        parse_field_value(&self.key, &self.text, self.original.as_ref())
+    }
+}
+
+/// Read a meta file for editing. Recordings inside an archive are refused
+/// since they can't be rewritten in place.
+pub fn read_editable(meta_path: &Path) -> Result<MetaSnapshot, String> {
+    if !meta_path.exists() {
+        return Err(format!("Recordings inside a .sigmf archive can't be edited: {}", meta_path.display()));
+    }
+    MetaSnapshot::read(meta_path).map_err(|e| format!("Failed to read {}: {}", meta_path.display(), e))
+}
+
+/// Every field of `object`, in file order
+pub fn drafts_of(object: &Map<String, Value>) -> Vec<FieldDraft> {
+    object
+        .iter()
+        .map(|(key, value)| FieldDraft {
+            key: key.clone(),
+            text: field_text(value),
+            original: Some(value.clone()),
+        })
+        .collect()
+}
+
+/// The edited object, or the first field that doesn't parse
+pub fn build_object(fields: &[FieldDraft]) -> anyhow::Result<Map<String, Value>> {
+    let mut object = Map::new();
+    for field in fields {
+        object.insert(field.key.clone(), field.parse()?);
+    }
+    Ok(object)
+}
+
+/// Text of the "add field" row under the grid
+#[derive(Default)]
+pub struct NewField {
+    key: String,
+    value: String,
+}
+
+/// Grid of editable fields with a remove button each, and a row to add one.
+/// `suggestions` are offered in a menu when adding; `locked` fields are shown
+/// but can't be changed or removed. Returns whether anything changed.
+pub fn edit_fields(
+    ui: &mut egui::Ui,
+    id_salt: impl std::hash::Hash,
+    fields: &mut Vec<FieldDraft>,
+    new_field: &mut NewField,
+    suggestions: &[&str],
+    locked: &[&str],
+) -> bool {
+    let mut changed = false;
+    let mut remove = None;
+    egui::ScrollArea::vertical().id_salt(("fields_scroll", &id_salt)).max_height(300.0).show(ui, |ui| {
+        egui::Grid::new(("fields", &id_salt)).num_columns(3).striped(true).show(ui, |ui| {
+            for (idx, field) in fields.iter_mut().enumerate() {
+                // Invalid values are flagged on the key, with the reason on hover
+                match field.parse() {
+                    Ok(_) => ui.monospace(&field.key),
+                    Err(e) => ui
+                        .colored_label(egui::Color32::RED, egui::RichText::new(&field.key).monospace())
+                        .on_hover_text(e.to_string()),
+                };
+                let is_locked = locked.contains(&field.key.as_str());
+                let edit = egui::TextEdit::singleline(&mut field.text).desired_width(280.0);
+                changed |= ui.add_enabled(!is_locked, edit).changed();
+                if is_locked {
+                    ui.weak("locked");
+                } else if ui.small_button("Remove").clicked() {
+                    remove = Some(idx);
+                }
+                ui.end_row();
+            }
+        });
+    });
+    if let Some(idx) = remove {
+        fields.remove(idx);
+        changed = true;
+    }
+
+    ui.separator();
+    ui.horizontal(|ui| {
+        ui.add(egui::TextEdit::singleline(&mut new_field.key).hint_text("ds:my_field").desired_width(150.0));
+        if !suggestions.is_empty() {
+            ui.menu_button("Core", |ui| {
+                for key in suggestions {
+                    if ui.add_enabled(!fields.iter().any(|f| f.key == *key), egui::Button::new(*key)).clicked() {
+                        new_field.key = key.to_string();
+                        ui.close();
+                    }
+                }
+            });
+        }
+        ui.add(egui::TextEdit::singleline(&mut new_field.value).hint_text("value").desired_width(150.0));
+        let key = new_field.key.trim();
+        let exists = fields.iter().any(|f| f.key == key);
+        let response = ui.add_enabled(!key.is_empty() && !exists, egui::Button::new("Add Field"));
+        let response = if exists { response.on_disabled_hover_text("Already present; edit it above") } else { response };
+        if response.clicked() {
+            fields.push(FieldDraft::new(key, &std::mem::take(&mut new_field.value)));
+            new_field.key.clear();
+            changed = true;
+        }
+    });
+    changed
+}
+
+/// Error line and Save/Revert buttons at the bottom of an editor window.
+/// Returns whether (save, revert) were clicked.
+pub fn save_bar(ui: &mut egui::Ui, dirty: bool, read_only: bool, error: Option<&str>) -> (bool, bool) {
+    if let Some(error) = error {
+        ui.colored_label(egui::Color32::RED, error);
+    }
+    ui.separator();
+    let mut clicked = (false, false);
+    ui.horizontal(|ui| {
+        let response = ui.add_enabled(dirty && !read_only, egui::Button::new("Save"));
+        let response = if read_only { response.on_disabled_hover_text("Read-only mode") } else { response };
+        clicked.0 = response.clicked();
+        clicked.1 = ui.add_enabled(dirty, egui::Button::new("Revert")).clicked();
+        if dirty {
+            ui.colored_label(egui::Color32::from_rgb(255, 140, 0), "unsaved changes");
+        }
+    });
+    clicked
+}
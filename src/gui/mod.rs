@@ -0,0 +1,62 @@
+// GUI-only modules for the sig_viewer_gui binary. Each module adds its own
+// `impl SigViewerApp` block or self-contained widget state.
+pub mod activity_view;
+pub mod annotation_editor;
+pub mod band_filter;
+pub mod burst_detector;
+pub mod cluster_view;
+pub mod column_stats;
+pub mod compare;
+pub mod constellation_view;
+pub mod continuity_view;
+pub mod cursors;
+pub mod dashboard;
+pub mod dataset_loader;
+pub mod deep_link;
+pub mod derived_columns;
+pub mod demodulator;
+pub mod detail_panel;
+pub mod export;
+pub mod filter_presets;
+pub mod global_editor;
+pub mod group_by_view;
+pub mod histogram_view;
+pub mod jobs_panel;
+pub mod labeling;
+pub mod links;
+pub mod log_console;
+pub mod map_view;
+pub mod meta_fields;
+pub mod multi_sensor_view;
+pub mod noise_trend_view;
+pub mod plot;
+pub mod psd_compare;
+pub mod recent_dirs;
+pub mod sample_source;
+pub mod scan_failures;
+pub mod scatter_view;
+pub mod selection;
+pub mod sensor_profiles;
+pub mod session;
+pub mod settings_bundle;
+pub mod similar_search;
+pub mod sorting;
+pub mod spectrogram_view;
+pub mod thumbnails;
+pub mod time_domain_view;
+pub mod watch_mode;
+pub mod watchlist_panel;
+pub mod write_back;
+
+use eframe::egui;
+use sig_viewer::parser::sigmf::SegmentedRecording;
+use std::path::Path;
+
+/// A visualization tied to a single recording, opened from its meta file.
+/// `stitched` is the segmented capture the recording is part of, if any,
+/// for views that scroll across the whole capture.
+pub trait RecordingView: Sized {
+    fn open(meta_path: &Path, stitched: Option<SegmentedRecording>) -> anyhow::Result<Self>;
+    fn meta_path(&self) -> &Path;
+    fn show(&mut self, ui: &mut egui::Ui);
+}
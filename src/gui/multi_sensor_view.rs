@@ -0,0 +1,418 @@
+use super::plot::{draw_x_axis, draw_y_axis, format_frequency, format_seconds};
+use crate::SigViewerApp;
+use chrono::NaiveDateTime;
+use eframe::egui;
+use sig_viewer::data_ops::{find_simultaneous, SyncedRecording};
+use sig_viewer::parser::SigMFParser;
+use sig_viewer::viz::{Colormap, Spectrogram, SpectrogramParams};
+use sig_viewer::workers::{Priority, TaskHandle, WorkerPool};
+use std::path::PathBuf;
+
+const FFT_SIZES: [usize; 5] = [256, 512, 1024, 2048, 4096];
+const AXIS_MARGIN_LEFT: f32 = 80.0;
+const AXIS_MARGIN_BOTTOM: f32 = 20.0;
+const LANE_HEADER_HEIGHT: f32 = 18.0;
+const MIN_LANE_HEIGHT: f32 = 90.0;
+
+/// One sensor's recording, as a horizontal strip of the stacked view
+struct SensorLane {
+    recording: SyncedRecording,
+    meta_path: PathBuf,
+    /// Start relative to the reference recording, in seconds
+    offset_s: f64,
+    total_samples: u64,
+    /// `None` while `task` is computing it, or when nothing of the recording is in view
+    spectrogram: Option<Result<Spectrogram, String>>,
+    task: Option<TaskHandle<Spectrogram>>,
+    texture: Option<egui::TextureHandle>,
+    texture_dirty: bool,
+}
+
+impl SensorLane {
+    fn time_of(&self, sample: f64) -> f64 {
+        self.offset_s + sample / self.recording.sample_rate_hz
+    }
+
+    /// Compute the part of the recording inside [t0, t1] on the worker pool,
+    /// replacing any computation still running
+    fn load(&mut self, workers: &WorkerPool, t0: f64, t1: f64, params: &SpectrogramParams) {
+        if let Some(old) = self.task.take() {
+            workers.cancel(old.id());
+        }
+        let rate = self.recording.sample_rate_hz;
+        let start = ((t0 - self.offset_s) * rate).clamp(0.0, self.total_samples as f64) as u64;
+        let end = ((t1 - self.offset_s) * rate).clamp(0.0, self.total_samples as f64) as u64;
+        self.spectrogram = None;
+        if end.saturating_sub(start) < params.fft_size as u64 {
+            self.texture = None;
+            return;
+        }
+        let (meta_path, params) = (self.meta_path.clone(), params.clone());
+        let name = format!("Spectrogram: {}", self.recording.meta_filename);
+        self.task = Some(workers.submit(&name, Priority::High, move |ctx| {
+            let parser = SigMFParser::from_meta_file(&meta_path)?;
+            let mut reader = parser.open_reader()?;
+            ctx.check_cancelled()?;
+            Spectrogram::compute(&mut reader, start, end - start, &params)
+        }));
+    }
+
+    fn poll(&mut self) {
+        if let Some(result) = self.task.as_ref().and_then(|task| task.try_take()) {
+            self.spectrogram = Some(result.map_err(|e| e.to_string()));
+            self.texture_dirty = true;
+            self.task = None;
+        }
+    }
+
+    fn spectrogram(&self) -> Option<&Spectrogram> {
+        self.spectrogram.as_ref()?.as_ref().ok()
+    }
+
+    /// Texture with time left to right and frequency bottom to top
+    fn update_texture(&mut self, ctx: &egui::Context, colormap: Colormap, min_db: f32, max_db: f32) {
+        self.texture_dirty = false;
+        let Some(spectrogram) = self.spectrogram() else {
+            self.texture = None;
+            return;
+        };
+        let rgb = spectrogram.to_rgb(colormap, min_db, max_db);
+        let (width, height) = (spectrogram.num_rows, spectrogram.fft_size);
+        let mut pixels = Vec::with_capacity(width * height * 3);
+        for bin in (0..height).rev() {
+            for row in 0..width {
+                pixels.extend_from_slice(&rgb[row * height + bin]);
+            }
+        }
+        let image = egui::ColorImage::from_rgb([width, height], &pixels);
+        match self.texture {
+            Some(ref mut texture) => texture.set(image, egui::TextureOptions::LINEAR),
+            None => {
+                let name = format!("multi_sensor_{}", self.recording.meta_filename);
+                self.texture = Some(ctx.load_texture(name, image, egui::TextureOptions::LINEAR));
+            }
+        }
+    }
+
+    /// Level readout for the lane header: peak, median (as the floor) and their difference
+    fn levels(&self) -> Option<String> {
+        let spectrogram = self.spectrogram()?;
+        let (peak, floor) = (spectrogram.percentile_db(99.9), spectrogram.percentile_db(50.0));
+        Some(format!("peak {:.1} dB, floor {:.1} dB, SNR {:.1} dB", peak, floor, peak - floor))
+    }
+}
+
+/// Spectrograms of the same event from several sensors, stacked on one
+/// absolute time axis. Time runs left to right so the lanes line up; panning
+/// and zooming move every lane together.
+pub struct MultiSensorView {
+    lanes: Vec<SensorLane>,
+    /// Start of the first (reference) recording; times are seconds from it
+    reference: NaiveDateTime,
+    /// Extent of all recordings
+    t_min: f64,
+    t_max: f64,
+    /// Visible time range
+    t0: f64,
+    t1: f64,
+    fft_size: usize,
+    colormap: Colormap,
+    min_db: f32,
+    max_db: f32,
+    range_initialized: bool,
+    /// Show only the band every sensor covers, so the lanes line up in frequency too
+    common_band: bool,
+    needs_compute: bool,
+    /// Matched recordings whose meta file couldn't be found
+    missing: Vec<String>,
+}
+
+impl MultiSensorView {
+    fn new(recordings: Vec<(SyncedRecording, PathBuf)>, missing: Vec<String>) -> Option<Self> {
+        let reference = recordings.first()?.0.start;
+        let lanes: Vec<SensorLane> = recordings
+            .into_iter()
+            .map(|(recording, meta_path)| SensorLane {
+                offset_s: recording.offset_s(reference),
+                total_samples: (recording.duration_s * recording.sample_rate_hz).round() as u64,
+                recording,
+                meta_path,
+                spectrogram: None,
+                task: None,
+                texture: None,
+                texture_dirty: false,
+            })
+            .collect();
+        let t_min = lanes.iter().map(|lane| lane.offset_s).fold(f64::INFINITY, f64::min);
+        let t_max = lanes.iter().map(|lane| lane.offset_s + lane.recording.duration_s).fold(f64::NEG_INFINITY, f64::max);
+        Some(MultiSensorView {
+            lanes,
+            reference,
+            t_min,
+            t_max,
+            t0: t_min,
+            t1: t_max,
+            fft_size: 1024,
+            colormap: Colormap::Viridis,
+            min_db: -100.0,
+            max_db: 0.0,
+            range_initialized: false,
+            common_band: true,
+            needs_compute: true,
+            missing,
+        })
+    }
+
+    fn cancel(&mut self, workers: &WorkerPool) {
+        for task in self.lanes.iter_mut().filter_map(|lane| lane.task.take()) {
+            workers.cancel(task.id());
+        }
+    }
+
+    fn recompute(&mut self, workers: &WorkerPool, columns: usize) {
+        self.needs_compute = false;
+        let params = SpectrogramParams { fft_size: self.fft_size, max_rows: columns.max(64) };
+        for lane in &mut self.lanes {
+            lane.load(workers, self.t0, self.t1, &params);
+        }
+    }
+
+    /// Band shared by every lane, if they overlap at all
+    fn shared_band(&self) -> Option<(f64, f64)> {
+        let (lo, hi) = self.lanes.iter().map(|lane| lane.recording.band()).fold(
+            (f64::NEG_INFINITY, f64::INFINITY),
+            |(lo, hi), (lane_lo, lane_hi)| (lo.max(lane_lo), hi.min(lane_hi)),
+        );
+        (hi > lo).then_some((lo, hi))
+    }
+
+    /// One color scale for every lane, so levels compare across sensors
+    fn auto_range(&mut self) {
+        let spectrograms: Vec<&Spectrogram> = self.lanes.iter().filter_map(SensorLane::spectrogram).collect();
+        if spectrograms.is_empty() {
+            return;
+        }
+        self.min_db = spectrograms.iter().map(|s| s.percentile_db(5.0)).fold(f32::INFINITY, f32::min);
+        self.max_db = spectrograms.iter().map(|s| s.percentile_db(99.9)).fold(f32::NEG_INFINITY, f32::max);
+        self.range_initialized = true;
+        for lane in &mut self.lanes {
+            lane.texture_dirty = true;
+        }
+    }
+
+    fn zoom(&mut self, factor: f64, anchor_frac: f64) {
+        let span = self.t1 - self.t0;
+        let min_span = self.fft_size as f64 * 8.0
+            / self.lanes.iter().map(|lane| lane.recording.sample_rate_hz).fold(f64::INFINITY, f64::min);
+        let new_span = (span * factor).clamp(min_span.min(self.t_max - self.t_min), self.t_max - self.t_min);
+        let anchor = self.t0 + anchor_frac * span;
+        self.t0 = (anchor - anchor_frac * new_span).clamp(self.t_min, self.t_max - new_span);
+        self.t1 = self.t0 + new_span;
+        self.needs_compute = true;
+    }
+
+    fn pan(&mut self, delta_s: f64) {
+        let span = self.t1 - self.t0;
+        self.t0 = (self.t0 + delta_s).clamp(self.t_min, self.t_max - span);
+        self.t1 = self.t0 + span;
+        self.needs_compute = true;
+    }
+
+    fn reset_view(&mut self) {
+        self.t0 = self.t_min;
+        self.t1 = self.t_max;
+        self.needs_compute = true;
+    }
+
+    fn show(&mut self, ui: &mut egui::Ui, workers: &WorkerPool) {
+        for lane in &mut self.lanes {
+            lane.poll();
+        }
+        if !self.range_initialized && self.lanes.iter().all(|lane| lane.task.is_none()) {
+            self.auto_range();
+        }
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("FFT")
+                .selected_text(self.fft_size.to_string())
+                .show_ui(ui, |ui| {
+                    for size in FFT_SIZES {
+                        self.needs_compute |= ui.selectable_value(&mut self.fft_size, size, size.to_string()).changed();
+                    }
+                });
+            egui::ComboBox::from_label("Colormap")
+                .selected_text(self.colormap.name())
+                .show_ui(ui, |ui| {
+                    for colormap in Colormap::ALL {
+                        if ui.selectable_value(&mut self.colormap, colormap, colormap.name()).changed() {
+                            self.lanes.iter_mut().for_each(|lane| lane.texture_dirty = true);
+                        }
+                    }
+                });
+            ui.add_enabled(self.shared_band().is_some(), egui::Checkbox::new(&mut self.common_band, "Common band only"))
+                .on_disabled_hover_text("The sensors' bands don't all overlap");
+        });
+        ui.horizontal(|ui| {
+            ui.label("Range (dB):");
+            let min_changed = ui.add(egui::DragValue::new(&mut self.min_db).speed(0.5).prefix("min ")).changed();
+            let max_changed = ui.add(egui::DragValue::new(&mut self.max_db).speed(0.5).prefix("max ")).changed();
+            if min_changed || max_changed {
+                self.lanes.iter_mut().for_each(|lane| lane.texture_dirty = true);
+            }
+            if ui.button("Auto Range").on_hover_text("One scale for all sensors").clicked() {
+                self.auto_range();
+            }
+            if ui.button("Reset View").clicked() {
+                self.reset_view();
+            }
+        });
+        ui.label(format!(
+            "{} recordings, times relative to {} UTC. Drag to pan, scroll to zoom time, double-click to reset",
+            self.lanes.len(),
+            self.reference.format("%Y-%m-%d %H:%M:%S%.3f")
+        ));
+        if !self.missing.is_empty() {
+            ui.colored_label(egui::Color32::RED, format!("Could not locate {}", self.missing.join(", ")));
+        }
+
+        let lane_height = ((ui.available_height() - AXIS_MARGIN_BOTTOM) / self.lanes.len() as f32).max(MIN_LANE_HEIGHT);
+        let size = egui::vec2(ui.available_width(), lane_height * self.lanes.len() as f32 + AXIS_MARGIN_BOTTOM);
+        let (response, painter) = ui.allocate_painter(size, egui::Sense::click_and_drag());
+        let plot_left = response.rect.left() + AXIS_MARGIN_LEFT;
+        let plot_width = (response.rect.right() - plot_left).max(1.0);
+
+        // Interaction moves every lane at once
+        if response.double_clicked() {
+            self.reset_view();
+        }
+        if response.dragged() {
+            self.pan(-(response.drag_delta().x / plot_width) as f64 * (self.t1 - self.t0));
+        }
+        if let Some(hover) = response.hover_pos() {
+            let scroll = ui.input(|i| i.raw_scroll_delta);
+            let scroll = scroll.x + scroll.y;
+            if scroll != 0.0 && hover.x > plot_left {
+                self.zoom((-scroll * 0.002).exp() as f64, ((hover.x - plot_left) / plot_width) as f64);
+            }
+        }
+        if self.needs_compute && !response.dragged() {
+            self.recompute(workers, plot_width as usize);
+        }
+
+        let shared_band = self.shared_band().filter(|_| self.common_band);
+        let (t0, t1) = (self.t0, self.t1);
+        let x_of = |t: f64| plot_left + ((t - t0) / (t1 - t0)) as f32 * plot_width;
+        let (colormap, min_db, max_db) = (self.colormap, self.min_db, self.max_db);
+        let text_color = ui.visuals().text_color();
+        let mut plot_bottom = response.rect.top();
+        for (idx, lane) in self.lanes.iter_mut().enumerate() {
+            if lane.texture_dirty {
+                lane.update_texture(ui.ctx(), colormap, min_db, max_db);
+            }
+            let top = response.rect.top() + idx as f32 * lane_height;
+            let rect = egui::Rect::from_min_max(
+                egui::pos2(plot_left, top + LANE_HEADER_HEIGHT),
+                egui::pos2(response.rect.right(), top + lane_height - 4.0),
+            );
+            plot_bottom = rect.bottom();
+            let (lane_lo, lane_hi) = lane.recording.band();
+            let (freq_lo, freq_hi) = shared_band.unwrap_or((lane_lo, lane_hi));
+
+            let mut header = format!(
+                "{} · {} · {:+.3} s",
+                if lane.recording.sdr_handle.is_empty() { "(unknown sensor)" } else { &lane.recording.sdr_handle },
+                lane.recording.meta_filename,
+                lane.offset_s
+            );
+            match (&lane.spectrogram, &lane.task) {
+                (_, Some(_)) => header.push_str(" · computing…"),
+                (Some(Err(e)), _) => header.push_str(&format!(" · {}", e)),
+                _ => {}
+            }
+            if let Some(levels) = lane.levels() {
+                header.push_str(&format!(" · {}", levels));
+            }
+            painter.text(egui::pos2(plot_left, top + 2.0), egui::Align2::LEFT_TOP, header, egui::FontId::proportional(12.0), text_color);
+
+            painter.rect_filled(rect, 0.0, egui::Color32::BLACK);
+            if let (Some(texture), Some(spectrogram)) = (&lane.texture, lane.spectrogram()) {
+                // Placed at the times it was computed for, which may lag the view mid-drag
+                let covered_t0 = lane.time_of(spectrogram.start_sample as f64);
+                let covered_t1 = lane.time_of((spectrogram.start_sample + spectrogram.num_rows as u64 * spectrogram.row_step) as f64);
+                let image_rect = egui::Rect::from_x_y_ranges(x_of(covered_t0)..=x_of(covered_t1), rect.y_range());
+                let v_of = |freq: f64| (1.0 - (freq - lane_lo) / (lane_hi - lane_lo)) as f32;
+                let uv = egui::Rect::from_min_max(egui::pos2(0.0, v_of(freq_hi)), egui::pos2(1.0, v_of(freq_lo)));
+                painter.with_clip_rect(rect).image(texture.id(), image_rect, uv, egui::Color32::WHITE);
+            }
+            draw_y_axis(&painter, rect, freq_lo, freq_hi, false, format_frequency);
+        }
+        let axis_rect = egui::Rect::from_x_y_ranges(plot_left..=response.rect.right(), response.rect.top()..=plot_bottom);
+        draw_x_axis(&painter, axis_rect, self.t0, self.t1, format_seconds);
+
+        // Time cursor across every lane
+        if let Some(hover) = response.hover_pos().filter(|p| p.x > plot_left) {
+            let t = self.t0 + ((hover.x - plot_left) / plot_width) as f64 * (self.t1 - self.t0);
+            painter.line_segment(
+                [egui::pos2(hover.x, response.rect.top()), egui::pos2(hover.x, plot_bottom)],
+                egui::Stroke::new(1.0, egui::Color32::WHITE.gamma_multiply(0.6)),
+            );
+            let at = self.reference + chrono::Duration::nanoseconds((t * 1e9) as i64);
+            response.on_hover_text_at_pointer(format!("{}\n{} UTC", format_seconds(t), at.format("%H:%M:%S%.6f")));
+        }
+    }
+}
+
+// handle the time-synchronized multi-sensor view of the selected recording
+impl SigViewerApp {
+    /// Stack the selected recording with every other sensor's recording of
+    /// the same time and band
+    pub(crate) fn open_multi_sensor_view(&mut self) {
+        let Some(meta_filename) = self.selected_row_data.as_ref().and_then(|row| row.get("meta_filename")).cloned() else {
+            return;
+        };
+        // Match against everything loaded, so filters don't hide other sensors
+        let Some(dataset) = self.dataset.as_ref() else {
+            return;
+        };
+        let recordings = match find_simultaneous(dataset, &meta_filename) {
+            Ok(recordings) => recordings,
+            Err(e) => {
+                self.error_message = Some(format!("Multi-sensor view: {}", e));
+                return;
+            }
+        };
+        if recordings.len() < 2 {
+            self.error_message = Some(format!("No other sensor has a recording overlapping {} in time and frequency", meta_filename));
+            return;
+        }
+        let mut located = Vec::new();
+        let mut missing = Vec::new();
+        for recording in recordings {
+            match self.locate_meta_file(&recording.meta_filename) {
+                Some(path) => located.push((recording, path)),
+                None => missing.push(recording.meta_filename),
+            }
+        }
+        if let Some(mut old) = self.multi_sensor_view.take() {
+            old.cancel(&self.workers);
+        }
+        self.multi_sensor_view = MultiSensorView::new(located, missing);
+    }
+
+    pub(crate) fn render_multi_sensor_view(&mut self, ctx: &egui::Context) {
+        let Some(ref mut view) = self.multi_sensor_view else {
+            return;
+        };
+        let workers = &self.workers;
+        let mut open = true;
+        egui::Window::new("Multi-Sensor View")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([1000.0, 600.0])
+            .show(ctx, |ui| view.show(ui, workers));
+        if !open {
+            view.cancel(workers);
+            self.multi_sensor_view = None;
+        }
+    }
+}
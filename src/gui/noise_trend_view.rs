@@ -0,0 +1,173 @@
+use crate::gui::plot;
+use crate::SigViewerApp;
+use chrono::NaiveDateTime;
+use eframe::egui;
+use sig_viewer::data_ops::{noise_floor_trend, SensorNoiseTrend};
+
+const LABEL_WIDTH: f32 = 160.0;
+const PANEL_HEIGHT: f32 = 110.0;
+const AXIS_HEIGHT: f32 = 20.0;
+
+// handle noise floor trend per sensor
+impl SigViewerApp {
+    pub(crate) fn render_noise_trend_window(&mut self, ctx: &egui::Context) {
+        if !self.show_noise_trend {
+            return;
+        }
+        let mut open = true;
+        egui::Window::new("Noise Floor Trend")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([900.0, 500.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Flag steps and drifts of at least");
+                    let response = ui.add(egui::DragValue::new(&mut self.noise_threshold_db).range(0.5..=30.0).speed(0.1).suffix(" dB"));
+                    if response.changed() {
+                        self.noise_trend = None;
+                    }
+                });
+
+                if self.noise_trend.is_none() {
+                    if let Some(ref dataset) = self.filtered_dataset {
+                        self.noise_trend = Some(noise_floor_trend(dataset, self.noise_threshold_db).map_err(|e| e.to_string()));
+                    }
+                }
+                let trend = match self.noise_trend {
+                    Some(Ok(ref trend)) => trend,
+                    Some(Err(ref e)) => {
+                        ui.colored_label(egui::Color32::RED, format!("Cannot build trend: {}", e));
+                        ui.label("Reload with \"Estimate noise floor from samples\" checked, or open a dataset built with --noise-floor.");
+                        return;
+                    }
+                    None => {
+                        ui.label("Load a dataset first");
+                        return;
+                    }
+                };
+                if trend.skipped_rows > 0 {
+                    ui.label(format!("{} rows without a noise floor or parseable capture_datetime were skipped", trend.skipped_rows));
+                }
+                let Some((start, end)) = trend.time_range() else {
+                    ui.label("No recordings with a noise floor in the current view");
+                    return;
+                };
+
+                let threshold = self.noise_threshold_db;
+                let flagged = trend
+                    .sensors
+                    .iter()
+                    .filter(|s| !s.steps.is_empty() || s.is_drifting(threshold))
+                    .count();
+                ui.label(format!("{} of {} sensors with steps or drift", flagged, trend.sensors.len()));
+
+                egui::ScrollArea::vertical().show(ui, |ui| {
+                    for sensor in &trend.sensors {
+                        draw_sensor_trend(ui, sensor, start, end, threshold, self.sensor_color(&sensor.sensor));
+                    }
+                });
+            });
+        if !open {
+            self.show_noise_trend = false;
+        }
+    }
+}
+
+fn seconds_since(start: NaiveDateTime, time: NaiveDateTime) -> f64 {
+    (time - start).num_milliseconds() as f64 / 1000.0
+}
+
+/// One panel: noise floor over the shared time axis, steps as red lines and
+/// the fitted drift as a dashed line when it exceeds the threshold. The line
+/// takes the sensor profile's color if it has one.
+fn draw_sensor_trend(
+    ui: &mut egui::Ui,
+    sensor: &SensorNoiseTrend,
+    start: NaiveDateTime,
+    end: NaiveDateTime,
+    threshold_db: f64,
+    color: Option<egui::Color32>,
+) {
+    let width = ui.available_width().max(LABEL_WIDTH + 200.0);
+    let (response, painter) = ui.allocate_painter(egui::vec2(width, PANEL_HEIGHT + AXIS_HEIGHT), egui::Sense::hover());
+    let plot_rect = egui::Rect::from_min_max(
+        response.rect.min + egui::vec2(LABEL_WIDTH, 4.0),
+        egui::pos2(response.rect.right() - 10.0, response.rect.top() + PANEL_HEIGHT),
+    );
+    let visuals = ui.visuals();
+    let text_color = visuals.text_color();
+    let drifting = sensor.is_drifting(threshold_db);
+
+    // Label with the drift summary
+    let mut label = sensor.sensor.clone();
+    if let Some(drift) = sensor.drift_db() {
+        label.push_str(&format!("\ndrift {:+.1} dB", drift));
+    }
+    if !sensor.steps.is_empty() {
+        label.push_str(&format!("\n{} step{}", sensor.steps.len(), if sensor.steps.len() == 1 { "" } else { "s" }));
+    }
+    let label_color = if drifting || !sensor.steps.is_empty() { egui::Color32::from_rgb(255, 140, 0) } else { text_color };
+    painter.text(
+        egui::pos2(response.rect.left(), plot_rect.top()),
+        egui::Align2::LEFT_TOP,
+        label,
+        egui::FontId::proportional(12.0),
+        label_color,
+    );
+    painter.rect_stroke(plot_rect, 0.0, egui::Stroke::new(1.0, visuals.weak_text_color()), egui::StrokeKind::Inside);
+
+    // Per-sensor dB range so small steps stay visible, padded a little
+    let (min_db, max_db) = sensor.points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+        (min.min(p.noise_floor_db), max.max(p.noise_floor_db))
+    });
+    let pad = ((max_db - min_db) * 0.1).max(0.5);
+    let (min_db, max_db) = (min_db - pad, max_db + pad);
+    let span_s = seconds_since(start, end).max(1.0);
+    let to_screen = |time: NaiveDateTime, db: f64| {
+        egui::pos2(
+            plot_rect.left() + (seconds_since(start, time) / span_s) as f32 * plot_rect.width(),
+            plot_rect.bottom() - ((db - min_db) / (max_db - min_db)) as f32 * plot_rect.height(),
+        )
+    };
+
+    plot::draw_y_axis(&painter, plot_rect, min_db, max_db, false, |db| format!("{:.0}", db));
+    plot::draw_x_axis(&painter, plot_rect, 0.0, span_s, |s| {
+        (start + chrono::Duration::milliseconds((s * 1000.0) as i64)).format("%Y-%m-%d %H:%M").to_string()
+    });
+
+    for step in &sensor.steps {
+        let x = to_screen(step.datetime, min_db).x;
+        painter.line_segment(
+            [egui::pos2(x, plot_rect.top()), egui::pos2(x, plot_rect.bottom())],
+            egui::Stroke::new(1.5, egui::Color32::RED),
+        );
+    }
+
+    let line_color = color.unwrap_or(visuals.selection.bg_fill);
+    let points: Vec<egui::Pos2> = sensor.points.iter().map(|p| to_screen(p.datetime, p.noise_floor_db)).collect();
+    painter.add(egui::Shape::line(points.clone(), egui::Stroke::new(1.0, line_color)));
+    for point in &points {
+        painter.circle_filled(*point, 2.5, line_color);
+    }
+
+    if drifting {
+        let (first, last) = (&sensor.points[0], &sensor.points[sensor.points.len() - 1]);
+        if let (Some(from_db), Some(to_db)) = (sensor.fitted_db(first.datetime), sensor.fitted_db(last.datetime)) {
+            let line = [to_screen(first.datetime, from_db), to_screen(last.datetime, to_db)];
+            painter.extend(egui::Shape::dashed_line(&line, egui::Stroke::new(1.5, egui::Color32::from_rgb(255, 140, 0)), 6.0, 4.0));
+        }
+    }
+
+    // Tooltip for the nearest recording
+    if let Some(pos) = response.hover_pos() {
+        let nearest = points
+            .iter()
+            .enumerate()
+            .min_by(|(_, a), (_, b)| a.distance_sq(pos).total_cmp(&b.distance_sq(pos)))
+            .filter(|(_, p)| p.distance(pos) < 12.0);
+        if let Some((idx, _)) = nearest {
+            let point = &sensor.points[idx];
+            response.on_hover_text(format!("{}\n{}\n{:.1} dB", point.meta_filename, point.datetime, point.noise_floor_db));
+        }
+    }
+}
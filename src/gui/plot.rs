@@ -0,0 +1,188 @@
+use eframe::egui;
+
+/// Pick roughly `target` evenly spaced "nice" tick values covering [min, max]
+pub fn nice_ticks(min: f64, max: f64, target: usize) -> Vec<f64> {
+    if min.partial_cmp(&max) != Some(std::cmp::Ordering::Less) || target == 0 {
+        return Vec::new();
+    }
+    let raw_step = (max - min) / target as f64;
+    let magnitude = 10f64.powf(raw_step.log10().floor());
+    let normalized = raw_step / magnitude;
+    let step = if normalized < 1.5 {
+        1.0
+    } else if normalized < 3.0 {
+        2.0
+    } else if normalized < 7.0 {
+        5.0
+    } else {
+        10.0
+    } * magnitude;
+
+    let mut ticks = Vec::new();
+    let mut value = (min / step).ceil() * step;
+    while value <= max {
+        ticks.push(value);
+        value += step;
+    }
+    ticks
+}
+
+pub fn format_frequency(hz: f64) -> String {
+    let abs = hz.abs();
+    if abs >= 1e9 {
+        format!("{:.4} GHz", hz / 1e9)
+    } else if abs >= 1e6 {
+        format!("{:.3} MHz", hz / 1e6)
+    } else if abs >= 1e3 {
+        format!("{:.2} kHz", hz / 1e3)
+    } else {
+        format!("{:.0} Hz", hz)
+    }
+}
+
+pub fn format_seconds(seconds: f64) -> String {
+    let abs = seconds.abs();
+    if abs >= 1.0 || abs == 0.0 {
+        format!("{:.3} s", seconds)
+    } else if abs >= 1e-3 {
+        format!("{:.3} ms", seconds * 1e3)
+    } else {
+        format!("{:.1} µs", seconds * 1e6)
+    }
+}
+
+/// Draw tick labels along the bottom edge of `rect` for a horizontal axis
+/// spanning [min, max]
+pub fn draw_x_axis(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    min: f64,
+    max: f64,
+    format: impl Fn(f64) -> String,
+) {
+    let color = painter.ctx().style().visuals.text_color();
+    for tick in nice_ticks(min, max, 5) {
+        let x = rect.left() + ((tick - min) / (max - min)) as f32 * rect.width();
+        painter.line_segment(
+            [egui::pos2(x, rect.bottom()), egui::pos2(x, rect.bottom() + 4.0)],
+            egui::Stroke::new(1.0, color),
+        );
+        painter.text(
+            egui::pos2(x, rect.bottom() + 5.0),
+            egui::Align2::CENTER_TOP,
+            format(tick),
+            egui::FontId::monospace(10.0),
+            color,
+        );
+    }
+}
+
+/// Draw tick labels along the left edge of `rect` for a vertical axis.
+/// `min` is at the top when `top_down` is set, otherwise at the bottom.
+pub fn draw_y_axis(
+    painter: &egui::Painter,
+    rect: egui::Rect,
+    min: f64,
+    max: f64,
+    top_down: bool,
+    format: impl Fn(f64) -> String,
+) {
+    let color = painter.ctx().style().visuals.text_color();
+    for tick in nice_ticks(min, max, 5) {
+        let frac = ((tick - min) / (max - min)) as f32;
+        let y = if top_down {
+            rect.top() + frac * rect.height()
+        } else {
+            rect.bottom() - frac * rect.height()
+        };
+        painter.line_segment(
+            [egui::pos2(rect.left() - 4.0, y), egui::pos2(rect.left(), y)],
+            egui::Stroke::new(1.0, color),
+        );
+        painter.text(
+            egui::pos2(rect.left() - 6.0, y),
+            egui::Align2::RIGHT_CENTER,
+            format(tick),
+            egui::FontId::monospace(10.0),
+            color,
+        );
+    }
+}
+
+/// Small inline bar sparkline of `values`, sized to fit a table cell
+pub fn sparkline(ui: &mut egui::Ui, values: &[u64]) -> egui::Response {
+    let size = egui::vec2(values.len().max(1) as f32 * 4.0, ui.available_height().min(16.0));
+    let (response, painter) = ui.allocate_painter(size, egui::Sense::hover());
+    let rect = response.rect;
+    let max = values.iter().copied().max().unwrap_or(0);
+    let color = ui.visuals().selection.bg_fill;
+    painter.line_segment(
+        [rect.left_bottom(), rect.right_bottom()],
+        egui::Stroke::new(1.0, ui.visuals().weak_text_color()),
+    );
+    if max > 0 {
+        for (i, &value) in values.iter().enumerate() {
+            if value == 0 {
+                continue;
+            }
+            let height = (value as f32 / max as f32 * rect.height()).max(1.0);
+            let left = rect.left() + i as f32 * 4.0;
+            let bar = egui::Rect::from_min_max(
+                egui::pos2(left, rect.bottom() - height),
+                egui::pos2(left + 3.0, rect.bottom()),
+            );
+            painter.rect_filled(bar, 0.0, color);
+        }
+    }
+    response
+}
+
+/// Visible window over a recording's samples, shared by the time-based views
+#[derive(Debug, Clone)]
+pub struct SampleWindow {
+    pub total: u64,
+    pub start: u64,
+    pub len: u64,
+    pub min_len: u64,
+}
+
+impl SampleWindow {
+    pub fn new(total: u64, min_len: u64) -> Self {
+        Self {
+            total,
+            start: 0,
+            len: total,
+            min_len: min_len.min(total),
+        }
+    }
+
+    pub fn end(&self) -> u64 {
+        self.start + self.len
+    }
+
+    pub fn reset(&mut self) {
+        self.start = 0;
+        self.len = self.total;
+    }
+
+    /// Zoom by `factor` (< 1 zooms in) keeping the sample at `anchor_frac` of
+    /// the window fixed. Returns whether the window changed.
+    pub fn zoom(&mut self, factor: f64, anchor_frac: f64) -> bool {
+        let anchor = self.start as f64 + anchor_frac * self.len as f64;
+        let new_len = (self.len as f64 * factor).clamp(self.min_len as f64, self.total as f64);
+        let new_start = (anchor - anchor_frac * new_len).clamp(0.0, self.total as f64 - new_len);
+        let (old_start, old_len) = (self.start, self.len);
+        self.start = new_start as u64;
+        self.len = new_len as u64;
+        (self.start, self.len) != (old_start, old_len)
+    }
+
+    /// Shift the window by a (possibly negative) number of samples
+    pub fn pan(&mut self, delta_samples: f64) -> bool {
+        let max_start = self.total.saturating_sub(self.len) as f64;
+        let new_start = (self.start as f64 + delta_samples).clamp(0.0, max_start) as u64;
+        let changed = new_start != self.start;
+        self.start = new_start;
+        changed
+    }
+}
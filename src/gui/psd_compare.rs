@@ -0,0 +1,286 @@
+use super::cursors::MeasurementCursors;
+use super::plot::{draw_x_axis, draw_y_axis, format_frequency};
+use eframe::egui;
+use sig_viewer::parser::SigMFParser;
+use sig_viewer::viz::{Psd, PsdParams};
+use sig_viewer::workers::{Priority, TaskHandle, WorkerPool};
+use std::path::PathBuf;
+
+const AXIS_MARGIN_LEFT: f32 = 50.0;
+const AXIS_MARGIN_BOTTOM: f32 = 20.0;
+const DIFFERENCE_HEIGHT: f32 = 120.0;
+const FFT_SIZES: [usize; 5] = [512, 1024, 2048, 4096, 8192];
+const DIFFERENCE_COLOR: egui::Color32 = egui::Color32::from_rgb(120, 120, 120);
+
+/// One recording's spectrum in the comparison
+struct PsdTrace {
+    name: String,
+    meta_path: PathBuf,
+    /// `None` while `task` is computing it
+    psd: Option<Result<Psd, String>>,
+    task: Option<TaskHandle<Psd>>,
+    color: egui::Color32,
+    /// Added to every bin, to line up sensors with different gains
+    offset_db: f32,
+}
+
+impl PsdTrace {
+    /// Start computing the spectrum on the worker pool, replacing any
+    /// computation still running
+    fn load(&mut self, workers: &WorkerPool, params: &PsdParams) {
+        if let Some(old) = self.task.take() {
+            workers.cancel(old.id());
+        }
+        let (meta_path, params) = (self.meta_path.clone(), params.clone());
+        let name = format!("Spectrum ({} FFT): {}", params.fft_size, self.name);
+        self.psd = None;
+        self.task = Some(workers.submit(&name, Priority::High, move |ctx| {
+            let parser = SigMFParser::from_meta_file(&meta_path)?;
+            let mut reader = parser.open_reader()?;
+            ctx.check_cancelled()?;
+            Psd::compute(&mut reader, parser.sample_rate(), parser.center_frequency(), &params)
+        }));
+    }
+
+    /// Pick up the result if the computation has finished
+    fn poll(&mut self) {
+        if let Some(result) = self.task.as_ref().and_then(|task| task.try_take()) {
+            self.psd = Some(result.map_err(|e| e.to_string()));
+            self.task = None;
+        }
+    }
+
+    fn psd(&self) -> Option<&Psd> {
+        self.psd.as_ref()?.as_ref().ok()
+    }
+}
+
+/// The Spectrum tab of the recording comparison: two recordings' PSDs
+/// overlaid on one frequency axis, plus their difference
+pub struct PsdComparison {
+    traces: [PsdTrace; 2],
+    fft_size: usize,
+    show_difference: bool,
+    cursors: MeasurementCursors,
+}
+
+impl PsdComparison {
+    pub fn new(a: (String, PathBuf), b: (String, PathBuf), workers: &WorkerPool) -> Self {
+        let trace = |(name, meta_path): (String, PathBuf), color| PsdTrace {
+            name,
+            meta_path,
+            psd: None,
+            task: None,
+            color,
+            offset_db: 0.0,
+        };
+        let mut comparison = PsdComparison {
+            traces: [
+                trace(a, egui::Color32::from_rgb(31, 119, 180)),
+                trace(b, egui::Color32::from_rgb(255, 127, 14)),
+            ],
+            fft_size: PsdParams::default().fft_size,
+            show_difference: true,
+            cursors: MeasurementCursors::new(false),
+        };
+        comparison.recompute(workers);
+        comparison
+    }
+
+    fn recompute(&mut self, workers: &WorkerPool) {
+        let params = PsdParams {
+            fft_size: self.fft_size,
+            ..PsdParams::default()
+        };
+        for trace in &mut self.traces {
+            trace.load(workers, &params);
+        }
+    }
+
+    /// Stop computations nobody will look at
+    pub fn cancel(&mut self, workers: &WorkerPool) {
+        for task in self.traces.iter_mut().filter_map(|trace| trace.task.take()) {
+            workers.cancel(task.id());
+        }
+    }
+
+    /// Offset for B that puts its median level on A's
+    fn align_offsets(&mut self) {
+        if let (Some(a), Some(b)) = (self.traces[0].psd(), self.traces[1].psd()) {
+            self.traces[1].offset_db = a.median_db() + self.traces[0].offset_db - b.median_db();
+        }
+    }
+
+    /// Level of each trace at `freq`, offset included
+    fn levels_at(&self, freq: f64) -> [Option<f32>; 2] {
+        [0, 1].map(|idx| {
+            let trace = &self.traces[idx];
+            trace.psd().and_then(|psd| psd.value_at(freq)).map(|db| db + trace.offset_db)
+        })
+    }
+
+    /// Each cursor's frequency and levels, then the differences
+    fn cursor_readout(&self) -> Vec<String> {
+        let format_level = |db: Option<f32>| db.map_or_else(|| "—".to_string(), |db| format!("{:.1} dB", db));
+        let mut lines = Vec::new();
+        let mut measured = Vec::new();
+        for (idx, point) in self.cursors.points().iter().enumerate() {
+            let Some((freq, _)) = *point else {
+                continue;
+            };
+            let [a, b] = self.levels_at(freq);
+            lines.push(format!("{}: {}  A {}  B {}", idx + 1, format_frequency(freq), format_level(a), format_level(b)));
+            measured.push((freq, [a, b]));
+        }
+        if let [(f1, levels1), (f2, levels2)] = measured[..] {
+            let mut delta = format!("Δf {}", format_frequency(f2 - f1));
+            for (letter, (l1, l2)) in ["A", "B"].iter().zip(levels1.iter().zip(levels2)) {
+                if let (Some(l1), Some(l2)) = (l1, l2) {
+                    delta.push_str(&format!("  Δ{} {:+.1} dB", letter, l2 - l1));
+                }
+            }
+            lines.push(delta);
+        }
+        lines
+    }
+
+    fn difference(&self) -> Vec<(f64, f32)> {
+        match (self.traces[0].psd(), self.traces[1].psd()) {
+            (Some(a), Some(b)) => a.difference(b, self.traces[0].offset_db, self.traces[1].offset_db),
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, workers: &WorkerPool) {
+        for trace in &mut self.traces {
+            trace.poll();
+        }
+        let mut recompute = false;
+        ui.horizontal(|ui| {
+            ui.label("FFT size:");
+            egui::ComboBox::from_id_salt("psd_fft_size")
+                .selected_text(self.fft_size.to_string())
+                .show_ui(ui, |ui| {
+                    for size in FFT_SIZES {
+                        recompute |= ui.selectable_value(&mut self.fft_size, size, size.to_string()).changed();
+                    }
+                });
+            ui.checkbox(&mut self.show_difference, "Show A − B");
+            if ui.button("Align Levels").on_hover_text("Offset B so both traces have the same median level").clicked() {
+                self.align_offsets();
+            }
+            self.cursors.controls(ui);
+        });
+        for (trace, letter) in self.traces.iter_mut().zip(["A", "B"]) {
+            ui.horizontal(|ui| {
+                ui.color_edit_button_srgba(&mut trace.color);
+                ui.strong(letter);
+                ui.label(&trace.name);
+                ui.label("offset");
+                ui.add(egui::DragValue::new(&mut trace.offset_db).speed(0.1).range(-100.0..=100.0).suffix(" dB"));
+                match trace.psd {
+                    None => {
+                        ui.spinner();
+                    }
+                    Some(Err(ref e)) => {
+                        ui.colored_label(egui::Color32::RED, e);
+                    }
+                    Some(Ok(_)) => {}
+                }
+            });
+        }
+        if recompute {
+            self.recompute(workers);
+        }
+
+        let visible: Vec<(&Psd, f32, egui::Color32)> = self
+            .traces
+            .iter()
+            .filter_map(|trace| Some((trace.psd()?, trace.offset_db, trace.color)))
+            .collect();
+        if visible.is_empty() {
+            return;
+        }
+        let (min_freq, max_freq) = visible.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), (psd, _, _)| {
+            let (low, high) = psd.freq_range();
+            (min.min(low), max.max(high))
+        });
+        let (min_db, max_db) = visible.iter().fold((f32::INFINITY, f32::NEG_INFINITY), |(min, max), (psd, offset, _)| {
+            psd.power_db.iter().fold((min, max), |(min, max), db| (min.min(db + offset), max.max(db + offset)))
+        });
+        let pad = ((max_db - min_db) * 0.05).max(1.0);
+        let (min_db, max_db) = ((min_db - pad) as f64, (max_db + pad) as f64);
+        let difference = if self.show_difference { self.difference() } else { Vec::new() };
+
+        let reserved = if difference.is_empty() { 0.0 } else { DIFFERENCE_HEIGHT + AXIS_MARGIN_BOTTOM };
+        let size = egui::vec2(ui.available_width(), (ui.available_height() - reserved).max(150.0));
+        let (response, painter) = ui.allocate_painter(size, egui::Sense::click_and_drag());
+        let rect = egui::Rect::from_min_max(
+            response.rect.min + egui::vec2(AXIS_MARGIN_LEFT, 0.0),
+            response.rect.max - egui::vec2(0.0, AXIS_MARGIN_BOTTOM),
+        );
+        let freq_span = (max_freq - min_freq).max(f64::EPSILON);
+        let x_of = |freq: f64| rect.left() + ((freq - min_freq) / freq_span) as f32 * rect.width();
+        let y_of = |db: f64, rect: egui::Rect, min: f64, max: f64| rect.bottom() - ((db - min) / (max - min)) as f32 * rect.height();
+
+        painter.rect_stroke(rect, 0.0, egui::Stroke::new(1.0, ui.visuals().weak_text_color()), egui::StrokeKind::Inside);
+        draw_x_axis(&painter, rect, min_freq, max_freq, format_frequency);
+        draw_y_axis(&painter, rect, min_db, max_db, false, |db| format!("{:.0}", db));
+        for (psd, offset, color) in &visible {
+            let points = (0..psd.power_db.len())
+                .map(|bin| egui::pos2(x_of(psd.freq_of(bin)), y_of((psd.power_db[bin] + offset) as f64, rect, min_db, max_db)))
+                .collect();
+            painter.add(egui::Shape::line(points, egui::Stroke::new(1.0, *color)));
+        }
+
+        let mut diff_rect = None;
+        if !difference.is_empty() {
+            let (diff_response, diff_painter) = ui.allocate_painter(egui::vec2(ui.available_width(), DIFFERENCE_HEIGHT + AXIS_MARGIN_BOTTOM), egui::Sense::hover());
+            let drect = egui::Rect::from_min_max(
+                egui::pos2(rect.left(), diff_response.rect.top()),
+                egui::pos2(rect.right(), diff_response.rect.bottom() - AXIS_MARGIN_BOTTOM),
+            );
+            // Symmetric around zero so the sign reads at a glance
+            let limit = difference.iter().fold(1.0f32, |limit, (_, db)| limit.max(db.abs())) as f64 * 1.1;
+            diff_painter.rect_stroke(drect, 0.0, egui::Stroke::new(1.0, ui.visuals().weak_text_color()), egui::StrokeKind::Inside);
+            draw_x_axis(&diff_painter, drect, min_freq, max_freq, format_frequency);
+            draw_y_axis(&diff_painter, drect, -limit, limit, false, |db| format!("{:+.0}", db));
+            let zero = y_of(0.0, drect, -limit, limit);
+            diff_painter.line_segment([egui::pos2(drect.left(), zero), egui::pos2(drect.right(), zero)], egui::Stroke::new(1.0, ui.visuals().weak_text_color()));
+            let points = difference.iter().map(|(freq, db)| egui::pos2(x_of(*freq), y_of(*db as f64, drect, -limit, limit))).collect();
+            diff_painter.add(egui::Shape::line(points, egui::Stroke::new(1.0, DIFFERENCE_COLOR)));
+            diff_painter.text(drect.left_top() + egui::vec2(4.0, 2.0), egui::Align2::LEFT_TOP, "A − B", egui::FontId::proportional(11.0), DIFFERENCE_COLOR);
+            diff_rect = Some((drect, diff_response));
+        }
+
+        // Cursors are vertical lines at a frequency
+        let to_screen = |freq: f64, _| egui::pos2(x_of(freq), rect.center().y);
+        let from_screen = |pos: egui::Pos2| (min_freq + ((pos.x - rect.left()) / rect.width()) as f64 * freq_span, 0.0);
+        self.cursors.interact(&response, rect, to_screen, from_screen);
+        self.cursors.draw(&painter, rect, to_screen);
+        self.cursors.draw_readout(&painter, rect, &self.cursor_readout());
+
+        // Cursor readout of both traces and their difference
+        let hover = response.hover_pos().map(|pos| (pos, &response)).or_else(|| {
+            diff_rect.as_ref().and_then(|(_, r)| r.hover_pos().map(|pos| (pos, r)))
+        });
+        if let Some((pos, hovered)) = hover {
+            let freq = min_freq + ((pos.x - rect.left()) / rect.width()) as f64 * freq_span;
+            if (min_freq..=max_freq).contains(&freq) {
+                let cursor = egui::Stroke::new(1.0, ui.visuals().text_color().gamma_multiply(0.5));
+                painter.line_segment([egui::pos2(pos.x, rect.top()), egui::pos2(pos.x, rect.bottom())], cursor);
+                let mut text = format_frequency(freq);
+                let values = self.levels_at(freq);
+                for (value, letter) in values.iter().zip(["A", "B"]) {
+                    if let Some(db) = value {
+                        text.push_str(&format!("\n{}: {:.1} dB", letter, db));
+                    }
+                }
+                if let [Some(a), Some(b)] = values {
+                    text.push_str(&format!("\nA − B: {:+.1} dB", a - b));
+                }
+                hovered.clone().on_hover_text_at_pointer(text);
+            }
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use std::path::Path;
+
+/// Loaded directories and dataset files remembered in the config
+pub const MAX_RECENT_DIRECTORIES: usize = 10;
+
+// handle the recent and favorite directories offered in the File menu and
+// on the start screen
+impl SigViewerApp {
+    /// Move a successfully loaded `path` to the top of the recent list
+    pub(crate) fn remember_directory(&mut self, path: &str) {
+        let recent = &mut self.config.recent_directories;
+        recent.retain(|dir| dir != path);
+        recent.insert(0, path.to_string());
+        recent.truncate(MAX_RECENT_DIRECTORIES);
+    }
+
+    fn is_favorite(&self, path: &str) -> bool {
+        self.config.favorite_directories.iter().any(|dir| dir == path)
+    }
+
+    /// Pin `path` as a favorite, or unpin it
+    pub(crate) fn toggle_favorite(&mut self, path: &str) {
+        if self.is_favorite(path) {
+            self.config.favorite_directories.retain(|dir| dir != path);
+        } else {
+            self.config.favorite_directories.push(path.to_string());
+        }
+        self.save_config();
+    }
+
+    /// Favorites first, then recent directories that aren't pinned
+    fn directory_entries(&self) -> Vec<(String, bool)> {
+        let favorites = self.config.favorite_directories.iter().map(|dir| (dir.clone(), true));
+        let recent = self.config.recent_directories.iter().filter(|dir| !self.is_favorite(dir)).map(|dir| (dir.clone(), false));
+        favorites.chain(recent).collect()
+    }
+
+    fn load_recent(&mut self, path: &str) {
+        self.directory_path = path.to_string();
+        self.load_dataset(path);
+    }
+
+    /// File → Open Recent
+    pub(crate) fn render_recent_menu(&mut self, ui: &mut egui::Ui) {
+        let entries = self.directory_entries();
+        ui.add_enabled_ui(!entries.is_empty(), |ui| {
+            ui.menu_button("Open Recent", |ui| {
+                let mut open = None;
+                for (idx, (path, favorite)) in entries.iter().enumerate() {
+                    if idx > 0 && !favorite && entries[idx - 1].1 {
+                        ui.separator();
+                    }
+                    let label = if *favorite { format!("★ {}", path) } else { path.clone() };
+                    if ui.add_enabled(self.dataset_load.is_none(), egui::Button::new(label)).clicked() {
+                        open = Some(path.clone());
+                        ui.close();
+                    }
+                }
+                ui.separator();
+                if ui.button("Clear Recent").clicked() {
+                    self.config.recent_directories.clear();
+                    self.save_config();
+                    ui.close();
+                }
+                if let Some(path) = open {
+                    self.load_recent(&path);
+                }
+            });
+        });
+        let current = self.directory_path.clone();
+        let label = if self.is_favorite(&current) { "Unpin Current Directory" } else { "Pin Current Directory" };
+        if ui.add_enabled(self.dataset.is_some() && !current.is_empty(), egui::Button::new(label)).clicked() {
+            self.toggle_favorite(&current);
+            ui.close();
+        }
+    }
+
+    /// Favorites and recent directories with a ☆ to pin each, for the start
+    /// screen and the Load Dataset window
+    pub(crate) fn render_directory_list(&mut self, ui: &mut egui::Ui) {
+        let entries = self.directory_entries();
+        if entries.is_empty() {
+            return;
+        }
+        let mut open = None;
+        let mut toggle = None;
+        ui.group(|ui| {
+            ui.label("Favorites and recent");
+            egui::ScrollArea::vertical().max_height(200.0).id_salt("recent_directories").show(ui, |ui| {
+                for (path, favorite) in &entries {
+                    ui.horizontal(|ui| {
+                        let (star, hover) = if *favorite { ("★", "Unpin") } else { ("☆", "Pin as a favorite") };
+                        if ui.small_button(star).on_hover_text(hover).clicked() {
+                            toggle = Some(path.clone());
+                        }
+                        let exists = Path::new(path).exists();
+                        let response = ui.add_enabled(exists && self.dataset_load.is_none(), egui::Link::new(path.as_str()));
+                        if response.on_disabled_hover_text("Not found").clicked() {
+                            open = Some(path.clone());
+                        }
+                    });
+                }
+            });
+        });
+        if let Some(path) = toggle {
+            self.toggle_favorite(&path);
+        }
+        if let Some(path) = open {
+            self.load_recent(&path);
+        }
+    }
+}
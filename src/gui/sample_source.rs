@@ -0,0 +1,94 @@
+use super::plot::SampleWindow;
+use eframe::egui;
+use sig_viewer::parser::sigmf::SegmentedRecording;
+use sig_viewer::parser::{FileParser, SampleReader};
+use std::path::{Path, PathBuf};
+
+/// Where a time-based view reads samples from: the recording itself, or the
+/// whole segmented capture it's part of, so views scroll across file
+/// boundaries
+pub enum SampleSource {
+    Single(PathBuf),
+    Stitched(SegmentedRecording),
+}
+
+impl SampleSource {
+    /// Stitched when `meta_path` is one of the segments of `stitched`
+    pub fn new(meta_path: &Path, stitched: Option<SegmentedRecording>) -> Self {
+        match stitched.filter(|recording| recording.position_of(meta_path).is_some()) {
+            Some(recording) => SampleSource::Stitched(recording),
+            None => SampleSource::Single(meta_path.to_path_buf()),
+        }
+    }
+
+    pub fn reader(&self) -> anyhow::Result<Box<dyn SampleReader>> {
+        Ok(match self {
+            SampleSource::Single(meta_path) => FileParser::open_reader(meta_path)?,
+            SampleSource::Stitched(recording) => Box::new(recording.open_reader()?),
+        })
+    }
+
+    /// First sample of `meta_path`'s own file (0 unless stitched)
+    pub fn offset_of(&self, meta_path: &Path) -> u64 {
+        match self {
+            SampleSource::Single(_) => 0,
+            SampleSource::Stitched(recording) => recording
+                .position_of(meta_path)
+                .map_or(0, |idx| recording.segment_starts()[idx]),
+        }
+    }
+
+    /// The file holding stitched sample `sample`, and the sample's offset in it
+    pub fn locate(&self, sample: u64) -> (&Path, u64) {
+        match self {
+            SampleSource::Single(meta_path) => (meta_path, sample),
+            SampleSource::Stitched(recording) => {
+                let idx = recording.segment_at(sample);
+                (&recording.segments[idx].meta_path, sample - recording.segment_starts()[idx])
+            }
+        }
+    }
+
+    /// Which file of a stitched capture `sample` falls in, for a status line
+    pub fn describe(&self, sample: u64) -> Option<String> {
+        match self {
+            SampleSource::Single(_) => None,
+            SampleSource::Stitched(recording) => Some(format!(
+                "Stitched capture {}: segment {} of {}",
+                recording.name(),
+                recording.segment_at(sample) + 1,
+                recording.segments.len()
+            )),
+        }
+    }
+
+    /// Mark where one file ends and the next begins. Time runs down the rect
+    /// when `vertical`, else left to right.
+    pub fn draw_boundaries(&self, painter: &egui::Painter, rect: egui::Rect, window: &SampleWindow, vertical: bool) {
+        let SampleSource::Stitched(recording) = self else {
+            return;
+        };
+        let stroke = egui::Stroke::new(1.0, egui::Color32::from_rgba_unmultiplied(255, 255, 255, 160));
+        for (idx, start) in recording.segment_starts().into_iter().enumerate().skip(1) {
+            if start <= window.start || start >= window.end() {
+                continue;
+            }
+            let frac = ((start - window.start) as f64 / window.len as f64) as f32;
+            let (line, label_pos, align) = if vertical {
+                let y = rect.top() + frac * rect.height();
+                ([egui::pos2(rect.left(), y), egui::pos2(rect.right(), y)], egui::pos2(rect.right() - 4.0, y - 2.0), egui::Align2::RIGHT_BOTTOM)
+            } else {
+                let x = rect.left() + frac * rect.width();
+                ([egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())], egui::pos2(x + 3.0, rect.top() + 2.0), egui::Align2::LEFT_TOP)
+            };
+            painter.extend(egui::Shape::dashed_line(&line, stroke, 6.0, 4.0));
+            painter.text(
+                label_pos,
+                align,
+                format!("seq {}", recording.segments[idx].sequence_num),
+                egui::FontId::monospace(10.0),
+                stroke.color,
+            );
+        }
+    }
+}
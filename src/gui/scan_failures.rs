@@ -0,0 +1,77 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use std::collections::BTreeMap;
+
+// handle the files the last directory scan couldn't read
+impl SigViewerApp {
+    /// "N files failed" in the menu bar while the last scan skipped any,
+    /// as a button that opens the list
+    pub(crate) fn render_scan_failures_indicator(&mut self, ui: &mut egui::Ui) {
+        let Some(report) = self.scan_report.as_ref().filter(|_| self.dataset_load.is_none()) else {
+            return;
+        };
+        let n = report.failures.len();
+        let label = format!("⚠ {} file{} failed — view details", n, if n == 1 { "" } else { "s" });
+        if ui.button(egui::RichText::new(label).color(egui::Color32::from_rgb(200, 120, 0))).clicked() {
+            self.show_scan_failures = true;
+        }
+    }
+
+    pub(crate) fn render_scan_failures(&mut self, ctx: &egui::Context) {
+        if !self.show_scan_failures {
+            return;
+        }
+        let Some(report) = self.scan_report.as_ref() else {
+            self.show_scan_failures = false;
+            return;
+        };
+        let mut open = true;
+        egui::Window::new("Failed Files")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([800.0, 400.0])
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} of {} files could not be read and are missing from the table",
+                    report.failures.len(),
+                    report.files
+                ));
+                let mut kinds: BTreeMap<&str, usize> = BTreeMap::new();
+                for failure in &report.failures {
+                    *kinds.entry(failure.error.kind()).or_default() += 1;
+                }
+                let breakdown: Vec<String> = kinds.iter().map(|(kind, n)| format!("{} {}", n, kind)).collect();
+                ui.horizontal(|ui| {
+                    ui.weak(breakdown.join(", "));
+                    ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                        if ui.button("Copy List").on_hover_text("Copy one line per failure").clicked() {
+                            let lines: Vec<String> = report.failures.iter().map(|failure| failure.to_string()).collect();
+                            ui.ctx().copy_text(lines.join("\n"));
+                        }
+                    });
+                });
+                ui.separator();
+                egui::ScrollArea::both().auto_shrink([false, false]).show(ui, |ui| {
+                    egui::Grid::new("scan_failures").striped(true).show(ui, |ui| {
+                        ui.strong("File");
+                        ui.strong("Step");
+                        ui.strong("Kind");
+                        ui.strong("Reason");
+                        ui.end_row();
+                        for failure in &report.failures {
+                            let name = failure.path.file_name().map_or_else(
+                                || failure.path.display().to_string(),
+                                |name| name.to_string_lossy().to_string(),
+                            );
+                            ui.label(name).on_hover_text(failure.path.display().to_string());
+                            ui.label(failure.stage.description().trim_start_matches("Failed to "));
+                            ui.label(failure.error.kind());
+                            ui.label(failure.error.to_string());
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+        self.show_scan_failures = open;
+    }
+}
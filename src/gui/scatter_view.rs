@@ -0,0 +1,377 @@
+use crate::gui::cluster_view::cluster_color;
+use crate::gui::map_view::draw_legend;
+use crate::gui::plot;
+use crate::SigViewerApp;
+use eframe::egui;
+use polars::prelude::*;
+use sig_viewer::data_ops::CLUSTER_COLUMN;
+use sig_viewer::viz::Colormap;
+use std::collections::HashMap;
+
+const AXIS_MARGIN_LEFT: f32 = 80.0;
+const AXIS_MARGIN_BOTTOM: f32 = 20.0;
+const POINT_RADIUS: f32 = 3.0;
+/// How close (in pixels) a click or hover has to be to pick a point
+const PICK_DISTANCE: f32 = 6.0;
+/// Text values given their own color; the rest share one
+const MAX_CATEGORIES: usize = 10;
+const OTHER_COLOR: egui::Color32 = egui::Color32::from_rgb(150, 150, 150);
+const UNIFORM_COLOR: egui::Color32 = egui::Color32::from_rgb(31, 119, 180);
+
+/// State of the Scatter Plot window: the column pair, the color-by column
+/// and the zoomed range
+pub struct ScatterView {
+    x_column: Option<String>,
+    y_column: Option<String>,
+    color_column: Option<String>,
+    /// (x min, x max, y min, y max) shown; None fits every point
+    range: Option<[f64; 4]>,
+    /// Rebuilt when a column choice or the filtered rows change
+    pub points: Option<Result<ScatterPoints, String>>,
+}
+
+pub struct ScatterPoints {
+    points: Vec<ScatterPoint>,
+    /// Rows without a finite x and y
+    skipped_rows: usize,
+    colors: PointColors,
+}
+
+struct ScatterPoint {
+    row: usize,
+    x: f64,
+    y: f64,
+    /// Color-by value, or the index of its category
+    value: Option<f64>,
+}
+
+/// How `ScatterPoint::value` maps to a color
+enum PointColors {
+    Uniform,
+    /// Viridis from min to max
+    Scale(f64, f64),
+    /// Cluster ids in the cluster palette
+    Clusters,
+    /// The most frequent values, most frequent first, and how many distinct
+    /// values there are; an index past the names is "other"
+    Categories(Vec<String>, usize),
+}
+
+impl PointColors {
+    fn color(&self, value: Option<f64>) -> egui::Color32 {
+        match (self, value) {
+            (PointColors::Uniform, _) => UNIFORM_COLOR,
+            (PointColors::Scale(min, max), Some(value)) => {
+                let t = if max > min { ((value - min) / (max - min)) as f32 } else { 0.5 };
+                let [r, g, b] = Colormap::Viridis.map(t);
+                egui::Color32::from_rgb(r, g, b)
+            }
+            (PointColors::Clusters, Some(id)) => cluster_color(id as i64),
+            (PointColors::Categories(names, _), Some(idx)) if (idx as usize) < names.len() => cluster_color(idx as i64),
+            _ => OTHER_COLOR,
+        }
+    }
+}
+
+// handle the scatter plot of one column against another
+impl SigViewerApp {
+    /// Open on center_freq_hz against snr_db when the dataset has them, else
+    /// the first two numeric columns
+    pub(crate) fn open_scatter_view(&mut self) {
+        if self.scatter_view.is_some() {
+            return;
+        }
+        let numeric = self.numeric_columns();
+        let pick = |preferred: &str, fallback: usize| {
+            Some(preferred.to_string()).filter(|name| numeric.contains(name)).or_else(|| numeric.get(fallback).cloned())
+        };
+        self.scatter_view = Some(ScatterView {
+            x_column: pick("center_freq_hz", 0),
+            y_column: pick("snr_db", 1),
+            color_column: None,
+            range: None,
+            points: None,
+        });
+    }
+
+    pub(crate) fn render_scatter_view(&mut self, ctx: &egui::Context) {
+        let Some(mut view) = self.scatter_view.take() else {
+            return;
+        };
+        let mut open = true;
+        let mut clicked_row = None;
+        egui::Window::new("Scatter Plot")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([800.0, 600.0])
+            .show(ctx, |ui| {
+                let Some(dataset) = self.filtered_dataset.clone() else {
+                    ui.label("Load a dataset first");
+                    return;
+                };
+                let numeric = self.numeric_columns();
+                let before = (view.x_column.clone(), view.y_column.clone(), view.color_column.clone());
+                ui.horizontal(|ui| {
+                    for (label, axis) in [("x:", &mut view.x_column), ("y:", &mut view.y_column)] {
+                        ui.label(label);
+                        egui::ComboBox::from_id_salt(format!("scatter_axis_{}", label))
+                            .selected_text(axis.clone().unwrap_or_else(|| "(none)".to_string()))
+                            .height(400.0)
+                            .show_ui(ui, |ui| {
+                                for name in &numeric {
+                                    ui.selectable_value(axis, Some(name.clone()), name);
+                                }
+                            });
+                    }
+                    ui.label("Color by:");
+                    egui::ComboBox::from_id_salt("scatter_color")
+                        .selected_text(view.color_column.clone().unwrap_or_else(|| "(none)".to_string()))
+                        .height(400.0)
+                        .show_ui(ui, |ui| {
+                            ui.selectable_value(&mut view.color_column, None, "(none)");
+                            for column in dataset.get_columns() {
+                                let name = column.name().to_string();
+                                ui.selectable_value(&mut view.color_column, Some(name.clone()), name);
+                            }
+                        });
+                    if ui.button("Reset View").clicked() {
+                        view.range = None;
+                    }
+                });
+                ui.weak("Drag to pan, scroll to zoom, double-click to fit, click a point to select its row");
+                if before != (view.x_column.clone(), view.y_column.clone(), view.color_column.clone()) {
+                    view.points = None;
+                    if (&before.0, &before.1) != (&view.x_column, &view.y_column) {
+                        view.range = None;
+                    }
+                }
+
+                let (Some(x), Some(y)) = (view.x_column.clone(), view.y_column.clone()) else {
+                    ui.label("Pick numeric columns for both axes");
+                    return;
+                };
+                if view.points.is_none() {
+                    view.points = Some(scatter_points(&dataset, &x, &y, view.color_column.as_deref()).map_err(|e| e.to_string()));
+                }
+                let points = match view.points {
+                    Some(Ok(ref points)) => points,
+                    Some(Err(ref e)) => {
+                        ui.colored_label(egui::Color32::RED, format!("Cannot plot: {}", e));
+                        return;
+                    }
+                    None => return,
+                };
+                if points.skipped_rows > 0 {
+                    ui.label(format!("{} rows without both values were skipped", points.skipped_rows));
+                }
+                if let PointColors::Categories(ref names, distinct) = points.colors {
+                    show_categories(ui, names, distinct);
+                }
+                if points.points.is_empty() {
+                    ui.label("No rows with both values in the current view");
+                    return;
+                }
+                let labels = AxisLabels { x: &x, y: &y, color: view.color_column.as_deref() };
+                clicked_row = self.draw_scatter_plot(ui, points, &mut view.range, &dataset, &labels);
+            });
+        if open {
+            self.scatter_view = Some(view);
+        }
+        if let Some(row) = clicked_row {
+            let modifiers = ctx.input(|i| i.modifiers);
+            self.click_row(row, modifiers);
+        }
+    }
+
+    /// Draw the points; returns the row of a clicked point
+    fn draw_scatter_plot(
+        &self,
+        ui: &mut egui::Ui,
+        points: &ScatterPoints,
+        range: &mut Option<[f64; 4]>,
+        dataset: &DataFrame,
+        labels: &AxisLabels,
+    ) -> Option<usize> {
+        let size = ui.available_size().max(egui::vec2(300.0, 200.0));
+        let (response, painter) = ui.allocate_painter(size, egui::Sense::click_and_drag());
+        let rect = egui::Rect::from_min_max(
+            response.rect.min + egui::vec2(AXIS_MARGIN_LEFT, 0.0),
+            response.rect.max - egui::vec2(0.0, AXIS_MARGIN_BOTTOM),
+        );
+        if response.double_clicked() {
+            *range = None;
+        }
+        let [x_min, x_max, y_min, y_max] = *range.get_or_insert_with(|| fit(&points.points));
+        let (x_span, y_span) = (x_max - x_min, y_max - y_min);
+        if response.dragged() {
+            let delta = response.drag_delta();
+            let dx = -(delta.x / rect.width()) as f64 * x_span;
+            let dy = (delta.y / rect.height()) as f64 * y_span;
+            *range = Some([x_min + dx, x_max + dx, y_min + dy, y_max + dy]);
+        }
+        if let Some(pos) = response.hover_pos().filter(|pos| rect.contains(*pos)) {
+            let scroll = ui.input(|i| i.smooth_scroll_delta.y);
+            if scroll != 0.0 {
+                let factor = (-scroll as f64 * 0.003).exp();
+                let anchor_x = x_min + ((pos.x - rect.left()) / rect.width()) as f64 * x_span;
+                let anchor_y = y_max - ((pos.y - rect.top()) / rect.height()) as f64 * y_span;
+                *range = Some([
+                    anchor_x - (anchor_x - x_min) * factor,
+                    anchor_x + (x_max - anchor_x) * factor,
+                    anchor_y - (anchor_y - y_min) * factor,
+                    anchor_y + (y_max - anchor_y) * factor,
+                ]);
+            }
+        }
+        let [x_min, x_max, y_min, y_max] = range.unwrap_or([x_min, x_max, y_min, y_max]);
+        let to_screen = |p: &ScatterPoint| {
+            egui::pos2(
+                rect.left() + ((p.x - x_min) / (x_max - x_min)) as f32 * rect.width(),
+                rect.bottom() - ((p.y - y_min) / (y_max - y_min)) as f32 * rect.height(),
+            )
+        };
+
+        let visuals = ui.visuals();
+        painter.rect_stroke(rect, 0.0, egui::Stroke::new(1.0, visuals.weak_text_color()), egui::StrokeKind::Inside);
+        plot::draw_x_axis(&painter, rect, x_min, x_max, axis_format(labels.x));
+        plot::draw_y_axis(&painter, rect, y_min, y_max, false, axis_format(labels.y));
+        let clipped = painter.with_clip_rect(rect);
+        let positions: Vec<egui::Pos2> = points.points.iter().map(to_screen).collect();
+        for (point, pos) in points.points.iter().zip(&positions) {
+            clipped.circle_filled(*pos, POINT_RADIUS, points.colors.color(point.value));
+        }
+        // Selected rows on top, ringed
+        let highlight = egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 140, 0));
+        for (point, pos) in points.points.iter().zip(&positions) {
+            if self.selected_rows.contains(&point.row) {
+                clipped.circle(*pos, POINT_RADIUS + 2.0, points.colors.color(point.value), highlight);
+            }
+        }
+        if let PointColors::Scale(min, max) = points.colors {
+            draw_legend(&painter, rect, min, max);
+        }
+
+        let pointer = response.hover_pos()?;
+        let idx = positions
+            .iter()
+            .enumerate()
+            .filter(|(_, pos)| rect.contains(**pos) && pos.distance(pointer) <= PICK_DISTANCE)
+            .min_by(|(_, a), (_, b)| a.distance_sq(pointer).total_cmp(&b.distance_sq(pointer)))
+            .map(|(idx, _)| idx)?;
+        let point = &points.points[idx];
+        let clicked = response.clicked();
+        let cell = |column: &str| {
+            dataset.column(column).ok().and_then(|c| c.get(point.row).ok()).map(|value| match value {
+                AnyValue::String(text) => text.to_string(),
+                AnyValue::Null => "null".to_string(),
+                value => value.to_string(),
+            })
+        };
+        let mut text = format!(
+            "{}\nrow {}\n{}: {}\n{}: {}",
+            cell("meta_filename").unwrap_or_default(),
+            point.row + 1,
+            labels.x,
+            point.x,
+            labels.y,
+            point.y
+        );
+        if let Some(column) = labels.color {
+            text.push_str(&format!("\n{}: {}", column, cell(column).unwrap_or_default()));
+        }
+        response.on_hover_text_at_pointer(text);
+        clicked.then_some(point.row)
+    }
+}
+
+struct AxisLabels<'a> {
+    x: &'a str,
+    y: &'a str,
+    color: Option<&'a str>,
+}
+
+/// Frequencies in Hz/kHz/MHz/GHz, other values as plain numbers
+fn axis_format(column: &str) -> fn(f64) -> String {
+    if column.ends_with("_hz") {
+        plot::format_frequency
+    } else {
+        |value| format!("{}", (value * 1000.0).round() / 1000.0)
+    }
+}
+
+/// Range of the points with a little padding, so none sits on the border
+fn fit(points: &[ScatterPoint]) -> [f64; 4] {
+    let range = |values: &mut dyn Iterator<Item = f64>| {
+        let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), v| (min.min(v), max.max(v)));
+        let pad = if max > min { (max - min) * 0.05 } else { 0.5 };
+        (min - pad, max + pad)
+    };
+    let (x_min, x_max) = range(&mut points.iter().map(|p| p.x));
+    let (y_min, y_max) = range(&mut points.iter().map(|p| p.y));
+    [x_min, x_max, y_min, y_max]
+}
+
+fn show_categories(ui: &mut egui::Ui, names: &[String], distinct: usize) {
+    ui.horizontal_wrapped(|ui| {
+        for (idx, name) in names.iter().enumerate() {
+            let name = if name.is_empty() { "(empty)" } else { name };
+            ui.label(egui::RichText::new(format!("■ {}", name)).color(cluster_color(idx as i64)));
+        }
+        if distinct > names.len() {
+            ui.label(egui::RichText::new(format!("■ {} others", distinct - names.len())).color(OTHER_COLOR));
+        }
+    });
+}
+
+/// Rows of `df` with finite `x` and `y` values, with what to color them by:
+/// a scale for numeric and boolean columns, cluster colors for cluster ids
+/// and the most frequent values of anything else
+fn scatter_points(df: &DataFrame, x: &str, y: &str, color_by: Option<&str>) -> anyhow::Result<ScatterPoints> {
+    let xs = df.column(x)?.cast(&DataType::Float64)?;
+    let ys = df.column(y)?.cast(&DataType::Float64)?;
+    let (xs, ys) = (xs.f64()?, ys.f64()?);
+    let (values, colors) = match color_by.map(|name| df.column(name)).transpose()? {
+        None => (None, PointColors::Uniform),
+        Some(column) if column.dtype().is_numeric() || matches!(column.dtype(), DataType::Boolean) => {
+            let values = column.cast(&DataType::Float64)?;
+            let colors = if column.name() == CLUSTER_COLUMN {
+                PointColors::Clusters
+            } else {
+                match (values.f64()?.min(), values.f64()?.max()) {
+                    (Some(min), Some(max)) => PointColors::Scale(min, max),
+                    _ => PointColors::Uniform,
+                }
+            };
+            (Some(values.f64()?.into_iter().collect::<Vec<_>>()), colors)
+        }
+        Some(column) => {
+            let text = column.cast(&DataType::String)?;
+            let text: Vec<Option<&str>> = text.str()?.into_iter().collect();
+            let mut counts: HashMap<&str, usize> = HashMap::new();
+            for value in text.iter().flatten() {
+                *counts.entry(value).or_default() += 1;
+            }
+            let mut names: Vec<(&str, usize)> = counts.into_iter().collect();
+            names.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+            let distinct = names.len();
+            let index: HashMap<&str, usize> = names.iter().enumerate().map(|(idx, (name, _))| (*name, idx.min(MAX_CATEGORIES))).collect();
+            let values = text.iter().map(|value| value.map(|value| index[value] as f64)).collect();
+            let names = names.into_iter().take(MAX_CATEGORIES).map(|(name, _)| name.to_string()).collect();
+            (Some(values), PointColors::Categories(names, distinct))
+        }
+    };
+    let mut skipped_rows = 0;
+    let points = (0..df.height())
+        .filter_map(|row| {
+            let point = match (xs.get(row), ys.get(row)) {
+                (Some(x), Some(y)) if x.is_finite() && y.is_finite() => {
+                    Some(ScatterPoint { row, x, y, value: values.as_ref().and_then(|values| values[row]) })
+                }
+                _ => None,
+            };
+            skipped_rows += usize::from(point.is_none());
+            point
+        })
+        .collect();
+    Ok(ScatterPoints { points, skipped_rows, colors })
+}
@@ -0,0 +1,184 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use polars::prelude::*;
+use sig_viewer::parser::sigmf::copy_recording;
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Above this many recordings, "open in" actions are disabled rather than
+/// spawning a window or tab per recording
+const MAX_OPEN_AT_ONCE: usize = 20;
+
+// handle multi-row selection and batch actions
+impl SigViewerApp {
+    /// A click on a row's select cell. Plain clicks select just that row (or
+    /// clear it if it's the only one selected), ctrl/cmd toggles the row and
+    /// shift selects the range from the primary row.
+    pub(crate) fn click_row(&mut self, row: usize, modifiers: egui::Modifiers) {
+        match self.selected_row {
+            Some(anchor) if modifiers.shift => {
+                self.selected_rows = (anchor.min(row)..=anchor.max(row)).collect();
+            }
+            _ if modifiers.command => {
+                if self.selected_rows.insert(row) {
+                    self.focus_row(row);
+                } else if self.selected_row == Some(row) {
+                    self.selected_rows.remove(&row);
+                    match self.selected_rows.last().copied() {
+                        Some(next) => self.focus_row(next),
+                        None => self.clear_selection(),
+                    }
+                } else {
+                    self.selected_rows.remove(&row);
+                }
+            }
+            _ if self.selected_rows.len() == 1 && self.selected_rows.contains(&row) => self.clear_selection(),
+            _ => self.select_row(row),
+        }
+    }
+
+    /// `meta_filename` of each selected row, each recording once
+    pub(crate) fn selected_meta_filenames(&self) -> Vec<String> {
+        let Some(filenames) = self
+            .filtered_dataset
+            .as_ref()
+            .and_then(|df| df.column("meta_filename").ok())
+            .and_then(|column| column.str().ok())
+        else {
+            return Vec::new();
+        };
+        let mut seen = HashSet::new();
+        self.selected_rows
+            .iter()
+            .filter_map(|&row| filenames.get(row))
+            .filter(|name| seen.insert(*name))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// Located meta files of the selected recordings, and the names that
+    /// couldn't be found
+    fn selected_meta_paths(&self) -> (Vec<PathBuf>, Vec<String>) {
+        let mut found = Vec::new();
+        let mut missing = Vec::new();
+        for name in self.selected_meta_filenames() {
+            match self.locate_meta_file(&name) {
+                Some(path) => found.push(path),
+                None => missing.push(name),
+            }
+        }
+        (found, missing)
+    }
+
+    /// Toolbar shown above the table while several rows are selected
+    pub(crate) fn render_batch_actions(&mut self, ui: &mut egui::Ui, dataset: &DataFrame) {
+        let recordings = self.selected_meta_filenames().len();
+        ui.label(format!(
+            "{} rows selected ({} recording{})",
+            self.selected_rows.len(),
+            recordings,
+            if recordings == 1 { "" } else { "s" }
+        ));
+
+        if ui.button("Export Selected...").clicked() {
+            self.open_export_dialog(Some(self.selected_rows.iter().copied().collect()));
+        }
+        let is_pair = self.selected_rows.len() == 2;
+        let response = ui
+            .add_enabled(is_pair, egui::Button::new("Compare"))
+            .on_hover_text("Overlay their spectra, show their spectrograms side by side and list the columns that differ")
+            .on_disabled_hover_text("Select exactly two rows to compare them");
+        if response.clicked() {
+            self.open_comparison();
+        }
+        self.render_measure_menu(ui);
+        if ui.button("Copy Files To...").clicked() {
+            let start = Some(PathBuf::from(&self.directory_path)).filter(|path| path.is_dir());
+            let mut dialog = egui_file::FileDialog::select_folder(start)
+                .title("Copy selected recordings to");
+            dialog.open();
+            self.copy_selected_dialog = Some(dialog);
+        }
+
+        let too_many = recordings > MAX_OPEN_AT_ONCE;
+        let too_many_hint = format!("Select at most {} recordings to open them all", MAX_OPEN_AT_ONCE);
+        let response = ui.add_enabled(!too_many, egui::Button::new("Open All in Inspectrum"));
+        if response.on_disabled_hover_text(&too_many_hint).clicked() {
+            self.open_selected_in_inspectrum();
+        }
+
+        // One button per link template, opening a tab for each selected row
+        let mut links: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for &row in &self.selected_rows {
+            for (name, url) in self.row_links(dataset, row) {
+                let urls = links.entry(name).or_default();
+                if !urls.contains(&url) {
+                    urls.push(url);
+                }
+            }
+        }
+        for (name, urls) in links {
+            let enabled = urls.len() <= MAX_OPEN_AT_ONCE;
+            let response = ui
+                .add_enabled(enabled, egui::Button::new(format!("Open All in {}", name)))
+                .on_disabled_hover_text(&too_many_hint);
+            if response.clicked() {
+                for url in urls {
+                    ui.ctx().open_url(egui::OpenUrl::new_tab(url));
+                }
+            }
+        }
+
+        if ui.button("Clear Selection").clicked() {
+            self.clear_selection();
+        }
+    }
+
+    fn open_selected_in_inspectrum(&mut self) {
+        let (paths, missing) = self.selected_meta_paths();
+        let mut failed: Vec<String> = missing.into_iter().map(|name| format!("Could not locate {}", name)).collect();
+        let mut launched = 0;
+        for path in &paths {
+            match self.open_recording_viewer(path) {
+                Ok(()) => launched += 1,
+                Err(e) => failed.push(e.to_string()),
+            }
+        }
+        self.status_message = format!("Opened {} recordings in Inspectrum", launched);
+        if !failed.is_empty() {
+            self.error_message = Some(failed.join("\n"));
+        }
+    }
+
+    /// Copy the selected recordings' meta and data files into `out_dir`
+    fn copy_selected_to(&mut self, out_dir: &Path) {
+        let (paths, missing) = self.selected_meta_paths();
+        let mut failed: Vec<String> = missing.into_iter().map(|name| format!("Could not locate {}", name)).collect();
+        let mut copied = 0;
+        for path in &paths {
+            match copy_recording(path, out_dir) {
+                Ok(_) => copied += 1,
+                Err(e) => failed.push(format!("{}: {}", path.display(), e)),
+            }
+        }
+        self.status_message = format!("Copied {} recordings to {}", copied, out_dir.display());
+        if !failed.is_empty() {
+            self.error_message = Some(format!("{} recordings were not copied:\n{}", failed.len(), failed.join("\n")));
+        }
+    }
+
+    pub(crate) fn render_copy_selected_dialog(&mut self, ctx: &egui::Context) {
+        let Some(ref mut dialog) = self.copy_selected_dialog else {
+            return;
+        };
+        if dialog.show(ctx).selected() {
+            let out_dir = dialog.path().map(Path::to_path_buf);
+            self.copy_selected_dialog = None;
+            if let Some(out_dir) = out_dir {
+                self.copy_selected_to(&out_dir);
+            }
+        } else if !dialog.visible() {
+            self.copy_selected_dialog = None;
+        }
+    }
+}
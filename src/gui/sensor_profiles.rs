@@ -0,0 +1,162 @@
+use crate::{launch_inspectrum, SigViewerApp};
+use eframe::egui;
+use polars::prelude::*;
+use sig_viewer::data_ops::{find_profile, SensorProfile};
+use sig_viewer::parser::SigMFParser;
+use std::path::Path;
+
+// handle sensor profiles: settings window, colors and per-sensor viewers
+impl SigViewerApp {
+    /// Display color of the profile for `sdr_handle`, if it has one
+    pub(crate) fn sensor_color(&self, sdr_handle: &str) -> Option<egui::Color32> {
+        find_profile(&self.config.sensor_profiles, sdr_handle)
+            .and_then(|profile| profile.color)
+            .map(|[r, g, b]| egui::Color32::from_rgb(r, g, b))
+    }
+
+    /// `sdr_handle` of the first row of the loaded dataset from `meta_filename`
+    fn sdr_handle_of(&self, meta_filename: &str) -> Option<String> {
+        let dataset = self.dataset.as_ref()?;
+        let filenames = dataset.column("meta_filename").ok()?.str().ok()?;
+        let handles = dataset.column("sdr_handle").ok()?.str().ok()?;
+        let row = filenames.iter().position(|name| name == Some(meta_filename))?;
+        handles.get(row).map(str::to_string)
+    }
+
+    /// Open a recording in its sensor's configured viewer, or inspectrum
+    pub(crate) fn open_recording_viewer(&self, meta_path: &Path) -> anyhow::Result<()> {
+        let profile = meta_path
+            .file_name()
+            .and_then(|name| self.sdr_handle_of(&name.to_string_lossy()))
+            .and_then(|handle| find_profile(&self.config.sensor_profiles, &handle).cloned());
+        let Some(profile) = profile.filter(|p| !p.viewer_command.trim().is_empty()) else {
+            return launch_inspectrum(meta_path);
+        };
+        let data_path = SigMFParser::from_meta_file(meta_path)?.data_file_path;
+        let args = profile.viewer_args(meta_path, &data_path).unwrap_or_default();
+        let (program, args) = args.split_first().ok_or_else(|| anyhow::anyhow!("Empty viewer command"))?;
+        std::process::Command::new(program)
+            .args(args)
+            .spawn()
+            .map_err(|e| anyhow::anyhow!("Failed to launch {} for {}: {}", program, profile.name, e))?;
+        Ok(())
+    }
+
+    pub(crate) fn render_sensor_profiles(&mut self, ctx: &egui::Context) {
+        if !self.show_sensor_profiles {
+            return;
+        }
+        // Handles in the loaded data without a profile yet, to add with one click
+        let unprofiled: Vec<String> = self
+            .dataset
+            .as_ref()
+            .and_then(|df| df.column("sdr_handle").ok()?.str().ok()?.unique().ok())
+            .map(|handles| {
+                let mut handles: Vec<String> = handles
+                    .into_iter()
+                    .flatten()
+                    .filter(|h| !h.is_empty() && find_profile(&self.config.sensor_profiles, h).is_none())
+                    .map(str::to_string)
+                    .collect();
+                handles.sort();
+                handles
+            })
+            .unwrap_or_default();
+
+        let mut open = true;
+        let mut changed = false;
+        let mut reload = false;
+        egui::Window::new("Sensor Profiles")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([760.0, 300.0])
+            .show(ctx, |ui| {
+                ui.label("Per-sensor settings matched on sdr_handle. Colors and viewers apply right away; power and frequency offsets when recordings are next loaded. Viewer commands take {meta} and {data} placeholders, e.g. inspectrum {data}");
+                ui.separator();
+
+                let mut remove = None;
+                egui::Grid::new("sensor_profiles").num_columns(7).striped(true).show(ui, |ui| {
+                    for heading in ["Name", "sdr_handle", "Power offset (dB)", "Freq offset (Hz)", "Viewer command", "Color"] {
+                        ui.strong(heading);
+                    }
+                    ui.end_row();
+                    for (idx, profile) in self.config.sensor_profiles.iter_mut().enumerate() {
+                        changed |= render_profile_row(ui, profile);
+                        if ui.button("Remove").clicked() {
+                            remove = Some(idx);
+                        }
+                        ui.end_row();
+                    }
+                });
+                if let Some(idx) = remove {
+                    self.config.sensor_profiles.remove(idx);
+                    changed = true;
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button("Add Profile").clicked() {
+                        self.config.sensor_profiles.push(SensorProfile::default());
+                        changed = true;
+                    }
+                    if !unprofiled.is_empty() {
+                        ui.menu_button("Add for Sensor", |ui| {
+                            for handle in &unprofiled {
+                                if ui.button(handle).clicked() {
+                                    self.config.sensor_profiles.push(SensorProfile {
+                                        name: handle.clone(),
+                                        sdr_handle: handle.clone(),
+                                        ..SensorProfile::default()
+                                    });
+                                    changed = true;
+                                    ui.close();
+                                }
+                            }
+                        });
+                    }
+                    let can_reload = !self.directory_path.is_empty() && self.dataset.is_some();
+                    if ui
+                        .add_enabled(can_reload, egui::Button::new("Reload with Offsets"))
+                        .on_hover_text("Rescan the current directory so changed offsets take effect")
+                        .clicked()
+                    {
+                        reload = true;
+                    }
+                });
+            });
+
+        if changed {
+            self.save_config();
+            self.invalidate_cache();
+        }
+        if reload {
+            let path = self.directory_path.clone();
+            self.load_dataset(&path);
+        }
+        if !open {
+            self.show_sensor_profiles = false;
+        }
+    }
+}
+
+/// Editors for one profile's fields; returns whether anything changed
+fn render_profile_row(ui: &mut egui::Ui, profile: &mut SensorProfile) -> bool {
+    let mut changed = false;
+    changed |= ui.add(egui::TextEdit::singleline(&mut profile.name).desired_width(120.0)).changed();
+    changed |= ui.add(egui::TextEdit::singleline(&mut profile.sdr_handle).desired_width(120.0)).changed();
+    changed |= ui.add(egui::DragValue::new(&mut profile.power_offset_db).speed(0.1).suffix(" dB")).changed();
+    changed |= ui.add(egui::DragValue::new(&mut profile.freq_offset_hz).speed(10.0).suffix(" Hz")).changed();
+    changed |= ui
+        .add(egui::TextEdit::singleline(&mut profile.viewer_command).hint_text("inspectrum {meta}").desired_width(180.0))
+        .changed();
+    ui.horizontal(|ui| {
+        let mut has_color = profile.color.is_some();
+        if ui.checkbox(&mut has_color, "").changed() {
+            profile.color = has_color.then_some([70, 130, 220]);
+            changed = true;
+        }
+        if let Some(ref mut color) = profile.color {
+            changed |= ui.color_edit_button_srgb(color).changed();
+        }
+    });
+    changed
+}
@@ -0,0 +1,271 @@
+use crate::gui::sorting::SortState;
+use crate::{SigViewerApp, VizTab};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use sig_viewer::data_ops::{gui_config_path, parse_cast_spec};
+use sig_viewer::parser::sigmf::Granularity;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+/// Marks a JSON file as a saved session, like the settings bundle's format
+const SESSION_FORMAT: &str = "sig_viewer_session";
+const SESSION_VERSION: u32 = 1;
+
+/// Where the analysis stood: what was loaded and how, and how the table and
+/// windows looked. Settings kept in the config aren't part of it.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct Session {
+    format: String,
+    version: u32,
+    /// RFC 3339
+    saved_at: String,
+    /// Directory or exported dataset file; empty if nothing was loaded
+    path: String,
+    /// Further directories scanned along with `path`
+    other_roots: Vec<String>,
+    granularity: Granularity,
+    verify_checksums: bool,
+    estimate_noise_floor: bool,
+    compute_metrics: bool,
+    annotation_extra_columns: bool,
+    lenient_parsing: bool,
+    watch_directory: bool,
+    /// Files in a preview sample, None for a full load
+    preview_size: Option<usize>,
+    /// `column=type`, as for `--cast`
+    casts: Vec<String>,
+    /// Non-blank filter text per column
+    filters: BTreeMap<String, String>,
+    sort: Option<SortState>,
+    hidden_columns: Vec<String>,
+    /// Primary selected row, by position in the filtered table and by file
+    /// name, in case the rows changed since
+    selected_row: Option<usize>,
+    selected_file: Option<String>,
+    /// Windows that were open, by [`SigViewerApp::window_flags`] name
+    windows: Vec<String>,
+    viz_tab: String,
+}
+
+impl Session {
+    /// Saved on exit and restored at startup, next to the config
+    pub fn default_path() -> PathBuf {
+        gui_config_path().with_file_name("session.json")
+    }
+
+    fn read(path: &Path) -> Result<Self> {
+        let contents = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let value: serde_json::Value = serde_json::from_str(&contents).with_context(|| format!("{} isn't valid JSON", path.display()))?;
+        if value.get("format").and_then(|f| f.as_str()) != Some(SESSION_FORMAT) {
+            anyhow::bail!("{} isn't a Sig Viewer session", path.display());
+        }
+        let session: Session = serde_json::from_value(value)?;
+        if session.version > SESSION_VERSION {
+            anyhow::bail!("{} is session version {}; this release reads up to version {}", path.display(), session.version, SESSION_VERSION);
+        }
+        Ok(session)
+    }
+}
+
+/// Which way the session file dialog goes
+pub enum SessionTransfer {
+    Save,
+    Open,
+}
+
+const VIZ_TABS: [(VizTab, &str); 4] = [
+    (VizTab::Parameters, "parameters"),
+    (VizTab::Spectrogram, "spectrogram"),
+    (VizTab::TimeDomain, "time_domain"),
+    (VizTab::Constellation, "constellation"),
+];
+
+// handle saving the session to a file and resuming it
+impl SigViewerApp {
+    /// The windows a session records, by name. Windows holding edits or
+    /// results of their own (editors, detectors) start closed.
+    fn window_flags(&mut self) -> [(&'static str, &mut bool); 11] {
+        [
+            ("visualize", &mut self.show_visualization_dialog),
+            ("columns", &mut self.show_column_selector),
+            ("cast", &mut self.show_cast_dialog),
+            ("links", &mut self.show_link_settings),
+            ("sensor_profiles", &mut self.show_sensor_profiles),
+            ("watchlists", &mut self.show_watchlist_panel),
+            ("activity", &mut self.show_activity_window),
+            ("dashboard", &mut self.show_dashboard),
+            ("noise_trend", &mut self.show_noise_trend),
+            ("map", &mut self.show_map_window),
+            ("jobs", &mut self.show_jobs_panel),
+        ]
+    }
+
+    fn capture_session(&mut self) -> Session {
+        let selected_file = self.selected_row_data.as_ref().and_then(|row| row.get("meta_filename")).cloned();
+        let mut hidden_columns: Vec<String> = self.hidden_columns.iter().cloned().collect();
+        hidden_columns.sort();
+        Session {
+            format: SESSION_FORMAT.to_string(),
+            version: SESSION_VERSION,
+            saved_at: Utc::now().to_rfc3339(),
+            path: if self.dataset.is_some() { self.directory_path.clone() } else { String::new() },
+            other_roots: self.other_roots.clone(),
+            granularity: self.granularity,
+            verify_checksums: self.verify_checksums,
+            estimate_noise_floor: self.estimate_noise_floor,
+            compute_metrics: self.compute_metrics,
+            annotation_extra_columns: self.annotation_extra_columns,
+            lenient_parsing: self.lenient_parsing,
+            watch_directory: self.watch_directory,
+            preview_size: self.loaded_sample,
+            casts: self.column_casts.iter().map(|(column, target)| format!("{}={}", column, target.name())).collect(),
+            filters: self
+                .column_filters
+                .iter()
+                .filter(|(_, text)| !text.trim().is_empty())
+                .map(|(column, text)| (column.clone(), text.clone()))
+                .collect(),
+            sort: self.sort.clone(),
+            hidden_columns,
+            selected_row: self.selected_row,
+            selected_file,
+            windows: self.window_flags().into_iter().filter(|(_, open)| **open).map(|(name, _)| name.to_string()).collect(),
+            viz_tab: VIZ_TABS.iter().find(|(tab, _)| *tab == self.viz_tab).map_or("parameters", |(_, name)| name).to_string(),
+        }
+    }
+
+    pub(crate) fn save_session_to(&mut self, path: &Path) -> Result<()> {
+        let session = self.capture_session();
+        std::fs::write(path, serde_json::to_string_pretty(&session)?).with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Save to the default session file when the window closes. Closing
+    /// before anything was loaded keeps the previous session.
+    pub(crate) fn autosave_session(&mut self, ctx: &egui::Context) {
+        if self.dataset.is_none() || !ctx.input(|i| i.viewport().close_requested()) {
+            return;
+        }
+        if let Err(e) = self.save_session_to(&Session::default_path()) {
+            warn!("Failed to save the session: {:#}", e);
+        }
+    }
+
+    /// Resume the session saved on exit, if there is one
+    pub(crate) fn restore_last_session(&mut self) {
+        let path = Session::default_path();
+        if path.exists() {
+            self.open_session(&path);
+        }
+    }
+
+    /// Load the session's dataset with its options; the table and windows
+    /// are restored once it's in
+    pub(crate) fn open_session(&mut self, path: &Path) {
+        let session = match Session::read(path) {
+            Ok(session) => session,
+            Err(e) => {
+                self.error_message = Some(format!("Failed to open session: {:#}", e));
+                return;
+            }
+        };
+        self.granularity = session.granularity;
+        self.verify_checksums = session.verify_checksums;
+        self.estimate_noise_floor = session.estimate_noise_floor;
+        self.compute_metrics = session.compute_metrics;
+        self.annotation_extra_columns = session.annotation_extra_columns;
+        self.lenient_parsing = session.lenient_parsing;
+        self.watch_directory = session.watch_directory;
+        self.preview_sample = session.preview_size.is_some();
+        if let Some(n) = session.preview_size {
+            self.preview_size = n;
+        }
+        self.column_casts = session.casts.iter().filter_map(|spec| parse_cast_spec(spec).ok()).collect();
+        self.other_roots_text = session.other_roots.join("\n");
+        if session.path.is_empty() {
+            self.restore_session_view(session);
+            return;
+        }
+        let dataset_path = session.path.clone();
+        self.load_dataset(&dataset_path);
+        match self.dataset_load.as_mut() {
+            Some(load) => load.then_restore = Some(session),
+            None => self.error_message = Some(format!("Failed to open session: couldn't load {}", dataset_path)),
+        }
+    }
+
+    /// Filters, sort, hidden columns, selection and windows of a session,
+    /// over the dataset it loaded
+    pub(crate) fn restore_session_view(&mut self, session: Session) {
+        let mut missing = Vec::new();
+        if let Some(ref dataset) = self.dataset {
+            for (column, text) in &session.filters {
+                if dataset.column(column).is_ok() {
+                    self.column_filters.insert(column.clone(), text.clone());
+                } else {
+                    missing.push(column.as_str());
+                }
+            }
+            self.sort = session.sort.clone().filter(|sort| dataset.column(&sort.column).is_ok());
+        }
+        self.apply_filters();
+        self.hidden_columns = session.hidden_columns.iter().cloned().collect();
+        self.invalidate_cache();
+
+        let row = session.selected_file.as_deref().and_then(|name| {
+            let names = self.filtered_dataset.as_ref()?.column("meta_filename").ok()?.str().ok()?.clone();
+            let at = |row: usize| names.get(row) == Some(name);
+            session.selected_row.filter(|row| at(*row)).or_else(|| (0..names.len()).find(|row| at(*row)))
+        });
+        if let Some(row) = row {
+            self.select_row(row);
+        }
+        let windows = session.windows.clone();
+        for (name, open) in self.window_flags() {
+            *open = windows.iter().any(|window| window == name);
+        }
+        // Views are opened for a selected row only
+        self.show_visualization_dialog &= self.selected_row.is_some();
+        self.viz_tab = VIZ_TABS.iter().find(|(_, name)| *name == session.viz_tab).map_or(VizTab::Parameters, |(tab, _)| *tab);
+        if !missing.is_empty() {
+            self.status_message = format!("Restored session; no column {} to filter", missing.join(", "));
+        } else if !session.path.is_empty() {
+            self.status_message = format!("Restored session from {}", session.saved_at);
+        }
+    }
+
+    pub(crate) fn open_session_dialog(&mut self, transfer: SessionTransfer) {
+        let start = Some(PathBuf::from(&self.directory_path)).filter(|path| path.is_dir());
+        let mut dialog = match transfer {
+            SessionTransfer::Save => egui_file::FileDialog::save_file(start)
+                .title("Save Session")
+                .default_filename("sig_viewer_session.json"),
+            SessionTransfer::Open => egui_file::FileDialog::open_file(start).title("Open Session"),
+        };
+        dialog.open();
+        self.session_dialog = Some((transfer, dialog));
+    }
+
+    pub(crate) fn render_session_dialog(&mut self, ctx: &egui::Context) {
+        let Some((_, ref mut dialog)) = self.session_dialog else {
+            return;
+        };
+        if dialog.show(ctx).selected() {
+            let path = dialog.path().map(|path| path.to_path_buf());
+            if let (Some((transfer, _)), Some(path)) = (self.session_dialog.take(), path) {
+                match transfer {
+                    SessionTransfer::Save => match self.save_session_to(&path) {
+                        Ok(()) => self.status_message = format!("Saved session to {}", path.display()),
+                        Err(e) => self.error_message = Some(format!("Failed to save session: {:#}", e)),
+                    },
+                    SessionTransfer::Open => self.open_session(&path),
+                }
+            }
+        } else if !dialog.visible() {
+            self.session_dialog = None;
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{AppConfig, SigViewerApp};
+use anyhow::{Context, Result};
+use chrono::Utc;
+use eframe::egui;
+use serde::{Deserialize, Serialize};
+use sig_viewer::parser::sigmf::set_read_only;
+use std::path::{Path, PathBuf};
+
+/// Marks a JSON file as a settings bundle, so importing some other JSON file
+/// fails with a clear message instead of resetting everything to defaults
+const BUNDLE_FORMAT: &str = "sig_viewer_settings";
+const BUNDLE_VERSION: u32 = 1;
+
+/// Everything in [`AppConfig`] in one file, for handing a standard setup to
+/// other analysts. Whatever is stored in the config travels with it.
+#[derive(Serialize, Deserialize)]
+struct SettingsBundle {
+    format: String,
+    version: u32,
+    /// RFC 3339
+    exported_at: String,
+    tool_version: String,
+    settings: AppConfig,
+}
+
+/// Serialize the shareable part of `config` (see [`keep_local_settings`])
+fn export_settings(config: &AppConfig, path: &Path) -> Result<()> {
+    let mut settings: AppConfig = serde_json::from_value(serde_json::to_value(config)?)?;
+    keep_local_settings(&mut settings, &AppConfig::default());
+    let bundle = SettingsBundle {
+        format: BUNDLE_FORMAT.to_string(),
+        version: BUNDLE_VERSION,
+        exported_at: Utc::now().to_rfc3339(),
+        tool_version: env!("CARGO_PKG_VERSION").to_string(),
+        settings,
+    };
+    std::fs::write(path, serde_json::to_string_pretty(&bundle)?).with_context(|| format!("Failed to write {}", path.display()))
+}
+
+fn read_settings(path: &Path) -> Result<AppConfig> {
+    let contents = std::fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let value: serde_json::Value = serde_json::from_str(&contents).with_context(|| format!("{} isn't valid JSON", path.display()))?;
+    if value.get("format").and_then(|f| f.as_str()) != Some(BUNDLE_FORMAT) {
+        anyhow::bail!("{} isn't a Sig Viewer settings export", path.display());
+    }
+    let bundle: SettingsBundle = serde_json::from_value(value)?;
+    if bundle.version > BUNDLE_VERSION {
+        anyhow::bail!(
+            "{} is settings version {} from Sig Viewer {}; this release reads up to version {}",
+            path.display(),
+            bundle.version,
+            bundle.tool_version,
+            BUNDLE_VERSION
+        );
+    }
+    Ok(bundle.settings)
+}
+
+/// Machine-specific state isn't part of a shared setup: the bundle carries
+/// `from`'s values for these, i.e. the defaults on export and the analyst's
+/// own on import
+fn keep_local_settings(settings: &mut AppConfig, from: &AppConfig) {
+    settings.last_directory = from.last_directory.clone();
+    settings.recent_directories = from.recent_directories.clone();
+    settings.favorite_directories = from.favorite_directories.clone();
+    settings.window_size = from.window_size;
+}
+
+/// Counts of what a bundle sets, for the status bar
+fn describe_settings(settings: &AppConfig) -> String {
+    format!(
+        "{} sensor profiles, {} record links, {} watchlists, {} data roots, {} dashboard panels",
+        settings.sensor_profiles.len(),
+        settings.link_templates.len(),
+        settings.watchlists.len(),
+        settings.data_roots.len(),
+        settings.dashboard.panels.len(),
+    )
+}
+
+/// Which way the settings file dialog goes
+pub enum SettingsTransfer {
+    Export,
+    Import,
+}
+
+// handle exporting and importing the app settings as one file
+impl SigViewerApp {
+    pub(crate) fn open_settings_dialog(&mut self, transfer: SettingsTransfer) {
+        let start = Some(PathBuf::from(&self.directory_path)).filter(|path| path.is_dir());
+        let mut dialog = match transfer {
+            SettingsTransfer::Export => egui_file::FileDialog::save_file(start)
+                .title("Export Settings")
+                .default_filename("sig_viewer_settings.json"),
+            SettingsTransfer::Import => egui_file::FileDialog::open_file(start).title("Import Settings"),
+        };
+        dialog.open();
+        self.settings_dialog = Some((transfer, dialog));
+    }
+
+    pub(crate) fn render_settings_dialog(&mut self, ctx: &egui::Context) {
+        let Some((_, ref mut dialog)) = self.settings_dialog else {
+            return;
+        };
+        if dialog.show(ctx).selected() {
+            let path = dialog.path().map(|path| path.to_path_buf());
+            if let (Some((transfer, _)), Some(path)) = (self.settings_dialog.take(), path) {
+                match transfer {
+                    SettingsTransfer::Export => self.export_settings_to(&path),
+                    SettingsTransfer::Import => self.import_settings_from(&path, ctx),
+                }
+            }
+        } else if !dialog.visible() {
+            self.settings_dialog = None;
+        }
+    }
+
+    fn export_settings_to(&mut self, path: &Path) {
+        self.save_config();
+        match export_settings(&self.config, path) {
+            Ok(()) => self.status_message = format!("Exported settings to {}", path.display()),
+            Err(e) => self.error_message = Some(format!("Failed to export settings: {:#}", e)),
+        }
+    }
+
+    /// Replace the settings with a bundle's and apply them straight away
+    fn import_settings_from(&mut self, path: &Path, ctx: &egui::Context) {
+        let mut settings = match read_settings(path) {
+            Ok(settings) => settings,
+            Err(e) => {
+                self.error_message = Some(format!("Failed to import settings: {:#}", e));
+                return;
+            }
+        };
+        keep_local_settings(&mut settings, &self.config);
+        self.config = settings;
+        self.use_dark_theme = self.config.use_dark_theme;
+        ctx.set_visuals(if self.use_dark_theme { egui::Visuals::dark() } else { egui::Visuals::light() });
+        self.hidden_columns = self.config.hidden_columns.clone();
+        self.data_roots_text = self.config.data_roots.join("\n");
+        // Read-only can only be switched on while running, like at startup
+        if self.config.read_only && !self.read_only {
+            self.read_only = true;
+            set_read_only(true);
+        }
+        self.apply_data_roots();
+        self.reload_watchlists();
+        self.invalidate_cache();
+        self.save_config();
+        self.status_message = format!("Imported settings from {}: {}", path.display(), describe_settings(&self.config));
+    }
+}
@@ -0,0 +1,176 @@
+use crate::{format_cell_value, SigViewerApp};
+use eframe::egui;
+use polars::prelude::*;
+use sig_viewer::data_ops::{default_similarity_columns, find_similar, SimilarOptions, SimilarRow};
+
+/// State of the Similar Rows window: the row searched from and the ranking,
+/// recomputed whenever an option changes
+pub struct SimilarSearch {
+    /// Row of the filtered dataset the ranking is for
+    reference: usize,
+    reference_name: String,
+    /// Height of the filtered dataset and filter hash when searched, to tell
+    /// when `reference` and the result rows no longer point at the same rows
+    searched_in: (usize, u64),
+    columns: Vec<String>,
+    standardize: bool,
+    other_recordings_only: bool,
+    limit: usize,
+    results: Option<Result<Vec<SimilarRow>, String>>,
+}
+
+// handle finding rows similar to the selected one
+impl SigViewerApp {
+    pub(crate) fn open_similar_search(&mut self) {
+        let (Some(row), Some(df)) = (self.selected_row, self.filtered_dataset.as_ref()) else {
+            return;
+        };
+        let columns: Vec<String> = if self.config.similarity_columns.is_empty() {
+            default_similarity_columns(df)
+        } else {
+            self.config.similarity_columns.iter().filter(|name| df.column(name).is_ok()).cloned().collect()
+        };
+        let previous = self.similar_search.take();
+        self.similar_search = Some(SimilarSearch {
+            reference: row,
+            reference_name: meta_filename(df, row),
+            searched_in: (df.height(), self.last_filter_hash),
+            columns,
+            standardize: previous.as_ref().is_none_or(|s| s.standardize),
+            other_recordings_only: previous.as_ref().is_none_or(|s| s.other_recordings_only),
+            limit: previous.as_ref().map_or(20, |s| s.limit),
+            results: None,
+        });
+    }
+
+    pub(crate) fn render_similar_search(&mut self, ctx: &egui::Context) {
+        let Some(mut search) = self.similar_search.take() else {
+            return;
+        };
+        let Some(df) = self.filtered_dataset.clone() else {
+            return;
+        };
+        let stale = search.searched_in != (df.height(), self.last_filter_hash) || meta_filename(&df, search.reference) != search.reference_name;
+        if search.results.is_none() && !stale {
+            let options = SimilarOptions {
+                columns: search.columns.clone(),
+                standardize: search.standardize,
+                other_recordings_only: search.other_recordings_only,
+                limit: search.limit,
+            };
+            search.results = Some(find_similar(&df, search.reference, &options).map_err(|e| e.to_string()));
+        }
+        let numeric_columns: Vec<String> = df
+            .get_columns()
+            .iter()
+            .filter(|column| column.dtype().is_numeric())
+            .map(|column| column.name().to_string())
+            .collect();
+
+        let mut open = true;
+        let mut columns_changed = false;
+        let mut search_again = false;
+        let mut clicked_row = None;
+        egui::Window::new("Similar Rows")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([800.0, 500.0])
+            .show(ctx, |ui| {
+                ui.label(format!("Nearest to row {}: {}", search.reference + 1, search.reference_name));
+                ui.collapsing(format!("Features ({} selected)", search.columns.len()), |ui| {
+                    egui::ScrollArea::vertical().max_height(160.0).show(ui, |ui| {
+                        for name in &numeric_columns {
+                            let mut selected = search.columns.contains(name);
+                            if ui.checkbox(&mut selected, name).changed() {
+                                if selected {
+                                    search.columns.push(name.clone());
+                                } else {
+                                    search.columns.retain(|c| c != name);
+                                }
+                                columns_changed = true;
+                            }
+                        }
+                    });
+                });
+                let mut changed = columns_changed;
+                ui.horizontal(|ui| {
+                    ui.label("Show");
+                    changed |= ui.add(egui::DragValue::new(&mut search.limit).range(1..=1000)).changed();
+                    ui.label("rows");
+                    changed |= ui.checkbox(&mut search.other_recordings_only, "Other recordings only").changed();
+                    changed |= ui
+                        .checkbox(&mut search.standardize, "Standardize")
+                        .on_hover_text("Scale each feature to zero mean and unit variance, so a frequency in Hz doesn't drown out a probability")
+                        .changed();
+                });
+                if changed {
+                    search.results = None;
+                }
+                ui.separator();
+
+                if stale {
+                    ui.label("The table changed since this search.");
+                    if ui.add_enabled(self.selected_row.is_some(), egui::Button::new("Search from the selected row")).clicked() {
+                        search_again = true;
+                    }
+                    return;
+                }
+                let results = match search.results {
+                    Some(Ok(ref results)) => results,
+                    Some(Err(ref e)) => {
+                        ui.colored_label(egui::Color32::RED, format!("Cannot rank rows: {}", e));
+                        return;
+                    }
+                    None => return,
+                };
+                if results.is_empty() {
+                    ui.label("No other rows have every feature");
+                    return;
+                }
+                egui::ScrollArea::both().show(ui, |ui| {
+                    egui::Grid::new("similar_rows").striped(true).show(ui, |ui| {
+                        ui.strong("Row");
+                        ui.strong("Distance");
+                        ui.strong("meta_filename");
+                        for name in &search.columns {
+                            ui.strong(name);
+                        }
+                        ui.end_row();
+                        for result in results {
+                            let selected = self.selected_rows.contains(&result.row);
+                            if ui.selectable_label(selected, (result.row + 1).to_string()).on_hover_text("Select this row").clicked() {
+                                clicked_row = Some(result.row);
+                            }
+                            ui.label(format!("{:.3}", result.distance));
+                            ui.label(meta_filename(&df, result.row));
+                            for name in &search.columns {
+                                ui.label(df.column(name).map(|column| format_cell_value(column, result.row, !self.config.raw_values)).unwrap_or_default());
+                            }
+                            ui.end_row();
+                        }
+                    });
+                });
+            });
+
+        if columns_changed {
+            self.config.similarity_columns = search.columns.clone();
+            self.save_config();
+        }
+        if open {
+            self.similar_search = Some(search);
+        }
+        if search_again {
+            self.open_similar_search();
+        }
+        if let Some(row) = clicked_row {
+            self.select_row(row);
+        }
+    }
+}
+
+fn meta_filename(df: &DataFrame, row: usize) -> String {
+    df.column("meta_filename")
+        .ok()
+        .and_then(|column| column.str().ok()?.get(row).map(str::to_string))
+        .unwrap_or_default()
+}
@@ -0,0 +1,57 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use polars::prelude::*;
+use serde::{Deserialize, Serialize};
+
+/// Active table sort
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct SortState {
+    pub column: String,
+    pub descending: bool,
+}
+
+// handle sorting the table by column
+impl SigViewerApp {
+    /// Sort the (filtered) rows lazily, so it's part of the same query as the filters
+    pub(crate) fn apply_sort(&self, lf: LazyFrame) -> LazyFrame {
+        match self.sort {
+            Some(ref sort) => lf.sort(
+                [sort.column.as_str()],
+                SortMultipleOptions::default()
+                    .with_order_descending(sort.descending)
+                    .with_nulls_last(true)
+                    .with_maintain_order(true),
+            ),
+            None => lf,
+        }
+    }
+
+    /// Header click: ascending, then descending, then back to file order
+    pub(crate) fn toggle_sort(&mut self, column: &str) {
+        self.sort = match self.sort.take() {
+            Some(sort) if sort.column == column && !sort.descending => Some(SortState {
+                column: sort.column,
+                descending: true,
+            }),
+            Some(sort) if sort.column == column => None,
+            _ => Some(SortState {
+                column: column.to_string(),
+                descending: false,
+            }),
+        };
+        // The sort is part of the filter hash, so this re-runs the query
+        self.apply_filters();
+        self.clear_selection();
+    }
+
+    /// Clickable column header with a sort indicator
+    pub(crate) fn sort_header(&self, ui: &mut egui::Ui, column: &str) -> egui::Response {
+        let indicator = match self.sort {
+            Some(ref sort) if sort.column == column && sort.descending => " ⬇",
+            Some(ref sort) if sort.column == column => " ⬆",
+            _ => "",
+        };
+        ui.add(egui::Label::new(egui::RichText::new(format!("{}{}", column, indicator)).strong()).sense(egui::Sense::click()))
+            .on_hover_text("Click to sort, right-click for column stats")
+    }
+}
@@ -0,0 +1,552 @@
+use super::cursors::MeasurementCursors;
+use super::plot::{draw_x_axis, draw_y_axis, format_frequency, format_seconds, SampleWindow};
+use super::sample_source::SampleSource;
+use super::RecordingView;
+use eframe::egui;
+use sig_viewer::dsp::{ChannelParams, ChannelizedReader, CHANNEL_OVERSAMPLE};
+use sig_viewer::parser::sigmf::SegmentedRecording;
+use sig_viewer::parser::{SampleReader, SigMFParser};
+use sig_viewer::viz::{Colormap, DeepLink, Spectrogram, SpectrogramParams};
+use std::path::{Path, PathBuf};
+
+const FFT_SIZES: [usize; 6] = [256, 512, 1024, 2048, 4096, 8192];
+const AXIS_MARGIN_LEFT: f32 = 80.0;
+const AXIS_MARGIN_BOTTOM: f32 = 20.0;
+
+/// Zoomable/pannable waterfall of a single recording. Time runs downwards,
+/// frequency left to right. Zooming in time recomputes the spectrogram for
+/// the visible range so detail isn't limited by the initial stride.
+/// Segments of a split capture are shown as one continuous recording.
+/// Drilling down swaps the recording for a channel of it: the visible
+/// frequency span mixed to baseband and decimated, so a narrow signal gets
+/// the whole FFT.
+pub struct SpectrogramView {
+    meta_path: PathBuf,
+    source: SampleSource,
+    sample_rate: f64,
+    center_freq: f64,
+    total_samples: u64,
+    fft_size: usize,
+    colormap: Colormap,
+    min_db: f32,
+    max_db: f32,
+    range_initialized: bool,
+    // Visible time window
+    window: SampleWindow,
+    // Visible frequency window as a fraction of the captured band
+    freq_lo: f32,
+    freq_hi: f32,
+    spectrogram: Option<Spectrogram>,
+    texture: Option<egui::TextureHandle>,
+    texture_dirty: bool,
+    needs_compute: bool,
+    error: Option<String>,
+    // Set while drilled down; sample_rate, center_freq, total_samples and
+    // window then describe the channel
+    channel: Option<Channel>,
+    // At absolute frequencies and seconds, so they survive drilling down
+    cursors: MeasurementCursors,
+}
+
+/// What a [`SpectrogramView`] shows: seconds from the start of the
+/// (possibly stitched) recording and frequencies in Hz
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ViewSpan {
+    pub start_s: f64,
+    pub end_s: f64,
+    pub freq_lower: f64,
+    pub freq_upper: f64,
+}
+
+/// A drilled-down channel and the full-band view to return to
+struct Channel {
+    params: ChannelParams,
+    decimation: usize,
+    full_rate: f64,
+    full_center: f64,
+    full_total: u64,
+    full_window: SampleWindow,
+    full_span: (f32, f32),
+}
+
+impl RecordingView for SpectrogramView {
+    fn open(meta_path: &Path, stitched: Option<SegmentedRecording>) -> anyhow::Result<Self> {
+        let parser = SigMFParser::from_meta_file(meta_path)?;
+        let source = SampleSource::new(meta_path, stitched);
+        let total_samples = source.reader()?.num_samples();
+        // Start on the selected file; the rest of a stitched capture is a scroll away
+        let mut window = SampleWindow::new(total_samples, 1024 * 8);
+        window.start = source.offset_of(meta_path);
+        window.len = parser.num_samples().max(window.min_len).min(total_samples - window.start);
+
+        Ok(SpectrogramView {
+            meta_path: meta_path.to_path_buf(),
+            source,
+            sample_rate: parser.sample_rate(),
+            center_freq: parser.center_frequency().unwrap_or(0.0),
+            total_samples,
+            fft_size: 1024,
+            colormap: Colormap::Viridis,
+            min_db: -100.0,
+            max_db: 0.0,
+            range_initialized: false,
+            window,
+            freq_lo: 0.0,
+            freq_hi: 1.0,
+            spectrogram: None,
+            texture: None,
+            texture_dirty: false,
+            needs_compute: true,
+            error: None,
+            channel: None,
+            cursors: MeasurementCursors::new(true),
+        })
+    }
+
+    fn meta_path(&self) -> &Path {
+        &self.meta_path
+    }
+
+    fn show(&mut self, ui: &mut egui::Ui) {
+        self.show_view(ui);
+    }
+}
+
+impl SpectrogramView {
+    fn compute(&mut self, max_rows: usize) {
+        self.needs_compute = false;
+        let params = SpectrogramParams {
+            fft_size: self.fft_size,
+            max_rows,
+        };
+        let channel = self.channel.as_ref().map(|channel| (channel.full_rate, channel.params.clone()));
+        let result = self.source.reader().and_then(|reader| {
+            let mut reader: Box<dyn SampleReader> = match channel {
+                Some((rate, params)) => Box::new(ChannelizedReader::new(reader, rate, &params)?),
+                None => reader,
+            };
+            Spectrogram::compute(&mut *reader, self.window.start, self.window.len, &params)
+        });
+
+        match result {
+            Ok(spectrogram) => {
+                if !self.range_initialized {
+                    self.min_db = spectrogram.percentile_db(5.0);
+                    self.max_db = spectrogram.percentile_db(99.9);
+                    self.range_initialized = true;
+                }
+                self.spectrogram = Some(spectrogram);
+                self.texture_dirty = true;
+                self.error = None;
+            }
+            Err(e) => {
+                self.error = Some(format!("Failed to compute spectrogram: {}", e));
+            }
+        }
+    }
+
+    /// Show the sample range and frequency span of `link` (parts it leaves
+    /// out keep the full extent)
+    pub fn goto(&mut self, link: &DeepLink) {
+        self.back_to_full_band();
+        self.reset_view();
+        if let Some(start) = link.sample_start {
+            let start = (start + self.source.offset_of(&link.file)).min(self.total_samples.saturating_sub(self.window.min_len));
+            let len = link.sample_count.unwrap_or(self.total_samples - start);
+            self.window.start = start;
+            self.window.len = len.clamp(self.window.min_len, self.total_samples - start);
+        }
+        let freq_to_frac = |freq: f64| (((freq - self.center_freq) / self.sample_rate) + 0.5).clamp(0.0, 1.0) as f32;
+        let lo = link.freq_lower.map_or(0.0, freq_to_frac);
+        let hi = link.freq_upper.map_or(1.0, freq_to_frac);
+        if hi > lo {
+            self.freq_lo = lo;
+            self.freq_hi = hi;
+            // Applies the minimum span
+            self.zoom_freq(1.0, 0.5);
+        }
+    }
+
+    /// Link to what's currently on screen, relative to the file it starts
+    /// in. A drilled-down view links to the same span of the full recording.
+    pub fn deep_link(&self) -> DeepLink {
+        let (file, start) = self.source.locate(self.full_sample(self.window.start));
+        DeepLink {
+            sample_start: Some(start),
+            sample_count: Some(self.full_sample(self.window.len)),
+            freq_lower: Some(self.frac_to_freq(self.freq_lo)),
+            freq_upper: Some(self.frac_to_freq(self.freq_hi)),
+            ..DeepLink::new(file)
+        }
+    }
+
+    pub fn view_span(&self) -> ViewSpan {
+        ViewSpan {
+            start_s: self.window.start as f64 / self.sample_rate,
+            end_s: self.window.end() as f64 / self.sample_rate,
+            freq_lower: self.frac_to_freq(self.freq_lo),
+            freq_upper: self.frac_to_freq(self.freq_hi),
+        }
+    }
+
+    /// Show `span`, or as much of it as this recording covers
+    pub fn set_view_span(&mut self, span: &ViewSpan) {
+        let last_start = self.total_samples.saturating_sub(self.window.min_len);
+        let start = ((span.start_s * self.sample_rate).max(0.0) as u64).min(last_start);
+        let len = (((span.end_s - span.start_s) * self.sample_rate).max(0.0) as u64).clamp(self.window.min_len, self.total_samples - start);
+        if (start, len) != (self.window.start, self.window.len) {
+            self.window.start = start;
+            self.window.len = len;
+            self.needs_compute = true;
+        }
+        let freq_to_frac = |freq: f64| (((freq - self.center_freq) / self.sample_rate) + 0.5).clamp(0.0, 1.0) as f32;
+        let (lo, hi) = (freq_to_frac(span.freq_lower), freq_to_frac(span.freq_upper));
+        if hi > lo {
+            self.freq_lo = lo;
+            self.freq_hi = hi;
+            self.zoom_freq(1.0, 0.5);
+        }
+    }
+
+    /// Channelize the visible frequency span, keeping the visible time range
+    fn drill_down(&mut self) {
+        let (lower, upper) = (self.frac_to_freq(self.freq_lo), self.frac_to_freq(self.freq_hi));
+        let params = ChannelParams {
+            offset_hz: (lower + upper) / 2.0 - self.center_freq,
+            bandwidth_hz: upper - lower,
+            decimation: None,
+        };
+        let decimation = match params.resolve_decimation(self.sample_rate) {
+            Ok(decimation) => decimation,
+            Err(e) => {
+                self.error = Some(format!("Cannot drill down: {}", e));
+                return;
+            }
+        };
+        let total = self.total_samples.div_ceil(decimation as u64);
+        let mut window = SampleWindow::new(total, self.fft_size as u64 * 8);
+        window.len = (self.window.len / decimation as u64).max(window.min_len).min(total);
+        window.start = (self.window.start / decimation as u64).min(total - window.len);
+        self.channel = Some(Channel {
+            params: params.clone(),
+            decimation,
+            full_rate: self.sample_rate,
+            full_center: self.center_freq,
+            full_total: self.total_samples,
+            full_window: std::mem::replace(&mut self.window, window),
+            full_span: (self.freq_lo, self.freq_hi),
+        });
+        self.sample_rate /= decimation as f64;
+        self.center_freq += params.offset_hz;
+        self.total_samples = total;
+        // Frame the channel's passband within the wider decimated band
+        let half = (params.bandwidth_hz / 2.0 / self.sample_rate) as f32;
+        self.freq_lo = (0.5 - half).max(0.0);
+        self.freq_hi = (0.5 + half).min(1.0);
+        self.zoom_freq(1.0, 0.5);
+        self.range_initialized = false;
+        self.needs_compute = true;
+        self.error = None;
+    }
+
+    fn back_to_full_band(&mut self) {
+        let Some(channel) = self.channel.take() else {
+            return;
+        };
+        self.sample_rate = channel.full_rate;
+        self.center_freq = channel.full_center;
+        self.total_samples = channel.full_total;
+        self.window = channel.full_window;
+        (self.freq_lo, self.freq_hi) = channel.full_span;
+        self.range_initialized = false;
+        self.needs_compute = true;
+    }
+
+    /// A sample index or count of the view in samples of the recording
+    fn full_sample(&self, sample: u64) -> u64 {
+        sample * self.channel.as_ref().map_or(1, |channel| channel.decimation as u64)
+    }
+
+    fn reset_view(&mut self) {
+        self.window.reset();
+        self.freq_lo = 0.0;
+        self.freq_hi = 1.0;
+        self.needs_compute = true;
+    }
+
+    fn zoom_freq(&mut self, factor: f32, anchor_frac: f32) {
+        let span = self.freq_hi - self.freq_lo;
+        let anchor = self.freq_lo + anchor_frac * span;
+        let new_span = (span * factor).clamp(1.0 / self.fft_size as f32 * 8.0, 1.0);
+        self.freq_lo = (anchor - anchor_frac * new_span).clamp(0.0, 1.0 - new_span);
+        self.freq_hi = self.freq_lo + new_span;
+    }
+
+    fn pan_freq(&mut self, delta_frac: f32) {
+        let span = self.freq_hi - self.freq_lo;
+        self.freq_lo = (self.freq_lo + delta_frac).clamp(0.0, 1.0 - span);
+        self.freq_hi = self.freq_lo + span;
+    }
+
+    fn frac_to_freq(&self, frac: f32) -> f64 {
+        self.center_freq + (frac as f64 - 0.5) * self.sample_rate
+    }
+
+    /// Power of the computed spectrogram at a fraction of the band and a
+    /// sample of the view, if it covers them
+    fn power_at(&self, freq_frac: f32, sample: f64) -> Option<f32> {
+        let s = self.spectrogram.as_ref()?;
+        let row = ((sample - s.start_sample as f64) / s.row_step as f64).floor();
+        let bin = (freq_frac * s.fft_size as f32).floor();
+        if row < 0.0 || bin < 0.0 || bin as usize >= s.fft_size {
+            return None;
+        }
+        s.power_db.get(row as usize * s.fft_size + bin as usize).copied()
+    }
+
+    /// Each cursor's frequency, time and power, then the differences
+    fn cursor_readout(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        let mut measured = Vec::new();
+        for (idx, point) in self.cursors.points().iter().enumerate() {
+            let Some((freq, seconds)) = *point else {
+                continue;
+            };
+            let freq_frac = ((freq - self.center_freq) / self.sample_rate + 0.5) as f32;
+            let power = self.power_at(freq_frac, seconds * self.sample_rate);
+            let power_text = power.map_or_else(|| "—".to_string(), |db| format!("{:.1} dB", db));
+            lines.push(format!("{}: {}  {}  {}", idx + 1, format_frequency(freq), format_seconds(seconds), power_text));
+            measured.push((freq, seconds, power));
+        }
+        if let [(f1, t1, p1), (f2, t2, p2)] = measured[..] {
+            let mut delta = format!("Δf {}  Δt {}", format_frequency(f2 - f1), format_seconds(t2 - t1));
+            if let (Some(p1), Some(p2)) = (p1, p2) {
+                delta.push_str(&format!("  ΔP {:+.1} dB", p2 - p1));
+            }
+            lines.push(delta);
+            if t2 != t1 {
+                lines.push(format!("1/Δt {}", format_frequency(1.0 / (t2 - t1).abs())));
+            }
+        }
+        lines
+    }
+
+    fn update_texture(&mut self, ctx: &egui::Context) {
+        let Some(ref spectrogram) = self.spectrogram else {
+            return;
+        };
+        let pixels: Vec<u8> = spectrogram
+            .to_rgb(self.colormap, self.min_db, self.max_db)
+            .into_iter()
+            .flatten()
+            .collect();
+        let image = egui::ColorImage::from_rgb([spectrogram.fft_size, spectrogram.num_rows], &pixels);
+        match self.texture {
+            Some(ref mut texture) => texture.set(image, egui::TextureOptions::LINEAR),
+            None => {
+                self.texture = Some(ctx.load_texture("spectrogram", image, egui::TextureOptions::LINEAR));
+            }
+        }
+        self.texture_dirty = false;
+    }
+
+    fn show_view(&mut self, ui: &mut egui::Ui) {
+        // Controls
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_label("FFT")
+                .selected_text(self.fft_size.to_string())
+                .show_ui(ui, |ui| {
+                    for size in FFT_SIZES {
+                        if ui.selectable_value(&mut self.fft_size, size, size.to_string()).changed() {
+                            self.window.min_len = (size as u64 * 8).min(self.total_samples);
+                            self.needs_compute = true;
+                        }
+                    }
+                });
+            egui::ComboBox::from_label("Colormap")
+                .selected_text(self.colormap.name())
+                .show_ui(ui, |ui| {
+                    for colormap in Colormap::ALL {
+                        if ui.selectable_value(&mut self.colormap, colormap, colormap.name()).changed() {
+                            self.texture_dirty = true;
+                        }
+                    }
+                });
+        });
+        ui.horizontal(|ui| {
+            ui.label("Range (dB):");
+            let min_changed = ui.add(egui::DragValue::new(&mut self.min_db).speed(0.5).prefix("min ")).changed();
+            let max_changed = ui.add(egui::DragValue::new(&mut self.max_db).speed(0.5).prefix("max ")).changed();
+            if min_changed || max_changed {
+                self.texture_dirty = true;
+            }
+            if ui.button("Auto Range").clicked() {
+                if let Some(ref spectrogram) = self.spectrogram {
+                    self.min_db = spectrogram.percentile_db(5.0);
+                    self.max_db = spectrogram.percentile_db(99.9);
+                    self.texture_dirty = true;
+                }
+            }
+            if ui.button("Reset View").clicked() {
+                self.reset_view();
+            }
+            if ui.button("Copy Link").on_hover_text("Copy a sigviewer:// link to this view").clicked() {
+                ui.ctx().copy_text(self.deep_link().to_uri());
+            }
+            self.cursors.controls(ui);
+        });
+        ui.horizontal(|ui| match self.channel {
+            None => {
+                // Drilling into half the band or more wouldn't decimate
+                let narrow = (self.freq_hi - self.freq_lo) as f64 * CHANNEL_OVERSAMPLE <= 0.5;
+                let button = ui
+                    .add_enabled(narrow, egui::Button::new("Drill Down"))
+                    .on_hover_text("Mix the visible frequency span to baseband, filter and decimate it, to see a narrow signal in more detail")
+                    .on_disabled_hover_text("Zoom in on frequency (shift+scroll) to less than 40% of the band first");
+                if button.clicked() {
+                    self.drill_down();
+                }
+            }
+            Some(ref channel) => {
+                ui.label(format!(
+                    "Channel at {}, {} wide, decimated by {} to {} samples/s",
+                    format_frequency(self.center_freq),
+                    format_frequency(channel.params.bandwidth_hz),
+                    channel.decimation,
+                    format_frequency(self.sample_rate)
+                ));
+                if ui.button("Back to Full Band").clicked() {
+                    self.back_to_full_band();
+                }
+            }
+        });
+        ui.label("Drag to pan, scroll to zoom time, shift+scroll to zoom frequency, double-click to reset");
+        if let Some(stitched) = self.source.describe(self.full_sample(self.window.start)) {
+            ui.label(stitched);
+        }
+
+        if let Some(ref error) = self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
+        let size = egui::vec2(ui.available_width(), ui.available_height().max(300.0));
+        let (response, painter) = ui.allocate_painter(size, egui::Sense::click_and_drag());
+        let plot_rect = egui::Rect::from_min_max(
+            response.rect.min + egui::vec2(AXIS_MARGIN_LEFT, 0.0),
+            response.rect.max - egui::vec2(0.0, AXIS_MARGIN_BOTTOM),
+        );
+
+        // Cursors are placed at (frequency, seconds)
+        let (freq_lo, freq_span) = (self.freq_lo as f64, (self.freq_hi - self.freq_lo) as f64);
+        let (center_freq, sample_rate) = (self.center_freq, self.sample_rate);
+        let (window_start, window_len) = (self.window.start as f64, self.window.len as f64);
+        let to_screen = |freq: f64, seconds: f64| {
+            let frac = (freq - center_freq) / sample_rate + 0.5;
+            egui::pos2(
+                plot_rect.left() + ((frac - freq_lo) / freq_span) as f32 * plot_rect.width(),
+                plot_rect.top() + ((seconds * sample_rate - window_start) / window_len) as f32 * plot_rect.height(),
+            )
+        };
+        let from_screen = |pos: egui::Pos2| {
+            let frac = freq_lo + ((pos.x - plot_rect.left()) / plot_rect.width()) as f64 * freq_span;
+            let sample = window_start + ((pos.y - plot_rect.top()) / plot_rect.height()) as f64 * window_len;
+            (center_freq + (frac - 0.5) * sample_rate, sample / sample_rate)
+        };
+
+        // Interaction
+        let cursors_took_pointer = self.cursors.interact(&response, plot_rect, to_screen, from_screen);
+        if response.double_clicked() {
+            self.reset_view();
+        }
+        if response.dragged() && !cursors_took_pointer {
+            let delta = response.drag_delta();
+            self.pan_freq(-delta.x / plot_rect.width() * (self.freq_hi - self.freq_lo));
+            let delta_samples = -(delta.y / plot_rect.height()) as f64 * self.window.len as f64;
+            if self.window.pan(delta_samples) {
+                self.needs_compute = true;
+            }
+        }
+        if let Some(hover) = response.hover_pos() {
+            let (scroll, shift) = ui.input(|i| (i.raw_scroll_delta, i.modifiers.shift));
+            let scroll = scroll.x + scroll.y;
+            if scroll != 0.0 && plot_rect.contains(hover) {
+                let factor = (-scroll * 0.002).exp();
+                if shift {
+                    let anchor = (hover.x - plot_rect.left()) / plot_rect.width();
+                    self.zoom_freq(factor, anchor);
+                } else {
+                    let anchor = ((hover.y - plot_rect.top()) / plot_rect.height()) as f64;
+                    if self.window.zoom(factor as f64, anchor) {
+                        self.needs_compute = true;
+                    }
+                }
+            }
+        }
+
+        // Recompute once the user lets go, so dragging stays responsive
+        if self.needs_compute && !response.dragged() && self.total_samples > 0 {
+            self.compute(plot_rect.height().max(64.0) as usize);
+        }
+        if self.texture_dirty {
+            self.update_texture(ui.ctx());
+        }
+
+        painter.rect_filled(plot_rect, 0.0, egui::Color32::BLACK);
+        if let (Some(texture), Some(spectrogram)) = (&self.texture, &self.spectrogram) {
+            // The texture covers the range it was computed for, which may lag
+            // the view while a drag is in progress
+            let covered_start = spectrogram.start_sample as f64;
+            let covered_len = (spectrogram.num_rows as u64 * spectrogram.row_step).max(1) as f64;
+            let v0 = (self.window.start as f64 - covered_start) / covered_len;
+            let v1 = v0 + self.window.len as f64 / covered_len;
+            let uv = egui::Rect::from_min_max(
+                egui::pos2(self.freq_lo, v0 as f32),
+                egui::pos2(self.freq_hi, v1 as f32),
+            );
+            painter.image(texture.id(), plot_rect, uv, egui::Color32::WHITE);
+        }
+
+        if self.channel.is_none() {
+            self.source.draw_boundaries(&painter, plot_rect, &self.window, true);
+        }
+
+        // Axes
+        let t0 = self.window.start as f64 / self.sample_rate;
+        let t1 = self.window.end() as f64 / self.sample_rate;
+        draw_y_axis(&painter, plot_rect, t0, t1, true, format_seconds);
+        draw_x_axis(
+            &painter,
+            plot_rect,
+            self.frac_to_freq(self.freq_lo),
+            self.frac_to_freq(self.freq_hi),
+            format_frequency,
+        );
+
+        self.cursors.draw(&painter, plot_rect, to_screen);
+        self.cursors.draw_readout(&painter, plot_rect, &self.cursor_readout());
+
+        // Hover readout
+        if let Some(hover) = response.hover_pos().filter(|p| plot_rect.contains(*p)) {
+            let x_frac = (hover.x - plot_rect.left()) / plot_rect.width();
+            let y_frac = ((hover.y - plot_rect.top()) / plot_rect.height()) as f64;
+            let freq_frac = self.freq_lo + x_frac * (self.freq_hi - self.freq_lo);
+            let sample = self.window.start as f64 + y_frac * self.window.len as f64;
+            let power = self.power_at(freq_frac, sample);
+            let mut text = format!(
+                "{}\n{}",
+                format_frequency(self.frac_to_freq(freq_frac)),
+                format_seconds(sample / self.sample_rate)
+            );
+            if let Some(power) = power {
+                text.push_str(&format!("\n{:.1} dB", power));
+            }
+            painter.text(
+                plot_rect.left_top() + egui::vec2(6.0, 6.0),
+                egui::Align2::LEFT_TOP,
+                text,
+                egui::FontId::monospace(11.0),
+                egui::Color32::WHITE,
+            );
+        }
+    }
+}
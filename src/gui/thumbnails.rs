@@ -0,0 +1,230 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use polars::prelude::*;
+use sig_viewer::parser::sigmf::SigMFDataset;
+use sig_viewer::viz::{default_thumbnail_dir, read_thumbnail, write_thumbnail, ThumbnailParams, THUMBNAIL_COLUMN};
+use sig_viewer::workers::{Priority, TaskHandle};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// Size of a thumbnail in the table's Thumbnail column; rows are 20 px high
+const TABLE_THUMBNAIL_SIZE: egui::Vec2 = egui::vec2(40.0, 18.0);
+/// Gallery cells: the thumbnail at its rendered size with the name below
+const GALLERY_THUMBNAIL_SIZE: egui::Vec2 = egui::vec2(128.0, 64.0);
+const GALLERY_CELL_SIZE: egui::Vec2 = egui::vec2(136.0, 88.0);
+
+/// Thumbnails of the loaded dataset's recordings by `meta_filename`, loaded
+/// (and rendered into the cache if missing) on the worker pool as the table
+/// or gallery first shows them
+#[derive(Default)]
+pub struct ThumbnailCache {
+    entries: HashMap<String, ThumbnailEntry>,
+    pending: Vec<(String, TaskHandle<Pixels>)>,
+}
+
+/// A decoded thumbnail: width, height and RGB bytes
+type Pixels = (usize, usize, Vec<u8>);
+
+pub enum ThumbnailEntry {
+    Loading,
+    Ready(egui::TextureHandle),
+    Failed(String),
+}
+
+impl ThumbnailCache {
+    pub fn get(&self, meta_filename: &str) -> Option<&ThumbnailEntry> {
+        self.entries.get(meta_filename)
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.pending.clear();
+    }
+}
+
+/// A recording shown in the gallery: its name, first row in the filtered
+/// table and the dataset's `thumbnail_path`, if it has one
+struct GalleryItem {
+    meta_filename: String,
+    row: usize,
+    thumbnail_path: Option<String>,
+}
+
+// handle spectrogram thumbnails in the table and the gallery
+impl SigViewerApp {
+    /// Turn finished thumbnail loads into textures
+    pub(crate) fn poll_thumbnails(&mut self, ctx: &egui::Context) {
+        let cache = &mut self.thumbnails;
+        let entries = &mut cache.entries;
+        cache.pending.retain(|(name, task)| match task.try_take() {
+            None => true,
+            Some(Ok((width, height, rgb))) => {
+                let image = egui::ColorImage::from_rgb([width, height], &rgb);
+                let texture = ctx.load_texture(format!("thumbnail:{}", name), image, egui::TextureOptions::LINEAR);
+                entries.insert(name.clone(), ThumbnailEntry::Ready(texture));
+                false
+            }
+            Some(Err(e)) => {
+                entries.insert(name.clone(), ThumbnailEntry::Failed(format!("{:#}", e)));
+                false
+            }
+        });
+        if !cache.pending.is_empty() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(200));
+        }
+    }
+
+    /// Start loading the thumbnails of recordings not asked for yet, from
+    /// the dataset's `thumbnail_path` where it has one, else from the cache
+    /// `thumbnails` writes to, rendering any missing
+    pub(crate) fn request_thumbnails(&mut self, recordings: Vec<(String, Option<String>)>) {
+        let root = Path::new(&self.directory_path);
+        let root = if root.is_file() { root.parent().unwrap_or(Path::new(".")) } else { root };
+        for (meta_filename, thumbnail_path) in recordings {
+            if self.thumbnails.entries.contains_key(&meta_filename) {
+                continue;
+            }
+            let thumbnail_path = thumbnail_path.map(PathBuf::from).filter(|path| path.is_file());
+            let root = root.to_path_buf();
+            let name = meta_filename.clone();
+            let task = self.workers.submit(&format!("Thumbnail: {}", meta_filename), Priority::Normal, move |_| {
+                let path = match thumbnail_path {
+                    Some(path) => path,
+                    None => {
+                        let direct = root.join(&name);
+                        let meta_path = if direct.exists() { Some(direct) } else { SigMFDataset::find_meta_file(&root, &name) };
+                        let meta_path = meta_path.ok_or_else(|| anyhow::anyhow!("Could not locate {} under {}", name, root.display()))?;
+                        write_thumbnail(&meta_path, &default_thumbnail_dir(), &ThumbnailParams::default(), false)?.0
+                    }
+                };
+                read_thumbnail(&path)
+            });
+            self.thumbnails.entries.insert(meta_filename.clone(), ThumbnailEntry::Loading);
+            self.thumbnails.pending.push((meta_filename, task));
+        }
+    }
+
+    /// The Thumbnail column's cell for a recording; missing thumbnails are
+    /// added to `wanted` for [`Self::request_thumbnails`]
+    pub(crate) fn thumbnail_cell(&self, ui: &mut egui::Ui, meta_filename: &str, thumbnail_path: Option<&str>, wanted: &mut Vec<(String, Option<String>)>) -> egui::Response {
+        match self.thumbnails.get(meta_filename) {
+            Some(ThumbnailEntry::Ready(texture)) => ui
+                .add(egui::Image::new(texture).fit_to_exact_size(TABLE_THUMBNAIL_SIZE).sense(egui::Sense::click()))
+                .on_hover_ui(|ui| {
+                    ui.add(egui::Image::new(texture).fit_to_exact_size(GALLERY_THUMBNAIL_SIZE * 2.0));
+                }),
+            Some(ThumbnailEntry::Failed(error)) => ui.weak("—").on_hover_text(error),
+            Some(ThumbnailEntry::Loading) => ui.spinner(),
+            None => {
+                wanted.push((meta_filename.to_string(), thumbnail_path.map(str::to_string)));
+                ui.spinner()
+            }
+        }
+    }
+
+    pub(crate) fn render_thumbnail_gallery(&mut self, ctx: &egui::Context) {
+        if !self.show_thumbnail_gallery {
+            return;
+        }
+        let Some(df) = self.filtered_dataset.clone() else {
+            return;
+        };
+        let items = gallery_items(&df);
+        let loading = self.thumbnails.pending.len();
+
+        let mut open = true;
+        let mut wanted = Vec::new();
+        let mut clicked_row = None;
+        egui::Window::new("Thumbnail Gallery")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([720.0, 520.0])
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} recordings", items.len()));
+                    if ui
+                        .button("Render All")
+                        .on_hover_text("Load or render every recording's thumbnail now rather than as they scroll into view")
+                        .clicked()
+                    {
+                        wanted.extend(items.iter().map(|item| (item.meta_filename.clone(), item.thumbnail_path.clone())));
+                    }
+                    if loading > 0 {
+                        ui.spinner();
+                        ui.label(format!("{} loading", loading));
+                    }
+                });
+                ui.separator();
+                if items.is_empty() {
+                    ui.label("No recordings in the filtered table");
+                    return;
+                }
+                let per_row = ((ui.available_width() / GALLERY_CELL_SIZE.x) as usize).max(1);
+                let num_rows = items.len().div_ceil(per_row);
+                egui::ScrollArea::vertical().auto_shrink([false, false]).show_rows(ui, GALLERY_CELL_SIZE.y, num_rows, |ui, rows| {
+                    for grid_row in rows {
+                        ui.horizontal(|ui| {
+                            for item in items.iter().skip(grid_row * per_row).take(per_row) {
+                                let selected = self.selected_rows.contains(&item.row);
+                                let (rect, response) = ui.allocate_exact_size(GALLERY_CELL_SIZE, egui::Sense::click());
+                                if selected {
+                                    ui.painter().rect_filled(rect, 4.0, ui.visuals().selection.bg_fill);
+                                }
+                                let image_rect = egui::Rect::from_min_size(rect.min + egui::vec2(4.0, 4.0), GALLERY_THUMBNAIL_SIZE);
+                                match self.thumbnails.get(&item.meta_filename) {
+                                    Some(ThumbnailEntry::Ready(texture)) => {
+                                        egui::Image::new(texture).fit_to_exact_size(GALLERY_THUMBNAIL_SIZE).paint_at(ui, image_rect);
+                                    }
+                                    entry => {
+                                        if entry.is_none() {
+                                            wanted.push((item.meta_filename.clone(), item.thumbnail_path.clone()));
+                                        }
+                                        let text = if matches!(entry, Some(ThumbnailEntry::Failed(_))) { "no thumbnail" } else { "loading…" };
+                                        ui.painter().rect_filled(image_rect, 0.0, ui.visuals().extreme_bg_color);
+                                        ui.painter().text(image_rect.center(), egui::Align2::CENTER_CENTER, text, egui::FontId::proportional(11.0), ui.visuals().weak_text_color());
+                                    }
+                                }
+                                let name = egui::RichText::new(&item.meta_filename).small();
+                                let label_rect = egui::Rect::from_min_max(egui::pos2(rect.left() + 4.0, image_rect.bottom() + 2.0), rect.right_bottom());
+                                ui.put(label_rect, egui::Label::new(name).truncate());
+                                let hover = match self.thumbnails.get(&item.meta_filename) {
+                                    Some(ThumbnailEntry::Failed(error)) => format!("{}\n{}", item.meta_filename, error),
+                                    _ => format!("{}\nClick to select its first row", item.meta_filename),
+                                };
+                                if response.on_hover_text(hover).clicked() {
+                                    clicked_row = Some(item.row);
+                                }
+                            }
+                        });
+                    }
+                });
+            });
+
+        self.request_thumbnails(wanted);
+        if let Some(row) = clicked_row {
+            self.select_row(row);
+        }
+        self.show_thumbnail_gallery = open;
+    }
+}
+
+/// Each recording of the filtered table once, in table order
+fn gallery_items(df: &DataFrame) -> Vec<GalleryItem> {
+    let Some(names) = df.column("meta_filename").ok().and_then(|column| column.str().ok()) else {
+        return Vec::new();
+    };
+    let paths = df.column(THUMBNAIL_COLUMN).ok().and_then(|column| column.str().ok());
+    let mut seen = std::collections::HashSet::new();
+    let mut items = Vec::new();
+    for (row, name) in names.into_iter().enumerate() {
+        let Some(name) = name.filter(|name| seen.insert(name.to_string())) else {
+            continue;
+        };
+        items.push(GalleryItem {
+            meta_filename: name.to_string(),
+            row,
+            thumbnail_path: paths.and_then(|paths| paths.get(row)).map(str::to_string),
+        });
+    }
+    items
+}
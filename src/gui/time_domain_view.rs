@@ -0,0 +1,207 @@
+use super::plot::{draw_x_axis, draw_y_axis, format_seconds, SampleWindow};
+use super::sample_source::SampleSource;
+use super::RecordingView;
+use eframe::egui;
+use sig_viewer::parser::sigmf::SegmentedRecording;
+use sig_viewer::parser::SigMFParser;
+use sig_viewer::viz::TimeDomainEnvelope;
+use std::path::{Path, PathBuf};
+
+const AXIS_MARGIN_LEFT: f32 = 60.0;
+const AXIS_MARGIN_BOTTOM: f32 = 20.0;
+const I_COLOR: egui::Color32 = egui::Color32::from_rgb(31, 119, 180);
+const Q_COLOR: egui::Color32 = egui::Color32::from_rgb(255, 127, 14);
+const MAG_COLOR: egui::Color32 = egui::Color32::from_rgb(44, 160, 44);
+
+/// I/Q amplitude and magnitude against time. Long ranges are drawn as a
+/// min/max envelope with one bucket per horizontal pixel.
+pub struct TimeDomainView {
+    meta_path: PathBuf,
+    source: SampleSource,
+    sample_rate: f64,
+    window: SampleWindow,
+    show_i: bool,
+    show_q: bool,
+    show_magnitude: bool,
+    envelope: Option<TimeDomainEnvelope>,
+    needs_compute: bool,
+    error: Option<String>,
+}
+
+impl RecordingView for TimeDomainView {
+    fn open(meta_path: &Path, stitched: Option<SegmentedRecording>) -> anyhow::Result<Self> {
+        let parser = SigMFParser::from_meta_file(meta_path)?;
+        let source = SampleSource::new(meta_path, stitched);
+        let total_samples = source.reader()?.num_samples();
+        let mut window = SampleWindow::new(total_samples, 16);
+        window.start = source.offset_of(meta_path);
+        window.len = parser.num_samples().max(window.min_len).min(total_samples - window.start);
+        Ok(TimeDomainView {
+            meta_path: meta_path.to_path_buf(),
+            source,
+            sample_rate: parser.sample_rate(),
+            window,
+            show_i: true,
+            show_q: true,
+            show_magnitude: false,
+            envelope: None,
+            needs_compute: true,
+            error: None,
+        })
+    }
+
+    fn meta_path(&self) -> &Path {
+        &self.meta_path
+    }
+
+    fn show(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.checkbox(&mut self.show_i, egui::RichText::new("I").color(I_COLOR));
+            ui.checkbox(&mut self.show_q, egui::RichText::new("Q").color(Q_COLOR));
+            ui.checkbox(&mut self.show_magnitude, egui::RichText::new("Magnitude").color(MAG_COLOR));
+            if ui.button("Reset View").clicked() {
+                self.window.reset();
+                self.needs_compute = true;
+            }
+            if let Some(ref envelope) = self.envelope {
+                ui.separator();
+                if envelope.samples_per_point > 1 {
+                    ui.label(format!("{} samples/point (min/max envelope)", envelope.samples_per_point));
+                } else {
+                    ui.label("Full resolution");
+                }
+                if envelope.clipped_samples > 0 {
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        format!("{} clipped samples in view", envelope.clipped_samples),
+                    );
+                }
+            }
+        });
+        ui.label("Drag to pan, scroll to zoom, double-click to reset");
+        if let Some(stitched) = self.source.describe(self.window.start) {
+            ui.label(stitched);
+        }
+
+        if let Some(ref error) = self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
+        let size = egui::vec2(ui.available_width(), ui.available_height().max(250.0));
+        let (response, painter) = ui.allocate_painter(size, egui::Sense::click_and_drag());
+        let plot_rect = egui::Rect::from_min_max(
+            response.rect.min + egui::vec2(AXIS_MARGIN_LEFT, 0.0),
+            response.rect.max - egui::vec2(0.0, AXIS_MARGIN_BOTTOM),
+        );
+
+        if response.double_clicked() {
+            self.window.reset();
+            self.needs_compute = true;
+        }
+        if response.dragged() {
+            let delta = -(response.drag_delta().x / plot_rect.width()) as f64 * self.window.len as f64;
+            if self.window.pan(delta) {
+                self.needs_compute = true;
+            }
+        }
+        if let Some(hover) = response.hover_pos().filter(|p| plot_rect.contains(*p)) {
+            let scroll = ui.input(|i| i.raw_scroll_delta);
+            let scroll = scroll.x + scroll.y;
+            if scroll != 0.0 {
+                let anchor = ((hover.x - plot_rect.left()) / plot_rect.width()) as f64;
+                if self.window.zoom((-scroll as f64 * 0.002).exp(), anchor) {
+                    self.needs_compute = true;
+                }
+            }
+        }
+
+        if self.needs_compute && !response.dragged() && self.window.total > 0 {
+            self.compute(plot_rect.width().max(1.0) as usize);
+        }
+
+        let visuals = ui.visuals();
+        painter.rect_filled(plot_rect, 0.0, visuals.extreme_bg_color);
+        painter.rect_stroke(
+            plot_rect,
+            0.0,
+            egui::Stroke::new(1.0, visuals.weak_text_color()),
+            egui::StrokeKind::Inside,
+        );
+
+        let peak = self.envelope.as_ref().map_or(1.0, |e| e.peak_amplitude()).max(1e-6) * 1.1;
+        let to_y = |value: f32| plot_rect.center().y - value / peak * plot_rect.height() / 2.0;
+        painter.hline(plot_rect.x_range(), to_y(0.0), egui::Stroke::new(1.0, visuals.weak_text_color()));
+
+        if let Some(ref envelope) = self.envelope {
+            let to_x = |point: usize| {
+                let sample = envelope.start_sample + point as u64 * envelope.samples_per_point;
+                let frac = (sample as f64 - self.window.start as f64) / self.window.len.max(1) as f64;
+                plot_rect.left() + frac as f32 * plot_rect.width()
+            };
+            let traces = [
+                (self.show_magnitude, &envelope.mag_min, &envelope.mag_max, MAG_COLOR),
+                (self.show_i, &envelope.i_min, &envelope.i_max, I_COLOR),
+                (self.show_q, &envelope.q_min, &envelope.q_max, Q_COLOR),
+            ];
+            let clipped = painter.with_clip_rect(plot_rect);
+            for (visible, mins, maxs, color) in traces {
+                if !visible {
+                    continue;
+                }
+                let stroke = egui::Stroke::new(1.0, color);
+                if envelope.samples_per_point == 1 {
+                    let points: Vec<egui::Pos2> = mins.iter()
+                        .enumerate()
+                        .map(|(i, v)| egui::pos2(to_x(i), to_y(*v)))
+                        .collect();
+                    clipped.add(egui::Shape::line(points, stroke));
+                } else {
+                    for (i, (lo, hi)) in mins.iter().zip(maxs.iter()).enumerate() {
+                        if lo.is_finite() && hi.is_finite() {
+                            let x = to_x(i);
+                            clipped.line_segment([egui::pos2(x, to_y(*lo)), egui::pos2(x, to_y(*hi))], stroke);
+                        }
+                    }
+                }
+            }
+        }
+
+        self.source.draw_boundaries(&painter, plot_rect, &self.window, false);
+        draw_y_axis(&painter, plot_rect, -peak as f64, peak as f64, false, |v| format!("{:.3}", v));
+        draw_x_axis(
+            &painter,
+            plot_rect,
+            self.window.start as f64 / self.sample_rate,
+            self.window.end() as f64 / self.sample_rate,
+            format_seconds,
+        );
+
+        if let Some(hover) = response.hover_pos().filter(|p| plot_rect.contains(*p)) {
+            let frac = ((hover.x - plot_rect.left()) / plot_rect.width()) as f64;
+            let sample = self.window.start as f64 + frac * self.window.len as f64;
+            painter.text(
+                plot_rect.left_top() + egui::vec2(6.0, 6.0),
+                egui::Align2::LEFT_TOP,
+                format!("sample {:.0}\n{}", sample, format_seconds(sample / self.sample_rate)),
+                egui::FontId::monospace(11.0),
+                visuals.text_color(),
+            );
+        }
+    }
+}
+
+impl TimeDomainView {
+    fn compute(&mut self, max_points: usize) {
+        self.needs_compute = false;
+        let result = self.source.reader().and_then(|mut reader| {
+            TimeDomainEnvelope::compute(&mut *reader, self.window.start, self.window.len, max_points)
+        });
+        match result {
+            Ok(envelope) => {
+                self.envelope = Some(envelope);
+                self.error = None;
+            }
+            Err(e) => self.error = Some(format!("Failed to read samples: {}", e)),
+        }
+    }
+}
@@ -0,0 +1,107 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use polars::prelude::*;
+use sig_viewer::data_ops::{apply_derived_columns, update_dataset, DirectoryWatcher, WatchUpdate, WATCH_SETTLE};
+use sig_viewer::parser::sigmf::DatasetOptions;
+use sig_viewer::workers::{Priority, TaskHandle};
+use std::path::PathBuf;
+
+/// Watch on the loaded directory, re-reading recordings as a pipeline
+/// writes them
+pub struct DirectoryWatch {
+    pub directory: String,
+    watcher: DirectoryWatcher,
+    /// Options the directory was loaded with, for the re-read rows
+    options: DatasetOptions,
+    task: Option<TaskHandle<(DataFrame, WatchUpdate)>>,
+}
+
+impl DirectoryWatch {
+    /// Start watching before the scan, so recordings written during it aren't missed
+    pub fn start(directory: &str, options: &DatasetOptions) -> anyhow::Result<Self> {
+        Ok(DirectoryWatch {
+            directory: directory.to_string(),
+            watcher: DirectoryWatcher::new(directory)?,
+            options: options.clone(),
+            task: None,
+        })
+    }
+}
+
+// handle watch mode: incremental updates of a directory being written to
+impl SigViewerApp {
+    /// Re-read changed recordings on a worker and merge them in. Called once per frame.
+    pub(crate) fn poll_directory_watch(&mut self, ctx: &egui::Context) {
+        let Some(watch) = self.directory_watch.as_mut() else {
+            return;
+        };
+        ctx.request_repaint_after(WATCH_SETTLE);
+
+        if let Some(result) = watch.task.as_ref().and_then(|task| task.try_take()) {
+            watch.task = None;
+            match result {
+                Ok((dataset, update)) => self.apply_watch_update(dataset, update),
+                Err(e) => self.error_message = Some(format!("Watch update failed: {}", e)),
+            }
+            return;
+        }
+        // One update at a time; changes meanwhile wait in the watcher
+        if watch.task.is_some() {
+            return;
+        }
+        let changed = watch.watcher.take_changed();
+        let Some(dataset) = self.dataset.clone().filter(|_| !changed.is_empty()) else {
+            return;
+        };
+        let options = watch.options.clone();
+        let profiles = self.config.sensor_profiles.clone();
+        let derived = self.derived_columns.clone();
+        let root = PathBuf::from(&watch.directory);
+        let name = format!("Update {} ({} files)", watch.directory, changed.len());
+        watch.task = Some(self.workers.submit(&name, Priority::High, move |_| {
+            // Re-read rows come back without the derived columns
+            let (dataset, update) = update_dataset(dataset, &root, &changed, &options, &profiles)?;
+            Ok((apply_derived_columns(dataset, &derived)?, update))
+        }));
+    }
+
+    fn apply_watch_update(&mut self, dataset: DataFrame, update: WatchUpdate) {
+        if let Some((path, e)) = update.errors.first() {
+            self.status_message = format!("Watching: skipped {} ({})", path.display(), e);
+        }
+        if update.is_empty() {
+            return;
+        }
+        let dataset = self.apply_session_casts(dataset);
+        for name in dataset.get_column_names() {
+            self.column_filters.entry(name.to_string()).or_default();
+        }
+        // Re-read rows move to the end, so row numbers no longer match
+        if update.updated_files + update.removed_files > 0 {
+            self.clear_selection();
+        }
+        if let Some(ref mut provenance) = self.provenance {
+            provenance.num_rows = dataset.height();
+        }
+        self.dataset = Some(dataset);
+        self.force_refilter();
+        self.status_message = format!("Watching: {}", update.summary());
+    }
+
+    /// Menu bar indicator while watching, with a button to stop
+    pub(crate) fn render_watch_indicator(&mut self, ui: &mut egui::Ui) {
+        let Some(watch) = self.directory_watch.as_ref() else {
+            return;
+        };
+        let busy = watch.task.is_some();
+        let response = ui
+            .button(if busy { "👁 Updating…" } else { "👁 Watching" })
+            .on_hover_text(format!("New and changed recordings in {} are added as they're written. Click to stop.", watch.directory));
+        if response.clicked() {
+            if let Some(task) = self.directory_watch.take().and_then(|watch| watch.task) {
+                self.workers.cancel(task.id());
+            }
+            self.status_message = "Stopped watching".to_string();
+        }
+    }
+}
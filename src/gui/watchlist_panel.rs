@@ -0,0 +1,188 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use sig_viewer::data_ops::{watch_matches, WatchEntry, Watchlist};
+use std::collections::BTreeSet;
+use std::path::PathBuf;
+
+/// Background tint for table rows matching a watchlist entry
+pub(crate) const WATCH_HIGHLIGHT: egui::Color32 = egui::Color32::from_rgba_premultiplied(60, 45, 0, 40);
+
+// handle signal-of-interest watchlists: loading, matching and the panel
+impl SigViewerApp {
+    /// (Re)read every watchlist CSV in the config. Files that fail to load
+    /// stay listed, without entries, so they can be fixed and reloaded.
+    pub(crate) fn reload_watchlists(&mut self) {
+        self.watchlist_errors.clear();
+        self.watchlists = self
+            .config
+            .watchlists
+            .iter()
+            .map(|path| {
+                Watchlist::from_csv(path).unwrap_or_else(|e| {
+                    self.watchlist_errors.insert(PathBuf::from(path), e.to_string());
+                    Watchlist {
+                        name: path.clone(),
+                        path: PathBuf::from(path),
+                        entries: Vec::new(),
+                    }
+                })
+            })
+            .collect();
+        self.watch_matches = None;
+    }
+
+    /// Match the filtered dataset against the watchlists, if not done since
+    /// the last change to either
+    pub(crate) fn ensure_watch_matches(&mut self) {
+        if self.watch_matches.is_some() {
+            return;
+        }
+        let matches = match self.filtered_dataset.as_ref() {
+            Some(dataset) if !self.watchlists.is_empty() => watch_matches(dataset, &self.watchlists).unwrap_or_else(|e| {
+                self.status_message = format!("Watchlist matching failed: {}", e);
+                Vec::new()
+            }),
+            _ => Vec::new(),
+        };
+        self.watch_matches = Some(matches);
+    }
+
+    /// `list/entry` names a filtered row matches, for hover text
+    pub(crate) fn watch_label(&self, row: usize) -> Option<String> {
+        let matches = self.watch_matches.as_ref()?.get(row).filter(|m| !m.is_empty())?;
+        let names: Vec<String> = matches
+            .iter()
+            .map(|&(list, entry)| format!("{}/{}", self.watchlists[list].name, self.watchlists[list].entries[entry].name))
+            .collect();
+        Some(names.join(", "))
+    }
+
+    pub(crate) fn render_watchlist_panel(&mut self, ctx: &egui::Context) {
+        if !self.show_watchlist_panel {
+            return;
+        }
+        self.ensure_watch_matches();
+
+        // Matching rows per (list, entry), and how many rows match anything
+        let mut entry_rows: Vec<Vec<BTreeSet<usize>>> =
+            self.watchlists.iter().map(|list| vec![BTreeSet::new(); list.entries.len()]).collect();
+        let mut matched_rows = 0;
+        for (row, matches) in self.watch_matches.iter().flatten().enumerate() {
+            matched_rows += !matches.is_empty() as usize;
+            for &(list, entry) in matches {
+                entry_rows[list][entry].insert(row);
+            }
+        }
+        let total_rows = self.filtered_dataset.as_ref().map(|df| df.height()).unwrap_or(0);
+
+        let mut open = true;
+        let mut select = None;
+        let mut remove = None;
+        let mut add = false;
+        let mut reload = false;
+        egui::Window::new("Watchlists")
+            .open(&mut open)
+            .resizable(true)
+            .default_size([620.0, 360.0])
+            .show(ctx, |ui| {
+                ui.label("Signals of interest from CSV files with name, freq_min_hz/freq_max_hz (or _mhz), label and uuid columns. Rows matching every criterion an entry gives are highlighted in the table.");
+                ui.label(format!("{} of {} filtered rows match", matched_rows, total_rows));
+                ui.separator();
+
+                egui::ScrollArea::vertical().max_height(260.0).show(ui, |ui| {
+                    if self.watchlists.is_empty() {
+                        ui.label("No watchlists loaded");
+                    }
+                    for (list_idx, list) in self.watchlists.iter().enumerate() {
+                        let listed: usize = entry_rows[list_idx].iter().map(BTreeSet::len).sum();
+                        egui::CollapsingHeader::new(format!("{} ({} entries, {} matches)", list.name, list.entries.len(), listed))
+                            .id_salt(("watchlist", list_idx))
+                            .default_open(true)
+                            .show(ui, |ui| {
+                                ui.horizontal(|ui| {
+                                    ui.weak(list.path.display().to_string());
+                                    if ui.small_button("Remove").clicked() {
+                                        remove = Some(list_idx);
+                                    }
+                                });
+                                if let Some(error) = self.watchlist_errors.get(&list.path) {
+                                    ui.colored_label(egui::Color32::RED, error);
+                                }
+                                egui::Grid::new(("watch_entries", list_idx)).num_columns(4).striped(true).show(ui, |ui| {
+                                    for heading in ["Name", "Criteria", "Matches"] {
+                                        ui.strong(heading);
+                                    }
+                                    ui.end_row();
+                                    for (entry_idx, entry) in list.entries.iter().enumerate() {
+                                        let rows = &entry_rows[list_idx][entry_idx];
+                                        ui.label(&entry.name);
+                                        ui.label(describe_entry(entry));
+                                        ui.label(rows.len().to_string());
+                                        if ui.add_enabled(!rows.is_empty(), egui::Button::new("Select")).clicked() {
+                                            select = Some(rows.clone());
+                                        }
+                                        ui.end_row();
+                                    }
+                                });
+                            });
+                    }
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.add(egui::TextEdit::singleline(&mut self.watchlist_path_input).hint_text("/path/to/watchlist.csv").desired_width(320.0));
+                    if ui.add_enabled(!self.watchlist_path_input.trim().is_empty(), egui::Button::new("Add")).clicked() {
+                        add = true;
+                    }
+                    if ui.button("Reload All").on_hover_text("Re-read the CSV files after editing them").clicked() {
+                        reload = true;
+                    }
+                });
+            });
+
+        if let Some(rows) = select {
+            if let Some(&first) = rows.first() {
+                self.selected_rows = rows;
+                self.focus_row(first);
+            }
+        }
+        if let Some(idx) = remove {
+            self.config.watchlists.remove(idx);
+            reload = true;
+        }
+        if add {
+            let path = self.watchlist_path_input.trim().to_string();
+            if !self.config.watchlists.contains(&path) {
+                self.config.watchlists.push(path);
+            }
+            self.watchlist_path_input.clear();
+            reload = true;
+        }
+        if reload {
+            self.save_config();
+            self.reload_watchlists();
+        }
+        if !open {
+            self.show_watchlist_panel = false;
+        }
+    }
+}
+
+/// Criteria of an entry for display, e.g. `433.05–434.79 MHz, label lora`
+fn describe_entry(entry: &WatchEntry) -> String {
+    let mut parts = Vec::new();
+    if let Some((min, max)) = entry.freq_range_hz {
+        parts.push(match (min.is_finite(), max.is_finite()) {
+            (true, true) => format!("{:.3}–{:.3} MHz", min / 1e6, max / 1e6),
+            (true, false) => format!("≥ {:.3} MHz", min / 1e6),
+            _ => format!("≤ {:.3} MHz", max / 1e6),
+        });
+    }
+    if let Some(label) = &entry.label {
+        parts.push(format!("label {}", label));
+    }
+    if let Some(uuid) = &entry.uuid {
+        parts.push(format!("uuid {}", uuid));
+    }
+    parts.join(", ")
+}
@@ -0,0 +1,109 @@
+use crate::SigViewerApp;
+use eframe::egui;
+use serde_json::Value;
+use sig_viewer::parser::sigmf::{write_back, Journal, MetaSnapshot, WriteConflict, WriteOutcome};
+
+// handle metadata write-back and conflict prompts
+impl SigViewerApp {
+    /// Write edited metadata back to disk. If the file changed since
+    /// `snapshot` was taken (e.g. the recorder appended annotations), the
+    /// write is held back and the user is asked how to resolve it. Every write
+    /// is journaled so it can be undone with `sig_viewer_cli rollback`. Returns the
+    /// new snapshot when the write went through immediately.
+    pub(crate) fn save_meta(&mut self, snapshot: &MetaSnapshot, value: &Value) -> Option<MetaSnapshot> {
+        if self.read_only {
+            self.error_message = Some("Read-only mode: changes can't be saved".to_string());
+            return None;
+        }
+        let journal = Journal::begin(&format!("GUI edit of {}", snapshot.path.display()));
+        match write_back(snapshot, value, &journal) {
+            Ok(WriteOutcome::Written(written)) => {
+                self.status_message = format!("Saved {}", written.path.display());
+                Some(written)
+            }
+            Ok(WriteOutcome::Conflict(conflict)) => {
+                self.write_conflict = Some(conflict);
+                None
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to save {}: {}", snapshot.path.display(), e));
+                None
+            }
+        }
+    }
+
+    pub(crate) fn render_write_conflict(&mut self, ctx: &egui::Context) {
+        let Some(ref conflict) = self.write_conflict else {
+            return;
+        };
+        let mut resolution = None;
+        egui::Window::new("File Changed on Disk")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(format!(
+                    "{} was modified by another process since you opened it.",
+                    conflict.base.path.display()
+                ));
+                if let Some(modified) = conflict.theirs.modified {
+                    let age = modified.elapsed().map(|d| d.as_secs()).unwrap_or(0);
+                    ui.label(format!("Last modified {}s ago.", age));
+                }
+                ui.add_space(6.0);
+                match conflict.merged {
+                    Ok(_) => {
+                        ui.label("Your edits don't overlap with the new changes and can be merged.");
+                    }
+                    Err(ref paths) => {
+                        ui.colored_label(egui::Color32::YELLOW, "Both sides changed these fields:");
+                        egui::ScrollArea::vertical().max_height(150.0).show(ui, |ui| {
+                            for path in paths {
+                                ui.monospace(path);
+                            }
+                        });
+                    }
+                }
+                ui.add_space(6.0);
+                ui.horizontal(|ui| {
+                    if ui.add_enabled(conflict.merged.is_ok(), egui::Button::new("Merge and Save")).clicked() {
+                        resolution = Some(Resolution::Merge);
+                    }
+                    if ui.button("Overwrite with Mine").clicked() {
+                        resolution = Some(Resolution::Overwrite);
+                    }
+                    if ui.button("Discard My Changes").clicked() {
+                        resolution = Some(Resolution::Discard);
+                    }
+                });
+            });
+
+        if let Some(resolution) = resolution {
+            let conflict = *self.write_conflict.take().unwrap();
+            self.resolve_write_conflict(conflict, resolution);
+        }
+    }
+
+    fn resolve_write_conflict(&mut self, conflict: WriteConflict, resolution: Resolution) {
+        let value = match resolution {
+            Resolution::Merge => match conflict.merged {
+                Ok(merged) => merged,
+                Err(_) => return,
+            },
+            Resolution::Overwrite => conflict.ours,
+            Resolution::Discard => {
+                self.status_message = format!("Kept on-disk version of {}", conflict.theirs.path.display());
+                return;
+            }
+        };
+        // The on-disk version becomes the new base, so a further change
+        // during the prompt is caught again rather than overwritten
+        self.save_meta(&conflict.theirs, &value);
+    }
+}
+
+enum Resolution {
+    Merge,
+    Overwrite,
+    Discard,
+}
@@ -1,24 +1,118 @@
+mod gui;
+
 use eframe::egui;
+use gui::annotation_editor::AnnotationEditor;
+use gui::constellation_view::ConstellationView;
+use gui::dashboard::{DashboardConfig, PanelData};
+use gui::dataset_loader::{DatasetLoad, LoadedDataset};
+use gui::export::ExportDialog;
+use gui::global_editor::GlobalEditor;
+use gui::histogram_view::HistogramView;
+use gui::jobs_panel::PendingMeasurement;
+use gui::burst_detector::BurstDetector;
+use gui::cluster_view::ClusterView;
+use gui::labeling::LabelingSession;
+use gui::similar_search::SimilarSearch;
+use gui::thumbnails::ThumbnailCache;
+use gui::demodulator::Demodulator;
+use gui::detail_panel::DetailPanel;
+use gui::compare::RecordingComparison;
+use gui::group_by_view::GroupByView;
+use gui::scatter_view::ScatterView;
+use gui::session::SessionTransfer;
+use gui::settings_bundle::SettingsTransfer;
+use gui::sorting::SortState;
+use gui::links::LinkTemplate;
+use gui::map_view::MapViewport;
+use gui::multi_sensor_view::MultiSensorView;
+use gui::spectrogram_view::SpectrogramView;
+use gui::time_domain_view::TimeDomainView;
+use gui::watch_mode::DirectoryWatch;
+use gui::log_console::LogBuffer;
+use gui::RecordingView;
 use polars::prelude::*;
+use sig_viewer::data_ops::{
+    cast_columns, empty_columns, filter_geo, gui_config_path, humanize_value, parse_filters, ActivityHeatmap, CastTarget, ColumnStats, ContinuityReport, DerivedColumns, ExportFormat, FilterExpr, FilterPreset, GeoFilter, GeoPoints, NoiseTrend, Provenance,
+    SensorProfile, TimeBucket, Watchlist, CLUSTER_COLUMN, DEFAULT_CONTINUITY_TOLERANCE_S, DEFAULT_NOISE_STEP_DB, SUMMARY_SCHEMA_VERSION,
+};
+use sig_viewer::data_ops::schema::stamped_version;
 use sig_viewer::parser::SigMFDataset;
+use sig_viewer::parser::raw::{set_raw_iq_defaults, RawIqParams};
+use sig_viewer::parser::sigmf::{
+    set_data_roots, set_parse_mode, set_read_only, DataRoots, DatasetOptions, Granularity, ParseMode, ScanReport, SegmentedRecording, WriteConflict,
+    META_PATH_COLUMN, SOURCE_ROOT_COLUMN,
+};
+use sig_viewer::viz::{DeepLink, DEEP_LINK_SCHEME, THUMBNAIL_COLUMN};
+use sig_viewer::workers::WorkerPool;
 use anyhow::Result;
-use std::collections::{HashMap, HashSet};
-use std::path::PathBuf;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
+use tracing::{debug, info, warn};
+
+/// Example shown in an empty filter box
+fn filter_hint(dtype: &DataType) -> &'static str {
+    if matches!(dtype, DataType::Date | DataType::Datetime(..)) {
+        "2024-01-01..2024-02-01, >= 2024-01-01T12:00"
+    } else if dtype.is_numeric() {
+        ">= 10, 100e6..200e6"
+    } else if matches!(dtype, DataType::Boolean) {
+        "true / false"
+    } else {
+        "value, != x, contains:x, regex:^x"
+    }
+}
+
+/// Rows formatted on either side of the visible ones, so scrolling a little
+/// doesn't rebuild the table cache
+const TABLE_CACHE_MARGIN: usize = 200;
 
-#[derive(Debug, Clone)]
-enum FilterValue {
-    Text(String),
-    Range { min: String, max: String},
-    Boolean(String),
+const FILTER_SYNTAX_HELP: &str = "Filter syntax:\n\
+    value        equal to value\n\
+    > < >= <= != compare (numbers for numeric columns)\n\
+    lo..hi       inclusive range, e.g. 100e6..200e6\n\
+                 or for times 2024-01-01..2024-02-01 (through the end day)\n\
+    contains:x   text contains x\n\
+    regex:^x     text matches the regular expression";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VizTab {
+    Parameters,
+    Spectrogram,
+    TimeDomain,
+    Constellation,
 }
 
 
 
 
 fn main() -> eframe::Result<()> {
+    let log = LogBuffer::install();
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let read_only = args.iter().any(|arg| arg == "--read-only");
+    // Start empty instead of resuming the session saved on exit
+    let restore_session = !args.iter().any(|arg| arg == "--no-session");
+    // `--goto <link>`, or the bare link as passed by a sigviewer:// URI handler
+    let goto = args.iter()
+        .position(|arg| arg == "--goto")
+        .and_then(|i| args.get(i + 1))
+        .or_else(|| args.iter().find(|arg| arg.starts_with(&format!("{}://", DEEP_LINK_SCHEME))))
+        .cloned();
+    // `--data-root <dir|meta=data>`, repeatable, on top of the configured roots
+    let data_roots: Vec<String> = args.windows(2)
+        .filter(|pair| pair[0] == "--data-root")
+        .map(|pair| pair[1].clone())
+        .collect();
+    // `--sample-rate <hz>` / `--center-freq <hz>` for raw IQ files without a sidecar
+    let flag_value = |flag: &str| args.windows(2).find(|pair| pair[0] == flag).and_then(|pair| pair[1].parse::<f64>().ok());
+    set_raw_iq_defaults(RawIqParams {
+        sample_rate: flag_value("--sample-rate"),
+        center_freq: flag_value("--center-freq"),
+        ..Default::default()
+    });
     let options = eframe::NativeOptions {
         viewport: egui::ViewportBuilder::default()
             .with_inner_size([1200.0, 800.0])
@@ -33,27 +127,64 @@ fn main() -> eframe::Result<()> {
             // Set light theme
             cc.egui_ctx.set_visuals(egui::Visuals::light());
             
-            Ok(Box::new(SigViewerApp::new()))
+            Ok(Box::new(SigViewerApp::new(read_only, goto.as_deref(), data_roots, restore_session, log)))
         }),
     )
 }
 
+/// Persisted settings. File → Export Settings... shares all of it except
+/// the directories and window size, so team-wide setup belongs here.
 #[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
 struct AppConfig {
     last_directory: String,
+    /// Directories and dataset files loaded, most recent first
+    recent_directories: Vec<String>,
+    /// Pinned above the recent ones in File → Open Recent
+    favorite_directories: Vec<String>,
     use_dark_theme: bool,
     hidden_columns: HashSet<String>,
     window_size: Option<[f32; 2]>,
+    link_templates: Vec<LinkTemplate>,
+    dashboard: DashboardConfig,
+    /// Start in read-only mode even without `--read-only`
+    read_only: bool,
+    /// Don't auto-hide columns that are all null/zero/empty on load
+    show_empty_columns: bool,
+    /// Where else to look for data files, as for `--data-root`
+    data_roots: Vec<String>,
+    /// Per-sdr_handle calibration, viewer and color
+    sensor_profiles: Vec<SensorProfile>,
+    /// Watchlist CSV files, reloaded at startup
+    watchlists: Vec<String>,
+    /// Classes offered in the Labeling window, in key order
+    label_classes: Vec<String>,
+    /// Feature columns Find Similar ranks rows by; empty for the defaults
+    similarity_columns: Vec<String>,
+    /// Show the selected recording's full metadata beside the table
+    show_detail_panel: bool,
+    /// Named column filters, also read by `dataset --preset`
+    filter_presets: Vec<FilterPreset>,
+    /// Show the log console along the bottom of the window
+    show_log_console: bool,
+    /// Band map TOML for the band and channel columns; empty for the built-in bands
+    band_map: String,
+    /// TOML file of derived columns added to every loaded dataset; empty for none
+    derived_columns: String,
+    /// Show numbers as stored instead of in readable units (MHz, ms, MiB, dBm)
+    raw_values: bool,
+    /// Show a spectrogram thumbnail of each row's recording in the table
+    thumbnail_column: bool,
 }
 
 impl AppConfig {
     fn config_path() -> PathBuf {
-        let config_dir = dirs::config_dir()
-            .unwrap_or_else(|| PathBuf::from("."))
-            .join("sig_viewer");
-        
-        std::fs::create_dir_all(&config_dir).ok();
-        config_dir.join("config.json")
+        // Shared with the CLI, which reads the filter presets from it
+        let path = gui_config_path();
+        if let Some(config_dir) = path.parent() {
+            std::fs::create_dir_all(config_dir).ok();
+        }
+        path
     }
     
     fn load() -> Self {
@@ -81,21 +212,122 @@ struct SigViewerApp {
     filtered_dataset: Option<DataFrame>,
     directory_path: String,
     status_message: String,
-    column_filters: HashMap<String, FilterValue>,
+    column_filters: HashMap<String, String>, // Filter expression text per column
+    filter_errors: HashMap<String, String>,  // Columns whose filter text doesn't parse
     show_load_dialog: bool,
     error_message: Option<String>,
     file_dialog: egui_file::FileDialog,
+    export_dialog: Option<ExportDialog>,
+    annotation_editor: Option<AnnotationEditor>,
+    global_editor: Option<GlobalEditor>,
+    comparison: Option<RecordingComparison>,
+    multi_sensor_view: Option<MultiSensorView>,
+    provenance: Option<Provenance>, // Where the loaded dataset came from, carried into exports
+    sort: Option<SortState>,
     hidden_columns: HashSet<String>,
+    auto_hidden_columns: HashSet<String>, // Empty columns hidden on load, not persisted
     show_column_selector: bool,
     config: AppConfig,
     use_dark_theme: bool,
-    table_cache: Option<Vec<Vec<String>>>, // Cached formatted cell values
+    table_cache: Option<Vec<Vec<String>>>, // Formatted cell values of the rows in table_cache_rows
+    link_cache: Option<Vec<Vec<(String, String)>>>, // Cached (name, url) record links, same rows
+    table_cache_rows: std::ops::Range<usize>, // Filtered rows the caches hold, around the visible ones
     cache_valid: bool,
     last_filter_hash: u64, // To detect when filters actually change
     visible_row_range: std::ops::Range<usize>, // Only render visible rows
-    selected_row: Option<usize>, // Currently selected row
+    selected_row: Option<usize>, // Primary selected row, the one views open
+    selected_rows: BTreeSet<usize>, // Every selected row, for batch actions
+    copy_selected_dialog: Option<egui_file::FileDialog>,
     show_visualization_dialog: bool,
     selected_row_data: Option<HashMap<String, String>>,
+    viz_tab: VizTab,
+    spectrogram_view: Option<SpectrogramView>,
+    time_domain_view: Option<TimeDomainView>,
+    constellation_view: Option<ConstellationView>,
+    column_casts: Vec<(String, CastTarget)>, // Session-only dtype overrides, re-applied on reload
+    show_cast_dialog: bool,
+    cast_column: String,
+    cast_target: CastTarget,
+    show_link_settings: bool,
+    show_sensor_profiles: bool,
+    show_watchlist_panel: bool,
+    watchlists: Vec<Watchlist>, // Loaded from config.watchlists, in the same order
+    watchlist_errors: HashMap<PathBuf, String>,
+    watchlist_path_input: String,
+    watch_matches: Option<Vec<Vec<(usize, usize)>>>, // Per filtered row, rebuilt when invalidated
+    show_activity_window: bool,
+    activity_bucket: TimeBucket,
+    activity_heatmap: Option<ActivityHeatmap>, // Rebuilt from the filtered dataset when invalidated
+    show_dashboard: bool,
+    show_noise_trend: bool,
+    column_stats_column: Option<String>, // Open while the Column Stats window is
+    column_stats: Option<Result<ColumnStats, String>>, // Rebuilt from the filtered dataset when invalidated
+    histogram_view: Option<HistogramView>, // Open while the Histogram window is
+    scatter_view: Option<ScatterView>, // Open while the Scatter Plot window is
+    group_by_view: Option<GroupByView>, // Open while the Group By window is
+    noise_trend: Option<Result<NoiseTrend, String>>, // Rebuilt from the filtered dataset when invalidated
+    noise_threshold_db: f64,
+    show_continuity: bool,
+    continuity: Option<Result<ContinuityReport, String>>, // Rebuilt from the filtered dataset when invalidated
+    continuity_tolerance_s: f64,
+    show_map_window: bool,
+    map_color_column: Option<String>,
+    map_points: Option<Result<GeoPoints, String>>, // Rebuilt from the filtered dataset when invalidated
+    map_viewport: MapViewport,
+    map_bbox: Option<GeoFilter>, // Box drawn on the map, applied with the column filters
+    map_drawing_box: bool, // Dragging on the map draws a box instead of panning
+    verify_checksums: bool,
+    estimate_noise_floor: bool,
+    compute_metrics: bool,
+    annotation_extra_columns: bool,
+    lenient_parsing: bool, // Repair non-compliant meta files instead of skipping them
+    use_dataset_cache: bool,
+    preview_sample: bool,
+    preview_size: usize,
+    loaded_sample: Option<usize>, // The loaded dataset is a preview of this many files
+    granularity: Granularity,
+    write_conflict: Option<Box<WriteConflict>>,
+    read_only: bool,
+    dashboard_data: Option<Vec<Result<PanelData, String>>>,
+    workers: WorkerPool, // Runs checksums, measurements and spectra off the UI thread
+    pending_measurements: Vec<PendingMeasurement>,
+    show_jobs_panel: bool,
+    data_roots_text: String, // Configured data roots being edited, one per line
+    other_roots_text: String, // Further directories to scan with the next load, one per line
+    other_roots: Vec<String>, // Further directories scanned into the loaded dataset
+    log: LogBuffer, // Events captured for the log console
+    log_level: tracing::Level, // Most verbose level the console shows
+    log_search: String,
+    log_warnings_seen: usize, // Warning count when the console was last open
+    scan_report: Option<ScanReport>, // Files the last directory scan skipped, if any
+    show_scan_failures: bool,
+    cli_data_roots: Vec<String>, // From --data-root, not persisted
+    schema_prompt: Option<SchemaPrompt>,
+    dataset_load: Option<DatasetLoad>, // Directory scan or dataset read running on a worker
+    watch_directory: bool,
+    directory_watch: Option<DirectoryWatch>, // Keeps the loaded directory's rows current
+    derived_columns: DerivedColumns, // Read from config.derived_columns on load
+    settings_dialog: Option<(SettingsTransfer, egui_file::FileDialog)>,
+    session_dialog: Option<(SessionTransfer, egui_file::FileDialog)>,
+    labeling: Option<LabelingSession>, // Open while the Labeling window is
+    cluster_view: Option<ClusterView>, // Open while the Clusters window is
+    similar_search: Option<SimilarSearch>, // Open while the Similar Rows window is
+    demodulator: Option<Demodulator>, // Open while the Demodulate window is
+    burst_detector: Option<BurstDetector>, // Open while the Burst Detection window is
+    detail_panel: Option<DetailPanel>, // Metadata of the recording last shown in the detail panel
+    preset_name: String, // Name typed for saving the current filters as a preset
+    thumbnails: ThumbnailCache, // Spectrogram thumbnails of the loaded dataset's recordings
+    show_thumbnail_gallery: bool,
+}
+
+/// Offer to rebuild a re-opened dataset whose summary schema was migrated or
+/// is too new to read
+struct SchemaPrompt {
+    message: String,
+    /// Directory the dataset was built from
+    source: String,
+    /// Whether the dataset was loaded (migrated) rather than refused
+    migrated: bool,
 }
 
 impl Default for SigViewerApp {
@@ -108,6 +340,7 @@ impl Default for SigViewerApp {
             directory_path: config.last_directory.clone(),
             status_message: "No data loaded".to_string(),
             column_filters: HashMap::new(),
+            filter_errors: HashMap::new(),
             show_load_dialog: true,
             error_message: None,
             file_dialog: egui_file::FileDialog::select_folder(
@@ -117,25 +350,131 @@ impl Default for SigViewerApp {
                     Some(PathBuf::from(&config.last_directory)) 
                 }
             ),
+            export_dialog: None,
+            annotation_editor: None,
+            global_editor: None,
+            comparison: None,
+            multi_sensor_view: None,
+            provenance: None,
+            sort: None,
             hidden_columns: config.hidden_columns.clone(),
+            auto_hidden_columns: HashSet::new(),
             show_column_selector: false,
             use_dark_theme: config.use_dark_theme,
+            data_roots_text: config.data_roots.join("\n"),
+            other_roots_text: String::new(),
+            other_roots: Vec::new(),
+            log: LogBuffer::default(),
+            log_level: tracing::Level::INFO,
+            log_search: String::new(),
+            log_warnings_seen: 0,
+            scan_report: None,
+            show_scan_failures: false,
             config,
             table_cache: None,
+            link_cache: None,
+            table_cache_rows: 0..0,
             cache_valid: false,
             last_filter_hash: 0,
             visible_row_range: 0..0,
             selected_row: None,
+            selected_rows: BTreeSet::new(),
+            copy_selected_dialog: None,
             show_visualization_dialog: false,
             selected_row_data: None,
+            viz_tab: VizTab::Parameters,
+            spectrogram_view: None,
+            time_domain_view: None,
+            constellation_view: None,
+            column_casts: Vec::new(),
+            show_cast_dialog: false,
+            cast_column: String::new(),
+            cast_target: CastTarget::Float64,
+            show_link_settings: false,
+            show_sensor_profiles: false,
+            show_watchlist_panel: false,
+            watchlists: Vec::new(),
+            watchlist_errors: HashMap::new(),
+            watchlist_path_input: String::new(),
+            watch_matches: None,
+            show_activity_window: false,
+            activity_bucket: TimeBucket::HourOfDay,
+            activity_heatmap: None,
+            show_dashboard: false,
+            show_noise_trend: false,
+            column_stats_column: None,
+            column_stats: None,
+            histogram_view: None,
+            scatter_view: None,
+            group_by_view: None,
+            noise_trend: None,
+            noise_threshold_db: DEFAULT_NOISE_STEP_DB,
+            show_continuity: false,
+            continuity: None,
+            continuity_tolerance_s: DEFAULT_CONTINUITY_TOLERANCE_S,
+            show_map_window: false,
+            map_color_column: None,
+            map_points: None,
+            map_viewport: MapViewport::default(),
+            map_bbox: None,
+            map_drawing_box: false,
+            verify_checksums: false,
+            estimate_noise_floor: false,
+            compute_metrics: false,
+            annotation_extra_columns: false,
+            lenient_parsing: false,
+            use_dataset_cache: true,
+            preview_sample: false,
+            preview_size: 500,
+            loaded_sample: None,
+            granularity: Granularity::default(),
+            write_conflict: None,
+            read_only: false,
+            dashboard_data: None,
+            workers: WorkerPool::with_default_threads(),
+            pending_measurements: Vec::new(),
+            show_jobs_panel: false,
+            cli_data_roots: Vec::new(),
+            schema_prompt: None,
+            dataset_load: None,
+            watch_directory: false,
+            directory_watch: None,
+            derived_columns: DerivedColumns::default(),
+            settings_dialog: None,
+            session_dialog: None,
+            labeling: None,
+            cluster_view: None,
+            similar_search: None,
+            demodulator: None,
+            burst_detector: None,
+            detail_panel: None,
+            preset_name: String::new(),
+            thumbnails: ThumbnailCache::default(),
+            show_thumbnail_gallery: false,
         }
     }
 }
 
 // main functionality impl block
 impl SigViewerApp {
-    fn new() -> Self {
-        Self::default()
+    fn new(read_only: bool, goto: Option<&str>, data_roots: Vec<String>, restore_session: bool, log: LogBuffer) -> Self {
+        let mut app = Self::default();
+        app.log = log;
+        // Either source turns it on; neither can be overridden from the UI
+        app.read_only = read_only || app.config.read_only;
+        set_read_only(app.read_only);
+        app.cli_data_roots = data_roots;
+        app.apply_data_roots();
+        app.reload_watchlists();
+        if let Some(uri) = goto {
+            match DeepLink::parse(uri) {
+                Ok(link) => app.open_deep_link(&link),
+                Err(e) => app.error_message = Some(e.to_string()),
+            }
+        } else if restore_session {
+            app.restore_last_session();
+        }
+        app
     }
 
     fn save_config(&mut self) {
@@ -148,70 +487,188 @@ impl SigViewerApp {
     fn invalidate_cache(&mut self) {
         self.cache_valid = false;
         self.table_cache = None;
+        self.link_cache = None;
+        self.activity_heatmap = None;
+        self.noise_trend = None;
+        self.continuity = None;
+        self.column_stats = None;
+        if let Some(ref mut view) = self.histogram_view {
+            view.histogram = None;
+        }
+        if let Some(ref mut view) = self.scatter_view {
+            view.points = None;
+        }
+        if let Some(ref mut view) = self.group_by_view {
+            view.result = None;
+        }
+        self.map_points = None;
+        self.dashboard_data = None;
+        self.watch_matches = None;
     }
 
+    /// Format the rows around the ones last shown; the table only asks for
+    /// visible rows, so this stays small however many rows there are
     fn build_table_cache(&mut self, dataset: &DataFrame, visible_columns: &[String]) {
-        if self.cache_valid {
+        let visible = &self.visible_row_range;
+        let covered = self.table_cache_rows.start <= visible.start && visible.end <= self.table_cache_rows.end;
+        if self.cache_valid && self.table_cache.is_some() && covered {
             return;
         }
         
-        let num_rows = dataset.height().min(1000);
-        let mut cache = Vec::with_capacity(num_rows);
+        let start = visible.start.saturating_sub(TABLE_CACHE_MARGIN).min(dataset.height());
+        let end = (visible.end + TABLE_CACHE_MARGIN).min(dataset.height());
+        let humanize = !self.config.raw_values;
+        let cache = (start..end).map(|row_idx| format_row(dataset, visible_columns, row_idx, humanize)).collect();
         
-        for row_idx in 0..num_rows {
-            let mut row_cache = Vec::with_capacity(visible_columns.len());
-            for column_name in visible_columns {
-                if let Ok(column) = dataset.column(column_name) {
-                    let cell_value = format_cell_value(column, row_idx);
-                    row_cache.push(cell_value);
-                } else {
-                    row_cache.push("Error".to_string());
+        self.link_cache = if self.config.link_templates.is_empty() {
+            None
+        } else {
+            Some((start..end).map(|row_idx| self.row_links(dataset, row_idx)).collect())
+        };
+        self.table_cache = Some(cache);
+        self.table_cache_rows = start..end;
+        self.cache_valid = true;
+    }
+
+    /// Use the configured and `--data-root` roots for finding data files.
+    /// Returns false (with an error shown) if one doesn't parse.
+    fn apply_data_roots(&mut self) -> bool {
+        let configured: Vec<String> = self.data_roots_text
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(str::to_string)
+            .collect();
+        match DataRoots::parse(&[self.cli_data_roots.clone(), configured.clone()].concat()) {
+            Ok(roots) => {
+                set_data_roots(roots);
+                if configured != self.config.data_roots {
+                    self.config.data_roots = configured;
+                    self.save_config();
                 }
+                true
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Invalid data root: {}", e));
+                false
             }
-            cache.push(row_cache);
         }
-        
-        self.table_cache = Some(cache);
-        self.cache_valid = true;
     }
 
+    /// Start loading a directory or exported dataset in the background; see
+    /// `poll_dataset_load` for where it lands
     fn load_dataset(&mut self, path: &str) {
-        self.status_message = "Loading...".to_string();
         self.error_message = None;
+        if !self.apply_data_roots() || !self.apply_band_map() || !self.load_derived_columns() {
+            self.status_message = "Load failed".to_string();
+            return;
+        }
         
-        match SigMFDataset::from_directory(path) {
-            Ok(dataset) => {
+        // A dataset previously exported as CSV/Parquet, or a directory of recordings
+        let is_dataset_file = Path::new(path).is_file() && ExportFormat::from_path(path).is_some();
+        // Keep the original lineage; a missing/corrupt sidecar just means unknown
+        let provenance = if is_dataset_file { Provenance::read(path).ok().flatten() } else { None };
+        self.schema_prompt = None;
+        set_parse_mode(if self.lenient_parsing { ParseMode::Lenient } else { ParseMode::Strict });
+        let options = DatasetOptions {
+            granularity: self.granularity,
+            verify_checksums: self.verify_checksums,
+            estimate_noise_floor: self.estimate_noise_floor,
+            compute_metrics: self.compute_metrics,
+            annotation_extra_columns: self.annotation_extra_columns,
+            cache: self.use_dataset_cache,
+            sample: self.preview_sample.then_some(self.preview_size),
+            sample_seed: None,
+            quiet: false,
+        };
+        // Watch from before the scan so nothing written during it is missed
+        // A preview doesn't have every recording to keep current
+        // Nor follows more than one root
+        let watch = if self.watch_directory && !is_dataset_file && !self.preview_sample && self.other_roots_to_load().is_empty() {
+            DirectoryWatch::start(path, &options)
+                .map_err(|e| self.error_message = Some(format!("Can't watch {}: {}", path, e)))
+                .ok()
+        } else {
+            None
+        };
+        self.start_dataset_load(path, is_dataset_file, provenance, options);
+        if let Some(load) = self.dataset_load.as_mut() {
+            load.watch = watch;
+        }
+    }
+
+    /// Show a finished load, or its error
+    fn finish_dataset_load(&mut self, load: &DatasetLoad, loaded: Result<LoadedDataset>) {
+        let path = load.path.as_str();
+        let is_dataset_file = load.is_dataset_file;
+        let provenance = load.provenance.clone();
+        match loaded {
+            Ok((dataset, migration, report)) => {
                 self.status_message = format!("Loaded {} files", dataset.height());
+                self.scan_report = Some(report).filter(|report| !report.failures.is_empty());
+                self.show_scan_failures = false;
+                if let Some(migration) = migration.filter(|m| m.was_migrated()) {
+                    self.schema_prompt = Some(SchemaPrompt {
+                        message: migration.summary(),
+                        source: provenance.as_ref().map(|p| p.source.clone()).unwrap_or_default(),
+                        migrated: true,
+                    });
+                }
+                self.provenance = if is_dataset_file {
+                    provenance
+                } else {
+                    let mut provenance = Provenance::new(path, load.scanned_at, dataset.height());
+                    provenance.set_other_roots(&load.other_roots);
+                    provenance.row_per = load.granularity.as_str().to_string();
+                    provenance.filter = load.sample.map(|n| format!("random sample of {} files", n));
+                    Some(provenance)
+                };
+                self.loaded_sample = load.sample;
+                if let Some(n) = load.sample {
+                    self.status_message = format!("Preview: {} rows from a random sample of {} files", dataset.height(), n);
+                }
+                if let (true, Some(provenance)) = (is_dataset_file, self.provenance.as_ref()) {
+                    self.status_message = format!("Loaded {}", provenance.summary());
+                }
+                let dataset = self.apply_session_casts(dataset);
                 
                 // Initialize column filters
                 self.column_filters.clear();
+                self.filter_errors.clear();
+                self.map_bbox = None;
                 for col_name in dataset.get_column_names() {
-                    if let Ok(column) = dataset.column(col_name) {
-                        let filter_value = match column.dtype() {
-                            DataType::Float64 | DataType::Float32 | 
-                            DataType::Int64 | DataType::Int32 | 
-                            DataType::UInt64 | DataType::UInt32 => {
-                                FilterValue::Range { min: String::new(), max: String::new() }
-                            }
-                            DataType::Boolean => FilterValue::Boolean(String::new()),
-                            _ => FilterValue::Text(String::new()),
-                        };
-                        self.column_filters.insert(col_name.to_string(), filter_value);
-                    }
+                    self.column_filters.insert(col_name.to_string(), String::new());
                 }
                 
+                self.auto_hidden_columns = if self.config.show_empty_columns {
+                    HashSet::new()
+                } else {
+                    empty_columns(&dataset).into_iter().collect()
+                };
+                
+                self.sort = None;
                 self.filtered_dataset = Some(dataset.clone());
                 self.dataset = Some(dataset);
+                self.thumbnails.clear();
+                self.clear_selection();
                 self.invalidate_cache(); // Add this line
                 self.show_load_dialog = false;
+                self.show_dashboard = self.config.dashboard.show_on_load;
                 
                 // Save the successful directory path
                 self.directory_path = path.to_string();
+                self.other_roots = load.other_roots.clone();
+                self.remember_directory(path);
                 self.save_config();
             }
             Err(e) => {
-                self.error_message = Some(format!("Failed to load dataset: {}", e));
                 self.status_message = "Load failed".to_string();
+                match provenance.filter(|p| stamped_version(Some(p)) > SUMMARY_SCHEMA_VERSION) {
+                    Some(provenance) => {
+                        self.schema_prompt = Some(SchemaPrompt { message: e.to_string(), source: provenance.source, migrated: false });
+                    }
+                    None => self.error_message = Some(format!("Failed to load dataset: {}", e)),
+                }
             }
         }
     }
@@ -235,70 +692,27 @@ impl SigViewerApp {
         
         let mut filtered = dataset.clone().lazy();
         
-        // Apply filters
-        for (column_name, filter_value) in &self.column_filters {
-            if let Ok(column) = dataset.column(column_name) {
-                match filter_value {
-                    FilterValue::Range { min, max } => {
-                        let mut has_filter = false;
-                        
-                        // Apply min filter if specified
-                        if !min.is_empty() {
-                            match column.dtype() {
-                                DataType::Float64 | DataType::Float32 => {
-                                    if let Ok(min_val) = min.parse::<f64>() {
-                                        filtered = filtered.filter(col(column_name).gt_eq(lit(min_val)));
-                                        has_filter = true;
-                                    }
-                                }
-                                DataType::Int64 | DataType::Int32 | DataType::UInt64 | DataType::UInt32 => {
-                                    if let Ok(min_val) = min.parse::<i64>() {
-                                        filtered = filtered.filter(col(column_name).gt_eq(lit(min_val)));
-                                        has_filter = true;
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
-                        
-                        // Apply max filter if specified
-                        if !max.is_empty() {
-                            match column.dtype() {
-                                DataType::Float64 | DataType::Float32 => {
-                                    if let Ok(max_val) = max.parse::<f64>() {
-                                        filtered = filtered.filter(col(column_name).lt_eq(lit(max_val)));
-                                        has_filter = true;
-                                    }
-                                }
-                                DataType::Int64 | DataType::Int32 | DataType::UInt64 | DataType::UInt32 => {
-                                    if let Ok(max_val) = max.parse::<i64>() {
-                                        filtered = filtered.filter(col(column_name).lt_eq(lit(max_val)));
-                                        has_filter = true;
-                                    }
-                                }
-                                _ => {}
-                            }
-                        }
-                    }
-                    FilterValue::Text(text) => {
-                        if !text.is_empty() {
-                            filtered = filtered.filter(col(column_name).eq(lit(text.clone())));
-                        }
-                    }
-                    FilterValue::Boolean(text) => {
-                        if !text.is_empty() {
-                            if text.to_lowercase() == "true" {
-                                filtered = filtered.filter(col(column_name));
-                            } else if text.to_lowercase() == "false" {
-                                filtered = filtered.filter(col(column_name).not());
-                            }
-                        }
-                    }
+        // Apply filters; ones that don't parse are skipped and flagged next to their box
+        self.filter_errors.clear();
+        for (column_name, text) in &self.column_filters {
+            let Ok(column) = dataset.column(column_name) else {
+                continue;
+            };
+            match FilterExpr::parse(column_name, column.dtype(), text) {
+                Ok(Some(filter)) => filtered = filtered.filter(filter.to_expr()),
+                Ok(None) => {}
+                Err(e) => {
+                    self.filter_errors.insert(column_name.clone(), e.to_string());
                 }
             }
         }
         
-        match filtered.collect() {
+        let result = self.apply_sort(filtered).collect().map_err(anyhow::Error::from);
+        let result = match self.map_bbox {
+            Some(bbox) => result.and_then(|result| filter_geo(&result, &[bbox])),
+            None => result,
+        };
+        match result {
             Ok(result) => {
                 let result_height = result.height();
                 self.filtered_dataset = Some(result);
@@ -315,29 +729,41 @@ impl SigViewerApp {
         }
     }
 
+    /// Human-readable form of the active filters, e.g. `snr_db >= 10 AND agc == true`
+    fn filter_description(&self) -> Option<String> {
+        let dataset = self.dataset.as_ref()?;
+        let mut filter_vec: Vec<(&str, &str)> = self
+            .column_filters
+            .iter()
+            .filter(|(key, _)| !self.filter_errors.contains_key(*key))
+            .map(|(key, text)| (key.as_str(), text.as_str()))
+            .collect();
+        filter_vec.sort_by_key(|&(key, _)| key);
+        
+        let mut clauses: Vec<String> = parse_filters(dataset, filter_vec)
+            .ok()?
+            .iter()
+            .map(|filter| filter.to_string())
+            .collect();
+        clauses.extend(self.map_bbox.map(|bbox| bbox.to_string()));
+        if clauses.is_empty() {
+            None
+        } else {
+            Some(clauses.join(" AND "))
+        }
+    }
+
     fn calculate_filter_hash(&self) -> u64 {
         let mut hasher = DefaultHasher::new();
         
-        let mut filter_vec: Vec<(&String, &FilterValue)> = self.column_filters.iter().collect();
+        let mut filter_vec: Vec<(&String, &String)> = self.column_filters.iter().collect();
         filter_vec.sort_by_key(|&(key, _)| key);
         
-        for (key, value) in filter_vec {
+        self.sort.hash(&mut hasher);
+        self.map_bbox.map(|bbox| bbox.to_string()).hash(&mut hasher);
+        for (key, text) in filter_vec {
             key.hash(&mut hasher);
-            match value {
-                FilterValue::Range { min, max } => {
-                    "range".hash(&mut hasher);
-                    min.hash(&mut hasher);
-                    max.hash(&mut hasher);
-                }
-                FilterValue::Text(text) => {
-                    "text".hash(&mut hasher);
-                    text.hash(&mut hasher);
-                }
-                FilterValue::Boolean(text) => {
-                    "bool".hash(&mut hasher);
-                    text.hash(&mut hasher);
-                }
-            }
+            text.trim().hash(&mut hasher);
         }
         
         hasher.finish()
@@ -354,7 +780,9 @@ impl SigViewerApp {
         
         // Selection info and buttons
         ui.horizontal(|ui| {
-            if let Some(selected_idx) = self.selected_row {
+            if self.selected_rows.len() > 1 {
+                self.render_batch_actions(ui, &dataset);
+            } else if let Some(selected_idx) = self.selected_row {
                 ui.label(format!("Selected row: {}", selected_idx + 1));
                 
                 if ui.button("Visualize").clicked() {
@@ -363,6 +791,42 @@ impl SigViewerApp {
                 if ui.button("Open in Inspectrum").clicked() {
                     self.open_in_inspectrum();
                 }
+                if ui.button("Multi-Sensor View")
+                    .on_hover_text("Stack spectrograms of every sensor's recording overlapping this one in time and frequency")
+                    .clicked()
+                {
+                    self.open_multi_sensor_view();
+                }
+                if ui.button("Find Similar")
+                    .on_hover_text("Rank the other rows by distance to this one over feature columns")
+                    .clicked()
+                {
+                    self.open_similar_search();
+                }
+                if ui.button("Demodulate")
+                    .on_hover_text("Listen to this row's signal as FM or AM, picked from ml_fm_prob/ml_am_prob")
+                    .clicked()
+                {
+                    self.open_demodulator();
+                }
+                self.render_measure_menu(ui);
+                ui.menu_button("Edit", |ui| {
+                    let annotations = ui.button("Annotations...").clicked();
+                    let global = ui.button("Global Metadata...").clicked();
+                    if annotations || global {
+                        ui.close();
+                        match self.selected_meta_path() {
+                            Some(path) if annotations => self.open_annotation_editor(&path),
+                            Some(path) => self.open_global_editor(&path),
+                            None => self.error_message = Some("Could not locate the selected recording's meta file".to_string()),
+                        }
+                    }
+                });
+                for (name, url) in self.row_links(&dataset, selected_idx) {
+                    if ui.button(format!("Open in {}", name)).on_hover_text(&url).clicked() {
+                        ui.ctx().open_url(egui::OpenUrl::new_tab(url));
+                    }
+                }
                 if ui.button("Clear Selection").clicked() {
                     self.clear_selection();
                 }
@@ -374,7 +838,12 @@ impl SigViewerApp {
         ui.separator();
         
         // Store selection changes to apply after table rendering
-        let mut selection_change: Option<Option<usize>> = None;
+        let mut row_clicked: Option<(usize, egui::Modifiers)> = None;
+        let mut sort_clicked: Option<String> = None;
+        let mut stats_clicked: Option<String> = None;
+        let mut histogram_clicked: Option<String> = None;
+        let mut shown_rows: Option<std::ops::Range<usize>> = None;
+        let mut wanted_thumbnails = Vec::new();
         
         egui::ScrollArea::both()
             .max_height(available_height)
@@ -385,11 +854,25 @@ impl SigViewerApp {
                     if ui.button("Columns...").clicked() {
                         self.show_column_selector = true;
                     }
+                    let auto_hidden = self.auto_hidden_columns.len();
+                    if auto_hidden > 0 {
+                        let mut names: Vec<&String> = self.auto_hidden_columns.iter().collect();
+                        names.sort();
+                        let tooltip = names.iter().map(|s| s.as_str()).collect::<Vec<_>>().join("\n");
+                        ui.weak(format!("{} empty column{} hidden", auto_hidden, if auto_hidden == 1 { "" } else { "s" }))
+                            .on_hover_text(tooltip);
+                        if ui.small_button("Show").clicked() {
+                            self.auto_hidden_columns.clear();
+                            self.invalidate_cache();
+                        }
+                    }
                     if ui.button("Apply Filters").clicked() {
                         self.apply_filters();
                         self.invalidate_cache();
                         self.clear_selection();
                     }
+                    self.render_filter_presets(ui);
+                    self.render_band_filter(ui);
                 });
     let mut filter_updates = Vec::new(); // Store changes to apply later
     let visible_columns = self.get_visible_columns(&dataset);
@@ -400,40 +883,20 @@ impl SigViewerApp {
                 ui.vertical(|ui| {
                     ui.strong(column_name_str);
                     
-                    // Clone the current filter value to avoid borrowing self
-                    if let Some(filter_value) = self.column_filters.get(column_name_str).cloned() {
-                        match filter_value {
-                            FilterValue::Range { mut min, mut max } => {
-                                ui.horizontal(|ui| {
-                                    ui.label("Min:");
-                                    let min_response = ui.text_edit_singleline(&mut min);
-                                    if min_response.changed() {
-                                        filter_updates.push((column_name_str.clone(), FilterValue::Range { min: min.clone(), max: max.clone() }));
-                                    }
-                                });
-                                ui.horizontal(|ui| {
-                                    ui.label("Max:");
-                                    let max_response = ui.text_edit_singleline(&mut max);
-                                    if max_response.changed() {
-                                        filter_updates.push((column_name_str.clone(), FilterValue::Range { min, max }));
-                                    }
-                                });
-                            }
-                            FilterValue::Text(mut text) => {
-                                let response = ui.text_edit_singleline(&mut text);
-                                if response.changed() {
-                                    filter_updates.push((column_name_str.clone(), FilterValue::Text(text)));
-                                }
-                            }
-                            FilterValue::Boolean(mut text) => {
-                                ui.horizontal(|ui| {
-                                    ui.label("Bool:");
-                                    let response = ui.text_edit_singleline(&mut text);
-                                    if response.changed() {
-                                        filter_updates.push((column_name_str.clone(), FilterValue::Boolean(text)));
-                                    }
-                                });
-                            }
+                    // Clone the current filter text to avoid borrowing self
+                    if let Some(mut text) = self.column_filters.get(column_name_str).cloned() {
+                        let hint = dataset
+                            .column(column_name_str)
+                            .map(|column| filter_hint(column.dtype()))
+                            .unwrap_or_default();
+                        let response = ui
+                            .add(egui::TextEdit::singleline(&mut text).hint_text(hint).desired_width(140.0))
+                            .on_hover_text(FILTER_SYNTAX_HELP);
+                        if response.changed() {
+                            filter_updates.push((column_name_str.clone(), text));
+                        }
+                        if let Some(error) = self.filter_errors.get(column_name_str) {
+                            ui.colored_label(egui::Color32::RED, error);
                         }
                     }
                 });
@@ -447,8 +910,8 @@ impl SigViewerApp {
 
     // Apply all filter updates after UI rendering is complete
     if !filter_updates.is_empty() {
-        for (column_name, new_filter_value) in filter_updates {
-            self.column_filters.insert(column_name, new_filter_value);
+        for (column_name, new_filter_text) in filter_updates {
+            self.column_filters.insert(column_name, new_filter_text);
         }
         self.apply_filters();
         self.clear_selection();
@@ -457,14 +920,20 @@ impl SigViewerApp {
             ui.separator();
             
             // Build cache if needed
-            if !self.cache_valid || self.table_cache.is_none() {
-                self.build_table_cache(&dataset, &visible_columns);
-            }
+            self.build_table_cache(&dataset, &visible_columns);
+            self.ensure_watch_matches();
             
             // Table with selection
             use egui_extras::{Column, TableBuilder};
             
             let num_columns = visible_columns.len();
+            let has_links = self.link_cache.is_some();
+            let show_thumbnails = self.config.thumbnail_column;
+            let meta_filenames = dataset.column("meta_filename").ok().and_then(|column| column.str().ok()).cloned();
+            let thumbnail_paths = dataset.column(THUMBNAIL_COLUMN).ok().and_then(|column| column.str().ok()).cloned();
+            let sensor_colors: HashMap<String, egui::Color32> = self.config.sensor_profiles.iter()
+                .filter_map(|profile| Some((profile.sdr_handle.clone(), self.sensor_color(&profile.sdr_handle)?)))
+                .collect();
             
             if num_columns > 0 {
                 TableBuilder::new(ui)
@@ -472,45 +941,140 @@ impl SigViewerApp {
                     .resizable(true)
                     .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
                     .column(Column::exact(30.0)) // Selection column
-                    .columns(Column::auto().at_least(100.0), num_columns)
+                    .columns(Column::exact(44.0), show_thumbnails as usize)
+                    .columns(Column::auto().at_least(100.0), num_columns + has_links as usize)
                     .header(25.0, |mut header| {
                         header.col(|ui| {
                             ui.strong("Select");
                         });
+                        if show_thumbnails {
+                            header.col(|ui| {
+                                ui.strong("Thumb").on_hover_text("Spectrogram of the row's recording");
+                            });
+                        }
+                        if has_links {
+                            header.col(|ui| {
+                                ui.strong("Links");
+                            });
+                        }
                         for column_name in &visible_columns {
                             header.col(|ui| {
-                                ui.strong(column_name);
+                                let response = self.sort_header(ui, column_name);
+                                if response.clicked() {
+                                    sort_clicked = Some(column_name.clone());
+                                }
+                                response.context_menu(|ui| {
+                                    if ui.button("Column Stats").clicked() {
+                                        stats_clicked = Some(column_name.clone());
+                                        ui.close();
+                                    }
+                                    let numeric = dataset.column(column_name).is_ok_and(|column| column.dtype().is_numeric());
+                                    if ui.add_enabled(numeric, egui::Button::new("Plot Histogram")).clicked() {
+                                        histogram_clicked = Some(column_name.clone());
+                                        ui.close();
+                                    }
+                                });
                             });
                         }
                     })
                     .body(|body| {
                         let cache = self.table_cache.as_ref();
-                        let current_selection = self.selected_row;
+                        let link_cache = self.link_cache.as_ref();
+                        let cache_rows = self.table_cache_rows.clone();
+                        let selected_rows = &self.selected_rows;
                         
                         if let Some(cache) = cache {
-                            body.rows(20.0, cache.len(), |mut row| {
+                            body.rows(20.0, dataset.height(), |mut row| {
                                 let row_index = row.index();
-                                let is_selected = current_selection == Some(row_index);
+                                shown_rows = match shown_rows.take() {
+                                    Some(rows) => Some(rows.start.min(row_index)..rows.end.max(row_index + 1)),
+                                    None => Some(row_index..row_index + 1),
+                                };
+                                // Scrolled past the cache: format this frame's rows directly,
+                                // the cache moves here on the next
+                                let cache_idx = cache_rows.contains(&row_index).then(|| row_index - cache_rows.start);
+                                let row_data = match cache_idx.and_then(|idx| cache.get(idx)) {
+                                    Some(row_data) => Cow::Borrowed(row_data),
+                                    None => Cow::Owned(format_row(&dataset, &visible_columns, row_index, !self.config.raw_values)),
+                                };
+                                let is_selected = selected_rows.contains(&row_index);
+                                row.set_selected(is_selected);
+                                let watched = self.watch_label(row_index);
+                                let highlight = |ui: &mut egui::Ui| {
+                                    if watched.is_some() && !is_selected {
+                                        ui.painter().rect_filled(ui.max_rect(), 0.0, gui::watchlist_panel::WATCH_HIGHLIGHT);
+                                    }
+                                };
                                 
-                                // Selection column - try a different approach
+                                // Selection column; ctrl/shift-click to select several
                                 row.col(|ui| {
-                                    // Add some debug visual feedback
-                                    if ui.selectable_label(is_selected, if is_selected { "●" } else { "○" }).clicked() {
-                                        if is_selected {
-                                            selection_change = Some(None); // Clear selection
-                                        } else {
-                                            selection_change = Some(Some(row_index)); // Select this row
-                                        }
+                                    highlight(ui);
+                                    let hover = match &watched {
+                                        Some(names) => format!("Watchlist: {}\nCtrl-click to add or remove rows, shift-click to select a range", names),
+                                        None => "Ctrl-click to add or remove rows, shift-click to select a range".to_string(),
+                                    };
+                                    if ui.selectable_label(is_selected, if is_selected { "●" } else if watched.is_some() { "★" } else { "○" })
+                                        .on_hover_text(hover)
+                                        .clicked()
+                                    {
+                                        row_clicked = Some((row_index, ui.input(|i| i.modifiers)));
                                     }
                                 });
+
+                                if show_thumbnails {
+                                    row.col(|ui| {
+                                        highlight(ui);
+                                        let Some(name) = meta_filenames.as_ref().and_then(|names| names.get(row_index)) else {
+                                            return;
+                                        };
+                                        let thumbnail_path = thumbnail_paths.as_ref().and_then(|paths| paths.get(row_index));
+                                        if self.thumbnail_cell(ui, name, thumbnail_path, &mut wanted_thumbnails).clicked() {
+                                            row_clicked = Some((row_index, ui.input(|i| i.modifiers)));
+                                        }
+                                    });
+                                }
                                 
-                                // Data columns
-                                if let Some(row_data) = cache.get(row_index) {
-                                    for cell_value in row_data {
-                                        row.col(|ui| {
-                                            ui.label(cell_value);
-                                        });
+                                let links = link_cache.map(|links| {
+                                    match cache_idx.and_then(|idx| links.get(idx)) {
+                                        Some(links) => Cow::Borrowed(links),
+                                        None => Cow::Owned(self.row_links(&dataset, row_index)),
                                     }
+                                });
+                                if let Some(ref links) = links {
+                                    row.col(|ui| {
+                                        highlight(ui);
+                                        for (name, url) in links.iter() {
+                                            ui.hyperlink_to(name, url);
+                                        }
+                                    });
+                                }
+
+                                // Data columns
+                                for (column_name, cell_value) in visible_columns.iter().zip(row_data.iter()) {
+                                    row.col(|ui| {
+                                        highlight(ui);
+                                        let response = if column_name == "annotation_density" {
+                                            let counts: Vec<u64> = cell_value.split(',').filter_map(|c| c.parse().ok()).collect();
+                                            gui::plot::sparkline(ui, &counts)
+                                                .on_hover_text(format!("Annotations over time: {}", cell_value))
+                                        } else if let Some(color) = sensor_colors.get(cell_value).filter(|_| column_name == "sdr_handle") {
+                                            ui.add(egui::Label::new(egui::RichText::new(cell_value).color(*color)).sense(egui::Sense::click()))
+                                        } else if let Some(id) = cell_value.parse::<i64>().ok().filter(|_| column_name == CLUSTER_COLUMN) {
+                                            ui.add(egui::Label::new(egui::RichText::new(cell_value).color(gui::cluster_view::cluster_color(id))).sense(egui::Sense::click()))
+                                        } else {
+                                            ui.add(egui::Label::new(cell_value).sense(egui::Sense::click()))
+                                        };
+                                        if let Some(links) = links.as_ref().filter(|links| !links.is_empty()) {
+                                            response.context_menu(|ui| {
+                                                for (name, url) in links.iter() {
+                                                    if ui.button(format!("Open in {}", name)).clicked() {
+                                                        ui.ctx().open_url(egui::OpenUrl::new_tab(url));
+                                                        ui.close();
+                                                    }
+                                                }
+                                            });
+                                        }
+                                    });
                                 }
                             });
                         }
@@ -521,11 +1085,21 @@ impl SigViewerApp {
         });
         
         // Apply selection change after table rendering
-        if let Some(new_selection) = selection_change {
-            match new_selection {
-                Some(row_idx) => self.select_row(row_idx),
-                None => self.clear_selection(),
-            }
+        if let Some((row_idx, modifiers)) = row_clicked {
+            self.click_row(row_idx, modifiers);
+        }
+        if let Some(rows) = shown_rows {
+            self.visible_row_range = rows;
+        }
+        self.request_thumbnails(wanted_thumbnails);
+        if let Some(column) = sort_clicked {
+            self.toggle_sort(&column);
+        }
+        if let Some(column) = stats_clicked {
+            self.open_column_stats(&column);
+        }
+        if let Some(column) = histogram_clicked {
+            self.open_histogram(Some(&column));
         }
     }
 
@@ -539,9 +1113,48 @@ impl SigViewerApp {
                     ui.heading("Load SigMF Dataset");
                     
                     ui.horizontal(|ui| {
-                        ui.label("Directory:");
+                        ui.label("Directory or dataset file:");
                         ui.text_edit_singleline(&mut self.directory_path);
                     });
+                    self.render_directory_list(ui);
+                    ui.checkbox(&mut self.verify_checksums, "Verify SHA-512 checksums (slow on large datasets)");
+                    ui.checkbox(&mut self.estimate_noise_floor, "Estimate noise floor from samples (for Noise Floor Trend)");
+                    ui.checkbox(&mut self.compute_metrics, "Measure noise floor, peak power, occupied bandwidth and SNR from samples (measured_*)");
+                    ui.checkbox(&mut self.annotation_extra_columns, "Add columns for unrecognized annotation fields (ann_*)");
+                    ui.checkbox(&mut self.lenient_parsing, "Lenient parsing: repair non-compliant metadata instead of skipping the file")
+                        .on_hover_text("Missing core:version, numbers written as text, malformed geolocation, ... Each repair is listed in the parse_warnings column.");
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.preview_sample, "Preview a random sample of");
+                        ui.add_enabled(self.preview_sample, egui::DragValue::new(&mut self.preview_size).range(1..=100_000));
+                        ui.label("files");
+                    });
+                    ui.checkbox(&mut self.use_dataset_cache, "Reuse cached rows for files unchanged since the last load");
+                    ui.checkbox(&mut self.watch_directory, "Watch for new recordings (directories only)");
+                    ui.horizontal(|ui| {
+                        ui.label("One row per");
+                        ui.radio_value(&mut self.granularity, Granularity::Annotation, "ML annotation");
+                        ui.radio_value(&mut self.granularity, Granularity::Capture, "capture segment");
+                        ui.radio_value(&mut self.granularity, Granularity::File, "file");
+                    });
+                    ui.collapsing("More directories", |ui| {
+                        ui.label("Further directories to scan into the same dataset, one per line, e.g. recordings split across drives. Each row's directory goes in the source_root column.");
+                        ui.add(egui::TextEdit::multiline(&mut self.other_roots_text).desired_rows(3).desired_width(f32::INFINITY).hint_text("/mnt/drive2/recordings"));
+                    });
+                    ui.collapsing("Data roots", |ui| {
+                        ui.label("Where to look for data files that aren't next to their meta file, one per line: a directory searched by file name, or META_PREFIX=DATA_PREFIX");
+                        ui.add(egui::TextEdit::multiline(&mut self.data_roots_text).desired_rows(3).desired_width(f32::INFINITY).hint_text("/mnt/meta=/mnt/iq"));
+                        for root in &self.cli_data_roots {
+                            ui.weak(format!("{} (from --data-root)", root));
+                        }
+                    });
+                    ui.collapsing("Band map", |ui| {
+                        ui.label("TOML file of bands (and their channels) for the band and channel columns, checked before the built-in ISM 915, WiFi and GPS L1 bands. Leave empty for the built-in ones.");
+                        ui.add(egui::TextEdit::singleline(&mut self.config.band_map).desired_width(f32::INFINITY).hint_text("/path/to/bands.toml"));
+                    });
+                    ui.collapsing("Derived columns", |ui| {
+                        ui.label("TOML file of extra columns computed from the others, e.g. freq_mhz = center_freq_hz / 1e6, added to every dataset loaded. Leave empty for none.");
+                        ui.add(egui::TextEdit::singleline(&mut self.config.derived_columns).desired_width(f32::INFINITY).hint_text("/path/to/derived.toml"));
+                    });
                     
                     ui.horizontal(|ui| {
                         if ui.button("Load").clicked() && !self.directory_path.is_empty() {
@@ -594,11 +1207,18 @@ impl SigViewerApp {
                             .max_height(300.0)
                             .show(ui, |ui| {
                                 for column_name in &column_names {
-                                    let mut is_visible = !self.hidden_columns.contains(column_name);
+                                    let auto_hidden = self.auto_hidden_columns.contains(column_name);
+                                    let mut is_visible = !self.hidden_columns.contains(column_name) && !auto_hidden;
+                                    let label = if auto_hidden {
+                                        format!("{} (empty)", column_name)
+                                    } else {
+                                        column_name.clone()
+                                    };
                                     
-                                    if ui.checkbox(&mut is_visible, column_name).changed() {
+                                    if ui.checkbox(&mut is_visible, label).changed() {
                                         if is_visible {
                                             self.hidden_columns.remove(column_name);
+                                            self.auto_hidden_columns.remove(column_name);
                                         } else {
                                             self.hidden_columns.insert(column_name.clone());
                                         }
@@ -615,6 +1235,7 @@ impl SigViewerApp {
                         ui.horizontal(|ui| {
                             if ui.button("Show All").clicked() {
                                 self.hidden_columns.clear();
+                                self.auto_hidden_columns.clear();
                                 self.invalidate_cache();
                                 self.save_config();
                             }
@@ -625,6 +1246,9 @@ impl SigViewerApp {
                                 self.save_config();
                             }
                         });
+                        if ui.checkbox(&mut self.config.show_empty_columns, "Show empty columns on load").changed() {
+                            self.save_config();
+                        }
                     }
                     
                     if ui.button("Close").clicked() {
@@ -639,12 +1263,19 @@ impl SigViewerApp {
             .iter()
             .map(|s| s.to_string())
             .filter(|col_name| !self.hidden_columns.contains(col_name))
+            .filter(|col_name| !self.auto_hidden_columns.contains(col_name))
             .collect()
     }
 }
 
 impl eframe::App for SigViewerApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_dataset_load();
+        self.poll_directory_watch(ctx);
+        self.poll_jobs(ctx);
+        self.poll_thumbnails(ctx);
+        self.autosave_session(ctx);
+
         // Apply theme if it changed
         if self.use_dark_theme != self.config.use_dark_theme {
             if self.use_dark_theme {
@@ -663,26 +1294,40 @@ impl eframe::App for SigViewerApp {
                         self.show_load_dialog = true;
                         ui.close();
                     }
-                    if ui.button("Export CSV...").clicked() {
-                        // TODO: Implement CSV export
+                    self.render_recent_menu(ui);
+                    ui.separator();
+                    if ui.add_enabled(self.filtered_dataset.is_some(), egui::Button::new("Export...")).clicked() {
+                        self.open_export_dialog(None);
+                        ui.close();
+                    }
+                    ui.separator();
+                    if ui.button("Open Session...").clicked() {
+                        self.open_session_dialog(SessionTransfer::Open);
+                        ui.close();
+                    }
+                    if ui.add_enabled(self.dataset.is_some(), egui::Button::new("Save Session...")).clicked() {
+                        self.open_session_dialog(SessionTransfer::Save);
+                        ui.close();
+                    }
+                    ui.separator();
+                    if ui.button("Export Settings...").clicked() {
+                        self.open_settings_dialog(SettingsTransfer::Export);
+                        ui.close();
+                    }
+                    if ui.button("Import Settings...").clicked() {
+                        self.open_settings_dialog(SettingsTransfer::Import);
                         ui.close();
                     }
                 });
                 
                 ui.menu_button("View", |ui| {
                     if ui.button("Clear Filters").clicked() {
-                        for filter_value in self.column_filters.values_mut() {
-                            match filter_value {
-                                FilterValue::Range { min, max } => {
-                                    min.clear();
-                                    max.clear();
-                                }
-                                FilterValue::Text(text) => text.clear(),
-                                FilterValue::Boolean(text) => text.clear(),
-                            }
+                        for text in self.column_filters.values_mut() {
+                            text.clear();
                         }
                         if self.dataset.is_some() {
-                            self.filtered_dataset = self.dataset.clone();
+                            self.apply_filters(); // keeps the sort
+                            self.invalidate_cache();
                             self.status_message = format!("Showing all {} files", 
                                 self.dataset.as_ref().unwrap().height());
                         }
@@ -692,8 +1337,93 @@ impl eframe::App for SigViewerApp {
                         self.show_column_selector = true;
                         ui.close();
                     }
+                    if ui.button("Cast Column...").clicked() {
+                        self.show_cast_dialog = true;
+                        ui.close();
+                    }
+                    if ui.button("Dashboard...").clicked() {
+                        self.show_dashboard = true;
+                        ui.close();
+                    }
+                    if ui.button("Sensor Activity...").clicked() {
+                        self.show_activity_window = true;
+                        ui.close();
+                    }
+                    if ui.button("Histogram...").clicked() {
+                        self.open_histogram(None);
+                        ui.close();
+                    }
+                    if ui.button("Scatter Plot...").clicked() {
+                        self.open_scatter_view();
+                        ui.close();
+                    }
+                    if ui.button("Group By...").clicked() {
+                        self.open_group_by_view();
+                        ui.close();
+                    }
+                    if ui.button("Noise Floor Trend...").clicked() {
+                        self.show_noise_trend = true;
+                        ui.close();
+                    }
+                    if ui.button("Continuity...").clicked() {
+                        self.show_continuity = true;
+                        ui.close();
+                    }
+                    if ui.button("Map...").clicked() {
+                        self.show_map_window = true;
+                        ui.close();
+                    }
+                    if ui.add_enabled(self.filtered_dataset.is_some(), egui::Button::new("Thumbnail Gallery...")).clicked() {
+                        self.show_thumbnail_gallery = true;
+                        ui.close();
+                    }
+                    if ui.button("Record Links...").clicked() {
+                        self.show_link_settings = true;
+                        ui.close();
+                    }
+                    if ui.button("Sensor Profiles...").clicked() {
+                        self.show_sensor_profiles = true;
+                        ui.close();
+                    }
+                    if ui.button("Watchlists...").clicked() {
+                        self.show_watchlist_panel = true;
+                        ui.close();
+                    }
+                    if ui.button("Jobs...").clicked() {
+                        self.show_jobs_panel = true;
+                        ui.close();
+                    }
+                    if ui.add_enabled(self.filtered_dataset.is_some(), egui::Button::new("Labeling...")).clicked() {
+                        self.open_labeling();
+                        ui.close();
+                    }
+                    if ui.add_enabled(self.dataset.is_some(), egui::Button::new("Clusters...")).clicked() {
+                        self.open_cluster_view();
+                        ui.close();
+                    }
                     
                     ui.separator();
+                    if ui.checkbox(&mut self.config.show_detail_panel, "Metadata Panel").changed() {
+                        self.save_config();
+                    }
+                    if ui.checkbox(&mut self.config.show_log_console, "Log Console").changed() {
+                        self.save_config();
+                    }
+                    if ui
+                        .checkbox(&mut self.config.thumbnail_column, "Thumbnail Column")
+                        .on_hover_text("A spectrogram of each row's recording beside the table, rendered into the thumbnail cache as rows scroll into view")
+                        .changed()
+                    {
+                        self.save_config();
+                    }
+                    if ui
+                        .checkbox(&mut self.config.raw_values, "Raw Values")
+                        .on_hover_text("Show frequencies, durations, sizes and levels as stored instead of in MHz, ms, MiB, dBm, ...")
+                        .changed()
+                    {
+                        self.invalidate_cache();
+                        self.save_config();
+                    }
                     if ui.checkbox(&mut self.use_dark_theme, "Dark Theme").changed() {
                         if self.use_dark_theme {
                             ctx.set_visuals(egui::Visuals::dark());
@@ -706,10 +1436,24 @@ impl eframe::App for SigViewerApp {
                 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                     ui.label(&self.status_message);
+                    self.render_load_progress(ui);
+                    self.render_preview_indicator(ui);
+                    self.render_scan_failures_indicator(ui);
+                    self.render_watch_indicator(ui);
+                    self.render_jobs_indicator(ui);
+                    self.render_log_indicator(ui);
+                    if self.read_only {
+                        ui.colored_label(egui::Color32::from_rgb(200, 120, 0), "🔒 Read-only")
+                            .on_hover_text("Write-back, renaming and deletion are disabled");
+                    }
                 });
             });
         });
 
+        // Side panels claim their space before the central panel
+        self.render_log_console(ctx);
+        self.render_detail_panel(ctx);
+
         // Main content area
         egui::CentralPanel::default().show(ctx, |ui| {
             if self.dataset.is_some() {
@@ -717,10 +1461,17 @@ impl eframe::App for SigViewerApp {
             } else {
                 ui.vertical_centered(|ui| {
                     ui.heading("Sig Viewer");
-                    ui.label("Load a dataset to get started");
+                    if self.dataset_load.is_some() {
+                        self.render_load_progress(ui);
+                    } else {
+                        ui.label("Load a dataset to get started");
+                    }
                     if ui.button("Load Dataset").clicked() {
                         self.show_load_dialog = true;
                     }
+                    if !self.show_load_dialog {
+                        self.render_directory_list(ui);
+                    }
                 });
             }
         });
@@ -728,7 +1479,38 @@ impl eframe::App for SigViewerApp {
         // Dialogs
         self.render_load_dialog(ctx);
         self.render_column_selector(ctx);
+        self.render_cast_dialog(ctx);
+        self.render_link_settings(ctx);
+        self.render_sensor_profiles(ctx);
+        self.render_watchlist_panel(ctx);
+        self.render_settings_dialog(ctx);
+        self.render_session_dialog(ctx);
+        self.render_activity_window(ctx);
+        self.render_noise_trend_window(ctx);
+        self.render_continuity_window(ctx);
+        self.render_column_stats(ctx);
+        self.render_histogram_window(ctx);
+        self.render_scatter_view(ctx);
+        self.render_group_by_view(ctx);
+        self.render_map_window(ctx);
+        self.render_thumbnail_gallery(ctx);
+        self.render_dashboard(ctx);
+        self.render_write_conflict(ctx);
+        self.render_export_dialog(ctx);
+        self.render_copy_selected_dialog(ctx);
+        self.render_annotation_editor(ctx);
+        self.render_global_editor(ctx);
+        self.render_comparison(ctx);
+        self.render_multi_sensor_view(ctx);
         self.render_visualization_dialog(ctx);
+        self.render_jobs_panel(ctx);
+        self.render_labeling(ctx);
+        self.render_cluster_view(ctx);
+        self.render_similar_search(ctx);
+        self.render_demodulator(ctx);
+        self.render_burst_detector(ctx);
+        self.render_schema_prompt(ctx);
+        self.render_scan_failures(ctx);
         
         // Error popup
         let show_error = self.error_message.is_some();
@@ -748,7 +1530,201 @@ impl eframe::App for SigViewerApp {
     }
 }
 
-fn format_cell_value(column: &polars::series::Series, row_idx: usize) -> String {
+// handle summary schema migration of re-opened datasets
+impl SigViewerApp {
+    fn render_schema_prompt(&mut self, ctx: &egui::Context) {
+        let Some(ref prompt) = self.schema_prompt else {
+            return;
+        };
+        let (message, source, migrated) = (prompt.message.clone(), prompt.source.clone(), prompt.migrated);
+        let mut rebuild = false;
+        let mut dismiss = false;
+        egui::Window::new("Dataset schema")
+            .collapsible(false)
+            .resizable(false)
+            .anchor(egui::Align2::CENTER_CENTER, egui::Vec2::ZERO)
+            .show(ctx, |ui| {
+                ui.label(&message);
+                if migrated {
+                    ui.label("Migrated columns hold defaults; rebuild from the recordings for real values.");
+                }
+                ui.horizontal(|ui| {
+                    let can_rebuild = !source.is_empty() && Path::new(&source).exists();
+                    if ui
+                        .add_enabled(can_rebuild, egui::Button::new("Rebuild from source"))
+                        .on_hover_text(&source)
+                        .on_disabled_hover_text(format!("{} is not available", if source.is_empty() { "The source" } else { &source }))
+                        .clicked()
+                    {
+                        rebuild = true;
+                    }
+                    if ui.button(if migrated { "Keep" } else { "Close" }).clicked() {
+                        dismiss = true;
+                    }
+                });
+            });
+        if rebuild {
+            self.schema_prompt = None;
+            self.load_dataset(&source);
+        } else if dismiss {
+            self.schema_prompt = None;
+        }
+    }
+}
+
+// handle column dtype casting
+impl SigViewerApp {
+    /// Re-apply this session's casts to a freshly loaded dataset, skipping
+    /// columns the new dataset doesn't have
+    fn apply_session_casts(&mut self, dataset: DataFrame) -> DataFrame {
+        let casts: Vec<(String, CastTarget)> = self.column_casts.iter()
+            .filter(|(column, _)| dataset.column(column).is_ok())
+            .cloned()
+            .collect();
+        if casts.is_empty() {
+            return dataset;
+        }
+        match cast_columns(dataset.clone().lazy(), &casts).and_then(|lf| Ok(lf.collect()?)) {
+            Ok(casted) => casted,
+            Err(e) => {
+                self.error_message = Some(format!("Failed to re-apply column casts: {}", e));
+                dataset
+            }
+        }
+    }
+
+    fn apply_column_cast(&mut self, column: &str, target: CastTarget) {
+        let Some(ref dataset) = self.dataset else {
+            return;
+        };
+        let cast = [(column.to_string(), target)];
+        match cast_columns(dataset.clone().lazy(), &cast).and_then(|lf| Ok(lf.collect()?)) {
+            Ok(casted) => {
+                self.column_casts.retain(|(existing, _)| existing != column);
+                self.column_casts.push((column.to_string(), target));
+                // The old filter may not make sense for the new dtype
+                self.column_filters.insert(column.to_string(), String::new());
+                self.dataset = Some(casted);
+                self.force_refilter();
+                self.clear_selection();
+                self.status_message = format!("Cast {} to {}", column, target.name());
+            }
+            Err(e) => {
+                self.error_message = Some(format!("Failed to cast {}: {}", column, e));
+            }
+        }
+    }
+
+    /// Re-run filtering even though the filter inputs haven't changed, e.g.
+    /// after the underlying dataset was modified
+    fn force_refilter(&mut self) {
+        self.last_filter_hash = 0;
+        self.apply_filters();
+        self.invalidate_cache();
+    }
+
+    fn render_cast_dialog(&mut self, ctx: &egui::Context) {
+        if !self.show_cast_dialog {
+            return;
+        }
+        let columns: Vec<(String, String)> = self.dataset.as_ref()
+            .map(|dataset| dataset.get_columns().iter()
+                .map(|column| (column.name().to_string(), column.dtype().to_string()))
+                .collect())
+            .unwrap_or_default();
+
+        let mut open = true;
+        let mut apply = false;
+        egui::Window::new("Cast Column")
+            .open(&mut open)
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                if columns.is_empty() {
+                    ui.label("Load a dataset first");
+                    return;
+                }
+                ui.label("Change a column's type for this session, e.g. to filter a numeric field that was parsed as text.");
+                egui::Grid::new("cast_dialog").num_columns(2).show(ui, |ui| {
+                    ui.label("Column:");
+                    let selected_text = columns.iter()
+                        .find(|(name, _)| *name == self.cast_column)
+                        .map(|(name, dtype)| format!("{} ({})", name, dtype))
+                        .unwrap_or_else(|| "Select...".to_string());
+                    egui::ComboBox::from_id_salt("cast_column")
+                        .selected_text(selected_text)
+                        .show_ui(ui, |ui| {
+                            for (name, dtype) in &columns {
+                                ui.selectable_value(&mut self.cast_column, name.clone(), format!("{} ({})", name, dtype));
+                            }
+                        });
+                    ui.end_row();
+
+                    ui.label("Cast to:");
+                    egui::ComboBox::from_id_salt("cast_target")
+                        .selected_text(self.cast_target.name())
+                        .show_ui(ui, |ui| {
+                            for target in CastTarget::ALL {
+                                ui.selectable_value(&mut self.cast_target, target, target.name());
+                            }
+                        });
+                    ui.end_row();
+                });
+
+                ui.add_enabled_ui(!self.cast_column.is_empty(), |ui| {
+                    if ui.button("Apply").clicked() {
+                        apply = true;
+                    }
+                });
+
+                if !self.column_casts.is_empty() {
+                    ui.separator();
+                    ui.label("Active casts (reload the dataset to revert):");
+                    for (column, target) in &self.column_casts {
+                        ui.label(format!("{} → {}", column, target.name()));
+                    }
+                }
+            });
+
+        if apply {
+            let column = self.cast_column.clone();
+            self.apply_column_cast(&column, self.cast_target);
+        }
+        if !open {
+            self.show_cast_dialog = false;
+        }
+    }
+}
+
+fn launch_inspectrum(meta_path: &Path) -> anyhow::Result<()> {
+    if !meta_path.exists() {
+        anyhow::bail!("Inspectrum can't open recordings inside a .sigmf archive: {}", meta_path.display());
+    }
+    std::process::Command::new("inspectrum")
+        .arg(meta_path)
+        .spawn()
+        .map_err(|e| anyhow::anyhow!("Failed to launch inspectrum: {}", e))?;
+    Ok(())
+}
+
+/// Cells of one table row, in `columns` order
+fn format_row(dataset: &DataFrame, columns: &[String], row_idx: usize, humanize: bool) -> Vec<String> {
+    columns
+        .iter()
+        .map(|name| dataset.column(name).map_or_else(|_| "Error".to_string(), |column| format_cell_value(column, row_idx, humanize)))
+        .collect()
+}
+
+/// A cell as shown. With `humanize`, numbers in columns whose name gives
+/// their unit (`_hz`, `_s`, `_bytes`, `_dbm`, ...) read as e.g. `915.000 MHz`
+/// or `1.5 MiB`; otherwise they're shown as stored.
+fn format_cell_value(column: &polars::series::Series, row_idx: usize, humanize: bool) -> String {
+    if humanize && column.dtype().is_numeric() {
+        let value = column.get(row_idx).ok().and_then(|value| value.extract::<f64>());
+        if let Some(text) = value.and_then(|value| humanize_value(column.name(), value)) {
+            return text;
+        }
+    }
     match column.dtype() {
         DataType::String => {
             column.str().unwrap().get(row_idx).unwrap_or("").to_string()
@@ -794,7 +1770,13 @@ fn format_cell_value(column: &polars::series::Series, row_idx: usize) -> String
 // handle selectable rows
 impl SigViewerApp {
     fn select_row(&mut self, row_index: usize) {
-    println!("Selecting row: {}", row_index); // Debug output
+        self.selected_rows = BTreeSet::from([row_index]);
+        self.focus_row(row_index);
+    }
+
+    /// Make `row_index` the primary row without changing the rest of the selection
+    fn focus_row(&mut self, row_index: usize) {
+    debug!("Selecting row {}", row_index);
     self.selected_row = Some(row_index);
     
     // Use filtered_dataset instead of dataset
@@ -805,24 +1787,26 @@ impl SigViewerApp {
         if row_index < dataset.height() {
             for column_name in dataset.get_column_names() {
                 if let Ok(column) = dataset.column(column_name) {
-                    let cell_value = format_cell_value(column, row_index);
+                    // As stored, for the code reading it back
+                    let cell_value = format_cell_value(column, row_index, false);
                     row_data.insert(column_name.to_string(), cell_value);
                 }
             }
             self.selected_row_data = Some(row_data);
-            println!("Row data cached for row {}", row_index); // Debug output
+            debug!("Row data cached for row {}", row_index);
         } else {
-            println!("Row index {} out of bounds (dataset height: {})", row_index, dataset.height());
+            warn!("Row index {} out of bounds (dataset height: {})", row_index, dataset.height());
             self.selected_row_data = None;
         }
     } else {
         self.selected_row_data = None;
-        println!("No filtered dataset available");
+        debug!("No filtered dataset available");
     }
     }
 
     fn clear_selection(&mut self) {
         self.selected_row = None;
+        self.selected_rows.clear();
         self.selected_row_data = None;
     }
 
@@ -831,104 +1815,198 @@ impl SigViewerApp {
             egui::Window::new("Visualize Signal Data")
                 .collapsible(false)
                 .resizable(true)
-                .default_size([600.0, 400.0])
+                .default_size([900.0, 650.0])
                 .show(ctx, |ui| {
-                    ui.heading("Signal Visualization");
-                    
-                    if let Some(ref row_data) = self.selected_row_data {
-                        ui.separator();
-                        
-                        // Show key signal parameters
-                        ui.label("Selected Signal Parameters:");
-                        
-                        egui::ScrollArea::vertical()
-                            .max_height(200.0)
-                            .show(ui, |ui| {
-                                egui::Grid::new("signal_params")
-                                    .num_columns(2)
-                                    .spacing([20.0, 4.0])
-                                    .show(ui, |ui| {
-                                        // Show important parameters first
-                                        let important_params = [
-                                            ("meta_filename", "Filename"),
-                                            ("sig_center_freq_hz", "Center Frequency (Hz)"),
-                                            ("sample_rate_hz", "Sample Rate (Hz)"),
-                                            ("sig_bandwidth_hz", "Bandwidth (Hz)"),
-                                            ("snr_db", "SNR (dB)"),
-                                            ("power_dbm", "Power (dBm)"),
-                                            ("duration_s", "Duration (s)"),
-                                            ("ml_wifi_prob", "WiFi Probability"),
-                                            ("ml_cell_prob", "Cellular Probability"),
-                                            ("ml_radar_prob", "Radar Probability"),
-                                        ];
-                                        
-                                        for (key, display_name) in &important_params {
-                                            if let Some(value) = row_data.get(*key) {
-                                                ui.label(format!("{}:", display_name));
-                                                ui.label(value);
-                                                ui.end_row();
-                                            }
-                                        }
-                                    });
-                            });
-                        
+                    ui.horizontal(|ui| {
+                        ui.heading("Signal Visualization");
                         ui.separator();
-                        
-                        // Placeholder for actual visualization buttons
-                        ui.horizontal(|ui| {
-                            
-                            if ui.button("PSD").clicked() {
-                                // TODO: Implement frequency domain visualization
-                                println!("Frequency domain plot requested for: {:?}", row_data.get("meta_filename"));
-                            }
-                            
-                            if ui.button("Spectrogram").clicked() {
-                                // TODO: Implement spectrogram visualization
-                                println!("Spectrogram requested for: {:?}", row_data.get("meta_filename"));
+                        ui.selectable_value(&mut self.viz_tab, VizTab::Parameters, "Parameters");
+                        ui.selectable_value(&mut self.viz_tab, VizTab::Spectrogram, "Spectrogram");
+                        ui.selectable_value(&mut self.viz_tab, VizTab::TimeDomain, "Time Domain");
+                        ui.selectable_value(&mut self.viz_tab, VizTab::Constellation, "Constellation");
+                        ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
+                            if ui.button("Close").clicked() {
+                                self.show_visualization_dialog = false;
                             }
                         });
-                        
-                        ui.separator();
-                        ui.label("Note: Visualization functionality will load and process the actual signal data file.");
-                        
-                    } else {
-                        ui.colored_label(egui::Color32::RED, "No row data available");
-                    }
-                    
+                    });
                     ui.separator();
-                    if ui.button("Close").clicked() {
-                        self.show_visualization_dialog = false;
+
+                    match self.viz_tab {
+                        VizTab::Parameters => self.render_parameters_tab(ui),
+                        VizTab::Spectrogram => self.render_recording_tab(ui, |app| &mut app.spectrogram_view),
+                        VizTab::TimeDomain => self.render_recording_tab(ui, |app| &mut app.time_domain_view),
+                        VizTab::Constellation => self.render_recording_tab(ui, |app| &mut app.constellation_view),
                     }
                 });
         }
     }
+
+    fn render_parameters_tab(&mut self, ui: &mut egui::Ui) {
+        let mut open_spectrogram = false;
+
+        if let Some(ref row_data) = self.selected_row_data {
+            // Show key signal parameters
+            ui.label("Selected Signal Parameters:");
+            
+            egui::ScrollArea::vertical()
+                .max_height(200.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("signal_params")
+                        .num_columns(2)
+                        .spacing([20.0, 4.0])
+                        .show(ui, |ui| {
+                            // Show important parameters first
+                            let important_params = [
+                                ("meta_filename", "Filename"),
+                                ("sig_center_freq_hz", "Center Frequency (Hz)"),
+                                ("sample_rate_hz", "Sample Rate (Hz)"),
+                                ("sig_bandwidth_hz", "Bandwidth (Hz)"),
+                                ("snr_db", "SNR (dB)"),
+                                ("power_dbm", "Power (dBm)"),
+                                ("duration_s", "Duration (s)"),
+                                ("ml_wifi_prob", "WiFi Probability"),
+                                ("ml_cell_prob", "Cellular Probability"),
+                                ("ml_radar_prob", "Radar Probability"),
+                            ];
+                            
+                            for (key, display_name) in &important_params {
+                                if let Some(value) = row_data.get(*key) {
+                                    ui.label(format!("{}:", display_name));
+                                    ui.label(value);
+                                    ui.end_row();
+                                }
+                            }
+                        });
+                });
+            
+            ui.separator();
+            
+            ui.horizontal(|ui| {
+                
+                if ui.button("PSD").clicked() {
+                    // TODO: Implement frequency domain visualization
+                    debug!("Frequency domain plot requested for: {:?}", row_data.get("meta_filename"));
+                }
+                
+                if ui.button("Spectrogram").clicked() {
+                    open_spectrogram = true;
+                }
+            });
+            
+            ui.separator();
+            ui.label("Note: Visualization functionality will load and process the actual signal data file.");
+            
+        } else {
+            ui.colored_label(egui::Color32::RED, "No row data available");
+        }
+
+        if open_spectrogram {
+            self.viz_tab = VizTab::Spectrogram;
+        }
+    }
+
+    /// Show a per-recording view, (re)opening it when the selection points
+    /// at a different recording than the one it was built for
+    fn render_recording_tab<V: RecordingView>(
+        &mut self,
+        ui: &mut egui::Ui,
+        slot: fn(&mut SigViewerApp) -> &mut Option<V>,
+    ) {
+        let Some(meta_filename) = self.selected_row_data.as_ref()
+            .and_then(|row_data| row_data.get("meta_filename"))
+            .cloned()
+        else {
+            ui.colored_label(egui::Color32::RED, "No row data available");
+            return;
+        };
+
+        let selected = self.selected_meta_path();
+        let is_current = slot(self).as_ref()
+            .is_some_and(|view| Some(view.meta_path()) == selected.as_deref());
+        if !is_current {
+            *slot(self) = None;
+            let opened = selected
+                .ok_or_else(|| anyhow::anyhow!("Could not locate {}", meta_filename))
+                .and_then(|path| V::open(&path, self.stitched_recording(&path)));
+            match opened {
+                Ok(view) => *slot(self) = Some(view),
+                Err(e) => {
+                    ui.colored_label(egui::Color32::RED, format!("Failed to open recording: {}", e));
+                    return;
+                }
+            }
+        }
+
+        if let Some(view) = slot(self).as_mut() {
+            view.show(ui);
+        }
+    }
 }
 
 // handle visualizations
 impl SigViewerApp {
+    /// Directory the loaded dataset was scanned from. A reopened dataset
+    /// file is usually saved next to its recordings.
+    fn dataset_root(&self) -> &Path {
+        let root = Path::new(&self.directory_path);
+        if root.is_file() {
+            root.parent().unwrap_or(Path::new("."))
+        } else {
+            root
+        }
+    }
+
+    /// Full path to the selected row's meta file: its `meta_path` under its
+    /// `source_root` or the loaded directory. Datasets from before
+    /// `meta_path`, and recordings inside archives, need a search of the
+    /// loaded tree by file name.
+    fn selected_meta_path(&self) -> Option<PathBuf> {
+        let row_data = self.selected_row_data.as_ref()?;
+        if let Some(meta_path) = row_data.get(META_PATH_COLUMN) {
+            let root = row_data.get(SOURCE_ROOT_COLUMN).filter(|root| !root.is_empty()).map_or(self.dataset_root(), Path::new);
+            let path = root.join(meta_path);
+            if path.is_file() {
+                return Some(path);
+            }
+        }
+        self.locate_meta_file(row_data.get("meta_filename")?)
+    }
+
+    /// The segmented capture `meta_path` is part of, from the loaded
+    /// dataset's segment columns
+    pub(crate) fn stitched_recording(&self, meta_path: &Path) -> Option<SegmentedRecording> {
+        let dataset = self.dataset.as_ref()?;
+        SegmentedRecording::from_dataset(dataset, self.dataset_root(), meta_path).ok().flatten()
+    }
+
+    /// Full path to a dataset row's meta file, by its `meta_filename`
+    fn locate_meta_file(&self, meta_filename: &str) -> Option<PathBuf> {
+        let root = self.dataset_root();
+        let direct = root.join(meta_filename);
+        if direct.exists() {
+            return Some(direct);
+        }
+        SigMFDataset::find_meta_file(root, meta_filename)
+    }
+
     fn open_in_inspectrum(&self) {
         if let Some(ref row_data) = self.selected_row_data {
-            if let Some(meta_filename) = row_data.get("meta_filename") {
+            if row_data.contains_key("meta_filename") {
                 // Get the full path to the meta file
-                let meta_path = std::path::Path::new(&self.directory_path).join(meta_filename);
-                
-                // Launch inspectrum with the meta file path
-                match std::process::Command::new("inspectrum")
-                    .arg(meta_path.to_string_lossy().to_string())
-                    .spawn()
-                {
-                    Ok(_) => {
-                        println!("Launched inspectrum with: {}", meta_path.display());
-                    }
-                    Err(e) => {
-                        println!("Failed to launch inspectrum");
-                    }
+                let Some(meta_path) = self.selected_meta_path() else {
+                    warn!("Could not locate meta file for selected row");
+                    return;
+                };
+                match self.open_recording_viewer(&meta_path) {
+                    Ok(()) => info!("Launched inspectrum with: {}", meta_path.display()),
+                    Err(e) => warn!("{}", e),
                 }
             } else {
-                println!("No meta filename found in selected row data");
+                debug!("No meta filename found in selected row data");
             }
         } else {
-            println!("No row selected or row data not available");
+            debug!("No row selected or row data not available");
         }
     }
 }
\ No newline at end of file
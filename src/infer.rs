@@ -0,0 +1,246 @@
+//! Running an ONNX classifier over recordings.
+//!
+//! Each summary row (an ML annotation, or a recording without any) gets one
+//! snippet of IQ: centered in the annotation, or in the middle of the
+//! recording. The snippet goes to the model as float32 I and Q, either as
+//! two channels (`[1, 2, N]`) or interleaved (`[1, N, 2]`), and the model's
+//! output is taken as one score per class, turned into probabilities with a
+//! softmax unless it already is. The probabilities become `ml_<name>_<class>`
+//! columns of the dataset and/or `ds:customClassifierProbs` entries of the
+//! annotations. Running models needs the `onnx` feature (tract).
+
+use crate::parser::sigmf::{normalize, update_meta, Journal};
+use crate::parser::{SampleReader, SigMFDataset, SigMFParser};
+use anyhow::Result;
+use num_complex::Complex;
+use polars::prelude::*;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// How a snippet is laid out in the model's input tensor
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum InputLayout {
+    /// `[1, 2, N]`: all I, then all Q
+    #[default]
+    Channels,
+    /// `[1, N, 2]`: I, Q, I, Q, ...
+    Interleaved,
+}
+
+impl InputLayout {
+    pub fn from_string(s: &str) -> Result<Self> {
+        match s {
+            "channels" => Ok(InputLayout::Channels),
+            "interleaved" => Ok(InputLayout::Interleaved),
+            _ => anyhow::bail!("Unknown input layout '{}': use channels or interleaved", s),
+        }
+    }
+
+    /// Shape of the input tensor for `len` samples
+    pub fn shape(&self, len: usize) -> [usize; 3] {
+        match self {
+            InputLayout::Channels => [1, 2, len],
+            InputLayout::Interleaved => [1, len, 2],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ClassifierOptions {
+    /// Samples per snippet, the model's input length
+    pub snippet_len: usize,
+    pub layout: InputLayout,
+    /// Scale each snippet to unit RMS power first
+    pub normalize: bool,
+}
+
+impl Default for ClassifierOptions {
+    fn default() -> Self {
+        ClassifierOptions { snippet_len: 1024, layout: InputLayout::Channels, normalize: false }
+    }
+}
+
+/// An ONNX model and the classes its outputs stand for
+pub struct Classifier {
+    classes: Vec<String>,
+    options: ClassifierOptions,
+    #[cfg(feature = "onnx")]
+    model: tract_onnx::prelude::TypedRunnableModel<tract_onnx::prelude::TypedModel>,
+}
+
+impl Classifier {
+    #[cfg(not(feature = "onnx"))]
+    pub fn load(model_path: &Path, _classes: Vec<String>, _options: ClassifierOptions) -> Result<Self> {
+        anyhow::bail!("This build can't run ONNX models ({:?}); rebuild with `--features onnx`", model_path)
+    }
+
+    /// Load `model_path`, fixing its input to the shape of a snippet
+    #[cfg(feature = "onnx")]
+    pub fn load(model_path: &Path, classes: Vec<String>, options: ClassifierOptions) -> Result<Self> {
+        use tract_onnx::prelude::*;
+        if classes.is_empty() {
+            anyhow::bail!("At least one class is required");
+        }
+        let model = tract_onnx::onnx()
+            .model_for_path(model_path)
+            .map_err(|e| anyhow::anyhow!("Could not load {:?}: {}", model_path, e))?
+            .with_input_fact(0, f32::fact(options.layout.shape(options.snippet_len)).into())?
+            .into_optimized()?
+            .into_runnable()?;
+        Ok(Classifier { classes, options, model })
+    }
+
+    pub fn classes(&self) -> &[String] {
+        &self.classes
+    }
+
+    pub fn options(&self) -> &ClassifierOptions {
+        &self.options
+    }
+
+    /// Class probabilities for one snippet, in class order
+    #[cfg(not(feature = "onnx"))]
+    pub fn classify(&self, _snippet: &[Complex<f32>]) -> Result<Vec<f32>> {
+        anyhow::bail!("This build can't run ONNX models; rebuild with `--features onnx`")
+    }
+
+    /// Class probabilities for one snippet, in class order
+    #[cfg(feature = "onnx")]
+    pub fn classify(&self, snippet: &[Complex<f32>]) -> Result<Vec<f32>> {
+        use tract_onnx::prelude::*;
+        let input = tract_ndarray::Array::from_shape_vec(self.options.layout.shape(self.options.snippet_len), model_input(snippet, &self.options))?;
+        let outputs = self.model.run(tvec!(Tensor::from(input).into()))?;
+        let scores: Vec<f32> = outputs[0].to_array_view::<f32>()?.iter().copied().collect();
+        if scores.len() != self.classes.len() {
+            anyhow::bail!("The model gives {} outputs for {} classes", scores.len(), self.classes.len());
+        }
+        Ok(to_probabilities(scores))
+    }
+}
+
+/// The model input for `snippet`: zero-padded or cut to the snippet length,
+/// optionally normalized, laid out per `options.layout`
+pub fn model_input(snippet: &[Complex<f32>], options: &ClassifierOptions) -> Vec<f32> {
+    let len = options.snippet_len;
+    let mut samples: Vec<Complex<f32>> = snippet.iter().copied().take(len).collect();
+    samples.resize(len, Complex::new(0.0, 0.0));
+    if options.normalize {
+        let rms = (samples.iter().map(|s| s.norm_sqr()).sum::<f32>() / len.max(1) as f32).sqrt();
+        if rms > 0.0 {
+            samples.iter_mut().for_each(|s| *s /= rms);
+        }
+    }
+    match options.layout {
+        InputLayout::Channels => samples.iter().map(|s| s.re).chain(samples.iter().map(|s| s.im)).collect(),
+        InputLayout::Interleaved => samples.iter().flat_map(|s| [s.re, s.im]).collect(),
+    }
+}
+
+/// Scores as probabilities: kept if they already are (each in [0, 1],
+/// summing to about 1), else through a softmax
+pub fn to_probabilities(scores: Vec<f32>) -> Vec<f32> {
+    let sum: f32 = scores.iter().sum();
+    if scores.iter().all(|s| (0.0..=1.0).contains(s)) && (sum - 1.0).abs() < 1e-3 {
+        return scores;
+    }
+    let max = scores.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+    let exps: Vec<f32> = scores.iter().map(|s| (s - max).exp()).collect();
+    let total: f32 = exps.iter().sum();
+    exps.into_iter().map(|e| e / total).collect()
+}
+
+/// Where the snippet of each summary row of `parser` starts: centered in
+/// each ML annotation, or in the recording when it has none
+pub fn snippet_starts(parser: &SigMFParser, num_samples: u64, len: usize) -> Vec<u64> {
+    let centered = |start: u64, count: u64| start + count.saturating_sub(len as u64) / 2;
+    let annotations = parser.metadata.annotations.as_deref().unwrap_or_default();
+    let indices = parser.ml_annotation_indices();
+    if indices.is_empty() {
+        return vec![centered(0, num_samples)];
+    }
+    indices
+        .into_iter()
+        .map(|idx| centered(annotations[idx].sample_start, annotations[idx].sample_count))
+        .collect()
+}
+
+/// Class probabilities for each summary row of one recording
+#[derive(Debug, Clone)]
+pub struct RecordingInference {
+    pub meta_path: PathBuf,
+    pub probs: Vec<Vec<f32>>,
+}
+
+pub fn infer_recording(meta_path: &Path, classifier: &Classifier) -> Result<RecordingInference> {
+    let parser = SigMFParser::from_meta_file(meta_path)?;
+    let mut reader = parser.open_reader()?;
+    let len = classifier.options().snippet_len;
+    let probs = snippet_starts(&parser, reader.num_samples(), len)
+        .into_iter()
+        .map(|start| classifier.classify(&reader.read_samples(start, len)?))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(RecordingInference { meta_path: meta_path.to_path_buf(), probs })
+}
+
+/// `ml_<name>_<class>` for each class, lowercase with non-alphanumerics as `_`
+pub fn probability_columns(name: &str, classes: &[String]) -> Vec<String> {
+    let clean = |s: &str| -> String { s.chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' }).collect() };
+    classes.iter().map(|class| format!("ml_{}_{}", clean(name), clean(class))).collect()
+}
+
+/// Add `columns` (see [`probability_columns`]) to `dataset`, scanned from
+/// `root`: a recording's rows, in order, get its rows' probabilities. Rows
+/// of recordings without results are null. Recordings are matched on their
+/// meta file's path.
+pub fn append_probabilities(mut dataset: DataFrame, root: &Path, results: &[RecordingInference], columns: &[String]) -> Result<DataFrame> {
+    let by_path: HashMap<PathBuf, &RecordingInference> = results.iter().map(|result| (normalize(&result.meta_path), result)).collect();
+    let paths = SigMFDataset::row_meta_paths(&dataset, root)?;
+    let mut seen: HashMap<PathBuf, usize> = HashMap::new();
+    let mut values: Vec<Vec<Option<f64>>> = vec![Vec::with_capacity(dataset.height()); columns.len()];
+    for path in paths {
+        let path = path.map(|path| normalize(&path)).unwrap_or_default();
+        let row = seen.entry(path.clone()).or_default();
+        let probs = by_path.get(&path).and_then(|result| result.probs.get(*row));
+        *row += 1;
+        for (class, column) in values.iter_mut().enumerate() {
+            column.push(probs.and_then(|probs| probs.get(class)).map(|p| *p as f64));
+        }
+    }
+    for (name, column) in columns.iter().zip(values) {
+        dataset.with_column(Series::new(name.as_str().into(), column))?;
+    }
+    Ok(dataset)
+}
+
+/// Write a recording's probabilities into its ML annotations as
+/// `ds:customClassifierProbs` entries, replacing entries of the same
+/// classes. A recording without ML annotations is left alone. Returns the
+/// number of annotations written.
+pub fn write_annotation_probs(result: &RecordingInference, classes: &[String], journal: &Journal) -> Result<usize> {
+    let parser = SigMFParser::from_meta_file(&result.meta_path)?;
+    let indices = parser.ml_annotation_indices();
+    if indices.is_empty() {
+        return Ok(0);
+    }
+    update_meta(&result.meta_path, journal, |metadata| {
+        let annotations = metadata
+            .get_mut("annotations")
+            .and_then(Value::as_array_mut)
+            .ok_or_else(|| anyhow::anyhow!("Missing annotations in {:?}", result.meta_path))?;
+        for (idx, probs) in indices.iter().zip(&result.probs) {
+            let annotation = annotations
+                .get_mut(*idx)
+                .and_then(Value::as_object_mut)
+                .ok_or_else(|| anyhow::anyhow!("Annotation {} of {:?} changed while inferring", idx, result.meta_path))?;
+            let entries = annotation.entry("ds:customClassifierProbs").or_insert_with(|| json!([]));
+            let entries = entries
+                .as_array_mut()
+                .ok_or_else(|| anyhow::anyhow!("ds:customClassifierProbs of annotation {} is not a list", idx))?;
+            entries.retain(|entry| entry.get("className").and_then(Value::as_str).is_none_or(|name| !classes.iter().any(|c| c == name)));
+            entries.extend(classes.iter().zip(probs).map(|(class, prob)| json!({ "className": class, "classProb": prob })));
+        }
+        Ok(())
+    })?;
+    Ok(indices.len().min(result.probs.len()))
+}
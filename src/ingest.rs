@@ -0,0 +1,204 @@
+//! Rolling a live IQ stream from the network into SigMF recordings.
+//!
+//! GNU Radio's UDP sink and ZeroMQ sinks (PUB or PUSH, without tags) send
+//! bare samples, so what arrives is appended to the current recording as-is,
+//! in the format given. Every `roll` worth of samples the recording is
+//! finished (see [`RecordingWriter`]) and the next one started, so a
+//! directory watched by `dataset --watch` or the GUI picks up each one as it
+//! is done. ZeroMQ needs libzmq and the `zmq` feature.
+
+use crate::capture::{now_datetime, RecordingInfo, RecordingWriter};
+use crate::parser::sigmf::SigMFDataType;
+use anyhow::Result;
+use std::net::UdpSocket;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+/// Where samples come from
+#[derive(Debug, Clone, PartialEq)]
+pub enum IngestSource {
+    /// Address to receive datagrams on, e.g. `0.0.0.0:5000`
+    Udp(String),
+    /// Endpoint to connect a SUB socket to, e.g. `tcp://127.0.0.1:5555`
+    ZmqSub(String),
+    /// Endpoint to connect a PULL socket to
+    ZmqPull(String),
+}
+
+impl IngestSource {
+    pub fn describe(&self) -> String {
+        match self {
+            IngestSource::Udp(address) => format!("udp://{}", address),
+            IngestSource::ZmqSub(endpoint) => format!("{} (ZeroMQ SUB)", endpoint),
+            IngestSource::ZmqPull(endpoint) => format!("{} (ZeroMQ PULL)", endpoint),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct IngestSettings {
+    pub source: IngestSource,
+    /// Format of the incoming samples, kept in the recordings
+    pub data_type: SigMFDataType,
+    pub sample_rate: f64,
+    pub frequency: Option<f64>,
+    /// Length of each recording
+    pub roll: Duration,
+    pub out_dir: PathBuf,
+    /// Recordings are named `<prefix>_<start time>`
+    pub prefix: String,
+    /// Stop after this long; run until killed otherwise
+    pub duration: Option<Duration>,
+}
+
+/// A finished recording
+#[derive(Debug, Clone)]
+pub struct RolledRecording {
+    pub meta_path: PathBuf,
+    pub num_samples: u64,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct IngestReport {
+    pub recordings: usize,
+    pub samples: u64,
+    /// Bytes of messages that didn't end on a whole sample
+    pub dropped_bytes: u64,
+}
+
+/// Receive `settings.source` into rolling recordings, calling `on_roll` as
+/// each one is finished. The last one is finished with whatever it has when
+/// `settings.duration` is up.
+pub fn ingest(settings: &IngestSettings, mut on_roll: impl FnMut(&RolledRecording)) -> Result<IngestReport> {
+    if settings.sample_rate <= 0.0 {
+        anyhow::bail!("The sample rate must be positive");
+    }
+    let sample_size = settings.data_type.sample_size_bytes() as u64;
+    let roll_bytes = ((settings.roll.as_secs_f64() * settings.sample_rate).round() as u64).max(1) * sample_size;
+    let mut receiver = Receiver::open(&settings.source)?;
+    std::fs::create_dir_all(&settings.out_dir)?;
+
+    let started = Instant::now();
+    let mut report = IngestReport::default();
+    let mut current: Option<(RecordingWriter, RecordingInfo)> = None;
+    let mut message = Vec::new();
+    let mut finish = |(writer, info): (RecordingWriter, RecordingInfo), report: &mut IngestReport| -> Result<()> {
+        let num_samples = writer.len() / sample_size;
+        let (meta_path, _) = writer.finish(&info)?;
+        report.recordings += 1;
+        report.samples += num_samples;
+        on_roll(&RolledRecording { meta_path, num_samples });
+        Ok(())
+    };
+
+    while settings.duration.is_none_or(|duration| started.elapsed() < duration) {
+        if !receiver.recv(&mut message)? {
+            continue;
+        }
+        // Sinks send whole samples; carrying a partial one over would
+        // misalign everything after a lost datagram
+        let whole = message.len() - message.len() % sample_size as usize;
+        report.dropped_bytes += (message.len() - whole) as u64;
+        let mut bytes = &message[..whole];
+        while !bytes.is_empty() {
+            let (writer, _) = match current.as_mut() {
+                Some(current) => current,
+                None => current.insert(start_recording(settings)?),
+            };
+            let take = bytes.len().min((roll_bytes - writer.len()) as usize);
+            writer.write(&bytes[..take])?;
+            bytes = &bytes[take..];
+            if writer.len() >= roll_bytes {
+                finish(current.take().expect("a recording is open"), &mut report)?;
+            }
+        }
+    }
+    if let Some(current) = current.take() {
+        finish(current, &mut report)?;
+    }
+    Ok(report)
+}
+
+fn start_recording(settings: &IngestSettings) -> Result<(RecordingWriter, RecordingInfo)> {
+    let datetime = now_datetime();
+    // 2024-05-01T12:00:00.123456Z → 20240501T120000.123Z
+    let stamp: String = datetime.chars().filter(|c| !matches!(c, '-' | ':')).take(19).collect();
+    let writer = RecordingWriter::create(&settings.out_dir.join(format!("{}_{}Z", settings.prefix, stamp)))?;
+    let info = RecordingInfo {
+        data_type: settings.data_type.clone(),
+        sample_rate: settings.sample_rate,
+        frequency: settings.frequency,
+        datetime,
+        hardware: None,
+        description: Some(format!("Streamed from {}", settings.source.describe())),
+        gain: None,
+        agc: None,
+    };
+    Ok((writer, info))
+}
+
+/// One message at a time from the source, with a timeout so the caller can
+/// check the clock
+enum Receiver {
+    Udp(UdpSocket),
+    #[cfg(feature = "zmq")]
+    Zmq(zmq::Socket),
+}
+
+const RECV_TIMEOUT: Duration = Duration::from_millis(200);
+
+impl Receiver {
+    fn open(source: &IngestSource) -> Result<Self> {
+        match source {
+            IngestSource::Udp(address) => {
+                let socket = UdpSocket::bind(address).map_err(|e| anyhow::anyhow!("Could not listen on {}: {}", address, e))?;
+                socket.set_read_timeout(Some(RECV_TIMEOUT))?;
+                Ok(Receiver::Udp(socket))
+            }
+            #[cfg(feature = "zmq")]
+            IngestSource::ZmqSub(endpoint) | IngestSource::ZmqPull(endpoint) => {
+                let context = zmq::Context::new();
+                let socket = if matches!(source, IngestSource::ZmqSub(_)) {
+                    let socket = context.socket(zmq::SUB)?;
+                    socket.set_subscribe(b"")?;
+                    socket
+                } else {
+                    context.socket(zmq::PULL)?
+                };
+                socket.set_rcvtimeo(RECV_TIMEOUT.as_millis() as i32)?;
+                socket.connect(endpoint).map_err(|e| anyhow::anyhow!("Could not connect to {}: {}", endpoint, e))?;
+                Ok(Receiver::Zmq(socket))
+            }
+            #[cfg(not(feature = "zmq"))]
+            IngestSource::ZmqSub(_) | IngestSource::ZmqPull(_) => {
+                anyhow::bail!("This build can't receive ZeroMQ; rebuild with `--features zmq`")
+            }
+        }
+    }
+
+    /// Replace `message` with the next one; false on timeout
+    fn recv(&mut self, message: &mut Vec<u8>) -> Result<bool> {
+        match self {
+            Receiver::Udp(socket) => {
+                message.resize(65536, 0);
+                match socket.recv(message) {
+                    Ok(len) => {
+                        message.truncate(len);
+                        Ok(true)
+                    }
+                    Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => Ok(false),
+                    Err(e) => Err(e.into()),
+                }
+            }
+            #[cfg(feature = "zmq")]
+            Receiver::Zmq(socket) => match socket.recv_bytes(0) {
+                Ok(bytes) => {
+                    *message = bytes;
+                    Ok(true)
+                }
+                Err(zmq::Error::EAGAIN) => Ok(false),
+                Err(e) => Err(e.into()),
+            },
+        }
+    }
+}
@@ -0,0 +1,432 @@
+//! Declarative batch pipeline for `sig_viewer_cli run jobs.toml`.
+//!
+//! A job file lists the steps of a nightly run (scan → validate → filter →
+//! features → thumbnails → export → report), each with its own options, so
+//! processing new captures is one cron entry:
+//!
+//! ```toml
+//! [scan]
+//! dir = "/data/captures"
+//! verify_checksums = true
+//!
+//! [validate]
+//! drop_invalid = true
+//!
+//! [[filter]]
+//! column = "snr_db"
+//! min = 10.0
+//!
+//! [features]
+//! casts = ["gain=f64"]
+//!
+//! [thumbnails]
+//! dir = "thumbnails"
+//!
+//! [export]
+//! path = "exports/dataset-{date}.parquet"
+//!
+//! [report]
+//! path = "exports/report-{date}.txt"
+//! group_by = [{ key = "sdr_handle", aggregations = "count,mean:snr_db" }]
+//! ```
+//!
+//! Relative paths are resolved against the job file's directory.
+
+use crate::data_ops::{
+    apply_sensor_profiles, cast_columns, group_by, load_sensor_profiles, parse_cast_spec, write_dataset, Aggregation, ExportFormat, GroupKey,
+    Provenance,
+};
+use crate::parser::sigmf::{normalize, validate_path, DatasetOptions, Granularity, SigMFDataset};
+use crate::viz::{default_thumbnail_dir, thumbnail_dataset, ThumbnailParams, ThumbnailSummary};
+use crate::workers::WorkerPool;
+use anyhow::Result;
+use polars::prelude::*;
+use serde::Deserialize;
+use serde_json::Value;
+use tracing::info;
+use std::collections::HashSet;
+use std::fmt::Write;
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct JobFile {
+    pub scan: ScanStep,
+    pub validate: Option<ValidateStep>,
+    #[serde(default)]
+    pub filter: Vec<FilterRule>,
+    pub features: Option<FeaturesStep>,
+    pub thumbnails: Option<ThumbnailsStep>,
+    pub export: Option<ExportStep>,
+    pub report: Option<ReportStep>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScanStep {
+    pub dir: PathBuf,
+    #[serde(default)]
+    pub verify_checksums: bool,
+    /// Fill the `noise_floor_db` column from the samples
+    #[serde(default)]
+    pub noise_floor: bool,
+    /// Add the `measured_*` columns from the samples
+    #[serde(default)]
+    pub compute_metrics: bool,
+    /// Add `ann_*` columns for unrecognized annotation fields
+    #[serde(default)]
+    pub annotation_fields: bool,
+    /// JSON file of sensor profiles whose offsets are applied to the rows
+    pub profiles: Option<PathBuf>,
+    /// "annotation" (default), "capture" or "file"
+    #[serde(default)]
+    pub granularity: Granularity,
+    /// Reuse rows of files unchanged since the last run (default true)
+    #[serde(default = "default_cache")]
+    pub cache: bool,
+}
+
+fn default_cache() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ValidateStep {
+    /// Stop the job (nonzero exit) if any recording fails validation
+    #[serde(default)]
+    pub fail_on_invalid: bool,
+    /// Leave rows of recordings that fail validation out of the dataset
+    #[serde(default)]
+    pub drop_invalid: bool,
+}
+
+/// Keep rows where `column` is within [min, max] and/or equal to `equals`
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FilterRule {
+    pub column: String,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub equals: Option<Value>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FeaturesStep {
+    /// Column casts as `column=type`, like `--cast`
+    #[serde(default)]
+    pub casts: Vec<String>,
+}
+
+/// Render a spectrogram thumbnail per recording and add a `thumbnail_path`
+/// column
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ThumbnailsStep {
+    /// Where the PNGs go; defaults to the cache directory the GUI reads
+    pub dir: Option<PathBuf>,
+    #[serde(default = "default_thumbnail_width")]
+    pub width: usize,
+    #[serde(default = "default_thumbnail_height")]
+    pub height: usize,
+    /// Render again even where a thumbnail is cached
+    #[serde(default)]
+    pub force: bool,
+}
+
+fn default_thumbnail_width() -> usize {
+    ThumbnailParams::default().width
+}
+
+fn default_thumbnail_height() -> usize {
+    ThumbnailParams::default().height
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ExportStep {
+    /// Output file; `{date}` expands to the run date (YYYY-MM-DD)
+    pub path: PathBuf,
+    /// csv, parquet, ndjson, geojson or kml; defaults to the path's extension
+    pub format: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReportStep {
+    /// Report file; printed to stdout when omitted. `{date}` as for export.
+    pub path: Option<PathBuf>,
+    /// Summary tables to include
+    #[serde(default)]
+    pub group_by: Vec<ReportTable>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ReportTable {
+    /// Group key as for `GroupKey::parse`
+    pub key: String,
+    /// Comma-separated aggregations as for `Aggregation::parse_list`
+    #[serde(default = "default_aggregations")]
+    pub aggregations: String,
+}
+
+fn default_aggregations() -> String {
+    "count".to_string()
+}
+
+/// What a job run did, rendered as the report
+#[derive(Debug, Clone)]
+pub struct JobReport {
+    pub source: PathBuf,
+    pub started_at: chrono::DateTime<chrono::Utc>,
+    pub rows_scanned: usize,
+    /// Meta files that failed validation, with their violations
+    pub invalid: Vec<(PathBuf, Vec<String>)>,
+    pub rows_dropped_invalid: usize,
+    pub rows_filtered: usize,
+    pub thumbnails: Option<ThumbnailSummary>,
+    pub rows_exported: Option<(PathBuf, usize)>,
+    pub tables: Vec<(String, DataFrame)>,
+}
+
+impl FilterRule {
+    fn expr(&self) -> Result<Expr> {
+        let mut conditions = Vec::new();
+        if let Some(min) = self.min {
+            conditions.push(col(&self.column).cast(DataType::Float64).gt_eq(lit(min)));
+        }
+        if let Some(max) = self.max {
+            conditions.push(col(&self.column).cast(DataType::Float64).lt_eq(lit(max)));
+        }
+        match &self.equals {
+            None => {}
+            Some(Value::String(s)) => conditions.push(col(&self.column).eq(lit(s.clone()))),
+            Some(Value::Bool(b)) => conditions.push(col(&self.column).eq(lit(*b))),
+            Some(Value::Number(n)) => {
+                let n = n.as_f64().unwrap_or(f64::NAN);
+                conditions.push(col(&self.column).cast(DataType::Float64).eq(lit(n)));
+            }
+            Some(other) => anyhow::bail!("Filter on {}: can't compare with {}", self.column, other),
+        }
+        conditions
+            .into_iter()
+            .reduce(|a, b| a.and(b))
+            .ok_or_else(|| anyhow::anyhow!("Filter on {} needs min, max or equals", self.column))
+    }
+
+    /// Same form as the GUI's filter description, e.g. `snr_db >= 10`
+    pub fn describe(&self) -> String {
+        let mut clauses = Vec::new();
+        if let Some(min) = self.min {
+            clauses.push(format!("{} >= {}", self.column, min));
+        }
+        if let Some(max) = self.max {
+            clauses.push(format!("{} <= {}", self.column, max));
+        }
+        match &self.equals {
+            Some(Value::String(s)) => clauses.push(format!("{} == '{}'", self.column, s)),
+            Some(value) => clauses.push(format!("{} == {}", self.column, value)),
+            None => {}
+        }
+        clauses.join(" AND ")
+    }
+}
+
+impl JobFile {
+    /// Read a TOML job file, resolving relative paths against its directory
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Cannot read job file {:?}: {}", path, e))?;
+        let mut job: JobFile = toml::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Invalid job file {:?}: {}", path, e))?;
+
+        let base = path.parent().unwrap_or(Path::new("."));
+        job.scan.dir = base.join(&job.scan.dir);
+        job.scan.profiles = job.scan.profiles.as_ref().map(|p| base.join(p));
+        if let Some(ref mut thumbnails) = job.thumbnails {
+            thumbnails.dir = thumbnails.dir.as_ref().map(|p| base.join(p));
+        }
+        if let Some(ref mut export) = job.export {
+            export.path = base.join(&export.path);
+        }
+        if let Some(ref mut report) = job.report {
+            report.path = report.path.as_ref().map(|p| base.join(p));
+        }
+        Ok(job)
+    }
+
+    /// Execute every configured step in order
+    pub fn run(&self) -> Result<JobReport> {
+        let started_at = chrono::Utc::now();
+        let date = started_at.format("%Y-%m-%d").to_string();
+
+        info!("[scan] {}", self.scan.dir.display());
+        let options = DatasetOptions {
+            granularity: self.scan.granularity,
+            verify_checksums: self.scan.verify_checksums,
+            estimate_noise_floor: self.scan.noise_floor,
+            compute_metrics: self.scan.compute_metrics,
+            annotation_extra_columns: self.scan.annotation_fields,
+            cache: self.scan.cache,
+            ..DatasetOptions::default()
+        };
+        let mut dataset = SigMFDataset::from_directory_with_options(&self.scan.dir, &options)?;
+        if let Some(ref profiles) = self.scan.profiles {
+            dataset = apply_sensor_profiles(dataset, &load_sensor_profiles(profiles)?)?;
+        }
+        let mut report = JobReport {
+            source: self.scan.dir.clone(),
+            started_at,
+            rows_scanned: dataset.height(),
+            invalid: Vec::new(),
+            rows_dropped_invalid: 0,
+            rows_filtered: 0,
+            thumbnails: None,
+            rows_exported: None,
+            tables: Vec::new(),
+        };
+
+        if let Some(ref validate) = self.validate {
+            report.invalid = validate_path(&self.scan.dir)?
+                .into_iter()
+                .filter(|r| !r.is_valid())
+                .map(|r| (r.meta_path, r.violations))
+                .collect();
+            info!("[validate] {} invalid recordings", report.invalid.len());
+            if validate.fail_on_invalid && !report.invalid.is_empty() {
+                anyhow::bail!("{} recordings failed validation", report.invalid.len());
+            }
+            if validate.drop_invalid && !report.invalid.is_empty() {
+                let invalid_paths: HashSet<PathBuf> = report.invalid.iter().map(|(path, _)| normalize(path)).collect();
+                let before = dataset.height();
+                let keep: BooleanChunked = SigMFDataset::row_meta_paths(&dataset, &self.scan.dir)?
+                    .into_iter()
+                    .map(|path| Some(!path.is_some_and(|path| invalid_paths.contains(&normalize(&path)))))
+                    .collect();
+                dataset = dataset.filter(&keep)?;
+                report.rows_dropped_invalid = before - dataset.height();
+            }
+        }
+
+        if !self.filter.is_empty() {
+            let before = dataset.height();
+            let mut lf = dataset.lazy();
+            for rule in &self.filter {
+                lf = lf.filter(rule.expr()?);
+            }
+            dataset = lf.collect()?;
+            report.rows_filtered = before - dataset.height();
+            info!("[filter] {} rows removed", report.rows_filtered);
+        }
+
+        let cast_specs = self.features.as_ref().map(|f| f.casts.clone()).unwrap_or_default();
+        if !cast_specs.is_empty() {
+            let casts = cast_specs.iter().map(|spec| parse_cast_spec(spec)).collect::<Result<Vec<_>>>()?;
+            dataset = cast_columns(dataset.lazy(), &casts)?.collect()?;
+            info!("[features] {} casts applied", casts.len());
+        }
+
+        if let Some(ref thumbnails) = self.thumbnails {
+            if thumbnails.width == 0 || thumbnails.height == 0 {
+                anyhow::bail!("Thumbnails need a width and height of at least one pixel");
+            }
+            let dir = thumbnails.dir.clone().unwrap_or_else(default_thumbnail_dir);
+            let params = ThumbnailParams { width: thumbnails.width, height: thumbnails.height, ..ThumbnailParams::default() };
+            let (with_thumbnails, summary) =
+                thumbnail_dataset(&dataset, &self.scan.dir, &dir, &params, thumbnails.force, &WorkerPool::with_default_threads())?;
+            dataset = with_thumbnails;
+            info!("[thumbnails] {} rendered, {} cached, {} failed", summary.rendered, summary.reused, summary.failed.len());
+            report.thumbnails = Some(summary);
+        }
+
+        if let Some(ref export) = self.export {
+            let path = expand_date(&export.path, &date);
+            let format = match export.format {
+                Some(ref format) => ExportFormat::from_string(format)?,
+                None => ExportFormat::from_path(&path).unwrap_or(ExportFormat::Csv),
+            };
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            write_dataset(&mut dataset.clone(), &path, format)?;
+
+            let mut provenance = Provenance::new(&self.scan.dir.to_string_lossy(), started_at, dataset.height());
+            provenance.row_per = self.scan.granularity.as_str().to_string();
+            let filters: Vec<String> = self.filter.iter().map(FilterRule::describe).collect();
+            provenance.filter = (!filters.is_empty()).then(|| filters.join(" AND "));
+            provenance.casts = cast_specs;
+            provenance.write(&path)?;
+            info!("[export] {} rows to {} ({})", dataset.height(), path.display(), format.name());
+            report.rows_exported = Some((path, dataset.height()));
+        }
+
+        if let Some(ref report_step) = self.report {
+            for table in &report_step.group_by {
+                let key = GroupKey::parse(&table.key)?;
+                let aggregations = Aggregation::parse_list(&table.aggregations)?;
+                let grouped = group_by(&dataset, &key, &aggregations)?;
+                report.tables.push((format!("{} by {}", table.aggregations, table.key), grouped));
+            }
+            let text = report.to_text();
+            match report_step.path {
+                Some(ref path) => {
+                    let path = expand_date(path, &date);
+                    if let Some(parent) = path.parent() {
+                        std::fs::create_dir_all(parent)?;
+                    }
+                    std::fs::write(&path, text)?;
+                    info!("[report] {}", path.display());
+                }
+                None => print!("{}", text),
+            }
+        }
+
+        Ok(report)
+    }
+}
+
+impl JobReport {
+    pub fn to_text(&self) -> String {
+        let mut text = String::new();
+        let _ = writeln!(text, "sig_viewer job report");
+        let _ = writeln!(text, "Source:  {}", self.source.display());
+        let _ = writeln!(text, "Started: {}", self.started_at.to_rfc3339_opts(chrono::SecondsFormat::Secs, true));
+        let _ = writeln!(text, "Rows scanned: {}", self.rows_scanned);
+        if !self.invalid.is_empty() {
+            let _ = writeln!(text, "Invalid recordings: {} ({} rows dropped)", self.invalid.len(), self.rows_dropped_invalid);
+            for (path, violations) in &self.invalid {
+                let _ = writeln!(text, "  {}", path.display());
+                for violation in violations {
+                    let _ = writeln!(text, "    - {}", violation);
+                }
+            }
+        }
+        let _ = writeln!(text, "Rows removed by filters: {}", self.rows_filtered);
+        if let Some(ref thumbnails) = self.thumbnails {
+            let _ = writeln!(
+                text,
+                "Thumbnails: {} rendered, {} already cached, {} failed",
+                thumbnails.rendered,
+                thumbnails.reused,
+                thumbnails.failed.len()
+            );
+            for (name, error) in &thumbnails.failed {
+                let _ = writeln!(text, "  {}: {}", name, error);
+            }
+        }
+        if let Some((ref path, rows)) = self.rows_exported {
+            let _ = writeln!(text, "Exported: {} rows to {}", rows, path.display());
+        }
+        for (title, table) in &self.tables {
+            let _ = writeln!(text, "\n{}\n{}", title, table);
+        }
+        text
+    }
+}
+
+fn expand_date(path: &Path, date: &str) -> PathBuf {
+    PathBuf::from(path.to_string_lossy().replace("{date}", date))
+}
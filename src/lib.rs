@@ -1,4 +1,13 @@
 pub mod parser;
-// pub mod data_ops;
-// pub mod viz;
+pub mod data_ops;
+pub mod viz;
+pub mod jobs;
+pub mod workers;
+pub mod capture;
+pub mod ingest;
+pub mod infer;
+pub mod detect;
+pub mod demod;
+pub mod dsp;
+pub mod tui;
 // pub mod file_picker;
\ No newline at end of file
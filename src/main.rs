@@ -1,16 +1,59 @@
-mod parser;
-//mod data_ops;
-//mod viz;
-//mod file_picker;
-
 use clap::{Parser, Subcommand};
 use anyhow::Result;
-use parser::{FileParser, SigMFDataset};
+use sig_viewer::data_ops::{
+    apply_derived_columns, apply_sensor_profiles, cast_columns, cluster, continuity_report, default_cluster_columns, describe, diff_datasets, filter_geo, filter_watchlists, find_preset, format_duration, format_frequency, group_by, histogram, load_saved_presets, load_sensor_profiles, migrate_dataset, noise_floor_trend, occupancy, parse_cast_spec, scan_dataset, set_band_map, split_dataset, update_dataset,
+    with_cluster_column, with_split_column, write_dataset, Aggregation, BandMap, ClusterMethod, ClusterOptions, DerivedColumns, DirectoryWatcher, ExportFormat, GeoFilter, GroupKey, Metric, Provenance, SplitRatios, TimeBucket, Watchlist, DEFAULT_DIFF_KEY, DEFAULT_NOISE_STEP_DB,
+    CLUSTER_COLUMN, DBSCAN_NOISE, DEFAULT_CONTINUITY_TOLERANCE_S, SPLIT_NAMES,
+};
+use sig_viewer::parser::{FileParser, SigMFDataset, SigMFParser};
+use sig_viewer::parser::raw::{set_raw_iq_defaults, RawIqParams};
+use sig_viewer::parser::vrt::record_udp;
+use sig_viewer::parser::sigmf::{
+    add_annotation, channelize_recording, compress_data_file, convert_recording, organize_recording, copy_recording, extract_slice, group_segments, list_batches, pack_archive, read_entries, rollback, set_parse_mode, set_read_only, validate_path,
+    parse_field_value, qualify_key, set_data_roots, set_global_fields, write_sha512, ChecksumStatus, Compression, DataRoots, DatasetOptions, GlobalEdit, Granularity, Journal, ParseMode, ScanProgress, Segment, SigMFDataType,
+    OrganizeMode, OrganizeTemplate, OrganizedRecording, SliceRange,
+    PROTECTED_GLOBAL_FIELDS,
+};
+use sig_viewer::capture::{capture, CaptureSettings};
+use sig_viewer::demod::{demodulate, likeliest_analog, write_wav, DemodMode, DemodParams};
+use sig_viewer::dsp::ChannelParams;
+use sig_viewer::detect::{bursts_dataframe, detect_recording, write_bursts, DetectParams};
+use sig_viewer::infer::{append_probabilities, infer_recording, probability_columns, write_annotation_probs, Classifier, ClassifierOptions, InputLayout};
+use sig_viewer::ingest::{ingest, IngestSettings, IngestSource};
+use sig_viewer::jobs::JobFile;
+use sig_viewer::workers::{Priority, WorkerPool};
+use sig_viewer::viz::{
+    ascii_histogram, default_thumbnail_dir, edge_precision, thumbnail_dataset, write_histogram_png, Constellation, DeepLink, FreqCorrection, ThumbnailParams,
+    THUMBNAIL_COLUMN,
+};
+use indicatif::{ProgressBar, ProgressState, ProgressStyle};
+use std::io::IsTerminal;
+use std::sync::Mutex;
+use tracing::warn;
+use tracing_subscriber::filter::{LevelFilter, Targets};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
 use polars::prelude::*;
 #[derive(Parser)]
 #[command(name = "sig_viewer_cli")]
 #[command(about = "A CLI tool for exploring RF data files")]
 struct Cli {
+    #[arg(long, global = true, help = "Refuse anything that would modify, rename or delete recordings")]
+    read_only: bool,
+    #[arg(long = "data-root", value_name = "DIR|META=DATA", global = true,
+        help = "Where to look for data files not next to their meta file: a directory searched by file name, or META_PREFIX=DATA_PREFIX to remap (repeatable)")]
+    data_roots: Vec<String>,
+    #[arg(long, value_name = "HZ", global = true, help = "Sample rate of raw IQ files (.cfile) whose sidecar TOML doesn't give one")]
+    sample_rate: Option<f64>,
+    #[arg(long, value_name = "HZ", global = true, help = "Center frequency of raw IQ files (.cfile, .wav) whose header or sidecar TOML doesn't give one")]
+    center_freq: Option<f64>,
+    #[arg(long, global = true, help = "Repair non-compliant meta files (missing core:version, numbers as text, malformed geolocation) instead of skipping them; each repair goes in the parse_warnings column")]
+    lenient: bool,
+    #[arg(long, value_name = "TOML", global = true, help = "Band map file for the band and channel columns; its bands are checked before the built-in ISM 915, WiFi and GPS L1 ones")]
+    bands: Option<String>,
+    #[arg(short, long, global = true, action = clap::ArgAction::Count, help = "Log more: -v adds debug detail such as each file read, -vv everything")]
+    verbose: u8,
     #[command(subcommand)]
     command: Commands,
 }
@@ -18,23 +61,475 @@ struct Cli {
 #[derive(Subcommand)]
 enum Commands {
     Parse { 
-        #[arg(help = "File or directory to parse")]
+        #[arg(help = "File (.sigmf-meta, .sigmf archive, .sigmf-collection, raw .cfile, IQ .wav, VITA-49 .vrt or BLUE .tmp) to parse")]
         path: String 
     },
+    Info {
+        #[arg(help = "Meta file (or archive member, raw .cfile, IQ .wav, BLUE .tmp) to describe")]
+        path: String,
+    },
     Dataset {
-        #[arg(help = "Directory containing SigMF files")]
-        dir: String,
-        #[arg(long, help = "Output CSV file")]
+        #[arg(required = true, help = "Directory containing SigMF files; give several to combine them, with a source_root column")]
+        dirs: Vec<String>,
+        #[arg(long, help = "Output file (CSV, Parquet, NDJSON, or GeoJSON/KML of the located rows)")]
         output: Option<String>,
+        #[arg(long, help = "Output format: csv, parquet, ndjson, geojson or kml (default: from the output extension, else csv)")]
+        format: Option<String>,
+        #[arg(long = "cast", help = "Cast a column for this run, e.g. --cast gain=f64 (repeatable)")]
+        casts: Vec<String>,
+        #[arg(long, help = "Verify data files against core:sha512 (fills the checksum_status column)")]
+        verify: bool,
+        #[arg(long, help = "Estimate each recording's noise floor from its samples (fills the noise_floor_db column)")]
+        noise_floor: bool,
+        #[arg(long, help = "Measure each row's noise floor, peak power, 99% occupied bandwidth and SNR from the samples (adds measured_* columns)")]
+        compute_metrics: bool,
+        #[arg(long, help = "Add an ann_<field> column for every annotation field sig_viewer doesn't recognize (custom extensions)")]
+        annotation_fields: bool,
+        #[arg(long, value_name = "FILE", help = "JSON file of sensor profiles to apply (calibration and frequency offsets per sdr_handle)")]
+        profiles: Option<String>,
+        #[arg(long, value_name = "TOML", help = "Add columns computed from the others, from a TOML file of expressions such as center_freq_hz / 1e6 (see README)")]
+        derived: Option<String>,
+        #[arg(long, default_value = "annotation", help = "One row per ML annotation (annotation, with an annotation_index column), per capture segment (capture) or per file (file)")]
+        granularity: String,
+        #[arg(long = "watchlist", value_name = "CSV", help = "Keep only rows matching a watchlist (frequency ranges, labels, uuids), adding a watch_match column (repeatable)")]
+        watchlists: Vec<String>,
+        #[arg(long, value_name = "LAT,LON,KM", allow_hyphen_values = true, help = "Keep only rows recorded within KM kilometers of a point, e.g. --within 39.74,-104.99,25")]
+        within: Option<String>,
+        #[arg(long, value_name = "LAT_MIN,LON_MIN,LAT_MAX,LON_MAX", allow_hyphen_values = true, help = "Keep only rows recorded inside a latitude/longitude box (south-west corner first)")]
+        bbox: Option<String>,
+        #[arg(long, value_name = "NAME", help = "Keep only rows passing a filter preset saved in the GUI")]
+        preset: Option<String>,
+        #[arg(long, help = "Keep running and update the dataset (and --output) as recordings are added, changed or removed")]
+        watch: bool,
+        #[arg(long, help = "Parse every file again instead of reusing cached rows of files unchanged since the last scan")]
+        no_cache: bool,
+        #[arg(long, value_name = "N", help = "Preview: only read a random sample of N files")]
+        sample: Option<usize>,
+        #[arg(long, requires = "sample", help = "Random seed for --sample, to draw the same files again")]
+        seed: Option<u64>,
+        #[arg(long, short, help = "No progress bar or summary lines, only errors and the result (for scripts)")]
+        quiet: bool,
     },
     Stats {
-        #[arg(help = "Dataset CSV file")]
+        #[arg(help = "Dataset CSV or Parquet file")]
+        dataset: String,
+        #[arg(long = "cast", help = "Cast a column before computing stats, e.g. --cast gain=f64 (repeatable)")]
+        casts: Vec<String>,
+        #[arg(long, value_delimiter = ',', help = "Columns to describe, comma-separated (default: all numeric columns)")]
+        columns: Vec<String>,
+        #[arg(long, value_delimiter = ',', help = "Metrics: count, mean, std, min, max, n_unique, percentiles or pNN, comma-separated (default: count, mean, std, min, p25, p50, p75, max)")]
+        metrics: Vec<String>,
+    },
+    Hist {
+        #[arg(help = "Dataset CSV or Parquet file")]
         dataset: String,
+        #[arg(long, help = "Numeric column to bin, e.g. snr_db or center_freq_hz")]
+        column: String,
+        #[arg(long, default_value_t = 20, help = "Number of equal-width bins")]
+        bins: usize,
+        #[arg(long, help = "Scale bars by log(1 + count), for columns where a few bins dwarf the rest")]
+        log: bool,
+        #[arg(long = "cast", help = "Cast a column before binning, e.g. --cast gain=f64 (repeatable)")]
+        casts: Vec<String>,
+        #[arg(long, help = "Write the chart as a PNG instead of printing it")]
+        output: Option<String>,
+        #[arg(long, default_value_t = 800, help = "PNG width in pixels")]
+        width: usize,
+        #[arg(long, default_value_t = 400, help = "PNG height in pixels")]
+        height: usize,
+    },
+    Groupby {
+        #[arg(help = "Dataset CSV or Parquet file")]
+        dataset: String,
+        #[arg(long, help = "Group key: a column, day:column, hour:column or bin:column:width (e.g. bin:center_freq_hz:1e8)")]
+        by: String,
+        #[arg(long, default_value = "count", help = "Aggregations, comma-separated: count or mean|median|sum|min|max|std|n_unique:column")]
+        agg: String,
+        #[arg(long = "cast", help = "Cast a column before grouping, e.g. --cast gain=f64 (repeatable)")]
+        casts: Vec<String>,
+        #[arg(long, help = "Output file (CSV, Parquet or NDJSON) instead of printing the table")]
+        output: Option<String>,
+    },
+    Diff {
+        #[arg(help = "Earlier dataset CSV or Parquet file")]
+        old: String,
+        #[arg(help = "Later dataset CSV or Parquet file")]
+        new: String,
+        #[arg(long, value_delimiter = ',', help = "Columns identifying a row, comma-separated (default: meta_filename,sig_uuid)")]
+        key: Vec<String>,
+        #[arg(long, default_value_t = 20, help = "Added, removed and changed rows listed of each (0 for all)")]
+        limit: usize,
+        #[arg(long, help = "Output file (CSV, Parquet or NDJSON) with every added row, removed row and changed value")]
+        output: Option<String>,
+    },
+    Segments {
+        #[arg(help = "Directory to search for captures split into consecutive files (ds:sequence_num)")]
+        dir: String,
+    },
+    NoiseTrend {
+        #[arg(help = "Directory of recordings, or a dataset file built with --noise-floor")]
+        path: String,
+        #[arg(long, default_value_t = DEFAULT_NOISE_STEP_DB, help = "Change in dB flagged as a step or drift")]
+        threshold_db: f64,
+    },
+    Continuity {
+        #[arg(help = "Directory of recordings, or a dataset file")]
+        path: String,
+        #[arg(long, value_name = "SECONDS", default_value_t = DEFAULT_CONTINUITY_TOLERANCE_S, help = "Gap or overlap between consecutive recordings still counted as continuous")]
+        tolerance: f64,
+        #[arg(long, help = "Save the issues as a table (CSV, Parquet or NDJSON)")]
+        output: Option<String>,
+    },
+    Occupancy {
+        #[arg(help = "Directory of recordings, or a dataset file")]
+        path: String,
+        #[arg(long, default_value_t = 1e6, help = "Frequency bin width in Hz")]
+        bin_width: f64,
+        #[arg(long, default_value = "day", help = "Time buckets: day or hour (of day)")]
+        by: String,
+        #[arg(long, help = "Output file (CSV, Parquet or NDJSON) with one row per frequency bin and time bucket")]
+        output: Option<String>,
+        #[arg(long, value_name = "FILE", help = "Write the occupancy heatmap as an SVG image")]
+        svg: Option<String>,
+    },
+    Split {
+        #[arg(help = "Directory of recordings, or a dataset file")]
+        path: String,
+        #[arg(help = "Directory to write the file lists (or copies) to")]
+        out_dir: String,
+        #[arg(long, default_value = "0.8,0.1,0.1", help = "Train, validation and test fractions, comma-separated")]
+        ratios: String,
+        #[arg(long, value_name = "KEY", help = "Keep each set's share of every value of a key: a column, bin:column:width, day:column, hour:column, or dominant_class (the highest ml_* probability)")]
+        stratify: Option<String>,
+        #[arg(long, help = "Random seed, to draw the same split again")]
+        seed: Option<u64>,
+        #[arg(long, help = "Copy each set's SigMF pairs into train/, val/ and test/ instead of writing file lists")]
+        copy: bool,
+        #[arg(long, value_name = "DIR", help = "Where the recordings of a dataset file are (default: the directory it was built from)")]
+        root: Option<String>,
+        #[arg(long, help = "Also save the dataset with a split column (CSV, Parquet or NDJSON)")]
+        output: Option<String>,
+    },
+    Tui {
+        #[arg(help = "Directory of recordings, or a dataset file")]
+        path: String,
+        #[arg(long, value_name = "DIR", help = "Where the recordings of a dataset file are, for the spectrum in the row details (default: the directory it was built from)")]
+        root: Option<String>,
+    },
+    Thumbnails {
+        #[arg(help = "Directory of recordings, or a dataset file")]
+        path: String,
+        #[arg(long, value_name = "DIR", help = "Where to keep the PNGs (default: ~/.cache/sig_viewer/thumbnails, where the GUI looks)")]
+        cache_dir: Option<String>,
+        #[arg(long, default_value_t = 128, help = "Thumbnail width in pixels (frequency)")]
+        width: usize,
+        #[arg(long, default_value_t = 64, help = "Thumbnail height in pixels (time)")]
+        height: usize,
+        #[arg(long, help = "Render again even where a thumbnail is cached")]
+        force: bool,
+        #[arg(long, help = "Recordings rendered at once (default: one per CPU core)")]
+        jobs: Option<usize>,
+        #[arg(long, value_name = "DIR", help = "Where the recordings of a dataset file are (default: the directory it was built from)")]
+        root: Option<String>,
+        #[arg(long, help = "Also save the dataset with a thumbnail_path column (CSV, Parquet or NDJSON)")]
+        output: Option<String>,
+    },
+    Analyze {
+        #[command(subcommand)]
+        analysis: Analysis,
+    },
+    Validate {
+        #[arg(help = "Meta file or directory to check against the SigMF spec")]
+        path: String,
+    },
+    Checksum {
+        #[arg(help = "Meta file or directory")]
+        path: String,
+        #[arg(long, help = "Only verify existing checksums, don't write anything")]
+        check: bool,
+        #[arg(long, help = "Files hashed at once (default: one per CPU core)")]
+        jobs: Option<usize>,
+    },
+    SetMeta {
+        #[arg(help = "Meta file or directory")]
+        path: String,
+        #[arg(long = "set", value_name = "KEY=VALUE", help = "Set a global field, e.g. author=\"Jane Doe\"; keys without a namespace are core: fields (repeatable)")]
+        set: Vec<String>,
+        #[arg(long, value_name = "KEY", help = "Remove a global field (repeatable)")]
+        unset: Vec<String>,
+        #[arg(long, help = "List the files that would change without writing them")]
+        dry_run: bool,
+    },
+    Annotate {
+        #[arg(help = "SigMF meta file")]
+        meta_file: String,
+        #[arg(long, help = "First sample of the annotation")]
+        start_sample: u64,
+        #[arg(long, help = "Length of the annotation in samples")]
+        count: Option<u64>,
+        #[arg(long, help = "core:label of the annotation")]
+        label: Option<String>,
+        #[arg(long, value_name = "HZ", requires = "freq_upper", help = "Lower frequency edge in Hz")]
+        freq_lower: Option<f64>,
+        #[arg(long, value_name = "HZ", requires = "freq_lower", help = "Upper frequency edge in Hz")]
+        freq_upper: Option<f64>,
+        #[arg(long, help = "core:comment of the annotation")]
+        comment: Option<String>,
+        #[arg(long = "field", value_name = "KEY=VALUE", help = "Any other field, e.g. ds:sig_snr=12.5; keys without a namespace are core: fields (repeatable)")]
+        fields: Vec<String>,
+    },
+    Organize {
+        #[arg(help = "Meta file or directory")]
+        path: String,
+        #[arg(long, help = "Directory to organize into")]
+        out: String,
+        #[arg(long, help = "Subdirectory per recording, e.g. \"{date}/{center_freq_mhz}MHz/\"; fields: date, year, month, day, hour, center_freq_hz, center_freq_mhz, sample_rate_hz, datatype, author, hardware, label, stem, or a global field like {core:recorder}")]
+        template: String,
+        #[arg(long, help = "Copy the recordings instead of moving them")]
+        copy: bool,
+        #[arg(long, value_name = "FILE", help = "Write where each recording went (CSV, Parquet or NDJSON)")]
+        manifest: Option<String>,
+        #[arg(long, help = "List where the recordings would go without touching them")]
+        dry_run: bool,
+    },
+    Convert {
+        #[arg(help = "SigMF meta file")]
+        meta_file: String,
+        #[arg(long, help = "Target datatype: cf32_le, ci16_le, ci16_be, ci8 or cu8")]
+        to: String,
+        #[arg(long, help = "Directory for the converted recording")]
+        out: String,
+        #[arg(long, value_name = "zstd|gzip", help = "Compress the written data file (.sigmf-data.zst or .gz)")]
+        compress: Option<String>,
+    },
+    Extract {
+        #[arg(help = "SigMF meta file")]
+        meta_file: String,
+        #[arg(long, help = "Extract the extent of annotation N (0-based, in file order)")]
+        annotation: Option<usize>,
+        #[arg(long, help = "First sample of the slice")]
+        start_sample: Option<u64>,
+        #[arg(long, requires = "start_sample", help = "Samples in the slice (default: to the end)")]
+        num_samples: Option<u64>,
+        #[arg(long, help = "Slice start in seconds from the start of the recording")]
+        start_time: Option<f64>,
+        #[arg(long, requires = "start_time", help = "Slice length in seconds (default: to the end)")]
+        duration: Option<f64>,
+        #[arg(long, help = "Directory for the extracted recording")]
+        out: String,
+        #[arg(long, value_name = "zstd|gzip", help = "Compress the written data file (.sigmf-data.zst or .gz)")]
+        compress: Option<String>,
+    },
+    Channelize {
+        #[arg(help = "SigMF meta file")]
+        meta_file: String,
+        #[arg(long, value_name = "HZ", help = "Absolute center frequency of the channel")]
+        freq: Option<f64>,
+        #[arg(long, value_name = "HZ", conflicts_with = "freq", allow_hyphen_values = true, help = "Channel center relative to the recording's center (default: 0)")]
+        offset: Option<f64>,
+        #[arg(long, value_name = "HZ", help = "Channel bandwidth")]
+        bandwidth: f64,
+        #[arg(long, help = "Decimation factor (default: the largest keeping 1.25x the bandwidth)")]
+        decimation: Option<usize>,
+        #[arg(long, value_name = "HZ", help = "Resample the channel to this rate after decimating")]
+        rate: Option<f64>,
+        #[arg(long, help = "Directory for the channelized recording")]
+        out: String,
+        #[arg(long, value_name = "zstd|gzip", help = "Compress the written data file (.sigmf-data.zst or .gz)")]
+        compress: Option<String>,
+    },
+    Link {
+        #[arg(help = "SigMF meta file")]
+        meta_file: String,
+        #[arg(long, help = "Link to the extent of annotation N (0-based, in file order)")]
+        annotation: Option<usize>,
+        #[arg(long, conflicts_with = "annotation", help = "First sample to show")]
+        start_sample: Option<u64>,
+        #[arg(long, requires = "start_sample", help = "Samples to show")]
+        num_samples: Option<u64>,
+        #[arg(long, help = "Lower edge of the frequency span (Hz)")]
+        freq_lo: Option<f64>,
+        #[arg(long, help = "Upper edge of the frequency span (Hz)")]
+        freq_hi: Option<f64>,
+    },
+    Rollback {
+        #[arg(help = "Batch id to revert (default: the most recent batch not yet rolled back)")]
+        batch: Option<String>,
+        #[arg(long, help = "List journaled batches instead of rolling back")]
+        list: bool,
+        #[arg(long, help = "Revert files even if they changed again after the batch")]
+        force: bool,
+    },
+    Run {
+        #[arg(help = "Job file (TOML) describing the scan/validate/filter/features/export/report steps")]
+        job_file: String,
+    },
+    Pack {
+        #[arg(help = "Archive to create, e.g. run1.sigmf")]
+        output: String,
+        #[arg(required = true, help = "Meta files or directories to include")]
+        inputs: Vec<String>,
+    },
+    VrtRecord {
+        #[arg(help = "Capture file to append packets to, e.g. capture.vrt")]
+        output: String,
+        #[arg(long, default_value = "0.0.0.0:4991", help = "Address to receive VITA-49 UDP packets on")]
+        listen: String,
+        #[arg(long, value_name = "SECONDS", help = "Stop after this long")]
+        duration: Option<f64>,
+        #[arg(long, help = "Stop after this many packets")]
+        packets: Option<u64>,
+    },
+    Capture {
+        #[arg(long, help = "SoapySDR driver (rtlsdr, hackrf, ...) or device arguments (driver=rtlsdr,serial=...)")]
+        driver: String,
+        #[arg(long, value_name = "HZ", help = "Center frequency")]
+        freq: f64,
+        #[arg(long, value_name = "HZ", help = "Sample rate")]
+        rate: f64,
+        #[arg(long, help = "How long to record")]
+        seconds: f64,
+        #[arg(long, value_name = "DB", help = "Receive gain; automatic gain control if not given")]
+        gain: Option<f64>,
+        #[arg(long, default_value_t = 0, help = "Receive channel of the device")]
+        channel: usize,
+        #[arg(long, help = "Output name; writes <out>.sigmf-meta and <out>.sigmf-data")]
+        out: String,
+    },
+    Ingest {
+        #[arg(long, value_name = "ADDRESS", help = "Receive IQ datagrams (e.g. from GNU Radio's UDP sink) on this address, e.g. 0.0.0.0:5000")]
+        udp: Option<String>,
+        #[arg(long, value_name = "ENDPOINT", help = "Subscribe to a ZeroMQ PUB sink, e.g. tcp://127.0.0.1:5555")]
+        zmq_sub: Option<String>,
+        #[arg(long, value_name = "ENDPOINT", help = "Pull from a ZeroMQ PUSH sink")]
+        zmq_pull: Option<String>,
+        #[arg(long, value_name = "HZ", help = "Sample rate of the stream")]
+        rate: f64,
+        #[arg(long, value_name = "HZ", help = "Center frequency of the stream")]
+        freq: Option<f64>,
+        #[arg(long, default_value = "cf32_le", help = "Sample format of the stream: cf32_le, ci16_le, ci16_be, ci8 or cu8")]
+        datatype: String,
+        #[arg(long, value_name = "SECONDS", default_value_t = 60.0, help = "Length of each recording")]
+        roll: f64,
+        #[arg(long, value_name = "SECONDS", help = "Stop after this long; run until interrupted otherwise")]
+        duration: Option<f64>,
+        #[arg(long, default_value = "stream", help = "Recordings are named <prefix>_<start time>")]
+        prefix: String,
+        #[arg(help = "Directory to write recordings to")]
+        out_dir: String,
+    },
+    Infer {
+        #[arg(help = "Directory of SigMF recordings")]
+        dir: String,
+        #[arg(long, help = "ONNX classifier model")]
+        model: String,
+        #[arg(long, value_delimiter = ',', required = true, help = "Class of each model output, in order, comma-separated")]
+        classes: Vec<String>,
+        #[arg(long, help = "Name in the ml_<name>_<class> columns (default: the model file name)")]
+        name: Option<String>,
+        #[arg(long, default_value_t = 1024, help = "Samples per snippet (the model's input length)")]
+        snippet: usize,
+        #[arg(long, default_value = "channels", help = "Input layout: channels ([1, 2, N]) or interleaved ([1, N, 2])")]
+        layout: String,
+        #[arg(long, help = "Scale each snippet to unit RMS power before classifying")]
+        normalize: bool,
+        #[arg(long, help = "Save the dataset with the probability columns (CSV, Parquet or NDJSON)")]
+        output: Option<String>,
+        #[arg(long, help = "Write the probabilities into the ML annotations as ds:customClassifierProbs")]
+        write_annotations: bool,
+    },
+    Detect {
+        #[arg(help = "Meta file or directory")]
+        path: String,
+        #[arg(long, default_value_t = 1024, help = "Samples per power window, the time resolution")]
+        window: usize,
+        #[arg(long, default_value_t = 10.0, help = "dB above the noise floor a window must reach to be part of a burst")]
+        threshold_db: f32,
+        #[arg(long, value_name = "DB", help = "Noise floor in dBFS per window (default: estimated per recording)")]
+        noise_floor: Option<f32>,
+        #[arg(long, default_value_t = 0.0, value_name = "SECONDS", help = "Drop bursts shorter than this")]
+        min_duration: f64,
+        #[arg(long, default_value_t = 0.0, value_name = "SECONDS", help = "Join bursts separated by less than this")]
+        merge_gap: f64,
+        #[arg(long, default_value_t = 10.0, help = "dB below a burst's spectral peak that bounds its bandwidth")]
+        bandwidth_db: f32,
+        #[arg(long, help = "Add the bursts to the recordings' annotations")]
+        write_annotations: bool,
+        #[arg(long, help = "core:label of written annotations")]
+        label: Option<String>,
+        #[arg(long, help = "Remove annotations written by an earlier detect run first")]
+        replace: bool,
+        #[arg(long, help = "Save one row per burst (CSV, Parquet or NDJSON)")]
+        output: Option<String>,
+    },
+    Demod {
+        #[arg(help = "SigMF meta file")]
+        meta_file: String,
+        #[arg(long, default_value = "auto", help = "nbfm, wbfm, am, or auto: from the annotation with the highest ds:analogFmProb/ds:analogAmProb")]
+        mode: String,
+        #[arg(long, value_name = "HZ", help = "Frequency to tune to (default: that annotation's signal, else the recording's center)")]
+        freq: Option<f64>,
+        #[arg(long, value_name = "HZ", help = "Channel bandwidth (default: the annotation's, else 12.5 kHz NBFM, 200 kHz WBFM, 10 kHz AM)")]
+        bandwidth: Option<f64>,
+        #[arg(long, help = "First sample (default: the annotation's start, else 0)")]
+        start_sample: Option<u64>,
+        #[arg(long, help = "Number of samples (default: the annotation's, else to the end)")]
+        num_samples: Option<u64>,
+        #[arg(long, default_value_t = 48_000, help = "Audio sample rate")]
+        audio_rate: u32,
+        #[arg(long, default_value_t = 75.0, value_name = "MICROSECONDS", help = "WBFM de-emphasis time constant (75 in the Americas, 50 elsewhere)")]
+        deemphasis: f64,
+        #[arg(long, help = "WAV file to write")]
+        output: String,
+    },
+    Constellation {
+        #[arg(help = "SigMF meta file")]
+        meta_file: String,
+        #[arg(long, default_value_t = 0, help = "First sample of the range")]
+        start_sample: u64,
+        #[arg(long, default_value_t = 4096, help = "Number of samples in the range")]
+        num_samples: usize,
+        #[arg(long, help = "Coarse frequency correction power (1 = spectral peak, 2 = BPSK, 4 = QPSK)")]
+        correct: Option<u32>,
+        #[arg(long, help = "Output CSV file of I/Q points")]
+        output: String,
+    },
+}
+
+#[derive(Subcommand)]
+enum Analysis {
+    Cluster {
+        #[arg(help = "Directory of recordings, or a dataset file")]
+        path: String,
+        #[arg(long, value_delimiter = ',', help = "Numeric feature columns, comma-separated (default: snr_db, sig_bandwidth_hz and the ml_* probabilities)")]
+        columns: Vec<String>,
+        #[arg(long, default_value = "kmeans", help = "Clustering method: kmeans or dbscan")]
+        method: String,
+        #[arg(long, default_value_t = 5, help = "Number of clusters (kmeans)")]
+        k: usize,
+        #[arg(long, default_value_t = 0.5, help = "Neighborhood radius in feature units, standard deviations unless --no-standardize (dbscan)")]
+        eps: f64,
+        #[arg(long, default_value_t = 5, help = "Neighbors, itself included, that make a row a core point (dbscan)")]
+        min_points: usize,
+        #[arg(long, help = "Cluster the raw values instead of scaling each column to unit variance")]
+        no_standardize: bool,
+        #[arg(long, help = "Random seed for the k-means starting centers")]
+        seed: Option<u64>,
+        #[arg(long, help = "Save the dataset with a cluster_id column (CSV, Parquet or NDJSON)")]
+        output: Option<String>,
     },
 }
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    init_logging(cli.verbose);
+    set_read_only(cli.read_only);
+    set_parse_mode(if cli.lenient { ParseMode::Lenient } else { ParseMode::Strict });
+    set_data_roots(DataRoots::parse(&cli.data_roots)?);
+    set_raw_iq_defaults(RawIqParams { sample_rate: cli.sample_rate, center_freq: cli.center_freq, ..Default::default() });
+    if let Some(ref bands) = cli.bands {
+        set_band_map(BandMap::from_toml(bands)?);
+    }
     
     match cli.command {
         Commands::Parse { path } => {
@@ -59,36 +554,1268 @@ fn main() -> Result<()> {
             }
         }
         
-        Commands::Dataset { dir, output } => {
-            println!("Building dataset from directory: {}", dir);
-            let dataset = SigMFDataset::from_directory(&dir)?;
+        Commands::Info { path } => {
+            print!("{}", SigMFParser::from_meta_file(&path)?.info_report());
+        }
+
+        Commands::Dataset { dirs, output, format, casts, verify, noise_floor, compute_metrics, annotation_fields, profiles, derived, granularity, watchlists, within, bbox, preset, watch, no_cache, sample, seed, quiet } => {
+            if !quiet {
+                println!("Building dataset from {}: {}", if dirs.len() > 1 { "directories" } else { "directory" }, dirs.join(", "));
+            }
+            let cast_specs = casts;
+            let casts = cast_specs.iter().map(|spec| parse_cast_spec(spec)).collect::<Result<Vec<_>>>()?;
+            let options = DatasetOptions {
+                granularity: Granularity::from_string(&granularity)?,
+                verify_checksums: verify,
+                estimate_noise_floor: noise_floor,
+                compute_metrics,
+                annotation_extra_columns: annotation_fields,
+                cache: !no_cache,
+                sample,
+                sample_seed: seed,
+                // The progress bar and its summary stand in for the scan's lines
+                quiet: true,
+            };
+            if watch && sample.is_some() {
+                anyhow::bail!("--watch keeps the whole directory current and can't be combined with --sample");
+            }
+            if watch && dirs.len() > 1 {
+                anyhow::bail!("--watch follows a single directory; combine several without it");
+            }
+            let dir = dirs[0].clone();
+            let profiles = match profiles {
+                Some(profiles) => load_sensor_profiles(profiles)?,
+                None => Vec::new(),
+            };
+            let derived = match derived {
+                Some(path) => DerivedColumns::from_toml(path)?,
+                None => DerivedColumns::default(),
+            };
+            // Recorded as the export's filter, since neither keeps every recording
+            let sample_filter = sample.map(|n| match seed {
+                Some(seed) => format!("random sample of {} files (seed {})", n, seed),
+                None => format!("random sample of {} files", n),
+            });
+            let watchlist_filter = (!watchlists.is_empty()).then(|| format!("watchlist {}", watchlists.join(", ")));
+            let preset = match preset {
+                Some(name) => Some(find_preset(&load_saved_presets()?, &name)?.clone()),
+                None => None,
+            };
+            let preset_filter = preset.as_ref().map(|preset| format!("preset {:?} ({})", preset.name, preset.description()));
+            let geo_filters = [within.as_deref().map(GeoFilter::parse_within), bbox.as_deref().map(GeoFilter::parse_bbox)]
+                .into_iter()
+                .flatten()
+                .collect::<Result<Vec<_>>>()?;
+            let geo_filter = (!geo_filters.is_empty()).then(|| geo_filters.iter().map(|f| f.to_string()).collect::<Vec<_>>().join(" AND "));
+            let filter = [sample_filter, watchlist_filter, geo_filter, preset_filter].into_iter().flatten().collect::<Vec<_>>();
+            let filter = (!filter.is_empty()).then(|| filter.join(" AND "));
+            let watchlists = watchlists.iter().map(Watchlist::from_csv).collect::<Result<Vec<_>>>()?;
+            let format = match format {
+                Some(format) => Some(ExportFormat::from_string(&format)?),
+                None => output.as_deref().map(|path| ExportFormat::from_path(path).unwrap_or(ExportFormat::Csv)),
+            };
+            // Filter, cast and save (or preview) the scanned rows
+            let write = |scanned: &DataFrame, scanned_at| -> Result<()> {
+                // First, so watchlists, casts and presets can use the derived columns
+                let mut dataset = apply_derived_columns(scanned.clone(), &derived)?;
+                if !watchlists.is_empty() {
+                    dataset = filter_watchlists(&dataset, &watchlists)?;
+                    if !quiet {
+                        println!("{} of {} rows match the watchlists", dataset.height(), scanned.height());
+                    }
+                }
+                if !geo_filters.is_empty() {
+                    let before = dataset.height();
+                    dataset = filter_geo(&dataset, &geo_filters)?;
+                    if !quiet {
+                        println!("{} of {} rows were recorded {}", dataset.height(), before, geo_filters.iter().map(|f| f.to_string()).collect::<Vec<_>>().join(" and "));
+                    }
+                }
+                let mut dataset = cast_columns(dataset.lazy(), &casts)?.collect()?;
+                if let Some(ref preset) = preset {
+                    let before = dataset.height();
+                    dataset = preset.apply(&dataset)?;
+                    if !quiet {
+                        println!("{} of {} rows pass preset {:?}: {}", dataset.height(), before, preset.name, preset.description());
+                    }
+                }
+                
+                if !quiet {
+                    println!("Dataset shape: {:?}", dataset.shape());
+                }
+                
+                if let (Some(output_path), Some(format)) = (&output, format) {
+                    write_dataset(&mut dataset.clone(), output_path, format)?;
+                    let mut provenance = Provenance::new(&dir, scanned_at, dataset.height());
+                    provenance.set_other_roots(&dirs[1..]);
+                    provenance.row_per = options.granularity.as_str().to_string();
+                    provenance.casts = cast_specs.clone();
+                    provenance.filter = filter.clone();
+                    provenance.write(output_path)?;
+                    if !quiet {
+                        println!("Saved dataset to: {} ({})", output_path, format.name());
+                    }
+                } else {
+                    println!("First 5 rows:");
+                    println!("{}", dataset.head(Some(5)));
+                }
+                Ok(())
+            };
             
-            println!("Dataset shape: {:?}", dataset.shape());
+            // Watch before the first scan so nothing written during it is missed
+            let mut watcher = if watch { Some(DirectoryWatcher::new(&dir)?) } else { None };
+            let scanned_at = chrono::Utc::now();
+            let mut dataset = scan_with_progress(&dirs, &options, quiet)?;
+            dataset = apply_sensor_profiles(dataset, &profiles)?;
+            write(&dataset, scanned_at)?;
             
-            if let Some(output_path) = output {
-                use polars::prelude::*;
-                let mut file = std::fs::File::create(&output_path)?;
-                CsvWriter::new(&mut file).finish(&mut dataset.clone())?;
-                println!("Saved dataset to: {}", output_path);
-            } else {
-                println!("First 5 rows:");
-                println!("{}", dataset.head(Some(5)));
+            if let Some(ref mut watcher) = watcher {
+                if !quiet {
+                    println!("Watching {} for new recordings (Ctrl-C to stop)", dir);
+                }
+                loop {
+                    let changed = watcher.wait_changed()?;
+                    let scanned_at = chrono::Utc::now();
+                    let (updated, update) = update_dataset(dataset, std::path::Path::new(&dir), &changed, &options, &profiles)?;
+                    dataset = updated;
+                    for (path, error) in &update.errors {
+                        warn!("Skipped {:?}: {}", path, error);
+                    }
+                    if update.is_empty() {
+                        continue;
+                    }
+                    if !quiet {
+                        println!("[{}] {}", scanned_at.format("%H:%M:%S"), update.summary());
+                    }
+                    write(&dataset, scanned_at)?;
+                }
             }
         }
         
-        Commands::Stats { dataset } => {
+        Commands::Stats { dataset, casts, columns, metrics } => {
             println!("Loading dataset: {}", dataset);
-            let lf = LazyCsvReader::new(dataset).finish()?;
-            let stats = lf.select([
-                col("ml_wifi_prob").mean().alias("avg_wifi_prob"),
-                col("ml_snr_db").mean().alias("avg_snr"),
-                col("center_freq_hz").n_unique().alias("unique_freqs"),
-            ]).collect()?;
+            let provenance = Provenance::read(&dataset)?;
+            if let Some(ref provenance) = provenance {
+                println!("Provenance: {}", provenance.summary());
+            }
+            let (lf, migration) = migrate_dataset(scan_dataset(&dataset)?, provenance.as_ref())?;
+            if migration.was_migrated() {
+                println!("{}", migration.summary());
+            }
+            let casts = casts.iter().map(|spec| parse_cast_spec(spec)).collect::<Result<Vec<_>>>()?;
+            let lf = cast_columns(lf, &casts)?;
+            let metrics: Vec<Metric> = metrics.iter().map(|spec| Metric::parse(spec)).collect::<Result<Vec<_>>>()?.concat();
+            let stats = describe(lf, &columns, &metrics)?;
             
             println!("Dataset statistics:");
-            println!("{}", stats);
+            print!("{}", whole_table(&stats));
+        }
+
+        Commands::Hist { dataset, column, bins, log, casts, output, width, height } => {
+            let provenance = Provenance::read(&dataset)?;
+            let (lf, migration) = migrate_dataset(scan_dataset(&dataset)?, provenance.as_ref())?;
+            if migration.was_migrated() {
+                println!("{}", migration.summary());
+            }
+            let casts = casts.iter().map(|spec| parse_cast_spec(spec)).collect::<Result<Vec<_>>>()?;
+            let mut lf = cast_columns(lf, &casts)?;
+            match lf.schema()?.get(&column) {
+                None => anyhow::bail!("No column named '{}'", column),
+                Some(dtype) if !dtype.is_numeric() => anyhow::bail!("Column '{}' is {}, not numeric; cast it with --cast {}=f64", column, dtype, column),
+                Some(_) => {}
+            }
+            let df = lf.select([col(&column)]).collect()?;
+            let series = df.column(&column)?;
+            let histogram = histogram(series, bins)?.ok_or_else(|| anyhow::anyhow!("Column '{}' has no finite values", column))?;
+            let precision = edge_precision(histogram.bin_width());
+            let skipped = series.len() - histogram.total() as usize;
+            println!(
+                "{}: {} values in {} bins from {:.*} to {:.*}{}",
+                column,
+                histogram.total(),
+                histogram.counts.len(),
+                precision,
+                histogram.min,
+                precision,
+                histogram.max,
+                if skipped > 0 { format!(" ({} null or non-finite skipped)", skipped) } else { String::new() }
+            );
+            match output {
+                Some(path) => {
+                    write_histogram_png(&histogram, std::path::Path::new(&path), width, height, log)?;
+                    println!("Wrote {}", path);
+                }
+                None => print!("{}", ascii_histogram(&histogram, 50, log)),
+            }
+        }
+
+        Commands::Groupby { dataset, by, agg, casts, output } => {
+            let key = GroupKey::parse(&by)?;
+            let aggregations = Aggregation::parse_list(&agg)?;
+            let provenance = Provenance::read(&dataset)?;
+            let (lf, migration) = migrate_dataset(scan_dataset(&dataset)?, provenance.as_ref())?;
+            if migration.was_migrated() {
+                println!("{}", migration.summary());
+            }
+            let casts = casts.iter().map(|spec| parse_cast_spec(spec)).collect::<Result<Vec<_>>>()?;
+            let df = cast_columns(lf, &casts)?.collect()?;
+            let mut grouped = group_by(&df, &key, &aggregations)?;
+            match output {
+                Some(output) => {
+                    write_dataset(&mut grouped, &output, ExportFormat::from_path(&output).unwrap_or(ExportFormat::Csv))?;
+                    println!("Saved {} groups to: {}", grouped.height(), output);
+                }
+                None => {
+                    print!("{}", whole_table(&grouped));
+                }
+            }
+        }
+
+        Commands::Diff { old, new, key, limit, output } => {
+            let load = |path: &str| -> Result<DataFrame> {
+                let (lf, migration) = migrate_dataset(scan_dataset(path)?, Provenance::read(path)?.as_ref())?;
+                if migration.was_migrated() {
+                    println!("{}: {}", path, migration.summary());
+                }
+                Ok(lf.collect()?)
+            };
+            let (old_df, new_df) = (load(&old)?, load(&new)?);
+            let key = if key.is_empty() { DEFAULT_DIFF_KEY.iter().map(|c| c.to_string()).collect() } else { key };
+            let diff = diff_datasets(&old_df, &new_df, &key)?;
+            let limit = if limit == 0 { usize::MAX } else { limit };
+            let more = |shown: usize, total: usize| {
+                if total > shown {
+                    println!("    ... {} more", total - shown);
+                }
+            };
+
+            println!("{} rows added, {} removed, {} changed, {} unchanged",
+                diff.added.len(), diff.removed.len(), diff.changed_rows, diff.unchanged_rows);
+            if !diff.added_columns.is_empty() {
+                println!("Columns added: {}", diff.added_columns.join(", "));
+            }
+            if !diff.removed_columns.is_empty() {
+                println!("Columns removed: {}", diff.removed_columns.join(", "));
+            }
+            for (title, keys) in [("Added", &diff.added), ("Removed", &diff.removed)] {
+                if keys.is_empty() {
+                    continue;
+                }
+                println!("{} rows:", title);
+                for row_key in keys.iter().take(limit) {
+                    println!("    {}", row_key);
+                }
+                more(limit, keys.len());
+            }
+            if !diff.changes.is_empty() {
+                println!("Changed values by column:");
+                for (column, count) in diff.changes_by_column() {
+                    println!("    {:<24} {}", column, count);
+                }
+                println!("Changes:");
+                let shown = |value: &str| if value.is_empty() { "(empty)".to_string() } else { value.to_string() };
+                for change in diff.changes.iter().take(limit) {
+                    println!("    {}: {} {} -> {}", change.key, change.column, shown(&change.old), shown(&change.new));
+                }
+                more(limit, diff.changes.len());
+            }
+            if let Some(output) = output {
+                let mut table = diff.to_dataframe()?;
+                write_dataset(&mut table, &output, ExportFormat::from_path(&output).unwrap_or(ExportFormat::Csv))?;
+                println!("Saved diff to: {}", output);
+            }
+        }
+
+        Commands::Segments { dir } => {
+            let mut segments = Vec::new();
+            for meta_path in SigMFDataset::meta_files(&dir)? {
+                match SigMFParser::from_meta_file(&meta_path) {
+                    Ok(parser) => segments.extend(Segment::from_parser(&meta_path, &parser)),
+                    Err(e) => warn!("Skipping {}: {}", meta_path.display(), e),
+                }
+            }
+            let recordings = group_segments(segments);
+            for recording in &recordings {
+                let first = &recording.segments[0];
+                let last = &recording.segments[recording.segments.len() - 1];
+                println!("{}: {} files (seq {}..{}), {} samples, {:.3} s",
+                    recording.name(), recording.segments.len(), first.sequence_num, last.sequence_num,
+                    recording.total_samples(), recording.duration_s());
+                for segment in &recording.segments {
+                    println!("    {:>6}  {}", segment.sequence_num, segment.meta_path.display());
+                }
+            }
+            let total = recordings.iter().fold(0.0, |total, r| total + r.duration_s());
+            println!("{} segmented captures, {:.3} s in total", recordings.len(), total);
+        }
+
+        Commands::NoiseTrend { path, threshold_db } => {
+            let dataset = if std::path::Path::new(&path).is_dir() {
+                let options = DatasetOptions {
+                    estimate_noise_floor: true,
+                    ..DatasetOptions::default()
+                };
+                SigMFDataset::from_directory_with_options(&path, &options)?
+            } else {
+                let (lf, migration) = migrate_dataset(scan_dataset(&path)?, Provenance::read(&path)?.as_ref())?;
+                if migration.was_migrated() {
+                    println!("{}", migration.summary());
+                }
+                lf.collect()?
+            };
+            let trend = noise_floor_trend(&dataset, threshold_db)?;
+            if trend.skipped_rows > 0 {
+                println!("{} rows without a noise floor or capture_datetime were skipped", trend.skipped_rows);
+            }
+            for sensor in &trend.sensors {
+                let (first, last) = (&sensor.points[0], &sensor.points[sensor.points.len() - 1]);
+                let (min, max) = sensor.points.iter().fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), p| {
+                    (min.min(p.noise_floor_db), max.max(p.noise_floor_db))
+                });
+                println!("{}: {} recordings, {} .. {}, {:.1} to {:.1} dB",
+                    sensor.sensor, sensor.points.len(), first.datetime, last.datetime, min, max);
+                if let (Some(per_day), Some(total)) = (sensor.drift_db_per_day, sensor.drift_db()) {
+                    let flag = if sensor.is_drifting(threshold_db) { "  DRIFT" } else { "" };
+                    println!("    drift {:+.3} dB/day ({:+.1} dB over the period){}", per_day, total, flag);
+                }
+                for step in &sensor.steps {
+                    println!("    STEP at {} ({}): {:.1} -> {:.1} dB ({:+.1})",
+                        step.datetime, step.meta_filename, step.before_db, step.after_db, step.change_db());
+                }
+            }
+        }
+
+        Commands::Continuity { path, tolerance, output } => {
+            let dataset = if std::path::Path::new(&path).is_dir() {
+                SigMFDataset::from_directory(&path)?
+            } else {
+                let (lf, migration) = migrate_dataset(scan_dataset(&path)?, Provenance::read(&path)?.as_ref())?;
+                if migration.was_migrated() {
+                    println!("{}", migration.summary());
+                }
+                lf.collect()?
+            };
+            let report = continuity_report(&dataset, tolerance)?;
+            if report.untimed_recordings > 0 {
+                println!("{} recordings without a parseable capture_datetime were left out of the time checks", report.untimed_recordings);
+            }
+            for sensor in &report.sensors {
+                let mut summary = format!("{}: {} recordings", sensor.sensor, sensor.recordings.len());
+                if let Some((first, last)) = sensor.sequence_range() {
+                    summary.push_str(&format!(", sequence {}..{} ({} missing)", first, last, sensor.missing_count()));
+                }
+                if let Some((start, end)) = sensor.time_range() {
+                    summary.push_str(&format!(", {} .. {}", start, end));
+                }
+                println!("{}", summary);
+                if sensor.issues.is_empty() {
+                    println!("    continuous");
+                } else {
+                    println!(
+                        "    gaps: {} ({} in total), overlaps: {}, duplicate sequence numbers: {}",
+                        sensor.count("gap"),
+                        format_duration(sensor.total_gap_s()),
+                        sensor.count("overlap"),
+                        sensor.count("duplicate")
+                    );
+                }
+                for issue in &sensor.issues {
+                    println!("    {}", issue);
+                }
+            }
+            if let Some(output) = output {
+                let mut issues = report.issues_dataframe()?;
+                write_dataset(&mut issues, &output, ExportFormat::from_path(&output).unwrap_or(ExportFormat::Csv))?;
+                println!("Saved {} issues to: {}", report.issue_count(), output);
+            }
+        }
+
+        Commands::Occupancy { path, bin_width, by, output, svg } => {
+            let bucket = TimeBucket::from_string(&by)?;
+            let dataset = if std::path::Path::new(&path).is_dir() {
+                SigMFDataset::from_directory(&path)?
+            } else {
+                let (lf, migration) = migrate_dataset(scan_dataset(&path)?, Provenance::read(&path)?.as_ref())?;
+                if migration.was_migrated() {
+                    println!("{}", migration.summary());
+                }
+                lf.collect()?
+            };
+            let occupancy = occupancy(&dataset, bin_width, bucket)?;
+            if occupancy.skipped_rows > 0 {
+                println!("{} rows without a capture_datetime or sample rate were skipped", occupancy.skipped_rows);
+            }
+            for (bin, bin_hz) in occupancy.bins_hz.iter().enumerate() {
+                let captures: u32 = occupancy.captures[bin].iter().sum();
+                let percent = occupancy.bin_percent(bin).unwrap_or(0.0);
+                println!("{:>16}  {:>5.1}%  of {} captures", format_frequency(*bin_hz), percent, captures);
+            }
+            if let Some(output) = output {
+                let mut table = occupancy.to_dataframe()?;
+                write_dataset(&mut table, &output, ExportFormat::from_path(&output).unwrap_or(ExportFormat::Csv))?;
+                println!("Saved occupancy to: {}", output);
+            }
+            if let Some(svg) = svg {
+                std::fs::write(&svg, occupancy.to_svg())?;
+                println!("Saved heatmap to: {}", svg);
+            }
+        }
+
+        Commands::Split { path, out_dir, ratios, stratify, seed, copy, root, output } => {
+            let ratios = SplitRatios::parse(&ratios)?;
+            let stratify = stratify.as_deref().map(GroupKey::parse).transpose()?;
+            let (dataset, root, provenance) = if std::path::Path::new(&path).is_dir() {
+                let scanned_at = chrono::Utc::now();
+                let dataset = SigMFDataset::from_directory(&path)?;
+                let provenance = Provenance::new(&path, scanned_at, dataset.height());
+                (dataset, path.clone(), Some(provenance))
+            } else {
+                let provenance = Provenance::read(&path)?;
+                let (lf, migration) = migrate_dataset(scan_dataset(&path)?, provenance.as_ref())?;
+                if migration.was_migrated() {
+                    println!("{}", migration.summary());
+                }
+                let root = root
+                    .or_else(|| provenance.as_ref().map(|p| p.source.clone()).filter(|source| std::path::Path::new(source).is_dir()))
+                    .unwrap_or_else(|| std::path::Path::new(&path).parent().unwrap_or(std::path::Path::new(".")).to_string_lossy().to_string());
+                (lf.collect()?, root, provenance)
+            };
+            let split = split_dataset(&dataset, &ratios, stratify.as_ref(), seed)?;
+            if stratify.is_some() {
+                for (value, counts) in &split.strata {
+                    let value = if value.is_empty() { "(none)" } else { value };
+                    println!("{:<24} train {:>5}  val {:>5}  test {:>5}", value, counts[0], counts[1], counts[2]);
+                }
+            }
+
+            // Recordings are keyed by their path under the root; search by
+            // name for those inside archives and VRT captures, and for
+            // datasets from before meta_path
+            let locate = |name: &str| {
+                let path = std::path::Path::new(&root).join(name);
+                if path.is_file() {
+                    return Some(path);
+                }
+                let file_name = path.file_name()?.to_string_lossy().to_string();
+                SigMFDataset::find_meta_file(&root, &file_name)
+            };
+            std::fs::create_dir_all(&out_dir)?;
+            let mut missing = 0;
+            for (set, set_name) in split.sets.iter().zip(SPLIT_NAMES) {
+                let mut paths = Vec::with_capacity(set.len());
+                for name in set {
+                    match locate(name) {
+                        Some(path) => paths.push((name, path)),
+                        None => {
+                            warn!("Could not locate {} under {}", name, root);
+                            missing += 1;
+                        }
+                    }
+                }
+                if copy {
+                    let set_dir = std::path::Path::new(&out_dir).join(set_name);
+                    let mut copied = 0;
+                    for (name, meta_path) in &paths {
+                        // Keep the recording's subdirectory, so names don't clash
+                        let subdir = std::path::Path::new(name.as_str()).parent().filter(|dir| dir.is_relative()).unwrap_or(std::path::Path::new(""));
+                        match copy_recording(meta_path, set_dir.join(subdir)) {
+                            Ok(_) => copied += 1,
+                            Err(e) => warn!("Failed to copy {}: {}", meta_path.display(), e),
+                        }
+                    }
+                    println!("{}: copied {} of {} recordings to {}", set_name, copied, set.len(), set_dir.display());
+                } else {
+                    let list_path = std::path::Path::new(&out_dir).join(format!("{}.txt", set_name));
+                    let list: String = paths.iter().map(|(_, path)| format!("{}\n", path.display())).collect();
+                    std::fs::write(&list_path, list)?;
+                    println!("{}: {} recordings listed in {}", set_name, paths.len(), list_path.display());
+                }
+            }
+            if missing > 0 {
+                println!("{} recordings could not be located and were left out", missing);
+            }
+            if let Some(output) = output {
+                let mut dataset = with_split_column(&dataset, &split)?;
+                write_dataset(&mut dataset, &output, ExportFormat::from_path(&output).unwrap_or(ExportFormat::Csv))?;
+                if let Some(provenance) = provenance {
+                    provenance.write(&output)?;
+                }
+                println!("Saved dataset with a split column to: {}", output);
+            }
+        }
+
+        Commands::Tui { path, root } => {
+            if !std::io::stdout().is_terminal() {
+                anyhow::bail!("tui needs a terminal; use `dataset` or `stats` in scripts");
+            }
+            let (dataset, root) = if std::path::Path::new(&path).is_dir() {
+                let options = DatasetOptions { quiet: true, ..DatasetOptions::default() };
+                (scan_with_progress(std::slice::from_ref(&path), &options, false)?, path)
+            } else {
+                let provenance = Provenance::read(&path)?;
+                let (lf, _) = migrate_dataset(scan_dataset(&path)?, provenance.as_ref())?;
+                let root = root
+                    .or_else(|| provenance.map(|p| p.source).filter(|source| std::path::Path::new(source).is_dir()))
+                    .unwrap_or_else(|| std::path::Path::new(&path).parent().unwrap_or(std::path::Path::new(".")).to_string_lossy().to_string());
+                (lf.collect()?, root)
+            };
+            sig_viewer::tui::run(dataset, root.into())?;
+        }
+
+        Commands::Thumbnails { path, cache_dir, width, height, force, jobs, root, output } => {
+            if width == 0 || height == 0 {
+                anyhow::bail!("Thumbnails need a width and height of at least one pixel");
+            }
+            let (dataset, root, provenance) = if std::path::Path::new(&path).is_dir() {
+                let scanned_at = chrono::Utc::now();
+                let dataset = SigMFDataset::from_directory(&path)?;
+                let provenance = Provenance::new(&path, scanned_at, dataset.height());
+                (dataset, path.clone(), Some(provenance))
+            } else {
+                let provenance = Provenance::read(&path)?;
+                let (lf, migration) = migrate_dataset(scan_dataset(&path)?, provenance.as_ref())?;
+                if migration.was_migrated() {
+                    println!("{}", migration.summary());
+                }
+                let root = root
+                    .or_else(|| provenance.as_ref().map(|p| p.source.clone()).filter(|source| std::path::Path::new(source).is_dir()))
+                    .unwrap_or_else(|| std::path::Path::new(&path).parent().unwrap_or(std::path::Path::new(".")).to_string_lossy().to_string());
+                (lf.collect()?, root, provenance)
+            };
+            let dir = cache_dir.map(std::path::PathBuf::from).unwrap_or_else(default_thumbnail_dir);
+            let params = ThumbnailParams { width, height, ..ThumbnailParams::default() };
+            let pool = jobs.map(WorkerPool::new).unwrap_or_else(WorkerPool::with_default_threads);
+            let (mut dataset, summary) = thumbnail_dataset(&dataset, std::path::Path::new(&root), &dir, &params, force, &pool)?;
+            for (name, error) in &summary.failed {
+                warn!("Failed to render {}: {}", name, error);
+            }
+            println!(
+                "{} thumbnails rendered, {} already cached, {} failed, in {}",
+                summary.rendered,
+                summary.reused,
+                summary.failed.len(),
+                dir.display()
+            );
+            if let Some(output) = output {
+                write_dataset(&mut dataset, &output, ExportFormat::from_path(&output).unwrap_or(ExportFormat::Csv))?;
+                if let Some(provenance) = provenance {
+                    provenance.write(&output)?;
+                }
+                println!("Saved dataset with a {} column to: {}", THUMBNAIL_COLUMN, output);
+            }
+        }
+
+        Commands::Analyze { analysis: Analysis::Cluster { path, columns, method, k, eps, min_points, no_standardize, seed, output } } => {
+            let method = match method.as_str() {
+                "kmeans" => ClusterMethod::KMeans { k },
+                "dbscan" => ClusterMethod::Dbscan { eps, min_points },
+                _ => anyhow::bail!("Unknown clustering method '{}': use kmeans or dbscan", method),
+            };
+            let (dataset, provenance) = if std::path::Path::new(&path).is_dir() {
+                let scanned_at = chrono::Utc::now();
+                let dataset = SigMFDataset::from_directory(&path)?;
+                let provenance = Provenance::new(&path, scanned_at, dataset.height());
+                (dataset, Some(provenance))
+            } else {
+                let provenance = Provenance::read(&path)?;
+                let (lf, migration) = migrate_dataset(scan_dataset(&path)?, provenance.as_ref())?;
+                if migration.was_migrated() {
+                    println!("{}", migration.summary());
+                }
+                (lf.collect()?, provenance)
+            };
+            let columns = if columns.is_empty() { default_cluster_columns(&dataset) } else { columns };
+            if columns.is_empty() {
+                anyhow::bail!("The dataset has none of the default feature columns; choose some with --columns");
+            }
+            let options = ClusterOptions { columns: columns.clone(), method, standardize: !no_standardize, seed };
+            println!("Clustering {} rows by {} ({})", dataset.height(), columns.join(", "), options.method.describe());
+            let clustering = cluster(&dataset, &options, |_| Ok(()))?;
+            if clustering.skipped_rows > 0 {
+                println!("{} rows missing a feature value were left unclustered", clustering.skipped_rows);
+            }
+            print!("{} clusters", clustering.num_clusters());
+            if let Some(noise) = clustering.sizes.get(&DBSCAN_NOISE) {
+                print!(", {} noise rows", noise);
+            }
+            match clustering.inertia {
+                Some(inertia) => println!(", inertia {:.3}", inertia),
+                None => println!(),
+            }
+
+            let mut dataset = with_cluster_column(&dataset, &clustering)?;
+            let aggregations: Vec<Aggregation> = std::iter::once(Aggregation::Count).chain(columns.iter().map(|c| Aggregation::Mean(c.clone()))).collect();
+            let summary = group_by(&dataset, &GroupKey::Column(CLUSTER_COLUMN.to_string()), &aggregations)?;
+            print!("{}", whole_table(&summary));
+            if let Some(output) = output {
+                write_dataset(&mut dataset, &output, ExportFormat::from_path(&output).unwrap_or(ExportFormat::Csv))?;
+                if let Some(provenance) = provenance {
+                    provenance.write(&output)?;
+                }
+                println!("Saved dataset with a cluster_id column to: {}", output);
+            }
+        }
+
+        Commands::Validate { path } => {
+            let reports = validate_path(&path)?;
+            let num_failed = reports.iter().filter(|r| !r.is_valid()).count();
+            for report in &reports {
+                if report.is_valid() {
+                    println!("PASS {}", report.meta_path.display());
+                } else {
+                    println!("FAIL {}", report.meta_path.display());
+                    for violation in &report.violations {
+                        println!("    - {}", violation);
+                    }
+                }
+            }
+            println!("{} files checked, {} passed, {} failed",
+                reports.len(),
+                reports.len() - num_failed,
+                num_failed);
+            if num_failed > 0 {
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Checksum { path, check, jobs } => {
+            let journal = Journal::begin(&format!("checksum {}", path));
+            let pool = jobs.map(WorkerPool::new).unwrap_or_else(WorkerPool::with_default_threads);
+            let meta_files = SigMFDataset::meta_files(&path)?;
+            let mut num_failed = 0;
+            // Files are hashed in parallel but reported in order
+            if check {
+                let tasks: Vec<_> = meta_files
+                    .into_iter()
+                    .map(|meta_path| {
+                        let task = pool.submit(&meta_path.display().to_string(), Priority::Normal, {
+                            let meta_path = meta_path.clone();
+                            move |ctx| SigMFParser::from_meta_file(&meta_path)?.verify_checksum_with_progress(|f| ctx.report(f))
+                        });
+                        (meta_path, task)
+                    })
+                    .collect();
+                for (meta_path, task) in tasks {
+                    match task.wait() {
+                        Ok(status) => {
+                            if status == ChecksumStatus::Mismatch {
+                                num_failed += 1;
+                            }
+                            println!("{:<11} {}", status.as_str(), meta_path.display());
+                        }
+                        Err(e) => {
+                            num_failed += 1;
+                            println!("{:<11} {} ({})", "error", meta_path.display(), e);
+                        }
+                    }
+                }
+            } else {
+                let tasks: Vec<_> = meta_files
+                    .into_iter()
+                    .map(|meta_path| {
+                        let task = pool.submit(&meta_path.display().to_string(), Priority::Normal, {
+                            let (meta_path, journal) = (meta_path.clone(), journal.clone());
+                            move |_| write_sha512(&meta_path, &journal)
+                        });
+                        (meta_path, task)
+                    })
+                    .collect();
+                for (meta_path, task) in tasks {
+                    match task.wait() {
+                        Ok(digest) => println!("{}  {}", &digest[..16], meta_path.display()),
+                        Err(e) => {
+                            num_failed += 1;
+                            warn!("Failed to checksum {:?}: {}", meta_path, e);
+                        }
+                    }
+                }
+            }
+            if num_failed > 0 {
+                std::process::exit(1);
+            }
+        }
+
+        Commands::SetMeta { path, set, unset, dry_run } => {
+            let mut edits = set.iter().map(|text| GlobalEdit::parse_set(text)).collect::<Result<Vec<_>>>()?;
+            edits.extend(unset.iter().map(|key| GlobalEdit::Unset(qualify_key(key))));
+            if edits.is_empty() {
+                anyhow::bail!("Nothing to change; give --set key=value or --unset key");
+            }
+            if let Some(edit) = edits.iter().find(|edit| PROTECTED_GLOBAL_FIELDS.contains(&edit.key())) {
+                anyhow::bail!("{} follows the data file; change it with convert or checksum", edit.key());
+            }
+            let journal = Journal::begin(&format!("set-meta {}", path));
+            let (mut num_changed, mut num_failed, mut num_files) = (0, 0, 0);
+            for meta_path in SigMFDataset::meta_files(&path)? {
+                num_files += 1;
+                match set_global_fields(&meta_path, &edits, &journal, dry_run) {
+                    Ok(true) => {
+                        num_changed += 1;
+                        println!("{}  {}", if dry_run { "would change" } else { "changed" }, meta_path.display());
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        num_failed += 1;
+                        warn!("Failed to update {:?}: {}", meta_path, e);
+                    }
+                }
+            }
+            if dry_run {
+                println!("{} of {} files would change", num_changed, num_files);
+            } else {
+                println!("Changed {} of {} files", num_changed, num_files);
+                if num_changed > 0 {
+                    println!("Undo with: sig_viewer_cli rollback {}", journal.batch());
+                }
+            }
+            if num_failed > 0 {
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Annotate { meta_file, start_sample, count, label, freq_lower, freq_upper, comment, fields } => {
+            let mut annotation = serde_json::Map::new();
+            annotation.insert("core:sample_start".to_string(), start_sample.into());
+            if let Some(count) = count {
+                annotation.insert("core:sample_count".to_string(), count.into());
+            }
+            if let (Some(lower), Some(upper)) = (freq_lower, freq_upper) {
+                annotation.insert("core:freq_lower_edge".to_string(), lower.into());
+                annotation.insert("core:freq_upper_edge".to_string(), upper.into());
+            }
+            if let Some(label) = label {
+                annotation.insert("core:label".to_string(), label.into());
+            }
+            if let Some(comment) = comment {
+                annotation.insert("core:comment".to_string(), comment.into());
+            }
+            for text in &fields {
+                let (key, value) = text
+                    .split_once('=')
+                    .ok_or_else(|| anyhow::anyhow!("Expected key=value, got {:?}", text))?;
+                let key = qualify_key(key.trim());
+                let value = parse_field_value(&key, value, None)?;
+                annotation.insert(key, value);
+            }
+            let journal = Journal::begin(&format!("annotate {}", meta_file));
+            let position = add_annotation(&meta_file, annotation, &journal)?;
+            println!("Added annotation {} to {}", position, meta_file);
+            println!("Undo with: sig_viewer_cli rollback {}", journal.batch());
+        }
+
+        Commands::Organize { path, out, template, copy, manifest, dry_run } => {
+            let template = OrganizeTemplate::parse(&template)?;
+            let mode = if copy { OrganizeMode::Copy } else { OrganizeMode::Move };
+            let journal = Journal::begin(&format!("organize {}", path));
+            let (mut organized, mut num_failed) = (Vec::new(), 0);
+            for meta_path in SigMFDataset::meta_files(&path)? {
+                match organize_recording(&meta_path, &out, &template, mode, &journal, dry_run) {
+                    Ok(recording) => {
+                        let action = match (recording.unchanged, dry_run, mode) {
+                            (true, _, _) => "in place",
+                            (false, true, OrganizeMode::Move) => "would move",
+                            (false, true, OrganizeMode::Copy) => "would copy",
+                            (false, false, OrganizeMode::Move) => "moved",
+                            (false, false, OrganizeMode::Copy) => "copied",
+                        };
+                        println!("{:<10}  {} -> {}", action, meta_path.display(), recording.meta_path.display());
+                        organized.push(recording);
+                    }
+                    Err(e) => {
+                        num_failed += 1;
+                        warn!("Failed to organize {:?}: {}", meta_path, e);
+                    }
+                }
+            }
+            let num_changed = organized.iter().filter(|r| !r.unchanged).count();
+            if dry_run {
+                println!("{} of {} recordings would be {}", num_changed, organized.len() + num_failed, if copy { "copied" } else { "moved" });
+            } else {
+                println!("{} {} of {} recordings", if copy { "Copied" } else { "Moved" }, num_changed, organized.len() + num_failed);
+                if num_changed > 0 && !copy {
+                    println!("Undo with: sig_viewer_cli rollback {}", journal.batch());
+                }
+            }
+            if let (Some(manifest), false) = (manifest, dry_run) {
+                let text = |f: fn(&OrganizedRecording) -> &std::path::Path| -> Vec<String> {
+                    organized.iter().map(|r| f(r).display().to_string()).collect()
+                };
+                let mut table = DataFrame::new(vec![
+                    Series::new("source".into(), text(|r| &r.source)),
+                    Series::new("meta_path".into(), text(|r| &r.meta_path)),
+                    Series::new("data_path".into(), text(|r| &r.data_path)),
+                ])?;
+                write_dataset(&mut table, &manifest, ExportFormat::from_path(&manifest).unwrap_or(ExportFormat::Csv))?;
+                println!("Saved manifest to: {}", manifest);
+            }
+            if num_failed > 0 {
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Convert { meta_file, to, out, compress } => {
+            let target = SigMFDataType::from_string(&to)?;
+            let compress = compress.as_deref().map(Compression::from_string).transpose()?;
+            let report = convert_recording(&meta_file, &target, &out)?;
+            let data_path = match compress {
+                Some(compression) => compress_data_file(&report.data_path, compression)?,
+                None => report.data_path,
+            };
+            println!("Wrote {} samples as {} to {}", report.num_samples, target.name(), data_path.display());
+            println!("Metadata: {}", report.meta_path.display());
+            if report.clipped_samples > 0 {
+                println!("Warning: {} samples clipped", report.clipped_samples);
+            }
+        }
+
+        Commands::Extract { meta_file, annotation, start_sample, num_samples, start_time, duration, out, compress } => {
+            let range = match (annotation, start_sample, start_time) {
+                (Some(index), None, None) => SliceRange::Annotation(index),
+                (None, Some(start), None) => SliceRange::Samples { start, count: num_samples },
+                (None, None, Some(start)) => SliceRange::Time { start, duration },
+                _ => anyhow::bail!("Give exactly one of --annotation, --start-sample or --start-time"),
+            };
+            let compress = compress.as_deref().map(Compression::from_string).transpose()?;
+            let report = extract_slice(&meta_file, &range, &out)?;
+            if let Some(compression) = compress {
+                compress_data_file(&report.data_path, compression)?;
+            }
+            println!("Extracted samples {}..{} to {}",
+                report.start_sample,
+                report.start_sample + report.num_samples,
+                report.meta_path.display());
+        }
+
+        Commands::Channelize { meta_file, freq, offset, bandwidth, decimation, rate, out, compress } => {
+            let offset_hz = match (freq, offset) {
+                (Some(freq), _) => {
+                    let center = SigMFParser::from_meta_file(&meta_file)?.center_frequency()
+                        .ok_or_else(|| anyhow::anyhow!("{} has no center frequency; give --offset instead", meta_file))?;
+                    freq - center
+                }
+                (None, offset) => offset.unwrap_or(0.0),
+            };
+            let compress = compress.as_deref().map(Compression::from_string).transpose()?;
+            let params = ChannelParams { offset_hz, bandwidth_hz: bandwidth, decimation };
+            let report = channelize_recording(&meta_file, &params, rate, &out)?;
+            if let Some(compression) = compress {
+                compress_data_file(&report.data_path, compression)?;
+            }
+            println!("Wrote {} samples at {} ({} wide{}) to {}",
+                report.num_samples,
+                format_frequency(report.sample_rate),
+                format_frequency(bandwidth),
+                report.center_freq.map(|freq| format!(", centered on {}", format_frequency(freq))).unwrap_or_default(),
+                report.meta_path.display());
+        }
+
+        Commands::Link { meta_file, annotation, start_sample, num_samples, freq_lo, freq_hi } => {
+            let mut link = match annotation {
+                Some(index) => {
+                    let parser = SigMFParser::from_meta_file(&meta_file)?;
+                    let annotation = parser.get_annotations()
+                        .and_then(|annotations| annotations.get(index))
+                        .ok_or_else(|| anyhow::anyhow!("Recording has no annotation {}", index))?;
+                    DeepLink::for_annotation(&meta_file, annotation)
+                }
+                None => DeepLink {
+                    sample_start: start_sample,
+                    sample_count: num_samples,
+                    ..DeepLink::new(&meta_file)
+                },
+            };
+            link.freq_lower = freq_lo.or(link.freq_lower);
+            link.freq_upper = freq_hi.or(link.freq_upper);
+            println!("{}", link);
+        }
+
+        Commands::Rollback { batch, list, force } => {
+            let journal_path = Journal::default_path();
+            let batches = list_batches(&read_entries(&journal_path)?);
+            if list {
+                for batch in &batches {
+                    let state = if batch.rolled_back { " (rolled back)" } else { "" };
+                    println!("{}  {}  {} ops  {}{}",
+                        batch.batch, batch.timestamp, batch.operations, batch.description, state);
+                }
+                return Ok(());
+            }
+            let batch = match batch {
+                Some(batch) => batch,
+                None => batches
+                    .iter()
+                    .rev()
+                    .find(|b| !b.rolled_back && b.reverts.is_none())
+                    .map(|b| b.batch.clone())
+                    .ok_or_else(|| anyhow::anyhow!("Nothing to roll back in {:?}", journal_path))?,
+            };
+            let report = rollback(&journal_path, &batch, force)?;
+            println!("Reverted {} operations from batch {}", report.reverted, batch);
+            for skipped in &report.skipped {
+                println!("    skipped: {}", skipped);
+            }
+            if !report.skipped.is_empty() {
+                println!("Use --force to revert changed files anyway");
+                std::process::exit(1);
+            }
+        }
+
+        Commands::Run { job_file } => {
+            let job = JobFile::from_file(&job_file)?;
+            job.run()?;
+        }
+
+        Commands::Pack { output, inputs } => {
+            let mut meta_paths = Vec::new();
+            for input in &inputs {
+                meta_paths.extend(SigMFDataset::meta_files(input)?);
+            }
+            let count = pack_archive(&output, &meta_paths)?;
+            println!("Packed {} recordings into {}", count, output);
+        }
+
+        Commands::VrtRecord { output, listen, duration, packets } => {
+            println!("Recording VITA-49 packets from {} to {}", listen, output);
+            let duration = duration.map(std::time::Duration::from_secs_f64);
+            let report = record_udp(&listen, std::path::Path::new(&output), duration, packets, |report| {
+                println!("{} packets, {} bytes", report.packets, report.bytes);
+            })?;
+            println!("Recorded {} packets ({} bytes) to {}", report.packets, report.bytes, output);
+            if report.skipped > 0 {
+                println!("Skipped {} datagrams that weren't VITA-49 packets", report.skipped);
+            }
+        }
+
+        Commands::Capture { driver, freq, rate, seconds, gain, channel, out } => {
+            let settings = CaptureSettings { driver, frequency: freq, sample_rate: rate, seconds, gain, channel };
+            println!("Recording {} s at {} ({} samples/s) from {}", seconds, format_frequency(freq), rate, settings.driver);
+            let total = settings.num_samples();
+            let report = capture(&settings, std::path::Path::new(&out), |written| {
+                println!("{} of {} samples", written, total);
+            })?;
+            println!("Recorded {} samples to {}", report.num_samples, report.meta_path.display());
+            if report.overflows > 0 {
+                println!("Warning: the device dropped samples {} time(s); the recording has gaps", report.overflows);
+            }
+        }
+
+        Commands::Ingest { udp, zmq_sub, zmq_pull, rate, freq, datatype, roll, duration, prefix, out_dir } => {
+            let source = match (udp, zmq_sub, zmq_pull) {
+                (Some(address), None, None) => IngestSource::Udp(address),
+                (None, Some(endpoint), None) => IngestSource::ZmqSub(endpoint),
+                (None, None, Some(endpoint)) => IngestSource::ZmqPull(endpoint),
+                _ => anyhow::bail!("Give exactly one of --udp, --zmq-sub and --zmq-pull"),
+            };
+            if roll <= 0.0 {
+                anyhow::bail!("--roll must be positive");
+            }
+            let settings = IngestSettings {
+                source,
+                data_type: SigMFDataType::from_string(&datatype)?,
+                sample_rate: rate,
+                frequency: freq,
+                roll: std::time::Duration::from_secs_f64(roll),
+                out_dir: out_dir.into(),
+                prefix,
+                duration: duration.map(std::time::Duration::from_secs_f64),
+            };
+            println!("Recording {} into {:?}, {} s per recording", settings.source.describe(), settings.out_dir, roll);
+            let report = ingest(&settings, |recording| {
+                println!("Wrote {} ({} samples)", recording.meta_path.display(), recording.num_samples);
+            })?;
+            println!("Wrote {} recordings, {} samples", report.recordings, report.samples);
+            if report.dropped_bytes > 0 {
+                println!("Dropped {} bytes of messages that didn't end on a whole sample", report.dropped_bytes);
+            }
+        }
+
+        Commands::Infer { dir, model, classes, name, snippet, layout, normalize, output, write_annotations } => {
+            if output.is_none() && !write_annotations {
+                anyhow::bail!("Give --output, --write-annotations or both");
+            }
+            let options = ClassifierOptions { snippet_len: snippet, layout: InputLayout::from_string(&layout)?, normalize };
+            let model_path = std::path::Path::new(&model);
+            let classifier = Classifier::load(model_path, classes, options)?;
+            let name = name.unwrap_or_else(|| model_path.file_stem().unwrap_or_default().to_string_lossy().to_string());
+            let columns = probability_columns(&name, classifier.classes());
+
+            let meta_files = SigMFDataset::meta_files(&dir)?;
+            let journal = Journal::begin(&format!("infer {} {}", model, dir));
+            let mut results = Vec::new();
+            let mut written = 0;
+            for (done, meta_path) in meta_files.iter().enumerate() {
+                if done % 10 == 0 {
+                    println!("Classified {} of {} recordings...", done, meta_files.len());
+                }
+                match infer_recording(meta_path, &classifier) {
+                    Ok(result) => {
+                        if write_annotations {
+                            written += write_annotation_probs(&result, classifier.classes(), &journal)?;
+                        }
+                        results.push(result);
+                    }
+                    Err(e) => warn!("Failed to classify {:?}: {}", meta_path, e),
+                }
+            }
+            println!("Classified {} of {} recordings", results.len(), meta_files.len());
+            if write_annotations {
+                println!("Wrote probabilities into {} annotations (batch {})", written, journal.batch());
+            }
+
+            if let Some(output) = output {
+                let scanned_at = chrono::Utc::now();
+                let dataset = SigMFDataset::from_directory(&dir)?;
+                let mut dataset = append_probabilities(dataset, std::path::Path::new(&dir), &results, &columns)?;
+                let format = ExportFormat::from_path(&output).unwrap_or(ExportFormat::Csv);
+                write_dataset(&mut dataset, &output, format)?;
+                Provenance::new(&dir, scanned_at, dataset.height()).write(&output)?;
+                println!("Saved dataset with {} to: {}", columns.join(", "), output);
+            }
+        }
+
+        Commands::Demod { meta_file, mode, freq, bandwidth, start_sample, num_samples, audio_rate, deemphasis, output } => {
+            let parser = SigMFParser::from_meta_file(&meta_file)?;
+            let signal = likeliest_analog(&parser);
+            let mode = match (mode.as_str(), &signal) {
+                ("auto", Some(signal)) => signal.mode,
+                ("auto", None) => anyhow::bail!("No annotation of {} has an FM or AM probability; pass --mode", meta_file),
+                (mode, _) => DemodMode::from_string(mode)?,
+            };
+            if let Some(ref signal) = signal {
+                println!("Annotation at sample {} ({} {:.0}%)", signal.sample_start, signal.mode.name(), signal.probability * 100.0);
+            }
+            let center = parser.center_frequency().unwrap_or(0.0);
+            let freq = freq.or(signal.as_ref().and_then(|s| s.freq_hz)).unwrap_or(center);
+            let params = DemodParams {
+                mode,
+                offset_hz: freq - center,
+                bandwidth_hz: bandwidth.or(signal.as_ref().and_then(|s| s.bandwidth_hz)).unwrap_or(mode.default_bandwidth_hz()),
+                audio_rate,
+                deemphasis_us: deemphasis,
+            };
+            let start = start_sample.or(signal.as_ref().map(|s| s.sample_start)).unwrap_or(0);
+            // A zero-length annotation covers the rest of the recording
+            let count = num_samples.or(signal.as_ref().map(|s| s.sample_count).filter(|count| *count > 0)).unwrap_or(u64::MAX);
+            println!("{} at {} ({} wide), samples from {}", mode.name(), format_frequency(freq), format_frequency(params.bandwidth_hz), start);
+            let mut reader = parser.open_reader()?;
+            let audio = demodulate(&mut reader, parser.sample_rate(), start, count, &params, |_| Ok(()))?;
+            write_wav(std::path::Path::new(&output), &audio)?;
+            println!("Wrote {:.2} s of audio at {} Hz to {}", audio.duration_s(), audio.sample_rate, output);
+        }
+
+        Commands::Detect { path, window, threshold_db, noise_floor, min_duration, merge_gap, bandwidth_db, write_annotations, label, replace, output } => {
+            let params = DetectParams {
+                window,
+                threshold_db,
+                noise_floor_db: noise_floor,
+                min_duration_s: min_duration,
+                merge_gap_s: merge_gap,
+                bandwidth_db,
+                ..DetectParams::default()
+            };
+            let meta_files = SigMFDataset::meta_files(&path)?;
+            let journal = Journal::begin(&format!("detect {}", path));
+            let mut detections = Vec::new();
+            let mut written = 0;
+            for meta_path in &meta_files {
+                let detection = match detect_recording(meta_path, &params, |_| Ok(())) {
+                    Ok(detection) => detection,
+                    Err(e) => {
+                        warn!("Failed to scan {}: {}", meta_path.display(), e);
+                        continue;
+                    }
+                };
+                println!("{}: {} bursts (noise floor {:.1} dB)", meta_path.display(), detection.bursts.len(), detection.noise_floor_db);
+                for burst in &detection.bursts {
+                    println!("    {:>12} .. {:<12} {:>10.6} s  {:>16}  bw {:>12}  SNR {:>5.1} dB",
+                        burst.start_sample, burst.stop_sample(), burst.duration_s,
+                        format_frequency(burst.center_freq_hz), format_frequency(burst.bandwidth_hz), burst.snr_db);
+                }
+                if write_annotations {
+                    written += write_bursts(&detection, label.as_deref(), replace, &journal)?;
+                }
+                detections.push(detection);
+            }
+            let total: usize = detections.iter().map(|d| d.bursts.len()).sum();
+            println!("{} bursts in {} of {} recordings", total, detections.len(), meta_files.len());
+            if write_annotations {
+                println!("Wrote {} annotations (batch {})", written, journal.batch());
+            }
+            if let Some(output) = output {
+                let mut table = bursts_dataframe(&detections)?;
+                write_dataset(&mut table, &output, ExportFormat::from_path(&output).unwrap_or(ExportFormat::Csv))?;
+                println!("Saved {} bursts to: {}", table.height(), output);
+            }
+        }
+
+        Commands::Constellation { meta_file, start_sample, num_samples, correct, output } => {
+            let parser = SigMFParser::from_meta_file(&meta_file)?;
+            let mut reader = parser.open_reader()?;
+            let correction = correct.map_or(FreqCorrection::None, FreqCorrection::PowerPeak);
+            let constellation = Constellation::compute(
+                &mut reader,
+                start_sample,
+                num_samples,
+                parser.sample_rate(),
+                correction,
+            )?;
+            if let Some(offset) = constellation.freq_offset_hz {
+                println!("Removed frequency offset: {:.1} Hz", offset);
+            }
+            constellation.write_csv(&output)?;
+            println!("Saved {} points to: {}", constellation.points.len(), output);
         }
     }
     
     Ok(())
 }
+
+/// The progress bar being drawn, if any, which log lines go above
+static PROGRESS_BAR: Mutex<Option<ProgressBar>> = Mutex::new(None);
+
+/// Log to stderr: info and up by default, debug with `-v`, everything with
+/// `-vv`. Other crates only log warnings unless `-vv`.
+fn init_logging(verbose: u8) {
+    let level = match verbose {
+        0 => LevelFilter::INFO,
+        1 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    };
+    let others = if verbose >= 2 { LevelFilter::TRACE } else { LevelFilter::WARN };
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(|| LogWriter)
+                .with_ansi(std::io::stderr().is_terminal())
+                .without_time()
+                .with_target(verbose > 0)
+                .with_filter(Targets::new().with_target("sig_viewer", level).with_target("sig_viewer_cli", level).with_default(others)),
+        )
+        .init();
+}
+
+/// Writes log lines to stderr, above the progress bar while one is drawn
+struct LogWriter;
+
+impl std::io::Write for LogWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let bar = PROGRESS_BAR.lock().ok().and_then(|bar| bar.clone());
+        match bar {
+            Some(bar) => bar.suspend(|| std::io::stderr().write(buf)),
+            None => std::io::stderr().write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        std::io::stderr().flush()
+    }
+}
+
+/// Scan `dirs` (combined as by `SigMFDataset::from_directories` if there
+/// are several) with a progress bar on stderr showing files/s, ETA and the
+/// error count, failures logged above it. `quiet` leaves out the bar and the
+/// closing summary; failures are logged either way.
+fn scan_with_progress(dirs: &[String], options: &DatasetOptions, quiet: bool) -> Result<DataFrame> {
+    // Hidden anyway when stderr isn't a terminal
+    let bar = if quiet { ProgressBar::hidden() } else { ProgressBar::new(0) };
+    bar.set_style(
+        ProgressStyle::with_template("{spinner} [{elapsed_precise}] {wide_bar} {pos}/{len} files ({rate}, ETA {eta}) {msg}")?
+            .with_key("rate", |state: &ProgressState, w: &mut dyn std::fmt::Write| {
+                let _ = write!(w, "{:.0} files/s", state.per_sec());
+            })
+            .progress_chars("=> "),
+    );
+    let started = std::time::Instant::now();
+    let (mut total, mut errors) = (0, 0);
+    if let Ok(mut shown) = PROGRESS_BAR.lock() {
+        *shown = Some(bar.clone());
+    }
+    let on_progress = |progress: ScanProgress| {
+        for failure in progress.failures {
+            warn!("{}", failure);
+        }
+        bar.set_length(progress.total as u64);
+        bar.set_position(progress.done as u64);
+        if progress.errors > 0 {
+            bar.set_message(format!("{} errors", progress.errors));
+        }
+        (total, errors) = (progress.total, progress.errors);
+        Ok(())
+    };
+    let dataset = if dirs.len() > 1 {
+        SigMFDataset::from_directories_with_progress(dirs, options, on_progress)
+    } else {
+        SigMFDataset::from_directory_with_progress(&dirs[0], options, on_progress)
+    };
+    bar.finish_and_clear();
+    if let Ok(mut shown) = PROGRESS_BAR.lock() {
+        *shown = None;
+    }
+    let dataset = dataset?;
+    if !quiet {
+        let elapsed = started.elapsed().as_secs_f64();
+        println!(
+            "Scanned {} files in {:.1} s ({:.0} files/s), {} errors: {} rows",
+            total,
+            elapsed,
+            total as f64 / elapsed.max(1e-3),
+            errors,
+            dataset.height()
+        );
+    }
+    Ok(dataset)
+}
+
+/// `df` as an aligned text table with every row and column, where polars'
+/// own display elides them to fit the terminal. Numbers are right-aligned.
+fn whole_table(df: &DataFrame) -> String {
+    let cell = |value: AnyValue| -> String {
+        match value {
+            AnyValue::Null => String::new(),
+            AnyValue::String(s) => s.to_string(),
+            AnyValue::Float32(x) => format_float(x as f64),
+            AnyValue::Float64(x) => format_float(x),
+            value => value.to_string(),
+        }
+    };
+    let columns: Vec<(Vec<String>, bool)> = df
+        .get_columns()
+        .iter()
+        .map(|column| {
+            let mut cells = vec![column.name().to_string()];
+            cells.extend((0..df.height()).map(|row| column.get(row).map(&cell).unwrap_or_default()));
+            (cells, column.dtype().is_numeric())
+        })
+        .collect();
+    let widths: Vec<usize> = columns.iter().map(|(cells, _)| cells.iter().map(|c| c.chars().count()).max().unwrap_or(0)).collect();
+    let mut text = String::new();
+    for row in 0..=df.height() {
+        let line: Vec<String> = columns
+            .iter()
+            .zip(&widths)
+            .map(|((cells, numeric), &width)| if *numeric && row > 0 { format!("{:>width$}", cells[row]) } else { format!("{:<width$}", cells[row]) })
+            .collect();
+        text.push_str(line.join("  ").trim_end());
+        text.push('\n');
+        if row == 0 {
+            let rule: Vec<String> = widths.iter().map(|&width| "-".repeat(width)).collect();
+            text.push_str(&rule.join("  "));
+            text.push('\n');
+        }
+    }
+    text
+}
+
+/// Up to 6 decimals without trailing zeros, or scientific notation for
+/// values too large or small for that
+fn format_float(x: f64) -> String {
+    if x != 0.0 && x.is_finite() && !(1e-4..1e15).contains(&x.abs()) {
+        return format!("{:.4e}", x);
+    }
+    let text = format!("{:.6}", x);
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
@@ -0,0 +1,297 @@
+//! DigitalRF channels: directories of HDF5 files holding long captures.
+//!
+//! A channel is a directory with a `drf_properties.h5` (sample rate, sample
+//! type) and time-named subdirectories of `rf@<seconds>.<ms>.h5` files, each
+//! holding a run of samples (`rf_data`) and where they fall in time
+//! (`rf_data_index`, global sample index → row). Each channel becomes one
+//! recording: its start time and duration come from the first and last
+//! indexed samples, gaps between blocks read as zeros, and only the first
+//! subchannel is read. The center frequency comes from the channel's
+//! `metadata/` (Digital Metadata `center_frequencies`) when present, else
+//! from the sidecar `<channel>.toml` and defaults (see [`super::raw`]).
+//!
+//! Reading HDF5 needs the system HDF5 library, so this is behind the
+//! `digitalrf` feature; without it, channels found in a scan are reported
+//! as unreadable.
+
+use super::format::RecordingFormat;
+use super::sigmf::SigMFParser;
+use std::path::Path;
+
+/// The file that marks a directory as a DigitalRF channel
+pub const DRF_PROPERTIES: &str = "drf_properties.h5";
+
+pub fn is_channel(path: &Path) -> bool {
+    path.join(DRF_PROPERTIES).is_file()
+}
+
+pub struct DigitalRfFormat;
+
+impl RecordingFormat for DigitalRfFormat {
+    fn name(&self) -> &str {
+        "DigitalRF"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &[]
+    }
+
+    fn marker_file(&self) -> Option<&str> {
+        Some(DRF_PROPERTIES)
+    }
+
+    fn detect(&self, path: &Path) -> bool {
+        is_channel(path)
+    }
+
+    fn open(&self, path: &Path) -> anyhow::Result<SigMFParser> {
+        open_channel(path)
+    }
+}
+
+#[cfg(not(feature = "digitalrf"))]
+pub fn open_channel(path: &Path) -> anyhow::Result<SigMFParser> {
+    anyhow::bail!("{:?} is a DigitalRF channel; rebuild with `--features digitalrf` to read it", path)
+}
+
+#[cfg(feature = "digitalrf")]
+pub use imp::{open_channel, DigitalRfReader};
+
+#[cfg(feature = "digitalrf")]
+mod imp {
+    use super::DRF_PROPERTIES;
+    use crate::parser::raw::{raw_iq_params, synthesize, RawIqParams};
+    use crate::parser::sigmf::{DataLocation, SigMFDataType, SigMFParser};
+    use crate::parser::SampleReader;
+    use anyhow::Result;
+    use chrono::DateTime;
+    use hdf5::H5Type;
+    use num_complex::Complex;
+    use std::ops::Range;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use walkdir::WalkDir;
+
+    #[derive(H5Type, Clone, Copy)]
+    #[repr(C)]
+    struct ComplexI8 {
+        r: i8,
+        i: i8,
+    }
+
+    #[derive(H5Type, Clone, Copy)]
+    #[repr(C)]
+    struct ComplexI16 {
+        r: i16,
+        i: i16,
+    }
+
+    #[derive(H5Type, Clone, Copy)]
+    #[repr(C)]
+    struct ComplexF32 {
+        r: f32,
+        i: f32,
+    }
+
+    /// How a channel's samples are stored
+    #[derive(Debug, Clone, Copy)]
+    enum SampleKind {
+        I8,
+        I16,
+        F32,
+    }
+
+    impl SampleKind {
+        fn data_type(self) -> SigMFDataType {
+            match self {
+                SampleKind::I8 => SigMFDataType::Ci8,
+                SampleKind::I16 => SigMFDataType::Ci16Le,
+                SampleKind::F32 => SigMFDataType::Cf32Le,
+            }
+        }
+    }
+
+    /// A run of consecutive samples in one file
+    #[derive(Debug, Clone)]
+    struct Block {
+        /// Global sample index (samples since the Unix epoch) of the first sample
+        global: u64,
+        file: usize,
+        /// First row in the file's `rf_data`
+        row: u64,
+        len: u64,
+    }
+
+    /// Where every sample of a channel is
+    #[derive(Debug)]
+    struct ChannelIndex {
+        files: Vec<PathBuf>,
+        /// In global order
+        blocks: Vec<Block>,
+        kind: SampleKind,
+    }
+
+    impl ChannelIndex {
+        fn start(&self) -> u64 {
+            self.blocks.first().map(|b| b.global).unwrap_or(0)
+        }
+
+        /// Samples from the first to the last, gaps included
+        fn num_samples(&self) -> u64 {
+            self.blocks.last().map(|b| b.global + b.len - self.start()).unwrap_or(0)
+        }
+    }
+
+    /// First value of an attribute stored as a scalar or a one-element array
+    fn attr<T: H5Type + Copy>(group: &hdf5::Group, name: &str) -> Option<T> {
+        group.attr(name).ok()?.read_raw::<T>().ok()?.first().copied()
+    }
+
+    fn read_index(channel: &Path) -> Result<(f64, ChannelIndex)> {
+        let properties = hdf5::File::open(channel.join(DRF_PROPERTIES))?;
+        let sample_rate = match (attr::<u64>(&properties, "sample_rate_numerator"), attr::<u64>(&properties, "sample_rate_denominator")) {
+            (Some(numerator), Some(denominator)) if denominator > 0 => numerator as f64 / denominator as f64,
+            _ => attr::<f64>(&properties, "samples_per_second").ok_or_else(|| anyhow::anyhow!("{:?} gives no sample rate", channel))?,
+        };
+        if attr::<i64>(&properties, "is_complex").unwrap_or(0) == 0 {
+            anyhow::bail!("{:?} holds real samples; only complex channels are supported", channel);
+        }
+        // H5T_INTEGER is 0, H5T_FLOAT 1; the size is per component
+        let kind = match (attr::<i64>(&properties, "H5Tget_class"), attr::<i64>(&properties, "H5Tget_size")) {
+            (Some(0), Some(1)) => SampleKind::I8,
+            (Some(0), Some(2)) => SampleKind::I16,
+            (Some(1), Some(4)) => SampleKind::F32,
+            (class, size) => anyhow::bail!("{:?}: unsupported sample type (class {:?}, {:?} bytes)", channel, class, size),
+        };
+
+        let mut files: Vec<PathBuf> = WalkDir::new(channel)
+            .min_depth(2)
+            .max_depth(2)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .map(|e| e.into_path())
+            .filter(|path| {
+                let name = path.file_name().unwrap_or_default().to_string_lossy();
+                name.starts_with("rf@") && name.ends_with(".h5")
+            })
+            .collect();
+        files.sort();
+
+        let mut blocks = Vec::new();
+        for (file_idx, path) in files.iter().enumerate() {
+            let file = hdf5::File::open(path)?;
+            let rows = file.dataset("rf_data")?.shape().first().copied().unwrap_or(0) as u64;
+            // (global index, row) pairs, one per block
+            let index = file.dataset("rf_data_index")?.read_raw::<u64>()?;
+            let pairs: Vec<(u64, u64)> = index.chunks_exact(2).map(|pair| (pair[0], pair[1])).collect();
+            for (k, (global, row)) in pairs.iter().enumerate() {
+                let end = pairs.get(k + 1).map(|(_, next_row)| *next_row).unwrap_or(rows);
+                if end > *row {
+                    blocks.push(Block { global: *global, file: file_idx, row: *row, len: end - row });
+                }
+            }
+        }
+        if blocks.is_empty() {
+            anyhow::bail!("{:?} has no samples", channel);
+        }
+        blocks.sort_by_key(|block| block.global);
+        Ok((sample_rate, ChannelIndex { files, blocks, kind }))
+    }
+
+    /// The first center frequency in the channel's Digital Metadata, if any
+    fn metadata_center_freq(channel: &Path) -> Option<f64> {
+        let mut files: Vec<PathBuf> = WalkDir::new(channel.join("metadata"))
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .map(|e| e.into_path())
+            .filter(|path| path.file_name().is_some_and(|name| name.to_string_lossy().starts_with("metadata@")))
+            .collect();
+        files.sort();
+        let file = hdf5::File::open(files.first()?).ok()?;
+        // One group per sample index with metadata
+        let mut samples = file.member_names().ok()?;
+        samples.sort_by_key(|name| name.parse::<u64>().unwrap_or(u64::MAX));
+        let group = file.group(samples.first()?).ok()?;
+        group.dataset("center_frequencies").ok()?.read_raw::<f64>().ok()?.first().copied()
+    }
+
+    /// Open a channel directory as a single-capture recording
+    pub fn open_channel(path: &Path) -> Result<SigMFParser> {
+        let (sample_rate, index) = read_index(path)?;
+        let start_seconds = index.start() as f64 / sample_rate;
+        let datetime = DateTime::from_timestamp(start_seconds.floor() as i64, (start_seconds.fract() * 1e9) as u32)
+            .map(|time| time.format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string());
+        let params = RawIqParams {
+            center_freq: metadata_center_freq(path),
+            datetime,
+            description: Some("DigitalRF channel".to_string()),
+            ..Default::default()
+        }
+        .or(&raw_iq_params(path)?);
+
+        let data_type = index.kind.data_type();
+        let data_location = DataLocation {
+            file: path.to_path_buf(),
+            offset: 0,
+            size: index.num_samples() * data_type.sample_size_bytes() as u64,
+            pieces: None,
+        };
+        let mut parser = synthesize(path, params, sample_rate, data_type, data_location);
+        let index = Arc::new(index);
+        parser.open_samples = Some(Arc::new(move || Ok(Box::new(DigitalRfReader { index: index.clone(), open: None }) as Box<dyn SampleReader + Send>)));
+        Ok(parser)
+    }
+
+    /// Reads a channel's first subchannel across its files
+    pub struct DigitalRfReader {
+        index: Arc<ChannelIndex>,
+        /// The last file read from, kept open
+        open: Option<(usize, hdf5::File)>,
+    }
+
+    impl DigitalRfReader {
+        fn read_rows(&mut self, file_idx: usize, rows: Range<usize>) -> Result<Vec<Complex<f32>>> {
+            if self.open.as_ref().is_none_or(|(idx, _)| *idx != file_idx) {
+                self.open = Some((file_idx, hdf5::File::open(&self.index.files[file_idx])?));
+            }
+            let (_, file) = self.open.as_ref().expect("just opened");
+            let data = file.dataset("rf_data")?;
+            fn column<T: H5Type>(data: &hdf5::Dataset, rows: Range<usize>) -> Result<Vec<T>> {
+                Ok(if data.ndim() == 1 { data.read_slice_1d::<T, _>(rows)?.to_vec() } else { data.read_slice_1d::<T, _>((rows, 0))?.to_vec() })
+            }
+            Ok(match self.index.kind {
+                SampleKind::I8 => column::<ComplexI8>(&data, rows)?.iter().map(|c| Complex::new(c.r as f32 / 128.0, c.i as f32 / 128.0)).collect(),
+                SampleKind::I16 => column::<ComplexI16>(&data, rows)?.iter().map(|c| Complex::new(c.r as f32 / 32768.0, c.i as f32 / 32768.0)).collect(),
+                SampleKind::F32 => column::<ComplexF32>(&data, rows)?.iter().map(|c| Complex::new(c.r, c.i)).collect(),
+            })
+        }
+    }
+
+    impl SampleReader for DigitalRfReader {
+        fn num_samples(&self) -> u64 {
+            self.index.num_samples()
+        }
+
+        fn read_samples(&mut self, start: u64, count: usize) -> Result<Vec<Complex<f32>>> {
+            let total = self.num_samples();
+            if start >= total {
+                return Ok(Vec::new());
+            }
+            let count = count.min((total - start) as usize);
+            let first = self.index.start() + start;
+            let last = first + count as u64;
+            let mut samples = vec![Complex::new(0.0, 0.0); count];
+            let index = self.index.clone();
+            let from = index.blocks.partition_point(|block| block.global + block.len <= first);
+            for block in index.blocks[from..].iter().take_while(|block| block.global < last) {
+                let overlap_start = block.global.max(first);
+                let overlap_end = (block.global + block.len).min(last);
+                let row = (block.row + overlap_start - block.global) as usize;
+                let read = self.read_rows(block.file, row..row + (overlap_end - overlap_start) as usize)?;
+                let at = (overlap_start - first) as usize;
+                samples[at..at + read.len()].copy_from_slice(&read);
+            }
+            Ok(samples)
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::path::PathBuf;
+use std::sync::Arc;
+use thiserror::Error;
+
+/// Why a recording couldn't be read. Sources are shared so a scan's
+/// failure report can be copied out to the GUI.
+#[derive(Debug, Clone, Error)]
+pub enum ParserError {
+    #[error("cannot read file: {0}")]
+    Io(Arc<std::io::Error>),
+    #[error("invalid metadata JSON: {0}")]
+    InvalidJson(Arc<serde_json::Error>),
+    #[error("Unsupported datatype: {0}")]
+    UnsupportedDatatype(String),
+    /// `searched` counts the data roots looked through as well
+    #[error("Data file does not exist: {path:?}{}", searched_note(*.searched))]
+    MissingDataFile { path: PathBuf, searched: usize },
+    /// Anything else, as its message with its causes
+    #[error("{0}")]
+    Other(String),
+}
+
+fn searched_note(searched: usize) -> String {
+    match searched {
+        0 => String::new(),
+        1 => " (also searched 1 data root)".to_string(),
+        n => format!(" (also searched {} data roots)", n),
+    }
+}
+
+impl ParserError {
+    /// A short name for the kind of failure, to group failures by
+    pub fn kind(&self) -> &'static str {
+        match self {
+            ParserError::Io(_) => "I/O error",
+            ParserError::InvalidJson(_) => "Invalid JSON",
+            ParserError::UnsupportedDatatype(_) => "Unsupported datatype",
+            ParserError::MissingDataFile { .. } => "Missing data file",
+            ParserError::Other(_) => "Other",
+        }
+    }
+}
+
+impl From<std::io::Error> for ParserError {
+    fn from(e: std::io::Error) -> Self {
+        ParserError::Io(Arc::new(e))
+    }
+}
+
+impl From<serde_json::Error> for ParserError {
+    fn from(e: serde_json::Error) -> Self {
+        ParserError::InvalidJson(Arc::new(e))
+    }
+}
+
+/// Recover the typed error behind an `anyhow` one, for the parsers that
+/// still return `anyhow::Result`
+impl From<anyhow::Error> for ParserError {
+    fn from(e: anyhow::Error) -> Self {
+        let e = match e.downcast::<ParserError>() {
+            Ok(e) => return e,
+            Err(e) => e,
+        };
+        let e = match e.downcast::<std::io::Error>() {
+            Ok(e) => return e.into(),
+            Err(e) => e,
+        };
+        match e.downcast::<serde_json::Error>() {
+            Ok(e) => e.into(),
+            Err(e) => ParserError::Other(format!("{:#}", e)),
+        }
+    }
+}
@@ -0,0 +1,115 @@
+//! Recording formats other than SigMF itself, and the registry the parser,
+//! directory scans and viewers look them up in.
+//!
+//! A format recognizes its files, reads each recording's metadata into a
+//! [`SigMFParser`] (which the summary rows are built from, so the recording
+//! sits in a dataset like any SigMF file) and opens a reader over its
+//! samples. The built-in ones are raw IQ (`.cfile`), WAV, VITA-49, BLUE and
+//! DigitalRF; others can be added with [`register_format`], e.g. by a crate
+//! using sig_viewer as a library, without changes here.
+
+use super::digitalrf::DigitalRfFormat;
+use super::midas::BlueFormat;
+use super::raw::RawIqFormat;
+use super::sigmf::SigMFParser;
+use super::vrt::Vita49Format;
+use super::wav::WavFormat;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock, RwLock};
+
+pub trait RecordingFormat: Send + Sync {
+    /// Shown in messages, e.g. `WAV`
+    fn name(&self) -> &str;
+
+    /// File extensions (without the dot) a directory scan looks at
+    fn extensions(&self) -> &[&str];
+
+    /// A file whose directory is a recording in this format, for formats
+    /// stored as a directory (DigitalRF's `drf_properties.h5`)
+    fn marker_file(&self) -> Option<&str> {
+        None
+    }
+
+    /// Whether `path` is a recording (or a file of recordings) in this
+    /// format. By default, whether it has one of [`Self::extensions`];
+    /// formats sharing a common extension should check the contents too.
+    fn detect(&self, path: &Path) -> bool {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .is_some_and(|ext| self.extensions().contains(&ext))
+    }
+
+    /// Read one recording's metadata. `path` is either one [`Self::detect`]
+    /// accepted or one of the recording paths [`Self::recordings`] gave.
+    /// Samples are read from the parser's `data_location` as its
+    /// `data_type`; formats that can't be read that way set its
+    /// `open_samples`, so every reader of the recording goes through it.
+    fn open(&self, path: &Path) -> Result<SigMFParser>;
+
+    /// Every recording in the file at `path`, under the path each opens
+    /// with. Most files hold one, the file itself; formats holding several
+    /// (VITA-49 streams) give each a virtual path below the file.
+    fn recordings(&self, path: &Path) -> Result<Vec<(PathBuf, Result<SigMFParser>)>> {
+        Ok(vec![(path.to_path_buf(), self.open(path))])
+    }
+}
+
+fn registry() -> &'static RwLock<Vec<Arc<dyn RecordingFormat>>> {
+    static FORMATS: OnceLock<RwLock<Vec<Arc<dyn RecordingFormat>>>> = OnceLock::new();
+    FORMATS.get_or_init(|| {
+        RwLock::new(vec![
+            Arc::new(DigitalRfFormat),
+            Arc::new(RawIqFormat),
+            Arc::new(WavFormat),
+            Arc::new(Vita49Format),
+            Arc::new(BlueFormat),
+        ])
+    })
+}
+
+/// Read recordings in `format` for the rest of the process. It's checked
+/// before the formats already registered, so it can take over an extension.
+pub fn register_format(format: Arc<dyn RecordingFormat>) {
+    registry().write().unwrap_or_else(|e| e.into_inner()).insert(0, format);
+}
+
+/// Every registered format, in the order they're checked
+pub fn recording_formats() -> Vec<Arc<dyn RecordingFormat>> {
+    registry().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// The format of `path`: the first that detects it, else the first listing
+/// its extension (so opening it says what's wrong with the file). None for
+/// SigMF files and anything unrecognized.
+pub fn find_format(path: &Path) -> Option<Arc<dyn RecordingFormat>> {
+    let formats = registry().read().unwrap_or_else(|e| e.into_inner());
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    formats
+        .iter()
+        .find(|format| format.detect(path))
+        .or_else(|| formats.iter().find(|format| extension.is_some_and(|ext| format.extensions().contains(&ext))))
+        .cloned()
+}
+
+/// The format a directory scan reads the file at `path` as: one listing its
+/// extension that also detects it
+pub fn find_file_format(path: &Path) -> Option<Arc<dyn RecordingFormat>> {
+    let extension = path.extension()?.to_str()?;
+    registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|format| format.extensions().contains(&extension) && format.detect(path))
+        .cloned()
+}
+
+/// The format stored as the directory holding a file named `file_name`
+pub fn find_marked_format(file_name: &str) -> Option<Arc<dyn RecordingFormat>> {
+    registry()
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find(|format| format.marker_file() == Some(file_name))
+        .cloned()
+}
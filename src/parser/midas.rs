@@ -0,0 +1,184 @@
+//! X-Midas BLUE files (`.tmp`, `.prm`): a 512-byte header followed by the
+//! samples, or with the samples in a detached `.det` file next to it.
+//!
+//! Type 1000 files (one-dimensional data) of complex samples are read: the
+//! format code gives the sample type (`CB`, `CI`, `CF`), the data
+//! representation their byte order, `xdelta` the sample period and the
+//! timecode (seconds since 1950) the start time. The center frequency comes
+//! from a main-header keyword (`RF_FREQ=...` and the like) when there is
+//! one, else from the sidecar and defaults (see [`super::raw`]).
+
+use super::format::RecordingFormat;
+use super::raw::{raw_iq_params, synthesize, RawIqParams};
+use super::sigmf::{DataLocation, SigMFDataType, SigMFParser};
+use anyhow::Result;
+use byteorder::{BigEndian, ByteOrder, LittleEndian};
+use chrono::DateTime;
+use std::fs::File;
+use std::io::Read;
+use std::path::Path;
+
+const HEADER_SIZE: usize = 512;
+
+/// Seconds from the Midas epoch (1950-01-01) to the Unix epoch
+const MIDAS_EPOCH_OFFSET: f64 = 631_152_000.0;
+
+/// Main-header keywords taken as the center frequency, in Hz
+const FREQUENCY_KEYWORDS: [&str; 4] = ["RF_FREQ", "RFFREQ", "COL_RF", "FREQUENCY"];
+
+/// What a BLUE header says about its samples
+#[derive(Debug, Clone)]
+pub struct BlueHeader {
+    /// e.g. 1000 for one-dimensional data
+    pub file_type: i32,
+    /// Two characters: mode (`S`calar, `C`omplex, ...) and type (`B`yte,
+    /// `I`nt16, `L` int32, `F`loat32, `D`ouble)
+    pub format: String,
+    /// `EEEI` (little endian) or `IEEE` (big endian)
+    pub data_rep: String,
+    pub detached: bool,
+    /// Byte range of the samples
+    pub data_start: u64,
+    pub data_size: u64,
+    /// Seconds since 1950-01-01, zero if unknown
+    pub timecode: f64,
+    /// Sample period in seconds (type 1000)
+    pub xdelta: f64,
+    /// `NAME=VALUE` pairs from the main header
+    pub keywords: Vec<(String, String)>,
+}
+
+impl BlueHeader {
+    pub fn sample_rate(&self) -> Option<f64> {
+        (self.xdelta > 0.0).then(|| 1.0 / self.xdelta)
+    }
+
+    pub fn data_type(&self) -> Result<SigMFDataType> {
+        match (self.format.as_str(), self.data_rep.as_str()) {
+            ("CB", _) => Ok(SigMFDataType::Ci8),
+            ("CI", "EEEI") => Ok(SigMFDataType::Ci16Le),
+            ("CI", "IEEE") => Ok(SigMFDataType::Ci16Be),
+            ("CF", "EEEI") => Ok(SigMFDataType::Cf32Le),
+            (format, rep) => anyhow::bail!("Unsupported BLUE format {} ({})", format, rep),
+        }
+    }
+
+    /// Start time as ISO 8601, if the timecode is set
+    pub fn start_time(&self) -> Option<String> {
+        if self.timecode <= 0.0 {
+            return None;
+        }
+        let unix = self.timecode - MIDAS_EPOCH_OFFSET;
+        DateTime::from_timestamp(unix.floor() as i64, (unix.fract() * 1e9) as u32)
+            .map(|time| time.format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string())
+    }
+
+    pub fn center_freq(&self) -> Option<f64> {
+        self.keywords
+            .iter()
+            .find(|(name, _)| FREQUENCY_KEYWORDS.contains(&name.to_ascii_uppercase().as_str()))
+            .and_then(|(_, value)| value.trim().parse().ok())
+    }
+}
+
+/// Whether `path` starts like a BLUE file; `.tmp` is a common extension
+pub fn is_blue(path: &Path) -> bool {
+    let mut magic = [0u8; 4];
+    File::open(path).and_then(|mut file| file.read_exact(&mut magic)).is_ok() && &magic == b"BLUE"
+}
+
+pub fn read_blue_header(path: &Path) -> Result<BlueHeader> {
+    let mut header = [0u8; HEADER_SIZE];
+    File::open(path)?
+        .read_exact(&mut header)
+        .map_err(|e| anyhow::anyhow!("{:?} is too short for a BLUE header: {}", path, e))?;
+    if &header[0..4] != b"BLUE" {
+        anyhow::bail!("{:?} is not a BLUE file", path);
+    }
+    let text = |range: std::ops::Range<usize>| String::from_utf8_lossy(&header[range]).trim_end_matches('\0').to_string();
+    let head_rep = text(4..8);
+    let data_rep = text(8..12);
+    // The header's own fields are in `head_rep` byte order
+    let big_endian = match head_rep.as_str() {
+        "EEEI" => false,
+        "IEEE" => true,
+        other => anyhow::bail!("{:?}: unknown header representation {:?}", path, other),
+    };
+    let read_i32 = |bytes: &[u8]| if big_endian { BigEndian::read_i32(bytes) } else { LittleEndian::read_i32(bytes) };
+    let read_f64 = |bytes: &[u8]| if big_endian { BigEndian::read_f64(bytes) } else { LittleEndian::read_f64(bytes) };
+
+    let keyword_len = (read_i32(&header[160..164]).max(0) as usize).min(92);
+    let keywords = header[164..164 + keyword_len]
+        .split(|byte| *byte == 0)
+        .filter_map(|pair| {
+            let pair = String::from_utf8_lossy(pair);
+            let (name, value) = pair.split_once('=')?;
+            Some((name.trim().to_string(), value.to_string()))
+        })
+        .collect();
+
+    Ok(BlueHeader {
+        file_type: read_i32(&header[48..52]),
+        format: text(52..54),
+        data_rep,
+        detached: read_i32(&header[12..16]) != 0,
+        data_start: read_f64(&header[32..40]).max(0.0) as u64,
+        data_size: read_f64(&header[40..48]).max(0.0) as u64,
+        timecode: read_f64(&header[56..64]),
+        xdelta: read_f64(&header[264..272]),
+        keywords,
+    })
+}
+
+/// Open a BLUE file as a single-capture SigMF recording, with `fallback`
+/// (sidecar and defaults) for what the header doesn't say
+pub(crate) fn open_blue(path: &Path, fallback: RawIqParams) -> Result<SigMFParser> {
+    let header = read_blue_header(path)?;
+    if header.file_type / 1000 != 1 {
+        anyhow::bail!("{:?} is a type {} BLUE file; only type 1000 files are read", path, header.file_type);
+    }
+    let data_type = header.data_type()?;
+    let params = RawIqParams {
+        sample_rate: header.sample_rate(),
+        center_freq: header.center_freq(),
+        datetime: header.start_time(),
+        ..Default::default()
+    }
+    .or(&fallback);
+    let sample_rate = params
+        .sample_rate
+        .filter(|rate| *rate > 0.0)
+        .ok_or_else(|| anyhow::anyhow!("No sample rate for {:?}: its header has no xdelta", path))?;
+
+    let data_file = if header.detached { path.with_extension("det") } else { path.to_path_buf() };
+    // A file still being written (or cut short) has fewer samples than the header says
+    let available = std::fs::metadata(&data_file)
+        .map_err(|e| anyhow::anyhow!("Data file {:?}: {}", data_file, e))?
+        .len()
+        .saturating_sub(header.data_start);
+    let data_location = DataLocation { file: data_file, offset: header.data_start, size: header.data_size.min(available), pieces: None };
+    let mut parser = synthesize(path, params, sample_rate, data_type, data_location);
+    parser.data_file_path = parser.data_location.file.clone();
+    Ok(parser)
+}
+
+pub struct BlueFormat;
+
+impl RecordingFormat for BlueFormat {
+    fn name(&self) -> &str {
+        "BLUE"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["tmp", "prm"]
+    }
+
+    /// Plenty of programs leave `.tmp` files around, so check the magic too
+    fn detect(&self, path: &Path) -> bool {
+        path.extension().is_some_and(|ext| ext == "tmp" || ext == "prm") && is_blue(path)
+    }
+
+    fn open(&self, path: &Path) -> Result<SigMFParser> {
+        open_blue(path, raw_iq_params(path)?)
+    }
+}
@@ -1,7 +1,17 @@
 pub mod sigmf;
-// this is where we'd add other file types
+pub mod raw;
+pub mod digitalrf;
+pub mod midas;
+pub mod vrt;
+pub mod wav;
+pub mod format;
+mod reader;
+mod error;
 
 pub use sigmf::{SigMFParser, SigMFDataset};
+pub use reader::{OpenSamples, SampleReader};
+pub use error::ParserError;
+pub use format::{find_format, recording_formats, register_format, RecordingFormat};
 
 use anyhow::Result;
 use polars::prelude::*;
@@ -11,16 +21,20 @@ pub struct FileParser;
 
 impl FileParser {
     pub fn parse_sigmf_summary<P: AsRef<Path>>(path: P) -> Result<DataFrame> {
-        let parser = SigMFParser::from_meta_file(path)?;
-        parser.to_summary_row()
+        SigMFDataset::from_files(&[path])
     }
 
     pub fn parse_sigmf_directory<P: AsRef<Path>>(dir_path: P) -> Result<DataFrame> {
         SigMFDataset::from_directory(dir_path)
     }
 
+    /// A SigMF meta file, archive or collection, or a file in any registered
+    /// format (see [`format`])
     pub fn parse_file<P: AsRef<Path>>(path: P) -> Result<LazyFrame> {
         let path = path.as_ref();
+        if let Some(format) = find_format(path) {
+            return Ok(SigMFDataset::from_recordings(format.recordings(path)?)?.lazy());
+        }
         let extension = path.extension()
             .and_then(|ext| ext.to_str())
             .unwrap_or("");
@@ -29,10 +43,22 @@ impl FileParser {
                 let summary_df = Self::parse_sigmf_summary(path)?;
                 Ok(summary_df.lazy())
             }
+            "sigmf" => Ok(SigMFDataset::from_archive(path)?.lazy()),
+            "sigmf-collection" => Ok(SigMFDataset::from_collection(path)?.lazy()),
             _ => anyhow::bail!("Unsupported file extension: {}", extension),
         }
     }
 
+    /// Reader over the samples of the recording at `path`: a meta file, an
+    /// archive member or a recording in a registered format
+    pub fn open_reader<P: AsRef<Path>>(path: P) -> Result<Box<dyn SampleReader + Send>> {
+        let path = path.as_ref();
+        match find_format(path) {
+            Some(format) => format.open(path)?.open_reader(),
+            None => SigMFParser::from_meta_file(path)?.open_reader(),
+        }
+    }
+
     pub fn parse_directory<P : AsRef<Path>>(dir_path: P) -> Result<LazyFrame> {
         let df = Self::parse_sigmf_directory(dir_path)?;
         Ok(df.lazy())
@@ -0,0 +1,178 @@
+//! IQ recordings without SigMF metadata: raw interleaved samples, e.g.
+//! GNU Radio's `.cfile` (complex float32), baseband WAV files (see
+//! [`super::wav`]), VITA-49 packet captures (see [`super::vrt`]) and
+//! X-Midas BLUE files (see [`super::midas`]).
+//!
+//! What the samples can't say (sample rate, center frequency, start time)
+//! comes from a sidecar TOML next to the file, `<file>.toml`:
+//!
+//! ```toml
+//! sample_rate = 2.4e6
+//! center_freq = 915e6
+//! datetime = "2024-05-01T12:00:00Z"
+//! ```
+//!
+//! A WAV or BLUE header, or VITA-49 context packets, take precedence. Anything
+//! else the sidecar leaves out falls back to the defaults set for the
+//! process (`--sample-rate`, `--center-freq`), like the data roots. The file
+//! is then presented as a SigMF recording with synthesized metadata, so it
+//! gets the same summary rows and can sit in one dataset with SigMF files.
+
+use super::format::RecordingFormat;
+use super::sigmf::{CaptureInfo, DataLocation, GlobalInfo, SigMFDataType, SigMFMetadata, SigMFParser};
+use anyhow::Result;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+/// Extensions read as raw IQ
+pub const RAW_IQ_EXTENSIONS: [&str; 5] = ["cfile", "wav", "vrt", "tmp", "prm"];
+
+/// Recording parameters of a raw IQ file
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RawIqParams {
+    pub sample_rate: Option<f64>,
+    pub center_freq: Option<f64>,
+    /// SigMF datatype of the samples, `cf32_le` if not given
+    pub datatype: Option<String>,
+    /// When the recording started (ISO 8601)
+    pub datetime: Option<String>,
+    pub hardware: Option<String>,
+    pub author: Option<String>,
+    pub description: Option<String>,
+}
+
+impl RawIqParams {
+    /// These parameters, with any left out taken from `defaults`
+    pub fn or(self, defaults: &RawIqParams) -> RawIqParams {
+        RawIqParams {
+            sample_rate: self.sample_rate.or(defaults.sample_rate),
+            center_freq: self.center_freq.or(defaults.center_freq),
+            datatype: self.datatype.or_else(|| defaults.datatype.clone()),
+            datetime: self.datetime.or_else(|| defaults.datetime.clone()),
+            hardware: self.hardware.or_else(|| defaults.hardware.clone()),
+            author: self.author.or_else(|| defaults.author.clone()),
+            description: self.description.or_else(|| defaults.description.clone()),
+        }
+    }
+
+    /// The sidecar of `path`, if there is one
+    pub fn read_sidecar(path: &Path) -> Result<Option<Self>> {
+        let sidecar = sidecar_path(path);
+        if !sidecar.is_file() {
+            return Ok(None);
+        }
+        let contents = std::fs::read_to_string(&sidecar)?;
+        let params = toml::from_str(&contents).map_err(|e| anyhow::anyhow!("Invalid sidecar {:?}: {}", sidecar, e))?;
+        Ok(Some(params))
+    }
+}
+
+static RAW_IQ_DEFAULTS: RwLock<RawIqParams> = RwLock::new(RawIqParams {
+    sample_rate: None,
+    center_freq: None,
+    datatype: None,
+    datetime: None,
+    hardware: None,
+    author: None,
+    description: None,
+});
+
+/// Use `defaults` for raw IQ files without a sidecar (or fields it leaves
+/// out) for the rest of the process
+pub fn set_raw_iq_defaults(defaults: RawIqParams) {
+    *RAW_IQ_DEFAULTS.write().unwrap_or_else(|e| e.into_inner()) = defaults;
+}
+
+pub fn raw_iq_defaults() -> RawIqParams {
+    RAW_IQ_DEFAULTS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+pub fn is_raw_iq(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| RAW_IQ_EXTENSIONS.contains(&ext))
+}
+
+/// The sidecar of `path`, with any fields it leaves out (or all of them,
+/// without a sidecar) from the defaults
+pub fn raw_iq_params(path: &Path) -> Result<RawIqParams> {
+    Ok(RawIqParams::read_sidecar(path)?.unwrap_or_default().or(&raw_iq_defaults()))
+}
+
+/// `capture.cfile` → `capture.cfile.toml`
+pub fn sidecar_path(path: &Path) -> PathBuf {
+    let mut sidecar = path.as_os_str().to_os_string();
+    sidecar.push(".toml");
+    PathBuf::from(sidecar)
+}
+
+/// Open a file of raw interleaved samples as a single-capture SigMF
+/// recording without annotations. The file is both the "meta file" and the
+/// data file.
+pub fn open_raw_iq(path: &Path) -> Result<SigMFParser> {
+    let params = raw_iq_params(path)?;
+    let sample_rate = params
+        .sample_rate
+        .filter(|rate| *rate > 0.0)
+        .ok_or_else(|| anyhow::anyhow!("No sample rate for {:?}: add sample_rate to {:?} or pass --sample-rate", path, sidecar_path(path)))?;
+    let data_type = SigMFDataType::from_string(params.datatype.as_deref().unwrap_or("cf32_le"))?;
+    Ok(synthesize(path, params, sample_rate, data_type, DataLocation::whole_file(path)?))
+}
+
+/// Raw interleaved samples, `.cfile`
+pub struct RawIqFormat;
+
+impl RecordingFormat for RawIqFormat {
+    fn name(&self) -> &str {
+        "raw IQ"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["cfile"]
+    }
+
+    fn open(&self, path: &Path) -> Result<SigMFParser> {
+        open_raw_iq(path)
+    }
+}
+
+/// A recording of `path` whose samples are at `data_location`, with
+/// metadata made up from `params`
+pub(crate) fn synthesize(path: &Path, params: RawIqParams, sample_rate: f64, data_type: SigMFDataType, data_location: DataLocation) -> SigMFParser {
+    let metadata = SigMFMetadata {
+        global: GlobalInfo {
+            datatype: data_type.name().to_string(),
+            sample_rate,
+            version: "1.2.0".to_string(),
+            description: params.description,
+            author: params.author,
+            license: None,
+            hardware: params.hardware,
+            geolocation: None,
+            sha512: None,
+            dataset: None,
+        },
+        captures: vec![CaptureInfo {
+            sample_start: Some(0),
+            frequency: params.center_freq,
+            timestamp: params.datetime,
+            agc: None,
+            gain: None,
+            sequence_num: None,
+            extra_fields: HashMap::new(),
+        }],
+        annotations: None,
+    };
+    SigMFParser {
+        metadata,
+        meta_path: path.to_path_buf(),
+        data_type,
+        data_location,
+        data_file_path: path.to_path_buf(),
+        open_samples: None,
+        parse_warnings: Vec::new(),
+    }
+}
@@ -0,0 +1,36 @@
+use anyhow::Result;
+use num_complex::Complex;
+
+/// Random access to the complex samples of a recording, independent of the
+/// on-disk format. Visualization and DSP code should only go through this.
+pub trait SampleReader {
+    /// Total number of complex samples available
+    fn num_samples(&self) -> u64;
+
+    /// Read up to `count` samples starting at `start`. Reads past the end of
+    /// the recording are truncated rather than treated as an error.
+    fn read_samples(&mut self, start: u64, count: usize) -> Result<Vec<Complex<f32>>>;
+}
+
+impl<R: SampleReader + ?Sized> SampleReader for Box<R> {
+    fn num_samples(&self) -> u64 {
+        (**self).num_samples()
+    }
+
+    fn read_samples(&mut self, start: u64, count: usize) -> Result<Vec<Complex<f32>>> {
+        (**self).read_samples(start, count)
+    }
+}
+
+impl<R: SampleReader + ?Sized> SampleReader for &mut R {
+    fn num_samples(&self) -> u64 {
+        (**self).num_samples()
+    }
+
+    fn read_samples(&mut self, start: u64, count: usize) -> Result<Vec<Complex<f32>>> {
+        (**self).read_samples(start, count)
+    }
+}
+
+/// Opens a reader over samples that aren't one run of bytes in a file
+pub type OpenSamples = std::sync::Arc<dyn Fn() -> Result<Box<dyn SampleReader + Send>> + Send + Sync>;
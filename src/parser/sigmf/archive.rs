@@ -0,0 +1,134 @@
+//! SigMF archives: uncompressed tarballs (`name.sigmf`) holding a `name/`
+//! directory of meta and data files. Recordings are read in place; the data
+//! of an uncompressed tar member is a contiguous byte range of the archive.
+//!
+//! Members are addressed with virtual paths that continue through the
+//! archive, e.g. `/data/run1.sigmf/run1/cap0.sigmf-meta`.
+
+use super::compression::decompressed_path;
+use super::{Compression, DataLocation, SigMFParser};
+use anyhow::Result;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+/// A recording stored inside a `.sigmf` archive
+#[derive(Debug, Clone)]
+pub struct ArchiveRecording {
+    /// Virtual path of the meta file
+    pub meta_path: PathBuf,
+    pub meta_json: String,
+    pub data: Option<DataLocation>,
+}
+
+pub fn is_archive<P: AsRef<Path>>(path: P) -> bool {
+    let path = path.as_ref();
+    path.extension().and_then(|s| s.to_str()) == Some("sigmf") && path.is_file()
+}
+
+/// Split a virtual member path into the archive on disk and the path inside it
+pub fn split_member_path<P: AsRef<Path>>(path: P) -> Option<(PathBuf, PathBuf)> {
+    let path = path.as_ref();
+    path.ancestors()
+        .skip(1)
+        .find(|ancestor| is_archive(ancestor))
+        .map(|archive| (archive.to_path_buf(), path.strip_prefix(archive).unwrap().to_path_buf()))
+}
+
+/// Raw text of a meta file on disk or inside an archive
+pub fn read_meta_text<P: AsRef<Path>>(meta_path: P) -> Result<String> {
+    let meta_path = meta_path.as_ref();
+    if !meta_path.exists() {
+        if let Some((archive, _)) = split_member_path(meta_path) {
+            return list_recordings(&archive)?
+                .into_iter()
+                .find(|r| r.meta_path == meta_path)
+                .map(|r| r.meta_json)
+                .ok_or_else(|| anyhow::anyhow!("{:?} is not in archive {:?}", meta_path, archive));
+        }
+    }
+    Ok(std::fs::read_to_string(meta_path)?)
+}
+
+/// Every recording in an archive, in archive order
+pub fn list_recordings<P: AsRef<Path>>(archive_path: P) -> Result<Vec<ArchiveRecording>> {
+    let archive_path = archive_path.as_ref();
+    let mut archive = tar::Archive::new(File::open(archive_path)?);
+
+    let mut metas = Vec::new();
+    let mut data = HashMap::new();
+    for entry in archive.entries_with_seek()? {
+        let mut entry = entry?;
+        let inner = entry.path()?.into_owned();
+        match inner.extension().and_then(|s| s.to_str()) {
+            Some("sigmf-meta") => {
+                let mut json = String::new();
+                entry.read_to_string(&mut json)?;
+                metas.push((inner, json));
+            }
+            Some("sigmf-data") => {
+                let location = DataLocation {
+                    file: archive_path.to_path_buf(),
+                    offset: entry.raw_file_position(),
+                    size: entry.size(),
+                    pieces: None,
+                };
+                data.insert(inner.with_extension(""), location);
+            }
+            _ => {}
+        }
+    }
+
+    Ok(metas
+        .into_iter()
+        .map(|(inner, meta_json)| ArchiveRecording {
+            meta_path: archive_path.join(&inner),
+            meta_json,
+            data: data.get(&inner.with_extension("")).cloned(),
+        })
+        .collect())
+}
+
+/// Pack recordings into a new archive. Members go under a directory named
+/// after the archive, as the SigMF spec requires. Returns the number of
+/// recordings written.
+pub fn pack_archive<P: AsRef<Path>>(output: P, meta_paths: &[PathBuf]) -> Result<usize> {
+    let output = output.as_ref();
+    let name = output
+        .file_stem()
+        .and_then(|s| s.to_str())
+        .ok_or_else(|| anyhow::anyhow!("Invalid archive name: {:?}", output))?;
+    if output.extension().and_then(|s| s.to_str()) != Some("sigmf") {
+        anyhow::bail!("Archive name must end in .sigmf");
+    }
+
+    let mut seen = HashSet::new();
+    for meta_path in meta_paths {
+        let file_name = meta_path.file_name().unwrap_or_default();
+        if !seen.insert(file_name.to_os_string()) {
+            anyhow::bail!("Two recordings share the file name {:?}", file_name);
+        }
+    }
+    // Data files may live elsewhere (see `resolve_data_path`); inside the
+    // archive they always sit next to their meta file, uncompressed
+    let data_paths = meta_paths
+        .iter()
+        .map(|meta_path| {
+            let data_path = SigMFParser::from_meta_file(meta_path)?.data_file_path;
+            match Compression::from_path(&data_path) {
+                Some(compression) => decompressed_path(&data_path, compression),
+                None => Ok(data_path),
+            }
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut builder = tar::Builder::new(File::create(output)?);
+    for (meta_path, data_path) in meta_paths.iter().zip(&data_paths) {
+        let meta_member = Path::new(name).join(meta_path.file_name().unwrap());
+        builder.append_path_with_name(meta_path, &meta_member)?;
+        builder.append_path_with_name(data_path, meta_member.with_extension("sigmf-data"))?;
+    }
+    builder.into_inner()?.sync_all()?;
+    Ok(meta_paths.len())
+}
@@ -0,0 +1,346 @@
+use super::compression::existing_data_file;
+use super::{data_roots, parse_metadata, parse_mode, resolve_data_path, DatasetOptions, ParseMode, Segment};
+use crate::data_ops::{band_map, merge_rows, BandMap, SUMMARY_SCHEMA_VERSION};
+use crate::parser::raw::{is_raw_iq, raw_iq_defaults, sidecar_path, RawIqParams};
+use anyhow::Result;
+use polars::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+use tracing::warn;
+
+/// Bookkeeping columns stored next to the summary rows in a cache file
+const SOURCE: &str = "_cache_source";
+const META_MTIME: &str = "_cache_meta_mtime_ns";
+const DATA_MTIME: &str = "_cache_data_mtime_ns";
+const DATA_LEN: &str = "_cache_data_len";
+const META_PATH: &str = "_cache_meta_path";
+const SEQUENCE_NUM: &str = "_cache_sequence_num";
+const NUM_SAMPLES: &str = "_cache_num_samples";
+const SAMPLE_RATE: &str = "_cache_sample_rate";
+const CENTER_FREQ: &str = "_cache_center_freq";
+const DATATYPE: &str = "_cache_datatype";
+const HARDWARE: &str = "_cache_hardware";
+const CACHE_COLUMNS: [&str; 11] = [
+    SOURCE, META_MTIME, DATA_MTIME, DATA_LEN, META_PATH, SEQUENCE_NUM, NUM_SAMPLES, SAMPLE_RATE, CENTER_FREQ, DATATYPE, HARDWARE,
+];
+
+/// When a scanned file was last changed. A recording's rows also depend on
+/// its data file (duration, checksum, noise floor), so for a meta file the
+/// data file it resolves to counts too, wherever `core:dataset` or the data
+/// roots put it. For a raw IQ file, its sidecar counts instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct FileStamp {
+    meta_mtime_ns: i64,
+    data_mtime_ns: i64,
+    data_len: u64,
+}
+
+impl FileStamp {
+    fn of(source: &Path) -> Option<Self> {
+        let mtime_ns = |metadata: &std::fs::Metadata| {
+            metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok().map(|d| d.as_nanos() as i64)
+        };
+        let meta = std::fs::metadata(source).ok()?;
+        let companion = if is_raw_iq(source) {
+            existing_data_file(&sidecar_path(source))
+        } else if source.extension().is_some_and(|ext| ext == "sigmf-meta") {
+            resolved_data_file(source)
+        } else {
+            None
+        };
+        let data = companion.and_then(|path| std::fs::metadata(path).ok());
+        Some(FileStamp {
+            meta_mtime_ns: mtime_ns(&meta)?,
+            data_mtime_ns: data.as_ref().and_then(mtime_ns).unwrap_or(0),
+            data_len: data.map(|data| data.len()).unwrap_or(0),
+        })
+    }
+}
+
+/// The data file `meta_path` resolves to, as [`SigMFParser::from_meta_file`]
+/// finds it
+///
+/// [`SigMFParser::from_meta_file`]: super::SigMFParser::from_meta_file
+fn resolved_data_file(meta_path: &Path) -> Option<PathBuf> {
+    let (metadata, _) = parse_metadata(&std::fs::read_to_string(meta_path).ok()?).ok()?;
+    resolve_data_path(meta_path, metadata.global.dataset.as_deref()).ok()
+}
+
+/// Where one recording's summary rows are: in [`DatasetCache::rows`] when
+/// returned by [`DatasetCache::get`], in the scanned dataset when passed to
+/// [`DatasetCache::insert`]
+#[derive(Debug, Clone)]
+pub struct CachedRecording {
+    pub meta_path: PathBuf,
+    pub offset: usize,
+    pub len: usize,
+    pub segment: Option<Segment>,
+}
+
+/// Where a cached source's rows sit in the loaded cache table
+struct CacheEntry {
+    stamp: FileStamp,
+    offset: usize,
+    len: usize,
+}
+
+/// Summary rows from earlier scans of a directory, keyed by the scanned file
+/// (meta file or archive) and its modification time, so a rescan only parses
+/// what is new or changed. Stored as Parquet under the user cache directory,
+/// one file per directory and set of [`DatasetOptions`]; a cache from another
+/// summary schema version is ignored.
+pub struct DatasetCache {
+    path: PathBuf,
+    /// As stored, with the bookkeeping columns
+    table: Option<DataFrame>,
+    /// `table` without the bookkeeping columns
+    rows: Option<DataFrame>,
+    entries: HashMap<PathBuf, CacheEntry>,
+    /// Sources looked up this scan, and still current
+    reused: HashSet<PathBuf>,
+    /// Sources parsed this scan, with their recordings' rows in the dataset
+    inserted: Vec<(PathBuf, FileStamp, Vec<CachedRecording>)>,
+}
+
+impl DatasetCache {
+    /// `~/.cache/sig_viewer/datasets/<key>.parquet` (or the platform
+    /// equivalent), where the key covers the directory, the options that
+    /// change a row, the data roots, the band map and the summary schema
+    /// version
+    pub fn default_path(dir: &Path, options: &DatasetOptions) -> PathBuf {
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        let mut hasher = DefaultHasher::new();
+        dir.hash(&mut hasher);
+        (
+            options.granularity.as_str(),
+            options.verify_checksums,
+            options.estimate_noise_floor,
+            options.compute_metrics,
+            options.annotation_extra_columns,
+            SUMMARY_SCHEMA_VERSION,
+            parse_mode() == ParseMode::Lenient,
+        )
+            .hash(&mut hasher);
+        // Raw IQ defaults change the rows of raw files without a full sidecar
+        let raw_defaults = raw_iq_defaults();
+        if raw_defaults != RawIqParams::default() {
+            format!("{:?}", raw_defaults).hash(&mut hasher);
+        }
+        // As does a band map other than the built-in one
+        let bands = band_map();
+        if bands != BandMap::builtin() {
+            format!("{:?}", bands).hash(&mut hasher);
+        }
+        // Data roots change which data file a recording gets
+        let roots = data_roots();
+        if !roots.is_empty() {
+            format!("{:?}", roots).hash(&mut hasher);
+        }
+        dirs::cache_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("sig_viewer")
+            .join("datasets")
+            .join(format!("{:016x}.parquet", hasher.finish()))
+    }
+
+    /// Open the cache at `path`. A missing or unreadable cache is an empty one.
+    pub fn open<P: AsRef<Path>>(path: P) -> Self {
+        let path = path.as_ref().to_path_buf();
+        let mut cache = DatasetCache { path, table: None, rows: None, entries: HashMap::new(), reused: HashSet::new(), inserted: Vec::new() };
+        match cache.read() {
+            Ok(()) => {}
+            Err(e) if cache.path.exists() => warn!("Ignoring dataset cache {:?}: {}", cache.path, e),
+            Err(_) => {}
+        }
+        cache
+    }
+
+    fn read(&mut self) -> Result<()> {
+        let table = ParquetReader::new(File::open(&self.path)?).finish()?;
+        let sources = table.column(SOURCE)?.str()?;
+        let meta_mtimes = table.column(META_MTIME)?.i64()?;
+        let data_mtimes = table.column(DATA_MTIME)?.i64()?;
+        let data_lens = table.column(DATA_LEN)?.u64()?;
+        // Check the segment columns up front, so `get` can't fail on them
+        for name in [META_PATH, DATATYPE, HARDWARE] {
+            table.column(name)?.str()?;
+        }
+        table.column(SEQUENCE_NUM)?.u64()?;
+        table.column(NUM_SAMPLES)?.u64()?;
+        table.column(SAMPLE_RATE)?.f64()?;
+        table.column(CENTER_FREQ)?.f64()?;
+        // Sources are written as contiguous runs of rows
+        let mut offset = 0;
+        while offset < table.height() {
+            let source = sources.get(offset).unwrap_or_default();
+            let len = run_length(sources, offset);
+            let stamp = FileStamp {
+                meta_mtime_ns: meta_mtimes.get(offset).unwrap_or(0),
+                data_mtime_ns: data_mtimes.get(offset).unwrap_or(0),
+                data_len: data_lens.get(offset).unwrap_or(0),
+            };
+            self.entries.insert(PathBuf::from(source), CacheEntry { stamp, offset, len });
+            offset += len;
+        }
+        self.rows = Some(table.drop_many(&CACHE_COLUMNS));
+        self.table = Some(table);
+        Ok(())
+    }
+
+    /// The cached summary rows that recordings from [`get`](Self::get) point into
+    pub fn rows(&self) -> Option<&DataFrame> {
+        self.rows.as_ref()
+    }
+
+    /// The recordings `source` gave when last scanned, if it hasn't changed since
+    pub fn get(&mut self, source: &Path) -> Option<Vec<CachedRecording>> {
+        let entry = self.entries.get(source)?;
+        if FileStamp::of(source) != Some(entry.stamp) {
+            return None;
+        }
+        let table = self.table.as_ref()?;
+        let column = |name: &str| table.column(name).ok();
+        let meta_paths = column(META_PATH)?.str().ok()?;
+        let sequence_nums = column(SEQUENCE_NUM)?.u64().ok()?;
+        let num_samples = column(NUM_SAMPLES)?.u64().ok()?;
+        let sample_rates = column(SAMPLE_RATE)?.f64().ok()?;
+        let center_freqs = column(CENTER_FREQ)?.f64().ok()?;
+        let datatypes = column(DATATYPE)?.str().ok()?;
+        let hardware = column(HARDWARE)?.str().ok()?;
+
+        // Split back into recordings, which are runs of the same meta path
+        let mut recordings = Vec::new();
+        let mut offset = entry.offset;
+        while offset < entry.offset + entry.len {
+            let meta_path = meta_paths.get(offset).unwrap_or_default();
+            let len = run_length(meta_paths, offset).min(entry.offset + entry.len - offset);
+            let segment = sequence_nums.get(offset).map(|sequence_num| Segment {
+                meta_path: PathBuf::from(meta_path),
+                sequence_num,
+                num_samples: num_samples.get(offset).unwrap_or(0),
+                sample_rate: sample_rates.get(offset).unwrap_or(0.0),
+                center_freq: center_freqs.get(offset),
+                datatype: datatypes.get(offset).unwrap_or_default().to_string(),
+                hardware: hardware.get(offset).map(str::to_string),
+            });
+            recordings.push(CachedRecording { meta_path: PathBuf::from(meta_path), offset, len, segment });
+            offset += len;
+        }
+        self.reused.insert(source.to_path_buf());
+        Some(recordings)
+    }
+
+    /// Remember what `source` gave, to be saved with [`save`](Self::save);
+    /// `recordings` point into the dataset passed to it
+    pub fn insert(&mut self, source: &Path, recordings: Vec<CachedRecording>) {
+        if let Some(stamp) = FileStamp::of(source) {
+            self.inserted.push((source.to_path_buf(), stamp, recordings));
+        }
+    }
+
+    /// Number of sources whose rows were reused this scan
+    pub fn reused_count(&self) -> usize {
+        self.reused.len()
+    }
+
+    /// Write the sources looked up this scan, plus those inserted with their
+    /// rows from `dataset`, dropping the rest (files that were deleted or
+    /// changed). After a `partial` scan, which only looked at some files,
+    /// the others are kept too.
+    pub fn save(self, dataset: &DataFrame, partial: bool) -> Result<()> {
+        let mut parts = Vec::new();
+        if let Some(ref table) = self.table {
+            let inserted: HashSet<&PathBuf> = self.inserted.iter().map(|(source, _, _)| source).collect();
+            let mut kept: Vec<(usize, usize)> = self
+                .entries
+                .iter()
+                .filter(|(source, _)| if partial { !inserted.contains(source) } else { self.reused.contains(*source) })
+                .map(|(_, entry)| (entry.offset, entry.len))
+                .collect();
+            kept.sort_unstable();
+            parts.extend(coalesce(kept).into_iter().map(|(offset, len)| table.slice(offset as i64, len)));
+        }
+        if let Some(added) = added_rows(dataset, &self.inserted)? {
+            parts.push(added);
+        }
+        let Some(mut table) = merge_rows(parts)? else {
+            return Ok(());
+        };
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // Replace the old cache in one step, so an interrupted save can't corrupt it
+        let tmp = self.path.with_extension("parquet.tmp");
+        ParquetWriter::new(File::create(&tmp)?).finish(&mut table)?;
+        std::fs::rename(&tmp, &self.path)?;
+        Ok(())
+    }
+}
+
+/// The rows of newly parsed sources, taken from `dataset`, with the
+/// bookkeeping columns
+fn added_rows(dataset: &DataFrame, inserted: &[(PathBuf, FileStamp, Vec<CachedRecording>)]) -> Result<Option<DataFrame>> {
+    let (mut sources, mut meta_mtimes, mut data_mtimes, mut data_lens, mut meta_paths) = (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let (mut sequence_nums, mut num_samples, mut sample_rates, mut center_freqs, mut datatypes, mut hardware) =
+        (Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let mut ranges = Vec::new();
+    for (source, stamp, recordings) in inserted {
+        for recording in recordings {
+            let len = recording.len;
+            let segment = recording.segment.as_ref();
+            sources.extend(std::iter::repeat_n(source.to_string_lossy().to_string(), len));
+            meta_mtimes.extend(std::iter::repeat_n(stamp.meta_mtime_ns, len));
+            data_mtimes.extend(std::iter::repeat_n(stamp.data_mtime_ns, len));
+            data_lens.extend(std::iter::repeat_n(stamp.data_len, len));
+            meta_paths.extend(std::iter::repeat_n(recording.meta_path.to_string_lossy().to_string(), len));
+            sequence_nums.extend(std::iter::repeat_n(segment.map(|s| s.sequence_num), len));
+            num_samples.extend(std::iter::repeat_n(segment.map(|s| s.num_samples), len));
+            sample_rates.extend(std::iter::repeat_n(segment.map(|s| s.sample_rate), len));
+            center_freqs.extend(std::iter::repeat_n(segment.and_then(|s| s.center_freq), len));
+            datatypes.extend(std::iter::repeat_n(segment.map(|s| s.datatype.clone()), len));
+            hardware.extend(std::iter::repeat_n(segment.and_then(|s| s.hardware.clone()), len));
+            ranges.push((recording.offset, len));
+        }
+    }
+    let parts: Vec<DataFrame> = coalesce(ranges).into_iter().map(|(offset, len)| dataset.slice(offset as i64, len)).collect();
+    let Some(rows) = merge_rows(parts)? else {
+        return Ok(None);
+    };
+    let mut columns = vec![
+        Series::new(SOURCE.into(), sources),
+        Series::new(META_MTIME.into(), meta_mtimes),
+        Series::new(DATA_MTIME.into(), data_mtimes),
+        Series::new(DATA_LEN.into(), data_lens),
+        Series::new(META_PATH.into(), meta_paths),
+        Series::new(SEQUENCE_NUM.into(), sequence_nums),
+        Series::new(NUM_SAMPLES.into(), num_samples),
+        Series::new(SAMPLE_RATE.into(), sample_rates),
+        Series::new(CENTER_FREQ.into(), center_freqs),
+        Series::new(DATATYPE.into(), datatypes),
+        Series::new(HARDWARE.into(), hardware),
+    ];
+    columns.extend(rows.get_columns().iter().cloned());
+    Ok(Some(DataFrame::new(columns)?))
+}
+
+/// Number of rows from `offset` with the same value
+fn run_length(values: &StringChunked, offset: usize) -> usize {
+    let value = values.get(offset);
+    (offset + 1..values.len()).take_while(|&i| values.get(i) == value).count() + 1
+}
+
+/// Merge `(offset, len)` ranges that follow on from each other, keeping order
+pub(super) fn coalesce(ranges: Vec<(usize, usize)>) -> Vec<(usize, usize)> {
+    let mut merged: Vec<(usize, usize)> = Vec::with_capacity(ranges.len());
+    for (offset, len) in ranges {
+        match merged.last_mut() {
+            Some((start, run)) if *start + *run == offset => *run += len,
+            _ => merged.push((offset, len)),
+        }
+    }
+    merged
+}
@@ -0,0 +1,154 @@
+use super::{read_meta_text, SigMFDataType, SigMFParser};
+use crate::dsp::{ChannelParams, ChannelizedReader, Resampler};
+use crate::parser::SampleReader;
+use anyhow::Result;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha512};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const CHUNK_SAMPLES: usize = 1 << 18;
+
+#[derive(Debug, Clone)]
+pub struct ChannelizeReport {
+    pub meta_path: PathBuf,
+    pub data_path: PathBuf,
+    pub sample_rate: f64,
+    /// Absolute center of the channel, when the recording has a frequency
+    pub center_freq: Option<f64>,
+    pub num_samples: u64,
+}
+
+/// Write one channel of a recording as a new cf32_le SigMF pair into
+/// `out_dir`: the band `params` picks out, mixed to baseband and decimated,
+/// then resampled to `output_rate` if one is given. Sample indices of the
+/// captures and annotations are scaled to the new rate, capture frequencies
+/// moved to the channel's center, and annotations entirely outside the
+/// channel dropped.
+pub fn channelize_recording<P: AsRef<Path>, Q: AsRef<Path>>(
+    meta_path: P,
+    params: &ChannelParams,
+    output_rate: Option<f64>,
+    out_dir: Q,
+) -> Result<ChannelizeReport> {
+    let meta_path = meta_path.as_ref();
+    let parser = SigMFParser::from_meta_file(meta_path)?;
+    let sample_rate = parser.sample_rate();
+    if sample_rate <= 0.0 {
+        anyhow::bail!("{:?} has no sample rate to channelize with", meta_path);
+    }
+    if output_rate.is_some_and(|rate| rate < params.bandwidth_hz) {
+        anyhow::bail!("The output rate must be at least the {:.0} Hz channel bandwidth", params.bandwidth_hz);
+    }
+    let mut channel = ChannelizedReader::new(parser.open_reader()?, sample_rate, params)?;
+    let channel_rate = channel.output_rate();
+    let out_rate = output_rate.unwrap_or(channel_rate);
+    let center_freq = parser.center_frequency().map(|freq| freq + params.offset_hz);
+
+    let stem = meta_path.file_stem().unwrap().to_string_lossy();
+    let out_dir = out_dir.as_ref();
+    std::fs::create_dir_all(out_dir)?;
+    let out_meta = out_dir.join(format!("{}_ch{:.0}.sigmf-meta", stem, center_freq.unwrap_or(params.offset_hz)));
+    let out_data = out_meta.with_extension("sigmf-data");
+
+    let mut resampler = (out_rate != channel_rate).then(|| Resampler::new(channel_rate, out_rate));
+    let mut writer = BufWriter::new(std::fs::File::create(&out_data)?);
+    let mut hasher = Sha512::new();
+    let mut resampled = Vec::new();
+    let mut num_samples = 0;
+    let mut position = 0;
+    while position < channel.num_samples() {
+        let samples = channel.read_samples(position, CHUNK_SAMPLES)?;
+        if samples.is_empty() {
+            break;
+        }
+        position += samples.len() as u64;
+        let samples = match resampler {
+            Some(ref mut resampler) => {
+                resampled.clear();
+                resampler.process(&samples, &mut resampled);
+                &resampled
+            }
+            None => &samples,
+        };
+        let (bytes, _) = SigMFDataType::Cf32Le.encode_samples(samples);
+        writer.write_all(&bytes)?;
+        hasher.update(&bytes);
+        num_samples += samples.len() as u64;
+    }
+    writer.flush()?;
+
+    let mut metadata: Value = serde_json::from_str(&read_meta_text(meta_path)?)?;
+    let global = metadata
+        .get_mut("global")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| anyhow::anyhow!("Missing global object in {:?}", meta_path))?;
+    global.insert("core:sample_rate".to_string(), json!(out_rate));
+    global.insert("core:datatype".to_string(), Value::String(SigMFDataType::Cf32Le.name().to_string()));
+    // The new data file sits next to the meta file, whatever the source did
+    global.remove("core:dataset");
+    global.remove("core:metadata_only");
+    if global.contains_key("core:sha512") {
+        let digest: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+        global.insert("core:sha512".to_string(), Value::String(digest));
+    }
+    let scale = out_rate / sample_rate;
+    channelize_captures(&mut metadata, scale, params.offset_hz);
+    if let Some(center) = center_freq {
+        channelize_annotations(&mut metadata, scale, center - params.bandwidth_hz / 2.0, center + params.bandwidth_hz / 2.0);
+    }
+    std::fs::write(&out_meta, serde_json::to_string_pretty(&metadata)? + "\n")?;
+
+    Ok(ChannelizeReport {
+        meta_path: out_meta,
+        data_path: out_data,
+        sample_rate: out_rate,
+        center_freq,
+        num_samples,
+    })
+}
+
+fn scale_samples(value: &mut serde_json::Map<String, Value>, key: &str, scale: f64) {
+    if let Some(samples) = value.get(key).and_then(Value::as_u64) {
+        value.insert(key.to_string(), json!((samples as f64 * scale).round() as u64));
+    }
+}
+
+fn channelize_captures(metadata: &mut Value, scale: f64, offset_hz: f64) {
+    let Some(captures) = metadata.get_mut("captures").and_then(Value::as_array_mut) else {
+        return;
+    };
+    for capture in captures.iter_mut().filter_map(Value::as_object_mut) {
+        scale_samples(capture, "core:sample_start", scale);
+        if let Some(freq) = capture.get("core:frequency").and_then(Value::as_f64) {
+            capture.insert("core:frequency".to_string(), json!(freq + offset_hz));
+        }
+    }
+}
+
+// Annotations without frequency edges could be anywhere in the band, so
+// they're kept
+fn channelize_annotations(metadata: &mut Value, scale: f64, lower: f64, upper: f64) {
+    let Some(annotations) = metadata.get_mut("annotations").and_then(Value::as_array_mut) else {
+        return;
+    };
+    annotations.retain_mut(|annotation| {
+        let Some(annotation) = annotation.as_object_mut() else {
+            return true;
+        };
+        let low = annotation.get("core:freq_lower_edge").and_then(Value::as_f64);
+        let high = annotation.get("core:freq_upper_edge").and_then(Value::as_f64);
+        if low.is_some_and(|low| low >= upper) || high.is_some_and(|high| high <= lower) {
+            return false;
+        }
+        if let Some(low) = low {
+            annotation.insert("core:freq_lower_edge".to_string(), json!(low.max(lower)));
+        }
+        if let Some(high) = high {
+            annotation.insert("core:freq_upper_edge".to_string(), json!(high.min(upper)));
+        }
+        scale_samples(annotation, "core:sample_start", scale);
+        scale_samples(annotation, "core:sample_count", scale);
+        true
+    });
+}
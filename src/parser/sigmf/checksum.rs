@@ -0,0 +1,100 @@
+use super::{update_meta, DataLocation, Journal, SigMFParser};
+use anyhow::Result;
+use serde_json::Value;
+use sha2::{Digest, Sha512};
+use std::io::Read;
+use std::path::Path;
+
+/// Result of comparing a data file against its `core:sha512`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChecksumStatus {
+    Verified,
+    Mismatch,
+    /// The metadata doesn't declare a checksum
+    Missing,
+    /// Verification wasn't requested
+    NotChecked,
+}
+
+impl ChecksumStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChecksumStatus::Verified => "verified",
+            ChecksumStatus::Mismatch => "mismatch",
+            ChecksumStatus::Missing => "missing",
+            ChecksumStatus::NotChecked => "not_checked",
+        }
+    }
+}
+
+/// Lowercase hex SHA-512 of a file
+pub fn sha512_file<P: AsRef<Path>>(path: P) -> Result<String> {
+    sha512_reader(std::fs::File::open(path)?)
+}
+
+/// Lowercase hex SHA-512 of a stream, read in chunks so large recordings
+/// aren't loaded into memory
+pub fn sha512_reader<R: Read>(file: R) -> Result<String> {
+    sha512_reader_with_progress(file, |_| Ok(()))
+}
+
+/// Like [`sha512_reader`], calling `on_chunk` with the number of bytes hashed
+/// so far after each chunk. An error from `on_chunk` stops hashing.
+pub fn sha512_reader_with_progress<R: Read>(mut file: R, mut on_chunk: impl FnMut(u64) -> Result<()>) -> Result<String> {
+    let mut hasher = Sha512::new();
+    let mut buffer = vec![0u8; 1 << 20];
+    let mut total = 0u64;
+    loop {
+        let n = file.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        hasher.update(&buffer[..n]);
+        total += n as u64;
+        on_chunk(total)?;
+    }
+    Ok(hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+/// Compare recording data against an expected hex digest (case-insensitive)
+pub fn verify_sha512(data: &DataLocation, expected: Option<&str>) -> Result<ChecksumStatus> {
+    verify_sha512_with_progress(data, expected, |_| Ok(()))
+}
+
+/// Like [`verify_sha512`], calling `on_progress` with the fraction of the
+/// data hashed so far. An error from `on_progress` stops hashing.
+pub fn verify_sha512_with_progress(
+    data: &DataLocation,
+    expected: Option<&str>,
+    mut on_progress: impl FnMut(f32) -> Result<()>,
+) -> Result<ChecksumStatus> {
+    let Some(expected) = expected else {
+        return Ok(ChecksumStatus::Missing);
+    };
+    let size = data.size.max(1) as f64;
+    let actual = sha512_reader_with_progress(data.open()?, |done| on_progress((done as f64 / size) as f32))?;
+    if actual.eq_ignore_ascii_case(expected.trim()) {
+        Ok(ChecksumStatus::Verified)
+    } else {
+        Ok(ChecksumStatus::Mismatch)
+    }
+}
+
+/// Hash the data file of `meta_path` and store it as `core:sha512`.
+/// The rest of the metadata is kept as-is (field order included), and the
+/// file is locked so a recorder appending annotations isn't clobbered.
+pub fn write_sha512<P: AsRef<Path>>(meta_path: P, journal: &Journal) -> Result<String> {
+    let meta_path = meta_path.as_ref();
+    let parser = SigMFParser::from_meta_file(meta_path)?;
+    let digest = sha512_reader(parser.data_location.open()?)?;
+
+    update_meta(meta_path, journal, |metadata| {
+        let global = metadata
+            .get_mut("global")
+            .and_then(Value::as_object_mut)
+            .ok_or_else(|| anyhow::anyhow!("Missing global object in {:?}", meta_path))?;
+        global.insert("core:sha512".to_string(), Value::String(digest.clone()));
+        Ok(())
+    })?;
+    Ok(digest)
+}
@@ -0,0 +1,89 @@
+use anyhow::Result;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+
+/// A `.sigmf-collection` file: collection-level metadata plus the list of
+/// member recordings (`core:streams`)
+#[derive(Debug, Clone)]
+pub struct SigMFCollection {
+    pub path: PathBuf,
+    /// Everything in the `collection` object except `core:streams`
+    pub fields: Map<String, Value>,
+    pub streams: Vec<CollectionStream>,
+}
+
+#[derive(Debug, Clone)]
+pub struct CollectionStream {
+    /// Recording base name, relative to the collection file
+    pub name: String,
+    /// SHA-512 of the member's meta file, if recorded
+    pub hash: Option<String>,
+}
+
+impl SigMFCollection {
+    pub fn from_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path)?;
+        let mut document: Value = serde_json::from_str(&content)?;
+        let Some(Value::Object(mut fields)) = document.get_mut("collection").map(Value::take) else {
+            anyhow::bail!("Missing collection object in {:?}", path);
+        };
+
+        let streams = match fields.remove("core:streams") {
+            Some(Value::Array(streams)) => streams
+                .iter()
+                .map(|stream| {
+                    let name = stream
+                        .get("name")
+                        .and_then(Value::as_str)
+                        .ok_or_else(|| anyhow::anyhow!("Stream without a name in {:?}", path))?;
+                    Ok(CollectionStream {
+                        name: name.to_string(),
+                        hash: stream.get("hash").and_then(Value::as_str).map(str::to_string),
+                    })
+                })
+                .collect::<Result<Vec<_>>>()?,
+            Some(_) => anyhow::bail!("core:streams must be an array in {:?}", path),
+            None => Vec::new(),
+        };
+
+        Ok(SigMFCollection {
+            path: path.to_path_buf(),
+            fields,
+            streams,
+        })
+    }
+
+    /// Collection name, taken from the file name
+    pub fn name(&self) -> String {
+        self.path
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    /// Meta file paths of the member recordings
+    pub fn member_meta_paths(&self) -> Vec<PathBuf> {
+        let dir = self.path.parent().unwrap_or(Path::new(""));
+        self.streams
+            .iter()
+            .map(|stream| dir.join(format!("{}.sigmf-meta", stream.name)))
+            .collect()
+    }
+
+    /// Collection-level metadata flattened to dataset columns, e.g.
+    /// `core:description` becomes `collection_description` and
+    /// `ds:site` becomes `collection_ds_site`
+    pub fn columns(&self) -> Vec<(String, String)> {
+        let mut columns = vec![("collection".to_string(), self.name())];
+        for (key, value) in &self.fields {
+            let name = key.strip_prefix("core:").unwrap_or(key).replace(':', "_");
+            let value = match value {
+                Value::String(s) => s.clone(),
+                other => other.to_string(),
+            };
+            columns.push((format!("collection_{}", name), value));
+        }
+        columns
+    }
+}
@@ -0,0 +1,210 @@
+//! Compressed data files: `<name>.sigmf-data.zst` (or `.zstd`) and
+//! `<name>.sigmf-data.gz` stand in for a missing `<name>.sigmf-data`.
+//!
+//! Streaming reads decompress on the fly. Random access (the spectrogram,
+//! sample windows) needs the plain bytes, so the file is decompressed once
+//! into the user cache directory and that copy is memory-mapped; it's reused
+//! until the compressed file changes, when it's replaced.
+
+use anyhow::Result;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use memmap2::Mmap;
+use std::collections::hash_map::DefaultHasher;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use tracing::debug;
+
+/// zstd level used when compressing; zstd's default trades speed for size well
+const ZSTD_LEVEL: i32 = 3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Compression {
+    Zstd,
+    Gzip,
+}
+
+impl Compression {
+    pub fn from_string(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "zstd" | "zst" => Ok(Compression::Zstd),
+            "gzip" | "gz" => Ok(Compression::Gzip),
+            other => anyhow::bail!("Unknown compression {:?}; expected zstd or gzip", other),
+        }
+    }
+
+    /// The compression a data file's extension names, if any
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Self> {
+        match path.as_ref().extension()?.to_str()? {
+            "zst" | "zstd" => Some(Compression::Zstd),
+            "gz" => Some(Compression::Gzip),
+            _ => None,
+        }
+    }
+
+    /// Extension added to the data file's name
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Compression::Zstd => "zst",
+            Compression::Gzip => "gz",
+        }
+    }
+}
+
+/// Extensions looked for after a missing `.sigmf-data`, in order
+const EXTENSIONS: [&str; 3] = ["zst", "zstd", "gz"];
+
+/// `path` with `.ext` appended, e.g. `a.sigmf-data` → `a.sigmf-data.zst`
+fn with_suffix(path: &Path, extension: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".");
+    name.push(extension);
+    PathBuf::from(name)
+}
+
+/// `path` if it's a file, else a compressed file standing in for it
+pub fn existing_data_file(path: &Path) -> Option<PathBuf> {
+    if path.is_file() {
+        return Some(path.to_path_buf());
+    }
+    EXTENSIONS.iter().map(|ext| with_suffix(path, ext)).find(|candidate| candidate.is_file())
+}
+
+/// Names a compressed stand-in for a data file named `file_name` could have
+pub fn compressed_names(file_name: &std::ffi::OsStr) -> Vec<std::ffi::OsString> {
+    EXTENSIONS
+        .iter()
+        .map(|ext| {
+            let mut name = file_name.to_os_string();
+            name.push(".");
+            name.push(ext);
+            name
+        })
+        .collect()
+}
+
+/// Decompressing reader over a compressed data file
+pub fn open_decompressed(path: &Path, compression: Compression) -> Result<Box<dyn Read + Send>> {
+    let file = BufReader::new(File::open(path)?);
+    Ok(match compression {
+        Compression::Zstd => Box::new(zstd::Decoder::with_buffer(file)?),
+        Compression::Gzip => Box::new(GzDecoder::new(file)),
+    })
+}
+
+/// Size of the decompressed data. zstd frames usually record it; otherwise
+/// it's the size of the decompressed copy if there is one, else the file is
+/// decompressed and the bytes counted without being kept. (gzip's trailer
+/// only holds the size modulo 4 GiB, and only of the last member.)
+pub fn decompressed_size(path: &Path, compression: Compression) -> Result<u64> {
+    if compression == Compression::Zstd {
+        let file = File::open(path)?;
+        // SAFETY: only read. Walking the frames touches their headers, not
+        // the compressed blocks.
+        let map = unsafe { Mmap::map(&file)? };
+        if let Some(size) = zstd_content_size(&map) {
+            return Ok(size);
+        }
+    }
+    let (cached, _) = cache_paths(path)?;
+    if let Ok(metadata) = std::fs::metadata(&cached) {
+        return Ok(metadata.len());
+    }
+    Ok(std::io::copy(&mut open_decompressed(path, compression)?, &mut std::io::sink())?)
+}
+
+/// Total content size the frames of a zstd file declare, if every one does
+fn zstd_content_size(mut frames: &[u8]) -> Option<u64> {
+    let mut total = 0u64;
+    while !frames.is_empty() {
+        total += zstd::zstd_safe::get_frame_content_size(frames).ok()??;
+        let len = zstd::zstd_safe::find_frame_compressed_size(frames).ok()?;
+        frames = frames.get(len..)?;
+    }
+    Some(total)
+}
+
+/// Where the plain copy of the compressed file at `path` goes, named after
+/// the file and its current size and modification time, and the name
+/// prefix every copy of the file shares
+fn cache_paths(path: &Path) -> Result<(PathBuf, String)> {
+    let source = path.canonicalize()?;
+    let metadata = std::fs::metadata(&source)?;
+    let hash = |value: &dyn Fn(&mut DefaultHasher)| {
+        let mut hasher = DefaultHasher::new();
+        value(&mut hasher);
+        hasher.finish()
+    };
+    let prefix = format!("{:016x}-", hash(&|hasher| source.hash(hasher)));
+    let version = hash(&|hasher| (metadata.len(), metadata.modified().ok()).hash(hasher));
+    let dir = dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("sig_viewer")
+        .join("decompressed");
+    Ok((dir.join(format!("{}{:016x}.sigmf-data", prefix, version)), prefix))
+}
+
+/// A plain copy of a compressed data file, in
+/// `~/.cache/sig_viewer/decompressed` (or the platform equivalent). Made on
+/// first use and again whenever the compressed file's size or modification
+/// time changes, replacing the copy of its previous contents.
+pub fn decompressed_path(path: &Path, compression: Compression) -> Result<PathBuf> {
+    let (cached, prefix) = cache_paths(path)?;
+    if cached.is_file() {
+        return Ok(cached);
+    }
+    let dir = cached.parent().unwrap_or(Path::new("."));
+    std::fs::create_dir_all(dir)?;
+    // Written aside and renamed, so a reader never maps a partial copy
+    let partial = cached.with_extension(format!("partial-{}", std::process::id()));
+    let mut writer = BufWriter::new(File::create(&partial)?);
+    std::io::copy(&mut open_decompressed(path, compression)?, &mut writer)?;
+    writer.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+    std::fs::rename(&partial, &cached)?;
+    debug!("Decompressed {:?} into {:?}", path, cached);
+
+    // Copies of the file as it was are stale. One still mapped elsewhere
+    // stays readable until unmapped (on Unix), and a failed removal only
+    // leaves the file for the next time.
+    for entry in std::fs::read_dir(dir)?.flatten() {
+        let stale = entry.path();
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.starts_with(&prefix) && name.ends_with(".sigmf-data") && stale != cached {
+            match std::fs::remove_file(&stale) {
+                Ok(()) => debug!("Removed stale decompressed copy {:?}", stale),
+                Err(e) => debug!("Could not remove stale decompressed copy {:?}: {}", stale, e),
+            }
+        }
+    }
+    Ok(cached)
+}
+
+/// Compress a data file written by an export to `<path>.zst` or
+/// `<path>.gz` and remove the plain one. Returns the compressed file.
+pub fn compress_data_file<P: AsRef<Path>>(path: P, compression: Compression) -> Result<PathBuf> {
+    let path = path.as_ref();
+    let out = with_suffix(path, compression.extension());
+    if out.exists() {
+        anyhow::bail!("{:?} already exists", out);
+    }
+    let mut reader = BufReader::new(File::open(path)?);
+    let writer = BufWriter::new(File::create(&out)?);
+    match compression {
+        Compression::Zstd => {
+            let mut encoder = zstd::Encoder::new(writer, ZSTD_LEVEL)?;
+            // Recorded so readers can size the recording without decompressing
+            encoder.set_pledged_src_size(Some(std::fs::metadata(path)?.len()))?;
+            std::io::copy(&mut reader, &mut encoder)?;
+            encoder.finish()?.flush()?;
+        }
+        Compression::Gzip => {
+            let mut encoder = GzEncoder::new(writer, flate2::Compression::default());
+            std::io::copy(&mut reader, &mut encoder)?;
+            encoder.finish()?.flush()?;
+        }
+    }
+    std::fs::remove_file(path)?;
+    Ok(out)
+}
@@ -0,0 +1,79 @@
+use super::{read_meta_text, SigMFDataType, SigMFParser};
+use crate::parser::SampleReader;
+use anyhow::Result;
+use serde_json::Value;
+use sha2::{Digest, Sha512};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+const CHUNK_SAMPLES: usize = 1 << 20;
+
+#[derive(Debug, Clone)]
+pub struct ConvertReport {
+    pub meta_path: PathBuf,
+    pub data_path: PathBuf,
+    pub num_samples: u64,
+    /// Samples that saturated when converting to a narrower integer type
+    pub clipped_samples: u64,
+}
+
+/// Convert a recording's samples to `target`, writing a new data file and a
+/// meta file with the updated `core:datatype` (and `core:sha512`, if the
+/// source had one) into `out_dir`. Everything else in the metadata is kept.
+pub fn convert_recording<P: AsRef<Path>, Q: AsRef<Path>>(
+    meta_path: P,
+    target: &SigMFDataType,
+    out_dir: Q,
+) -> Result<ConvertReport> {
+    let meta_path = meta_path.as_ref();
+    let out_dir = out_dir.as_ref();
+    let parser = SigMFParser::from_meta_file(meta_path)?;
+    if parser.data_type == *target {
+        anyhow::bail!("{:?} is already {}", meta_path, target.name());
+    }
+
+    let out_meta = out_dir.join(meta_path.file_name().unwrap());
+    let out_data = out_meta.with_extension("sigmf-data");
+    if out_meta.exists() && out_meta.canonicalize()? == meta_path.canonicalize()? {
+        anyhow::bail!("Output would overwrite the source recording; choose another --out directory");
+    }
+    std::fs::create_dir_all(out_dir)?;
+
+    let mut reader = parser.open_reader()?;
+    let num_samples = reader.num_samples();
+    let mut writer = BufWriter::new(std::fs::File::create(&out_data)?);
+    let mut hasher = Sha512::new();
+    let mut clipped_samples = 0;
+    let mut position = 0;
+    while position < num_samples {
+        let samples = reader.read_samples(position, CHUNK_SAMPLES)?;
+        if samples.is_empty() {
+            break;
+        }
+        let (bytes, clipped) = target.encode_samples(&samples);
+        writer.write_all(&bytes)?;
+        hasher.update(&bytes);
+        clipped_samples += clipped;
+        position += samples.len() as u64;
+    }
+    writer.flush()?;
+
+    let mut metadata: Value = serde_json::from_str(&read_meta_text(meta_path)?)?;
+    let global = metadata
+        .get_mut("global")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| anyhow::anyhow!("Missing global object in {:?}", meta_path))?;
+    global.insert("core:datatype".to_string(), Value::String(target.name().to_string()));
+    if global.contains_key("core:sha512") {
+        let digest: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+        global.insert("core:sha512".to_string(), Value::String(digest));
+    }
+    std::fs::write(&out_meta, serde_json::to_string_pretty(&metadata)? + "\n")?;
+
+    Ok(ConvertReport {
+        meta_path: out_meta,
+        data_path: out_data,
+        num_samples: position,
+        clipped_samples,
+    })
+}
@@ -1,80 +1,1017 @@
-use super::SigMFParser;
+use super::cache::{CachedRecording, DatasetCache};
+use super::summary::{SummaryColumns, SummaryRow};
+use super::{group_segments, is_archive, list_recordings, ChecksumStatus, Segment, SigMFCollection, SigMFParser};
+use crate::data_ops::{merge_rows, ROW_PER_ML_ANNOTATION};
+use crate::parser::format::{find_file_format, find_format, find_marked_format};
+use crate::parser::vrt::list_streams;
+use crate::parser::ParserError;
+use crate::viz::{estimate_noise_floor, measure_signal, SignalMetrics};
 use anyhow::Result;
 use polars::prelude::*;
-use std::path::Path;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info, warn};
 use walkdir::WalkDir;
 
 pub struct SigMFDataset;
 
+/// Number of time buckets in the `annotation_density` column
+pub const ANNOTATION_DENSITY_BUCKETS: usize = 16;
+
+/// Prefix of the columns made from unrecognized annotation fields
+pub const ANNOTATION_FIELD_PREFIX: &str = "ann_";
+
+/// Column naming the root directory each row of a multi-root scan came from
+pub const SOURCE_ROOT_COLUMN: &str = "source_root";
+
+/// Column holding each row's meta file relative to the directory scanned
+/// (as given for recordings outside it), which tells apart recordings with
+/// the same `meta_filename` in different subdirectories
+pub const META_PATH_COLUMN: &str = "meta_path";
+
+/// What one row of a dataset stands for
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Granularity {
+    /// One row per ML annotation, or per file for recordings without any,
+    /// with an `annotation_index` column
+    #[default]
+    Annotation,
+    /// One row per capture segment, with its own frequency, gain and datetime
+    Capture,
+    /// One row per file, with the first ML annotation's values and
+    /// `num_detected_sigs` counting them all
+    File,
+}
+
+impl Granularity {
+    pub fn from_string(s: &str) -> Result<Self> {
+        match s.to_lowercase().as_str() {
+            "annotation" => Ok(Granularity::Annotation),
+            "capture" => Ok(Granularity::Capture),
+            "file" => Ok(Granularity::File),
+            other => anyhow::bail!("Unknown granularity {:?}; expected file, annotation or capture", other),
+        }
+    }
+
+    /// `row_per` value recorded in provenance sidecars
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Granularity::Annotation => ROW_PER_ML_ANNOTATION,
+            Granularity::Capture => "capture",
+            Granularity::File => "file",
+        }
+    }
+}
+
+/// Optional work done while building a dataset
+#[derive(Debug, Clone, Default)]
+pub struct DatasetOptions {
+    pub granularity: Granularity,
+    /// Hash every data file and compare against `core:sha512`. Slow on large
+    /// datasets, so off by default; the `checksum_status` column then reads
+    /// `not_checked`.
+    pub verify_checksums: bool,
+    /// Read a sample of each recording to estimate its noise floor (the
+    /// `noise_floor_db` column, null otherwise)
+    pub estimate_noise_floor: bool,
+    /// Measure each row's noise floor, peak power, occupied bandwidth and
+    /// SNR from the samples (the `measured_*` columns, see
+    /// [`measure_signal`]): over its capture segment with
+    /// `Granularity::Capture`, else over the whole recording
+    pub compute_metrics: bool,
+    /// Add a column for every scalar annotation field the parser doesn't
+    /// know (custom `ds:` or other extension fields), named
+    /// `ann_<field>` with non-alphanumerics replaced by `_`
+    pub annotation_extra_columns: bool,
+    /// Reuse the rows of files unchanged since the last scan of the same
+    /// directory with the same options, and save this scan's for the next
+    /// (see [`DatasetCache`])
+    pub cache: bool,
+    /// Only read a random sample of this many files (an archive counts as
+    /// one), picked while walking the directory, for a quick preview of a
+    /// large one. Collection members outside the directory are left out.
+    pub sample: Option<usize>,
+    /// Seed for `sample`, to draw the same files again; random otherwise
+    pub sample_seed: Option<u64>,
+    /// Don't log the scan's summary lines (files found, cache reuse, final
+    /// shape). Failures still reach the progress callback.
+    pub quiet: bool,
+}
+
+/// Where a directory scan stands, passed to its `on_progress` callback
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ScanProgress<'a> {
+    /// Files processed and found; an archive counts as one file
+    pub done: usize,
+    pub total: usize,
+    /// Files and recordings that failed to parse so far
+    pub errors: usize,
+    /// What failed since the previous call
+    pub failures: &'a [FileFailure],
+}
+
+/// The step of a scan a file failed at
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FailureStage {
+    /// Opening a recording: its meta file, datatype or data file
+    Parse,
+    /// Computing a parsed recording's summary row
+    Summary,
+    Archive,
+    Collection,
+    /// A collection member outside the scanned directory
+    CollectionMember,
+}
+
+impl FailureStage {
+    pub fn description(&self) -> &'static str {
+        match self {
+            FailureStage::Parse => "Failed to parse",
+            FailureStage::Summary => "Failed to create summary for",
+            FailureStage::Archive => "Failed to read archive",
+            FailureStage::Collection => "Failed to parse collection",
+            FailureStage::CollectionMember => "Failed to parse collection member",
+        }
+    }
+}
+
+/// A file or recording a scan skipped, and why
+#[derive(Debug, Clone)]
+pub struct FileFailure {
+    pub path: PathBuf,
+    pub stage: FailureStage,
+    pub error: ParserError,
+}
+
+impl FileFailure {
+    fn new(path: &Path, stage: FailureStage, error: impl Into<ParserError>) -> Self {
+        FileFailure { path: path.to_path_buf(), stage, error: error.into() }
+    }
+}
+
+impl std::fmt::Display for FileFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {:?}: {}", self.stage.description(), self.path, self.error)
+    }
+}
+
+/// Every failure of a scan, gathered from its progress callbacks
+#[derive(Debug, Clone, Default)]
+pub struct ScanReport {
+    /// Files found, as in [`ScanProgress::total`]
+    pub files: usize,
+    pub failures: Vec<FileFailure>,
+}
+
+impl ScanReport {
+    pub fn record(&mut self, progress: &ScanProgress) {
+        self.files = progress.total;
+        self.failures.extend_from_slice(progress.failures);
+    }
+}
+
 impl SigMFDataset {
-    /// Parse all .sigmf-meta files in a directory and create a dataset DataFrame
+    /// Parse all .sigmf-meta files in a directory and create a dataset DataFrame.
+    /// Recordings inside `.sigmf` archives are included, and members of any
+    /// `.sigmf-collection` found get the collection's metadata as extra columns.
+    /// Raw IQ files (`.cfile`, baseband `.wav`, VITA-49 `.vrt` captures and
+    /// X-Midas BLUE `.tmp`/`.prm` files, see [`crate::parser::raw`]) are read
+    /// too, as are DigitalRF channel directories (see
+    /// [`crate::parser::digitalrf`]).
     pub fn from_directory<P: AsRef<Path>>(dir_path: P) -> Result<DataFrame> {
-        let mut all_rows = Vec::new();
+        Self::from_directory_with_options(dir_path, &DatasetOptions::default())
+    }
+
+    /// Failures are logged as warnings as they happen
+    pub fn from_directory_with_options<P: AsRef<Path>>(dir_path: P, options: &DatasetOptions) -> Result<DataFrame> {
+        Self::from_directory_with_progress(dir_path, options, print_failures)
+    }
+
+    /// Like [`from_directory_with_options`](Self::from_directory_with_options),
+    /// calling `on_progress` before each file and once at the end, and
+    /// leaving failures to it rather than logging them. An error from
+    /// `on_progress` stops the scan.
+    pub fn from_directory_with_progress<P: AsRef<Path>>(
+        dir_path: P,
+        options: &DatasetOptions,
+        mut on_progress: impl FnMut(ScanProgress) -> Result<()>,
+    ) -> Result<DataFrame> {
+        let mut rows = DatasetBuilder::new(options);
+        let mut collections = Vec::new();
+        // Scanned files parsed without errors, with their recordings in `rows`
+        let mut cacheable = Vec::new();
         let mut processed_count = 0;
         let mut error_count = 0;
+        // Failures not yet handed to `on_progress`
+        let mut failures = Vec::new();
+        let dir_path = dir_path.as_ref();
         
-        println!("Scanning directory: {:?}", dir_path.as_ref());
+        if !options.quiet {
+            info!("Scanning directory: {:?}", dir_path);
+        }
+        let mut cache = options.cache.then(|| DatasetCache::open(DatasetCache::default_path(dir_path, options)));
+        rows.cached = cache.as_ref().and_then(|cache| cache.rows()).cloned();
         
-        // Find all .sigmf-meta files
+        // List the files first so progress has a total
+        let mut found = Vec::new();
+        let mut sample = options.sample.map(|size| Reservoir::new(size, options.sample_seed));
         for entry in WalkDir::new(dir_path).follow_links(true) {
             let entry = entry?;
-            let path = entry.path();
+            // A recording stored as a directory (DigitalRF) is found through its marker file
+            if entry.file_name().to_str().and_then(find_marked_format).is_some() {
+                if let Some(recording) = entry.path().parent().map(Path::to_path_buf) {
+                    match sample.as_mut() {
+                        Some(sample) => sample.offer(recording),
+                        None => found.push(recording),
+                    }
+                }
+                continue;
+            }
+            let extension = entry.path().extension().and_then(|s| s.to_str());
+            if extension == Some("sigmf-collection") {
+                found.push(entry.into_path());
+                continue;
+            }
+            if !matches!(extension, Some("sigmf-meta" | "sigmf")) && find_file_format(entry.path()).is_none() {
+                continue;
+            }
+            match sample.as_mut() {
+                Some(sample) => sample.offer(entry.into_path()),
+                None => found.push(entry.into_path()),
+            }
+        }
+        if let Some(sample) = sample {
+            if !options.quiet {
+                info!("Sampled {} of {} files", sample.items.len(), sample.seen);
+            }
+            found.extend(sample.items);
+            found.sort();
+        }
+        
+        // Find all .sigmf-meta files, recordings inside .sigmf archives, and raw IQ, WAV, VITA-49 and BLUE files
+        for (done, path) in found.iter().enumerate() {
+            on_progress(ScanProgress { done, total: found.len(), errors: error_count, failures: &failures })?;
+            failures.clear();
+            let path = path.as_path();
+            debug!("Reading {:?}", path);
             
-            if path.extension().and_then(|s| s.to_str()) == Some("sigmf-meta") {
-                processed_count += 1;
-                if processed_count % 10 == 0 {
-                    println!("Processed {} files...", processed_count);
+            if let Some(recordings) = cache.as_mut().and_then(|cache| cache.get(path)) {
+                for recording in recordings {
+                    processed_count += 1;
+                    rows.add_cached(recording);
                 }
-                
-                match SigMFParser::from_meta_file(path) {
+                continue;
+            }
+            
+            let parsers: Vec<(PathBuf, Result<SigMFParser>)> = match path.extension().and_then(|s| s.to_str()) {
+                Some("sigmf-meta") => vec![(path.to_path_buf(), SigMFParser::from_meta_file(path))],
+                Some("sigmf-collection") => {
+                    match SigMFCollection::from_file(path) {
+                        Ok(collection) => collections.push(collection),
+                        Err(e) => {
+                            error_count += 1;
+                            failures.push(FileFailure::new(path, FailureStage::Collection, e));
+                        }
+                    }
+                    continue;
+                }
+                Some("sigmf") if is_archive(path) => match list_recordings(path) {
+                    Ok(recordings) => recordings
+                        .into_iter()
+                        .map(|r| (r.meta_path.clone(), SigMFParser::from_archive_recording(r)))
+                        .collect(),
+                    Err(e) => {
+                        error_count += 1;
+                        failures.push(FileFailure::new(path, FailureStage::Archive, e));
+                        continue;
+                    }
+                },
+                _ => match find_format(path).map(|format| format.recordings(path)) {
+                    Some(Ok(recordings)) => recordings,
+                    Some(Err(e)) => {
+                        error_count += 1;
+                        failures.push(FileFailure::new(path, FailureStage::Parse, e));
+                        continue;
+                    }
+                    None => continue,
+                },
+            };
+
+            let first = rows.recording_count();
+            let mut failed = false;
+            for (path, parser) in parsers {
+                processed_count += 1;
+                match parser {
                     Ok(parser) => {
-                        match parser.to_summary_row() {
-                            Ok(row_df) => all_rows.push(row_df),
-                            Err(e) => {
-                                error_count += 1;
-                                eprintln!("Failed to create summary for {:?}: {}", path, e);
-                            }
+                        if let Err(e) = rows.add_parsed(path.clone(), &parser) {
+                            error_count += 1;
+                            failed = true;
+                            failures.push(FileFailure::new(&path, FailureStage::Summary, e));
                         }
                     }
                     Err(e) => {
                         error_count += 1;
-                        eprintln!("Failed to parse {:?}: {}", path, e);
+                        failed = true;
+                        failures.push(FileFailure::new(&path, FailureStage::Parse, e));
                     }
                 }
             }
+            // Files with errors are parsed again next time, so the errors are reported again.
+            // Recordings stored as a directory (DigitalRF) grow in subdirectories the file stamps don't see.
+            if !failed && !path.is_dir() {
+                cacheable.push((path.to_path_buf(), first..rows.recording_count()));
+            }
         }
         
-        println!("Processed {} files, {} errors", processed_count, error_count);
+        // Collection members that live outside the scanned directory
+        let scanned: HashSet<PathBuf> = rows.recordings.iter().map(|r| normalize(&r.meta_path)).collect();
+        for collection in collections.iter().filter(|_| options.sample.is_none()) {
+            for path in collection.member_meta_paths() {
+                if scanned.contains(&normalize(&path)) {
+                    continue;
+                }
+                processed_count += 1;
+                let added = SigMFParser::from_meta_file(&path).and_then(|parser| rows.add_parsed(path.clone(), &parser));
+                if let Err(e) = added {
+                    error_count += 1;
+                    failures.push(FileFailure::new(&path, FailureStage::CollectionMember, e));
+                }
+            }
+        }
         
-        if all_rows.is_empty() {
-            anyhow::bail!("No valid SigMF files found in directory");
+        on_progress(ScanProgress { done: found.len(), total: found.len(), errors: error_count, failures: &failures })?;
+        if !options.quiet {
+            info!("Processed {} files, {} errors", processed_count, error_count);
         }
         
-        // Combine all rows into one DataFrame using vstack
-        let mut combined = all_rows.clone().into_iter().next().unwrap();
-        for row_df in all_rows.into_iter().skip(1) {
-            combined.vstack_mut(&row_df)?;
+        if rows.recordings.is_empty() {
+            anyhow::bail!("No valid SigMF files found in directory");
         }
+        let (combined, recordings) = rows.finish()?;
+        if let Some(mut cache) = cache {
+            for (source, range) in cacheable {
+                cache.insert(&source, recordings[range].iter().map(RecordingSpan::to_cached).collect());
+            }
+            if !options.quiet {
+                info!("Reused cached rows for {} unchanged files", cache.reused_count());
+            }
+            // A cache that can't be written only costs the next scan time
+            if let Err(e) = cache.save(&combined, options.sample.is_some()) {
+                warn!("Could not save dataset cache: {}", e);
+            }
+        }
+        let combined = add_recording_columns(combined, &recordings, &collections, Some(dir_path))?;
         
-        println!("Final dataset shape: {:?}", combined.shape());
+        if !options.quiet {
+            debug!("Final dataset shape: {:?}", combined.shape());
+        }
         Ok(combined)
     }
-    
+
+    /// Scan several root directories, e.g. recordings split across drives,
+    /// into one dataset with a `source_root` column holding each row's root
+    /// (absolute). Columns only some roots have are null in the other rows.
+    pub fn from_directories<P: AsRef<Path>>(dir_paths: &[P]) -> Result<DataFrame> {
+        Self::from_directories_with_options(dir_paths, &DatasetOptions::default())
+    }
+
+    pub fn from_directories_with_options<P: AsRef<Path>>(dir_paths: &[P], options: &DatasetOptions) -> Result<DataFrame> {
+        Self::from_directories_with_progress(dir_paths, options, print_failures)
+    }
+
+    /// Like [`from_directory_with_progress`](Self::from_directory_with_progress)
+    /// for each root in turn. The total counts the files of the roots listed
+    /// so far, so it grows as each root's scan starts. Each root has its own
+    /// cache, and `options.sample` draws that many files from each.
+    pub fn from_directories_with_progress<P: AsRef<Path>>(
+        dir_paths: &[P],
+        options: &DatasetOptions,
+        mut on_progress: impl FnMut(ScanProgress) -> Result<()>,
+    ) -> Result<DataFrame> {
+        let roots = distinct_roots(dir_paths)?;
+        let mut parts = Vec::new();
+        let (mut finished, mut errors) = (0, 0);
+        for root in &roots {
+            let mut last = (0, 0);
+            let mut part = Self::from_directory_with_progress(root, options, |progress| {
+                last = (progress.total, progress.errors);
+                on_progress(ScanProgress {
+                    done: finished + progress.done,
+                    total: finished + progress.total,
+                    errors: errors + progress.errors,
+                    failures: progress.failures,
+                })
+            })
+            .map_err(|e| anyhow::anyhow!("{}: {}", root.display(), e))?;
+            finished += last.0;
+            errors += last.1;
+            part.with_column(Series::new(SOURCE_ROOT_COLUMN.into(), vec![root.display().to_string(); part.height()]))?;
+            parts.push(part);
+        }
+        let combined = merge_rows(parts)?.ok_or_else(|| anyhow::anyhow!("No directories to scan"))?;
+        if roots.len() > 1 && !options.quiet {
+            info!("Combined {} roots: {:?}", roots.len(), combined.shape());
+        }
+        Ok(combined)
+    }
+
+    /// Every member recording of a `.sigmf-collection`, with the collection's
+    /// metadata as extra columns. Members that can't be read are logged as
+    /// warnings and left out.
+    pub fn from_collection<P: AsRef<Path>>(collection_path: P) -> Result<DataFrame> {
+        let collection = SigMFCollection::from_file(collection_path.as_ref())?;
+        let members = collection.member_meta_paths();
+        if members.is_empty() {
+            anyhow::bail!("Collection has no member recordings");
+        }
+        let mut rows = DatasetBuilder::new(&DatasetOptions::default());
+        let mut failures = Vec::new();
+        for path in &members {
+            let added = SigMFParser::from_meta_file(path).and_then(|parser| rows.add_parsed(path.clone(), &parser));
+            if let Err(e) = added {
+                failures.push(FileFailure::new(path, FailureStage::CollectionMember, e));
+            }
+        }
+        print_failures(ScanProgress { done: members.len(), total: members.len(), errors: failures.len(), failures: &failures })?;
+        if rows.recordings.is_empty() {
+            anyhow::bail!("None of the collection's {} member recordings could be read", members.len());
+        }
+        let (combined, recordings) = rows.finish()?;
+        let root = collection_path.as_ref().parent();
+        add_recording_columns(combined, &recordings, std::slice::from_ref(&collection), root)
+    }
+
+    /// All .sigmf-meta files at `path` (a single file or a directory), sorted
+    pub fn meta_files<P: AsRef<Path>>(path: P) -> Result<Vec<PathBuf>> {
+        let path = path.as_ref();
+        if path.is_file() {
+            return Ok(vec![path.to_path_buf()]);
+        }
+        if !path.is_dir() {
+            anyhow::bail!("Path does not exist: {:?}", path);
+        }
+        let mut files = Vec::new();
+        for entry in WalkDir::new(path).follow_links(true) {
+            let entry = entry?;
+            if entry.path().extension().and_then(|s| s.to_str()) == Some("sigmf-meta") {
+                files.push(entry.into_path());
+            }
+        }
+        files.sort();
+        Ok(files)
+    }
+
+    /// Every recording in a `.sigmf` archive
+    pub fn from_archive<P: AsRef<Path>>(archive_path: P) -> Result<DataFrame> {
+        let recordings = list_recordings(archive_path)?
+            .into_iter()
+            .map(|r| (r.meta_path.clone(), SigMFParser::from_archive_recording(r)))
+            .collect();
+        Self::from_recordings(recordings)
+    }
+
+    /// Each row's meta file: its `meta_path` under its `source_root`, or
+    /// under `root` for rows without one. Datasets from before `meta_path`
+    /// fall back to `meta_filename`. `None` for rows with neither.
+    pub fn row_meta_paths(dataset: &DataFrame, root: &Path) -> Result<Vec<Option<PathBuf>>> {
+        let paths = match dataset.column(META_PATH_COLUMN) {
+            Ok(column) => column.str()?.clone(),
+            Err(_) => dataset.column("meta_filename")?.str()?.clone(),
+        };
+        let roots = dataset.column(SOURCE_ROOT_COLUMN).ok().and_then(|column| column.str().ok().cloned());
+        Ok((0..dataset.height())
+            .map(|row| {
+                let row_root = roots.as_ref().and_then(|roots| roots.get(row)).filter(|root| !root.is_empty()).map_or(root, Path::new);
+                paths.get(row).map(|path| row_root.join(path))
+            })
+            .collect())
+    }
+
+    /// Locate a recording's meta file by file name under `dir`, including
+    /// inside archives and multi-stream VITA-49 captures (returned as a
+    /// virtual path)
+    pub fn find_meta_file<P: AsRef<Path>>(dir: P, meta_filename: &str) -> Option<PathBuf> {
+        for entry in WalkDir::new(dir).follow_links(true).into_iter().filter_map(|e| e.ok()) {
+            let path = entry.path();
+            if entry.file_name().to_string_lossy() == meta_filename {
+                return Some(path.to_path_buf());
+            }
+            if path.extension().is_some_and(|ext| ext == "vrt") {
+                let found = list_streams(path).ok().and_then(|streams| {
+                    streams
+                        .into_iter()
+                        .find(|s| s.meta_path != path && s.meta_path.file_name().is_some_and(|name| name.to_string_lossy() == meta_filename))
+                });
+                if let Some(stream) = found {
+                    return Some(stream.meta_path);
+                }
+            }
+            if is_archive(path) {
+                let found = list_recordings(path).ok().and_then(|recordings| {
+                    recordings
+                        .into_iter()
+                        .find(|r| r.meta_path.file_name().is_some_and(|name| name.to_string_lossy() == meta_filename))
+                });
+                if let Some(recording) = found {
+                    return Some(recording.meta_path);
+                }
+            }
+        }
+        None
+    }
+
     /// Parse specific files into a dataset
     pub fn from_files<P: AsRef<Path>>(file_paths: &[P]) -> Result<DataFrame> {
+        Self::from_files_with_options(file_paths, &DatasetOptions::default())
+    }
+
+    /// A dataset of recordings already opened, e.g. those of a file in a
+    /// registered format (see [`crate::parser::format`])
+    pub fn from_recordings(recordings: Vec<(PathBuf, Result<SigMFParser>)>) -> Result<DataFrame> {
+        if recordings.is_empty() {
+            anyhow::bail!("No recordings provided");
+        }
+        let mut rows = DatasetBuilder::new(&DatasetOptions::default());
+        for (path, parser) in recordings {
+            rows.add_parsed(path, &parser?)?;
+        }
+        let (combined, recordings) = rows.finish()?;
+        add_recording_columns(combined, &recordings, &[], None)
+    }
+
+    /// Parse specific files into a dataset. Segments are only grouped among
+    /// the given files, and no collection columns are added.
+    pub fn from_files_with_options<P: AsRef<Path>>(file_paths: &[P], options: &DatasetOptions) -> Result<DataFrame> {
         if file_paths.is_empty() {
             anyhow::bail!("No files provided");
         }
-        let mut all_rows = Vec::new();
+        let mut rows = DatasetBuilder::new(options);
         for path in file_paths {
             let parser = SigMFParser::from_meta_file(path)?;
-            let row_df = parser.to_summary_row()?;
-            all_rows.push(row_df);
+            rows.add_parsed(path.as_ref().to_path_buf(), &parser)?;
         }
-        let mut combined = all_rows.remove(0);
-        for row_df in all_rows {
-            combined.vstack_mut(&row_df)?;
+        let (combined, recordings) = rows.finish()?;
+        add_recording_columns(combined, &recordings, &[], None)
+    }
+}
+
+/// Where one recording's rows are in a dataset being built
+struct RecordingRows {
+    meta_path: PathBuf,
+    source: RowSource,
+    offset: usize,
+    len: usize,
+    segment: Option<Segment>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum RowSource {
+    /// In the builder's columns
+    Parsed,
+    /// In the cached rows
+    Cached,
+}
+
+/// Where a recording's rows ended up in the built dataset
+struct RecordingSpan {
+    meta_path: PathBuf,
+    offset: usize,
+    len: usize,
+    segment: Option<Segment>,
+}
+
+impl RecordingSpan {
+    fn to_cached(&self) -> CachedRecording {
+        CachedRecording {
+            meta_path: self.meta_path.clone(),
+            offset: self.offset,
+            len: self.len,
+            segment: self.segment.clone(),
         }
-        Ok(combined)
     }
 }
+
+/// [`measure_signal`] for each row, measuring each distinct span once. A
+/// missing or short data file leaves the row's levels null rather than
+/// dropping the recording.
+fn measure_rows(parser: &SigMFParser, rows: &[SummaryRow]) -> Vec<Option<SignalMetrics>> {
+    let mut reader = match parser.open_reader() {
+        Ok(reader) => reader,
+        Err(e) => {
+            warn!("Could not measure {:?}: {}", parser.data_file_path, e);
+            return vec![None; rows.len()];
+        }
+    };
+    let mut measured: HashMap<(u64, u64), Option<SignalMetrics>> = HashMap::new();
+    rows.iter()
+        .map(|row| {
+            let span = row.capture.map_or((0, u64::MAX), |capture| (capture.sample_start, capture.sample_count));
+            *measured.entry(span).or_insert_with(|| match measure_signal(&mut reader, span.0, span.1, parser.sample_rate()) {
+                Ok(metrics) => Some(metrics),
+                Err(e) => {
+                    warn!("Could not measure {:?} from sample {}: {}", parser.data_file_path, span.0, e);
+                    None
+                }
+            })
+        })
+        .collect()
+}
+
+/// Rows of a dataset as recordings are scanned. Parsed recordings go into
+/// plain column vectors and cached ones are offsets into the cached rows;
+/// [`finish`](Self::finish) builds the DataFrame once, in scan order.
+struct DatasetBuilder {
+    options: DatasetOptions,
+    /// [`DatasetCache::rows`], which cached recordings point into
+    cached: Option<DataFrame>,
+    columns: SummaryColumns,
+    annotation_density: Vec<String>,
+    checksum_status: Vec<&'static str>,
+    noise_floor_db: Vec<Option<f64>>,
+    parse_warnings: Vec<String>,
+    measured: Vec<Option<SignalMetrics>>,
+    annotation_fields: Vec<Vec<(String, serde_json::Value)>>,
+    recordings: Vec<RecordingRows>,
+}
+
+impl DatasetBuilder {
+    fn new(options: &DatasetOptions) -> Self {
+        DatasetBuilder {
+            options: options.clone(),
+            cached: None,
+            columns: SummaryColumns::new(options.granularity),
+            annotation_density: Vec::new(),
+            checksum_status: Vec::new(),
+            noise_floor_db: Vec::new(),
+            parse_warnings: Vec::new(),
+            measured: Vec::new(),
+            annotation_fields: Vec::new(),
+            recordings: Vec::new(),
+        }
+    }
+
+    fn recording_count(&self) -> usize {
+        self.recordings.len()
+    }
+
+    /// Summary rows for one recording plus the per-file checksum status,
+    /// annotation density (comma-separated counts per time bucket), noise
+    /// floor, parse warnings ("; "-separated) and measured levels. Nothing is added if any of it fails.
+    fn add_parsed(&mut self, meta_path: PathBuf, parser: &SigMFParser) -> Result<()> {
+        let options = &self.options;
+        let rows = match options.granularity {
+            Granularity::Annotation => parser.summary_rows(),
+            Granularity::Capture => parser.capture_summary_rows(),
+            Granularity::File => parser.file_summary_rows(),
+        };
+        let density = parser
+            .annotation_density(ANNOTATION_DENSITY_BUCKETS)
+            .iter()
+            .map(|count| count.to_string())
+            .collect::<Vec<_>>()
+            .join(",");
+        let status = if options.verify_checksums {
+            parser.verify_checksum()?
+        } else {
+            ChecksumStatus::NotChecked
+        };
+        let noise_floor = if options.estimate_noise_floor {
+            // A missing or short data file shouldn't drop the recording
+            match parser.open_reader().and_then(|mut reader| estimate_noise_floor(&mut reader)) {
+                Ok(db) => Some(db as f64),
+                Err(e) => {
+                    warn!("Could not estimate noise floor for {:?}: {}", parser.data_file_path, e);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        if options.compute_metrics {
+            self.measured.extend(measure_rows(parser, &rows));
+        }
+        if options.annotation_extra_columns {
+            let fields = match options.granularity {
+                Granularity::Annotation => parser.annotation_extra_fields(),
+                Granularity::Capture => parser.capture_annotation_extra_fields(),
+                Granularity::File => parser.annotation_extra_fields().into_iter().take(1).collect(),
+            };
+            let owned = fields.into_iter().map(|row| row.into_iter().map(|(key, value)| (key.to_string(), value.clone())).collect());
+            self.annotation_fields.extend(owned);
+        }
+
+        let (offset, len) = (self.columns.len(), rows.len());
+        rows.into_iter().for_each(|row| self.columns.push(row));
+        self.annotation_density.extend(std::iter::repeat_n(density, len));
+        self.checksum_status.extend(std::iter::repeat_n(status.as_str(), len));
+        self.noise_floor_db.extend(std::iter::repeat_n(noise_floor, len));
+        self.parse_warnings.extend(std::iter::repeat_n(parser.parse_warnings.join("; "), len));
+        self.recordings.push(RecordingRows {
+            segment: Segment::from_parser(&meta_path, parser),
+            meta_path,
+            source: RowSource::Parsed,
+            offset,
+            len,
+        });
+        Ok(())
+    }
+
+    fn add_cached(&mut self, recording: CachedRecording) {
+        self.recordings.push(RecordingRows {
+            meta_path: recording.meta_path,
+            source: RowSource::Cached,
+            offset: recording.offset,
+            len: recording.len,
+            segment: recording.segment,
+        });
+    }
+
+    /// All rows in scan order, and where each recording's are
+    fn finish(self) -> Result<(DataFrame, Vec<RecordingSpan>)> {
+        let mut parsed = self.columns.finish()?;
+        let height = parsed.height();
+        parsed.with_column(Series::new("annotation_density".into(), self.annotation_density))?;
+        parsed.with_column(Series::new("checksum_status".into(), self.checksum_status))?;
+        parsed.with_column(Series::new("noise_floor_db".into(), self.noise_floor_db))?;
+        parsed.with_column(Series::new("parse_warnings".into(), self.parse_warnings))?;
+        if self.options.compute_metrics {
+            let column = |value: fn(&SignalMetrics) -> Option<f64>| -> Vec<Option<f64>> {
+                self.measured.iter().map(|m| m.as_ref().and_then(value)).collect()
+            };
+            parsed.with_column(Series::new("measured_noise_floor_db".into(), column(|m| Some(m.noise_floor_db as f64))))?;
+            parsed.with_column(Series::new("measured_peak_power_db".into(), column(|m| Some(m.peak_power_db as f64))))?;
+            parsed.with_column(Series::new("measured_occupied_bw_hz".into(), column(|m| m.occupied_bandwidth_hz)))?;
+            parsed.with_column(Series::new("measured_snr_db".into(), column(|m| m.snr_db.map(f64::from))))?;
+        }
+        if self.options.annotation_extra_columns {
+            let fields: Vec<Vec<(&str, &serde_json::Value)>> = self
+                .annotation_fields
+                .iter()
+                .map(|row| row.iter().map(|(key, value)| (key.as_str(), value)).collect())
+                .collect();
+            add_annotation_field_columns(&mut parsed, &fields)?;
+        }
+
+        // Recordings next to each other in the scan are mostly next to each
+        // other in their source too, so only whole runs need stacking
+        let mut runs: Vec<(RowSource, usize, usize)> = Vec::new();
+        let mut spans = Vec::with_capacity(self.recordings.len());
+        let mut next_offset = 0;
+        for recording in self.recordings {
+            match runs.last_mut() {
+                Some((source, offset, len)) if *source == recording.source && *offset + *len == recording.offset => *len += recording.len,
+                _ => runs.push((recording.source, recording.offset, recording.len)),
+            }
+            spans.push(RecordingSpan { meta_path: recording.meta_path, offset: next_offset, len: recording.len, segment: recording.segment });
+            next_offset += recording.len;
+        }
+        let mut parts = Vec::with_capacity(runs.len());
+        for (source, offset, len) in runs {
+            let rows = match source {
+                RowSource::Parsed if offset == 0 && len == height => parsed.clone(),
+                RowSource::Parsed => parsed.slice(offset as i64, len),
+                RowSource::Cached => self.cached.as_ref().ok_or_else(|| anyhow::anyhow!("No cached rows"))?.slice(offset as i64, len),
+            };
+            parts.push(rows);
+        }
+        let combined = merge_rows(parts)?.ok_or_else(|| anyhow::anyhow!("No rows"))?;
+        Ok((combined, spans))
+    }
+}
+
+/// Add each recording's collection columns (the union over `collections`,
+/// empty for recordings outside any) and segment columns: `segment_group`
+/// (first segment's meta file, empty for standalone recordings),
+/// `segment_index`, `segment_count` and `stitched_duration_s` (the whole
+/// capture's duration), and [`META_PATH_COLUMN`] relative to `root`.
+/// `ann_*` columns are moved last.
+fn add_recording_columns(mut dataset: DataFrame, recordings: &[RecordingSpan], collections: &[SigMFCollection], root: Option<&Path>) -> Result<DataFrame> {
+    let mut column_names: Vec<String> = Vec::new();
+    let mut membership = HashMap::new();
+    for collection in collections {
+        let columns = collection.columns();
+        for (name, _) in &columns {
+            if !column_names.contains(name) {
+                column_names.push(name.clone());
+            }
+        }
+        for path in collection.member_meta_paths() {
+            membership.insert(normalize(&path), columns.clone());
+        }
+    }
+    for name in &column_names {
+        let mut values: Vec<&str> = Vec::with_capacity(dataset.height());
+        for recording in recordings {
+            let value = membership
+                .get(&normalize(&recording.meta_path))
+                .and_then(|columns| columns.iter().find(|(n, _)| n == name))
+                .map(|(_, v)| v.as_str())
+                .unwrap_or("");
+            values.extend(std::iter::repeat_n(value, recording.len));
+        }
+        dataset.with_column(Series::new(name.as_str().into(), values))?;
+    }
+
+    let grouped = group_segments(recordings.iter().filter_map(|r| r.segment.clone()).collect());
+    let mut segment_of = HashMap::new();
+    for recording in &grouped {
+        for (idx, segment) in recording.segments.iter().enumerate() {
+            segment_of.insert(normalize(&segment.meta_path), (recording, idx));
+        }
+    }
+    let durations = dataset.column("duration_s")?.f64()?.clone();
+    let height = dataset.height();
+    let (mut groups, mut indexes, mut counts, mut stitched) =
+        (Vec::with_capacity(height), Vec::with_capacity(height), Vec::with_capacity(height), Vec::with_capacity(height));
+    for recording in recordings {
+        let (group, index, count, duration) = match segment_of.get(&normalize(&recording.meta_path)) {
+            Some((segmented, idx)) => (segmented.name(), *idx as u32, segmented.segments.len() as u32, segmented.duration_s()),
+            None => (String::new(), 0, 1, durations.get(recording.offset).unwrap_or(0.0)),
+        };
+        groups.extend(std::iter::repeat_n(group, recording.len));
+        indexes.extend(std::iter::repeat_n(index, recording.len));
+        counts.extend(std::iter::repeat_n(count, recording.len));
+        stitched.extend(std::iter::repeat_n(duration, recording.len));
+    }
+    dataset.with_column(Series::new("segment_group".into(), groups))?;
+    dataset.with_column(Series::new("segment_index".into(), indexes))?;
+    dataset.with_column(Series::new("segment_count".into(), counts))?;
+    dataset.with_column(Series::new("stitched_duration_s".into(), stitched))?;
+
+    let mut meta_paths = Vec::with_capacity(height);
+    for recording in recordings {
+        let path = match root {
+            Some(root) => relative_meta_path(&recording.meta_path, root),
+            None => recording.meta_path.to_string_lossy().to_string(),
+        };
+        meta_paths.extend(std::iter::repeat_n(path, recording.len));
+    }
+    if dataset.column(META_PATH_COLUMN).is_ok() {
+        let _ = dataset.drop_in_place(META_PATH_COLUMN)?;
+    }
+    let position = dataset.get_column_index("meta_filename").map_or(dataset.width(), |idx| idx + 1);
+    dataset.insert_column(position, Series::new(META_PATH_COLUMN.into(), meta_paths))?;
+
+    let (annotation_fields, others): (Vec<String>, Vec<String>) = dataset
+        .get_column_names()
+        .iter()
+        .map(|name| name.to_string())
+        .partition(|name| name.starts_with(ANNOTATION_FIELD_PREFIX));
+    if annotation_fields.is_empty() {
+        return Ok(dataset);
+    }
+    Ok(dataset.select(others.into_iter().chain(annotation_fields))?)
+}
+
+/// The `on_progress` of scans that don't report progress: failures are
+/// logged as warnings
+fn print_failures(progress: ScanProgress) -> Result<()> {
+    for failure in progress.failures {
+        warn!("{}", failure);
+    }
+    Ok(())
+}
+
+/// `dir_paths` made absolute, refusing missing directories and roots
+/// inside one another, whose recordings would be read twice
+fn distinct_roots<P: AsRef<Path>>(dir_paths: &[P]) -> Result<Vec<PathBuf>> {
+    let mut roots: Vec<PathBuf> = Vec::new();
+    for dir_path in dir_paths {
+        let dir_path = dir_path.as_ref();
+        if !dir_path.is_dir() {
+            anyhow::bail!("Not a directory: {}", dir_path.display());
+        }
+        let root = std::fs::canonicalize(dir_path)?;
+        if let Some(other) = roots.iter().find(|other| root.starts_with(other) || other.starts_with(&root)) {
+            anyhow::bail!("{} and {} overlap; give each recording's directory once", other.display(), root.display());
+        }
+        roots.push(root);
+    }
+    Ok(roots)
+}
+
+/// Uniform random sample of a stream of unknown length (Algorithm R)
+struct Reservoir<T> {
+    items: Vec<T>,
+    capacity: usize,
+    seen: usize,
+    rng: StdRng,
+}
+
+impl<T> Reservoir<T> {
+    fn new(capacity: usize, seed: Option<u64>) -> Self {
+        let rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_entropy(),
+        };
+        Reservoir { items: Vec::with_capacity(capacity), capacity, seen: 0, rng }
+    }
+
+    fn offer(&mut self, item: T) {
+        self.seen += 1;
+        if self.items.len() < self.capacity {
+            self.items.push(item);
+        } else {
+            let slot = self.rng.gen_range(0..self.seen);
+            if slot < self.capacity {
+                self.items[slot] = item;
+            }
+        }
+    }
+}
+
+/// `path` made canonical where possible, for comparing paths spelled
+/// differently (collections refer to members by relative path). A removed
+/// file or virtual archive member keeps its name under its canonical
+/// directory, so it still compares equal to the path it had.
+pub(crate) fn normalize(path: &Path) -> PathBuf {
+    if let Ok(path) = path.canonicalize() {
+        return path;
+    }
+    match (path.parent().and_then(|parent| parent.canonicalize().ok()), path.file_name()) {
+        (Some(parent), Some(name)) => parent.join(name),
+        _ => path.to_path_buf(),
+    }
+}
+
+/// `meta_path` relative to `root` as stored in [`META_PATH_COLUMN`], or the
+/// whole path when it's outside `root`
+pub fn relative_meta_path(meta_path: &Path, root: &Path) -> String {
+    let relative = meta_path
+        .strip_prefix(root)
+        .ok()
+        .map(Path::to_path_buf)
+        .or_else(|| normalize(meta_path).strip_prefix(normalize(root)).ok().map(Path::to_path_buf));
+    relative.unwrap_or_else(|| meta_path.to_path_buf()).to_string_lossy().to_string()
+}
+
+/// `ann_<field>` columns for one recording's rows, from
+/// `SigMFParser::annotation_extra_fields`. A field that is an integer, a
+/// number or a boolean in every row where it appears keeps that type;
+/// anything mixed becomes text.
+fn add_annotation_field_columns(rows: &mut DataFrame, fields: &[Vec<(&str, &serde_json::Value)>]) -> Result<()> {
+    use serde_json::Value;
+
+    let mut names: Vec<&str> = Vec::new();
+    for (key, _) in fields.iter().flatten() {
+        if !names.contains(key) {
+            names.push(key);
+        }
+    }
+
+    // Keys that clean up to the same name (`a:b` and `a_b`) can't share a
+    // column. Clean keys keep theirs, then the first in order; the others
+    // go in a column named after the key as it is, which no cleaned name
+    // can match.
+    let mut ordered = names.clone();
+    ordered.sort_by_key(|key| (annotation_field_column(key) != format!("{}{}", ANNOTATION_FIELD_PREFIX, key), *key));
+    let mut columns: HashMap<&str, String> = HashMap::new();
+    let mut taken: HashMap<String, &str> = HashMap::new();
+    for key in ordered {
+        let mut column = annotation_field_column(key);
+        if let Some(other) = taken.get(&column) {
+            let verbatim = format!("{}{}", ANNOTATION_FIELD_PREFIX, key);
+            warn!("Annotation fields {} and {} would both be column {}; {} goes in {} instead", other, key, column, key, verbatim);
+            column = verbatim;
+        }
+        taken.insert(column.clone(), key);
+        columns.insert(key, column);
+    }
+
+    for key in names {
+        let values: Vec<Option<&Value>> = fields
+            .iter()
+            .map(|row| row.iter().find(|(k, _)| *k == key).map(|(_, value)| *value))
+            .collect();
+        let column = &columns[key];
+        let present = || values.iter().flatten();
+        let series = if present().all(|v| v.is_i64()) {
+            let values: Vec<Option<i64>> = values.iter().map(|v| v.and_then(Value::as_i64)).collect();
+            Series::new(column.as_str().into(), values)
+        } else if present().all(|v| v.is_number()) {
+            let values: Vec<Option<f64>> = values.iter().map(|v| v.and_then(Value::as_f64)).collect();
+            Series::new(column.as_str().into(), values)
+        } else if present().all(|v| v.is_boolean()) {
+            let values: Vec<Option<bool>> = values.iter().map(|v| v.and_then(Value::as_bool)).collect();
+            Series::new(column.as_str().into(), values)
+        } else {
+            let values: Vec<Option<String>> = values
+                .iter()
+                .map(|v| v.map(|v| v.as_str().map(str::to_string).unwrap_or_else(|| v.to_string())))
+                .collect();
+            Series::new(column.as_str().into(), values)
+        };
+        rows.with_column(series)?;
+    }
+    Ok(())
+}
+
+/// Column name for an annotation field, e.g. `acme:burst_id` → `ann_acme_burst_id`
+fn annotation_field_column(key: &str) -> String {
+    let key: String = key.chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    format!("{}{}", ANNOTATION_FIELD_PREFIX, key)
+}
@@ -1,13 +1,20 @@
 // Put your SigMFDataType enum and related logic here
+use crate::parser::ParserError;
 use anyhow::Result;
+use byteorder::{BigEndian, LittleEndian, ReadBytesExt};
 use num_complex::Complex;
-use std::io::{Read, Cursor};
+use std::io::Cursor;
 
 // SNW - small subset of the sigmf data types, because we only ever use these two anyway
-#[derive(Debug, Clone)]
+// (plus the 8-bit types cheap SDRs produce, so recordings can be converted)
+#[derive(Debug, Clone, PartialEq)]
 pub enum SigMFDataType {
     Cf32Le,
     Ci16Le,
+    /// Network byte order, as VITA-49 packets carry it
+    Ci16Be,
+    Ci8,
+    Cu8,
 }
 
 impl SigMFDataType {
@@ -15,18 +22,110 @@ impl SigMFDataType {
         match s {
             "cf32_le" => Ok(SigMFDataType::Cf32Le),
             "ci16_le" => Ok(SigMFDataType::Ci16Le),
-            _ => Err(anyhow::anyhow!("Unsupported datatype: {}", s)),
+            "ci16_be" => Ok(SigMFDataType::Ci16Be),
+            "ci8" => Ok(SigMFDataType::Ci8),
+            "cu8" => Ok(SigMFDataType::Cu8),
+            _ => Err(ParserError::UnsupportedDatatype(s.to_string()).into()),
         }
     }
     
+    /// The `core:datatype` string
+    pub fn name(&self) -> &'static str {
+        match self {
+            SigMFDataType::Cf32Le => "cf32_le",
+            SigMFDataType::Ci16Le => "ci16_le",
+            SigMFDataType::Ci16Be => "ci16_be",
+            SigMFDataType::Ci8 => "ci8",
+            SigMFDataType::Cu8 => "cu8",
+        }
+    }
+
     pub fn sample_size_bytes(&self) -> usize {
         match self {
             SigMFDataType::Cf32Le => 8, // 4 bytes for I + 4 bytes for Q
-            SigMFDataType::Ci16Le => 4, // 2 bytes for I + 2 bytes for Q
+            SigMFDataType::Ci16Le | SigMFDataType::Ci16Be => 4, // 2 bytes for I + 2 bytes for Q
+            SigMFDataType::Ci8 | SigMFDataType::Cu8 => 2,
         }
     }
     
     pub fn is_complex(&self) -> bool {
-        return true; // Both cf32_le and ci16_le are complex types
+        true // All supported types are complex
+    }
+
+    /// Decode raw sample bytes into complex floats.
+    /// Integer types are scaled to roughly [-1.0, 1.0).
+    pub fn decode_samples(&self, bytes: &[u8]) -> Result<Vec<Complex<f32>>> {
+        let num_samples = bytes.len() / self.sample_size_bytes();
+        let mut samples = Vec::with_capacity(num_samples);
+        let mut cursor = Cursor::new(bytes);
+        for _ in 0..num_samples {
+            let sample = match self {
+                SigMFDataType::Cf32Le => {
+                    let i = cursor.read_f32::<LittleEndian>()?;
+                    let q = cursor.read_f32::<LittleEndian>()?;
+                    Complex::new(i, q)
+                }
+                SigMFDataType::Ci16Le => {
+                    let i = cursor.read_i16::<LittleEndian>()?;
+                    let q = cursor.read_i16::<LittleEndian>()?;
+                    Complex::new(i as f32 / 32768.0, q as f32 / 32768.0)
+                }
+                SigMFDataType::Ci16Be => {
+                    let i = cursor.read_i16::<BigEndian>()?;
+                    let q = cursor.read_i16::<BigEndian>()?;
+                    Complex::new(i as f32 / 32768.0, q as f32 / 32768.0)
+                }
+                SigMFDataType::Ci8 => {
+                    let i = cursor.read_i8()?;
+                    let q = cursor.read_i8()?;
+                    Complex::new(i as f32 / 128.0, q as f32 / 128.0)
+                }
+                SigMFDataType::Cu8 => {
+                    let i = cursor.read_u8()?;
+                    let q = cursor.read_u8()?;
+                    Complex::new((i as f32 - 127.5) / 128.0, (q as f32 - 127.5) / 128.0)
+                }
+            };
+            samples.push(sample);
+        }
+        Ok(samples)
+    }
+
+    /// Encode complex floats as raw sample bytes, the inverse of
+    /// `decode_samples`. Integer types saturate; returns the bytes and the
+    /// number of samples that had to be clipped.
+    pub fn encode_samples(&self, samples: &[Complex<f32>]) -> (Vec<u8>, u64) {
+        let mut bytes = Vec::with_capacity(samples.len() * self.sample_size_bytes());
+        let mut clipped = 0;
+        for sample in samples {
+            let components = [sample.re, sample.im];
+            let (scale, offset, min, max) = match self {
+                SigMFDataType::Cf32Le => {
+                    for x in components {
+                        bytes.extend_from_slice(&x.to_le_bytes());
+                    }
+                    continue;
+                }
+                SigMFDataType::Ci16Le | SigMFDataType::Ci16Be => (32768.0, 0.0, i16::MIN as f32, i16::MAX as f32),
+                SigMFDataType::Ci8 => (128.0, 0.0, i8::MIN as f32, i8::MAX as f32),
+                SigMFDataType::Cu8 => (128.0, 127.5, 0.0, 255.0),
+            };
+            let mut sample_clipped = false;
+            for x in components {
+                let v = (x * scale + offset).round();
+                sample_clipped |= v < min || v > max;
+                let v = v.clamp(min, max);
+                match self {
+                    SigMFDataType::Ci16Le => bytes.extend_from_slice(&(v as i16).to_le_bytes()),
+                    SigMFDataType::Ci16Be => bytes.extend_from_slice(&(v as i16).to_be_bytes()),
+                    SigMFDataType::Ci8 => bytes.push(v as i8 as u8),
+                    _ => bytes.push(v as u8),
+                }
+            }
+            if sample_clipped {
+                clipped += 1;
+            }
+        }
+        (bytes, clipped)
     }
 }
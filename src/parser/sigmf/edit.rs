@@ -0,0 +1,226 @@
+//! Helpers for hand edits to metadata JSON: turning typed text into values
+//! of the right type, keeping annotations well-formed, and batch changes to
+//! global fields.
+
+use super::{update_meta, Journal, MetaSnapshot, SigMFParser};
+use anyhow::Result;
+use serde_json::{json, Map, Number, Value};
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FieldKind {
+    Unsigned,
+    Float,
+    Text,
+    Bool,
+}
+
+/// Types the SigMF spec fixes for core fields, so e.g. a label of "123"
+/// stays a string and a sample count must be a whole number
+fn core_field_kind(key: &str) -> Option<FieldKind> {
+    Some(match key {
+        "core:sample_start" | "core:sample_count" | "core:num_channels" | "core:offset" | "core:global_index"
+        | "core:header_bytes" | "core:trailing_bytes" => FieldKind::Unsigned,
+        "core:sample_rate" | "core:frequency" | "core:freq_lower_edge" | "core:freq_upper_edge" => FieldKind::Float,
+        "core:datatype" | "core:version" | "core:author" | "core:description" | "core:license" | "core:hw"
+        | "core:recorder" | "core:sha512" | "core:dataset" | "core:data_doi" | "core:meta_doi" | "core:datetime"
+        | "core:label" | "core:comment" | "core:generator" | "core:uuid" => FieldKind::Text,
+        "core:is_metadata_only" => FieldKind::Bool,
+        _ => return None,
+    })
+}
+
+/// Parse `text` typed for field `key` into a JSON value. Core fields get
+/// their spec type; other fields keep the type of `previous` (their value
+/// before the edit). New non-core fields are read as a JSON literal when
+/// they look like one (`42`, `true`, `[1, 2]`), otherwise as a string.
+pub fn parse_field_value(key: &str, text: &str, previous: Option<&Value>) -> Result<Value> {
+    let kind = core_field_kind(key).or(match previous {
+        Some(Value::String(_)) => Some(FieldKind::Text),
+        Some(Value::Bool(_)) => Some(FieldKind::Bool),
+        Some(Value::Number(n)) if n.is_f64() => Some(FieldKind::Float),
+        _ => None,
+    });
+    let trimmed = text.trim();
+    match kind {
+        Some(FieldKind::Text) => Ok(Value::String(text.to_string())),
+        Some(FieldKind::Unsigned) => trimmed
+            .parse::<u64>()
+            .map(Value::from)
+            .map_err(|_| anyhow::anyhow!("{} must be a whole number, got {:?}", key, text)),
+        Some(FieldKind::Float) => trimmed
+            .parse::<f64>()
+            .ok()
+            .and_then(Number::from_f64)
+            .map(Value::Number)
+            .ok_or_else(|| anyhow::anyhow!("{} must be a number, got {:?}", key, text)),
+        Some(FieldKind::Bool) => trimmed
+            .parse::<bool>()
+            .map(Value::Bool)
+            .map_err(|_| anyhow::anyhow!("{} must be true or false, got {:?}", key, text)),
+        None => match serde_json::from_str::<Value>(trimmed) {
+            Ok(value) => Ok(value),
+            // A bare word is a string, but a number or structured value that
+            // no longer parses is more likely a typo than meant to become text
+            Err(_) if matches!(previous, Some(Value::Number(_))) => {
+                anyhow::bail!("{} must be a number, got {:?}", key, text)
+            }
+            Err(_) if matches!(previous, Some(Value::Array(_) | Value::Object(_))) => {
+                anyhow::bail!("{} must be valid JSON, got {:?}", key, text)
+            }
+            Err(_) => Ok(Value::String(text.to_string())),
+        },
+    }
+}
+
+/// A value as it's shown for editing: strings without quotes, everything
+/// else as compact JSON
+pub fn field_text(value: &Value) -> String {
+    match value {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Check an annotation has the fields SigMF requires and sane frequency edges
+pub fn check_annotation(annotation: &Map<String, Value>) -> Result<()> {
+    if annotation.get("core:sample_start").and_then(Value::as_u64).is_none() {
+        anyhow::bail!("core:sample_start is required");
+    }
+    let edge = |key: &str| annotation.get(key).and_then(Value::as_f64);
+    if let (Some(lower), Some(upper)) = (edge("core:freq_lower_edge"), edge("core:freq_upper_edge")) {
+        if lower > upper {
+            anyhow::bail!("core:freq_lower_edge ({}) is above core:freq_upper_edge ({})", lower, upper);
+        }
+    }
+    Ok(())
+}
+
+/// Put annotations back in `core:sample_start` order, as the spec requires.
+/// The sort is stable, so annotations starting together keep their order.
+pub fn sort_annotations(meta: &mut Value) {
+    if let Some(annotations) = meta.get_mut("annotations").and_then(Value::as_array_mut) {
+        annotations.sort_by_key(|a| a.get("core:sample_start").and_then(Value::as_u64).unwrap_or(0));
+    }
+}
+
+/// Append `annotation` to a meta file, journaled, keeping the annotations in
+/// `core:sample_start` order and every other field as it was. Besides
+/// [`check_annotation`], frequency edges must come in pairs and, if the
+/// recording can be read, the annotation must end within it. Returns the
+/// position the annotation landed at.
+pub fn add_annotation<P: AsRef<Path>>(meta_path: P, annotation: Map<String, Value>, journal: &Journal) -> Result<usize> {
+    let meta_path = meta_path.as_ref();
+    check_annotation(&annotation)?;
+    if annotation.contains_key("core:freq_lower_edge") != annotation.contains_key("core:freq_upper_edge") {
+        anyhow::bail!("core:freq_lower_edge and core:freq_upper_edge must be given together");
+    }
+    if let Ok(parser) = SigMFParser::from_meta_file(meta_path) {
+        let field = |key: &str| annotation.get(key).and_then(Value::as_u64).unwrap_or(0);
+        let end = field("core:sample_start") + field("core:sample_count");
+        let num_samples = parser.num_samples();
+        if end > num_samples {
+            anyhow::bail!("Annotation ends at sample {}, past the end of the recording ({} samples)", end, num_samples);
+        }
+    }
+    let annotation = Value::Object(annotation);
+    let mut position = 0;
+    update_meta(meta_path, journal, |metadata| {
+        let annotations = metadata
+            .as_object_mut()
+            .ok_or_else(|| anyhow::anyhow!("Metadata of {:?} is not a JSON object", meta_path))?
+            .entry("annotations")
+            .or_insert_with(|| json!([]))
+            .as_array_mut()
+            .ok_or_else(|| anyhow::anyhow!("annotations of {:?} is not a list", meta_path))?;
+        annotations.push(annotation.clone());
+        sort_annotations(metadata);
+        let annotations = metadata["annotations"].as_array().expect("just sorted");
+        position = annotations.iter().rposition(|a| *a == annotation).expect("just added");
+        Ok(())
+    })?;
+    Ok(position)
+}
+
+/// Global fields tied to the data file, changed only by `convert` and
+/// `checksum`
+pub const PROTECTED_GLOBAL_FIELDS: [&str; 2] = ["core:datatype", "core:sha512"];
+
+/// One change to a global field
+#[derive(Debug, Clone, PartialEq)]
+pub enum GlobalEdit {
+    Set { key: String, value: String },
+    Unset(String),
+}
+
+impl GlobalEdit {
+    /// Parse `key=value`. Keys without a namespace are taken as `core:` ones.
+    pub fn parse_set(text: &str) -> Result<Self> {
+        let (key, value) = text
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("Expected key=value, got {:?}", text))?;
+        Ok(GlobalEdit::Set {
+            key: qualify_key(key.trim()),
+            value: value.to_string(),
+        })
+    }
+
+    pub fn key(&self) -> &str {
+        match self {
+            GlobalEdit::Set { key, .. } | GlobalEdit::Unset(key) => key,
+        }
+    }
+}
+
+/// `author` → `core:author`; namespaced keys are left alone
+pub fn qualify_key(key: &str) -> String {
+    if key.contains(':') {
+        key.to_string()
+    } else {
+        format!("core:{}", key)
+    }
+}
+
+/// Apply `edits` to the `global` object of `meta`, typing values as
+/// [`parse_field_value`] does. Returns whether anything changed.
+pub fn apply_global_edits(meta: &mut Value, edits: &[GlobalEdit]) -> Result<bool> {
+    let global = meta
+        .get_mut("global")
+        .and_then(Value::as_object_mut)
+        .ok_or_else(|| anyhow::anyhow!("Missing global object"))?;
+    let mut changed = false;
+    for edit in edits {
+        if PROTECTED_GLOBAL_FIELDS.contains(&edit.key()) {
+            anyhow::bail!("{} can't be edited directly; use convert or checksum", edit.key());
+        }
+        match edit {
+            GlobalEdit::Set { key, value } => {
+                let value = parse_field_value(key, value, global.get(key))?;
+                if global.get(key) != Some(&value) {
+                    global.insert(key.clone(), value);
+                    changed = true;
+                }
+            }
+            GlobalEdit::Unset(key) => {
+                // shift_remove keeps the other keys in file order
+                changed |= global.shift_remove(key).is_some();
+            }
+        }
+    }
+    Ok(changed)
+}
+
+/// Apply `edits` to one meta file, journaled. Files the edits wouldn't change
+/// aren't rewritten. Returns whether the file changed (or would have, with
+/// `dry_run`).
+pub fn set_global_fields<P: AsRef<Path>>(meta_path: P, edits: &[GlobalEdit], journal: &Journal, dry_run: bool) -> Result<bool> {
+    let meta_path = meta_path.as_ref();
+    let mut preview = MetaSnapshot::read(meta_path)?.value;
+    let changes = apply_global_edits(&mut preview, edits)?;
+    if !changes || dry_run {
+        return Ok(changes);
+    }
+    // Re-applied to whatever is on disk under the write lock
+    update_meta(meta_path, journal, |meta| apply_global_edits(meta, edits).map(|_| ()))?;
+    Ok(true)
+}
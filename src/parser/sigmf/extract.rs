@@ -0,0 +1,203 @@
+use super::{read_meta_text, Compression, SigMFParser};
+use crate::data_ops::activity::parse_capture_datetime;
+use anyhow::Result;
+use serde_json::{json, Value};
+use sha2::{Digest, Sha512};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+/// Which part of a recording to extract
+#[derive(Debug, Clone)]
+pub enum SliceRange {
+    /// The extent of the Nth annotation (0-based, in file order)
+    Annotation(usize),
+    /// `count` of None runs to the end of the recording
+    Samples { start: u64, count: Option<u64> },
+    /// Seconds from the start of the recording
+    Time { start: f64, duration: Option<f64> },
+}
+
+#[derive(Debug, Clone)]
+pub struct ExtractReport {
+    pub meta_path: PathBuf,
+    pub data_path: PathBuf,
+    pub start_sample: u64,
+    pub num_samples: u64,
+}
+
+/// Write a new SigMF pair into `out_dir` holding only `range` of the
+/// recording. Captures and annotations are clipped to the slice and their
+/// sample indices (and capture datetimes) shifted to match.
+pub fn extract_slice<P: AsRef<Path>, Q: AsRef<Path>>(meta_path: P, range: &SliceRange, out_dir: Q) -> Result<ExtractReport> {
+    let meta_path = meta_path.as_ref();
+    let parser = SigMFParser::from_meta_file(meta_path)?;
+    let sample_size = parser.data_type.sample_size_bytes() as u64;
+    let total = parser.data_location.size / sample_size;
+    let mut metadata: Value = serde_json::from_str(&read_meta_text(meta_path)?)?;
+
+    let (start, count) = resolve_range(&metadata, range, parser.sample_rate(), total)?;
+    let end = start + count;
+
+    let stem = meta_path.file_stem().unwrap().to_string_lossy();
+    let out_dir = out_dir.as_ref();
+    std::fs::create_dir_all(out_dir)?;
+    let out_meta = out_dir.join(format!("{}_s{}_n{}.sigmf-meta", stem, start, count));
+    let out_data = out_meta.with_extension("sigmf-data");
+
+    // Raw bytes are copied as-is, no need to decode
+    let source: Box<dyn Read> = if Compression::from_path(&parser.data_location.file).is_some() {
+        // Compressed data can't be seeked; read through to the slice instead
+        let mut reader = parser.data_location.open()?;
+        std::io::copy(&mut (&mut reader).take(start * sample_size), &mut std::io::sink())?;
+        reader
+    } else {
+        let mut file = std::fs::File::open(&parser.data_location.file)?;
+        file.seek(SeekFrom::Start(parser.data_location.offset + start * sample_size))?;
+        Box::new(file)
+    };
+    let mut source = source.take(count * sample_size);
+    let mut writer = BufWriter::new(std::fs::File::create(&out_data)?);
+    let mut hasher = Sha512::new();
+    let mut buffer = vec![0u8; 1 << 20];
+    loop {
+        let n = source.read(&mut buffer)?;
+        if n == 0 {
+            break;
+        }
+        writer.write_all(&buffer[..n])?;
+        hasher.update(&buffer[..n]);
+    }
+    writer.flush()?;
+
+    if let Some(global) = metadata.get_mut("global").and_then(Value::as_object_mut) {
+        if global.contains_key("core:sha512") {
+            let digest: String = hasher.finalize().iter().map(|b| format!("{:02x}", b)).collect();
+            global.insert("core:sha512".to_string(), Value::String(digest));
+        }
+    }
+    slice_captures(&mut metadata, start, end, total, parser.sample_rate());
+    slice_annotations(&mut metadata, start, end);
+    std::fs::write(&out_meta, serde_json::to_string_pretty(&metadata)? + "\n")?;
+
+    Ok(ExtractReport {
+        meta_path: out_meta,
+        data_path: out_data,
+        start_sample: start,
+        num_samples: count,
+    })
+}
+
+/// Copy a recording's meta and data files into `out_dir` unchanged, keeping
+/// its name. Recordings inside a `.sigmf` archive come out as a plain pair.
+/// Existing files are never overwritten.
+pub fn copy_recording<P: AsRef<Path>, Q: AsRef<Path>>(meta_path: P, out_dir: Q) -> Result<PathBuf> {
+    let meta_path = meta_path.as_ref();
+    let parser = SigMFParser::from_meta_file(meta_path)?;
+    let meta_text = read_meta_text(meta_path)?;
+
+    let out_dir = out_dir.as_ref();
+    std::fs::create_dir_all(out_dir)?;
+    let file_name = meta_path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("{:?} has no file name", meta_path))?;
+    let out_meta = out_dir.join(file_name);
+    let out_data = out_meta.with_extension("sigmf-data");
+    for path in [&out_meta, &out_data] {
+        if path.exists() {
+            anyhow::bail!("{:?} already exists", path);
+        }
+    }
+
+    let mut writer = BufWriter::new(std::fs::File::create(&out_data)?);
+    std::io::copy(&mut parser.data_location.open()?, &mut writer)?;
+    writer.flush()?;
+    std::fs::write(&out_meta, meta_text)?;
+    Ok(out_meta)
+}
+
+fn resolve_range(metadata: &Value, range: &SliceRange, sample_rate: f64, total: u64) -> Result<(u64, u64)> {
+    let (start, count) = match *range {
+        SliceRange::Annotation(index) => {
+            let annotation = metadata
+                .get("annotations")
+                .and_then(Value::as_array)
+                .and_then(|annotations| annotations.get(index))
+                .ok_or_else(|| anyhow::anyhow!("Recording has no annotation {}", index))?;
+            let start = annotation.get("core:sample_start").and_then(Value::as_u64).unwrap_or(0);
+            let count = annotation.get("core:sample_count").and_then(Value::as_u64);
+            (start, count)
+        }
+        SliceRange::Samples { start, count } => (start, count),
+        SliceRange::Time { start, duration } => {
+            if start < 0.0 || duration.is_some_and(|d| d <= 0.0) {
+                anyhow::bail!("Start time and duration must be positive");
+            }
+            let to_samples = |seconds: f64| (seconds * sample_rate).round() as u64;
+            (to_samples(start), duration.map(to_samples))
+        }
+    };
+    if start >= total {
+        anyhow::bail!("Slice starts at sample {} but the recording has {} samples", start, total);
+    }
+    let count = count.unwrap_or(total - start).min(total - start);
+    if count == 0 {
+        anyhow::bail!("Slice is empty");
+    }
+    Ok((start, count))
+}
+
+fn sample_start(value: &Value) -> u64 {
+    value.get("core:sample_start").and_then(Value::as_u64).unwrap_or(0)
+}
+
+// A capture segment runs until the next one starts
+fn slice_captures(metadata: &mut Value, start: u64, end: u64, total: u64, sample_rate: f64) {
+    let Some(captures) = metadata.get_mut("captures").and_then(Value::as_array_mut) else {
+        return;
+    };
+    let starts: Vec<u64> = captures.iter().map(sample_start).collect();
+    let mut kept = Vec::new();
+    for (i, mut capture) in captures.drain(..).enumerate() {
+        let segment_end = starts.get(i + 1).copied().unwrap_or(total);
+        if segment_end <= start || starts[i] >= end {
+            continue;
+        }
+        let skipped = start.saturating_sub(starts[i]);
+        if let Some(capture) = capture.as_object_mut() {
+            capture.insert("core:sample_start".to_string(), json!(starts[i].max(start) - start));
+            let datetime = capture.get("core:datetime").and_then(Value::as_str).and_then(parse_capture_datetime);
+            if let (Some(datetime), true) = (datetime, skipped > 0 && sample_rate > 0.0) {
+                let offset = chrono::Duration::nanoseconds((skipped as f64 / sample_rate * 1e9) as i64);
+                let shifted = (datetime + offset).format("%Y-%m-%dT%H:%M:%S%.fZ").to_string();
+                capture.insert("core:datetime".to_string(), Value::String(shifted));
+            }
+        }
+        kept.push(capture);
+    }
+    *captures = kept;
+}
+
+fn slice_annotations(metadata: &mut Value, start: u64, end: u64) {
+    let Some(annotations) = metadata.get_mut("annotations").and_then(Value::as_array_mut) else {
+        return;
+    };
+    annotations.retain_mut(|annotation| {
+        let a_start = sample_start(annotation);
+        let a_end = annotation
+            .get("core:sample_count")
+            .and_then(Value::as_u64)
+            .map_or(end, |count| a_start + count);
+        if a_end <= start || a_start >= end {
+            return false;
+        }
+        let new_start = a_start.max(start) - start;
+        let new_end = a_end.min(end) - start;
+        if let Some(annotation) = annotation.as_object_mut() {
+            annotation.insert("core:sample_start".to_string(), json!(new_start));
+            if annotation.contains_key("core:sample_count") {
+                annotation.insert("core:sample_count".to_string(), json!(new_end - new_start));
+            }
+        }
+        true
+    });
+}
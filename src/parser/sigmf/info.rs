@@ -0,0 +1,143 @@
+use super::{AnnotationInfo, Compression, SigMFParser};
+use crate::data_ops::{format_bytes, format_frequency};
+use std::fmt::Write as _;
+
+/// Classifier probabilities an annotation carries, by short name
+fn probabilities(ann: &AnnotationInfo) -> Vec<(String, f64)> {
+    let fixed = [
+        ("ask", ann.ask_prob),
+        ("psk", ann.psk_prob),
+        ("fsk", ann.fsk_prob),
+        ("am", ann.analog_am_prob),
+        ("fm", ann.analog_fm_prob),
+        ("ook", ann.ook_prob),
+        ("chirp", ann.chirp_prob),
+        ("constellation", ann.constellation_prob),
+        ("css", ann.css_prob),
+    ];
+    let custom = ann.custom_classifier_probs.iter().flatten().map(|field| (field.class_name.clone(), field.class_prob as f64));
+    fixed.into_iter().filter_map(|(name, prob)| Some((name.to_string(), prob?))).chain(custom).collect()
+}
+
+impl SigMFParser {
+    /// A multi-line, human-readable description of the recording: global
+    /// metadata, file sizes and duration, then each capture and annotation.
+    /// What `info` prints.
+    pub fn info_report(&self) -> String {
+        let global = &self.metadata.global;
+        let rate = global.sample_rate;
+        let num_samples = self.num_samples();
+        let seconds = |samples: u64| if rate > 0.0 { samples as f64 / rate } else { 0.0 };
+        let mut out = String::new();
+        let mut field = |name: &str, value: String| {
+            let _ = writeln!(out, "{:<16}{}", format!("{}:", name), value);
+        };
+
+        field("Meta file", self.meta_path.display().to_string());
+        let data = match std::fs::metadata(&self.data_location.file) {
+            Ok(meta) if Compression::from_path(&self.data_location.file).is_some() => format!(
+                "{} ({}, {} compressed)",
+                self.data_file_path.display(),
+                format_bytes(self.data_location.size),
+                format_bytes(meta.len())
+            ),
+            Ok(_) => format!("{} ({})", self.data_file_path.display(), format_bytes(self.data_location.size)),
+            Err(_) => format!("{} (missing)", self.data_file_path.display()),
+        };
+        field("Data file", data);
+        if let Ok(meta) = std::fs::metadata(&self.meta_path) {
+            field("Meta size", format_bytes(meta.len()));
+        }
+        field("SigMF version", global.version.clone());
+        field("Datatype", format!("{} ({} bytes/sample)", global.datatype, self.data_type.sample_size_bytes()));
+        field("Sample rate", format_frequency(rate).replace("Hz", "S/s"));
+        field("Samples", num_samples.to_string());
+        field("Duration", format!("{:.6} s", seconds(num_samples)));
+        let optional = [
+            ("Description", &global.description),
+            ("Author", &global.author),
+            ("License", &global.license),
+            ("Hardware", &global.hardware),
+            ("SHA-512", &global.sha512),
+            ("Dataset", &global.dataset),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                field(name, value.clone());
+            }
+        }
+        if let Some(geolocation) = &global.geolocation {
+            let coordinates: Vec<String> = geolocation.coordinates.iter().map(|c| c.to_string()).collect();
+            field("Geolocation", format!("{} [{}]", geolocation.geo_type, coordinates.join(", ")));
+        }
+        for warning in &self.parse_warnings {
+            field("Repaired", warning.clone());
+        }
+
+        let captures = &self.metadata.captures;
+        let _ = writeln!(out, "\nCaptures ({}):", captures.len());
+        for (idx, (capture, (start, end))) in captures.iter().zip(self.capture_bounds()).enumerate() {
+            let mut parts = vec![format!("{:.6}–{:.6} s (samples {}..{})", seconds(start), seconds(end), start, end)];
+            parts.extend(capture.frequency.map(format_frequency));
+            parts.extend(capture.timestamp.clone());
+            parts.extend(capture.gain.map(|gain| format!("gain {} dB", gain)));
+            parts.extend(capture.agc.map(|agc| format!("AGC {}", if agc { "on" } else { "off" })));
+            parts.extend(capture.sequence_num.map(|seq| format!("seq {}", seq)));
+            let _ = writeln!(out, "  #{:<3} {}", idx, parts.join(", "));
+            write_extra_fields(&mut out, capture.extra_fields.iter());
+        }
+
+        let annotations = self.metadata.annotations.as_deref().unwrap_or_default();
+        let _ = writeln!(out, "\nAnnotations ({}):", annotations.len());
+        for (idx, ann) in annotations.iter().enumerate() {
+            let end = ann.sample_start + ann.sample_count;
+            let mut parts = vec![format!(
+                "{:.6}–{:.6} s (samples {}..{})",
+                seconds(ann.sample_start),
+                seconds(end),
+                ann.sample_start,
+                end
+            )];
+            match (ann.freq_lower_edge, ann.freq_upper_edge) {
+                (Some(lower), Some(upper)) => parts.push(format!("{} – {}", format_frequency(lower), format_frequency(upper))),
+                (Some(lower), None) => parts.push(format!("from {}", format_frequency(lower))),
+                (None, Some(upper)) => parts.push(format!("up to {}", format_frequency(upper))),
+                (None, None) => {}
+            }
+            parts.extend(ann.label.as_ref().map(|label| format!("label {:?}", label)));
+            let _ = writeln!(out, "  #{:<3} {}", idx, parts.join(", "));
+
+            let mut signal = Vec::new();
+            signal.extend(ann.sig_center_freq.map(|hz| format!("center {}", format_frequency(hz))));
+            signal.extend(ann.sig_bandwidth.map(|hz| format!("bandwidth {}", format_frequency(hz))));
+            signal.extend(ann.sig_snr.map(|db| format!("SNR {:.1} dB", db)));
+            signal.extend(ann.sig_power_dbm.map(|db| format!("{:.1} dBm", db)));
+            signal.extend(ann.sig_power_dbfs.map(|db| format!("{:.1} dBFS", db)));
+            signal.extend(ann.sdr_handle.as_ref().map(|sdr| format!("sdr {}", sdr)));
+            signal.extend(ann.uuid.as_ref().map(|uuid| format!("uuid {}", uuid)));
+            if ann.ml_no_sig == Some(true) {
+                signal.push("no signal".to_string());
+            }
+            if !signal.is_empty() {
+                let _ = writeln!(out, "       {}", signal.join(", "));
+            }
+            let mut probs = probabilities(ann);
+            probs.sort_by(|a, b| b.1.total_cmp(&a.1));
+            if !probs.is_empty() {
+                let probs: Vec<String> = probs.iter().map(|(name, prob)| format!("{} {:.2}", name, prob)).collect();
+                let _ = writeln!(out, "       probabilities: {}", probs.join(", "));
+            }
+            write_extra_fields(&mut out, ann.extra_fields.iter());
+        }
+        out
+    }
+}
+
+/// Fields sig_viewer doesn't model, one `key: value` line each, in key order
+fn write_extra_fields<'a>(out: &mut String, fields: impl Iterator<Item = (&'a String, &'a serde_json::Value)>) {
+    let mut fields: Vec<_> = fields.collect();
+    fields.sort_by_key(|(key, _)| key.as_str());
+    for (key, value) in fields {
+        let _ = writeln!(out, "       {}: {}", key, value);
+    }
+}
@@ -0,0 +1,247 @@
+//! Append-only journal of metadata mutations.
+//!
+//! Every write goes through here with enough information to undo it: the
+//! full previous meta file content for edits, the source path for moves.
+//! Operations from one command (or one GUI save) share a batch id, and
+//! [`rollback`] reverts a whole batch, newest operation first.
+
+use super::writeback::restore_meta;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::fs::OpenOptions;
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Refuse every journaled change (metadata writes, moves, rollbacks) for the
+/// rest of the process. Since all mutations are recorded before they happen,
+/// this is enforced in one place.
+pub fn set_read_only(read_only: bool) {
+    READ_ONLY.store(read_only, Ordering::Relaxed);
+}
+
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum Operation {
+    /// A meta file was rewritten. `before` is its previous raw content and
+    /// `after_digest` identifies the written content, so a rollback can tell
+    /// whether the file has been changed again since.
+    WriteMeta {
+        path: PathBuf,
+        before: String,
+        after_digest: String,
+    },
+    /// A file was moved or renamed
+    Move { from: PathBuf, to: PathBuf },
+}
+
+impl Operation {
+    /// The file being changed
+    pub fn target(&self) -> &Path {
+        match self {
+            Operation::WriteMeta { path, .. } => path,
+            Operation::Move { from, .. } => from,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct JournalEntry {
+    pub batch: String,
+    pub timestamp: String,
+    pub description: String,
+    /// Set on entries written by a rollback, naming the batch it reverted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverts: Option<String>,
+    #[serde(flatten)]
+    pub operation: Operation,
+}
+
+/// Summary of one batch for listing
+#[derive(Debug, Clone)]
+pub struct BatchSummary {
+    pub batch: String,
+    pub timestamp: String,
+    pub description: String,
+    pub operations: usize,
+    pub reverts: Option<String>,
+    pub rolled_back: bool,
+}
+
+/// Handle for recording one batch of operations
+#[derive(Debug, Clone)]
+pub struct Journal {
+    path: PathBuf,
+    batch: String,
+    description: String,
+    reverts: Option<String>,
+}
+
+impl Journal {
+    /// `~/.local/share/sig_viewer/journal.jsonl` (or the platform equivalent)
+    pub fn default_path() -> PathBuf {
+        dirs::data_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("sig_viewer")
+            .join("journal.jsonl")
+    }
+
+    /// Start a new batch in the default journal
+    pub fn begin(description: &str) -> Self {
+        Self::begin_at(Self::default_path(), description)
+    }
+
+    pub fn begin_at<P: AsRef<Path>>(path: P, description: &str) -> Self {
+        let now = chrono::Utc::now();
+        Journal {
+            path: path.as_ref().to_path_buf(),
+            batch: format!("{}-{}", now.format("%Y%m%dT%H%M%S%.3f"), std::process::id()),
+            description: description.to_string(),
+            reverts: None,
+        }
+    }
+
+    pub fn batch(&self) -> &str {
+        &self.batch
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an operation. Called before the change is made, so a crash
+    /// mid-write leaves an entry whose rollback is detected as stale rather
+    /// than an unrecorded change.
+    pub fn record(&self, operation: Operation) -> Result<()> {
+        if is_read_only() {
+            anyhow::bail!("Read-only mode: refusing to modify {:?}", operation.target());
+        }
+        let entry = JournalEntry {
+            batch: self.batch.clone(),
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            description: self.description.clone(),
+            reverts: self.reverts.clone(),
+            operation,
+        };
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path)?;
+        file.lock()?;
+        writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+        file.unlock()?;
+        Ok(())
+    }
+
+    /// Move a file, recording the move
+    pub fn rename<P: AsRef<Path>, Q: AsRef<Path>>(&self, from: P, to: Q) -> Result<()> {
+        let (from, to) = (from.as_ref(), to.as_ref());
+        if to.exists() {
+            anyhow::bail!("Destination already exists: {:?}", to);
+        }
+        self.record(Operation::Move {
+            from: from.to_path_buf(),
+            to: to.to_path_buf(),
+        })?;
+        if let Some(dir) = to.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        std::fs::rename(from, to)?;
+        Ok(())
+    }
+}
+
+pub fn read_entries<P: AsRef<Path>>(path: P) -> Result<Vec<JournalEntry>> {
+    let path = path.as_ref();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+    let reader = BufReader::new(std::fs::File::open(path)?);
+    let mut entries = Vec::new();
+    for (line_number, line) in reader.lines().enumerate() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(&line)
+            .map_err(|e| anyhow::anyhow!("Corrupt journal entry at {:?} line {}: {}", path, line_number + 1, e))?;
+        entries.push(entry);
+    }
+    Ok(entries)
+}
+
+/// Batches in the order they were started
+pub fn list_batches(entries: &[JournalEntry]) -> Vec<BatchSummary> {
+    let mut batches: Vec<BatchSummary> = Vec::new();
+    for entry in entries {
+        match batches.iter_mut().find(|b| b.batch == entry.batch) {
+            Some(batch) => batch.operations += 1,
+            None => batches.push(BatchSummary {
+                batch: entry.batch.clone(),
+                timestamp: entry.timestamp.clone(),
+                description: entry.description.clone(),
+                operations: 1,
+                reverts: entry.reverts.clone(),
+                rolled_back: false,
+            }),
+        }
+    }
+    let reverted: Vec<String> = batches.iter().filter_map(|b| b.reverts.clone()).collect();
+    for batch in &mut batches {
+        batch.rolled_back = reverted.contains(&batch.batch);
+    }
+    batches
+}
+
+#[derive(Debug, Default)]
+pub struct RollbackReport {
+    pub reverted: usize,
+    /// Operations skipped because the file changed again after the batch
+    pub skipped: Vec<String>,
+}
+
+/// Undo every operation of `batch`, newest first. Files changed again since
+/// are skipped (and reported) unless `force` is set. The rollback itself is
+/// journaled as a new batch.
+pub fn rollback<P: AsRef<Path>>(journal_path: P, batch: &str, force: bool) -> Result<RollbackReport> {
+    let journal_path = journal_path.as_ref();
+    let entries = read_entries(journal_path)?;
+    let operations: Vec<&JournalEntry> = entries.iter().filter(|e| e.batch == batch).collect();
+    if operations.is_empty() {
+        anyhow::bail!("No journal entries for batch {}", batch);
+    }
+
+    let mut journal = Journal::begin_at(journal_path, &format!("rollback of {}", batch));
+    journal.reverts = Some(batch.to_string());
+
+    let mut report = RollbackReport::default();
+    for entry in operations.into_iter().rev() {
+        match &entry.operation {
+            Operation::WriteMeta { path, before, after_digest } => {
+                let expected = if force { None } else { Some(after_digest.as_str()) };
+                if restore_meta(path, expected, before, &journal)? {
+                    report.reverted += 1;
+                } else {
+                    report.skipped.push(format!("{} changed since the edit", path.display()));
+                }
+            }
+            Operation::Move { from, to } => {
+                if !to.exists() {
+                    report.skipped.push(format!("{} no longer exists", to.display()));
+                } else if from.exists() {
+                    report.skipped.push(format!("{} already exists", from.display()));
+                } else {
+                    journal.rename(to, from)?;
+                    report.reverted += 1;
+                }
+            }
+        }
+    }
+    Ok(report)
+}
@@ -0,0 +1,83 @@
+//! Writing a summary row's label back to its recording as `core:label`.
+//!
+//! A row of an ML annotation is labeled on that annotation, found by its
+//! `ds:uuid` or, without one, by its signal center frequency and bandwidth.
+//! A recording without ML annotations is labeled on an annotation covering
+//! the whole recording (see
+//! [`SigMFParser::recording_annotation_index`]), added if there isn't one.
+
+use super::edit::sort_annotations;
+use super::journal::Journal;
+use super::parser::SigMFParser;
+use super::writeback::update_meta;
+use anyhow::Result;
+use serde_json::{json, Value};
+use std::path::Path;
+
+/// What identifies the annotation behind a summary row, from the row's
+/// `sig_uuid`, `sig_center_freq_hz` and `sig_bandwidth_hz`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RowAnnotation {
+    pub sig_uuid: String,
+    pub sig_center_freq_hz: f64,
+    pub sig_bandwidth_hz: f64,
+}
+
+impl RowAnnotation {
+    /// Position of the ML annotation this row stands for, if the recording
+    /// has one that matches. Several matching (no uuids, same signal) gives
+    /// the first.
+    pub fn find(&self, parser: &SigMFParser) -> Option<usize> {
+        let annotations = parser.metadata.annotations.as_deref()?;
+        parser.ml_annotation_indices().into_iter().find(|&idx| {
+            let ann = &annotations[idx];
+            if self.sig_uuid.is_empty() {
+                ann.sig_center_freq.unwrap_or(0.0) == self.sig_center_freq_hz && ann.sig_bandwidth.unwrap_or(0.0) == self.sig_bandwidth_hz
+            } else {
+                ann.uuid.as_deref() == Some(self.sig_uuid.as_str())
+            }
+        })
+    }
+}
+
+/// Set (or with `None`, remove) the `core:label` of the annotation behind a
+/// row of `meta_path`, journaled
+pub fn write_row_label(meta_path: &Path, row: &RowAnnotation, label: Option<&str>, journal: &Journal) -> Result<()> {
+    let parser = SigMFParser::from_meta_file(meta_path)?;
+    let target = if parser.ml_annotation_indices().is_empty() {
+        parser.recording_annotation_index()
+    } else {
+        Some(row.find(&parser).ok_or_else(|| anyhow::anyhow!("No annotation of {:?} matches the row", meta_path))?)
+    };
+    if target.is_none() && label.is_none() {
+        return Ok(());
+    }
+    let num_samples = parser.num_samples();
+    update_meta(meta_path, journal, |metadata| {
+        let object = metadata
+            .as_object_mut()
+            .ok_or_else(|| anyhow::anyhow!("Metadata of {:?} is not a JSON object", meta_path))?;
+        let annotations = object
+            .entry("annotations")
+            .or_insert_with(|| json!([]))
+            .as_array_mut()
+            .ok_or_else(|| anyhow::anyhow!("annotations of {:?} is not a list", meta_path))?;
+        let annotation = match target {
+            Some(idx) => annotations
+                .get_mut(idx)
+                .and_then(Value::as_object_mut)
+                .ok_or_else(|| anyhow::anyhow!("Annotation {} of {:?} changed while labeling", idx, meta_path))?,
+            None => {
+                annotations.push(json!({ "core:sample_start": 0, "core:sample_count": num_samples }));
+                annotations.last_mut().and_then(Value::as_object_mut).expect("just added")
+            }
+        };
+        match label {
+            Some(label) => annotation.insert("core:label".to_string(), json!(label)),
+            None => annotation.remove("core:label"),
+        };
+        sort_annotations(metadata);
+        Ok(())
+    })?;
+    Ok(())
+}
@@ -0,0 +1,340 @@
+//! Strict and lenient reading of `.sigmf-meta` files.
+//!
+//! Real-world meta files often bend the spec: no `core:version`, a sample
+//! rate written as `"2e6"`, geolocation that isn't GeoJSON. Strict mode (the
+//! default) refuses such a file, as serde does. Lenient mode repairs what it
+//! can before deserializing and returns a warning per repair, which datasets
+//! keep in their `parse_warnings` column. Fields that can't be repaired are
+//! dropped when optional; a file still fails if a required one (the datatype,
+//! the sample rate, an annotation's extent) is missing or unreadable.
+//!
+//! Like read-only mode, the mode applies to the whole process.
+
+use super::SigMFMetadata;
+use anyhow::Result;
+use serde_json::{Map, Number, Value};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static LENIENT: AtomicBool = AtomicBool::new(false);
+
+/// `core:version` given to files without one
+pub const ASSUMED_SIGMF_VERSION: &str = "1.0.0";
+
+/// How meta files that don't follow the spec are treated
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ParseMode {
+    /// Refuse the file, as before lenient mode existed
+    #[default]
+    Strict,
+    /// Coerce or default recoverable fields, recording a warning for each
+    Lenient,
+}
+
+pub fn set_parse_mode(mode: ParseMode) {
+    LENIENT.store(mode == ParseMode::Lenient, Ordering::Relaxed);
+}
+
+pub fn parse_mode() -> ParseMode {
+    if LENIENT.load(Ordering::Relaxed) {
+        ParseMode::Lenient
+    } else {
+        ParseMode::Strict
+    }
+}
+
+/// Deserialize a meta file's text in the current [`ParseMode`], with the
+/// repairs made (always none in strict mode). Text that isn't JSON fails in
+/// either mode.
+pub fn parse_metadata(text: &str) -> Result<(SigMFMetadata, Vec<String>)> {
+    if parse_mode() == ParseMode::Strict {
+        return Ok((serde_json::from_str(text)?, Vec::new()));
+    }
+    let mut value: Value = serde_json::from_str(text)?;
+    let mut warnings = Vec::new();
+    if let Some(root) = value.as_object_mut() {
+        repair_root(root, &mut warnings);
+    }
+    Ok((serde_json::from_value(value)?, warnings))
+}
+
+/// What a field has to deserialize as
+#[derive(Clone, Copy)]
+enum Want {
+    Float,
+    Unsigned,
+    Bool,
+    Text,
+}
+
+const GLOBAL_TEXT_FIELDS: [&str; 6] = ["core:description", "core:author", "core:license", "core:hw", "core:sha512", "core:dataset"];
+
+const CAPTURE_FIELDS: [(&str, Want); 6] = [
+    ("core:sample_start", Want::Unsigned),
+    ("core:frequency", Want::Float),
+    ("core:datetime", Want::Text),
+    ("ds:agc", Want::Bool),
+    ("ds:gain", Want::Float),
+    ("ds:sequence_num", Want::Unsigned),
+];
+
+/// Optional annotation fields; `core:sample_start`, `core:sample_count` and
+/// `ds:customClassifierProbs` are handled apart
+const ANNOTATION_FIELDS: [(&str, Want); 20] = [
+    ("core:freq_lower_edge", Want::Float),
+    ("core:freq_upper_edge", Want::Float),
+    ("core:label", Want::Text),
+    ("ds:analogAmProb", Want::Float),
+    ("ds:analogFmProb", Want::Float),
+    ("ds:askProb", Want::Float),
+    ("ds:fskProb", Want::Float),
+    ("ds:pskProb", Want::Float),
+    ("ds:chirpProb", Want::Float),
+    ("ds:constellationProb", Want::Float),
+    ("ds:cssProb", Want::Float),
+    ("ds:ml_no_sig", Want::Bool),
+    ("ds:ook_prob", Want::Float),
+    ("ds:sdr_handle", Want::Text),
+    ("ds:sigBandwidth", Want::Float),
+    ("ds:sigCenterFreq", Want::Float),
+    ("ds:sig_power_dbfs", Want::Float),
+    ("ds:sig_power_dbm", Want::Float),
+    ("ds:snr", Want::Float),
+    ("ds:uuid", Want::Text),
+];
+
+fn repair_root(root: &mut Map<String, Value>, warnings: &mut Vec<String>) {
+    if let Some(global) = root.get_mut("global").and_then(Value::as_object_mut) {
+        repair_global(global, warnings);
+    }
+
+    match root.get("captures") {
+        Some(Value::Array(_)) => {}
+        Some(Value::Null) | None => {
+            warnings.push("captures missing, read as none".to_string());
+            root.insert("captures".to_string(), Value::Array(Vec::new()));
+        }
+        Some(other) => {
+            warnings.push(format!("captures {} is not a list, ignored", other));
+            root.insert("captures".to_string(), Value::Array(Vec::new()));
+        }
+    }
+    if let Some(Value::Array(captures)) = root.get_mut("captures") {
+        let mut idx = 0;
+        captures.retain_mut(|capture| {
+            let place = format!("captures[{}]", idx);
+            idx += 1;
+            let Some(capture) = capture.as_object_mut() else {
+                warnings.push(format!("{} is not an object, dropped", place));
+                return false;
+            };
+            for (key, want) in CAPTURE_FIELDS {
+                coerce_optional(capture, key, want, &place, warnings);
+            }
+            true
+        });
+    }
+
+    match root.get_mut("annotations") {
+        Some(Value::Array(annotations)) => {
+            let mut idx = 0;
+            annotations.retain_mut(|annotation| {
+                let place = format!("annotations[{}]", idx);
+                idx += 1;
+                match annotation.as_object_mut() {
+                    Some(annotation) => repair_annotation(annotation, &place, warnings),
+                    None => {
+                        warnings.push(format!("{} is not an object, dropped", place));
+                        false
+                    }
+                }
+            });
+        }
+        Some(Value::Null) | None => {}
+        Some(other) => {
+            warnings.push(format!("annotations {} is not a list, ignored", other));
+            root.remove("annotations");
+        }
+    }
+}
+
+fn repair_global(global: &mut Map<String, Value>, warnings: &mut Vec<String>) {
+    match global.get("core:version") {
+        Some(Value::String(_)) => {}
+        Some(Value::Number(n)) => {
+            let version = n.to_string();
+            warnings.push(format!("global core:version {} read as \"{}\"", version, version));
+            global.insert("core:version".to_string(), Value::String(version));
+        }
+        _ => {
+            warnings.push(format!("global core:version missing, assumed {}", ASSUMED_SIGMF_VERSION));
+            global.insert("core:version".to_string(), Value::String(ASSUMED_SIGMF_VERSION.to_string()));
+        }
+    }
+
+    coerce_required(global, "core:sample_rate", Want::Float, "global", warnings);
+    if coerce_required(global, "core:datatype", Want::Text, "global", warnings) {
+        if let Some(Value::String(datatype)) = global.get_mut("core:datatype") {
+            let normalized = normalize_datatype(datatype);
+            if normalized != *datatype {
+                warnings.push(format!("global core:datatype \"{}\" read as \"{}\"", datatype, normalized));
+                *datatype = normalized;
+            }
+        }
+    }
+    for key in GLOBAL_TEXT_FIELDS {
+        coerce_optional(global, key, Want::Text, "global", warnings);
+    }
+
+    if let Some(geolocation) = global.get_mut("core:geolocation") {
+        if !geolocation.is_null() && !repair_geolocation(geolocation, warnings) {
+            warnings.push(format!("global core:geolocation {} is not a GeoJSON point, ignored", geolocation));
+            global.remove("core:geolocation");
+        }
+    }
+}
+
+/// Lower case, and the little-endian type for a float or 16-bit datatype
+/// given without an endianness
+fn normalize_datatype(datatype: &str) -> String {
+    let datatype = datatype.trim().to_lowercase();
+    match datatype.as_str() {
+        "cf32" | "ci16" | "rf32" | "ri16" => format!("{}_le", datatype),
+        _ => datatype,
+    }
+}
+
+/// Make `geolocation` a `{"type": "Point", "coordinates": [lon, lat, ...]}`
+/// object, coercing coordinates given as strings; false if it can't be
+fn repair_geolocation(geolocation: &mut Value, warnings: &mut Vec<String>) -> bool {
+    let Some(object) = geolocation.as_object_mut() else {
+        return false;
+    };
+    let Some(Value::Array(coordinates)) = object.get_mut("coordinates") else {
+        return false;
+    };
+    if coordinates.len() < 2 {
+        return false;
+    }
+    for coordinate in coordinates.iter_mut() {
+        if coordinate.is_number() {
+            continue;
+        }
+        match coordinate.as_str().and_then(parse_float) {
+            Some(number) => {
+                warnings.push(format!("global core:geolocation coordinate {} read as {}", coordinate, number));
+                *coordinate = Value::Number(number);
+            }
+            None => return false,
+        }
+    }
+    if !object.get("type").is_some_and(Value::is_string) {
+        warnings.push("global core:geolocation type missing, assumed \"Point\"".to_string());
+        object.insert("type".to_string(), Value::String("Point".to_string()));
+    }
+    true
+}
+
+/// False to drop the annotation, when its extent can't be read
+fn repair_annotation(annotation: &mut Map<String, Value>, place: &str, warnings: &mut Vec<String>) -> bool {
+    for key in ["core:sample_start", "core:sample_count"] {
+        if !coerce_required(annotation, key, Want::Unsigned, place, warnings) {
+            warnings.push(format!("{} has no usable {}, dropped", place, key));
+            return false;
+        }
+    }
+    for (key, want) in ANNOTATION_FIELDS {
+        coerce_optional(annotation, key, want, place, warnings);
+    }
+    let key = "ds:customClassifierProbs";
+    if !annotation.get(key).is_none_or(|probs| probs.is_null() || is_class_prob_list(probs)) {
+        warnings.push(format!("{} {} is not a list of class probabilities, ignored", place, key));
+        annotation.remove(key);
+    }
+    true
+}
+
+fn is_class_prob_list(probs: &Value) -> bool {
+    probs.as_array().is_some_and(|probs| {
+        probs.iter().all(|prob| prob.get("className").is_some_and(Value::is_string) && prob.get("classProb").is_some_and(Value::is_number))
+    })
+}
+
+/// Coerce an optional field in place, removing it if it can't be read
+fn coerce_optional(object: &mut Map<String, Value>, key: &str, want: Want, place: &str, warnings: &mut Vec<String>) {
+    let Some(value) = object.get_mut(key).filter(|value| !value.is_null()) else {
+        return;
+    };
+    let before = value.to_string();
+    match coerce(value, want) {
+        Some(true) => warnings.push(format!("{} {} {} read as {}", place, key, before, value)),
+        Some(false) => {}
+        None => {
+            warnings.push(format!("{} {} {} is unreadable, ignored", place, key, value));
+            object.remove(key);
+        }
+    }
+}
+
+/// Coerce a required field in place; false if it's missing or can't be
+/// read, leaving it for serde to report
+fn coerce_required(object: &mut Map<String, Value>, key: &str, want: Want, place: &str, warnings: &mut Vec<String>) -> bool {
+    let Some(value) = object.get_mut(key) else {
+        return false;
+    };
+    let before = value.to_string();
+    match coerce(value, want) {
+        Some(true) => {
+            warnings.push(format!("{} {} {} read as {}", place, key, before, value));
+            true
+        }
+        Some(false) => true,
+        None => false,
+    }
+}
+
+/// Turn `value` into what `want` needs: Some(true) if it was changed,
+/// Some(false) if it already fit, None if it can't be
+fn coerce(value: &mut Value, want: Want) -> Option<bool> {
+    let coerced = match (want, &*value) {
+        (Want::Float, Value::Number(_)) | (Want::Bool, Value::Bool(_)) | (Want::Text, Value::String(_)) => return Some(false),
+        (Want::Unsigned, Value::Number(n)) if n.is_u64() => return Some(false),
+        (Want::Float, Value::String(s)) => Value::Number(parse_float(s)?),
+        (Want::Unsigned, Value::Number(n)) => Value::from(whole(n.as_f64()?)?),
+        (Want::Unsigned, Value::String(s)) => Value::from(whole(parse_float(s)?.as_f64()?)?),
+        (Want::Bool, Value::String(s)) => match s.trim().to_lowercase().as_str() {
+            "true" | "1" => Value::Bool(true),
+            "false" | "0" => Value::Bool(false),
+            _ => return None,
+        },
+        (Want::Bool, Value::Number(n)) => match n.as_u64()? {
+            0 => Value::Bool(false),
+            1 => Value::Bool(true),
+            _ => return None,
+        },
+        (Want::Text, Value::Number(_) | Value::Bool(_)) => Value::String(value.to_string()),
+        _ => return None,
+    };
+    *value = coerced;
+    Some(true)
+}
+
+/// A number written as text, e.g. `"2e6"`, or with a rate unit such as
+/// `"2000000 Hz"` or `"2.4 MHz"`
+fn parse_float(text: &str) -> Option<Number> {
+    let text = text.trim().to_lowercase();
+    let (number, scale) = match ["hz", "sps", "s/s"].iter().find_map(|unit| text.strip_suffix(unit)) {
+        Some(rest) => {
+            let rest = rest.trim_end();
+            match rest.strip_suffix(['k', 'm', 'g']) {
+                Some(number) => (number, [1e3, 1e6, 1e9]["kmg".find(rest.chars().last()?)?]),
+                None => (rest, 1.0),
+            }
+        }
+        None => (text.as_str(), 1.0),
+    };
+    number.trim_end().parse::<f64>().ok().and_then(|value| Number::from_f64(value * scale))
+}
+
+fn whole(value: f64) -> Option<u64> {
+    (value >= 0.0 && value.fract() == 0.0 && value <= u64::MAX as f64).then_some(value as u64)
+}
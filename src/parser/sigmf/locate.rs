@@ -0,0 +1,171 @@
+//! Finding a recording's data file when it isn't next to its meta file.
+//!
+//! Metadata and bulk IQ often live on different mounts. The data file is
+//! looked for, in order:
+//!
+//! 1. the file named by `core:dataset` (relative to the meta file's
+//!    directory), or `<name>.sigmf-data` next to the meta file
+//! 2. that path with a remapped prefix (`--data-root /mnt/meta=/mnt/iq`)
+//! 3. under each search root (`--data-root /mnt/iq`): first at the meta
+//!    file's own relative location, then anywhere below the root by file name
+//!
+//! At each step a compressed `.sigmf-data.zst`, `.zstd` or `.gz` counts as
+//! the data file too (see [`super::compression`]).
+//!
+//! Roots apply to the whole process, like read-only mode, so every command
+//! and the GUI resolve paths the same way.
+
+use super::compression::{compressed_names, existing_data_file};
+use crate::parser::ParserError;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Mutex, OnceLock, RwLock};
+use walkdir::WalkDir;
+
+/// Where else to look for data files
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DataRoots {
+    /// Directories searched for data files
+    pub search: Vec<PathBuf>,
+    /// (meta prefix, data prefix) pairs: a data file expected under the
+    /// first is looked for at the same relative path under the second
+    pub remaps: Vec<(PathBuf, PathBuf)>,
+}
+
+impl DataRoots {
+    /// Parse `--data-root` values: `DIR` adds a search root and
+    /// `META_PREFIX=DATA_PREFIX` a remap
+    pub fn parse<S: AsRef<str>>(specs: &[S]) -> Result<Self> {
+        let mut roots = DataRoots::default();
+        for spec in specs {
+            let spec = spec.as_ref().trim();
+            if spec.is_empty() {
+                continue;
+            }
+            match spec.split_once('=') {
+                Some((from, to)) if !from.trim().is_empty() && !to.trim().is_empty() => {
+                    roots.remaps.push((PathBuf::from(from.trim()), PathBuf::from(to.trim())));
+                }
+                Some(_) => anyhow::bail!("Expected META_PREFIX=DATA_PREFIX, got {:?}", spec),
+                None => roots.search.push(PathBuf::from(spec)),
+            }
+        }
+        Ok(roots)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.search.is_empty() && self.remaps.is_empty()
+    }
+}
+
+static DATA_ROOTS: RwLock<DataRoots> = RwLock::new(DataRoots {
+    search: Vec::new(),
+    remaps: Vec::new(),
+});
+
+/// File name → every path with that name below one search root
+type FileIndex = HashMap<OsString, Vec<PathBuf>>;
+
+/// Index of each search root, built on first use
+static ROOT_INDEX: OnceLock<Mutex<HashMap<PathBuf, FileIndex>>> = OnceLock::new();
+
+/// Use `roots` for the rest of the process (until set again)
+pub fn set_data_roots(roots: DataRoots) {
+    *DATA_ROOTS.write().unwrap_or_else(|e| e.into_inner()) = roots;
+    if let Some(index) = ROOT_INDEX.get() {
+        index.lock().unwrap_or_else(|e| e.into_inner()).clear();
+    }
+}
+
+pub fn data_roots() -> DataRoots {
+    DATA_ROOTS.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Where the data file would be without any roots: `core:dataset` relative
+/// to the meta file's directory, or `<name>.sigmf-data` next to it
+pub fn nominal_data_path(meta_path: &Path, dataset: Option<&str>) -> PathBuf {
+    match dataset.map(str::trim).filter(|d| !d.is_empty()) {
+        Some(dataset) => meta_path.parent().unwrap_or(Path::new("")).join(dataset),
+        None => meta_path.with_extension("sigmf-data"),
+    }
+}
+
+/// Find the data file of `meta_path`, whose `core:dataset` is `dataset`
+pub fn resolve_data_path(meta_path: &Path, dataset: Option<&str>) -> Result<PathBuf> {
+    let nominal = nominal_data_path(meta_path, dataset);
+    if let Some(path) = existing_data_file(&nominal) {
+        return Ok(path);
+    }
+    let roots = data_roots();
+    if roots.is_empty() {
+        return Err(ParserError::MissingDataFile { path: nominal, searched: 0 }.into());
+    }
+
+    let absolute = std::path::absolute(&nominal).unwrap_or_else(|_| nominal.clone());
+    for (from, to) in &roots.remaps {
+        let from = std::path::absolute(from).unwrap_or_else(|_| from.clone());
+        if let Ok(relative) = absolute.strip_prefix(&from) {
+            if let Some(path) = existing_data_file(&to.join(relative)) {
+                return Ok(path);
+            }
+        }
+    }
+
+    let file_name = nominal
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("Data file does not exist: {:?}", nominal))?;
+    for root in &roots.search {
+        // The meta file's directory mirrored under the root, deepest first,
+        // e.g. /iq/meta/site1/x, /iq/site1/x, /iq/x
+        let parents: Vec<&std::ffi::OsStr> = absolute
+            .parent()
+            .into_iter()
+            .flat_map(|dir| dir.components())
+            .filter_map(|c| match c {
+                Component::Normal(name) => Some(name),
+                _ => None,
+            })
+            .collect();
+        for skip in 0..=parents.len() {
+            let candidate = parents[skip..].iter().fold(root.clone(), |path, name| path.join(name)).join(file_name);
+            if let Some(path) = existing_data_file(&candidate) {
+                return Ok(path);
+            }
+        }
+        let mut found = find_under_root(root, file_name);
+        if found.is_empty() {
+            found = compressed_names(file_name).iter().flat_map(|name| find_under_root(root, name)).collect();
+        }
+        match found.as_slice() {
+            [] => {}
+            [only] => return Ok(only.clone()),
+            several => anyhow::bail!(
+                "Data file {:?} not found next to {:?}, and {} files with that name are under {:?}",
+                file_name,
+                meta_path,
+                several.len(),
+                root
+            ),
+        }
+    }
+    Err(ParserError::MissingDataFile { path: nominal, searched: roots.search.len() + roots.remaps.len() }.into())
+}
+
+/// Every file named `file_name` below `root`. The root is walked once and
+/// indexed, so scanning a directory doesn't walk it per recording.
+fn find_under_root(root: &Path, file_name: &std::ffi::OsStr) -> Vec<PathBuf> {
+    let index = ROOT_INDEX.get_or_init(Default::default);
+    let mut index = index.lock().unwrap_or_else(|e| e.into_inner());
+    let files = index.entry(root.to_path_buf()).or_insert_with(|| {
+        let mut files = FileIndex::new();
+        for entry in WalkDir::new(root).follow_links(true).into_iter().filter_map(|e| e.ok()) {
+            if entry.file_type().is_file() {
+                files.entry(entry.file_name().to_os_string()).or_default().push(entry.into_path());
+            }
+        }
+        files
+    });
+    files.get(file_name).cloned().unwrap_or_default()
+}
@@ -26,7 +26,11 @@ pub struct GlobalInfo {
     pub hardware: Option<String>,
     #[serde(rename = "core:geolocation")]
     pub geolocation: Option<GeoLocation>,
-    
+    #[serde(rename = "core:sha512")]
+    pub sha512: Option<String>,
+    /// Data file name when it isn't `<name>.sigmf-data`
+    #[serde(rename = "core:dataset")]
+    pub dataset: Option<String>,
 }
 
 
@@ -70,6 +74,8 @@ pub struct AnnotationInfo {
     pub freq_lower_edge: Option<f64>,
     #[serde(rename = "core:freq_upper_edge")]
     pub freq_upper_edge: Option<f64>,
+    #[serde(rename = "core:label")]
+    pub label: Option<String>,
 
     // Distributed Spectrum Specific Fields
     //#[serde(rename = "ds:actually_using_wb_params")]
@@ -110,6 +116,9 @@ pub struct AnnotationInfo {
     pub sig_snr : Option<f64>,
     #[serde(rename = "ds:uuid")]
     pub uuid: Option<String>,
+
+    #[serde(flatten)]
+    pub extra_fields: HashMap<String, serde_json::Value>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
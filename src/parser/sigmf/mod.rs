@@ -2,10 +2,59 @@ mod metadata;
 mod datatypes;
 mod parser;
 mod dataset;
+mod reader;
+mod validate;
+mod checksum;
+mod writeback;
+mod archive;
+mod collection;
+mod journal;
+mod channelize;
+mod convert;
+mod extract;
+mod segments;
+mod edit;
+mod locate;
+mod cache;
+mod summary;
+mod label;
+mod lenient;
+mod info;
+mod organize;
+mod compression;
 
 pub use metadata::{SigMFMetadata, GlobalInfo, CaptureInfo, AnnotationInfo};
 pub use datatypes::SigMFDataType;
 pub use parser::SigMFParser;
-pub use dataset::SigMFDataset;
+pub use dataset::{
+    DatasetOptions, FailureStage, FileFailure, Granularity, ScanProgress, ScanReport, SigMFDataset, ANNOTATION_DENSITY_BUCKETS, ANNOTATION_FIELD_PREFIX,
+    META_PATH_COLUMN, SOURCE_ROOT_COLUMN,
+};
+pub use dataset::relative_meta_path;
+pub(crate) use dataset::normalize;
+pub use reader::{DataLocation, SigMFReader};
+pub use validate::{validate_meta_file, validate_path, ValidationReport};
 
 
+pub use checksum::{
+    sha512_file, sha512_reader, sha512_reader_with_progress, verify_sha512, verify_sha512_with_progress, write_sha512, ChecksumStatus,
+};
+pub use writeback::{merge_metadata, update_meta, write_back, MetaSnapshot, WriteConflict, WriteOutcome};
+pub use archive::{is_archive, list_recordings, pack_archive, read_meta_text, split_member_path, ArchiveRecording};
+pub use collection::{CollectionStream, SigMFCollection};
+pub use journal::{is_read_only, list_batches, read_entries, rollback, set_read_only, BatchSummary, Journal, JournalEntry, Operation, RollbackReport};
+pub use channelize::{channelize_recording, ChannelizeReport};
+pub use convert::{convert_recording, ConvertReport};
+pub use extract::{copy_recording, extract_slice, ExtractReport, SliceRange};
+pub use edit::{
+    add_annotation, apply_global_edits, check_annotation, field_text, parse_field_value, qualify_key, set_global_fields, sort_annotations, GlobalEdit,
+    PROTECTED_GLOBAL_FIELDS,
+};
+pub use segments::{dataset_segments, group_segments, Segment, SegmentedRecording, StitchedReader};
+pub use summary::{CaptureSpan, SummaryColumns, SummaryRow};
+pub use cache::{CachedRecording, DatasetCache};
+pub use label::{write_row_label, RowAnnotation};
+pub use lenient::{parse_metadata, parse_mode, set_parse_mode, ParseMode, ASSUMED_SIGMF_VERSION};
+pub use compression::{compress_data_file, Compression};
+pub use organize::{organize_recording, OrganizeMode, OrganizeTemplate, OrganizedRecording, TEMPLATE_FIELDS};
+pub use locate::{data_roots, nominal_data_path, resolve_data_path, set_data_roots, DataRoots};
@@ -0,0 +1,235 @@
+//! Sorting recordings into a directory tree named after their metadata.
+//!
+//! A template like `{date}/{center_freq_mhz}MHz/` is filled in per recording
+//! and its meta and data files are moved (journaled, so `rollback` can put
+//! them back) or copied under the output directory, always side by side.
+
+use super::{copy_recording, split_member_path, Compression, Journal, SigMFParser};
+use crate::data_ops::activity::parse_capture_datetime;
+use crate::parser::raw::is_raw_iq;
+use anyhow::Result;
+use serde_json::Value;
+use std::path::{Component, Path, PathBuf};
+
+/// Placeholders a template can use besides namespaced global fields
+pub const TEMPLATE_FIELDS: [&str; 13] = [
+    "date",
+    "year",
+    "month",
+    "day",
+    "hour",
+    "center_freq_hz",
+    "center_freq_mhz",
+    "sample_rate_hz",
+    "datatype",
+    "author",
+    "hardware",
+    "label",
+    "stem",
+];
+
+/// Directory name used when a recording has no value for a placeholder
+const MISSING_VALUE: &str = "unknown";
+
+#[derive(Debug, Clone, PartialEq)]
+enum Part {
+    Text(String),
+    Field(String),
+}
+
+/// A parsed `--template`: text with `{field}` placeholders, where a field is
+/// one of [`TEMPLATE_FIELDS`] or a namespaced global field such as
+/// `{core:recorder}` or `{ds:sensor_id}`
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrganizeTemplate {
+    parts: Vec<Part>,
+}
+
+impl OrganizeTemplate {
+    pub fn parse(template: &str) -> Result<Self> {
+        let relative = Path::new(template).components().all(|c| matches!(c, Component::Normal(_) | Component::CurDir));
+        if !relative {
+            anyhow::bail!("Template {:?} must be a relative path without \"..\"", template);
+        }
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(open) = rest.find('{') {
+            let close = open
+                + rest[open..]
+                    .find('}')
+                    .ok_or_else(|| anyhow::anyhow!("Unclosed {{ in template {:?}", template))?;
+            if open > 0 {
+                parts.push(Part::Text(rest[..open].to_string()));
+            }
+            let name = rest[open + 1..close].trim();
+            if !TEMPLATE_FIELDS.contains(&name) && !name.contains(':') {
+                anyhow::bail!(
+                    "Unknown template field {{{}}}; expected one of {} or a namespaced global field like {{core:recorder}}",
+                    name,
+                    TEMPLATE_FIELDS.join(", ")
+                );
+            }
+            parts.push(Part::Field(name.to_string()));
+            rest = &rest[close + 1..];
+        }
+        if rest.contains('}') {
+            anyhow::bail!("Unmatched }} in template {:?}", template);
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+        Ok(OrganizeTemplate { parts })
+    }
+
+    /// The relative directory for a recording. Values are made safe as path
+    /// components, and missing ones become `unknown`.
+    pub fn expand(&self, parser: &SigMFParser) -> PathBuf {
+        let mut path = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => path.push_str(text),
+                Part::Field(name) => {
+                    let value = field_value(parser, name).filter(|v| !v.trim().is_empty());
+                    path.push_str(&sanitize(value.as_deref().unwrap_or(MISSING_VALUE)));
+                }
+            }
+        }
+        PathBuf::from(path)
+    }
+}
+
+/// Frequencies as short as they can be without losing precision, e.g. `915`
+/// or `433.92`
+fn format_number(value: f64) -> String {
+    format!("{}", (value * 1e6).round() / 1e6)
+}
+
+fn field_value(parser: &SigMFParser, name: &str) -> Option<String> {
+    let global = &parser.metadata.global;
+    let datetime = || {
+        parser.metadata.captures.iter().find_map(|c| c.timestamp.as_deref()).and_then(parse_capture_datetime)
+    };
+    match name {
+        "date" => datetime().map(|dt| dt.format("%Y-%m-%d").to_string()),
+        "year" => datetime().map(|dt| dt.format("%Y").to_string()),
+        "month" => datetime().map(|dt| dt.format("%m").to_string()),
+        "day" => datetime().map(|dt| dt.format("%d").to_string()),
+        "hour" => datetime().map(|dt| dt.format("%H").to_string()),
+        "center_freq_hz" => parser.center_frequency().map(format_number),
+        "center_freq_mhz" => parser.center_frequency().map(|hz| format_number(hz / 1e6)),
+        "sample_rate_hz" => Some(format_number(global.sample_rate)),
+        "datatype" => Some(global.datatype.clone()),
+        "author" => global.author.clone(),
+        "hardware" => global.hardware.clone(),
+        "label" => parser.metadata.annotations.iter().flatten().find_map(|a| a.label.clone()),
+        "stem" => parser.meta_path.file_stem().map(|s| s.to_string_lossy().to_string()),
+        key => read_global_field(parser, key),
+    }
+}
+
+/// A global field sig_viewer may not model, read from the meta file itself
+fn read_global_field(parser: &SigMFParser, key: &str) -> Option<String> {
+    let text = std::fs::read_to_string(&parser.meta_path).ok()?;
+    let meta: Value = serde_json::from_str(&text).ok()?;
+    match meta.get("global")?.get(key)? {
+        Value::String(s) => Some(s.clone()),
+        Value::Null => None,
+        other => Some(other.to_string()),
+    }
+}
+
+/// Keep a value to one path component a file system will accept
+fn sanitize(value: &str) -> String {
+    let cleaned: String = value
+        .trim()
+        .chars()
+        .map(|c| if c.is_control() || "/\\:*?\"<>|".contains(c) { '_' } else { c })
+        .collect();
+    match cleaned.as_str() {
+        "." | ".." => cleaned.replace('.', "_"),
+        _ => cleaned,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrganizeMode {
+    /// Move the pair, journaled
+    #[default]
+    Move,
+    /// Copy the pair, leaving the original in place
+    Copy,
+}
+
+/// Where one recording went
+#[derive(Debug, Clone)]
+pub struct OrganizedRecording {
+    pub source: PathBuf,
+    pub meta_path: PathBuf,
+    pub data_path: PathBuf,
+    /// Already where the template puts it, so nothing was done
+    pub unchanged: bool,
+}
+
+/// Move or copy one recording into `out_dir` joined with the template's
+/// directory for it. The data file is named after the meta file, and
+/// decompressed when copied. Existing files are never overwritten. With
+/// `dry_run` only the destination is worked out.
+pub fn organize_recording<P: AsRef<Path>, Q: AsRef<Path>>(
+    meta_path: P,
+    out_dir: Q,
+    template: &OrganizeTemplate,
+    mode: OrganizeMode,
+    journal: &Journal,
+    dry_run: bool,
+) -> Result<OrganizedRecording> {
+    let meta_path = meta_path.as_ref();
+    let parser = SigMFParser::from_meta_file(meta_path)?;
+    let file_name = meta_path
+        .file_name()
+        .ok_or_else(|| anyhow::anyhow!("{:?} has no file name", meta_path))?;
+    let dest_meta = out_dir.as_ref().join(template.expand(&parser)).join(file_name);
+    // A moved compressed data file stays compressed; copies come out plain
+    let dest_data = match Compression::from_path(&parser.data_location.file).filter(|_| mode == OrganizeMode::Move) {
+        Some(_) => {
+            let extension = parser.data_location.file.extension().unwrap_or_default().to_string_lossy();
+            dest_meta.with_extension(format!("sigmf-data.{}", extension))
+        }
+        None => dest_meta.with_extension("sigmf-data"),
+    };
+    let unchanged = same_file(meta_path, &dest_meta) && same_file(&parser.data_location.file, &dest_data);
+    let organized = OrganizedRecording {
+        source: meta_path.to_path_buf(),
+        meta_path: dest_meta,
+        data_path: dest_data,
+        unchanged,
+    };
+    if unchanged || dry_run {
+        return Ok(organized);
+    }
+    match mode {
+        OrganizeMode::Copy => {
+            let dir = organized.meta_path.parent().unwrap_or(Path::new("."));
+            copy_recording(meta_path, dir)?;
+        }
+        OrganizeMode::Move => {
+            if split_member_path(meta_path).is_some() || is_raw_iq(meta_path) || parser.data_location.offset != 0 {
+                anyhow::bail!("{:?} isn't a plain SigMF pair and can only be copied", meta_path);
+            }
+            for path in [&organized.meta_path, &organized.data_path] {
+                if path.exists() {
+                    anyhow::bail!("{:?} already exists", path);
+                }
+            }
+            journal.rename(meta_path, &organized.meta_path)?;
+            journal.rename(&parser.data_location.file, &organized.data_path)?;
+        }
+    }
+    Ok(organized)
+}
+
+fn same_file(a: &Path, b: &Path) -> bool {
+    match (a.canonicalize(), b.canonicalize()) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
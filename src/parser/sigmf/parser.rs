@@ -1,4 +1,9 @@
-use super::{SigMFDataType, SigMFMetadata};
+use super::{parse_metadata, split_member_path, list_recordings, resolve_data_path, verify_sha512, verify_sha512_with_progress, ArchiveRecording, ChecksumStatus, DataLocation, SigMFDataType, SigMFMetadata, SigMFReader};
+use super::summary::{CaptureSpan, SummaryColumns, SummaryRow};
+use super::Granularity;
+use crate::data_ops::activity::parse_capture_datetime;
+use crate::parser::format::find_format;
+use crate::parser::{OpenSamples, ParserError, SampleReader};
 use polars::prelude::*;
 use anyhow::Result;
 use std::path::Path;
@@ -6,29 +11,141 @@ use std::fs::File;
 
 pub struct SigMFParser {
     pub metadata: SigMFMetadata,
+    pub meta_path: std::path::PathBuf,
     pub data_type: SigMFDataType,
     pub data_file_path: std::path::PathBuf,
+    /// Where the samples actually are; differs from `data_file_path` for
+    /// recordings inside a `.sigmf` archive. `data_file_path` may be a
+    /// compressed file (`.sigmf-data.zst`), which this reads through.
+    pub data_location: DataLocation,
+    /// For formats whose samples can't be read from `data_location` (e.g.
+    /// DigitalRF), how to read them instead
+    pub open_samples: Option<OpenSamples>,
+    /// Repairs lenient parsing made to the meta file, empty in strict mode
+    pub parse_warnings: Vec<String>,
 }
 
 impl SigMFParser{
+    /// Open a meta file on disk, a virtual path into a `.sigmf` archive, or
+    /// a recording in another registered format, e.g. a raw IQ file (see
+    /// [`crate::parser::format`])
     pub fn from_meta_file<P: AsRef<Path>>(meta_path: P) -> Result<Self> {
         let meta_path = meta_path.as_ref();
+        if let Some(format) = find_format(meta_path) {
+            return format.open(meta_path);
+        }
+        if !meta_path.exists() {
+            if let Some((archive, _)) = split_member_path(meta_path) {
+                let recording = list_recordings(&archive)?
+                    .into_iter()
+                    .find(|r| r.meta_path == meta_path)
+                    .ok_or_else(|| anyhow::anyhow!("{:?} is not in archive {:?}", meta_path, archive))?;
+                return Self::from_archive_recording(recording);
+            }
+        }
 
         let meta_content = std::fs::read_to_string(meta_path)?;
-        let metadata: SigMFMetadata = serde_json::from_str(&meta_content)?;
+        let (metadata, parse_warnings) = parse_metadata(&meta_content)?;
         let data_type = SigMFDataType::from_string(&metadata.global.datatype)?;
 
-        let data_file_path = meta_path.with_extension("sigmf-data");
-        if !data_file_path.exists() {
-            return Err(anyhow::anyhow!("Data file does not exist: {:?}", data_file_path));
-        }
+        // Next to the meta file, or wherever the data roots point
+        let data_file_path = resolve_data_path(meta_path, metadata.global.dataset.as_deref())?;
+        Ok(SigMFParser {
+            metadata,
+            meta_path: meta_path.to_path_buf(),
+            data_type,
+            data_location: DataLocation::whole_file(&data_file_path)?,
+            data_file_path,
+            open_samples: None,
+            parse_warnings,
+        })
+    }
+
+    pub fn from_archive_recording(recording: ArchiveRecording) -> Result<Self> {
+        let (metadata, parse_warnings) = parse_metadata(&recording.meta_json)?;
+        let data_type = SigMFDataType::from_string(&metadata.global.datatype)?;
+        let data_file_path = recording.meta_path.with_extension("sigmf-data");
+        let data_location = recording
+            .data
+            .ok_or_else(|| ParserError::MissingDataFile { path: data_file_path.clone(), searched: 0 })?;
         Ok(SigMFParser {
             metadata,
+            meta_path: recording.meta_path,
             data_type,
             data_file_path,
+            data_location,
+            open_samples: None,
+            parse_warnings,
         })
     }
     
+    /// Positions in the annotations array of the annotations that get a
+    /// summary row each, in row order
+    pub fn ml_annotation_indices(&self) -> Vec<usize> {
+        self.metadata.annotations.as_ref()
+            .map(|anns| anns.iter().enumerate().filter(|(_, ann)| self.is_ml_annotation(ann)).map(|(idx, _)| idx).collect())
+            .unwrap_or_default()
+    }
+
+    /// Position of the annotation labeling the whole recording: one that
+    /// isn't an ML annotation, starting at sample 0 and covering every
+    /// sample. Rows without an ML annotation take their `label` from it.
+    pub fn recording_annotation_index(&self) -> Option<usize> {
+        let (_, _, num_samples, _) = self.file_info();
+        self.metadata.annotations.as_ref()?
+            .iter()
+            .position(|ann| !self.is_ml_annotation(ann) && ann.sample_start == 0 && ann.sample_count >= num_samples)
+    }
+
+    /// Annotations with ML data; each becomes a summary row
+    fn ml_annotations(&self) -> Vec<&super::AnnotationInfo> {
+        self.metadata.annotations.as_ref()
+            .map(|anns| anns.iter().filter(|ann| self.is_ml_annotation(ann)).collect())
+            .unwrap_or_default()
+    }
+
+    /// Scalar annotation fields this parser doesn't know, for each summary
+    /// row in order (a single empty list for a recording without ML
+    /// annotations). Arrays, objects and nulls are skipped.
+    pub fn annotation_extra_fields(&self) -> Vec<Vec<(&str, &serde_json::Value)>> {
+        let ml_annotations = self.ml_annotations();
+        if ml_annotations.is_empty() {
+            return vec![Vec::new()];
+        }
+        ml_annotations.into_iter().map(extra_fields).collect()
+    }
+
+    /// Like [`annotation_extra_fields`](Self::annotation_extra_fields), for
+    /// the rows of [`to_capture_rows`](Self::to_capture_rows): the fields of
+    /// the first ML annotation starting in each segment
+    pub fn capture_annotation_extra_fields(&self) -> Vec<Vec<(&str, &serde_json::Value)>> {
+        let ml_annotations = self.ml_annotations();
+        self.capture_bounds()
+            .into_iter()
+            .map(|(start, end)| {
+                ml_annotations
+                    .iter()
+                    .find(|ann| ann.sample_start >= start && ann.sample_start < end)
+                    .map(|ann| extra_fields(ann))
+                    .unwrap_or_default()
+            })
+            .collect()
+    }
+
+    /// Sample range of each capture segment, as rows of `to_capture_rows`
+    pub(super) fn capture_bounds(&self) -> Vec<(u64, u64)> {
+        let (_, _, num_samples, _) = self.file_info();
+        let starts: Vec<u64> = self.metadata.captures.iter().map(|c| c.sample_start.unwrap_or(0)).collect();
+        if starts.is_empty() {
+            return vec![(0, num_samples)];
+        }
+        starts
+            .iter()
+            .enumerate()
+            .map(|(index, &start)| (start, starts.get(index + 1).copied().unwrap_or(num_samples.max(start))))
+            .collect()
+    }
+
     fn is_ml_annotation(&self, ann: &super::AnnotationInfo) -> bool {
         ann.sig_center_freq.is_some() || 
         ann.ask_prob.is_some() || 
@@ -50,9 +167,9 @@ impl SigMFParser{
             .map(|c| c.class_prob as f64)
     }
 
-    pub fn to_summary_rows(&self) -> Result<DataFrame> {
-        let global = &self.metadata.global;
-        
+    /// (meta filename, data filename, samples, data file bytes); zero
+    /// samples and bytes when the data file is missing
+    fn file_info(&self) -> (String, String, u64, u64) {
         // Get data filename (not full path)
         let data_filename = self.data_file_path
             .file_name()
@@ -60,22 +177,36 @@ impl SigMFParser{
             .to_string_lossy()
             .to_string();
         
-        let meta_filename = self.data_file_path
-            .with_extension("sigmf-meta")
+        let meta_filename = self.meta_path
             .file_name()
             .unwrap()
             .to_string_lossy()
             .to_string();
         
         // Calculate basic file info
-        let (num_samples, file_size_bytes) = if self.data_file_path.exists() {
-            let file_size = std::fs::metadata(&self.data_file_path)?.len();
+        let (num_samples, file_size_bytes) = if self.data_location.file.exists() {
+            let file_size = self.data_location.size;
             let sample_size = self.data_type.sample_size_bytes() as u64;
             let num_samples = file_size / sample_size;
             (num_samples, file_size)
         } else {
             (0, 0)
         };
+        (meta_filename, data_filename, num_samples, file_size_bytes)
+    }
+
+    pub fn to_summary_rows(&self) -> Result<DataFrame> {
+        let mut columns = SummaryColumns::new(Granularity::Annotation);
+        self.summary_rows().into_iter().for_each(|row| columns.push(row));
+        columns.finish()
+    }
+
+    /// One row per ML annotation, with its position in the annotations
+    /// array, or a single row with default ML values for a recording without
+    /// any
+    pub fn summary_rows(&self) -> Vec<SummaryRow> {
+        let global = &self.metadata.global;
+        let (meta_filename, data_filename, num_samples, file_size_bytes) = self.file_info();
 
         // Get capture info (this remains the same for all rows)
         let capture_with_freq = self.metadata.captures.iter()
@@ -86,13 +217,11 @@ impl SigMFParser{
             .find(|c| c.gain.is_some() || c.agc.is_some());
 
         // Get ML annotations (annotations with ML data)
-        let ml_annotations: Vec<_> = self.metadata.annotations.as_ref()
-            .map(|anns| anns.iter().filter(|ann| self.is_ml_annotation(ann)).collect())
-            .unwrap_or_default();
+        let ml_annotations = self.ml_annotations();
 
         // If no ML annotations, create a single row with default ML values
         if ml_annotations.is_empty() {
-            return self.create_single_row_dataframe(
+            return vec![self.summary_row(
                 &meta_filename,
                 &data_filename,
                 num_samples,
@@ -103,37 +232,115 @@ impl SigMFParser{
                 capture_with_datetime,
                 capture_with_ds_info,
                 None,
-            );
+            )];
         }
 
         // Create a row for each ML annotation
-        let mut all_rows = Vec::new();
         let num_linked_rows = ml_annotations.len() as u64;
-        for ml_annotation in ml_annotations {
-            let row_df = self.create_single_row_dataframe(
+        ml_annotations
+            .into_iter()
+            .zip(self.ml_annotation_indices())
+            .map(|(ml_annotation, idx)| SummaryRow {
+                annotation_index: Some(idx as u32),
+                ..self.summary_row(
+                    &meta_filename,
+                    &data_filename,
+                    num_samples,
+                    file_size_bytes,
+                    global,
+                    num_linked_rows,
+                    capture_with_freq,
+                    capture_with_datetime,
+                    capture_with_ds_info,
+                    Some(ml_annotation),
+                )
+            })
+            .collect()
+    }
+
+    /// A single row for the whole recording: the first row of
+    /// [`summary_rows`](Self::summary_rows), whose `num_detected_sigs`
+    /// already counts every ML annotation
+    pub fn file_summary_rows(&self) -> Vec<SummaryRow> {
+        let mut rows = self.summary_rows();
+        rows.truncate(1);
+        rows.iter_mut().for_each(|row| row.annotation_index = None);
+        rows
+    }
+
+    /// One row per capture segment (a capture runs until the next one
+    /// starts), with that capture's frequency, gain, AGC, sequence number and
+    /// datetime. A capture without a datetime gets the previous one's plus
+    /// the sample offset. ML columns come from the first ML annotation
+    /// starting in the segment and `num_detected_sigs` counts them. Adds
+    /// `capture_index`, `capture_sample_start`, `capture_sample_count` and
+    /// `capture_duration_s`.
+    pub fn to_capture_rows(&self) -> Result<DataFrame> {
+        let mut columns = SummaryColumns::new(Granularity::Capture);
+        self.capture_summary_rows().into_iter().for_each(|row| columns.push(row));
+        columns.finish()
+    }
+
+    /// The rows of [`to_capture_rows`](Self::to_capture_rows)
+    pub fn capture_summary_rows(&self) -> Vec<SummaryRow> {
+        let global = &self.metadata.global;
+        let (meta_filename, data_filename, num_samples, file_size_bytes) = self.file_info();
+        let ml_annotations = self.ml_annotations();
+
+        // A recording without captures is one segment covering the file
+        let bounds = self.capture_bounds();
+        let mut last_datetime: Option<(chrono::NaiveDateTime, u64)> = None;
+        let mut all_rows = Vec::with_capacity(bounds.len());
+        for (index, (start, end)) in bounds.into_iter().enumerate() {
+            let capture = self.metadata.captures.get(index);
+            let in_segment: Vec<_> = ml_annotations
+                .iter()
+                .copied()
+                .filter(|ann| ann.sample_start >= start && ann.sample_start < end)
+                .collect();
+
+            let mut capture = capture.cloned();
+            if let Some(ref mut capture) = capture {
+                match capture.timestamp.as_deref().and_then(parse_capture_datetime) {
+                    Some(datetime) => last_datetime = Some((datetime, start)),
+                    None if capture.timestamp.is_none() && global.sample_rate > 0.0 => {
+                        capture.timestamp = last_datetime.map(|(datetime, from)| {
+                            // Captures out of order put this one before the timestamped one
+                            let offset_s = (start as f64 - from as f64) / global.sample_rate;
+                            let offset = chrono::Duration::nanoseconds((offset_s * 1e9) as i64);
+                            (datetime + offset).format("%Y-%m-%dT%H:%M:%S%.fZ").to_string()
+                        });
+                    }
+                    None => {}
+                }
+            }
+
+            let mut row = self.summary_row(
                 &meta_filename,
                 &data_filename,
                 num_samples,
                 file_size_bytes,
                 global,
-                num_linked_rows,
-                capture_with_freq,
-                capture_with_datetime,
-                capture_with_ds_info,
-                Some(ml_annotation),
-            )?;
-            all_rows.push(row_df);
-        }
-
-        // Concatenate all rows
-        let mut result = all_rows[0].clone();
-        for df in all_rows.into_iter().skip(1) {
-            result = result.vstack(&df)?;
+                in_segment.len() as u64,
+                capture.as_ref(),
+                capture.as_ref(),
+                capture.as_ref(),
+                in_segment.first().copied(),
+            );
+            let count = end.saturating_sub(start);
+            row.capture = Some(CaptureSpan {
+                index: index as u32,
+                sample_start: start,
+                sample_count: count,
+                duration_s: samples_to_seconds(count, global.sample_rate),
+            });
+            all_rows.push(row);
         }
-        Ok(result)
+        all_rows
     }
 
-    fn create_single_row_dataframe(
+    #[allow(clippy::too_many_arguments)]
+    fn summary_row(
         &self,
         meta_filename: &str,
         data_filename: &str,
@@ -145,116 +352,87 @@ impl SigMFParser{
         capture_with_datetime: Option<&super::CaptureInfo>,
         capture_with_ds_info: Option<&super::CaptureInfo>,
         ml_annotation: Option<&super::AnnotationInfo>,
-    ) -> Result<DataFrame> {
-        let df = df! {
+    ) -> SummaryRow {
+        let first_annotation = self.metadata.annotations.as_ref().and_then(|anns| anns.first());
+        SummaryRow {
             // File identification
-            "meta_filename" => vec![meta_filename.to_string()],
-            "data_filename" => vec![data_filename.to_string()],
-            
+            meta_filename: meta_filename.to_string(),
+            data_filename: data_filename.to_string(),
+
             // Basic file info
-            "num_samples" => vec![num_samples],
-            "file_size_bytes" => vec![file_size_bytes],
-            "duration_s" => vec![num_samples as f64 / global.sample_rate],
-            
+            num_samples,
+            file_size_bytes,
+            duration_s: samples_to_seconds(num_samples, global.sample_rate),
+
             // Global metadata
-            "sample_rate_hz" => vec![global.sample_rate],
-            "datatype" => vec![global.datatype.clone()],
-            "sigmf_version" => vec![global.version.clone()],
-            "author" => vec![global.author.clone().unwrap_or_default()],
-            "hardware" => vec![global.hardware.clone().unwrap_or_default()],
-            "num_detected_sigs" => vec![num_linked_rows],
-            // Geolocation
-            "latitude" => vec![
-                global.geolocation.as_ref()
-                    .and_then(|g| g.coordinates.get(0))
-                    .copied()
-                    .unwrap_or(0.0)
-            ],
-            "longitude" => vec![
-                global.geolocation.as_ref()
-                    .and_then(|g| g.coordinates.get(1))
-                    .copied()
-                    .unwrap_or(0.0)
-            ],
-            "geo_type" => vec![
-                global.geolocation.as_ref()
-                    .map(|g| g.geo_type.clone())
-                    .unwrap_or_default()
-            ],
-            
+            sample_rate_hz: global.sample_rate,
+            datatype: global.datatype.clone(),
+            sigmf_version: global.version.clone(),
+            author: global.author.clone().unwrap_or_default(),
+            hardware: global.hardware.clone().unwrap_or_default(),
+            num_detected_sigs: num_linked_rows,
+            // Geolocation, a GeoJSON point: [longitude, latitude, altitude]
+            latitude: global.geolocation.as_ref()
+                .and_then(|g| g.coordinates.get(1))
+                .copied()
+                .unwrap_or(0.0),
+            longitude: global.geolocation.as_ref()
+                .and_then(|g| g.coordinates.first())
+                .copied()
+                .unwrap_or(0.0),
+            geo_type: global.geolocation.as_ref()
+                .map(|g| g.geo_type.clone())
+                .unwrap_or_default(),
+
             // Capture information
-            "center_freq_hz" => vec![
-                capture_with_freq
-                    .and_then(|c| c.frequency)
-                    .unwrap_or(0.0)
-            ],
-            "capture_datetime" => vec![
-                capture_with_datetime
-                    .and_then(|c| c.timestamp.clone())
-                    .unwrap_or_default()
-            ],
-            "gain" => vec![
-                capture_with_ds_info
-                    .and_then(|c| c.gain)
-                    .unwrap_or(0.0)
-            ],
-            "agc" => vec![
-                capture_with_ds_info
-                    .and_then(|c| c.agc)
-                    .unwrap_or(false)
-            ],
-            "sequence_num" => vec![
-                capture_with_ds_info
-                    .and_then(|c| c.sequence_num)
-                    .unwrap_or(0)
-            ],
-            
+            center_freq_hz: capture_with_freq.and_then(|c| c.frequency).unwrap_or(0.0),
+            capture_datetime: capture_with_datetime.and_then(|c| c.timestamp.clone()).unwrap_or_default(),
+            gain: capture_with_ds_info.and_then(|c| c.gain).unwrap_or(0.0),
+            agc: capture_with_ds_info.and_then(|c| c.agc).unwrap_or(false),
+            sequence_num: capture_with_ds_info.and_then(|c| c.sequence_num).unwrap_or(0),
+
             // Classical Signal Processing Derived Estimates
-            "snr_db" => vec![ml_annotation.and_then(|a| a.sig_snr).unwrap_or(0.0)],
-            "power_dbm" => vec![ml_annotation.and_then(|a| a.sig_power_dbm).unwrap_or(0.0)],
-            "power_dbfs" => vec![ml_annotation.and_then(|a| a.sig_power_dbfs).unwrap_or(0.0)],
-            "sig_bandwidth_hz" => vec![ml_annotation.and_then(|a| a.sig_bandwidth).unwrap_or(0.0)],
-            "sig_center_freq_hz" => vec![ml_annotation.and_then(|a| a.sig_center_freq).unwrap_or(0.0)],
-            
+            snr_db: ml_annotation.and_then(|a| a.sig_snr).unwrap_or(0.0),
+            power_dbm: ml_annotation.and_then(|a| a.sig_power_dbm).unwrap_or(0.0),
+            power_dbfs: ml_annotation.and_then(|a| a.sig_power_dbfs).unwrap_or(0.0),
+            sig_bandwidth_hz: ml_annotation.and_then(|a| a.sig_bandwidth).unwrap_or(0.0),
+            sig_center_freq_hz: ml_annotation.and_then(|a| a.sig_center_freq).unwrap_or(0.0),
+
             // Modulation probabilities
-            "ml_ask_prob" => vec![ml_annotation.and_then(|a| a.ask_prob).unwrap_or(0.0)],
-            "ml_psk_prob" => vec![ml_annotation.and_then(|a| a.psk_prob).unwrap_or(0.0)],
-            "ml_fsk_prob" => vec![ml_annotation.and_then(|a| a.fsk_prob).unwrap_or(0.0)],
-            "ml_am_prob" => vec![ml_annotation.and_then(|a| a.analog_am_prob).unwrap_or(0.0)],
-            "ml_fm_prob" => vec![ml_annotation.and_then(|a| a.analog_fm_prob).unwrap_or(0.0)],
-            "ml_ook_prob" => vec![ml_annotation.and_then(|a| a.ook_prob).unwrap_or(0.0)],
-            "ml_chirp_prob" => vec![ml_annotation.and_then(|a| a.chirp_prob).unwrap_or(0.0)],
-            "ml_constellation_prob" => vec![ml_annotation.and_then(|a| a.constellation_prob).unwrap_or(0.0)],
-            "ml_css_prob" => vec![ml_annotation.and_then(|a| a.css_prob).unwrap_or(0.0)],
-            
+            ml_ask_prob: ml_annotation.and_then(|a| a.ask_prob).unwrap_or(0.0),
+            ml_psk_prob: ml_annotation.and_then(|a| a.psk_prob).unwrap_or(0.0),
+            ml_fsk_prob: ml_annotation.and_then(|a| a.fsk_prob).unwrap_or(0.0),
+            ml_am_prob: ml_annotation.and_then(|a| a.analog_am_prob).unwrap_or(0.0),
+            ml_fm_prob: ml_annotation.and_then(|a| a.analog_fm_prob).unwrap_or(0.0),
+            ml_ook_prob: ml_annotation.and_then(|a| a.ook_prob).unwrap_or(0.0),
+            ml_chirp_prob: ml_annotation.and_then(|a| a.chirp_prob).unwrap_or(0.0),
+            ml_constellation_prob: ml_annotation.and_then(|a| a.constellation_prob).unwrap_or(0.0),
+            ml_css_prob: ml_annotation.and_then(|a| a.css_prob).unwrap_or(0.0),
+
             // Custom classifier results
-            "ml_wifi_prob" => vec![self.get_custom_classifier_prob_for_annotation(ml_annotation, "wifi").unwrap_or(0.0)],
-            "ml_cell_prob" => vec![self.get_custom_classifier_prob_for_annotation(ml_annotation, "cell").unwrap_or(0.0)],
-            "ml_radar_prob" => vec![self.get_custom_classifier_prob_for_annotation(ml_annotation, "radar").unwrap_or(0.0)],
-            
+            ml_wifi_prob: self.get_custom_classifier_prob_for_annotation(ml_annotation, "wifi").unwrap_or(0.0),
+            ml_cell_prob: self.get_custom_classifier_prob_for_annotation(ml_annotation, "cell").unwrap_or(0.0),
+            ml_radar_prob: self.get_custom_classifier_prob_for_annotation(ml_annotation, "radar").unwrap_or(0.0),
+
             // Boolean flags
-            "ml_no_sig" => vec![ml_annotation.and_then(|a| a.ml_no_sig).unwrap_or(false)],
-            
+            ml_no_sig: ml_annotation.and_then(|a| a.ml_no_sig).unwrap_or(false),
+
             // String identifiers
-            "sig_uuid" => vec![ml_annotation.and_then(|a| a.uuid.clone()).unwrap_or_default()],
-            "sdr_handle" => vec![ml_annotation.and_then(|a| a.sdr_handle.clone()).unwrap_or_default()],
-            
+            sig_uuid: ml_annotation.and_then(|a| a.uuid.clone()).unwrap_or_default(),
+            label: match ml_annotation {
+                Some(a) => a.label.clone(),
+                None => self.recording_annotation_index()
+                    .and_then(|idx| self.metadata.annotations.as_ref()?[idx].label.clone()),
+            }.unwrap_or_default(),
+            sdr_handle: ml_annotation.and_then(|a| a.sdr_handle.clone()).unwrap_or_default(),
+
             // Annotation frequency ranges
-            "freq_lower_edge_hz" => vec![
-                self.metadata.annotations.as_ref()
-                    .and_then(|anns| anns.first())
-                    .and_then(|ann| ann.freq_lower_edge)
-                    .unwrap_or(0.0)
-            ],
-            "freq_upper_edge_hz" => vec![
-                self.metadata.annotations.as_ref()
-                    .and_then(|anns| anns.first())
-                    .and_then(|ann| ann.freq_upper_edge)
-                    .unwrap_or(0.0)
-            ],
-        }?;
-        
-        Ok(df)
+            freq_lower_edge_hz: first_annotation.and_then(|ann| ann.freq_lower_edge).unwrap_or(0.0),
+            freq_upper_edge_hz: first_annotation.and_then(|ann| ann.freq_upper_edge).unwrap_or(0.0),
+
+            annotation_index: None,
+            capture: None,
+        }
     }
 
     pub fn to_summary_row(&self) -> Result<DataFrame> {
@@ -282,5 +460,74 @@ impl SigMFParser{
     pub fn get_captures(&self) -> &Vec<super::CaptureInfo> {
         &self.metadata.captures
     }
+
+    /// Samples in the data file, from its size
+    pub fn num_samples(&self) -> u64 {
+        self.data_location.size / self.data_type.sample_size_bytes() as u64
+    }
+
+    /// Center frequency of the first capture that declares one
+    pub fn center_frequency(&self) -> Option<f64> {
+        self.metadata.captures.iter().find_map(|c| c.frequency)
+    }
+
+    /// Annotation counts in `buckets` equal slices of the recording, bucketed
+    /// by `core:sample_start`. Uses the annotation extent when the data file
+    /// is missing.
+    pub fn annotation_density(&self, buckets: usize) -> Vec<u64> {
+        let mut counts = vec![0; buckets];
+        let annotations = match self.metadata.annotations.as_ref() {
+            Some(annotations) if buckets > 0 && !annotations.is_empty() => annotations,
+            _ => return counts,
+        };
+        let total_samples = self.num_samples()
+            .max(annotations.iter().map(|a| a.sample_start + a.sample_count).max().unwrap_or(0))
+            .max(1);
+        for annotation in annotations {
+            let bucket = (annotation.sample_start as u128 * buckets as u128 / total_samples as u128) as usize;
+            counts[bucket.min(buckets - 1)] += 1;
+        }
+        counts
+    }
+
+    pub fn open_reader(&self) -> Result<Box<dyn SampleReader + Send>> {
+        match &self.open_samples {
+            Some(open_samples) => open_samples(),
+            None => Ok(Box::new(SigMFReader::open_location(&self.data_location, self.data_type.clone())?)),
+        }
+    }
+
+    /// Hash the data file and compare it with `core:sha512`
+    pub fn verify_checksum(&self) -> Result<ChecksumStatus> {
+        verify_sha512(&self.data_location, self.metadata.global.sha512.as_deref())
+    }
+
+    /// [`Self::verify_checksum`] with progress reports; see
+    /// [`verify_sha512_with_progress`]
+    pub fn verify_checksum_with_progress(&self, on_progress: impl FnMut(f32) -> Result<()>) -> Result<ChecksumStatus> {
+        verify_sha512_with_progress(&self.data_location, self.metadata.global.sha512.as_deref(), on_progress)
+    }
 }
 
+/// Duration of `samples` at `sample_rate`, 0 for a recording without a
+/// usable rate rather than infinite
+fn samples_to_seconds(samples: u64, sample_rate: f64) -> f64 {
+    if sample_rate > 0.0 {
+        samples as f64 / sample_rate
+    } else {
+        0.0
+    }
+}
+
+/// An annotation's scalar fields the parser doesn't know, sorted by key.
+/// `core:` fields are left out: they're the spec's own, not extensions.
+fn extra_fields(ann: &super::AnnotationInfo) -> Vec<(&str, &serde_json::Value)> {
+    let mut fields: Vec<_> = ann.extra_fields
+        .iter()
+        .filter(|(key, _)| !key.starts_with("core:"))
+        .filter(|(_, value)| value.is_string() || value.is_number() || value.is_boolean())
+        .map(|(key, value)| (key.as_str(), value))
+        .collect();
+    fields.sort_by_key(|(key, _)| *key);
+    fields
+}
@@ -0,0 +1,177 @@
+use super::compression::{decompressed_path, decompressed_size, open_decompressed, Compression};
+use super::SigMFDataType;
+use crate::parser::SampleReader;
+use anyhow::Result;
+use memmap2::Mmap;
+use num_complex::Complex;
+use std::borrow::Cow;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// `(offset, len)` of each piece of data split across packets, in file order
+pub type Pieces = Arc<Vec<(u64, u64)>>;
+
+/// Where a recording's samples live: a whole `.sigmf-data` file (possibly
+/// compressed, see [`super::compression`]), a byte range of an archive or
+/// WAV file, or the payloads of a packet stream
+#[derive(Debug, Clone)]
+pub struct DataLocation {
+    pub file: PathBuf,
+    pub offset: u64,
+    pub size: u64,
+    /// For samples split across packets (VITA-49), the payloads. `size` is
+    /// then their total and `offset` the first one's.
+    pub pieces: Option<Pieces>,
+}
+
+impl DataLocation {
+    /// A whole file; for a compressed one, `size` is that of the data
+    /// decompressed
+    pub fn whole_file<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let size = match Compression::from_path(path) {
+            Some(compression) => decompressed_size(path, compression)?,
+            None => std::fs::metadata(path)?.len(),
+        };
+        Ok(DataLocation {
+            file: path.to_path_buf(),
+            offset: 0,
+            size,
+            pieces: None,
+        })
+    }
+
+    /// The compression of a whole compressed data file
+    fn compression(&self) -> Option<Compression> {
+        Compression::from_path(&self.file).filter(|_| self.offset == 0 && self.pieces.is_none())
+    }
+
+    /// Open positioned at the start of the data, limited to its size
+    pub fn open(&self) -> Result<Box<dyn Read + Send>> {
+        if let Some(compression) = self.compression() {
+            return Ok(Box::new(open_decompressed(&self.file, compression)?.take(self.size)));
+        }
+        let mut file = File::open(&self.file)?;
+        if let Some(pieces) = &self.pieces {
+            return Ok(Box::new(PieceReader { file, pieces: pieces.clone(), next: 0, left: 0 }));
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        Ok(Box::new(file.take(self.size)))
+    }
+}
+
+/// Reads the pieces of a location one after the other
+struct PieceReader {
+    file: File,
+    pieces: Pieces,
+    next: usize,
+    /// Bytes of the current piece not read yet
+    left: u64,
+}
+
+impl Read for PieceReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.left == 0 {
+            let Some(&(offset, len)) = self.pieces.get(self.next) else {
+                return Ok(0);
+            };
+            self.file.seek(SeekFrom::Start(offset))?;
+            self.next += 1;
+            self.left = len;
+        }
+        let wanted = buf.len().min(self.left as usize);
+        let n = self.file.read(&mut buf[..wanted])?;
+        self.left -= n as u64;
+        Ok(n)
+    }
+}
+
+/// Reads samples straight out of a `.sigmf-data` file (or archive member).
+/// The file is memory-mapped, so a read only pages in the bytes of the
+/// samples asked for: a spectrogram of a multi-gigabyte recording touches a
+/// few windows of it, not the whole file.
+pub struct SigMFReader {
+    map: Mmap,
+    offset: u64,
+    /// Pieces of a split location, with where each starts in the samples' bytes
+    pieces: Option<(Pieces, Vec<u64>)>,
+    data_type: SigMFDataType,
+    num_samples: u64,
+}
+
+impl SigMFReader {
+    pub fn open<P: AsRef<Path>>(data_path: P, data_type: SigMFDataType) -> Result<Self> {
+        Self::open_location(&DataLocation::whole_file(data_path)?, data_type)
+    }
+
+    pub fn open_location(location: &DataLocation, data_type: SigMFDataType) -> Result<Self> {
+        let num_samples = location.size / data_type.sample_size_bytes() as u64;
+        let pieces = location.pieces.as_ref().map(|pieces| {
+            let mut starts = Vec::with_capacity(pieces.len());
+            let mut total = 0;
+            for (_, len) in pieces.iter() {
+                starts.push(total);
+                total += len;
+            }
+            (pieces.clone(), starts)
+        });
+        let file = match location.compression() {
+            Some(compression) => File::open(decompressed_path(&location.file, compression)?)?,
+            None => File::open(&location.file)?,
+        };
+        // SAFETY: the map is only read. Recordings aren't written once
+        // captured; one truncated while mapped faults instead of erroring.
+        let map = unsafe { Mmap::map(&file)? };
+        Ok(SigMFReader {
+            map,
+            offset: location.offset,
+            pieces,
+            data_type,
+            num_samples,
+        })
+    }
+
+    /// The mapped bytes at `offset` in the file
+    fn mapped(&self, offset: u64, len: usize) -> Result<&[u8]> {
+        let start = usize::try_from(offset)?;
+        self.map
+            .get(start..start + len)
+            .ok_or_else(|| anyhow::anyhow!("Data file ends before byte {} its metadata covers", offset + len as u64))
+    }
+
+    /// `len` bytes from `position` in the samples' bytes, borrowed from the
+    /// map unless they're split across packets
+    fn read_bytes(&self, position: u64, len: usize) -> Result<Cow<'_, [u8]>> {
+        let Some((pieces, starts)) = &self.pieces else {
+            return Ok(Cow::Borrowed(self.mapped(self.offset + position, len)?));
+        };
+        let mut buffer = Vec::with_capacity(len);
+        let mut idx = starts.partition_point(|&start| start <= position).saturating_sub(1);
+        while buffer.len() < len {
+            let (offset, piece_len) = *pieces.get(idx).ok_or_else(|| anyhow::anyhow!("Read past the last packet"))?;
+            let within = (position + buffer.len() as u64).saturating_sub(starts[idx]);
+            let n = ((piece_len - within) as usize).min(len - buffer.len());
+            buffer.extend_from_slice(self.mapped(offset + within, n)?);
+            idx += 1;
+        }
+        Ok(Cow::Owned(buffer))
+    }
+}
+
+impl SampleReader for SigMFReader {
+    fn num_samples(&self) -> u64 {
+        self.num_samples
+    }
+
+    fn read_samples(&mut self, start: u64, count: usize) -> Result<Vec<Complex<f32>>> {
+        if start >= self.num_samples {
+            return Ok(Vec::new());
+        }
+        let count = count.min((self.num_samples - start) as usize);
+        let sample_size = self.data_type.sample_size_bytes();
+        let bytes = self.read_bytes(start * sample_size as u64, count * sample_size)?;
+        self.data_type.decode_samples(&bytes)
+    }
+}
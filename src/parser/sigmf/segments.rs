@@ -0,0 +1,265 @@
+use super::{normalize, SigMFDataset, SigMFParser};
+use crate::parser::{FileParser, SampleReader};
+use anyhow::Result;
+use num_complex::Complex;
+use polars::prelude::*;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// One file of a capture the recorder split into consecutive files, numbered
+/// by `ds:sequence_num`
+#[derive(Debug, Clone)]
+pub struct Segment {
+    pub meta_path: PathBuf,
+    pub sequence_num: u64,
+    pub num_samples: u64,
+    pub sample_rate: f64,
+    pub center_freq: Option<f64>,
+    pub datatype: String,
+    pub hardware: Option<String>,
+}
+
+impl Segment {
+    /// `None` for recordings without a `ds:sequence_num`
+    pub fn from_parser<P: AsRef<Path>>(meta_path: P, parser: &SigMFParser) -> Option<Self> {
+        let global = &parser.metadata.global;
+        Some(Segment {
+            meta_path: meta_path.as_ref().to_path_buf(),
+            sequence_num: parser.get_captures().iter().find_map(|c| c.sequence_num)?,
+            num_samples: parser.num_samples(),
+            sample_rate: global.sample_rate,
+            center_freq: parser.center_frequency(),
+            datatype: global.datatype.clone(),
+            hardware: global.hardware.clone(),
+        })
+    }
+
+    /// Segments of the same capture share a directory (or archive) and
+    /// recording parameters
+    fn group_key(&self) -> (PathBuf, u64, Option<u64>, String, Option<String>) {
+        (
+            self.meta_path.parent().map(Path::to_path_buf).unwrap_or_default(),
+            self.sample_rate.to_bits(),
+            self.center_freq.map(f64::to_bits),
+            self.datatype.clone(),
+            self.hardware.clone(),
+        )
+    }
+}
+
+/// Consecutive segments of one capture, in sequence order, treated as a
+/// single virtual recording
+#[derive(Debug, Clone)]
+pub struct SegmentedRecording {
+    pub segments: Vec<Segment>,
+}
+
+impl SegmentedRecording {
+    /// Named after the first segment's meta file
+    pub fn name(&self) -> String {
+        self.segments[0]
+            .meta_path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+
+    pub fn sample_rate(&self) -> f64 {
+        self.segments[0].sample_rate
+    }
+
+    pub fn center_freq(&self) -> Option<f64> {
+        self.segments[0].center_freq
+    }
+
+    pub fn total_samples(&self) -> u64 {
+        self.segments.iter().map(|s| s.num_samples).sum()
+    }
+
+    pub fn duration_s(&self) -> f64 {
+        self.total_samples() as f64 / self.sample_rate()
+    }
+
+    /// Sample offset of each segment within the stitched recording
+    pub fn segment_starts(&self) -> Vec<u64> {
+        self.segments
+            .iter()
+            .scan(0, |start, segment| {
+                let this = *start;
+                *start += segment.num_samples;
+                Some(this)
+            })
+            .collect()
+    }
+
+    /// Index of the segment holding stitched sample `sample`
+    pub fn segment_at(&self, sample: u64) -> usize {
+        self.segment_starts()
+            .iter()
+            .rposition(|&start| start <= sample)
+            .unwrap_or(0)
+    }
+
+    /// Index of `meta_path` among the segments, comparing canonical paths
+    /// where possible (virtual archive paths can't be canonicalized)
+    pub fn position_of(&self, meta_path: &Path) -> Option<usize> {
+        let canonical = |path: &Path| path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let wanted = canonical(meta_path);
+        self.segments.iter().position(|s| canonical(&s.meta_path) == wanted)
+    }
+
+    pub fn open_reader(&self) -> Result<StitchedReader> {
+        let readers = self
+            .segments
+            .iter()
+            .map(|segment| FileParser::open_reader(&segment.meta_path))
+            .collect::<Result<Vec<_>>>()?;
+        Ok(StitchedReader::new(readers))
+    }
+
+    /// The segmented capture `meta_path` belongs to, from the segment
+    /// columns of `dataset` (scanned from `root`) rather than a rescan of
+    /// its directory. `None` if it's standalone or not in the dataset.
+    pub fn from_dataset(dataset: &DataFrame, root: &Path, meta_path: &Path) -> Result<Option<Self>> {
+        let (Ok(groups), Ok(indexes)) = (dataset.column("segment_group"), dataset.column("segment_index")) else {
+            return Ok(None);
+        };
+        let indexes = indexes.cast(&DataType::UInt32)?;
+        let (groups, indexes) = (groups.str()?, indexes.u32()?);
+        let paths = SigMFDataset::row_meta_paths(dataset, root)?;
+        // Views get the path the dataset gives, so try that before canonical paths
+        let row = paths.iter().position(|path| path.as_deref() == Some(meta_path)).or_else(|| {
+            let wanted = normalize(meta_path);
+            paths.iter().position(|path| path.as_deref().is_some_and(|path| normalize(path) == wanted))
+        });
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let (Some(group), Some(dir)) = (groups.get(row).filter(|group| !group.is_empty()), paths[row].as_deref().and_then(Path::parent)) else {
+            return Ok(None);
+        };
+
+        // Rows per annotation repeat a segment; keep its first
+        let mut members = BTreeMap::new();
+        for (row, segment) in dataset_segments(dataset, &paths)?.into_iter().enumerate() {
+            let Some(segment) = segment.filter(|segment| segment.meta_path.parent() == Some(dir)) else {
+                continue;
+            };
+            if let Some(index) = indexes.get(row).filter(|_| groups.get(row) == Some(group)) {
+                members.entry(index).or_insert(segment);
+            }
+        }
+        let recording = SegmentedRecording { segments: members.into_values().collect() };
+        Ok(Some(recording).filter(|recording| recording.segments.len() > 1))
+    }
+}
+
+/// Group segments into recordings: same directory and recording parameters,
+/// consecutive sequence numbers. A gap or a repeated number starts a new
+/// recording, and single files are left out.
+pub fn group_segments(segments: Vec<Segment>) -> Vec<SegmentedRecording> {
+    let mut by_key: BTreeMap<_, Vec<Segment>> = BTreeMap::new();
+    for segment in segments {
+        by_key.entry(segment.group_key()).or_default().push(segment);
+    }
+
+    let mut recordings = Vec::new();
+    for (_, mut segments) in by_key {
+        segments.sort_by(|a, b| a.sequence_num.cmp(&b.sequence_num).then_with(|| a.meta_path.cmp(&b.meta_path)));
+        let mut run: Vec<Segment> = Vec::new();
+        for segment in segments {
+            if run.last().is_some_and(|last| segment.sequence_num != last.sequence_num + 1) {
+                recordings.push(SegmentedRecording { segments: std::mem::take(&mut run) });
+            }
+            run.push(segment);
+        }
+        recordings.push(SegmentedRecording { segments: run });
+    }
+    recordings.retain(|recording| recording.segments.len() > 1);
+    recordings
+}
+
+/// Each row's recording as a [`Segment`], from the summary columns instead
+/// of its meta file; `meta_paths` are the rows' meta files, as from
+/// `SigMFDataset::row_meta_paths`. The columns can't tell a `sequence_num`
+/// of 0 from a missing one, so a row numbered 0 only counts as a segment
+/// while `segment_count` puts it in one. `None` for other rows, and for
+/// every row of a dataset without the segment columns.
+pub fn dataset_segments(dataset: &DataFrame, meta_paths: &[Option<PathBuf>]) -> Result<Vec<Option<Segment>>> {
+    let column = |name: &str, dtype: DataType| -> Result<Series> { Ok(dataset.column(name)?.cast(&dtype)?) };
+    let (Ok(sequence_nums), Ok(counts)) = (column("sequence_num", DataType::UInt64), column("segment_count", DataType::UInt64)) else {
+        return Ok(vec![None; dataset.height()]);
+    };
+    let num_samples = column("num_samples", DataType::UInt64)?;
+    let sample_rates = column("sample_rate_hz", DataType::Float64)?;
+    let datatypes = column("datatype", DataType::String)?;
+    let center_freqs = column("center_freq_hz", DataType::Float64).ok();
+    let hardware = column("hardware", DataType::String).ok();
+    let (sequence_nums, counts, num_samples, sample_rates, datatypes) =
+        (sequence_nums.u64()?, counts.u64()?, num_samples.u64()?, sample_rates.f64()?, datatypes.str()?);
+    let center_freqs = center_freqs.as_ref().map(|c| c.f64()).transpose()?;
+    let hardware = hardware.as_ref().map(|h| h.str()).transpose()?;
+    Ok((0..dataset.height())
+        .map(|row| {
+            let sequence_num = sequence_nums.get(row)?;
+            if sequence_num == 0 && counts.get(row).unwrap_or(1) <= 1 {
+                return None;
+            }
+            Some(Segment {
+                meta_path: meta_paths.get(row)?.clone()?,
+                sequence_num,
+                num_samples: num_samples.get(row)?,
+                sample_rate: sample_rates.get(row)?,
+                // Recordings without a center frequency or hardware get 0 and
+                // an empty string in the summary
+                center_freq: center_freqs.and_then(|c| c.get(row)).filter(|freq| *freq != 0.0),
+                datatype: datatypes.get(row)?.to_string(),
+                hardware: hardware.and_then(|h| h.get(row)).filter(|h| !h.is_empty()).map(str::to_string),
+            })
+        })
+        .collect())
+}
+
+/// Reads across the files of a segmented recording as if they were one
+pub struct StitchedReader {
+    readers: Vec<Box<dyn SampleReader + Send>>,
+    starts: Vec<u64>,
+    total: u64,
+}
+
+impl StitchedReader {
+    pub fn new(readers: Vec<Box<dyn SampleReader + Send>>) -> Self {
+        let mut starts = Vec::with_capacity(readers.len());
+        let mut total = 0;
+        for reader in &readers {
+            starts.push(total);
+            total += reader.num_samples();
+        }
+        StitchedReader { readers, starts, total }
+    }
+}
+
+impl SampleReader for StitchedReader {
+    fn num_samples(&self) -> u64 {
+        self.total
+    }
+
+    fn read_samples(&mut self, start: u64, count: usize) -> Result<Vec<Complex<f32>>> {
+        let end = start.saturating_add(count as u64).min(self.total);
+        let mut samples = Vec::with_capacity(end.saturating_sub(start) as usize);
+        let mut position = start;
+        while position < end {
+            let idx = self.starts.iter().rposition(|&s| s <= position).unwrap_or(0);
+            let reader = &mut self.readers[idx];
+            let local = position - self.starts[idx];
+            let wanted = (end - position).min(reader.num_samples() - local) as usize;
+            let chunk = reader.read_samples(local, wanted)?;
+            if chunk.is_empty() {
+                break;
+            }
+            position += chunk.len() as u64;
+            samples.extend(chunk);
+        }
+        Ok(samples)
+    }
+}
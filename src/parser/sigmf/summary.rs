@@ -0,0 +1,265 @@
+use super::Granularity;
+use crate::data_ops::{add_band_columns, add_time_columns};
+use anyhow::Result;
+use polars::prelude::*;
+
+/// One dataset row as parsed from a meta file, before it goes into a column
+#[derive(Debug, Clone, Default)]
+pub struct SummaryRow {
+    pub meta_filename: String,
+    pub data_filename: String,
+    pub num_samples: u64,
+    pub file_size_bytes: u64,
+    pub duration_s: f64,
+    pub sample_rate_hz: f64,
+    pub datatype: String,
+    pub sigmf_version: String,
+    pub author: String,
+    pub hardware: String,
+    pub num_detected_sigs: u64,
+    pub latitude: f64,
+    pub longitude: f64,
+    pub geo_type: String,
+    pub center_freq_hz: f64,
+    pub capture_datetime: String,
+    pub gain: f64,
+    pub agc: bool,
+    pub sequence_num: u64,
+    pub snr_db: f64,
+    pub power_dbm: f64,
+    pub power_dbfs: f64,
+    pub sig_bandwidth_hz: f64,
+    pub sig_center_freq_hz: f64,
+    pub ml_ask_prob: f64,
+    pub ml_psk_prob: f64,
+    pub ml_fsk_prob: f64,
+    pub ml_am_prob: f64,
+    pub ml_fm_prob: f64,
+    pub ml_ook_prob: f64,
+    pub ml_chirp_prob: f64,
+    pub ml_constellation_prob: f64,
+    pub ml_css_prob: f64,
+    pub ml_wifi_prob: f64,
+    pub ml_cell_prob: f64,
+    pub ml_radar_prob: f64,
+    pub ml_no_sig: bool,
+    pub sig_uuid: String,
+    pub label: String,
+    pub sdr_handle: String,
+    pub freq_lower_edge_hz: f64,
+    pub freq_upper_edge_hz: f64,
+    /// Position of the row's ML annotation in the annotations array, with
+    /// `Granularity::Annotation`
+    pub annotation_index: Option<u32>,
+    /// The capture segment a row stands for, with `Granularity::Capture`
+    pub capture: Option<CaptureSpan>,
+}
+
+/// Where a capture segment sits in its recording
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CaptureSpan {
+    pub index: u32,
+    pub sample_start: u64,
+    pub sample_count: u64,
+    pub duration_s: f64,
+}
+
+/// Summary columns filled row by row, turned into a DataFrame once at the
+/// end instead of stacking a one-row frame per row
+#[derive(Default)]
+pub struct SummaryColumns {
+    granularity: Granularity,
+    meta_filename: Vec<String>,
+    data_filename: Vec<String>,
+    num_samples: Vec<u64>,
+    file_size_bytes: Vec<u64>,
+    duration_s: Vec<f64>,
+    sample_rate_hz: Vec<f64>,
+    datatype: Vec<String>,
+    sigmf_version: Vec<String>,
+    author: Vec<String>,
+    hardware: Vec<String>,
+    num_detected_sigs: Vec<u64>,
+    latitude: Vec<f64>,
+    longitude: Vec<f64>,
+    geo_type: Vec<String>,
+    center_freq_hz: Vec<f64>,
+    capture_datetime: Vec<String>,
+    gain: Vec<f64>,
+    agc: Vec<bool>,
+    sequence_num: Vec<u64>,
+    snr_db: Vec<f64>,
+    power_dbm: Vec<f64>,
+    power_dbfs: Vec<f64>,
+    sig_bandwidth_hz: Vec<f64>,
+    sig_center_freq_hz: Vec<f64>,
+    ml_ask_prob: Vec<f64>,
+    ml_psk_prob: Vec<f64>,
+    ml_fsk_prob: Vec<f64>,
+    ml_am_prob: Vec<f64>,
+    ml_fm_prob: Vec<f64>,
+    ml_ook_prob: Vec<f64>,
+    ml_chirp_prob: Vec<f64>,
+    ml_constellation_prob: Vec<f64>,
+    ml_css_prob: Vec<f64>,
+    ml_wifi_prob: Vec<f64>,
+    ml_cell_prob: Vec<f64>,
+    ml_radar_prob: Vec<f64>,
+    ml_no_sig: Vec<bool>,
+    sig_uuid: Vec<String>,
+    label: Vec<String>,
+    sdr_handle: Vec<String>,
+    freq_lower_edge_hz: Vec<f64>,
+    freq_upper_edge_hz: Vec<f64>,
+    annotation_index: Vec<Option<u32>>,
+    capture_index: Vec<u32>,
+    capture_sample_start: Vec<u64>,
+    capture_sample_count: Vec<u64>,
+    capture_duration_s: Vec<f64>,
+}
+
+impl SummaryColumns {
+    /// Columns for rows of `granularity`; annotation rows get
+    /// `annotation_index` and capture rows the `capture_*` columns
+    pub fn new(granularity: Granularity) -> Self {
+        SummaryColumns { granularity, ..Default::default() }
+    }
+
+    pub fn len(&self) -> usize {
+        self.meta_filename.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.meta_filename.is_empty()
+    }
+
+    pub fn push(&mut self, row: SummaryRow) {
+        self.meta_filename.push(row.meta_filename);
+        self.data_filename.push(row.data_filename);
+        self.num_samples.push(row.num_samples);
+        self.file_size_bytes.push(row.file_size_bytes);
+        self.duration_s.push(row.duration_s);
+        self.sample_rate_hz.push(row.sample_rate_hz);
+        self.datatype.push(row.datatype);
+        self.sigmf_version.push(row.sigmf_version);
+        self.author.push(row.author);
+        self.hardware.push(row.hardware);
+        self.num_detected_sigs.push(row.num_detected_sigs);
+        self.latitude.push(row.latitude);
+        self.longitude.push(row.longitude);
+        self.geo_type.push(row.geo_type);
+        self.center_freq_hz.push(row.center_freq_hz);
+        self.capture_datetime.push(row.capture_datetime);
+        self.gain.push(row.gain);
+        self.agc.push(row.agc);
+        self.sequence_num.push(row.sequence_num);
+        self.snr_db.push(row.snr_db);
+        self.power_dbm.push(row.power_dbm);
+        self.power_dbfs.push(row.power_dbfs);
+        self.sig_bandwidth_hz.push(row.sig_bandwidth_hz);
+        self.sig_center_freq_hz.push(row.sig_center_freq_hz);
+        self.ml_ask_prob.push(row.ml_ask_prob);
+        self.ml_psk_prob.push(row.ml_psk_prob);
+        self.ml_fsk_prob.push(row.ml_fsk_prob);
+        self.ml_am_prob.push(row.ml_am_prob);
+        self.ml_fm_prob.push(row.ml_fm_prob);
+        self.ml_ook_prob.push(row.ml_ook_prob);
+        self.ml_chirp_prob.push(row.ml_chirp_prob);
+        self.ml_constellation_prob.push(row.ml_constellation_prob);
+        self.ml_css_prob.push(row.ml_css_prob);
+        self.ml_wifi_prob.push(row.ml_wifi_prob);
+        self.ml_cell_prob.push(row.ml_cell_prob);
+        self.ml_radar_prob.push(row.ml_radar_prob);
+        self.ml_no_sig.push(row.ml_no_sig);
+        self.sig_uuid.push(row.sig_uuid);
+        self.label.push(row.label);
+        self.sdr_handle.push(row.sdr_handle);
+        self.freq_lower_edge_hz.push(row.freq_lower_edge_hz);
+        self.freq_upper_edge_hz.push(row.freq_upper_edge_hz);
+        self.annotation_index.push(row.annotation_index);
+        let capture = row.capture.unwrap_or_default();
+        self.capture_index.push(capture.index);
+        self.capture_sample_start.push(capture.sample_start);
+        self.capture_sample_count.push(capture.sample_count);
+        self.capture_duration_s.push(capture.duration_s);
+    }
+
+    pub fn finish(self) -> Result<DataFrame> {
+        let mut df = df! {
+            // File identification
+            "meta_filename" => self.meta_filename,
+            "data_filename" => self.data_filename,
+
+            // Basic file info
+            "num_samples" => self.num_samples,
+            "file_size_bytes" => self.file_size_bytes,
+            "duration_s" => self.duration_s,
+
+            // Global metadata
+            "sample_rate_hz" => self.sample_rate_hz,
+            "datatype" => self.datatype,
+            "sigmf_version" => self.sigmf_version,
+            "author" => self.author,
+            "hardware" => self.hardware,
+            "num_detected_sigs" => self.num_detected_sigs,
+            // Geolocation
+            "latitude" => self.latitude,
+            "longitude" => self.longitude,
+            "geo_type" => self.geo_type,
+
+            // Capture information
+            "center_freq_hz" => self.center_freq_hz,
+            "capture_datetime" => self.capture_datetime,
+            "gain" => self.gain,
+            "agc" => self.agc,
+            "sequence_num" => self.sequence_num,
+
+            // Classical Signal Processing Derived Estimates
+            "snr_db" => self.snr_db,
+            "power_dbm" => self.power_dbm,
+            "power_dbfs" => self.power_dbfs,
+            "sig_bandwidth_hz" => self.sig_bandwidth_hz,
+            "sig_center_freq_hz" => self.sig_center_freq_hz,
+
+            // Modulation probabilities
+            "ml_ask_prob" => self.ml_ask_prob,
+            "ml_psk_prob" => self.ml_psk_prob,
+            "ml_fsk_prob" => self.ml_fsk_prob,
+            "ml_am_prob" => self.ml_am_prob,
+            "ml_fm_prob" => self.ml_fm_prob,
+            "ml_ook_prob" => self.ml_ook_prob,
+            "ml_chirp_prob" => self.ml_chirp_prob,
+            "ml_constellation_prob" => self.ml_constellation_prob,
+            "ml_css_prob" => self.ml_css_prob,
+
+            // Custom classifier results
+            "ml_wifi_prob" => self.ml_wifi_prob,
+            "ml_cell_prob" => self.ml_cell_prob,
+            "ml_radar_prob" => self.ml_radar_prob,
+
+            // Boolean flags
+            "ml_no_sig" => self.ml_no_sig,
+
+            // String identifiers
+            "sig_uuid" => self.sig_uuid,
+            "label" => self.label,
+            "sdr_handle" => self.sdr_handle,
+
+            // Annotation frequency ranges
+            "freq_lower_edge_hz" => self.freq_lower_edge_hz,
+            "freq_upper_edge_hz" => self.freq_upper_edge_hz,
+        }?;
+        if self.granularity == Granularity::Annotation {
+            df.with_column(Series::new("annotation_index".into(), self.annotation_index))?;
+        }
+        if self.granularity == Granularity::Capture {
+            df.with_column(Series::new("capture_index".into(), self.capture_index))?;
+            df.with_column(Series::new("capture_sample_start".into(), self.capture_sample_start))?;
+            df.with_column(Series::new("capture_sample_count".into(), self.capture_sample_count))?;
+            df.with_column(Series::new("capture_duration_s".into(), self.capture_duration_s))?;
+        }
+        add_time_columns(&mut df)?;
+        add_band_columns(&mut df)?;
+        Ok(df)
+    }
+}
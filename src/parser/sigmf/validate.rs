@@ -0,0 +1,208 @@
+use anyhow::Result;
+use serde_json::Value;
+use std::path::{Path, PathBuf};
+use super::{nominal_data_path, resolve_data_path, SigMFDataset};
+
+/// Outcome of checking one `.sigmf-meta` file against the SigMF spec
+#[derive(Debug, Clone)]
+pub struct ValidationReport {
+    pub meta_path: PathBuf,
+    pub violations: Vec<String>,
+}
+
+impl ValidationReport {
+    pub fn is_valid(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Validate a single meta file, or every meta file under a directory
+pub fn validate_path<P: AsRef<Path>>(path: P) -> Result<Vec<ValidationReport>> {
+    let path = path.as_ref();
+    if path.is_file() {
+        return Ok(vec![validate_meta_file(path)]);
+    }
+    if !path.is_dir() {
+        anyhow::bail!("Path does not exist: {:?}", path);
+    }
+
+    Ok(SigMFDataset::meta_files(path)?
+        .iter()
+        .map(validate_meta_file)
+        .collect())
+}
+
+pub fn validate_meta_file<P: AsRef<Path>>(meta_path: P) -> ValidationReport {
+    let meta_path = meta_path.as_ref();
+    let mut violations = Vec::new();
+
+    let metadata = std::fs::read_to_string(meta_path)
+        .map_err(|e| format!("Cannot read file: {}", e))
+        .and_then(|content| {
+            serde_json::from_str::<Value>(&content).map_err(|e| format!("Invalid JSON: {}", e))
+        });
+    match metadata {
+        Ok(metadata) => check_metadata(meta_path, &metadata, &mut violations),
+        Err(e) => violations.push(e),
+    }
+
+    ValidationReport {
+        meta_path: meta_path.to_path_buf(),
+        violations,
+    }
+}
+
+fn check_metadata(meta_path: &Path, metadata: &Value, violations: &mut Vec<String>) {
+    let Some(global) = metadata.get("global").and_then(Value::as_object) else {
+        violations.push("Missing required object: global".to_string());
+        return;
+    };
+
+    // Required global fields
+    if global.get("core:version").and_then(Value::as_str).is_none() {
+        violations.push("global: missing required string core:version".to_string());
+    }
+    let sample_size = match global.get("core:datatype").and_then(Value::as_str) {
+        Some(datatype) => {
+            let size = datatype_sample_size(datatype);
+            if size.is_none() {
+                violations.push(format!("global: invalid core:datatype '{}'", datatype));
+            }
+            size
+        }
+        None => {
+            violations.push("global: missing required string core:datatype".to_string());
+            None
+        }
+    };
+    if let Some(sample_rate) = global.get("core:sample_rate") {
+        if !sample_rate.as_f64().is_some_and(|rate| rate > 0.0) {
+            violations.push(format!("global: core:sample_rate must be a positive number, got {}", sample_rate));
+        }
+    }
+    let num_channels = match global.get("core:num_channels") {
+        Some(value) => match value.as_u64() {
+            Some(n) if n > 0 => n,
+            _ => {
+                violations.push(format!("global: core:num_channels must be a positive integer, got {}", value));
+                1
+            }
+        },
+        None => 1,
+    };
+
+    // Data file size consistency
+    let dataset = global.get("core:dataset").and_then(Value::as_str);
+    let data_path = resolve_data_path(meta_path, dataset).unwrap_or_else(|_| nominal_data_path(meta_path, dataset));
+    let num_samples = match std::fs::metadata(&data_path) {
+        Ok(data_meta) => sample_size.map(|size| {
+            let frame_size = size as u64 * num_channels;
+            if data_meta.len() % frame_size != 0 {
+                violations.push(format!(
+                    "data file size {} is not a multiple of the {} byte sample size",
+                    data_meta.len(),
+                    frame_size
+                ));
+            }
+            data_meta.len() / frame_size
+        }),
+        Err(_) => {
+            violations.push(format!("data file not found: {:?}", data_path.file_name().unwrap_or_default()));
+            None
+        }
+    };
+
+    // Captures: required, each with a sample_start, strictly increasing
+    match metadata.get("captures").and_then(Value::as_array) {
+        Some(captures) => {
+            let mut previous: Option<u64> = None;
+            for (idx, capture) in captures.iter().enumerate() {
+                let Some(start) = capture.get("core:sample_start").and_then(Value::as_u64) else {
+                    violations.push(format!("captures[{}]: missing core:sample_start", idx));
+                    continue;
+                };
+                if previous.is_some_and(|prev| start <= prev) {
+                    violations.push(format!(
+                        "captures[{}]: core:sample_start {} is not greater than the previous capture's",
+                        idx, start
+                    ));
+                }
+                if num_samples.is_some_and(|n| start >= n && n > 0) {
+                    violations.push(format!("captures[{}]: core:sample_start {} is beyond the end of the data", idx, start));
+                }
+                previous = Some(start);
+            }
+        }
+        None => violations.push("Missing required array: captures".to_string()),
+    }
+
+    // Annotations: optional, but must be sorted and lie within the data
+    if let Some(annotations) = metadata.get("annotations") {
+        let Some(annotations) = annotations.as_array() else {
+            violations.push("annotations must be an array".to_string());
+            return;
+        };
+        let mut previous: Option<u64> = None;
+        for (idx, annotation) in annotations.iter().enumerate() {
+            let Some(start) = annotation.get("core:sample_start").and_then(Value::as_u64) else {
+                violations.push(format!("annotations[{}]: missing core:sample_start", idx));
+                continue;
+            };
+            if previous.is_some_and(|prev| start < prev) {
+                violations.push(format!("annotations[{}]: not sorted by core:sample_start", idx));
+            }
+            previous = Some(start);
+
+            let count = annotation.get("core:sample_count").and_then(Value::as_u64);
+            if let Some(n) = num_samples {
+                let end = start + count.unwrap_or(0);
+                if end > n {
+                    violations.push(format!(
+                        "annotations[{}]: samples {}..{} extend past the end of the data ({} samples)",
+                        idx, start, end, n
+                    ));
+                }
+            }
+
+            let lower = annotation.get("core:freq_lower_edge").and_then(Value::as_f64);
+            let upper = annotation.get("core:freq_upper_edge").and_then(Value::as_f64);
+            match (lower, upper) {
+                (Some(lower), Some(upper)) if lower > upper => {
+                    violations.push(format!("annotations[{}]: core:freq_lower_edge is above core:freq_upper_edge", idx));
+                }
+                (Some(_), None) | (None, Some(_)) => {
+                    violations.push(format!("annotations[{}]: frequency edges must be given together", idx));
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+/// Bytes per sample for any SigMF datatype string (e.g. `cf32_le`, `ri16_be`,
+/// `cu8`), or None if the string isn't a valid datatype
+pub fn datatype_sample_size(datatype: &str) -> Option<usize> {
+    let (complex, rest) = match datatype.chars().next()? {
+        'c' => (true, &datatype[1..]),
+        'r' => (false, &datatype[1..]),
+        _ => return None,
+    };
+    let (format, endianness) = match rest.split_once('_') {
+        Some((format, endianness)) => (format, Some(endianness)),
+        None => (rest, None),
+    };
+    let component_size = match format {
+        "f64" | "i64" | "u64" => 8,
+        "f32" | "i32" | "u32" => 4,
+        "i16" | "u16" => 2,
+        "i8" | "u8" => 1,
+        _ => return None,
+    };
+    match endianness {
+        // Byte-sized types have no endianness; everything else requires it
+        None if component_size == 1 => {}
+        Some("le") | Some("be") if component_size > 1 => {}
+        _ => return None,
+    }
+    Some(component_size * if complex { 2 } else { 1 })
+}
@@ -0,0 +1,316 @@
+//! Safe write-back of edited metadata.
+//!
+//! The capture pipeline keeps appending annotations to meta files while
+//! they're open in the viewer, so edits are written with an advisory lock
+//! and only if the file still matches what was read. Otherwise the caller
+//! gets a [`WriteConflict`] with a three-way merge attempt to offer the user.
+
+use super::{Journal, Operation};
+use crate::parser::raw::is_raw_iq;
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use sha2::{Digest, Sha512};
+use std::fs::{File, OpenOptions, TryLockError};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long to wait for another process to release its lock
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A meta file as it was read, kept so a later write can tell whether
+/// someone else changed the file in the meantime
+#[derive(Debug, Clone)]
+pub struct MetaSnapshot {
+    pub path: PathBuf,
+    pub modified: Option<SystemTime>,
+    pub digest: String,
+    pub value: Value,
+    // Raw text, journaled so a rollback restores the exact bytes
+    content: String,
+}
+
+impl MetaSnapshot {
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let path = path.as_ref();
+        let mut file = File::open(path)?;
+        lock_with_timeout(path, || file.try_lock_shared())?;
+        let snapshot = Self::from_open_file(path, &mut file);
+        file.unlock()?;
+        snapshot
+    }
+
+    fn from_open_file(path: &Path, file: &mut File) -> Result<Self> {
+        if is_raw_iq(path) {
+            anyhow::bail!("{:?} is a raw IQ file; its metadata comes from its sidecar and can't be edited here", path);
+        }
+        let mut content = String::new();
+        file.seek(SeekFrom::Start(0))?;
+        file.read_to_string(&mut content)?;
+        Ok(MetaSnapshot {
+            path: path.to_path_buf(),
+            modified: file.metadata()?.modified().ok(),
+            digest: digest(content.as_bytes()),
+            value: serde_json::from_str(&content)?,
+            content,
+        })
+    }
+
+    /// True if `other` is a later read of the same, unchanged file. The hash
+    /// decides; mtime alone misses edits within the filesystem's timestamp
+    /// resolution.
+    fn matches(&self, other: &MetaSnapshot) -> bool {
+        self.digest == other.digest
+    }
+}
+
+/// The file changed on disk between reading `base` and writing `ours`
+#[derive(Debug, Clone)]
+pub struct WriteConflict {
+    pub base: MetaSnapshot,
+    pub ours: Value,
+    pub theirs: MetaSnapshot,
+    /// Three-way merge of both sides, or the JSON paths both sides changed
+    /// differently
+    pub merged: std::result::Result<Value, Vec<String>>,
+}
+
+#[derive(Debug)]
+pub enum WriteOutcome {
+    /// Written; the snapshot describes the file as it is now
+    Written(MetaSnapshot),
+    Conflict(Box<WriteConflict>),
+}
+
+/// Write `ours` over the file `base` was read from, unless the file changed
+/// since. To resolve a conflict, call this again with `conflict.theirs` as the
+/// base and either the merged value or `ours`.
+pub fn write_back(base: &MetaSnapshot, ours: &Value, journal: &Journal) -> Result<WriteOutcome> {
+    let path = base.path.as_path();
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    lock_with_timeout(path, || file.try_lock())?;
+
+    let current = MetaSnapshot::from_open_file(path, &mut file)?;
+    let outcome = if base.matches(&current) {
+        let content = to_content(ours, &current.content)?;
+        write_locked(&mut file, path, current.content, content, journal)?;
+        WriteOutcome::Written(MetaSnapshot::from_open_file(path, &mut file)?)
+    } else {
+        let merged = merge_metadata(&base.value, ours, &current.value);
+        WriteOutcome::Conflict(Box::new(WriteConflict {
+            base: base.clone(),
+            ours: ours.clone(),
+            theirs: current,
+            merged,
+        }))
+    };
+    file.unlock()?;
+    Ok(outcome)
+}
+
+/// Read-modify-write under an exclusive lock, for automated edits that can be
+/// applied to whatever is on disk (e.g. storing a checksum)
+pub fn update_meta<P, F>(path: P, journal: &Journal, edit: F) -> Result<MetaSnapshot>
+where
+    P: AsRef<Path>,
+    F: FnOnce(&mut Value) -> Result<()>,
+{
+    let path = path.as_ref();
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    lock_with_timeout(path, || file.try_lock())?;
+
+    let before = MetaSnapshot::from_open_file(path, &mut file)?;
+    let mut value = before.value;
+    edit(&mut value)?;
+    let content = to_content(&value, &before.content)?;
+    write_locked(&mut file, path, before.content, content, journal)?;
+    let snapshot = MetaSnapshot::from_open_file(path, &mut file);
+    file.unlock()?;
+    snapshot
+}
+
+/// Put `content` back into a meta file, but only if the file still hashes to
+/// `expected_digest` (when given). Returns false if it didn't match.
+pub(crate) fn restore_meta(path: &Path, expected_digest: Option<&str>, content: &str, journal: &Journal) -> Result<bool> {
+    let mut file = OpenOptions::new().read(true).write(true).open(path)?;
+    lock_with_timeout(path, || file.try_lock())?;
+
+    let current = MetaSnapshot::from_open_file(path, &mut file)?;
+    let matches = expected_digest.is_none_or(|digest| digest == current.digest);
+    if matches {
+        write_locked(&mut file, path, current.content, content.to_string(), journal)?;
+    }
+    file.unlock()?;
+    Ok(matches)
+}
+
+fn lock_with_timeout(path: &Path, mut try_lock: impl FnMut() -> std::result::Result<(), TryLockError>) -> Result<()> {
+    let started = Instant::now();
+    loop {
+        match try_lock() {
+            Ok(()) => return Ok(()),
+            Err(TryLockError::WouldBlock) if started.elapsed() < LOCK_TIMEOUT => {
+                std::thread::sleep(Duration::from_millis(50));
+            }
+            Err(TryLockError::WouldBlock) => {
+                anyhow::bail!("Timed out waiting for lock on {:?} (is the recorder writing it?)", path)
+            }
+            Err(TryLockError::Error(e)) => return Err(e.into()),
+        }
+    }
+}
+
+// Rewritten in place rather than via rename so other processes' locks on the
+// file stay meaningful. Journaled first so nothing is written unrecorded.
+fn write_locked(file: &mut File, path: &Path, before: String, content: String, journal: &Journal) -> Result<()> {
+    journal.record(Operation::WriteMeta {
+        path: path.to_path_buf(),
+        before,
+        after_digest: digest(content.as_bytes()),
+    })?;
+    file.seek(SeekFrom::Start(0))?;
+    file.write_all(content.as_bytes())?;
+    file.set_len(content.len() as u64)?;
+    file.sync_all()?;
+    Ok(())
+}
+
+/// `value` laid out like `previous`, the file's text before the edit: on one
+/// line if it was, else with its indent (two spaces if none is found), and
+/// with a final newline if it had one
+fn to_content(value: &Value, previous: &str) -> Result<String> {
+    let body = previous.trim();
+    let mut content = if !body.is_empty() && !body.contains('\n') {
+        serde_json::to_string(value)?
+    } else {
+        let indent = body
+            .lines()
+            .skip(1)
+            .map(|line| &line[..line.len() - line.trim_start().len()])
+            .find(|indent| !indent.is_empty())
+            .unwrap_or("  ");
+        let mut out = Vec::new();
+        let formatter = serde_json::ser::PrettyFormatter::with_indent(indent.as_bytes());
+        value.serialize(&mut serde_json::Serializer::with_formatter(&mut out, formatter))?;
+        String::from_utf8(out)?
+    };
+    if previous.ends_with('\n') || previous.is_empty() {
+        content.push('\n');
+    }
+    Ok(content)
+}
+
+fn digest(bytes: &[u8]) -> String {
+    Sha512::digest(bytes).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Three-way merge of SigMF metadata. Objects merge per key, annotations merge
+/// per annotation (by `ds:uuid`, else by position and extent) so that the
+/// recorder appending annotations never conflicts with edits to other ones.
+/// Anything else changed differently on both sides is a conflict.
+pub fn merge_metadata(base: &Value, ours: &Value, theirs: &Value) -> std::result::Result<Value, Vec<String>> {
+    let mut conflicts = Vec::new();
+    let merged = merge_value("", Some(base), Some(ours), Some(theirs), &mut conflicts);
+    if conflicts.is_empty() {
+        Ok(merged.unwrap_or(Value::Null))
+    } else {
+        Err(conflicts)
+    }
+}
+
+fn merge_value(
+    path: &str,
+    base: Option<&Value>,
+    ours: Option<&Value>,
+    theirs: Option<&Value>,
+    conflicts: &mut Vec<String>,
+) -> Option<Value> {
+    if ours == theirs || theirs == base {
+        return ours.cloned();
+    }
+    if ours == base {
+        return theirs.cloned();
+    }
+    match (base, ours, theirs) {
+        (Some(Value::Object(b)), Some(Value::Object(o)), Some(Value::Object(t))) => {
+            Some(Value::Object(merge_objects(path, b, o, t, conflicts)))
+        }
+        (Some(Value::Array(b)), Some(Value::Array(o)), Some(Value::Array(t))) if path == "/annotations" => {
+            Some(Value::Array(merge_annotations(path, b, o, t, conflicts)))
+        }
+        _ => {
+            conflicts.push(if path.is_empty() { "/".to_string() } else { path.to_string() });
+            ours.cloned()
+        }
+    }
+}
+
+fn merge_objects(
+    path: &str,
+    base: &Map<String, Value>,
+    ours: &Map<String, Value>,
+    theirs: &Map<String, Value>,
+    conflicts: &mut Vec<String>,
+) -> Map<String, Value> {
+    // Keep the on-disk key order, with keys only we added at the end
+    let keys = theirs.keys().chain(ours.keys().filter(|k| !theirs.contains_key(*k)));
+    let mut merged = Map::new();
+    for key in keys {
+        let child = format!("{}/{}", path, key);
+        if let Some(value) = merge_value(&child, base.get(key), ours.get(key), theirs.get(key), conflicts) {
+            merged.insert(key.clone(), value);
+        }
+    }
+    merged
+}
+
+fn annotation_key(annotation: &Value) -> String {
+    if let Some(uuid) = annotation.get("ds:uuid").and_then(Value::as_str) {
+        return uuid.to_string();
+    }
+    let field = |name: &str| annotation.get(name).map(Value::to_string).unwrap_or_default();
+    format!(
+        "{}/{}/{}/{}",
+        field("core:sample_start"),
+        field("core:sample_count"),
+        field("core:freq_lower_edge"),
+        field("core:freq_upper_edge")
+    )
+}
+
+fn merge_annotations(
+    path: &str,
+    base: &[Value],
+    ours: &[Value],
+    theirs: &[Value],
+    conflicts: &mut Vec<String>,
+) -> Vec<Value> {
+    let find = |list: &[Value], key: &str| list.iter().find(|a| annotation_key(a) == key).cloned();
+
+    let mut merged = Vec::new();
+    for t in theirs {
+        let key = annotation_key(t);
+        let child = format!("{}/{}", path, key);
+        let b = find(base, &key);
+        let o = find(ours, &key);
+        if let Some(value) = merge_value(&child, b.as_ref(), o.as_ref(), Some(t), conflicts) {
+            merged.push(value);
+        }
+    }
+    for o in ours {
+        let key = annotation_key(o);
+        if theirs.iter().any(|t| annotation_key(t) == key) {
+            continue;
+        }
+        let child = format!("{}/{}", path, key);
+        let b = find(base, &key);
+        if let Some(value) = merge_value(&child, b.as_ref(), Some(o), None, conflicts) {
+            merged.push(value);
+        }
+    }
+
+    let sample_start = |a: &Value| a.get("core:sample_start").and_then(Value::as_u64).unwrap_or(0);
+    merged.sort_by_key(sample_start);
+    merged
+}
@@ -0,0 +1,388 @@
+//! VITA-49 (VRT) packet captures, including DIFI streams: a `.vrt` file of
+//! back-to-back packets as received, e.g. recorded with [`record_udp`].
+//!
+//! Each data stream (stream ID) in a file becomes one recording. Its
+//! context packets give the sample rate, RF frequency, gain, payload format
+//! and timestamps; a context packet that retunes starts a new capture. The
+//! data packets' payloads are read in place as one run of samples (see
+//! [`DataLocation::pieces`]). A file with a single stream is opened by its
+//! own path; with several, each stream has a virtual path
+//! `<file>/<stem>.<stream id>.vrt`, like recordings inside an archive.
+//!
+//! Anything the context packets don't say falls back to the sidecar and
+//! defaults (see [`super::raw`]); without a payload format, samples are
+//! taken as 16-bit big-endian complex, as DIFI specifies.
+
+use super::format::RecordingFormat;
+use super::raw::{raw_iq_params, synthesize, RawIqParams};
+use super::sigmf::{CaptureInfo, DataLocation, SigMFDataType, SigMFParser};
+use anyhow::Result;
+use byteorder::{BigEndian, ReadBytesExt};
+use chrono::DateTime;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::net::UdpSocket;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Seconds from the Unix epoch to the GPS epoch (1980-01-06), leap seconds aside
+const GPS_EPOCH_OFFSET: i64 = 315_964_800;
+
+/// What one context packet says about its stream
+#[derive(Debug, Clone, Default)]
+pub struct VrtContext {
+    /// Bytes of the stream's samples before this packet
+    pub byte_start: u64,
+    pub timestamp: Option<String>,
+    pub rf_freq: Option<f64>,
+    pub bandwidth: Option<f64>,
+    pub sample_rate: Option<f64>,
+    /// Sum of both gain stages, dB
+    pub gain: Option<f64>,
+    /// Data packet payload format word
+    pub payload_format: Option<u32>,
+}
+
+/// One data stream of a capture file
+#[derive(Debug, Clone)]
+pub struct VrtStream {
+    pub file: PathBuf,
+    pub stream_id: Option<u32>,
+    /// What the recording is opened by: the file, or a virtual path into it
+    pub meta_path: PathBuf,
+    /// `(offset, len)` of each data payload
+    pub pieces: Vec<(u64, u64)>,
+    pub contexts: Vec<VrtContext>,
+    /// Of the first data packet
+    pub first_timestamp: Option<String>,
+}
+
+impl VrtStream {
+    fn new(file: &Path, stream_id: Option<u32>) -> Self {
+        VrtStream { file: file.to_path_buf(), stream_id, meta_path: file.to_path_buf(), pieces: Vec::new(), contexts: Vec::new(), first_timestamp: None }
+    }
+
+    pub fn payload_bytes(&self) -> u64 {
+        self.pieces.iter().map(|(_, len)| len).sum()
+    }
+
+    /// A recording of this stream, with `fallback` for what the context
+    /// packets don't say
+    pub fn into_parser(self, fallback: RawIqParams) -> Result<SigMFParser> {
+        let first = |field: fn(&VrtContext) -> Option<f64>| self.contexts.iter().find_map(field);
+        let data_type = match self.contexts.iter().find_map(|c| c.payload_format) {
+            Some(format) => payload_data_type(format)?,
+            None => SigMFDataType::from_string(fallback.datatype.as_deref().unwrap_or("ci16_be"))?,
+        };
+        let sample_rate = first(|c| c.sample_rate)
+            .or(fallback.sample_rate)
+            .filter(|rate| *rate > 0.0)
+            .ok_or_else(|| anyhow::anyhow!("No sample rate for {:?}: its context packets give none; pass --sample-rate", self.meta_path))?;
+        let stream_name = match self.stream_id {
+            Some(id) => format!("VITA-49 stream {:#010x}", id),
+            None => "VITA-49 stream".to_string(),
+        };
+        let params = RawIqParams {
+            center_freq: first(|c| c.rf_freq),
+            datetime: self.contexts.iter().find_map(|c| c.timestamp.clone()).or_else(|| self.first_timestamp.clone()),
+            description: Some(stream_name),
+            ..Default::default()
+        }
+        .or(&fallback);
+
+        // A new capture wherever the stream was retuned
+        let sample_size = data_type.sample_size_bytes() as u64;
+        let mut captures: Vec<CaptureInfo> = Vec::new();
+        for context in &self.contexts {
+            let frequency = context.rf_freq.or(params.center_freq);
+            if captures.last().is_some_and(|last| last.frequency == frequency) {
+                continue;
+            }
+            captures.push(CaptureInfo {
+                sample_start: Some(if captures.is_empty() { 0 } else { context.byte_start / sample_size }),
+                frequency,
+                timestamp: context.timestamp.clone().or_else(|| captures.is_empty().then(|| params.datetime.clone()).flatten()),
+                agc: None,
+                gain: context.gain,
+                sequence_num: None,
+                extra_fields: HashMap::new(),
+            });
+        }
+
+        let data_location = DataLocation {
+            file: self.file.clone(),
+            offset: self.pieces.first().map(|(offset, _)| *offset).unwrap_or(0),
+            size: self.payload_bytes(),
+            pieces: Some(Arc::new(self.pieces)),
+        };
+        let mut parser = synthesize(&self.meta_path, params, sample_rate, data_type, data_location);
+        parser.data_file_path = self.file;
+        if !captures.is_empty() {
+            parser.metadata.captures = captures;
+        }
+        Ok(parser)
+    }
+}
+
+/// Index the packets of a capture file: its data streams in order of first
+/// appearance. A packet cut off at the end of the file is ignored.
+pub fn list_streams(path: &Path) -> Result<Vec<VrtStream>> {
+    let file_size = std::fs::metadata(path)?.len();
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut streams: BTreeMap<Option<u32>, VrtStream> = BTreeMap::new();
+    let mut order: Vec<Option<u32>> = Vec::new();
+    let mut position = 0u64;
+    while position + 4 <= file_size {
+        let header = reader.read_u32::<BigEndian>()?;
+        let kind = header >> 28;
+        let words = (header & 0xFFFF) as u64;
+        let has_stream_id = matches!(kind, 1 | 3 | 4 | 5);
+        let has_class_id = header & (1 << 27) != 0;
+        let has_trailer = kind <= 3 && header & (1 << 26) != 0;
+        let (tsi, tsf) = ((header >> 22) & 3, (header >> 20) & 3);
+        let prologue = 1 + has_stream_id as u64 + 2 * has_class_id as u64 + (tsi != 0) as u64 + 2 * (tsf != 0) as u64;
+        if kind > 7 || words < prologue + has_trailer as u64 {
+            anyhow::bail!("{:?} is not a VITA-49 capture (bad packet header at byte {})", path, position);
+        }
+        if position + words * 4 > file_size {
+            break;
+        }
+
+        let stream_id = if has_stream_id { Some(reader.read_u32::<BigEndian>()?) } else { None };
+        if has_class_id {
+            reader.read_u64::<BigEndian>()?;
+        }
+        let seconds = if tsi != 0 { Some(reader.read_u32::<BigEndian>()?) } else { None };
+        let fraction = if tsf != 0 { Some(reader.read_u64::<BigEndian>()?) } else { None };
+        let timestamp = seconds.and_then(|seconds| format_timestamp(tsi, seconds, tsf, fraction.unwrap_or(0)));
+        let payload_words = words - prologue - has_trailer as u64;
+        let payload_offset = position + prologue * 4;
+        let mut consumed = prologue * 4;
+
+        match kind {
+            // Signal and extension data
+            0..=3 => {
+                if !streams.contains_key(&stream_id) {
+                    order.push(stream_id);
+                }
+                let stream = streams.entry(stream_id).or_insert_with(|| VrtStream::new(path, stream_id));
+                if stream.first_timestamp.is_none() {
+                    stream.first_timestamp = timestamp;
+                }
+                if payload_words > 0 {
+                    stream.pieces.push((payload_offset, payload_words * 4));
+                }
+            }
+            // Context
+            4 | 5 => {
+                let mut payload = vec![0u32; payload_words as usize];
+                reader.read_u32_into::<BigEndian>(&mut payload)?;
+                consumed += payload_words * 4;
+                let mut context = parse_context(&payload);
+                context.timestamp = timestamp;
+                // Stream-less data takes the context of any stream
+                let target = if !streams.contains_key(&stream_id) && streams.len() == 1 && streams.contains_key(&None) { None } else { stream_id };
+                if !streams.contains_key(&target) {
+                    order.push(target);
+                }
+                let stream = streams.entry(target).or_insert_with(|| VrtStream::new(path, target));
+                context.byte_start = stream.payload_bytes();
+                stream.contexts.push(context);
+            }
+            // Commands and anything else are skipped
+            _ => {}
+        }
+        reader.seek_relative((words * 4 - consumed) as i64)?;
+        position += words * 4;
+    }
+
+    let mut streams: Vec<VrtStream> = order
+        .into_iter()
+        .filter_map(|id| streams.remove(&id))
+        .filter(|stream| !stream.pieces.is_empty())
+        .collect();
+    if streams.is_empty() {
+        anyhow::bail!("{:?} has no VITA-49 data packets", path);
+    }
+    if streams.len() > 1 {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy().to_string();
+        for stream in &mut streams {
+            let id = stream.stream_id.map(|id| format!("{:08x}", id)).unwrap_or_else(|| "none".to_string());
+            stream.meta_path = path.join(format!("{}.{}.vrt", stem, id));
+        }
+    }
+    Ok(streams)
+}
+
+/// Every stream of a capture file as a recording, for a directory scan
+pub fn open_streams(path: &Path) -> Result<Vec<(PathBuf, Result<SigMFParser>)>> {
+    let fallback = raw_iq_params(path)?;
+    Ok(list_streams(path)?
+        .into_iter()
+        .map(|stream| (stream.meta_path.clone(), stream.into_parser(fallback.clone())))
+        .collect())
+}
+
+/// Open a capture file with a single stream, or one stream by its virtual path
+pub(crate) fn open_vrt(path: &Path) -> Result<SigMFParser> {
+    let file = if path.is_file() { path } else { path.parent().filter(|parent| parent.is_file()).unwrap_or(path) };
+    let fallback = raw_iq_params(file)?;
+    let mut streams = list_streams(file)?;
+    if file == path {
+        if streams.len() > 1 {
+            anyhow::bail!("{:?} has {} streams; open one of them, e.g. {:?}", path, streams.len(), streams[0].meta_path);
+        }
+        return streams.remove(0).into_parser(fallback);
+    }
+    let stream = streams
+        .into_iter()
+        .find(|stream| stream.meta_path == path)
+        .ok_or_else(|| anyhow::anyhow!("{:?} is not a stream of {:?}", path, file))?;
+    stream.into_parser(fallback)
+}
+
+pub struct Vita49Format;
+
+impl RecordingFormat for Vita49Format {
+    fn name(&self) -> &str {
+        "VITA-49"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["vrt"]
+    }
+
+    fn open(&self, path: &Path) -> Result<SigMFParser> {
+        open_vrt(path)
+    }
+
+    fn recordings(&self, path: &Path) -> Result<Vec<(PathBuf, Result<SigMFParser>)>> {
+        open_streams(path)
+    }
+}
+
+/// The context fields up to the data packet payload format; later ones
+/// (geolocation, ephemeris, ...) are ignored
+fn parse_context(words: &[u32]) -> VrtContext {
+    let mut context = VrtContext::default();
+    let Some(&cif0) = words.first() else {
+        return context;
+    };
+    // CIF1, CIF2, CIF3 and CIF7 words come before the fields
+    let mut idx = 1 + [1, 2, 3, 7].iter().filter(|bit| cif0 & (1 << **bit) != 0).count();
+    let fixed64 = |field: &[u32]| ((((field[0] as u64) << 32) | field[1] as u64) as i64) as f64 / (1u64 << 20) as f64;
+    // Field sizes in words, from bit 30 (reference point) down to 15 (payload format)
+    const SIZES: [usize; 16] = [1, 2, 2, 2, 2, 2, 1, 1, 1, 2, 2, 1, 1, 2, 1, 2];
+    for (n, size) in SIZES.iter().enumerate() {
+        let bit = 30 - n as u32;
+        if cif0 & (1 << bit) == 0 {
+            continue;
+        }
+        let Some(field) = words.get(idx..idx + size) else {
+            break;
+        };
+        idx += size;
+        match bit {
+            29 => context.bandwidth = Some(fixed64(field)),
+            27 => context.rf_freq = Some(fixed64(field)),
+            23 => {
+                let stage = |bits: u32| (bits as u16 as i16) as f64 / 128.0;
+                context.gain = Some(stage(field[0] & 0xFFFF) + stage(field[0] >> 16));
+            }
+            21 => context.sample_rate = Some(fixed64(field)),
+            15 => context.payload_format = Some(field[0]),
+            _ => {}
+        }
+    }
+    context
+}
+
+/// The datatype of a payload format word: complex cartesian, signed
+/// fixed point, 8 or 16 bits per component
+fn payload_data_type(format: u32) -> Result<SigMFDataType> {
+    let complex = (format >> 29) & 3 == 1;
+    let item_format = (format >> 24) & 0x1F;
+    let bits = (format & 0x3F) + 1;
+    match (complex, item_format, bits) {
+        (true, 0, 16) => Ok(SigMFDataType::Ci16Be),
+        (true, 0, 8) => Ok(SigMFDataType::Ci8),
+        _ => anyhow::bail!(
+            "Unsupported VITA-49 payload format ({}, item format {}, {} bits)",
+            if complex { "complex" } else { "not complex cartesian" },
+            item_format,
+            bits
+        ),
+    }
+}
+
+/// UTC or GPS integer seconds, plus real-time picoseconds
+fn format_timestamp(tsi: u32, seconds: u32, tsf: u32, fraction: u64) -> Option<String> {
+    let seconds = match tsi {
+        1 => seconds as i64,
+        2 => seconds as i64 + GPS_EPOCH_OFFSET,
+        _ => return None,
+    };
+    let nanos = if tsf == 2 { (fraction / 1000).min(999_999_999) as u32 } else { 0 };
+    DateTime::from_timestamp(seconds, nanos).map(|time| time.format("%Y-%m-%dT%H:%M:%S%.6fZ").to_string())
+}
+
+/// What [`record_udp`] received
+#[derive(Debug, Clone, Default)]
+pub struct RecordReport {
+    pub packets: u64,
+    pub bytes: u64,
+    /// Datagrams that weren't VITA-49 packets
+    pub skipped: u64,
+}
+
+/// Append VITA-49 datagrams arriving at `listen` (e.g. `0.0.0.0:4991`) to
+/// `output`, until `duration` has passed or `max_packets` were written
+/// (one of them is required). `on_progress` gets the report once a second.
+pub fn record_udp(
+    listen: &str,
+    output: &Path,
+    duration: Option<Duration>,
+    max_packets: Option<u64>,
+    mut on_progress: impl FnMut(&RecordReport),
+) -> Result<RecordReport> {
+    if duration.is_none() && max_packets.is_none() {
+        anyhow::bail!("Give a duration or a packet count to stop after");
+    }
+    let socket = UdpSocket::bind(listen)?;
+    socket.set_read_timeout(Some(Duration::from_millis(200)))?;
+    let mut writer = BufWriter::new(std::fs::OpenOptions::new().create(true).append(true).open(output)?);
+    let started = Instant::now();
+    let mut last_report = Instant::now();
+    let mut report = RecordReport::default();
+    let mut buffer = vec![0u8; 65536];
+    loop {
+        if duration.is_some_and(|duration| started.elapsed() >= duration) || max_packets.is_some_and(|max| report.packets >= max) {
+            break;
+        }
+        match socket.recv(&mut buffer) {
+            Ok(len) => {
+                let datagram = &buffer[..len];
+                // The datagram must hold whole packets per its first header
+                let words = datagram.get(..4).map(|header| (u32::from_be_bytes([header[0], header[1], header[2], header[3]]) & 0xFFFF) as usize);
+                if len % 4 != 0 || words.is_none_or(|words| words == 0 || words * 4 > len) {
+                    report.skipped += 1;
+                    continue;
+                }
+                writer.write_all(datagram)?;
+                report.packets += 1;
+                report.bytes += len as u64;
+            }
+            Err(e) if matches!(e.kind(), std::io::ErrorKind::WouldBlock | std::io::ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e.into()),
+        }
+        if last_report.elapsed() >= Duration::from_secs(1) {
+            writer.flush()?;
+            on_progress(&report);
+            last_report = Instant::now();
+        }
+    }
+    writer.flush()?;
+    Ok(report)
+}
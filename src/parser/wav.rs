@@ -0,0 +1,184 @@
+//! Baseband IQ recordings in WAV files, as written by SDR#, SDRuno, HDSDR
+//! and others: two channels (I left, Q right) of 8-bit unsigned, 16-bit or
+//! float32 samples. RF64 files (over 4 GiB) are read too.
+//!
+//! The sample rate comes from the `fmt ` chunk; the center frequency and
+//! start time from the `auxi` chunk when there is one, else from a
+//! frequency in the file name (`..._915000000Hz_IQ.wav`, `..._1045kHz.wav`),
+//! else from the sidecar and defaults (see [`super::raw`]). The samples are
+//! read in place from the `data` chunk.
+
+use super::format::RecordingFormat;
+use super::raw::{raw_iq_params, sidecar_path, synthesize, RawIqParams};
+use super::sigmf::{DataLocation, SigMFDataType, SigMFParser};
+use anyhow::Result;
+use byteorder::{LittleEndian, ReadBytesExt};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+const FORMAT_PCM: u16 = 1;
+const FORMAT_FLOAT: u16 = 3;
+const FORMAT_EXTENSIBLE: u16 = 0xFFFE;
+
+/// What a WAV file's header says about its samples
+#[derive(Debug, Clone)]
+pub struct WavHeader {
+    pub sample_rate: u32,
+    pub data_type: SigMFDataType,
+    /// Byte range of the `data` chunk's samples
+    pub data_offset: u64,
+    pub data_size: u64,
+    /// From the `auxi` chunk
+    pub center_freq: Option<f64>,
+    /// From the `auxi` chunk, ISO 8601
+    pub start_time: Option<String>,
+}
+
+/// Read the chunks of `path` up to its samples
+pub fn read_wav_header(path: &Path) -> Result<WavHeader> {
+    let file_size = std::fs::metadata(path)?.len();
+    let mut reader = BufReader::new(File::open(path)?);
+    let mut riff = [0u8; 4];
+    reader.read_exact(&mut riff)?;
+    reader.read_u32::<LittleEndian>()?;
+    let mut wave = [0u8; 4];
+    reader.read_exact(&mut wave)?;
+    if !matches!(&riff, b"RIFF" | b"RF64") || &wave != b"WAVE" {
+        anyhow::bail!("{:?} is not a WAV file", path);
+    }
+
+    let mut format: Option<(u16, u16, u32, u16)> = None;
+    let mut rf64_data_size = None;
+    let mut auxi: Option<Vec<u8>> = None;
+    loop {
+        let mut id = [0u8; 4];
+        if reader.read_exact(&mut id).is_err() {
+            anyhow::bail!("{:?} has no data chunk", path);
+        }
+        let size = reader.read_u32::<LittleEndian>()? as u64;
+        let start = reader.stream_position()?;
+        match &id {
+            b"fmt " => {
+                let tag = reader.read_u16::<LittleEndian>()?;
+                let channels = reader.read_u16::<LittleEndian>()?;
+                let sample_rate = reader.read_u32::<LittleEndian>()?;
+                reader.read_u32::<LittleEndian>()?; // byte rate
+                reader.read_u16::<LittleEndian>()?; // block align
+                let bits = reader.read_u16::<LittleEndian>()?;
+                // The real format is the start of the sub-format GUID
+                let tag = if tag == FORMAT_EXTENSIBLE && size >= 40 {
+                    reader.seek(SeekFrom::Start(start + 24))?;
+                    reader.read_u16::<LittleEndian>()?
+                } else {
+                    tag
+                };
+                format = Some((tag, channels, sample_rate, bits));
+            }
+            b"ds64" => {
+                reader.read_u64::<LittleEndian>()?; // RIFF size
+                rf64_data_size = Some(reader.read_u64::<LittleEndian>()?);
+            }
+            b"auxi" => {
+                let mut contents = vec![0u8; size.min(4096) as usize];
+                reader.read_exact(&mut contents)?;
+                auxi = Some(contents);
+            }
+            b"data" => {
+                let (tag, channels, sample_rate, bits) = format.ok_or_else(|| anyhow::anyhow!("{:?} has no fmt chunk before its data", path))?;
+                if channels != 2 {
+                    anyhow::bail!("{:?} has {} channel(s); IQ recordings have 2", path, channels);
+                }
+                let data_type = match (tag, bits) {
+                    (FORMAT_PCM, 8) => SigMFDataType::Cu8,
+                    (FORMAT_PCM, 16) => SigMFDataType::Ci16Le,
+                    (FORMAT_FLOAT, 32) => SigMFDataType::Cf32Le,
+                    _ => anyhow::bail!("{:?}: unsupported WAV sample format (format {}, {} bits)", path, tag, bits),
+                };
+                let size = match rf64_data_size {
+                    Some(rf64_size) if size == u32::MAX as u64 => rf64_size,
+                    _ => size,
+                };
+                // A recording cut short (e.g. the recorder crashed) has fewer samples than the header says
+                let data_size = size.min(file_size.saturating_sub(start));
+                let (center_freq, start_time) = auxi.as_deref().map(parse_auxi).unwrap_or_default();
+                return Ok(WavHeader { sample_rate, data_type, data_offset: start, data_size, center_freq, start_time });
+            }
+            _ => {}
+        }
+        // Chunks are padded to an even size
+        reader.seek(SeekFrom::Start(start + size + size % 2))?;
+    }
+}
+
+/// Center frequency and start time from an `auxi` chunk: two SYSTEMTIMEs
+/// (start and stop, taken as UTC) followed by the center frequency in Hz
+fn parse_auxi(contents: &[u8]) -> (Option<f64>, Option<String>) {
+    if contents.len() < 36 {
+        return (None, None);
+    }
+    let word = |idx: usize| u16::from_le_bytes([contents[idx * 2], contents[idx * 2 + 1]]);
+    // year, month, day of week, day, hour, minute, second, milliseconds
+    let start_time = (word(0) > 0 && (1..=12).contains(&word(1))).then(|| {
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+            word(0),
+            word(1),
+            word(3),
+            word(4),
+            word(5),
+            word(6),
+            word(7)
+        )
+    });
+    let center_freq = u32::from_le_bytes([contents[32], contents[33], contents[34], contents[35]]);
+    ((center_freq > 0).then_some(center_freq as f64), start_time)
+}
+
+/// A frequency in the file name: an `_`-separated part like `915000000Hz`,
+/// `1045kHz` or `433.92MHz`
+fn frequency_from_name(path: &Path) -> Option<f64> {
+    let stem = path.file_stem()?.to_str()?;
+    stem.split('_').find_map(|part| {
+        let lower = part.to_ascii_lowercase();
+        [("ghz", 1e9), ("mhz", 1e6), ("khz", 1e3), ("hz", 1.0)]
+            .iter()
+            .find_map(|(unit, scale)| lower.strip_suffix(unit)?.parse::<f64>().ok().map(|value| value * scale))
+    })
+}
+
+/// Open a WAV file as a single-capture SigMF recording, with `fallback`
+/// (sidecar and defaults) for what the header doesn't say. A header sample
+/// rate of 0 counts as unsaid.
+pub(crate) fn open_wav(path: &Path, fallback: RawIqParams) -> Result<SigMFParser> {
+    let header = read_wav_header(path)?;
+    let params = RawIqParams {
+        sample_rate: Some(header.sample_rate as f64).filter(|rate| *rate > 0.0),
+        center_freq: header.center_freq.or_else(|| frequency_from_name(path)),
+        datetime: header.start_time.clone(),
+        ..Default::default()
+    }
+    .or(&fallback);
+    let sample_rate = params
+        .sample_rate
+        .filter(|rate| *rate > 0.0)
+        .ok_or_else(|| anyhow::anyhow!("{:?} has a sample rate of 0: add sample_rate to {:?} or pass --sample-rate", path, sidecar_path(path)))?;
+    let data_location = DataLocation { file: path.to_path_buf(), offset: header.data_offset, size: header.data_size, pieces: None };
+    Ok(synthesize(path, params, sample_rate, header.data_type, data_location))
+}
+
+pub struct WavFormat;
+
+impl RecordingFormat for WavFormat {
+    fn name(&self) -> &str {
+        "WAV"
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["wav"]
+    }
+
+    fn open(&self, path: &Path) -> Result<SigMFParser> {
+        open_wav(path, raw_iq_params(path)?)
+    }
+}
@@ -0,0 +1,453 @@
+//! Terminal table browser behind the `tui` command, for capture boxes
+//! reached over SSH where the GUI can't run.
+//!
+//! The rows scroll like the GUI's table, with one column under a cursor:
+//! `/` filters it with the same syntax as the GUI's filter boxes, `s` sorts
+//! by it, and Enter opens the row's fields (in readable units) under a
+//! sparkline of its recording's average spectrum.
+
+use crate::data_ops::{format_frequency, humanize_value, FilterExpr};
+use crate::parser::{FileParser, SigMFDataset, SigMFParser};
+use crate::viz::{Psd, PsdParams};
+use anyhow::Result;
+use polars::prelude::*;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Paragraph, Row, Sparkline, Table, TableState};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Widest a column is drawn; longer values are cut
+const MAX_COLUMN_WIDTH: usize = 32;
+
+/// FFT size of the spectrum behind the detail view's sparkline (smaller
+/// for short recordings); bins are pooled down to the terminal's width
+const SPARKLINE_FFT_SIZE: u64 = 512;
+
+const TABLE_KEYS: &str = "↑↓ rows  ←→ columns  / filter  c clear filter  s sort  Enter details  q quit";
+const DETAIL_KEYS: &str = "↑↓ scroll  n/p next/previous row  Esc back  q quit";
+
+/// Browse `dataset` until the user quits. `root` is where the rows'
+/// recordings are (for datasets without a `source_root` column), for the
+/// spectrum of the detail view.
+pub fn run(dataset: DataFrame, root: PathBuf) -> Result<()> {
+    let mut browser = Browser::new(dataset, root);
+    let mut terminal = ratatui::init();
+    let result = browser.run(&mut terminal);
+    ratatui::restore();
+    result
+}
+
+enum Mode {
+    Table,
+    /// Typing a filter for the column under the cursor
+    Filter(String),
+    Detail(Detail),
+}
+
+struct Detail {
+    /// Row of the filtered table
+    row: usize,
+    scroll: u16,
+    psd: std::result::Result<Psd, String>,
+}
+
+struct Browser {
+    dataset: DataFrame,
+    /// `dataset` filtered and sorted
+    rows: DataFrame,
+    root: PathBuf,
+    /// Filter text per column, as typed
+    filters: BTreeMap<String, String>,
+    /// Column and descending
+    sort: Option<(String, bool)>,
+    row: usize,
+    row_offset: usize,
+    column: usize,
+    column_offset: usize,
+    mode: Mode,
+    /// Shown in the status line until the next key
+    message: Option<String>,
+    quit: bool,
+}
+
+impl Browser {
+    fn new(dataset: DataFrame, root: PathBuf) -> Self {
+        Browser {
+            rows: dataset.clone(),
+            dataset,
+            root,
+            filters: BTreeMap::new(),
+            sort: None,
+            row: 0,
+            row_offset: 0,
+            column: 0,
+            column_offset: 0,
+            mode: Mode::Table,
+            message: None,
+            quit: false,
+        }
+    }
+
+    fn run(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        while !self.quit {
+            terminal.draw(|frame| self.draw(frame))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.message = None;
+                    self.handle_key(key);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn column_name(&self) -> Option<String> {
+        self.dataset.get_column_names().get(self.column).map(|name| name.to_string())
+    }
+
+    /// Re-run the filters and sort over the whole dataset
+    fn refilter(&mut self) -> Result<()> {
+        let mut lf = self.dataset.clone().lazy();
+        for (column, text) in &self.filters {
+            if let Some(filter) = FilterExpr::parse(column, self.dataset.column(column)?.dtype(), text)? {
+                lf = lf.filter(filter.to_expr());
+            }
+        }
+        if let Some((ref column, descending)) = self.sort {
+            lf = lf.sort(
+                [column.as_str()],
+                SortMultipleOptions::default().with_order_descending(descending).with_nulls_last(true).with_maintain_order(true),
+            );
+        }
+        self.rows = lf.collect()?;
+        self.row = self.row.min(self.rows.height().saturating_sub(1));
+        Ok(())
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        match std::mem::replace(&mut self.mode, Mode::Table) {
+            Mode::Table => self.table_key(key),
+            Mode::Filter(input) => self.filter_key(key, input),
+            Mode::Detail(detail) => self.detail_key(key, detail),
+        }
+    }
+
+    fn table_key(&mut self, key: KeyEvent) {
+        let last_row = self.rows.height().saturating_sub(1);
+        let page = 20;
+        match key.code {
+            KeyCode::Char('q') | KeyCode::Esc => self.quit = true,
+            KeyCode::Up | KeyCode::Char('k') => self.row = self.row.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => self.row = (self.row + 1).min(last_row),
+            KeyCode::PageUp => self.row = self.row.saturating_sub(page),
+            KeyCode::PageDown => self.row = (self.row + page).min(last_row),
+            KeyCode::Home | KeyCode::Char('g') => self.row = 0,
+            KeyCode::End | KeyCode::Char('G') => self.row = last_row,
+            KeyCode::Left | KeyCode::Char('h') => self.column = self.column.saturating_sub(1),
+            KeyCode::Right | KeyCode::Char('l') => self.column = (self.column + 1).min(self.dataset.width().saturating_sub(1)),
+            KeyCode::Char('/') | KeyCode::Char('f') => {
+                let current = self.column_name().and_then(|column| self.filters.get(&column).cloned()).unwrap_or_default();
+                self.mode = Mode::Filter(current);
+            }
+            KeyCode::Char('c') => {
+                if let Some(column) = self.column_name() {
+                    if self.filters.remove(&column).is_some() {
+                        self.apply_or_report();
+                    }
+                }
+            }
+            KeyCode::Char('s') => {
+                let Some(column) = self.column_name() else {
+                    return;
+                };
+                // Ascending, then descending, then back to file order
+                self.sort = match self.sort.take() {
+                    Some((sorted, false)) if sorted == column => Some((sorted, true)),
+                    Some((sorted, true)) if sorted == column => None,
+                    _ => Some((column, false)),
+                };
+                self.apply_or_report();
+            }
+            KeyCode::Enter if self.rows.height() > 0 => self.mode = Mode::Detail(self.open_detail(self.row)),
+            _ => {}
+        }
+    }
+
+    fn apply_or_report(&mut self) {
+        if let Err(e) = self.refilter() {
+            self.message = Some(e.to_string());
+        }
+    }
+
+    fn filter_key(&mut self, key: KeyEvent, mut input: String) {
+        match key.code {
+            KeyCode::Esc => return,
+            KeyCode::Enter => {
+                let Some(column) = self.column_name() else {
+                    return;
+                };
+                let dtype = self.dataset.column(&column).map(|c| c.dtype().clone()).unwrap_or(DataType::String);
+                // Checked here so a typo keeps the prompt open to fix it
+                if let Err(e) = FilterExpr::parse(&column, &dtype, &input) {
+                    self.message = Some(e.to_string());
+                    self.mode = Mode::Filter(input);
+                    return;
+                }
+                if input.trim().is_empty() {
+                    self.filters.remove(&column);
+                } else {
+                    self.filters.insert(column, input);
+                }
+                self.apply_or_report();
+                return;
+            }
+            KeyCode::Backspace => {
+                input.pop();
+            }
+            KeyCode::Char(c) => input.push(c),
+            _ => {}
+        }
+        self.mode = Mode::Filter(input);
+    }
+
+    fn detail_key(&mut self, key: KeyEvent, mut detail: Detail) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Enter | KeyCode::Backspace => return,
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Up | KeyCode::Char('k') => detail.scroll = detail.scroll.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => detail.scroll = detail.scroll.saturating_add(1).min(self.dataset.width() as u16),
+            KeyCode::PageUp => detail.scroll = detail.scroll.saturating_sub(20),
+            KeyCode::PageDown => detail.scroll = detail.scroll.saturating_add(20).min(self.dataset.width() as u16),
+            KeyCode::Char('n') if detail.row + 1 < self.rows.height() => {
+                self.row = detail.row + 1;
+                detail = Detail { scroll: detail.scroll, ..self.open_detail(self.row) };
+            }
+            KeyCode::Char('p') if detail.row > 0 => {
+                self.row = detail.row - 1;
+                detail = Detail { scroll: detail.scroll, ..self.open_detail(self.row) };
+            }
+            _ => {}
+        }
+        self.mode = Mode::Detail(detail);
+    }
+
+    fn open_detail(&self, row: usize) -> Detail {
+        Detail { row, scroll: 0, psd: self.row_psd(row).map_err(|e| format!("{:#}", e)) }
+    }
+
+    /// Average spectrum of the recording `row` of the filtered table is from
+    fn row_psd(&self, row: usize) -> Result<Psd> {
+        let text = |column: &str| -> Option<String> {
+            let value = self.rows.column(column).ok()?.get(row).ok()?;
+            Some(value.get_str()?.to_string()).filter(|text| !text.is_empty())
+        };
+        let meta_filename = text("meta_filename").ok_or_else(|| anyhow::anyhow!("No meta_filename for this row"))?;
+        let root = text("source_root").map(PathBuf::from).unwrap_or_else(|| self.root.clone());
+        let meta_path = locate_meta_file(&root, &meta_filename).ok_or_else(|| anyhow::anyhow!("Could not locate {} under {:?}", meta_filename, root))?;
+        let parser = SigMFParser::from_meta_file(&meta_path)?;
+        let mut reader = FileParser::open_reader(&meta_path)?;
+        let fft_size = (reader.num_samples().clamp(16, SPARKLINE_FFT_SIZE) as usize + 1).next_power_of_two() / 2;
+        let params = PsdParams { fft_size, averages: 64 };
+        Psd::compute(&mut reader, parser.sample_rate(), parser.center_frequency(), &params)
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status, keys] = Layout::vertical([Constraint::Min(3), Constraint::Length(1), Constraint::Length(1)]).areas(frame.area());
+        match &self.mode {
+            Mode::Detail(detail) => self.draw_detail(frame, main, detail),
+            _ => self.draw_table(frame, main),
+        }
+        frame.render_widget(Paragraph::new(self.status_line()), status);
+        let keys_line = match &self.mode {
+            Mode::Table => Line::from(TABLE_KEYS).dark_gray(),
+            Mode::Filter(input) => Line::from(vec![
+                Span::from(format!("Filter {}: ", self.column_name().unwrap_or_default())).bold(),
+                Span::from(input.as_str()),
+                Span::from("█"),
+                Span::from("   e.g. >= 10, 100e6..200e6, contains:wifi; Enter apply, Esc cancel").dark_gray(),
+            ]),
+            Mode::Detail(_) => Line::from(DETAIL_KEYS).dark_gray(),
+        };
+        frame.render_widget(Paragraph::new(keys_line), keys);
+    }
+
+    fn status_line(&self) -> Line<'static> {
+        if let Some(ref message) = self.message {
+            return Line::from(message.clone()).red();
+        }
+        let mut parts = vec![format!("{} of {} rows", self.rows.height(), self.dataset.height())];
+        if !self.filters.is_empty() {
+            let filters: Vec<String> = self.filters.iter().map(|(column, text)| format!("{} {}", column, text.trim())).collect();
+            parts.push(format!("filter: {}", filters.join(" AND ")));
+        }
+        if let Some((ref column, descending)) = self.sort {
+            parts.push(format!("sorted by {} {}", column, if descending { "↓" } else { "↑" }));
+        }
+        Line::from(parts.join("  |  "))
+    }
+
+    fn draw_table(&mut self, frame: &mut Frame, area: Rect) {
+        let names: Vec<String> = self.dataset.get_column_names().iter().map(|name| name.to_string()).collect();
+        // Header and borders take three lines
+        let page = (area.height as usize).saturating_sub(3).max(1);
+        if self.row < self.row_offset {
+            self.row_offset = self.row;
+        } else if self.row >= self.row_offset + page {
+            self.row_offset = self.row + 1 - page;
+        }
+        let visible_rows = self.row_offset..(self.row_offset + page).min(self.rows.height());
+        let columns: Vec<Option<&Series>> = names.iter().map(|name| self.rows.column(name).ok()).collect();
+        let width_of = |idx: usize| {
+            let values = visible_rows.clone().filter_map(|row| columns[idx].map(|column| cell_text(column, row).chars().count()));
+            values.chain([names[idx].chars().count() + 2]).max().unwrap_or(0).clamp(3, MAX_COLUMN_WIDTH)
+        };
+
+        // Scroll sideways until the cursor's column fits
+        let available = area.width.saturating_sub(2) as usize;
+        self.column_offset = self.column_offset.min(self.column);
+        let shown = loop {
+            let mut used = 0;
+            let mut shown = Vec::new();
+            for idx in self.column_offset..names.len() {
+                let width = width_of(idx);
+                if used + width > available && !shown.is_empty() {
+                    break;
+                }
+                used += width + 1;
+                shown.push((idx, width));
+            }
+            if shown.iter().any(|(idx, _)| *idx == self.column) || self.column_offset >= self.column {
+                break shown;
+            }
+            self.column_offset += 1;
+        };
+
+        let header = Row::new(shown.iter().map(|(idx, _)| {
+            let name = &names[*idx];
+            let mut label = name.clone();
+            if let Some((ref column, descending)) = self.sort {
+                if column == name {
+                    label.push_str(if descending { " ↓" } else { " ↑" });
+                }
+            }
+            let mut style = Style::new().bold();
+            if self.filters.contains_key(name) {
+                style = style.fg(Color::Yellow);
+            }
+            if *idx == self.column {
+                style = style.reversed();
+            }
+            Span::styled(label, style)
+        }));
+        let rows = visible_rows.map(|row| {
+            Row::new(shown.iter().map(|(idx, width)| {
+                let text = columns[*idx].map(|column| cell_text(column, row)).unwrap_or_default();
+                truncate(&text, *width)
+            }))
+        });
+        let table = Table::new(rows, shown.iter().map(|(_, width)| Constraint::Length(*width as u16)))
+            .header(header)
+            .row_highlight_style(Style::new().reversed())
+            .block(Block::bordered().title(format!(" columns {}–{} of {} ", self.column_offset + 1, self.column_offset + shown.len(), names.len())));
+        let mut state = TableState::default().with_selected((self.rows.height() > 0).then(|| self.row - self.row_offset));
+        frame.render_stateful_widget(table, area, &mut state);
+    }
+
+    fn draw_detail(&self, frame: &mut Frame, area: Rect, detail: &Detail) {
+        let [spectrum, fields] = Layout::vertical([Constraint::Length(8), Constraint::Min(3)]).areas(area);
+        match &detail.psd {
+            Ok(psd) => {
+                let (low, high) = psd.freq_range();
+                let peak = psd.power_db.iter().copied().fold(f32::NEG_INFINITY, f32::max);
+                let title = format!(" Spectrum {} – {}, peak {:.1} dB ", format_frequency(low), format_frequency(high), peak);
+                let data = sparkline_data(&psd.power_db, spectrum.width.saturating_sub(2) as usize);
+                frame.render_widget(Sparkline::default().block(Block::bordered().title(title)).data(&data).style(Style::new().fg(Color::Cyan)), spectrum);
+            }
+            Err(e) => frame.render_widget(Paragraph::new(e.as_str()).dark_gray().block(Block::bordered().title(" Spectrum ")), spectrum),
+        }
+
+        let names = self.rows.get_column_names();
+        let name_width = names.iter().map(|name| name.chars().count()).max().unwrap_or(0);
+        let lines: Vec<Line> = self
+            .rows
+            .get_columns()
+            .iter()
+            .map(|column| {
+                Line::from(vec![
+                    Span::from(format!("{:>width$}  ", column.name().to_string(), width = name_width)).bold(),
+                    Span::from(cell_text(column, detail.row)),
+                ])
+            })
+            .collect();
+        let title = format!(" Row {} of {} ", detail.row + 1, self.rows.height());
+        frame.render_widget(Paragraph::new(lines).scroll((detail.scroll, 0)).block(Block::bordered().title(title)), fields);
+    }
+}
+
+/// Where a row's meta file is: right under `root`, or found by name below it
+fn locate_meta_file(root: &Path, meta_filename: &str) -> Option<PathBuf> {
+    let direct = root.join(meta_filename);
+    if direct.exists() {
+        return Some(direct);
+    }
+    SigMFDataset::find_meta_file(root, meta_filename)
+}
+
+/// A cell as the GUI shows it: numbers in readable units where the column
+/// name gives one, empty for nulls
+fn cell_text(column: &Series, row: usize) -> String {
+    let Ok(value) = column.get(row) else {
+        return String::new();
+    };
+    if column.dtype().is_numeric() {
+        if let Some(text) = value.extract::<f64>().and_then(|number| humanize_value(column.name(), number)) {
+            return text;
+        }
+    }
+    match value {
+        AnyValue::Null => String::new(),
+        AnyValue::String(text) => text.to_string(),
+        AnyValue::Float64(number) => format_float(number),
+        AnyValue::Float32(number) => format_float(number as f64),
+        other => other.to_string(),
+    }
+}
+
+fn format_float(number: f64) -> String {
+    if number.abs() > 1000.0 || (number.abs() < 0.01 && number != 0.0) {
+        format!("{:.2e}", number)
+    } else {
+        format!("{:.3}", number)
+    }
+}
+
+/// `text` cut to `width` characters, ending in … if it was longer
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let mut cut: String = text.chars().take(width.saturating_sub(1)).collect();
+    cut.push('…');
+    cut
+}
+
+/// PSD bins pooled (by their peak) into `width` bars, as tenths of a dB
+/// above the lowest bar
+fn sparkline_data(power_db: &[f32], width: usize) -> Vec<u64> {
+    if power_db.is_empty() || width == 0 {
+        return Vec::new();
+    }
+    let bars = width.min(power_db.len());
+    let pooled: Vec<f32> = (0..bars)
+        .map(|bar| {
+            let bins = &power_db[bar * power_db.len() / bars..((bar + 1) * power_db.len() / bars).max(bar * power_db.len() / bars + 1)];
+            bins.iter().copied().fold(f32::NEG_INFINITY, f32::max)
+        })
+        .collect();
+    let floor = pooled.iter().copied().filter(|db| db.is_finite()).fold(f32::INFINITY, f32::min);
+    pooled.iter().map(|db| if db.is_finite() { ((db - floor) * 10.0).round() as u64 } else { 0 }).collect()
+}
@@ -0,0 +1,94 @@
+/// Colormaps for rendering power values as images
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Colormap {
+    Viridis,
+    Inferno,
+    Jet,
+    Grayscale,
+}
+
+// Anchor colors, evenly spaced over [0, 1] and linearly interpolated between
+const VIRIDIS: &[[u8; 3]] = &[
+    [68, 1, 84],
+    [72, 40, 120],
+    [62, 74, 137],
+    [49, 104, 142],
+    [38, 130, 142],
+    [31, 158, 137],
+    [53, 183, 121],
+    [109, 205, 89],
+    [180, 222, 44],
+    [253, 231, 37],
+];
+
+const INFERNO: &[[u8; 3]] = &[
+    [0, 0, 4],
+    [27, 12, 65],
+    [74, 12, 107],
+    [120, 28, 109],
+    [165, 44, 96],
+    [207, 68, 70],
+    [237, 105, 37],
+    [251, 155, 6],
+    [247, 209, 61],
+    [252, 255, 164],
+];
+
+const JET: &[[u8; 3]] = &[
+    [0, 0, 128],
+    [0, 0, 255],
+    [0, 128, 255],
+    [0, 255, 255],
+    [128, 255, 128],
+    [255, 255, 0],
+    [255, 128, 0],
+    [255, 0, 0],
+    [128, 0, 0],
+];
+
+const GRAYSCALE: &[[u8; 3]] = &[[0, 0, 0], [255, 255, 255]];
+
+impl Colormap {
+    pub const ALL: [Colormap; 4] = [
+        Colormap::Viridis,
+        Colormap::Inferno,
+        Colormap::Jet,
+        Colormap::Grayscale,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Colormap::Viridis => "Viridis",
+            Colormap::Inferno => "Inferno",
+            Colormap::Jet => "Jet",
+            Colormap::Grayscale => "Grayscale",
+        }
+    }
+
+    fn anchors(&self) -> &'static [[u8; 3]] {
+        match self {
+            Colormap::Viridis => VIRIDIS,
+            Colormap::Inferno => INFERNO,
+            Colormap::Jet => JET,
+            Colormap::Grayscale => GRAYSCALE,
+        }
+    }
+
+    /// Map a normalized value (clamped to [0, 1]) to an RGB color
+    pub fn map(&self, t: f32) -> [u8; 3] {
+        let anchors = self.anchors();
+        let t = if t.is_nan() { 0.0 } else { t.clamp(0.0, 1.0) };
+        let scaled = t * (anchors.len() - 1) as f32;
+        let idx = (scaled.floor() as usize).min(anchors.len() - 2);
+        let frac = scaled - idx as f32;
+
+        let lo = anchors[idx];
+        let hi = anchors[idx + 1];
+        let mut rgb = [0u8; 3];
+        for channel in 0..3 {
+            let value = lo[channel] as f32 + (hi[channel] as f32 - lo[channel] as f32) * frac;
+            rgb[channel] = value.round() as u8;
+        }
+        rgb
+    }
+}
@@ -0,0 +1,113 @@
+use crate::parser::SampleReader;
+use anyhow::Result;
+use num_complex::Complex;
+use polars::prelude::*;
+use rustfft::FftPlanner;
+use std::path::Path;
+
+// Samples used to estimate the frequency offset; longer ranges are truncated
+const MAX_ESTIMATE_FFT: usize = 1 << 16;
+
+/// Coarse carrier correction applied before plotting
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FreqCorrection {
+    None,
+    /// Raise the signal to the given power (1 for a plain spectral peak, 2 for
+    /// BPSK, 4 for QPSK, ...) to strip the modulation, then take the FFT peak
+    PowerPeak(u32),
+}
+
+#[derive(Debug, Clone)]
+pub struct Constellation {
+    pub start_sample: u64,
+    pub points: Vec<Complex<f32>>,
+    /// Offset that was removed, if a correction was applied
+    pub freq_offset_hz: Option<f64>,
+}
+
+impl Constellation {
+    pub fn compute(
+        reader: &mut dyn SampleReader,
+        start: u64,
+        count: usize,
+        sample_rate: f64,
+        correction: FreqCorrection,
+    ) -> Result<Self> {
+        let mut points = reader.read_samples(start, count)?;
+        if points.is_empty() {
+            anyhow::bail!("No samples in range starting at {}", start);
+        }
+
+        let freq_offset_hz = match correction {
+            FreqCorrection::None => None,
+            FreqCorrection::PowerPeak(power) => {
+                let offset = estimate_frequency_offset(&points, power);
+                mix(&mut points, -offset);
+                Some(offset * sample_rate)
+            }
+        };
+
+        Ok(Constellation {
+            start_sample: start,
+            points,
+            freq_offset_hz,
+        })
+    }
+
+    /// Largest |I| or |Q|, for axis scaling
+    pub fn peak_amplitude(&self) -> f32 {
+        self.points
+            .iter()
+            .fold(0.0f32, |acc, p| acc.max(p.re.abs()).max(p.im.abs()))
+    }
+
+    pub fn to_dataframe(&self) -> Result<DataFrame> {
+        let df = df! {
+            "sample" => (0..self.points.len() as u64).map(|i| self.start_sample + i).collect::<Vec<_>>(),
+            "i" => self.points.iter().map(|p| p.re).collect::<Vec<_>>(),
+            "q" => self.points.iter().map(|p| p.im).collect::<Vec<_>>(),
+        }?;
+        Ok(df)
+    }
+
+    pub fn write_csv<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        let mut file = std::fs::File::create(path)?;
+        CsvWriter::new(&mut file).finish(&mut self.to_dataframe()?)?;
+        Ok(())
+    }
+}
+
+/// Estimate the carrier offset in cycles/sample from the FFT peak of the
+/// signal raised to `power`
+pub fn estimate_frequency_offset(samples: &[Complex<f32>], power: u32) -> f64 {
+    let power = power.max(1);
+    let fft_size = samples.len().min(MAX_ESTIMATE_FFT).next_power_of_two();
+    let mut buffer: Vec<Complex<f32>> = samples
+        .iter()
+        .take(fft_size)
+        .map(|s| s.powu(power))
+        .collect();
+    buffer.resize(fft_size, Complex::new(0.0, 0.0));
+    FftPlanner::new().plan_fft_forward(fft_size).process(&mut buffer);
+
+    let peak_bin = buffer
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.norm_sqr().total_cmp(&b.1.norm_sqr()))
+        .map(|(bin, _)| bin)
+        .unwrap_or(0);
+    let signed_bin = if peak_bin >= fft_size / 2 {
+        peak_bin as f64 - fft_size as f64
+    } else {
+        peak_bin as f64
+    };
+    signed_bin / fft_size as f64 / power as f64
+}
+
+/// Frequency shift samples in place by `offset` cycles/sample
+pub fn mix(samples: &mut [Complex<f32>], offset: f64) {
+    for (n, sample) in samples.iter_mut().enumerate() {
+        let phase = 2.0 * std::f64::consts::PI * offset * n as f64;
+        *sample *= Complex::new(phase.cos() as f32, phase.sin() as f32);
+    }
+}
@@ -0,0 +1,101 @@
+use crate::parser::sigmf::AnnotationInfo;
+use anyhow::Result;
+use std::path::{Path, PathBuf};
+use url::Url;
+
+pub const DEEP_LINK_SCHEME: &str = "sigviewer";
+
+/// A location in a recording: `sigviewer://open?file=...&start=...&count=...&freq_lo=...&freq_hi=...`.
+/// Everything but the file is optional; missing parts show the whole
+/// recording or band.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeepLink {
+    /// Absolute path to the `.sigmf-meta` file
+    pub file: PathBuf,
+    pub sample_start: Option<u64>,
+    pub sample_count: Option<u64>,
+    /// Frequency span in Hz
+    pub freq_lower: Option<f64>,
+    pub freq_upper: Option<f64>,
+}
+
+impl DeepLink {
+    pub fn new<P: AsRef<Path>>(file: P) -> Self {
+        let file = file.as_ref();
+        DeepLink {
+            file: std::fs::canonicalize(file).unwrap_or_else(|_| file.to_path_buf()),
+            sample_start: None,
+            sample_count: None,
+            freq_lower: None,
+            freq_upper: None,
+        }
+    }
+
+    /// Link to the extent of an annotation
+    pub fn for_annotation<P: AsRef<Path>>(file: P, annotation: &AnnotationInfo) -> Self {
+        DeepLink {
+            sample_start: Some(annotation.sample_start),
+            sample_count: Some(annotation.sample_count),
+            freq_lower: annotation.freq_lower_edge,
+            freq_upper: annotation.freq_upper_edge,
+            ..Self::new(file)
+        }
+    }
+
+    pub fn parse(uri: &str) -> Result<Self> {
+        let url = Url::parse(uri).map_err(|e| anyhow::anyhow!("Invalid link {}: {}", uri, e))?;
+        if url.scheme() != DEEP_LINK_SCHEME {
+            anyhow::bail!("Not a {}:// link: {}", DEEP_LINK_SCHEME, uri);
+        }
+        let mut link = DeepLink {
+            file: PathBuf::new(),
+            sample_start: None,
+            sample_count: None,
+            freq_lower: None,
+            freq_upper: None,
+        };
+        for (key, value) in url.query_pairs() {
+            let invalid = || anyhow::anyhow!("Invalid {} in link: {}", key, value);
+            match key.as_ref() {
+                "file" => link.file = PathBuf::from(value.as_ref()),
+                "start" => link.sample_start = Some(value.parse().map_err(|_| invalid())?),
+                "count" => link.sample_count = Some(value.parse().map_err(|_| invalid())?),
+                "freq_lo" => link.freq_lower = Some(value.parse().map_err(|_| invalid())?),
+                "freq_hi" => link.freq_upper = Some(value.parse().map_err(|_| invalid())?),
+                // Unknown keys are ignored so newer links still open
+                _ => {}
+            }
+        }
+        if link.file.as_os_str().is_empty() {
+            anyhow::bail!("Link has no file: {}", uri);
+        }
+        Ok(link)
+    }
+
+    pub fn to_uri(&self) -> String {
+        let mut url = Url::parse(&format!("{}://open", DEEP_LINK_SCHEME)).expect("static URL");
+        {
+            let mut query = url.query_pairs_mut();
+            query.append_pair("file", &self.file.to_string_lossy());
+            if let Some(start) = self.sample_start {
+                query.append_pair("start", &start.to_string());
+            }
+            if let Some(count) = self.sample_count {
+                query.append_pair("count", &count.to_string());
+            }
+            if let Some(freq) = self.freq_lower {
+                query.append_pair("freq_lo", &freq.to_string());
+            }
+            if let Some(freq) = self.freq_upper {
+                query.append_pair("freq_hi", &freq.to_string());
+            }
+        }
+        url.to_string()
+    }
+}
+
+impl std::fmt::Display for DeepLink {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_uri())
+    }
+}
@@ -0,0 +1,153 @@
+use crate::data_ops::Histogram;
+use anyhow::Result;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::Path;
+
+/// Decimals that tell neighbouring bin edges apart: none for bins ten
+/// units or wider, up to 6 for narrow ones
+pub fn edge_precision(bin_width: f64) -> usize {
+    if bin_width.is_nan() || bin_width <= 0.0 {
+        return 0;
+    }
+    ((-bin_width.log10()).ceil() + 1.0).clamp(0.0, 6.0) as usize
+}
+
+/// One line per bin with its edges, a bar of `width` characters at most and
+/// its count
+pub fn ascii_histogram(histogram: &Histogram, width: usize, log_scale: bool) -> String {
+    let precision = edge_precision(histogram.bin_width());
+    let edges: Vec<(String, String)> = (0..histogram.counts.len())
+        .map(|idx| {
+            let (left, right) = histogram.bin_edges(idx);
+            (format!("{:.*}", precision, left), format!("{:.*}", precision, right))
+        })
+        .collect();
+    let edge_width = edges.iter().map(|(left, right)| left.len().max(right.len())).max().unwrap_or(0);
+    let mut out = String::new();
+    for ((left, right), &count) in edges.iter().zip(&histogram.counts) {
+        let bar = (histogram.bar_fraction(count, log_scale) * width as f64).round() as usize;
+        out.push_str(&format!(
+            "{:>w$} .. {:>w$} │{}{} {}\n",
+            left,
+            right,
+            "█".repeat(bar),
+            " ".repeat(width - bar.min(width)),
+            count,
+            w = edge_width
+        ));
+    }
+    out
+}
+
+const BACKGROUND: [u8; 3] = [255, 255, 255];
+const BAR: [u8; 3] = [70, 130, 180];
+const AXIS: [u8; 3] = [40, 40, 40];
+const MARGIN_LEFT: usize = 70;
+const MARGIN_RIGHT: usize = 20;
+const MARGIN_TOP: usize = 20;
+const MARGIN_BOTTOM: usize = 30;
+/// Pixels per font dot
+const FONT_SCALE: usize = 2;
+
+/// An RGB bar chart of `histogram` with its range and largest count
+/// labelled, written as a PNG
+pub fn write_histogram_png(histogram: &Histogram, path: &Path, width: usize, height: usize, log_scale: bool) -> Result<()> {
+    if width < MARGIN_LEFT + MARGIN_RIGHT + histogram.counts.len() || height < MARGIN_TOP + MARGIN_BOTTOM + 20 {
+        anyhow::bail!("{}x{} is too small for a {}-bin histogram", width, height, histogram.counts.len());
+    }
+    let mut canvas = Canvas { width, pixels: [BACKGROUND].repeat(width * height) };
+    let (plot_left, plot_right) = (MARGIN_LEFT, width - MARGIN_RIGHT);
+    let (plot_top, plot_bottom) = (MARGIN_TOP, height - MARGIN_BOTTOM);
+    let bin_width = (plot_right - plot_left) as f64 / histogram.counts.len() as f64;
+    let gap = usize::from(bin_width > 3.0);
+    for (idx, &count) in histogram.counts.iter().enumerate() {
+        let bar_height = (histogram.bar_fraction(count, log_scale) * (plot_bottom - plot_top) as f64).round() as usize;
+        let left = plot_left + (idx as f64 * bin_width) as usize;
+        let right = plot_left + ((idx + 1) as f64 * bin_width) as usize;
+        canvas.fill(left + gap, plot_bottom - bar_height, right, plot_bottom, BAR);
+    }
+    canvas.fill(plot_left - 1, plot_top, plot_left, plot_bottom + 1, AXIS);
+    canvas.fill(plot_left - 1, plot_bottom, plot_right, plot_bottom + 1, AXIS);
+
+    let precision = edge_precision(histogram.bin_width());
+    let label_y = plot_bottom + 6;
+    canvas.text(plot_left, label_y, &format!("{:.*}", precision, histogram.min), Align::Left);
+    canvas.text((plot_left + plot_right) / 2, label_y, &format!("{:.*}", precision, (histogram.min + histogram.max) / 2.0), Align::Center);
+    canvas.text(plot_right, label_y, &format!("{:.*}", precision, histogram.max), Align::Right);
+    let max_count = histogram.counts.iter().copied().max().unwrap_or(0);
+    canvas.text(plot_left - 6, plot_top, &max_count.to_string(), Align::Right);
+    canvas.text(plot_left - 6, plot_bottom - 5 * FONT_SCALE, "0", Align::Right);
+
+    let file = BufWriter::new(File::create(path).map_err(|e| anyhow::anyhow!("Failed to create {}: {}", path.display(), e))?);
+    let mut encoder = png::Encoder::new(file, width as u32, height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&canvas.pixels.concat())?;
+    Ok(())
+}
+
+enum Align {
+    Left,
+    Center,
+    Right,
+}
+
+struct Canvas {
+    width: usize,
+    pixels: Vec<[u8; 3]>,
+}
+
+impl Canvas {
+    /// Fill columns `x0..x1` of rows `y0..y1`
+    fn fill(&mut self, x0: usize, y0: usize, x1: usize, y1: usize, color: [u8; 3]) {
+        let height = self.pixels.len() / self.width;
+        for y in y0..y1.min(height) {
+            for x in x0..x1.min(self.width) {
+                self.pixels[y * self.width + x] = color;
+            }
+        }
+    }
+
+    /// Numbers only, in a 3x5 dot font; other characters leave a space
+    fn text(&mut self, x: usize, y: usize, text: &str, align: Align) {
+        let advance = 4 * FONT_SCALE;
+        let text_width = text.chars().count() * advance;
+        let mut left = match align {
+            Align::Left => x,
+            Align::Center => x.saturating_sub(text_width / 2),
+            Align::Right => x.saturating_sub(text_width),
+        };
+        for c in text.chars() {
+            if let Some(rows) = glyph(c) {
+                for (row, bits) in rows.iter().enumerate() {
+                    for col in 0..3 {
+                        if bits & (0b100 >> col) != 0 {
+                            let (px, py) = (left + col * FONT_SCALE, y + row * FONT_SCALE);
+                            self.fill(px, py, px + FONT_SCALE, py + FONT_SCALE, AXIS);
+                        }
+                    }
+                }
+            }
+            left += advance;
+        }
+    }
+}
+
+fn glyph(c: char) -> Option<[u8; 5]> {
+    Some(match c {
+        '0' => [0b111, 0b101, 0b101, 0b101, 0b111],
+        '1' => [0b010, 0b110, 0b010, 0b010, 0b111],
+        '2' => [0b111, 0b001, 0b111, 0b100, 0b111],
+        '3' => [0b111, 0b001, 0b111, 0b001, 0b111],
+        '4' => [0b101, 0b101, 0b111, 0b001, 0b001],
+        '5' => [0b111, 0b100, 0b111, 0b001, 0b111],
+        '6' => [0b111, 0b100, 0b111, 0b101, 0b111],
+        '7' => [0b111, 0b001, 0b001, 0b001, 0b001],
+        '8' => [0b111, 0b101, 0b111, 0b101, 0b111],
+        '9' => [0b111, 0b101, 0b111, 0b001, 0b111],
+        '.' => [0b000, 0b000, 0b000, 0b000, 0b010],
+        '-' => [0b000, 0b000, 0b111, 0b000, 0b000],
+        _ => return None,
+    })
+}
@@ -0,0 +1,93 @@
+use super::{Spectrogram, SpectrogramParams, NOISE_FLOOR_PERCENTILE};
+use crate::parser::SampleReader;
+use anyhow::Result;
+
+/// Fraction of the power above the noise floor that the occupied bandwidth
+/// holds (0.5% is left out on each side)
+pub const OCCUPIED_POWER_FRACTION: f64 = 0.99;
+
+// dB above the floor a bin must reach to count as signal. Averaged noise
+// bins scatter well under a dB around the floor, and summed over the whole
+// band that scatter would otherwise widen the occupied bandwidth.
+const SIGNAL_MARGIN_DB: f64 = 3.0;
+
+/// Levels measured from a recording's samples, as opposed to what its
+/// annotations claim. Powers are in dB per FFT bin relative to full scale,
+/// the same scale as [`estimate_noise_floor`](super::estimate_noise_floor).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SignalMetrics {
+    pub noise_floor_db: f32,
+    /// Strongest bin of the averaged spectrum
+    pub peak_power_db: f32,
+    /// Band holding [`OCCUPIED_POWER_FRACTION`] of the power above the noise
+    /// floor in bins clearly above it; `None` when no bin is
+    pub occupied_bandwidth_hz: Option<f64>,
+    /// Power above the noise in the occupied band over the noise power in the
+    /// same band; `None` along with `occupied_bandwidth_hz`
+    pub snr_db: Option<f32>,
+}
+
+/// Measure `count` samples starting at `start` from their averaged spectrum.
+/// Like the noise floor estimate, at most 64 FFTs spread over the span are
+/// read, so it's cheap enough to run while scanning a directory. A signal
+/// present for part of the span is averaged with the quiet rest of it.
+pub fn measure_signal(reader: &mut dyn SampleReader, start: u64, count: u64, sample_rate: f64) -> Result<SignalMetrics> {
+    let available = reader.num_samples().saturating_sub(start).min(count);
+    // Short spans get a smaller FFT rather than failing
+    let fft_size = (available.clamp(16, 1024) as usize + 1).next_power_of_two() / 2;
+    let params = SpectrogramParams {
+        fft_size,
+        max_rows: 64,
+    };
+    let spectrogram = Spectrogram::compute(reader, start, available, &params)?;
+    let fft_size = spectrogram.fft_size;
+    let mut spectrum = vec![0.0f64; fft_size];
+    for row in spectrogram.power_db.chunks(fft_size) {
+        for (sum, db) in spectrum.iter_mut().zip(row) {
+            *sum += 10f64.powf(*db as f64 / 10.0);
+        }
+    }
+    let rows = spectrogram.num_rows.max(1) as f64;
+    spectrum.iter_mut().for_each(|power| *power /= rows);
+
+    // Averaging leaves the noise bins close together, so a low percentile of
+    // them is the floor even with signals in a good part of the band
+    let mut sorted = spectrum.clone();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let noise = sorted[((NOISE_FLOOR_PERCENTILE / 100.0) * (fft_size - 1) as f32).round() as usize];
+    let peak = sorted[fft_size - 1];
+    let to_db = |power: f64| (10.0 * (power + 1e-20).log10()) as f32;
+
+    let signal_level = noise * 10f64.powf(SIGNAL_MARGIN_DB / 10.0);
+    let excess: Vec<f64> = spectrum.iter().map(|power| if *power >= signal_level { power - noise } else { 0.0 }).collect();
+    let total: f64 = excess.iter().sum();
+    let (occupied_bandwidth_hz, snr_db) = if total > 0.0 {
+        let tail = total * (1.0 - OCCUPIED_POWER_FRACTION) / 2.0;
+        let low = cumulative_crossing(excess.iter(), tail);
+        let high = fft_size - 1 - cumulative_crossing(excess.iter().rev(), tail);
+        let bins = high.saturating_sub(low) + 1;
+        let signal: f64 = excess[low..low + bins].iter().sum();
+        let bin_hz = sample_rate / fft_size as f64;
+        (Some(bins as f64 * bin_hz), Some(to_db(signal) - to_db(noise * bins as f64)))
+    } else {
+        (None, None)
+    };
+    Ok(SignalMetrics {
+        noise_floor_db: to_db(noise),
+        peak_power_db: to_db(peak),
+        occupied_bandwidth_hz,
+        snr_db,
+    })
+}
+
+/// Index of the bin where the running sum of `powers` first exceeds `tail`
+fn cumulative_crossing<'a>(powers: impl Iterator<Item = &'a f64>, tail: f64) -> usize {
+    let mut sum = 0.0;
+    for (idx, power) in powers.enumerate() {
+        sum += power;
+        if sum > tail {
+            return idx;
+        }
+    }
+    0
+}
@@ -0,0 +1,24 @@
+pub mod colormap;
+pub mod constellation;
+pub mod deep_link;
+pub mod histogram;
+pub mod metrics;
+pub mod noise_floor;
+pub mod psd;
+pub mod spectrogram;
+pub mod thumbnail;
+pub mod time_domain;
+
+pub use colormap::Colormap;
+pub use constellation::{Constellation, FreqCorrection};
+pub use deep_link::{DeepLink, DEEP_LINK_SCHEME};
+pub use histogram::{ascii_histogram, edge_precision, write_histogram_png};
+pub use metrics::{measure_signal, SignalMetrics, OCCUPIED_POWER_FRACTION};
+pub use noise_floor::{estimate_noise_floor, NOISE_FLOOR_PERCENTILE};
+pub use psd::{Psd, PsdParams};
+pub use spectrogram::{Spectrogram, SpectrogramParams};
+pub use thumbnail::{
+    default_thumbnail_dir, read_thumbnail, render_thumbnail, thumbnail_dataset, thumbnail_path, write_thumbnail, ThumbnailParams, ThumbnailSummary,
+    THUMBNAIL_COLUMN,
+};
+pub use time_domain::TimeDomainEnvelope;
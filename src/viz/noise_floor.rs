@@ -0,0 +1,23 @@
+use super::{Spectrogram, SpectrogramParams};
+use crate::parser::SampleReader;
+use anyhow::Result;
+
+/// Percentile of all spectrogram cells taken as the noise floor. Signals
+/// usually occupy a minority of time/frequency cells, so a low percentile
+/// tracks the noise without being pulled up by them.
+pub const NOISE_FLOOR_PERCENTILE: f32 = 20.0;
+
+/// Estimate the noise floor of a whole recording from its samples, in dB per
+/// FFT bin relative to full scale. Only up to 64 FFTs spread over the file
+/// are read, so it's cheap enough to run while scanning a directory.
+pub fn estimate_noise_floor(reader: &mut dyn SampleReader) -> Result<f32> {
+    let num_samples = reader.num_samples();
+    // Short recordings get a smaller FFT rather than failing
+    let fft_size = (num_samples.clamp(16, 1024) as usize + 1).next_power_of_two() / 2;
+    let params = SpectrogramParams {
+        fft_size,
+        max_rows: 64,
+    };
+    let spectrogram = Spectrogram::compute(reader, 0, num_samples, &params)?;
+    Ok(spectrogram.percentile_db(NOISE_FLOOR_PERCENTILE))
+}
@@ -0,0 +1,105 @@
+use super::{Spectrogram, SpectrogramParams};
+use crate::parser::SampleReader;
+use anyhow::Result;
+
+#[derive(Debug, Clone)]
+pub struct PsdParams {
+    pub fft_size: usize,
+    /// FFTs averaged, spread evenly over the recording
+    pub averages: usize,
+}
+
+impl Default for PsdParams {
+    fn default() -> Self {
+        Self {
+            fft_size: 2048,
+            averages: 128,
+        }
+    }
+}
+
+/// Average power spectrum of a recording (Welch's method without overlap
+/// control: Hann-windowed FFTs averaged in linear power)
+#[derive(Debug, Clone)]
+pub struct Psd {
+    /// Absolute frequency of the middle bin, or 0 (baseband) if unknown
+    pub center_freq: f64,
+    pub sample_rate: f64,
+    /// dB per bin, fft-shifted so the lowest frequency comes first
+    pub power_db: Vec<f32>,
+}
+
+impl Psd {
+    pub fn compute(reader: &mut dyn SampleReader, sample_rate: f64, center_freq: Option<f64>, params: &PsdParams) -> Result<Self> {
+        let spectrogram_params = SpectrogramParams {
+            fft_size: params.fft_size,
+            max_rows: params.averages,
+        };
+        let num_samples = reader.num_samples();
+        let spectrogram = Spectrogram::compute(reader, 0, num_samples, &spectrogram_params)?;
+        let fft_size = spectrogram.fft_size;
+        let mut linear = vec![0.0f64; fft_size];
+        for row in spectrogram.power_db.chunks(fft_size) {
+            for (sum, db) in linear.iter_mut().zip(row) {
+                *sum += 10f64.powf(*db as f64 / 10.0);
+            }
+        }
+        let rows = spectrogram.num_rows.max(1) as f64;
+        Ok(Psd {
+            center_freq: center_freq.unwrap_or(0.0),
+            sample_rate,
+            power_db: linear.iter().map(|sum| (10.0 * (sum / rows + 1e-20).log10()) as f32).collect(),
+        })
+    }
+
+    pub fn bin_hz(&self) -> f64 {
+        self.sample_rate / self.power_db.len() as f64
+    }
+
+    /// Absolute frequency of bin `bin`
+    pub fn freq_of(&self, bin: usize) -> f64 {
+        self.center_freq + (bin as f64 - (self.power_db.len() / 2) as f64) * self.bin_hz()
+    }
+
+    /// Lowest and highest bin frequency
+    pub fn freq_range(&self) -> (f64, f64) {
+        (self.freq_of(0), self.freq_of(self.power_db.len().saturating_sub(1)))
+    }
+
+    /// Power at `freq`, interpolated between bins. `None` outside the
+    /// recorded band.
+    pub fn value_at(&self, freq: f64) -> Option<f32> {
+        let (low, high) = self.freq_range();
+        if self.power_db.is_empty() || freq < low || freq > high {
+            return None;
+        }
+        let position = (freq - low) / self.bin_hz();
+        let below = position.floor() as usize;
+        let Some(&next) = self.power_db.get(below + 1) else {
+            return self.power_db.get(below).copied();
+        };
+        let frac = (position - below as f64) as f32;
+        Some(self.power_db[below] * (1.0 - frac) + next * frac)
+    }
+
+    /// Median bin power, a rough noise floor used to line traces up
+    pub fn median_db(&self) -> f32 {
+        let mut sorted = self.power_db.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        sorted.get(sorted.len() / 2).copied().unwrap_or(0.0)
+    }
+
+    /// `self - other` in dB (after adding each trace's offset) at each of this
+    /// trace's bins that `other` also covers, as (frequency, dB) pairs. The
+    /// two recordings can have different tunings and rates; `other` is
+    /// interpolated onto this trace's bins.
+    pub fn difference(&self, other: &Psd, self_offset_db: f32, other_offset_db: f32) -> Vec<(f64, f32)> {
+        (0..self.power_db.len())
+            .filter_map(|bin| {
+                let freq = self.freq_of(bin);
+                let theirs = other.value_at(freq)?;
+                Some((freq, (self.power_db[bin] + self_offset_db) - (theirs + other_offset_db)))
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,119 @@
+use super::Colormap;
+use crate::parser::SampleReader;
+use anyhow::Result;
+use num_complex::Complex;
+use rustfft::FftPlanner;
+
+#[derive(Debug, Clone)]
+pub struct SpectrogramParams {
+    pub fft_size: usize,
+    /// Upper bound on the number of time rows. Long ranges are strided
+    /// rather than averaged so the cost stays bounded.
+    pub max_rows: usize,
+}
+
+impl Default for SpectrogramParams {
+    fn default() -> Self {
+        Self {
+            fft_size: 1024,
+            max_rows: 1024,
+        }
+    }
+}
+
+/// Power spectra over time. Rows are time, columns are frequency bins
+/// (already fft-shifted so DC is in the middle).
+#[derive(Debug, Clone)]
+pub struct Spectrogram {
+    pub fft_size: usize,
+    pub num_rows: usize,
+    pub start_sample: u64,
+    /// Number of samples between the starts of consecutive rows
+    pub row_step: u64,
+    pub power_db: Vec<f32>,
+}
+
+impl Spectrogram {
+    /// Compute the spectrogram of `count` samples starting at `start`
+    pub fn compute(
+        reader: &mut dyn SampleReader,
+        start: u64,
+        count: u64,
+        params: &SpectrogramParams,
+    ) -> Result<Self> {
+        let fft_size = params.fft_size.max(16);
+        let available = reader.num_samples().saturating_sub(start).min(count);
+        if available < fft_size as u64 {
+            anyhow::bail!("Not enough samples for a {} point FFT", fft_size);
+        }
+
+        let full_rows = available / fft_size as u64;
+        let num_rows = full_rows.min(params.max_rows.max(1) as u64) as usize;
+        let row_step = if num_rows as u64 == full_rows {
+            fft_size as u64
+        } else {
+            (available - fft_size as u64) / (num_rows as u64 - 1).max(1)
+        };
+
+        let window = hann_window(fft_size);
+        let window_power: f32 = window.iter().map(|w| w * w).sum();
+        let fft = FftPlanner::new().plan_fft_forward(fft_size);
+
+        let mut power_db = Vec::with_capacity(num_rows * fft_size);
+        let mut buffer = vec![Complex::new(0.0f32, 0.0); fft_size];
+        for row in 0..num_rows {
+            let samples = reader.read_samples(start + row as u64 * row_step, fft_size)?;
+            for (i, slot) in buffer.iter_mut().enumerate() {
+                *slot = samples.get(i).copied().unwrap_or_default() * window[i];
+            }
+            fft.process(&mut buffer);
+
+            let half = fft_size / 2;
+            for bin in 0..fft_size {
+                let value = buffer[(bin + half) % fft_size];
+                power_db.push(10.0 * (value.norm_sqr() / window_power + 1e-20).log10());
+            }
+        }
+
+        Ok(Spectrogram {
+            fft_size,
+            num_rows,
+            start_sample: start,
+            row_step,
+            power_db,
+        })
+    }
+
+    /// Power level at the given percentile (0-100), useful for picking a
+    /// default dynamic range
+    pub fn percentile_db(&self, percentile: f32) -> f32 {
+        if self.power_db.is_empty() {
+            return 0.0;
+        }
+        let mut sorted = self.power_db.clone();
+        sorted.sort_by(|a, b| a.total_cmp(b));
+        let idx = ((percentile.clamp(0.0, 100.0) / 100.0) * (sorted.len() - 1) as f32).round() as usize;
+        sorted[idx]
+    }
+
+    /// Render to RGB pixels (row-major, `num_rows` x `fft_size`)
+    pub fn to_rgb(&self, colormap: Colormap, min_db: f32, max_db: f32) -> Vec<[u8; 3]> {
+        let span = (max_db - min_db).max(f32::EPSILON);
+        self.power_db
+            .iter()
+            .map(|db| colormap.map((db - min_db) / span))
+            .collect()
+    }
+}
+
+pub fn hann_window(size: usize) -> Vec<f32> {
+    if size < 2 {
+        return vec![1.0; size];
+    }
+    (0..size)
+        .map(|i| {
+            let phase = 2.0 * std::f32::consts::PI * i as f32 / (size - 1) as f32;
+            0.5 - 0.5 * phase.cos()
+        })
+        .collect()
+}
@@ -0,0 +1,235 @@
+//! Small spectrogram images of whole recordings, cached as PNGs so hundreds
+//! of recordings can be skimmed without opening each one.
+
+use super::{Colormap, Spectrogram, SpectrogramParams};
+use crate::parser::sigmf::SigMFDataset;
+use crate::parser::FileParser;
+use crate::workers::{Priority, WorkerPool};
+use anyhow::Result;
+use polars::prelude::*;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// Column [`thumbnail_dataset`] adds, holding each row's thumbnail file
+pub const THUMBNAIL_COLUMN: &str = "thumbnail_path";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ThumbnailParams {
+    /// Pixels across, frequency increasing to the right
+    pub width: usize,
+    /// Pixels down, time increasing downwards
+    pub height: usize,
+    pub colormap: Colormap,
+}
+
+impl Default for ThumbnailParams {
+    fn default() -> Self {
+        Self {
+            width: 128,
+            height: 64,
+            colormap: Colormap::Viridis,
+        }
+    }
+}
+
+/// `~/.cache/sig_viewer/thumbnails` (or the platform equivalent)
+pub fn default_thumbnail_dir() -> PathBuf {
+    dirs::cache_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("sig_viewer")
+        .join("thumbnails")
+}
+
+/// Where the thumbnail of the recording at `meta_path` goes in `dir`. The
+/// name follows the recording's path, the size and modification time of it
+/// and its `.sigmf-data` file, and the image size, so an edited recording
+/// gets a new thumbnail rather than a stale one.
+pub fn thumbnail_path(dir: &Path, meta_path: &Path, params: &ThumbnailParams) -> PathBuf {
+    let mut hasher = DefaultHasher::new();
+    let meta_path = meta_path.canonicalize().unwrap_or_else(|_| meta_path.to_path_buf());
+    meta_path.hash(&mut hasher);
+    for path in [meta_path.clone(), meta_path.with_extension("sigmf-data")] {
+        if let Ok(metadata) = std::fs::metadata(&path) {
+            (metadata.len(), metadata.modified().ok()).hash(&mut hasher);
+        }
+    }
+    (params.width, params.height, params.colormap.name()).hash(&mut hasher);
+    dir.join(format!("{:016x}.png", hasher.finish()))
+}
+
+/// Spectrogram of the whole recording at `meta_path` as `params.width` x
+/// `params.height` RGB pixels, row-major. Bins and time rows are pooled by
+/// their maximum, so a short burst still shows.
+pub fn render_thumbnail(meta_path: &Path, params: &ThumbnailParams) -> Result<Vec<[u8; 3]>> {
+    let (width, height) = (params.width.max(1), params.height.max(1));
+    let mut reader = FileParser::open_reader(meta_path)?;
+    let num_samples = reader.num_samples();
+    // Short recordings get a smaller FFT rather than failing
+    let fft_size = (num_samples.clamp(16, width.next_power_of_two().max(16) as u64) as usize + 1).next_power_of_two() / 2;
+    let spectrogram_params = SpectrogramParams {
+        fft_size,
+        max_rows: height * 4,
+    };
+    let spectrogram = Spectrogram::compute(reader.as_mut(), 0, num_samples, &spectrogram_params)?;
+    let min_db = spectrogram.percentile_db(5.0);
+    let max_db = spectrogram.percentile_db(99.9);
+
+    let (fft_size, num_rows) = (spectrogram.fft_size, spectrogram.num_rows);
+    let mut pooled = vec![f32::NEG_INFINITY; width * height];
+    for row in 0..num_rows {
+        let y = row * height / num_rows;
+        for bin in 0..fft_size {
+            let x = bin * width / fft_size;
+            let cell = &mut pooled[y * width + x];
+            *cell = cell.max(spectrogram.power_db[row * fft_size + bin]);
+        }
+    }
+    // Fewer rows or bins than pixels: repeat the nearest one
+    for y in 0..height {
+        for x in 0..width {
+            if pooled[y * width + x] == f32::NEG_INFINITY {
+                let (row, bin) = (y * num_rows / height, x * fft_size / width);
+                pooled[y * width + x] = spectrogram.power_db[row * fft_size + bin];
+            }
+        }
+    }
+    let span = (max_db - min_db).max(f32::EPSILON);
+    Ok(pooled.iter().map(|db| params.colormap.map((db - min_db) / span)).collect())
+}
+
+/// Thumbnail of the recording at `meta_path` in `dir`, rendered unless
+/// already cached (or always when `force`). Returns its path and whether it
+/// was rendered now.
+pub fn write_thumbnail(meta_path: &Path, dir: &Path, params: &ThumbnailParams, force: bool) -> Result<(PathBuf, bool)> {
+    let path = thumbnail_path(dir, meta_path, params);
+    if path.is_file() && !force {
+        return Ok((path, false));
+    }
+    let pixels = render_thumbnail(meta_path, params)?;
+    std::fs::create_dir_all(dir)?;
+    // Written aside and renamed, so a viewer never reads a partial image
+    let partial = path.with_extension(format!("partial-{}", std::process::id()));
+    let file = BufWriter::new(File::create(&partial).map_err(|e| anyhow::anyhow!("Failed to create {}: {}", partial.display(), e))?);
+    let mut encoder = png::Encoder::new(file, params.width as u32, params.height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder.write_header()?.write_image_data(&pixels.concat())?;
+    std::fs::rename(&partial, &path)?;
+    Ok((path, true))
+}
+
+/// Read a thumbnail written by [`write_thumbnail`] as `(width, height, rgb)`
+pub fn read_thumbnail(path: &Path) -> Result<(usize, usize, Vec<u8>)> {
+    let file = BufReader::new(File::open(path).map_err(|e| anyhow::anyhow!("Failed to open {}: {}", path.display(), e))?);
+    let mut decoder = png::Decoder::new(file);
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size().unwrap_or(0)];
+    let info = reader.next_frame(&mut buffer)?;
+    buffer.truncate(info.buffer_size());
+    let rgb = match info.color_type {
+        png::ColorType::Rgb => buffer,
+        png::ColorType::Rgba => buffer.chunks(4).flat_map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect(),
+        png::ColorType::Grayscale => buffer.iter().flat_map(|&level| [level; 3]).collect(),
+        png::ColorType::GrayscaleAlpha => buffer.chunks(2).flat_map(|pixel| [pixel[0]; 3]).collect(),
+        other => anyhow::bail!("Unsupported PNG color type {:?} in {}", other, path.display()),
+    };
+    Ok((info.width as usize, info.height as usize, rgb))
+}
+
+/// What [`thumbnail_dataset`] did
+#[derive(Debug, Clone, Default)]
+pub struct ThumbnailSummary {
+    pub rendered: usize,
+    /// Already cached and unchanged
+    pub reused: usize,
+    /// Recordings that couldn't be located or rendered, with the reason
+    pub failed: Vec<(String, String)>,
+}
+
+/// Make sure every recording in `dataset` has a thumbnail in `dir`,
+/// rendering on `pool`, and add a [`THUMBNAIL_COLUMN`] column (null where
+/// it failed). Recordings are looked up by `meta_filename` under the row's
+/// `source_root`, else `root`.
+pub fn thumbnail_dataset(
+    dataset: &DataFrame,
+    root: &Path,
+    dir: &Path,
+    params: &ThumbnailParams,
+    force: bool,
+    pool: &WorkerPool,
+) -> Result<(DataFrame, ThumbnailSummary)> {
+    let names = dataset.column("meta_filename")?.str()?;
+    let roots = dataset.column("source_root").ok().and_then(|column| column.str().ok().cloned());
+    let mut summary = ThumbnailSummary::default();
+    let mut indexes: HashMap<PathBuf, HashMap<String, PathBuf>> = HashMap::new();
+    let mut tasks = Vec::new();
+    let mut seen = HashMap::new();
+    // Index into `tasks` of each row's recording
+    let mut row_tasks = Vec::with_capacity(dataset.height());
+    for row in 0..dataset.height() {
+        let Some(name) = names.get(row) else {
+            row_tasks.push(None);
+            continue;
+        };
+        let row_root = roots.as_ref().and_then(|roots| roots.get(row)).filter(|root| !root.is_empty()).map_or(root, Path::new);
+        let key = (row_root.to_path_buf(), name.to_string());
+        if let Some(&idx) = seen.get(&key) {
+            row_tasks.push(Some(idx));
+            continue;
+        }
+        seen.insert(key, tasks.len());
+        row_tasks.push(Some(tasks.len()));
+        // One walk per root finds plain files; archive members and VITA-49
+        // streams need a search
+        let index = indexes.entry(row_root.to_path_buf()).or_insert_with(|| index_files(row_root));
+        let meta_path = index.get(name).cloned().or_else(|| SigMFDataset::find_meta_file(row_root, name));
+        let task = meta_path.map(|meta_path| {
+            let (dir, params) = (dir.to_path_buf(), params.clone());
+            pool.submit(&format!("Thumbnail: {}", name), Priority::Low, move |_| write_thumbnail(&meta_path, &dir, &params, force))
+        });
+        tasks.push((name.to_string(), row_root.to_path_buf(), task));
+    }
+
+    let mut thumbnails = Vec::with_capacity(tasks.len());
+    for (name, row_root, task) in tasks {
+        let result = match task {
+            Some(task) => task.wait(),
+            None => Err(anyhow::anyhow!("Could not locate it under {}", row_root.display())),
+        };
+        match result {
+            Ok((path, rendered)) => {
+                if rendered {
+                    summary.rendered += 1;
+                } else {
+                    summary.reused += 1;
+                }
+                thumbnails.push(Some(path.to_string_lossy().to_string()));
+            }
+            Err(e) => {
+                summary.failed.push((name, format!("{:#}", e)));
+                thumbnails.push(None);
+            }
+        }
+    }
+    let column: Vec<Option<String>> = row_tasks.iter().map(|idx| idx.and_then(|idx| thumbnails[idx].clone())).collect();
+    let mut dataset = dataset.clone();
+    dataset.with_column(Series::new(THUMBNAIL_COLUMN.into(), column))?;
+    Ok((dataset, summary))
+}
+
+/// Every file under `root` by file name
+fn index_files(root: &Path) -> HashMap<String, PathBuf> {
+    WalkDir::new(root)
+        .follow_links(true)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter(|entry| entry.file_type().is_file())
+        .map(|entry| (entry.file_name().to_string_lossy().to_string(), entry.into_path()))
+        .collect()
+}
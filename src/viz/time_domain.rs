@@ -0,0 +1,94 @@
+use crate::parser::SampleReader;
+use anyhow::Result;
+
+// Samples read per I/O call when building an envelope over a long range
+const READ_CHUNK: usize = 1 << 20;
+// Normalized amplitude treated as full scale when counting clipped samples
+const CLIP_LEVEL: f32 = 0.999;
+
+/// Min/max envelope of I, Q and magnitude over a sample range. When the range
+/// has more samples than output points, each point covers a bucket of samples
+/// and keeps its extremes so short bursts and clipping stay visible.
+#[derive(Debug, Clone, Default)]
+pub struct TimeDomainEnvelope {
+    pub start_sample: u64,
+    pub samples_per_point: u64,
+    pub i_min: Vec<f32>,
+    pub i_max: Vec<f32>,
+    pub q_min: Vec<f32>,
+    pub q_max: Vec<f32>,
+    pub mag_min: Vec<f32>,
+    pub mag_max: Vec<f32>,
+    /// Samples in the range where I or Q reached full scale
+    pub clipped_samples: u64,
+}
+
+impl TimeDomainEnvelope {
+    pub fn compute(
+        reader: &mut dyn SampleReader,
+        start: u64,
+        count: u64,
+        max_points: usize,
+    ) -> Result<Self> {
+        let count = reader.num_samples().saturating_sub(start).min(count);
+        let max_points = max_points.max(1) as u64;
+        let samples_per_point = count.div_ceil(max_points).max(1);
+        let num_points = count.div_ceil(samples_per_point) as usize;
+
+        let mut envelope = TimeDomainEnvelope {
+            start_sample: start,
+            samples_per_point,
+            i_min: vec![f32::INFINITY; num_points],
+            i_max: vec![f32::NEG_INFINITY; num_points],
+            q_min: vec![f32::INFINITY; num_points],
+            q_max: vec![f32::NEG_INFINITY; num_points],
+            mag_min: vec![f32::INFINITY; num_points],
+            mag_max: vec![f32::NEG_INFINITY; num_points],
+            clipped_samples: 0,
+        };
+
+        let mut offset = 0u64;
+        while offset < count {
+            let chunk_len = (count - offset).min(READ_CHUNK as u64) as usize;
+            let samples = reader.read_samples(start + offset, chunk_len)?;
+            if samples.is_empty() {
+                break;
+            }
+            for (i, sample) in samples.iter().enumerate() {
+                let point = ((offset + i as u64) / samples_per_point) as usize;
+                let magnitude = sample.norm();
+                envelope.i_min[point] = envelope.i_min[point].min(sample.re);
+                envelope.i_max[point] = envelope.i_max[point].max(sample.re);
+                envelope.q_min[point] = envelope.q_min[point].min(sample.im);
+                envelope.q_max[point] = envelope.q_max[point].max(sample.im);
+                envelope.mag_min[point] = envelope.mag_min[point].min(magnitude);
+                envelope.mag_max[point] = envelope.mag_max[point].max(magnitude);
+                if sample.re.abs() >= CLIP_LEVEL || sample.im.abs() >= CLIP_LEVEL {
+                    envelope.clipped_samples += 1;
+                }
+            }
+            offset += samples.len() as u64;
+        }
+
+        Ok(envelope)
+    }
+
+    pub fn len(&self) -> usize {
+        self.i_min.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.i_min.is_empty()
+    }
+
+    /// Largest absolute value over I, Q and magnitude, for axis scaling
+    pub fn peak_amplitude(&self) -> f32 {
+        self.i_min.iter()
+            .chain(&self.i_max)
+            .chain(&self.q_min)
+            .chain(&self.q_max)
+            .chain(&self.mag_max)
+            .filter(|v| v.is_finite())
+            .fold(0.0f32, |acc, v| acc.max(v.abs()))
+    }
+}
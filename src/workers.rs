@@ -0,0 +1,415 @@
+//! Shared pool of worker threads for slow background work (checksums,
+//! sample measurements, spectra).
+//!
+//! Work is submitted as a named task with a priority and runs on the first
+//! free worker, highest priority first and in submission order within a
+//! priority. Each task gets a [`TaskContext`] to report progress and notice
+//! cancellation, and its result comes back through the [`TaskHandle`]
+//! returned by [`WorkerPool::submit`]. [`WorkerPool::tasks`] lists queued,
+//! running and finished tasks for display.
+
+use anyhow::Result;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+use std::sync::mpsc::{channel, Receiver, TryRecvError};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// Finished tasks kept for display; older ones are forgotten
+const MAX_FINISHED_TASKS: usize = 200;
+
+pub type TaskId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    /// Bulk work nobody is waiting on
+    Low,
+    Normal,
+    /// Something the user is looking at right now
+    High,
+}
+
+impl Priority {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::Low => "low",
+            Priority::Normal => "normal",
+            Priority::High => "high",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum TaskStatus {
+    Queued,
+    Running,
+    Done,
+    Failed(String),
+    Cancelled,
+}
+
+impl TaskStatus {
+    pub fn is_finished(&self) -> bool {
+        matches!(self, TaskStatus::Done | TaskStatus::Failed(_) | TaskStatus::Cancelled)
+    }
+}
+
+/// What the pool knows about one task, for display
+#[derive(Debug, Clone)]
+pub struct TaskInfo {
+    pub id: TaskId,
+    pub name: String,
+    pub priority: Priority,
+    pub status: TaskStatus,
+    /// Fraction done (0..=1), if the task reports it
+    pub progress: Option<f32>,
+    /// Latest note from the task, e.g. its result in a few words
+    pub message: String,
+    pub started: Option<Instant>,
+    pub finished: Option<Instant>,
+}
+
+impl TaskInfo {
+    /// Time spent running so far, or in total once finished
+    pub fn elapsed(&self) -> Option<Duration> {
+        let started = self.started?;
+        Some(self.finished.unwrap_or_else(Instant::now).duration_since(started))
+    }
+}
+
+/// Error a task returns when it stopped because it was cancelled
+#[derive(Debug)]
+pub struct Cancelled;
+
+impl std::fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+type Job = Box<dyn FnOnce(&TaskContext) + Send>;
+
+struct QueuedJob {
+    id: TaskId,
+    priority: Priority,
+    job: Job,
+}
+
+// Max-heap order: higher priority first, then lower (older) id first
+impl Ord for QueuedJob {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority).then_with(|| other.id.cmp(&self.id))
+    }
+}
+
+impl PartialOrd for QueuedJob {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for QueuedJob {
+    fn eq(&self, other: &Self) -> bool {
+        self.id == other.id
+    }
+}
+
+impl Eq for QueuedJob {}
+
+struct TaskRecord {
+    info: TaskInfo,
+    cancel: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+struct State {
+    queue: BinaryHeap<QueuedJob>,
+    /// In submission order
+    tasks: Vec<TaskRecord>,
+    next_id: TaskId,
+    shutdown: bool,
+}
+
+impl State {
+    fn record(&mut self, id: TaskId) -> Option<&mut TaskRecord> {
+        self.tasks.iter_mut().find(|record| record.info.id == id)
+    }
+
+    fn forget_old_finished(&mut self) {
+        let finished = self.tasks.iter().filter(|record| record.info.status.is_finished()).count();
+        let mut excess = finished.saturating_sub(MAX_FINISHED_TASKS);
+        self.tasks.retain(|record| {
+            if excess > 0 && record.info.status.is_finished() {
+                excess -= 1;
+                return false;
+            }
+            true
+        });
+    }
+}
+
+struct Shared {
+    state: Mutex<State>,
+    work_available: Condvar,
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        // A panicking task is caught outside the lock, so poisoning only
+        // means a bug in the pool itself; the state is still usable
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn update(&self, id: TaskId, f: impl FnOnce(&mut TaskInfo)) {
+        if let Some(record) = self.lock().record(id) {
+            f(&mut record.info);
+        }
+    }
+}
+
+/// Passed to a running task to report progress and check for cancellation
+pub struct TaskContext {
+    id: TaskId,
+    shared: Arc<Shared>,
+    cancel: Arc<AtomicBool>,
+}
+
+impl TaskContext {
+    pub fn id(&self) -> TaskId {
+        self.id
+    }
+
+    pub fn set_progress(&self, fraction: f32) {
+        self.shared.update(self.id, |info| info.progress = Some(fraction.clamp(0.0, 1.0)));
+    }
+
+    pub fn set_message(&self, message: &str) {
+        self.shared.update(self.id, |info| info.message = message.to_string());
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel.load(AtomicOrdering::Relaxed)
+    }
+
+    /// `Err(Cancelled)` once the task has been cancelled, for `?` in loops
+    pub fn check_cancelled(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Cancelled.into());
+        }
+        Ok(())
+    }
+
+    /// Report progress and check for cancellation in one go; fits the
+    /// `on_progress` callbacks of the slow library calls
+    pub fn report(&self, fraction: f32) -> Result<()> {
+        self.set_progress(fraction);
+        self.check_cancelled()
+    }
+}
+
+/// Result of one submitted task
+pub struct TaskHandle<T> {
+    id: TaskId,
+    result: Receiver<Result<T>>,
+}
+
+impl<T> TaskHandle<T> {
+    pub fn id(&self) -> TaskId {
+        self.id
+    }
+
+    /// The result if the task has finished. A task cancelled before it
+    /// started gives `Err(Cancelled)`.
+    pub fn try_take(&self) -> Option<Result<T>> {
+        match self.result.try_recv() {
+            Ok(result) => Some(result),
+            Err(TryRecvError::Empty) => None,
+            Err(TryRecvError::Disconnected) => Some(Err(Cancelled.into())),
+        }
+    }
+
+    /// Block until the task finishes
+    pub fn wait(self) -> Result<T> {
+        self.result.recv().unwrap_or_else(|_| Err(Cancelled.into()))
+    }
+}
+
+/// Fixed set of worker threads taking tasks from one priority queue.
+/// Dropping the pool cancels everything; running tasks stop at their next
+/// cancellation check.
+pub struct WorkerPool {
+    shared: Arc<Shared>,
+    threads: usize,
+}
+
+impl WorkerPool {
+    pub fn new(threads: usize) -> Self {
+        let threads = threads.max(1);
+        let shared = Arc::new(Shared {
+            state: Mutex::new(State::default()),
+            work_available: Condvar::new(),
+        });
+        for n in 0..threads {
+            let shared = Arc::clone(&shared);
+            std::thread::Builder::new()
+                .name(format!("sig_viewer-worker-{}", n))
+                .spawn(move || worker_loop(shared))
+                .expect("failed to spawn worker thread");
+        }
+        WorkerPool { shared, threads }
+    }
+
+    /// One worker per CPU core
+    pub fn with_default_threads() -> Self {
+        Self::new(std::thread::available_parallelism().map(|n| n.get()).unwrap_or(4))
+    }
+
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    /// Queue `task` to run on a worker. Its `Err` is shown as the failure
+    /// reason; returning [`Cancelled`] (e.g. from
+    /// [`TaskContext::check_cancelled`]) marks it cancelled instead.
+    pub fn submit<T, F>(&self, name: &str, priority: Priority, task: F) -> TaskHandle<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&TaskContext) -> Result<T> + Send + 'static,
+    {
+        let (sender, result) = channel();
+        let shared = Arc::clone(&self.shared);
+        let job: Job = Box::new(move |ctx| {
+            let outcome = catch_unwind(AssertUnwindSafe(|| task(ctx)))
+                .unwrap_or_else(|panic| Err(anyhow::anyhow!("Panicked: {}", panic_message(&panic))));
+            let status = match &outcome {
+                Ok(_) => TaskStatus::Done,
+                Err(e) if e.is::<Cancelled>() || ctx.is_cancelled() => TaskStatus::Cancelled,
+                Err(e) => TaskStatus::Failed(e.to_string()),
+            };
+            shared.update(ctx.id, |info| {
+                if status == TaskStatus::Done {
+                    info.progress = info.progress.map(|_| 1.0);
+                }
+                info.status = status;
+                info.finished = Some(Instant::now());
+            });
+            // The handle may have been dropped; nobody wants the result then
+            let _ = sender.send(outcome);
+        });
+
+        let mut state = self.shared.lock();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.tasks.push(TaskRecord {
+            info: TaskInfo {
+                id,
+                name: name.to_string(),
+                priority,
+                status: TaskStatus::Queued,
+                progress: None,
+                message: String::new(),
+                started: None,
+                finished: None,
+            },
+            cancel: Arc::new(AtomicBool::new(false)),
+        });
+        state.queue.push(QueuedJob { id, priority, job });
+        drop(state);
+        self.shared.work_available.notify_one();
+        TaskHandle { id, result }
+    }
+
+    /// Every task not yet cleared, in submission order
+    pub fn tasks(&self) -> Vec<TaskInfo> {
+        self.shared.lock().tasks.iter().map(|record| record.info.clone()).collect()
+    }
+
+    /// Number of tasks queued or running
+    pub fn active_count(&self) -> usize {
+        self.shared.lock().tasks.iter().filter(|record| !record.info.status.is_finished()).count()
+    }
+
+    /// Cancel a task: a queued one is dropped without running, a running one
+    /// is asked to stop
+    pub fn cancel(&self, id: TaskId) {
+        let mut state = self.shared.lock();
+        let Some(record) = state.record(id) else {
+            return;
+        };
+        record.cancel.store(true, AtomicOrdering::Relaxed);
+        if record.info.status == TaskStatus::Queued {
+            record.info.status = TaskStatus::Cancelled;
+            record.info.finished = Some(Instant::now());
+            // Dropping the job drops its result sender, which the handle
+            // reads as cancelled
+            state.queue.retain(|queued| queued.id != id);
+        }
+    }
+
+    pub fn cancel_all(&self) {
+        let ids: Vec<TaskId> = self
+            .shared
+            .lock()
+            .tasks
+            .iter()
+            .filter(|record| !record.info.status.is_finished())
+            .map(|record| record.info.id)
+            .collect();
+        for id in ids {
+            self.cancel(id);
+        }
+    }
+
+    /// Forget finished tasks
+    pub fn clear_finished(&self) {
+        self.shared.lock().tasks.retain(|record| !record.info.status.is_finished());
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        self.cancel_all();
+        self.shared.lock().shutdown = true;
+        self.shared.work_available.notify_all();
+    }
+}
+
+fn worker_loop(shared: Arc<Shared>) {
+    loop {
+        let mut state = shared.lock();
+        while state.queue.is_empty() && !state.shutdown {
+            state = shared.work_available.wait(state).unwrap_or_else(|e| e.into_inner());
+        }
+        if state.shutdown {
+            return;
+        }
+        let Some(QueuedJob { id, job, .. }) = state.queue.pop() else {
+            continue;
+        };
+        let Some(record) = state.record(id) else {
+            continue;
+        };
+        record.info.status = TaskStatus::Running;
+        record.info.started = Some(Instant::now());
+        let ctx = TaskContext {
+            id,
+            shared: Arc::clone(&shared),
+            cancel: Arc::clone(&record.cancel),
+        };
+        state.forget_old_finished();
+        drop(state);
+        job(&ctx);
+    }
+}
+
+fn panic_message(panic: &Box<dyn std::any::Any + Send>) -> String {
+    panic
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| panic.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string())
+}